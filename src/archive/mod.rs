@@ -0,0 +1,269 @@
+use anyhow::{Context, Result};
+use aws_sdk_s3::Client as S3Client;
+use clap::ValueEnum;
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::OutputFormat;
+use crate::config::Config;
+use crate::extractors::s3::S3Location;
+use crate::hooks::TemplateVars;
+use crate::output::{self, JsonDetail, RenderOptions};
+use crate::transcribe::TranscriptionResult;
+
+/// A shared archive every finished transcript is copied to
+///
+/// `destination` is an `s3://bucket/prefix` or the `https://` URL of a WebDAV folder. Each
+/// transcript goes to `<layout>.<ext>` below it, once per format, with the backend's raw
+/// output next to it as `<layout>.raw.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArchiveConfig {
+    /// Where transcripts are archived (unset: no archive)
+    pub destination: Option<String>,
+
+    /// Path of a transcript below the destination; `{year}`, `{month}`, `{day}` and the hook
+    /// variables (`{title}`, `{job_id}`, ...) are expanded
+    pub layout: String,
+
+    /// Formats each transcript is archived in
+    pub formats: Vec<OutputFormat>,
+
+    /// WebDAV user name
+    pub username: Option<String>,
+
+    /// WebDAV password
+    pub password: Option<String>,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            destination: None,
+            layout: "{year}/{month}/{title}".to_string(),
+            formats: OutputFormat::value_variants().to_vec(),
+            username: None,
+            password: None,
+        }
+    }
+}
+
+impl ArchiveConfig {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(destination) = &self.destination {
+            if let Some(rest) = destination.strip_prefix("s3://") {
+                if rest.split('/').next().is_none_or(str::is_empty) {
+                    anyhow::bail!("Archive destination {} names no bucket", destination);
+                }
+            } else if !(destination.starts_with("https://") || destination.starts_with("http://")) {
+                anyhow::bail!("Archive destination must be an s3:// prefix or a WebDAV URL, got {}", destination);
+            }
+        }
+        if self.layout.trim_matches('/').is_empty() {
+            anyhow::bail!("Archive layout must not be empty");
+        }
+        Ok(())
+    }
+
+    /// Path of a transcript below the destination, without extension
+    ///
+    /// Each part of the layout is made safe as a file name; a transcript without a title is
+    /// named after its job.
+    pub fn path_for(&self, result: &TranscriptionResult) -> String {
+        let date = result.metadata.completed_at.with_timezone(&chrono::Local);
+        let vars = TemplateVars::from_result(result);
+        let expand = |part: &str| {
+            let part = part
+                .replace("{year}", &date.format("%Y").to_string())
+                .replace("{month}", &date.format("%m").to_string())
+                .replace("{day}", &date.format("%d").to_string());
+            crate::utils::sanitize_filename(&vars.expand(&part))
+        };
+
+        let mut parts: Vec<String> = self.layout.trim_matches('/').split('/').map(expand).collect();
+        let name = parts.pop().filter(|name| !name.is_empty());
+        parts.retain(|part| !part.is_empty());
+        parts.push(name.unwrap_or_else(|| crate::utils::sanitize_filename(&result.metadata.job_id)));
+        parts.join("/")
+    }
+}
+
+/// A configured archive, ready to take transcripts
+pub struct Archive {
+    config: ArchiveConfig,
+    store: Store,
+}
+
+enum Store {
+    S3 { client: S3Client, bucket: String, prefix: String },
+    WebDav { client: reqwest::Client, base: String, retries: u32 },
+}
+
+impl Archive {
+    /// The archive configured in `config`, if there is one
+    pub async fn from_config(config: &Config) -> Result<Option<Self>> {
+        match config.archive.destination {
+            Some(_) => Ok(Some(Self::with_settings(&config.archive, config).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// An archive at `settings.destination`, with AWS and HTTP settings from `config`
+    pub async fn with_settings(settings: &ArchiveConfig, config: &Config) -> Result<Self> {
+        let destination = settings.destination.as_deref().context("No archive destination configured")?;
+        let store = match destination.strip_prefix("s3://") {
+            Some(rest) => {
+                let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+                Store::S3 {
+                    client: S3Client::new(&config.aws_sdk_config().await),
+                    bucket: bucket.to_string(),
+                    prefix: prefix.trim_end_matches('/').to_string(),
+                }
+            }
+            None => Store::WebDav {
+                client: crate::http::build_client(&config.http, &config.network)?,
+                base: destination.trim_end_matches('/').to_string(),
+                retries: config.http.retries,
+            },
+        };
+
+        Ok(Self {
+            config: settings.clone(),
+            store,
+        })
+    }
+
+    /// Copy a transcript into the archive in every configured format; returns where it went
+    pub async fn store(&self, result: &TranscriptionResult) -> Result<String> {
+        self.store_at(result, &self.config.path_for(result)).await
+    }
+
+    /// Like `store`, but at `path` below the destination instead of the layout
+    pub async fn store_at(&self, result: &TranscriptionResult, path: &str) -> Result<String> {
+        let options = RenderOptions {
+            timestamps: true,
+            json_detail: JsonDetail::Full,
+            ..Default::default()
+        };
+
+        let mut files = Vec::new();
+        for format in &self.config.formats {
+            // Only results with chapters have a chapter list
+            if matches!(format, OutputFormat::Chapters) && result.chapters.is_empty() {
+                continue;
+            }
+            files.push((format!("{}.{}", path, format.file_suffix()), output::render(result, format, &options)?));
+        }
+        if let Some(raw) = &result.raw {
+            files.push((format!("{}.raw.json", path), serde_json::to_string_pretty(raw)?));
+        }
+
+        match &self.store {
+            Store::S3 { client, bucket, prefix } => {
+                let key = |file: &str| match prefix.as_str() {
+                    "" => file.to_string(),
+                    prefix => format!("{}/{}", prefix, file),
+                };
+                for (file, content) in files {
+                    client
+                        .put_object()
+                        .bucket(bucket)
+                        .key(key(&file))
+                        .body(content.into_bytes().into())
+                        .content_type(content_type(&file))
+                        .send()
+                        .await
+                        .with_context(|| format!("Failed to archive {} to s3://{}", file, bucket))?;
+                }
+                Ok(S3Location { bucket: bucket.clone(), key: key(path) }.uri())
+            }
+            Store::WebDav { client, base, retries } => {
+                self.make_folders(client, base, path).await?;
+                for (file, content) in files {
+                    let url = format!("{}/{}", base, file);
+                    let request = self
+                        .authorized(client.put(&url))
+                        .header(reqwest::header::CONTENT_TYPE, content_type(&file))
+                        .body(content);
+                    let response = crate::http::send_with_retry(request, *retries).await?;
+                    if !response.status().is_success() {
+                        anyhow::bail!("Failed to archive {}: HTTP {}", url, response.status());
+                    }
+                }
+                Ok(format!("{}/{}", base, path))
+            }
+        }
+    }
+
+    /// Create the folders of `path` on the WebDAV share; existing ones answer 405
+    async fn make_folders(&self, client: &reqwest::Client, base: &str, path: &str) -> Result<()> {
+        let mkcol = Method::from_bytes(b"MKCOL").expect("valid method");
+        let mut folder = base.to_string();
+        for part in path.split('/').rev().skip(1).collect::<Vec<_>>().into_iter().rev() {
+            folder = format!("{}/{}", folder, part);
+            let response = self
+                .authorized(client.request(mkcol.clone(), &folder))
+                .send()
+                .await
+                .with_context(|| format!("Failed to reach {}", folder))?;
+            if !(response.status().is_success() || response.status() == StatusCode::METHOD_NOT_ALLOWED) {
+                anyhow::bail!("Failed to create archive folder {}: HTTP {}", folder, response.status());
+            }
+        }
+        Ok(())
+    }
+
+    fn authorized(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.config.username {
+            Some(username) => request.basic_auth(username, self.config.password.as_ref()),
+            None => request,
+        }
+    }
+}
+
+fn content_type(file: &str) -> &'static str {
+    match file.rsplit('.').next() {
+        Some("json") => "application/json",
+        Some("srt") => "application/x-subrip",
+        Some("vtt") => "text/vtt",
+        Some("csv") => "text/csv",
+        Some("ttml") => "application/ttml+xml",
+        Some("eaf") => "application/xml",
+        Some("md") => "text/markdown; charset=utf-8",
+        Some("html") => "text/html; charset=utf-8",
+        _ => "text/plain; charset=utf-8",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_path_for_expands_layout() {
+        let mut result = crate::demo::result("https://example.com/sync.mp3");
+        result.audio_info.title = Some("Team sync: Q3/Q4".to_string());
+        result.metadata.job_id = "job-42".to_string();
+        result.metadata.completed_at = chrono::Local
+            .with_ymd_and_hms(2024, 3, 9, 12, 0, 0)
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        let config = ArchiveConfig::default();
+        assert_eq!(config.path_for(&result), "2024/03/Team sync_ Q3_Q4");
+
+        result.audio_info.title = None;
+        assert_eq!(config.path_for(&result), "2024/03/job-42");
+
+        let config = ArchiveConfig {
+            layout: "/{language}//{day}-{job_id}/".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(config.path_for(&result), format!("{}/09-job-42", result.metadata.language));
+
+        assert!(ArchiveConfig { destination: Some("ftp://x".to_string()), ..Default::default() }.validate().is_err());
+        assert!(ArchiveConfig { destination: Some("s3:///x".to_string()), ..Default::default() }.validate().is_err());
+        assert!(ArchiveConfig { destination: Some("s3://team/transcripts".to_string()), ..Default::default() }.validate().is_ok());
+    }
+}
@@ -0,0 +1,73 @@
+//! Formatter-only build of RustScribe: renders and lints transcripts produced elsewhere
+//!
+//! Builds without AWS, HTTP or external tools:
+//! `cargo build --release --no-default-features --bin rustscribe-format`
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+
+use rustscribe::captions::{self, LintRules};
+use rustscribe::cli::{LintArgs, OutputFormat, RenderArgs};
+use rustscribe::output;
+
+#[derive(Parser)]
+#[command(name = "rustscribe-format")]
+#[command(about = "Render and lint transcripts without the transcription pipeline")]
+#[command(version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Render a saved transcript (result JSON, SRT or VTT) in another format
+    Render(RenderArgs),
+
+    /// Check subtitles against caption guidelines
+    Lint(LintArgs),
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<()> {
+    match Cli::parse().command {
+        Command::Render(args) => {
+            let result = output::load_transcript(&args.input)?;
+            let format = args.format.clone().unwrap_or(OutputFormat::Text);
+            let options = args.render_options()?;
+
+            match &args.output {
+                Some(path) => {
+                    if let Some(backup) = output::save_to_file(&result, path, &format, &options, args.existing()).await? {
+                        eprintln!("Kept the previous file as {}", backup.display());
+                    }
+                    eprintln!("✓ Saved to {}", path.display());
+                }
+                None => output::print_to_console(&result, &format, &options)?,
+            }
+        }
+        Command::Lint(args) => {
+            let rules = args.rules(LintRules::default());
+
+            let cues = captions::parse_file(&args.file)?;
+            let violations = captions::lint_cues(&cues, &rules);
+
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&violations)?);
+            } else {
+                for violation in &violations {
+                    println!("{}", violation);
+                }
+            }
+
+            if !violations.is_empty() {
+                anyhow::bail!("{} violation(s) found in {} cue(s)", violations.len(), cues.len());
+            }
+            if !args.json {
+                println!("✓ {} cue(s) checked, no violations", cues.len());
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+pub mod store;
+
+pub use store::{ArtifactEntry, ArtifactKey, ArtifactKind, ArtifactStore, ArtifactsConfig, GcSummary};
+
+/// HTTP validators and cached audio for a previously downloaded source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedSource {
+    /// Source URL
+    pub url: String,
+    
+    /// ETag returned by the server
+    pub etag: Option<String>,
+    
+    /// Last-Modified returned by the server
+    pub last_modified: Option<String>,
+    
+    /// When the audio was cached
+    pub cached_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Cache of downloaded direct-URL media, revalidated with conditional requests
+///
+/// The audio is kept in the artifact store, with the server's validators alongside it.
+pub struct DownloadCache {
+    store: ArtifactStore,
+}
+
+impl DownloadCache {
+    /// Open the cache in the application data directory
+    pub fn open(limits: &ArtifactsConfig) -> Result<Self> {
+        Ok(Self {
+            store: ArtifactStore::open(limits)?,
+        })
+    }
+    
+    /// Use a specific artifact store
+    pub fn with_store(store: ArtifactStore) -> Self {
+        Self { store }
+    }
+    
+    /// Look up a cached source, returning its validators and audio path
+    pub fn lookup(&self, url: &str) -> Option<(CachedSource, PathBuf)> {
+        let (entry, audio_path) = self.store.get(&key(url))?;
+        let source: CachedSource = serde_json::from_value(entry.meta).ok()?;
+        (source.url == url).then_some((source, audio_path))
+    }
+    
+    /// Store downloaded audio with the validators the server returned
+    pub fn store(
+        &self,
+        url: &str,
+        etag: Option<String>,
+        last_modified: Option<String>,
+        audio_path: &Path,
+    ) -> Result<()> {
+        let source = CachedSource {
+            url: url.to_string(),
+            etag,
+            last_modified,
+            cached_at: chrono::Utc::now(),
+        };
+        self.store
+            .put_file(&key(url), audio_path, serde_json::to_value(&source)?)
+            .context("Failed to copy audio into the download cache")?;
+        
+        Ok(())
+    }
+}
+
+/// Direct downloads depend on nothing but their URL
+fn key(url: &str) -> ArtifactKey {
+    ArtifactKey::new(ArtifactKind::Audio, url, &())
+}
+
+/// Stable hex-encoded SHA-256 of a key
+pub fn hash_key(key: &str) -> String {
+    format!("{:x}", Sha256::digest(key.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_store_and_lookup() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ArtifactStore::at(dir.path().join("artifacts"), &ArtifactsConfig::default());
+        let cache = DownloadCache::with_store(store);
+        let audio = dir.path().join("audio.mp3");
+        fs_err::write(&audio, b"audio").unwrap();
+        
+        assert!(cache.lookup("https://example.com/a.mp3").is_none());
+        
+        cache.store("https://example.com/a.mp3", Some("\"abc\"".to_string()), None, &audio).unwrap();
+        let (source, path) = cache.lookup("https://example.com/a.mp3").unwrap();
+        
+        assert_eq!(source.etag.as_deref(), Some("\"abc\""));
+        assert_eq!(fs_err::read(path).unwrap(), b"audio");
+        assert!(cache.lookup("https://example.com/b.mp3").is_none());
+    }
+}
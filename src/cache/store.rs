@@ -0,0 +1,331 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::hash_key;
+use crate::config::Config;
+
+/// Limits of the artifact store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArtifactsConfig {
+    /// Keep downloaded audio, raw backend JSON and rendered outputs for reuse
+    pub enabled: bool,
+
+    /// Size the store is trimmed to, least recently used artifacts first (MiB)
+    pub max_size_mb: u64,
+
+    /// Artifacts unused for this long are removed by `cache gc`, or once the store is full (0 = never)
+    pub max_age_days: u64,
+}
+
+impl Default for ArtifactsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_size_mb: 2048,
+            max_age_days: 30,
+        }
+    }
+}
+
+/// What an artifact is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    /// Downloaded source audio
+    Audio,
+    /// Backend output as received, e.g. AWS Transcribe's JSON
+    RawTranscript,
+    /// An output file rendered from a result
+    Rendered,
+}
+
+/// Names an artifact by its kind, the source it came from and the options that shaped it
+#[derive(Debug, Clone)]
+pub struct ArtifactKey {
+    kind: ArtifactKind,
+    source: String,
+    options: String,
+}
+
+impl ArtifactKey {
+    pub fn new(kind: ArtifactKind, source: &str, options: &impl Serialize) -> Self {
+        Self {
+            kind,
+            source: source.to_string(),
+            options: serde_json::to_string(options).unwrap_or_default(),
+        }
+    }
+
+    /// Source hash and options hash, combined with the kind
+    fn id(&self) -> String {
+        hash_key(&format!("{:?}:{}:{}", self.kind, hash_key(&self.source), hash_key(&self.options)))
+    }
+}
+
+/// A stored artifact; its content lives in the object named by `object`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactEntry {
+    pub kind: ArtifactKind,
+    pub source: String,
+    /// SHA-256 of the content
+    pub object: String,
+    pub size: u64,
+    /// Whatever the feature storing the artifact needs to reuse it (e.g. HTTP validators)
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub meta: serde_json::Value,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub last_used: chrono::DateTime<chrono::Utc>,
+}
+
+/// What a garbage collection removed
+#[derive(Debug, Default)]
+pub struct GcSummary {
+    pub entries: usize,
+    pub objects: usize,
+    pub bytes: u64,
+}
+
+/// Content-addressed store of run artifacts in the data directory
+///
+/// Content is kept once per SHA-256 under `objects/`, however many keys refer to it; each key
+/// has a small JSON entry under `entries/` naming its object. Objects no entry refers to are
+/// removed by [`ArtifactStore::gc`].
+pub struct ArtifactStore {
+    dir: PathBuf,
+    limits: ArtifactsConfig,
+}
+
+impl ArtifactStore {
+    /// Open the store in the application data directory
+    pub fn open(limits: &ArtifactsConfig) -> Result<Self> {
+        Ok(Self::at(Config::data_dir()?.join("artifacts"), limits))
+    }
+
+    /// Open a store kept in a specific directory
+    pub fn at(dir: PathBuf, limits: &ArtifactsConfig) -> Self {
+        Self {
+            dir,
+            limits: limits.clone(),
+        }
+    }
+
+    /// The artifact stored under `key` and the path of its content, marking it as used
+    pub fn get(&self, key: &ArtifactKey) -> Option<(ArtifactEntry, PathBuf)> {
+        let entry_path = self.entry_path(key);
+        let mut entry: ArtifactEntry = serde_json::from_str(&fs_err::read_to_string(&entry_path).ok()?).ok()?;
+        let object = self.object_path(&entry.object);
+        if entry.kind != key.kind || entry.source != key.source || !object.exists() {
+            return None;
+        }
+
+        entry.last_used = chrono::Utc::now();
+        if let Ok(content) = serde_json::to_string_pretty(&entry) {
+            let _ = fs_err::write(&entry_path, content);
+        }
+        Some((entry, object))
+    }
+
+    /// Store a file's content under `key`, replacing what was stored there before
+    pub fn put_file(&self, key: &ArtifactKey, path: &Path, meta: serde_json::Value) -> Result<ArtifactEntry> {
+        let mut hasher = Sha256::new();
+        let mut file = fs_err::File::open(path)?;
+        let size = std::io::copy(&mut file, &mut hasher)?;
+        let object = format!("{:x}", hasher.finalize());
+
+        let entry = self.put_entry(key, object, size, meta)?;
+        self.write_object(&entry.object, |temp| {
+            fs_err::copy(path, temp).with_context(|| format!("Failed to store {}", path.display()))?;
+            Ok(())
+        })?;
+        self.trim();
+        Ok(entry)
+    }
+
+    /// Store content under `key`, replacing what was stored there before
+    pub fn put_bytes(&self, key: &ArtifactKey, content: &[u8], meta: serde_json::Value) -> Result<ArtifactEntry> {
+        let object = format!("{:x}", Sha256::digest(content));
+
+        let entry = self.put_entry(key, object, content.len() as u64, meta)?;
+        self.write_object(&entry.object, |temp| Ok(fs_err::File::create(temp)?.write_all(content)?))?;
+        self.trim();
+        Ok(entry)
+    }
+
+    /// Every stored artifact, most recently used first
+    pub fn entries(&self) -> Result<Vec<ArtifactEntry>> {
+        Ok(self.load_entries()?.into_iter().map(|(_, entry)| entry).collect())
+    }
+
+    /// Remove expired artifacts, then the least recently used ones beyond the size limit,
+    /// then content no artifact refers to any more
+    pub fn gc(&self) -> Result<GcSummary> {
+        let mut summary = GcSummary::default();
+        let max_age = chrono::Duration::days(self.limits.max_age_days as i64);
+        let max_bytes = self.limits.max_size_mb * 1024 * 1024;
+        let now = chrono::Utc::now();
+
+        let mut kept = HashSet::new();
+        let mut kept_bytes = 0;
+        for (path, entry) in self.load_entries()? {
+            let expired = self.limits.max_age_days > 0 && now - entry.last_used > max_age;
+            let fits = kept.contains(&entry.object) || kept_bytes + entry.size <= max_bytes;
+            if !expired && fits {
+                if kept.insert(entry.object.clone()) {
+                    kept_bytes += entry.size;
+                }
+                continue;
+            }
+            fs_err::remove_file(path)?;
+            summary.entries += 1;
+        }
+
+        let Ok(shards) = fs_err::read_dir(self.dir.join("objects")) else {
+            return Ok(summary);
+        };
+        for shard in shards.filter_map(|shard| shard.ok()) {
+            for object in fs_err::read_dir(shard.path())?.filter_map(|object| object.ok()) {
+                // Dot files are objects still being written
+                let name = object.file_name().to_string_lossy().into_owned();
+                if kept.contains(&name) || name.starts_with('.') {
+                    continue;
+                }
+                summary.bytes += object.metadata().map(|m| m.len()).unwrap_or(0);
+                fs_err::remove_file(object.path())?;
+                summary.objects += 1;
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Entries and their files, most recently used first
+    fn load_entries(&self) -> Result<Vec<(PathBuf, ArtifactEntry)>> {
+        let Ok(files) = fs_err::read_dir(self.dir.join("entries")) else {
+            return Ok(Vec::new());
+        };
+
+        let mut entries: Vec<(PathBuf, ArtifactEntry)> = files
+            .filter_map(|file| file.ok().map(|file| file.path()))
+            .filter_map(|path| {
+                let entry = serde_json::from_str(&fs_err::read_to_string(&path).ok()?).ok()?;
+                Some((path, entry))
+            })
+            .collect();
+        entries.sort_by_key(|(_, entry)| std::cmp::Reverse(entry.last_used));
+        Ok(entries)
+    }
+
+    /// Write an object unless it is already stored; `write` fills a temporary file first
+    fn write_object(&self, object: &str, write: impl FnOnce(&Path) -> Result<()>) -> Result<()> {
+        let path = self.object_path(object);
+        if path.exists() {
+            return Ok(());
+        }
+
+        let dir = path.parent().unwrap_or(&self.dir);
+        fs_err::create_dir_all(dir)?;
+        let temp = dir.join(format!(".{}.tmp", object));
+        write(&temp)?;
+        fs_err::rename(&temp, &path)?;
+        Ok(())
+    }
+
+    fn put_entry(&self, key: &ArtifactKey, object: String, size: u64, meta: serde_json::Value) -> Result<ArtifactEntry> {
+        let now = chrono::Utc::now();
+        let entry = ArtifactEntry {
+            kind: key.kind,
+            source: key.source.clone(),
+            object,
+            size,
+            meta,
+            created_at: now,
+            last_used: now,
+        };
+
+        // The entry goes first, so a concurrent collection never sees the new object unreferenced
+        let entry_path = self.entry_path(key);
+        fs_err::create_dir_all(entry_path.parent().unwrap_or(&self.dir))?;
+        fs_err::write(&entry_path, serde_json::to_string_pretty(&entry)?)?;
+        Ok(entry)
+    }
+
+    /// Collect garbage once the store has outgrown its size limit
+    fn trim(&self) {
+        if self.stored_bytes() <= self.limits.max_size_mb * 1024 * 1024 {
+            return;
+        }
+        if let Err(e) = self.gc() {
+            tracing::debug!("Artifact garbage collection failed: {:#}", e);
+        }
+    }
+
+    /// Size of the stored content, read from the objects' metadata alone
+    fn stored_bytes(&self) -> u64 {
+        let Ok(shards) = fs_err::read_dir(self.dir.join("objects")) else {
+            return 0;
+        };
+        shards
+            .filter_map(|shard| fs_err::read_dir(shard.ok()?.path()).ok())
+            .flatten()
+            .filter_map(|object| object.ok()?.metadata().ok())
+            .map(|metadata| metadata.len())
+            .sum()
+    }
+
+    fn entry_path(&self, key: &ArtifactKey) -> PathBuf {
+        self.dir.join("entries").join(format!("{}.json", key.id()))
+    }
+
+    fn object_path(&self, object: &str) -> PathBuf {
+        self.dir.join("objects").join(&object[..2]).join(object)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_put_get_and_gc() {
+        let dir = tempfile::tempdir().unwrap();
+        let limits = ArtifactsConfig {
+            max_size_mb: 1,
+            ..Default::default()
+        };
+        let store = ArtifactStore::at(dir.path().join("artifacts"), &limits);
+
+        let srt = ArtifactKey::new(ArtifactKind::Rendered, "https://example.com/a", &"srt");
+        let vtt = ArtifactKey::new(ArtifactKind::Rendered, "https://example.com/a", &"vtt");
+        assert!(store.get(&srt).is_none());
+
+        // Equal content is stored once
+        let first = store.put_bytes(&srt, b"same", serde_json::Value::Null).unwrap();
+        let second = store.put_bytes(&vtt, b"same", serde_json::json!({"etag": "x"})).unwrap();
+        assert_eq!(first.object, second.object);
+        let (entry, path) = store.get(&vtt).unwrap();
+        assert_eq!(entry.meta["etag"], "x");
+        assert_eq!(fs_err::read(path).unwrap(), b"same");
+
+        // Within the size limit nothing is collected, not even content no artifact uses any more
+        store.put_bytes(&srt, b"replaced", serde_json::Value::Null).unwrap();
+        store.put_bytes(&vtt, b"replaced", serde_json::Value::Null).unwrap();
+        assert!(store.object_path(&first.object).exists());
+
+        // Storing past the size limit drops the least recently used artifacts and their content
+        let audio = dir.path().join("audio.wav");
+        fs_err::write(&audio, vec![0u8; 1024 * 1024]).unwrap();
+        let key = ArtifactKey::new(ArtifactKind::Audio, "https://example.com/a", &());
+        store.put_file(&key, &audio, serde_json::Value::Null).unwrap();
+
+        assert!(store.get(&key).is_some());
+        assert!(store.get(&srt).is_none());
+        assert!(store.get(&vtt).is_none());
+        assert_eq!(store.entries().unwrap().len(), 1);
+        assert!(!store.object_path(&first.object).exists());
+    }
+}
@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+
+use super::Cue;
+use crate::utils::text;
+
+/// Caption compliance rules (defaults follow common broadcast guidelines)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LintRules {
+    /// Maximum reading speed in characters per second
+    pub max_chars_per_second: f64,
+    
+    /// Maximum line width in display cells (CJK characters count as two)
+    pub max_line_length: usize,
+    
+    /// Maximum lines per cue
+    pub max_lines: usize,
+    
+    /// Minimum cue duration in seconds
+    pub min_duration: f64,
+    
+    /// Maximum cue duration in seconds
+    pub max_duration: f64,
+    
+    /// Minimum gap between consecutive cues in seconds
+    pub min_gap: f64,
+    
+    /// Characters that must not appear in cue text
+    pub forbidden_chars: String,
+}
+
+impl Default for LintRules {
+    fn default() -> Self {
+        Self {
+            max_chars_per_second: 17.0,
+            max_line_length: 42,
+            max_lines: 2,
+            min_duration: 0.833,
+            max_duration: 7.0,
+            min_gap: 0.083,
+            forbidden_chars: "♪#*|~^".to_string(),
+        }
+    }
+}
+
+/// A rule violation found in a cue
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Violation {
+    /// 1-based cue number
+    pub cue: usize,
+    
+    /// Cue start time in seconds
+    pub start_time: f64,
+    
+    /// Cue end time in seconds
+    pub end_time: f64,
+    
+    /// Name of the violated rule
+    pub rule: &'static str,
+    
+    /// Human-readable description
+    pub message: String,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} --> {}  cue {}  [{}] {}",
+            crate::output::format_srt_timestamp(self.start_time),
+            crate::output::format_srt_timestamp(self.end_time),
+            self.cue,
+            self.rule,
+            self.message
+        )
+    }
+}
+
+/// Check every cue against the rules
+pub fn lint_cues(cues: &[Cue], rules: &LintRules) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    
+    for (i, cue) in cues.iter().enumerate() {
+        let mut report = |rule: &'static str, message: String| {
+            violations.push(Violation {
+                cue: cue.index,
+                start_time: cue.start_time,
+                end_time: cue.end_time,
+                rule,
+                message,
+            });
+        };
+        
+        let duration = cue.duration();
+        let lines = cue.plain_lines();
+        let char_count: usize = lines.iter().map(|line| line.trim().chars().count()).sum();
+        
+        if duration <= 0.0 {
+            report("timing", format!("end time is not after start time ({:.3}s)", duration));
+        } else {
+            if duration < rules.min_duration {
+                report("min-duration", format!("duration {:.3}s is below {:.3}s", duration, rules.min_duration));
+            }
+            if duration > rules.max_duration {
+                report("max-duration", format!("duration {:.3}s exceeds {:.3}s", duration, rules.max_duration));
+            }
+            
+            let chars_per_second = char_count as f64 / duration;
+            if chars_per_second > rules.max_chars_per_second {
+                report(
+                    "reading-speed",
+                    format!("{:.1} chars/s exceeds {:.1}", chars_per_second, rules.max_chars_per_second),
+                );
+            }
+        }
+        
+        if lines.len() > rules.max_lines {
+            report("max-lines", format!("{} lines exceeds {}", lines.len(), rules.max_lines));
+        }
+        
+        for (line_number, line) in lines.iter().enumerate() {
+            let length = text::display_width(line.trim());
+            if length > rules.max_line_length {
+                report(
+                    "line-length",
+                    format!("line {} is {} cells wide, exceeds {}", line_number + 1, length, rules.max_line_length),
+                );
+            }
+        }
+        
+        let forbidden: Vec<char> = lines
+            .iter()
+            .flat_map(|line| line.chars())
+            .filter(|c| rules.forbidden_chars.contains(*c))
+            .collect();
+        if !forbidden.is_empty() {
+            report("forbidden-chars", format!("contains forbidden characters: {}", forbidden.into_iter().collect::<String>()));
+        }
+        
+        if let Some(next) = cues.get(i + 1) {
+            let gap = next.start_time - cue.end_time;
+            if gap < 0.0 {
+                report("overlap", format!("overlaps cue {} by {:.3}s", next.index, -gap));
+            } else if gap < rules.min_gap {
+                report("min-gap", format!("gap of {:.3}s before cue {} is below {:.3}s", gap, next.index, rules.min_gap));
+            }
+        }
+    }
+    
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn cue(index: usize, start_time: f64, end_time: f64, text: &str) -> Cue {
+        Cue {
+            index,
+            start_time,
+            end_time,
+            lines: text.lines().map(str::to_string).collect(),
+        }
+    }
+    
+    #[test]
+    fn test_clean_cues_pass() {
+        let cues = vec![cue(1, 0.0, 2.0, "Hello there."), cue(2, 2.5, 4.0, "How are you?")];
+        
+        assert!(lint_cues(&cues, &LintRules::default()).is_empty());
+    }
+    
+    #[test]
+    fn test_reports_each_rule() {
+        let cues = vec![
+            cue(1, 0.0, 0.5, "This line is far too long to be read comfortably at all"),
+            cue(2, 0.4, 2.0, "One\nTwo\nThree ♪"),
+        ];
+        let rules: Vec<&str> = lint_cues(&cues, &LintRules::default())
+            .iter()
+            .map(|v| v.rule)
+            .collect();
+        
+        assert!(rules.contains(&"min-duration"));
+        assert!(rules.contains(&"reading-speed"));
+        assert!(rules.contains(&"line-length"));
+        assert!(rules.contains(&"overlap"));
+        assert!(rules.contains(&"max-lines"));
+        assert!(rules.contains(&"forbidden-chars"));
+    }
+}
@@ -0,0 +1,7 @@
+//! Tools for working with existing subtitle files
+
+pub mod lint;
+pub mod parse;
+
+pub use lint::{lint_cues, LintRules, Violation};
+pub use parse::{parse_file, parse_srt, parse_vtt, Cue};
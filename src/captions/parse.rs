@@ -0,0 +1,155 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// A single subtitle cue
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cue {
+    /// 1-based position of the cue in the file
+    pub index: usize,
+    
+    /// Start time in seconds
+    pub start_time: f64,
+    
+    /// End time in seconds
+    pub end_time: f64,
+    
+    /// Cue text lines, including any markup
+    pub lines: Vec<String>,
+}
+
+impl Cue {
+    /// Cue duration in seconds
+    pub fn duration(&self) -> f64 {
+        self.end_time - self.start_time
+    }
+    
+    /// Text lines with markup tags such as `<i>` or `<v Speaker>` removed
+    pub fn plain_lines(&self) -> Vec<String> {
+        self.lines.iter().map(|line| strip_tags(line)).collect()
+    }
+}
+
+/// Parse an SRT or VTT file, choosing the parser from the extension or header
+pub fn parse_file(path: &Path) -> Result<Vec<Cue>> {
+    let content = fs_err::read_to_string(path)?;
+    let is_vtt = path.extension().and_then(|ext| ext.to_str()) == Some("vtt")
+        || content.trim_start_matches('\u{feff}').starts_with("WEBVTT");
+    
+    let cues = if is_vtt { parse_vtt(&content) } else { parse_srt(&content) };
+    cues.with_context(|| format!("Failed to parse subtitles: {}", path.display()))
+}
+
+/// Parse SRT content into cues
+pub fn parse_srt(content: &str) -> Result<Vec<Cue>> {
+    parse_blocks(content.trim_start_matches('\u{feff}'))
+}
+
+/// Parse WebVTT content into cues, skipping the header and NOTE/STYLE blocks
+pub fn parse_vtt(content: &str) -> Result<Vec<Cue>> {
+    let content = content.trim_start_matches('\u{feff}');
+    if !content.starts_with("WEBVTT") {
+        anyhow::bail!("Missing WEBVTT header");
+    }
+    
+    parse_blocks(content)
+}
+
+/// Parse blank-line separated blocks, keeping only those with a timing line
+fn parse_blocks(content: &str) -> Result<Vec<Cue>> {
+    let normalized = content.replace("\r\n", "\n");
+    let mut cues = Vec::new();
+    
+    for block in normalized.split("\n\n") {
+        let lines: Vec<&str> = block.lines().collect();
+        if lines.first().is_some_and(|line| line.starts_with("NOTE") || line.starts_with("STYLE")) {
+            continue;
+        }
+        
+        let Some(timing_pos) = lines.iter().position(|line| line.contains("-->")) else {
+            continue;
+        };
+        
+        let (start_time, end_time) = parse_timing_line(lines[timing_pos])
+            .with_context(|| format!("Invalid timing line: {}", lines[timing_pos]))?;
+        
+        cues.push(Cue {
+            index: cues.len() + 1,
+            start_time,
+            end_time,
+            lines: lines[timing_pos + 1..]
+                .iter()
+                .map(|line| line.to_string())
+                .collect(),
+        });
+    }
+    
+    Ok(cues)
+}
+
+/// Parse `start --> end [settings]`
+fn parse_timing_line(line: &str) -> Result<(f64, f64)> {
+    let (start, rest) = line
+        .split_once("-->")
+        .ok_or_else(|| anyhow::anyhow!("Missing '-->'"))?;
+    let end = rest.split_whitespace().next().unwrap_or("");
+    
+    Ok((parse_timestamp(start.trim())?, parse_timestamp(end)?))
+}
+
+/// Parse `HH:MM:SS,mmm`, `HH:MM:SS.mmm` or `MM:SS.mmm` into seconds
+pub fn parse_timestamp(timestamp: &str) -> Result<f64> {
+    let normalized = timestamp.replace(',', ".");
+    let parts: Vec<&str> = normalized.split(':').collect();
+    
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [h, m, s] => (h.parse::<f64>()?, m.parse::<f64>()?, s.parse::<f64>()?),
+        [m, s] => (0.0, m.parse::<f64>()?, s.parse::<f64>()?),
+        _ => anyhow::bail!("Invalid timestamp: {}", timestamp),
+    };
+    
+    Ok(hours * 3600.0 + minutes * 60.0 + seconds)
+}
+
+/// Remove `<...>` markup from a cue line
+fn strip_tags(line: &str) -> String {
+    let mut plain = String::with_capacity(line.len());
+    let mut in_tag = false;
+    
+    for c in line.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => plain.push(c),
+            _ => {}
+        }
+    }
+    
+    plain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_parse_srt() {
+        let srt = "1\n00:00:01,000 --> 00:00:02,500\n<i>Speaker 1:</i> Hello\n\n2\n00:00:03,000 --> 00:00:04,000\nWorld\n";
+        let cues = parse_srt(srt).unwrap();
+        
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0].start_time, 1.0);
+        assert_eq!(cues[0].end_time, 2.5);
+        assert_eq!(cues[0].plain_lines(), vec!["Speaker 1: Hello"]);
+        assert_eq!(cues[1].index, 2);
+    }
+    
+    #[test]
+    fn test_parse_vtt_skips_notes() {
+        let vtt = "WEBVTT\n\nNOTE\nTranscribed by RustScribe\n\n00:05.000 --> 00:06.000 align:start\n<v Speaker 1>Hi\n";
+        let cues = parse_vtt(vtt).unwrap();
+        
+        assert_eq!(cues.len(), 1);
+        assert_eq!(cues[0].start_time, 5.0);
+        assert_eq!(cues[0].plain_lines(), vec!["Hi"]);
+    }
+}
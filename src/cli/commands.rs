@@ -0,0 +1,1123 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+use super::{Backend, LintArgs, OutputFormat, RenderArgs, SubtitleArgs};
+
+#[derive(Parser)]
+#[command(
+    name = "rustscribe",
+    about = "RustScribe - Turn YouTube, Twitter/X or local media into timestamped, speaker-labelled transcripts",
+    version,
+    long_about = "A powerful CLI tool for transcribing audio from various platforms including YouTube, Twitter/X, and direct media URLs. Uses AWS Transcribe for high-quality speech-to-text conversion."
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Commands,
+
+    /// Enable verbose logging
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+
+    /// Disable progress indicators
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Netscape-format cookies file for age-restricted, member-only or login-gated media
+    #[arg(long, global = true, value_name = "FILE")]
+    pub cookies: Option<PathBuf>,
+
+    /// Read login cookies from a browser (yt-dlp syntax, e.g. firefox or "chrome:Profile 1")
+    #[arg(long, global = true, value_name = "BROWSER")]
+    pub cookies_from_browser: Option<String>,
+
+    /// Proxy for extraction and downloads (e.g. http://proxy:3128 or socks5://127.0.0.1:1080)
+    #[arg(long, global = true, value_name = "URL")]
+    pub proxy: Option<String>,
+
+    /// Run against a bundled sample with a canned transcript; no config, network or AWS needed
+    #[arg(long, global = true)]
+    pub demo: bool,
+}
+
+/// Where `transcribe` keeps the audio and the original file
+#[derive(Args)]
+pub struct SavedAudioArgs {
+    /// Directory the saved audio is written to instead of the current one (implies --save-audio)
+    #[arg(long, value_name = "DIR")]
+    pub save_audio_dir: Option<PathBuf>,
+
+    /// Convert the saved audio with ffmpeg, e.g. flac for an archive copy (implies --save-audio)
+    #[arg(long, value_name = "FORMAT", value_parser = clap::builder::PossibleValuesParser::new(crate::deps::AUDIO_FORMATS))]
+    pub save_audio_format: Option<String>,
+
+    /// Move a transcribed local file into this folder once it is done
+    #[arg(long, value_name = "DIR")]
+    pub archive_original: Option<PathBuf>,
+}
+
+/// Which language `transcribe` expects
+#[derive(Args)]
+pub struct LanguageArgs {
+    /// Language code for transcription (auto-detect if not specified)
+    #[arg(short, long, value_name = "LANG")]
+    pub language: Option<String>,
+
+    /// Languages auto-detection chooses from, e.g. en-US,fr-FR,de-DE (AWS backend; defaults to
+    /// aws.transcription.language_candidates)
+    #[arg(long, visible_alias = "language-candidates", value_name = "LANGS", value_delimiter = ',',
+          conflicts_with = "language")]
+    pub languages: Vec<String>,
+
+    /// Identify every language spoken, for code-switched recordings, and label each segment
+    /// with its own (AWS backend)
+    #[arg(long, conflicts_with = "language")]
+    pub multi_language: bool,
+}
+
+/// Domain pack, vocabulary and corrections `transcribe` applies
+#[derive(Args)]
+pub struct DomainArgs {
+    /// Installed domain pack to apply (vocabulary, corrections and formatting preset)
+    #[arg(long, value_name = "NAME")]
+    pub domain: Option<String>,
+
+    /// Corrections file applied after transcription: `find => replace` lines (`/regex/ => replace`
+    /// for patterns) or a YAML list of rules; runs after the domain pack's corrections
+    #[arg(long, value_name = "FILE")]
+    pub corrections: Option<PathBuf>,
+
+    /// AWS custom vocabulary for product names and jargon (see `vocab create`; overrides the domain pack's)
+    #[arg(long, value_name = "NAME")]
+    pub vocabulary: Option<String>,
+}
+
+/// How `transcribe --live` records and captions a stream
+#[derive(Args)]
+pub struct LiveArgs {
+    /// Record an in-progress YouTube/Twitch live stream with yt-dlp and transcribe it (needs --max-duration)
+    #[arg(long, conflicts_with = "prefer_captions")]
+    pub live: bool,
+
+    /// With --live, record from the start of the stream instead of the live edge
+    #[arg(long, requires = "live")]
+    pub live_from_start: bool,
+
+    /// With --live, wait for a scheduled stream to start instead of failing
+    #[arg(long, requires = "live")]
+    pub live_wait: bool,
+
+    /// With --live, transcribe in chunks of this length (e.g. 5m) and print each as it is done
+    #[arg(long, value_name = "DURATION", requires = "live", value_parser = crate::utils::parse_duration)]
+    pub live_chunk: Option<std::time::Duration>,
+
+    /// With --live, phrases to get right, e.g. speaker or product names (repeatable, comma-separated)
+    #[arg(long, value_name = "PHRASE", requires = "live")]
+    pub live_boost: Vec<String>,
+
+    /// With --live, a file of phrases to boost, one per line
+    #[arg(long, value_name = "FILE", requires = "live")]
+    pub live_boost_file: Option<PathBuf>,
+}
+
+/// How `transcribe` has AWS screen the speech for personal information and toxicity
+#[derive(Args)]
+pub struct ModerationArgs {
+    /// Have AWS replace personal information with [PII]: every type, or only these
+    /// (e.g. --redact-pii=NAME,EMAIL,PHONE)
+    #[arg(long, value_name = "TYPES", num_args = 0..=1, require_equals = true, value_delimiter = ',', default_missing_value = "ALL")]
+    pub redact_pii: Option<Vec<String>>,
+
+    /// With --redact-pii, which transcripts AWS writes; the unredacted one only shows in --json-detail full
+    #[arg(long, value_enum, value_name = "OUTPUT", requires = "redact_pii")]
+    pub redaction_output: Option<crate::transcribe::RedactionOutput>,
+
+    /// Have AWS rate each segment for profanity, hate speech, threats and other toxic speech
+    /// (US English only; scores are in JSON and CSV output)
+    #[arg(long)]
+    pub detect_toxicity: bool,
+}
+
+/// What `transcribe` does once the run is over
+#[derive(Args)]
+pub struct FinishArgs {
+    /// Open the output file with this command when done, e.g. "vim", "aegisub" or
+    /// "code --diff {previous} {file}" (with --backup)
+    #[arg(long, value_name = "COMMAND", requires = "output")]
+    pub open_with: Option<String>,
+
+    /// POST a JSON summary to this URL when the run finishes or fails (defaults to
+    /// notify.url; signed with notify.secret if set)
+    #[arg(long, value_name = "URL")]
+    pub notify_url: Option<String>,
+
+    /// Announce the finished run with its title, duration and the start of the transcript
+    /// (defaults to notify.channels; slack needs notify.slack_webhook)
+    #[arg(long, value_enum, value_name = "CHANNEL", value_delimiter = ',')]
+    pub notify: Vec<crate::notify::Channel>,
+
+    /// Print a one-line summary of the run on stdout when done (status, output paths,
+    /// duration, language, confidence, cost estimate, job ID), even if it fails
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    pub summary: Option<crate::output::summary::SummaryFormat>,
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    /// Transcribe audio from a URL or local file
+    Transcribe {
+        /// URL or file path to transcribe (YouTube, Twitter, direct media, HLS/DASH streams, s3:// objects, or local audio/video files)
+        #[arg(value_name = "URL_OR_FILE")]
+        url: String,
+
+        /// Output file path (prints to console if not specified)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// Fail instead of overwriting an existing output file
+        #[arg(long, conflicts_with = "backup")]
+        no_clobber: bool,
+
+        /// Keep an existing output file as <FILE>.bak instead of overwriting it
+        #[arg(long)]
+        backup: bool,
+
+        /// Transcription backend (defaults to the configured backend)
+        #[arg(short, long, value_enum)]
+        backend: Option<Backend>,
+
+        /// Output format (defaults to the domain preset or configured default, then text); several,
+        /// like srt,json,txt, also save the others next to --output, named after it
+        #[arg(short, long, value_enum, value_delimiter = ',')]
+        format: Vec<OutputFormat>,
+
+        #[command(flatten)]
+        languages: Box<LanguageArgs>,
+
+        /// Save the extracted audio file
+        #[arg(long)]
+        save_audio: bool,
+
+        /// Format yt-dlp converts YouTube and Twitter/X audio to (kept by --save-audio)
+        #[arg(long, value_name = "FORMAT", value_parser = clap::builder::PossibleValuesParser::new(crate::deps::AUDIO_FORMATS))]
+        audio_format: Option<String>,
+
+        /// yt-dlp audio quality: 0 (best) to 10 (smallest), or a bitrate such as 192K
+        #[arg(long, value_name = "QUALITY")]
+        audio_quality: Option<String>,
+
+        #[command(flatten)]
+        saved_audio: Box<SavedAudioArgs>,
+
+        /// Enable speaker identification (shows who spoke when)
+        #[arg(long)]
+        speaker_labels: bool,
+
+        /// Maximum number of speakers to identify (2-10, default: auto-detect)
+        #[arg(long, value_name = "COUNT")]
+        max_speakers: Option<u8>,
+
+        /// Only report who spoke when: speaker turns with timestamps, no text (AWS backend)
+        #[arg(long, conflicts_with = "prefer_captions")]
+        diarize_only: bool,
+
+        /// Transcribe the left and right channel of a stereo recording separately and label them as
+        /// the speakers "left" and "right" (for calls and podcasts with one person per channel)
+        #[arg(long, conflicts_with_all = ["diarize_only", "prefer_captions", "live"])]
+        split_channels: bool,
+
+        /// Remove filler words ("um", "you know"), repeated words and false starts for a readable
+        /// transcript; JSON keeps the spoken text of changed segments as "verbatim"
+        #[arg(long, conflicts_with = "diarize_only")]
+        clean_speech: bool,
+
+        /// Add the transcript's key phrases and named entities to JSON and Markdown output, found
+        /// with Amazon Comprehend or locally (`--extract-keywords=local`, key phrases only)
+        #[arg(long, value_enum, value_name = "METHOD", num_args = 0..=1, require_equals = true,
+              default_missing_value = "comprehend", conflicts_with = "diarize_only")]
+        extract_keywords: Option<crate::transcribe::KeywordMethod>,
+
+        /// Chapters of the transcript: the source's markers, topical chapters found in the
+        /// transcript (auto; see `-f chapters` for a YouTube chapter list), or none
+        #[arg(long, value_enum, value_name = "MODE", default_value_t = crate::transcribe::ChapterMode::Source)]
+        chapters: crate::transcribe::ChapterMode,
+
+        /// Include timestamps in text output (srt/vtt formats always include timestamps)
+        #[arg(long)]
+        timestamps: bool,
+
+        /// Use detailed timestamps with milliseconds (implies --timestamps)
+        #[arg(long)]
+        detailed_timestamps: bool,
+
+        /// Timestamp format for text output, e.g. "[{hh}:{mm}:{ss}]", "({M}:{ss})" or "{S}s" (implies --timestamps)
+        #[arg(long, value_name = "FORMAT")]
+        timestamp_format: Option<crate::output::TimestampFormat>,
+
+        /// Group text output into paragraphs and timestamp only their starts (implies --timestamps)
+        #[arg(long)]
+        paragraph_timestamps: bool,
+
+        /// Group text output into paragraphs by pauses, speaker changes and sentence ends
+        #[arg(long)]
+        paragraphs: bool,
+
+        /// List words below this confidence (e.g. 0.85) in <output>.review.txt and mark them [?] in text output
+        #[arg(long, value_name = "CONFIDENCE", value_parser = crate::output::review::parse_threshold)]
+        review_threshold: Option<f64>,
+
+        /// How much JSON output includes: minimal (no words or confidences), standard, or full (adds raw backend output)
+        #[arg(long, value_enum, default_value_t = crate::output::JsonDetail::Standard)]
+        json_detail: crate::output::JsonDetail,
+
+        /// Keep each word's alternatives (other words AWS heard) in JSON output, for correction tools
+        #[arg(long)]
+        include_alternatives: bool,
+
+        /// List segments or single words (with timing, confidence and speaker) in JSON and CSV output
+        #[arg(long, value_enum, default_value_t = crate::output::Granularity::Segments)]
+        granularity: crate::output::Granularity,
+
+        /// Render the result through a Tera template (wiki markup, LaTeX, ...) instead of --format
+        #[arg(long, value_name = "FILE")]
+        template: Option<PathBuf>,
+
+        #[command(flatten)]
+        subtitles: Box<SubtitleArgs>,
+
+        /// Maximum segment length in seconds (default: 10, helps create more frequent timestamps)
+        #[arg(long, default_value = "10")]
+        max_segment_length: f64,
+
+        #[command(flatten)]
+        domain: Box<DomainArgs>,
+
+        /// Queue the run locally if AWS is unreachable instead of failing (see `queue flush`)
+        #[arg(long)]
+        queue_if_offline: bool,
+
+        /// Always download direct URLs again instead of reusing an unchanged cached copy
+        #[arg(long)]
+        no_cache: bool,
+
+        /// Stop capturing HLS/DASH streams after this long (e.g. 30m, 1h); needed to end live streams
+        #[arg(long, value_name = "DURATION", value_parser = crate::utils::parse_duration)]
+        max_duration: Option<std::time::Duration>,
+
+        #[command(flatten)]
+        live: Box<LiveArgs>,
+
+        /// Copy s3:// inputs from buckets in another region into the configured bucket
+        #[arg(long)]
+        copy_cross_region: bool,
+
+        /// Use a YouTube video's uploaded subtitles in the requested language instead of transcribing, when it has them
+        #[arg(long)]
+        prefer_captions: bool,
+
+        /// Timecode of the start of the recording for SRT/EDL/TTML (e.g. 01:00:00:00, or 01:00:00;00 drop-frame)
+        #[arg(long, value_name = "TIMECODE")]
+        timecode_offset: Option<String>,
+
+        /// Frame rate output times are snapped to (e.g. 25, 23.976, 29.97 drop-frame, 29.97ndf)
+        #[arg(long, value_name = "FPS")]
+        fps: Option<crate::output::FrameRate>,
+
+        /// Project tag recorded in the run history, for `report`
+        #[arg(long, value_name = "TAG")]
+        tag: Option<String>,
+
+        /// Stop before uploading if the estimated cost is above this many USD (see `estimate`)
+        #[arg(long, value_name = "USD")]
+        max_cost: Option<f64>,
+
+        #[command(flatten)]
+        moderation: Box<ModerationArgs>,
+
+        #[command(flatten)]
+        finish: Box<FinishArgs>,
+    },
+
+    /// Summarize a transcript with an Amazon Bedrock model: an executive summary, key points
+    /// and action items, added after the transcript
+    Summarize {
+        /// Result JSON (or SRT/VTT) of an earlier run, or a URL to transcribe first
+        #[arg(value_name = "RESULT_OR_URL")]
+        input: String,
+
+        /// Write to this file instead of the console
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// Output format (defaults to json for a .json output, otherwise markdown)
+        #[arg(short, long, value_enum)]
+        format: Option<crate::summarize::SummarizeFormat>,
+
+        /// Bedrock model ID (defaults to summarize.model_id)
+        #[arg(long, value_name = "MODEL_ID")]
+        model: Option<String>,
+    },
+
+    /// Download a URL's audio without transcribing it (no AWS account needed)
+    Extract {
+        /// URL or local file
+        #[arg(value_name = "URL_OR_FILE")]
+        url: String,
+
+        /// Audio file to write, converted to the format its extension names (default: the title
+        /// in the saved audio directory)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// Refuse to overwrite an existing output file
+        #[arg(long)]
+        no_clobber: bool,
+
+        /// Stop capturing HLS/DASH streams after this long (e.g. 30m, 1h); needed to end live streams
+        #[arg(long, value_name = "DURATION", value_parser = crate::utils::parse_duration)]
+        max_duration: Option<std::time::Duration>,
+    },
+
+    /// Serve a REST API that queues transcriptions: POST /jobs, GET /jobs/{id},
+    /// GET /jobs/{id}/transcript?format=srt and GET /jobs/{id}/events (server-sent events)
+    Serve {
+        /// Address to listen on (defaults to server.host, 127.0.0.1)
+        #[arg(long, value_name = "HOST")]
+        host: Option<String>,
+
+        /// Port to listen on (defaults to server.port, 8080)
+        #[arg(short, long, value_name = "PORT")]
+        port: Option<u16>,
+    },
+
+    /// Transcribe requests taken from an SQS queue and write the results to S3
+    ///
+    /// Message bodies are the JSON of a serve job, e.g. {"url": "...", "language": "en-US"}.
+    /// Results go to <output>/<message id>.<ext>; messages are deleted once they are written.
+    Worker {
+        /// Queue URL (defaults to worker.queue)
+        #[arg(long, value_name = "SQS_URL")]
+        queue: Option<String>,
+
+        /// s3://bucket/prefix to write results to (defaults to worker.output)
+        #[arg(short, long, value_name = "S3_PREFIX")]
+        output: Option<String>,
+
+        /// Exit once the queue is empty instead of waiting for more messages
+        #[arg(long)]
+        drain: bool,
+    },
+
+    /// Project what transcribing a URL or file would cost, without downloading it
+    Estimate {
+        /// URL or local file
+        #[arg(value_name = "URL_OR_FILE")]
+        url: String,
+
+        /// Backend to price (defaults to the configured backend)
+        #[arg(short, long, value_enum)]
+        backend: Option<Backend>,
+
+        /// Print the estimate as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// List the language codes a backend accepts for --language
+    Languages {
+        /// Backend to list languages for (defaults to the configured backend)
+        #[arg(short, long, value_enum)]
+        backend: Option<Backend>,
+
+        /// Print the languages as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Record from the microphone, then transcribe the recording
+    Record {
+        /// Stop after this long (e.g. 90s, 10m, 1h30m); records until Ctrl-C if not given
+        #[arg(short, long, value_parser = crate::utils::parse_duration)]
+        duration: Option<std::time::Duration>,
+
+        /// Output file path (prints to console if not specified)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// Fail instead of overwriting an existing output file
+        #[arg(long, conflicts_with = "backup")]
+        no_clobber: bool,
+
+        /// Keep an existing output file as <FILE>.bak instead of overwriting it
+        #[arg(long)]
+        backup: bool,
+
+        /// Transcription backend (defaults to the configured backend)
+        #[arg(short, long, value_enum)]
+        backend: Option<Backend>,
+
+        /// Output format (defaults to the configured default, then text)
+        #[arg(short, long, value_enum)]
+        format: Option<OutputFormat>,
+
+        /// Language code for transcription (auto-detect if not specified)
+        #[arg(short, long, value_name = "LANG")]
+        language: Option<String>,
+
+        /// Keep the recording alongside other saved audio
+        #[arg(long)]
+        save_audio: bool,
+
+        /// Enable speaker identification (shows who spoke when)
+        #[arg(long)]
+        speaker_labels: bool,
+
+        /// Maximum number of speakers to identify (2-10, default: auto-detect)
+        #[arg(long, value_name = "COUNT")]
+        max_speakers: Option<u8>,
+
+        /// Include timestamps in text output
+        #[arg(long)]
+        timestamps: bool,
+
+        /// Project tag recorded in the run history, for `report`
+        #[arg(long, value_name = "TAG")]
+        tag: Option<String>,
+    },
+
+    /// Transcribe many inputs and playlists at once, each into its own file
+    Batch {
+        /// URLs, files or YouTube playlists to transcribe
+        #[arg(value_name = "URL_OR_FILE")]
+        inputs: Vec<String>,
+
+        /// Also read inputs from a file, one per line (# starts a comment)
+        #[arg(long, value_name = "FILE")]
+        from: Option<PathBuf>,
+
+        /// Directory the transcripts are written to
+        #[arg(short, long, value_name = "DIR", default_value = ".")]
+        output_dir: PathBuf,
+
+        /// Transcription backend (defaults to the configured backend)
+        #[arg(short, long, value_enum)]
+        backend: Option<Backend>,
+
+        /// Output format (defaults to the configured default, then text)
+        #[arg(short, long, value_enum)]
+        format: Option<OutputFormat>,
+
+        /// Language code for transcription (auto-detect if not specified)
+        #[arg(short, long, value_name = "LANG")]
+        language: Option<String>,
+
+        /// Save the extracted audio files
+        #[arg(long)]
+        save_audio: bool,
+
+        /// Enable speaker identification (shows who spoke when)
+        #[arg(long)]
+        speaker_labels: bool,
+
+        /// Include timestamps in text output
+        #[arg(long)]
+        timestamps: bool,
+
+        /// Inputs processed at once (defaults to app.max_concurrent_jobs); stage limits come from `concurrency`
+        #[arg(short, long, value_name = "COUNT")]
+        jobs: Option<usize>,
+
+        /// Project tag recorded in the run history, for `report`
+        #[arg(long, value_name = "TAG")]
+        tag: Option<String>,
+    },
+
+    /// Identify the spoken language from the start of a source without transcribing it
+    DetectLanguage {
+        /// URL or local file to sample
+        #[arg(value_name = "URL")]
+        url: String,
+
+        /// How much of the start to listen to (e.g. 30s, 2m)
+        #[arg(long, default_value = "2m", value_name = "DURATION", value_parser = crate::utils::parse_duration)]
+        sample: std::time::Duration,
+
+        /// Backend to identify with (defaults to the configured backend; hybrid uses Whisper)
+        #[arg(short, long, value_enum)]
+        backend: Option<Backend>,
+
+        /// Report every language spoken in the sample, not just the dominant one (AWS only)
+        #[arg(long)]
+        multiple: bool,
+
+        /// Languages to choose from, e.g. en-US,fr-FR,de-DE (AWS only; defaults to
+        /// aws.transcription.language_candidates)
+        #[arg(long, value_name = "LANGS", value_delimiter = ',')]
+        language_candidates: Vec<String>,
+
+        /// Print the result as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Configure AWS credentials and settings
+    Config {
+        /// Show current configuration
+        #[arg(short, long)]
+        show: bool,
+
+        #[command(subcommand)]
+        action: Option<ConfigCommands>,
+    },
+
+    /// List supported platforms
+    Platforms,
+
+    /// Work with existing subtitle files
+    Captions {
+        #[command(subcommand)]
+        action: CaptionsCommands,
+    },
+
+    /// Manage domain packs (vocabulary, corrections and formatting bundles)
+    Domain {
+        #[command(subcommand)]
+        action: DomainCommands,
+    },
+
+    /// Join transcripts of a recording split into parts (saved with --format json) into one
+    Merge {
+        /// Transcript JSON files, in recording order
+        #[arg(required = true, num_args = 2.., value_name = "FILE")]
+        inputs: Vec<PathBuf>,
+
+        /// Start of each part in the combined recording, comma-separated (e.g. 0,58m,1h59m);
+        /// parts are placed back to back by default
+        #[arg(long, value_delimiter = ',', value_name = "OFFSETS", value_parser = crate::utils::parse_duration)]
+        offsets: Vec<std::time::Duration>,
+
+        /// Seconds between consecutive parts when placing them back to back
+        #[arg(long, default_value = "0", conflicts_with = "offsets")]
+        gap: f64,
+
+        /// Keep spk_N labels as they are instead of giving each part its own speakers
+        #[arg(long)]
+        keep_speakers: bool,
+
+        /// Output file path (prints to console if not specified)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// Output format (defaults to the configured default, then text)
+        #[arg(short, long, value_enum)]
+        format: Option<OutputFormat>,
+
+        /// Include timestamps in text output
+        #[arg(long)]
+        timestamps: bool,
+    },
+
+    /// Move the timings of an existing transcript or SRT/VTT file by a constant or linear correction
+    Shift {
+        /// Transcript JSON (saved with --format json) or SRT/VTT file
+        #[arg(value_name = "FILE")]
+        input: PathBuf,
+
+        /// Seconds to add to every time, e.g. -2.3s, +1.5 or 1m30s; negative moves cues earlier
+        #[arg(long, allow_hyphen_values = true, default_value = "0", value_parser = crate::transcribe::shift::parse_offset)]
+        offset: f64,
+
+        /// Factor every time is multiplied by before the offset, e.g. 1.001 for drift that grows
+        #[arg(long, default_value = "1", value_parser = crate::transcribe::shift::parse_stretch)]
+        stretch: f64,
+
+        /// Output file path (prints to console if not specified)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// Output format (defaults to the input's format for SRT/VTT, then the configured default)
+        #[arg(short, long, value_enum)]
+        format: Option<OutputFormat>,
+
+        /// Include timestamps in text output
+        #[arg(long)]
+        timestamps: bool,
+    },
+
+    /// Time an existing script against its audio (forced alignment) and write subtitles
+    ///
+    /// The audio is transcribed for its word timings, and the script's words are lined up with
+    /// the words heard. Each line of the script becomes a cue; the text is the script's, as written.
+    Align {
+        /// Audio or video file, or URL
+        #[arg(value_name = "AUDIO")]
+        audio: String,
+
+        /// Plain-text script of what is said
+        #[arg(value_name = "TRANSCRIPT")]
+        transcript: PathBuf,
+
+        /// Backend that hears the words (defaults to the configured backend; whisper aligns locally)
+        #[arg(short, long, value_enum)]
+        backend: Option<Backend>,
+
+        /// Language of the audio (e.g. en-US)
+        #[arg(short, long)]
+        language: Option<String>,
+
+        /// Output file path (prints to console if not specified)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Srt)]
+        format: OutputFormat,
+    },
+
+    /// Re-render a saved result (JSON, SRT or VTT) in another format without transcribing again
+    Convert(RenderArgs),
+
+    /// Score a transcript against a reference of what was said: word and character error rates
+    Eval {
+        /// Transcript to score (result JSON, SRT or VTT)
+        #[arg(value_name = "FILE")]
+        input: PathBuf,
+
+        /// What was actually said: plain text, or a transcript (result JSON, SRT or VTT)
+        #[arg(long, value_name = "FILE")]
+        reference: PathBuf,
+
+        /// Count differences in letter case as errors
+        #[arg(long)]
+        keep_case: bool,
+
+        /// Count punctuation as part of words
+        #[arg(long)]
+        keep_punctuation: bool,
+
+        /// Spell out numbers written in digits on both sides, so "42" matches "forty two" (English)
+        #[arg(long)]
+        spell_numbers: bool,
+
+        /// Print the scores as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Browse past transcriptions and print their saved transcripts
+    History {
+        #[command(subcommand)]
+        action: HistoryCommands,
+    },
+
+    /// Spot-check a past run: play random segments and mark each as correct or not
+    Qa {
+        /// Run number from `history list`
+        id: i64,
+
+        /// Number of segments to review
+        #[arg(short = 'n', long, default_value = "20")]
+        samples: usize,
+
+        /// Audio of the run, if it was not saved with it (see --save-audio)
+        #[arg(long, value_name = "FILE")]
+        audio: Option<PathBuf>,
+
+        /// Seed for picking segments, to review the same sample again
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+
+    /// Summarise transcribed hours and estimated spend over a date range
+    Report {
+        /// First day to include (YYYY-MM-DD)
+        #[arg(long, value_name = "DATE", conflicts_with = "month")]
+        since: Option<chrono::NaiveDate>,
+
+        /// Last day to include (YYYY-MM-DD)
+        #[arg(long, value_name = "DATE", conflicts_with = "month")]
+        until: Option<chrono::NaiveDate>,
+
+        /// Report on one calendar month (YYYY-MM)
+        #[arg(long, value_name = "MONTH", value_parser = crate::history::parse_month)]
+        month: Option<(chrono::NaiveDate, chrono::NaiveDate)>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t)]
+        format: crate::history::ReportFormat,
+
+        /// Write the report to a file instead of the console
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Collect AWS jobs an interrupted run was waiting for, without uploading again
+    Resume {
+        /// Job to resume (all interrupted jobs if not given)
+        #[arg(value_name = "JOB")]
+        job: Option<String>,
+
+        /// List interrupted jobs instead of resuming them
+        #[arg(long, conflicts_with = "discard")]
+        list: bool,
+
+        /// Forget the job and remove its upload instead of collecting it
+        #[arg(long, requires = "job")]
+        discard: bool,
+
+        /// Output file path (defaults to where the interrupted run was writing)
+        #[arg(short, long, value_name = "FILE", requires = "job")]
+        output: Option<PathBuf>,
+
+        /// Output format (defaults to the interrupted run's format)
+        #[arg(short, long, value_enum)]
+        format: Option<OutputFormat>,
+    },
+
+    /// List, inspect and cancel the AWS Transcribe jobs RustScribe started
+    Jobs {
+        #[command(subcommand)]
+        action: JobsCommands,
+    },
+
+    /// Inspect and trim the local store of downloads, raw transcripts and rendered outputs
+    Cache {
+        #[command(subcommand)]
+        action: CacheCommands,
+    },
+
+    /// Manage runs queued while AWS was unreachable
+    Queue {
+        #[command(subcommand)]
+        action: QueueCommands,
+    },
+
+    /// Manage AWS Transcribe custom vocabularies
+    Vocab {
+        #[command(subcommand)]
+        action: VocabCommands,
+    },
+
+    /// Install or check external tools
+    Deps {
+        #[command(subcommand)]
+        action: DepsCommands,
+    },
+
+    /// Manage models for the local Whisper backend
+    Models {
+        #[command(subcommand)]
+        action: ModelsCommands,
+    },
+
+    /// Enroll known speakers so transcripts name them instead of spk_0, spk_1, ...
+    Speakers {
+        #[command(subcommand)]
+        action: SpeakersCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Check that the AWS credentials allow every action a transcription needs, without running one
+    CheckPermissions,
+
+    /// Print one setting, e.g. `config get aws.s3_bucket`
+    Get {
+        /// Setting name, with sections separated by dots
+        key: String,
+    },
+
+    /// Change one setting in the config file, e.g. `config set aws.s3_bucket my-bucket`
+    Set {
+        /// Setting name, with sections separated by dots
+        key: String,
+
+        /// New value, read as YAML (`null` clears an optional setting)
+        value: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum HistoryCommands {
+    /// List completed runs, newest first
+    List {
+        /// First day to include (YYYY-MM-DD)
+        #[arg(long, value_name = "DATE", conflicts_with = "month")]
+        since: Option<chrono::NaiveDate>,
+
+        /// Last day to include (YYYY-MM-DD)
+        #[arg(long, value_name = "DATE", conflicts_with = "month")]
+        until: Option<chrono::NaiveDate>,
+
+        /// Only runs from one calendar month (YYYY-MM)
+        #[arg(long, value_name = "MONTH", value_parser = crate::history::parse_month)]
+        month: Option<(chrono::NaiveDate, chrono::NaiveDate)>,
+
+        /// Only runs from a platform, e.g. youtube, twitter or "local file"
+        #[arg(long)]
+        platform: Option<String>,
+
+        /// Maximum number of runs to show
+        #[arg(long, default_value = "20")]
+        limit: usize,
+
+        /// Print the runs as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Print or save the transcript of a past run
+    Show {
+        /// Run number from `history list`
+        id: i64,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Output file path (prints to console if not specified)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Include timestamps in text output
+        #[arg(long)]
+        timestamps: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum JobsCommands {
+    /// List jobs, newest first
+    List {
+        /// Only jobs with this status
+        #[arg(long, value_enum)]
+        status: Option<JobStatus>,
+
+        /// Maximum number of jobs to show
+        #[arg(long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Show the details of one job
+    Status {
+        /// Job name
+        #[arg(value_name = "JOB")]
+        name: String,
+    },
+
+    /// Delete a job, its upload and any resume state; a running job cannot be stopped, only forgotten
+    Cancel {
+        /// Job name
+        #[arg(value_name = "JOB")]
+        name: String,
+    },
+}
+
+/// Status filter for `jobs list`
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum JobStatus {
+    Queued,
+    InProgress,
+    Failed,
+    Completed,
+}
+
+#[derive(Subcommand)]
+pub enum CaptionsCommands {
+    /// Check SRT/VTT captions against compliance rules (defaults come from the `captions` config section)
+    Lint(LintArgs),
+}
+
+#[derive(Subcommand)]
+pub enum DomainCommands {
+    /// Install a domain pack from a YAML file
+    Install {
+        /// Path to the pack definition
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Create or update the pack's AWS vocabulary from its phrase list
+        #[arg(long)]
+        sync_vocabulary: bool,
+    },
+
+    /// List installed domain packs
+    List,
+
+    /// Show the definition of an installed pack
+    Show {
+        /// Pack name
+        name: String,
+    },
+
+    /// Remove an installed pack
+    Remove {
+        /// Pack name
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum CacheCommands {
+    /// List stored artifacts, most recently used first
+    List,
+
+    /// Remove expired artifacts and trim the store to its size limit
+    Gc,
+}
+
+#[derive(Subcommand)]
+pub enum QueueCommands {
+    /// List queued runs
+    List,
+
+    /// Submit queued runs to AWS
+    Flush {
+        /// Keep retrying every SECONDS until AWS is reachable again
+        #[arg(long, value_name = "SECONDS")]
+        watch: Option<u64>,
+    },
+
+    /// Remove a queued run and its audio
+    Remove {
+        /// Queue entry id (see `queue list`)
+        id: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum VocabCommands {
+    /// Create an AWS custom vocabulary from a file of words and phrases (one per line, # for comments)
+    Create {
+        /// Vocabulary name, used with `transcribe --vocabulary`
+        name: String,
+
+        /// Phrase file; the words of a phrase are joined with hyphens as AWS requires
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Language code of the vocabulary (defaults to the configured language)
+        #[arg(short, long, value_name = "LANG")]
+        language: Option<String>,
+
+        /// Wait until AWS has built the vocabulary and it can be used
+        #[arg(long)]
+        wait: bool,
+    },
+
+    /// Replace the phrases of an existing custom vocabulary
+    Update {
+        name: String,
+
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Language code of the vocabulary (defaults to the configured language)
+        #[arg(short, long, value_name = "LANG")]
+        language: Option<String>,
+
+        /// Wait until AWS has rebuilt the vocabulary
+        #[arg(long)]
+        wait: bool,
+    },
+
+    /// List custom vocabularies with their language and state
+    List,
+
+    /// Delete a custom vocabulary
+    Delete {
+        name: String,
+    },
+
+    /// Propose a custom vocabulary from terms that were repeatedly transcribed with low confidence
+    Suggest {
+        /// Transcript JSON files, or directories containing them (saved with --format json)
+        #[arg(required = true, value_name = "PATH")]
+        inputs: Vec<PathBuf>,
+
+        /// Confidence below which a word counts as poorly recognized
+        #[arg(long, default_value = "0.7")]
+        threshold: f64,
+
+        /// Minimum number of low-confidence occurrences before a term is proposed
+        #[arg(long, default_value = "2")]
+        min_count: usize,
+
+        /// Maximum number of terms to propose
+        #[arg(long, default_value = "50")]
+        limit: usize,
+
+        /// Write the phrase list to a file (prints to console if not specified)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// Create or update an AWS custom vocabulary with this name from the suggestions
+        #[arg(long, value_name = "NAME")]
+        apply: Option<String>,
+
+        /// Language code of the AWS vocabulary (defaults to the configured language)
+        #[arg(short, long, value_name = "LANG")]
+        language: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DepsCommands {
+    /// Download the pinned yt-dlp release into the data directory (used ahead of PATH)
+    Install {
+        /// Download it again even if it is already installed
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Show which yt-dlp, ffmpeg and ffplay (for `qa`) are used, and their versions
+    Check,
+}
+
+#[derive(Subcommand)]
+pub enum ModelsCommands {
+    /// Download a Whisper model (e.g. `large-v3`)
+    Pull {
+        /// Model name
+        name: String,
+
+        /// Quantized variant (q4_0, q4_1, q5_0, q5_1, q8_0; defaults to the configured one)
+        #[arg(short, long, value_name = "TYPE")]
+        quantization: Option<String>,
+    },
+
+    /// List downloaded models
+    List,
+
+    /// Delete a downloaded model
+    Remove {
+        /// Model name
+        name: String,
+
+        /// Quantized variant to delete
+        #[arg(short, long, value_name = "TYPE")]
+        quantization: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SpeakersCommands {
+    /// Enroll a speaker from one or more recordings of only their voice
+    Enroll {
+        /// Name to show in transcripts
+        name: String,
+
+        /// Voice samples (any audio/video file, 10-60 seconds each works best)
+        #[arg(required = true, value_name = "FILE")]
+        samples: Vec<PathBuf>,
+    },
+
+    /// List enrolled speakers
+    List,
+
+    /// Remove an enrolled speaker
+    Remove {
+        /// Speaker name
+        name: String,
+    },
+}
@@ -1,85 +1,228 @@
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Args, ValueEnum};
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
-#[derive(Parser)]
-#[command(
-    name = "rustscribe",
-    about = "RustScribe - Turn YouTube, Twitter/X or local media into timestamped, speaker-labelled transcripts",
-    version,
-    long_about = "A powerful CLI tool for transcribing audio from various platforms including YouTube, Twitter/X, and direct media URLs. Uses AWS Transcribe for high-quality speech-to-text conversion."
-)]
-pub struct Cli {
-    #[command(subcommand)]
-    pub command: Commands,
-
-    /// Enable verbose logging
-    #[arg(short, long, global = true)]
-    pub verbose: bool,
-
-    /// Disable progress indicators
-    #[arg(short, long, global = true)]
-    pub quiet: bool,
+use crate::captions::LintRules;
+use crate::output::{self, ExistingOutput, JsonDetail, RenderOptions, Timecode, TimestampStyle};
+
+#[cfg(feature = "pipeline")]
+mod commands;
+
+#[cfg(feature = "pipeline")]
+pub use commands::*;
+
+/// Options of caption linting, shared by `captions lint` and `rustscribe-format lint`
+#[derive(Args)]
+pub struct LintArgs {
+    /// Subtitle file to check
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Maximum reading speed in characters per second
+    #[arg(long, value_name = "CPS")]
+    pub max_cps: Option<f64>,
+
+    /// Maximum characters per line
+    #[arg(long, value_name = "CHARS")]
+    pub max_line_length: Option<usize>,
+
+    /// Maximum lines per cue
+    #[arg(long, value_name = "LINES")]
+    pub max_lines: Option<usize>,
+
+    /// Minimum cue duration in seconds
+    #[arg(long, value_name = "SECONDS")]
+    pub min_duration: Option<f64>,
+
+    /// Maximum cue duration in seconds
+    #[arg(long, value_name = "SECONDS")]
+    pub max_duration: Option<f64>,
+
+    /// Minimum gap between cues in seconds
+    #[arg(long, value_name = "SECONDS")]
+    pub min_gap: Option<f64>,
+
+    /// Characters that must not appear in captions
+    #[arg(long, value_name = "CHARS")]
+    pub forbidden_chars: Option<String>,
+
+    /// Print violations as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl LintArgs {
+    /// `defaults` with every rule given on the command line replaced
+    pub fn rules(&self, defaults: LintRules) -> LintRules {
+        LintRules {
+            max_chars_per_second: self.max_cps.unwrap_or(defaults.max_chars_per_second),
+            max_line_length: self.max_line_length.unwrap_or(defaults.max_line_length),
+            max_lines: self.max_lines.unwrap_or(defaults.max_lines),
+            min_duration: self.min_duration.unwrap_or(defaults.min_duration),
+            max_duration: self.max_duration.unwrap_or(defaults.max_duration),
+            min_gap: self.min_gap.unwrap_or(defaults.min_gap),
+            forbidden_chars: self.forbidden_chars.clone().unwrap_or(defaults.forbidden_chars),
+        }
+    }
+}
+
+/// How SRT, VTT and SBV output wraps lines and splits long segments into cues
+#[derive(Args, Clone, Debug, Default)]
+pub struct SubtitleArgs {
+    /// Wrap subtitle lines at this many characters (default: 42; CJK characters count as two)
+    #[arg(long, value_name = "CHARS")]
+    pub max_chars_per_line: Option<usize>,
+
+    /// Split segments into cues of at most this many lines
+    #[arg(long, value_name = "LINES")]
+    pub max_lines: Option<usize>,
+
+    /// Split segments into cues of at most this many seconds
+    #[arg(long, value_name = "SECONDS")]
+    pub max_cue_duration: Option<f64>,
+
+    /// Keep at least this many seconds between cues (e.g. 0.083 for two frames at 24 fps)
+    #[arg(long, value_name = "SECONDS")]
+    pub min_cue_gap: Option<f64>,
+
+    /// Time every word in VTT and ASS output for karaoke-style highlighting (needs word timings)
+    #[arg(long)]
+    pub karaoke: bool,
+}
+
+impl SubtitleArgs {
+    pub fn layout(&self) -> output::SubtitleLayout {
+        let defaults = output::SubtitleLayout::default();
+        output::SubtitleLayout {
+            max_chars_per_line: self.max_chars_per_line.unwrap_or(defaults.max_chars_per_line),
+            max_lines: self.max_lines,
+            max_cue_duration: self.max_cue_duration.filter(|secs| *secs > 0.0),
+            min_cue_gap: self.min_cue_gap,
+            karaoke: self.karaoke,
+        }
+    }
+}
+
+/// Options of re-rendering a saved transcript, shared by `convert` and `rustscribe-format render`
+#[derive(Args)]
+pub struct RenderArgs {
+    /// Transcript to render (result JSON, SRT or VTT)
+    #[arg(value_name = "FILE")]
+    pub input: PathBuf,
+
+    /// Output file path (prints to console if not specified)
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Output format (default: text; `convert` uses the configured default format first)
+    #[arg(short, long, value_enum)]
+    pub format: Option<OutputFormat>,
+
+    /// Include timestamps in text output
+    #[arg(long)]
+    pub timestamps: bool,
+
+    /// Use detailed timestamps with milliseconds (implies --timestamps)
+    #[arg(long)]
+    pub detailed_timestamps: bool,
+
+    /// Timestamp format for text output, e.g. "[{hh}:{mm}:{ss}]" (implies --timestamps)
+    #[arg(long, value_name = "FORMAT")]
+    pub timestamp_format: Option<output::TimestampFormat>,
+
+    /// Group text output into paragraphs and timestamp only their starts (implies --timestamps)
+    #[arg(long)]
+    pub paragraph_timestamps: bool,
+
+    /// Group text output into paragraphs by pauses, speaker changes and sentence ends
+    #[arg(long)]
+    pub paragraphs: bool,
+
+    /// List words below this confidence (e.g. 0.85) in <output>.review.txt and mark them [?] in text output
+    #[arg(long, value_name = "CONFIDENCE", value_parser = output::review::parse_threshold)]
+    pub review_threshold: Option<f64>,
+
+    /// How much JSON output includes: minimal, standard, or full
+    #[arg(long, value_enum, default_value_t = JsonDetail::Standard)]
+    pub json_detail: JsonDetail,
+
+    /// Keep each word's alternatives in JSON output, for correction tools
+    #[arg(long)]
+    pub include_alternatives: bool,
+
+    /// List segments or single words in JSON and CSV output
+    #[arg(long, value_enum, default_value_t = output::Granularity::Segments)]
+    pub granularity: output::Granularity,
+
+    /// Render through a Tera template instead of --format
+    #[arg(long, value_name = "FILE")]
+    pub template: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub subtitles: SubtitleArgs,
+
+    /// Timecode of the start of the recording for SRT/EDL/TTML (e.g. 01:00:00:00)
+    #[arg(long, value_name = "TIMECODE")]
+    pub timecode_offset: Option<String>,
+
+    /// Frame rate output times are snapped to (e.g. 25, 23.976, 29.97 drop-frame)
+    #[arg(long, value_name = "FPS")]
+    pub fps: Option<output::FrameRate>,
+
+    /// Refuse to replace an existing output file
+    #[arg(long, conflicts_with = "backup")]
+    pub no_clobber: bool,
+
+    /// Keep an existing output file as <name>.bak before replacing it
+    #[arg(long)]
+    pub backup: bool,
+}
+
+impl RenderArgs {
+    pub fn render_options(&self) -> anyhow::Result<RenderOptions> {
+        Ok(RenderOptions {
+            timestamps: self.timestamps
+                || self.detailed_timestamps
+                || self.timestamp_format.is_some()
+                || self.paragraph_timestamps,
+            detailed_timestamps: self.detailed_timestamps,
+            timecode: Timecode::new(self.timecode_offset.as_deref(), self.fps)?,
+            timestamp_style: TimestampStyle {
+                format: self.timestamp_format.clone(),
+                per_paragraph: self.paragraph_timestamps,
+            },
+            json_detail: self.json_detail,
+            paragraphs: self.paragraphs,
+            review_threshold: self.review_threshold,
+            include_alternatives: self.include_alternatives,
+            granularity: self.granularity,
+            template: self.template.clone(),
+            subtitles: self.subtitles.layout(),
+        })
+    }
+
+    pub fn existing(&self) -> ExistingOutput {
+        ExistingOutput::from_flags(self.no_clobber, self.backup)
+    }
 }
 
-#[derive(Subcommand)]
-pub enum Commands {
-    /// Transcribe audio from a URL or local file
-    Transcribe {
-        /// URL or file path to transcribe (YouTube, Twitter, direct media, or local audio/video files)
-        #[arg(value_name = "URL_OR_FILE")]
-        url: String,
-
-        /// Output file path (prints to console if not specified)
-        #[arg(short, long, value_name = "FILE")]
-        output: Option<PathBuf>,
-
-        /// Output format
-        #[arg(short, long, value_enum, default_value = "text")]
-        format: OutputFormat,
-
-        /// Language code for transcription (auto-detect if not specified)
-        #[arg(short, long, value_name = "LANG")]
-        language: Option<String>,
-
-        /// Save the extracted audio file
-        #[arg(long)]
-        save_audio: bool,
-
-        /// Enable speaker identification (shows who spoke when)
-        #[arg(long)]
-        speaker_labels: bool,
-
-        /// Maximum number of speakers to identify (2-10, default: auto-detect)
-        #[arg(long, value_name = "COUNT")]
-        max_speakers: Option<u8>,
-
-        /// Include timestamps in text output (srt/vtt formats always include timestamps)
-        #[arg(long)]
-        timestamps: bool,
-
-        /// Use detailed timestamps with milliseconds (implies --timestamps)
-        #[arg(long)]
-        detailed_timestamps: bool,
-
-        /// Maximum segment length in seconds (default: 10, helps create more frequent timestamps)
-        #[arg(long, default_value = "10")]
-        max_segment_length: f64,
-    },
-
-    /// Configure AWS credentials and settings
-    Config {
-        /// Show current configuration
-        #[arg(short, long)]
-        show: bool,
-    },
-
-    /// List supported platforms
-    Platforms,
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// AWS Transcribe
+    #[default]
+    Aws,
+    /// Local whisper.cpp
+    Whisper,
+    /// Local Whisper draft, with low-confidence spans re-transcribed by AWS
+    Hybrid,
 }
 
-#[derive(ValueEnum, Clone, Debug)]
+#[derive(ValueEnum, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum OutputFormat {
     /// Plain text
+    #[value(alias = "txt")]
     Text,
     /// JSON with timestamps
     Json,
@@ -87,8 +230,85 @@ pub enum OutputFormat {
     Srt,
     /// WebVTT format
     Vtt,
+    /// SubViewer captions, for upload to YouTube Studio
+    Sbv,
+    /// Advanced SubStation Alpha subtitles; --karaoke highlights each word as it is spoken
+    Ass,
     /// CSV format
     Csv,
+    /// Deposition-style legal transcript with numbered lines
+    Legal,
+    /// Markdown article with chapter headings and paragraphs
+    Markdown,
+    /// Screenplay-style "SPEAKER: text" turns; --timestamps adds timed turn headers
+    Dialogue,
+    /// YouTube chapter list ("0:00 Title" lines) for a video description
+    Chapters,
+    /// Standalone web page with clickable timestamps, and a synced player with --save-audio
+    Html,
+    /// CMX 3600 edit decision list with one event per segment
+    Edl,
+    /// TTML timed text
+    Ttml,
+    /// Praat TextGrid with segment and word tiers per speaker
+    Textgrid,
+    /// ELAN annotation document with segment and word tiers per speaker
+    Eaf,
+}
+
+impl OutputFormat {
+    /// File extension for outputs in this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Text | OutputFormat::Legal | OutputFormat::Chapters | OutputFormat::Dialogue => "txt",
+            OutputFormat::Json => "json",
+            OutputFormat::Markdown => "md",
+            OutputFormat::Html => "html",
+            OutputFormat::Srt => "srt",
+            OutputFormat::Vtt => "vtt",
+            OutputFormat::Sbv => "sbv",
+            OutputFormat::Ass => "ass",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Edl => "edl",
+            OutputFormat::Ttml => "ttml",
+            OutputFormat::Textgrid => "TextGrid",
+            OutputFormat::Eaf => "eaf",
+        }
+    }
+    
+    /// End of the file name for outputs in this format, kept apart from other text formats
+    pub fn file_suffix(&self) -> &'static str {
+        match self {
+            OutputFormat::Legal => "legal.txt",
+            OutputFormat::Dialogue => "dialogue.txt",
+            OutputFormat::Chapters => "chapters.txt",
+            format => format.extension(),
+        }
+    }
+    
+    /// MIME type of outputs in this format, for `serve`
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            OutputFormat::Text
+            | OutputFormat::Legal
+            | OutputFormat::Edl
+            | OutputFormat::Chapters
+            | OutputFormat::Sbv
+            | OutputFormat::Dialogue => {
+                "text/plain; charset=utf-8"
+            }
+            OutputFormat::Json => "application/json",
+            OutputFormat::Markdown => "text/markdown; charset=utf-8",
+            OutputFormat::Html => "text/html; charset=utf-8",
+            OutputFormat::Srt => "application/x-subrip",
+            OutputFormat::Vtt => "text/vtt",
+            OutputFormat::Ass => "text/x-ssa",
+            OutputFormat::Csv => "text/csv",
+            OutputFormat::Ttml => "application/ttml+xml",
+            OutputFormat::Textgrid => "text/plain; charset=utf-8",
+            OutputFormat::Eaf => "application/xml",
+        }
+    }
 }
 
 impl std::fmt::Display for OutputFormat {
@@ -98,7 +318,18 @@ impl std::fmt::Display for OutputFormat {
             OutputFormat::Json => write!(f, "json"),
             OutputFormat::Srt => write!(f, "srt"),
             OutputFormat::Vtt => write!(f, "vtt"),
+            OutputFormat::Sbv => write!(f, "sbv"),
+            OutputFormat::Ass => write!(f, "ass"),
             OutputFormat::Csv => write!(f, "csv"),
+            OutputFormat::Legal => write!(f, "legal"),
+            OutputFormat::Markdown => write!(f, "markdown"),
+            OutputFormat::Dialogue => write!(f, "dialogue"),
+            OutputFormat::Chapters => write!(f, "chapters"),
+            OutputFormat::Html => write!(f, "html"),
+            OutputFormat::Edl => write!(f, "edl"),
+            OutputFormat::Ttml => write!(f, "ttml"),
+            OutputFormat::Textgrid => write!(f, "textgrid"),
+            OutputFormat::Eaf => write!(f, "eaf"),
         }
     }
 } 
\ No newline at end of file
@@ -3,6 +3,35 @@ use aws_config::Region;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+use crate::archive::ArchiveConfig;
+use crate::cache::ArtifactsConfig;
+use crate::captions::LintRules;
+use crate::cli::Backend;
+use crate::deps::YtDlpConfig;
+use crate::extractors::cookies::CookiesConfig;
+use crate::extractors::plugin::ExtractorsConfig;
+use crate::health::HealthConfig;
+use crate::history::HistoryConfig;
+use crate::hooks::HooksConfig;
+use crate::http::{HttpConfig, NetworkConfig};
+use crate::keywords::KeywordsConfig;
+use crate::record::RecordConfig;
+use crate::notify::NotifyConfig;
+use crate::server::ServerConfig;
+use crate::summarize::SummarizeConfig;
+use crate::worker::WorkerConfig;
+use crate::speakers::SpeakerConfig;
+use crate::transcribe::hybrid::HybridConfig;
+use crate::transcribe::saved_audio::SavedAudioConfig;
+use crate::transcribe::scheduler::ConcurrencyConfig;
+use crate::whisper::WhisperConfig;
+
+pub mod permissions;
+pub mod settings;
+
+/// How long before expiry AWS credentials are refreshed
+const CREDENTIAL_REFRESH_BUFFER: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     /// AWS configuration
@@ -10,6 +39,94 @@ pub struct Config {
     
     /// Application settings
     pub app: AppConfig,
+    
+    /// Caption compliance rules used by `captions lint`
+    #[serde(default)]
+    pub captions: LintRules,
+    
+    /// HTTP client settings (timeouts, pooling, proxy, headers)
+    #[serde(default)]
+    pub http: HttpConfig,
+    
+    /// Proxy for extraction and downloads (HTTP client, yt-dlp and ffmpeg)
+    #[serde(default)]
+    pub network: NetworkConfig,
+    
+    /// Local Whisper backend settings
+    #[serde(default)]
+    pub whisper: WhisperConfig,
+    
+    /// Hybrid backend settings
+    #[serde(default)]
+    pub hybrid: HybridConfig,
+    
+    /// Recognition of enrolled speakers
+    #[serde(default)]
+    pub speakers: SpeakerConfig,
+    
+    /// Microphone capture for `record`
+    #[serde(default)]
+    pub record: RecordConfig,
+    
+    /// Heartbeat logging and liveness file for long runs
+    #[serde(default)]
+    pub health: HealthConfig,
+    
+    /// External extractor plugins
+    #[serde(default)]
+    pub extractors: ExtractorsConfig,
+    
+    /// Login cookies for gated media
+    #[serde(default)]
+    pub cookies: CookiesConfig,
+    
+    /// yt-dlp binary and download format used for YouTube and Twitter/X
+    #[serde(default)]
+    pub yt_dlp: YtDlpConfig,
+    
+    /// Run history and prices for `report`
+    #[serde(default)]
+    pub history: HistoryConfig,
+    
+    /// External commands run before upload, after transcription and after formatting
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    
+    /// Per-stage limits for batch and playlist runs
+    #[serde(default)]
+    pub concurrency: ConcurrencyConfig,
+    
+    /// Where saved audio goes, its format, and archiving of transcribed local files
+    #[serde(default)]
+    pub saved_audio: SavedAudioConfig,
+    
+    /// Shared archive every finished transcript is copied to
+    #[serde(default)]
+    pub archive: ArchiveConfig,
+    
+    /// Limits of the local store of downloads, raw transcripts and rendered outputs
+    #[serde(default)]
+    pub artifacts: ArtifactsConfig,
+    
+    /// HTTP API of `serve`
+    #[serde(default)]
+    pub server: ServerConfig,
+    
+    /// SQS queue consumer of `worker`
+    #[serde(default)]
+    pub worker: WorkerConfig,
+    
+    /// Webhook told when a transcription finishes or fails
+    #[serde(default)]
+    pub notify: NotifyConfig,
+    
+    /// Amazon Bedrock model of `summarize`
+    #[serde(default)]
+    pub summarize: SummarizeConfig,
+    
+    /// How many key phrases and entities `--extract-keywords` keeps
+    #[serde(default)]
+    pub keywords: KeywordsConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,6 +160,10 @@ pub struct TranscriptionConfig {
     
     /// Maximum speakers for identification
     pub max_speakers: Option<u8>,
+    
+    /// Languages auto-detection chooses from (at least two; empty: any AWS supports)
+    #[serde(default)]
+    pub language_candidates: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,8 +177,36 @@ pub struct AppConfig {
     /// Default output format
     pub default_output_format: String,
     
+    /// Backend used unless `--backend` is given
+    #[serde(default)]
+    pub backend: Backend,
+    
     /// Maximum concurrent jobs
     pub max_concurrent_jobs: usize,
+    
+    /// Queue runs locally instead of failing when AWS is unreachable
+    #[serde(default)]
+    pub queue_when_offline: bool,
+    
+    /// Cache direct-URL downloads and revalidate them with ETag/Last-Modified
+    #[serde(default = "default_true")]
+    pub download_cache: bool,
+    
+    /// Apply punctuation and casing rules for the detected language
+    #[serde(default = "default_true")]
+    pub language_rules: bool,
+    
+    /// Stop capturing HLS/DASH streams after this many seconds (live streams never end otherwise)
+    #[serde(default)]
+    pub max_stream_secs: Option<u64>,
+    
+    /// Use the bundled sample and canned transcript instead of real media and backends (`--demo`)
+    #[serde(skip)]
+    pub demo: bool,
+}
+
+fn default_true() -> bool {
+    true
 }
 
 impl Default for Config {
@@ -73,14 +222,43 @@ impl Default for Config {
                     sample_rate: Some(16000),
                     speaker_identification: false,
                     max_speakers: None,
+                    language_candidates: Vec::new(),
                 },
             },
             app: AppConfig {
                 temp_dir: None,
                 keep_audio: false,
                 default_output_format: "text".to_string(),
+                backend: Backend::Aws,
                 max_concurrent_jobs: 3,
+                queue_when_offline: false,
+                download_cache: true,
+                language_rules: true,
+                max_stream_secs: None,
+                demo: false,
             },
+            captions: LintRules::default(),
+            http: HttpConfig::default(),
+            network: NetworkConfig::default(),
+            whisper: WhisperConfig::default(),
+            hybrid: HybridConfig::default(),
+            speakers: SpeakerConfig::default(),
+            record: RecordConfig::default(),
+            health: HealthConfig::default(),
+            extractors: ExtractorsConfig::default(),
+            cookies: CookiesConfig::default(),
+            yt_dlp: YtDlpConfig::default(),
+            history: HistoryConfig::default(),
+            hooks: HooksConfig::default(),
+            concurrency: ConcurrencyConfig::default(),
+            saved_audio: SavedAudioConfig::default(),
+            archive: ArchiveConfig::default(),
+            artifacts: ArtifactsConfig::default(),
+            server: ServerConfig::default(),
+            worker: WorkerConfig::default(),
+            notify: NotifyConfig::default(),
+            summarize: SummarizeConfig::default(),
+            keywords: KeywordsConfig::default(),
         }
     }
 }
@@ -88,6 +266,15 @@ impl Default for Config {
 impl Config {
     /// Load configuration from file or create default
     pub async fn load() -> Result<Self> {
+        Self::load_checked(Self::validate).await
+    }
+    
+    /// Load configuration for commands that never reach AWS, where an unset bucket is fine
+    pub async fn load_local() -> Result<Self> {
+        Self::load_checked(Self::validate_settings).await
+    }
+    
+    async fn load_checked(validate: fn(&Self) -> Result<()>) -> Result<Self> {
         let config_path = Self::config_path()?;
         
         if config_path.exists() {
@@ -97,7 +284,7 @@ impl Config {
             let config: Config = serde_yaml::from_str(&content)
                 .context("Failed to parse config file")?;
             
-            config.validate()?;
+            validate(&config)?;
             Ok(config)
         } else {
             let config = Self::default();
@@ -106,6 +293,28 @@ impl Config {
         }
     }
     
+    /// Configuration for `--demo` runs, which never read or write the config file
+    pub fn demo() -> Self {
+        let mut config = Self::default();
+        config.aws.s3_bucket = "rustscribe-demo".to_string();
+        config.app.demo = true;
+        // Demo runs are not real usage and stay out of `report`
+        config.history.enabled = false;
+        config.artifacts.enabled = false;
+        config
+    }
+    
+    /// The configuration file as it is, without checking that it is complete (for `config set`)
+    pub fn load_unchecked() -> Result<Self> {
+        let config_path = Self::config_path()?;
+        if !config_path.exists() {
+            return Ok(Self::default());
+        }
+        
+        let content = fs_err::read_to_string(&config_path).context("Failed to read config file")?;
+        serde_yaml::from_str(&content).context("Failed to parse config file")
+    }
+    
     /// Save configuration to file
     pub async fn save(&self) -> Result<()> {
         let config_path = Self::config_path()?;
@@ -132,19 +341,54 @@ impl Config {
         }
         
         // Then try system config directory for user-specific settings
+        Ok(Self::config_dir()?.join("config.yaml"))
+    }
+    
+    /// Get the directory for application data (queue, cache, logs)
+    pub fn data_dir() -> Result<PathBuf> {
+        let data_dir = dirs::data_dir()
+            .context("Could not determine data directory")?;
+        
+        Ok(data_dir.join("rustscribe"))
+    }
+    
+    /// Get the user-specific configuration directory
+    pub fn config_dir() -> Result<PathBuf> {
         let config_dir = dirs::config_dir()
             .context("Could not determine config directory")?;
         
-        Ok(config_dir.join("rustscribe").join("config.yaml"))
+        Ok(config_dir.join("rustscribe"))
     }
     
     /// Validate configuration
     fn validate(&self) -> Result<()> {
         if self.aws.s3_bucket.is_empty() {
-            anyhow::bail!("AWS S3 bucket must be configured");
+            anyhow::bail!("AWS S3 bucket must be configured (rustscribe config set aws.s3_bucket NAME)");
         }
         
+        self.validate_settings()
+    }
+    
+    /// Check every setting that has a value; unlike [`Config::validate`], an unset bucket is fine
+    fn validate_settings(&self) -> Result<()> {
         Region::new(self.aws.region.clone());
+        self.yt_dlp.validate()?;
+        self.saved_audio.validate()?;
+        self.archive.validate()?;
+        self.worker.validate()?;
+        self.notify.validate()?;
+        self.keywords.validate()?;
+        if let Some(language) = &self.aws.transcription.default_language {
+            crate::languages::validate(language, crate::cli::Backend::Aws)?;
+        }
+        for language in &self.aws.transcription.language_candidates {
+            crate::languages::validate(language, crate::cli::Backend::Aws)?;
+        }
+        if self.aws.transcription.language_candidates.len() == 1 {
+            anyhow::bail!(
+                "aws.transcription.language_candidates needs at least two languages; use default_language for one"
+            );
+        }
         
         Ok(())
     }
@@ -159,6 +403,7 @@ impl Config {
         }
         println!("  Keep Audio: {}", self.app.keep_audio);
         println!("  Default Format: {}", self.app.default_output_format);
+        println!("  Backend: {:?}", self.app.backend);
     }
     
     /// Interactive configuration setup
@@ -173,4 +418,20 @@ impl Config {
     pub fn aws_region(&self) -> Region {
         Region::new(self.aws.region.clone())
     }
+    
+    /// Load the shared AWS SDK configuration for the configured region
+    ///
+    /// Expiring credentials (STS, SSO, instance roles) are refreshed well before they run
+    /// out, so a request started just before expiry is not signed with a dying token.
+    pub async fn aws_sdk_config(&self) -> aws_config::SdkConfig {
+        let identity_cache = aws_config::identity::IdentityCache::lazy()
+            .buffer_time(CREDENTIAL_REFRESH_BUFFER)
+            .build();
+        
+        aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .region(self.aws_region())
+            .identity_cache(identity_cache)
+            .load()
+            .await
+    }
 } 
\ No newline at end of file
@@ -0,0 +1,205 @@
+use aws_sdk_s3::error::{ProvideErrorMetadata, SdkError};
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_transcribe::types::{LanguageCode, Media};
+use uuid::Uuid;
+
+use super::Config;
+
+/// Error codes AWS returns when the caller's policies do not allow an action
+const DENIED_CODES: &[&str] = &["AccessDenied", "AccessDeniedException", "AllAccessDisabled", "UnauthorizedOperation"];
+
+/// Error codes for credentials that are rejected outright, which says nothing about policies
+const REJECTED_CODES: &[&str] = &[
+    "ExpiredToken",
+    "ExpiredTokenException",
+    "InvalidAccessKeyId",
+    "InvalidClientTokenId",
+    "SignatureDoesNotMatch",
+    "UnrecognizedClientException",
+];
+
+/// What a probe found out about one action
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Allowed,
+    Denied(String),
+    /// The call never reached authorization (network trouble, bad credentials)
+    Unknown(String),
+}
+
+/// One IAM action and what the probe for it found
+#[derive(Debug, Clone)]
+pub struct PermissionCheck {
+    /// IAM action, e.g. `s3:PutObject`
+    pub action: &'static str,
+
+    /// What RustScribe needs it for
+    pub used_for: &'static str,
+
+    /// Whether every AWS transcription needs it, rather than one command or input type
+    pub required: bool,
+
+    pub outcome: Outcome,
+}
+
+/// Probe every AWS action the pipeline uses with calls that change nothing
+///
+/// S3 is checked with a tiny object that is written, read back and deleted again under the
+/// configured prefix. Transcribe and vocabulary actions are checked with requests AWS
+/// authorizes and then rejects as invalid, so no job or vocabulary is ever created: a
+/// validation error means the action is allowed, an access-denied error means it is not.
+pub async fn check(config: &Config) -> Vec<PermissionCheck> {
+    let aws_config = config.aws_sdk_config().await;
+    let s3 = aws_sdk_s3::Client::new(&aws_config);
+    let transcribe = aws_sdk_transcribe::Client::new(&aws_config);
+
+    let bucket = config.aws.s3_bucket.as_str();
+    let probe_key = format!(
+        "{}permission_check_{}.txt",
+        config.aws.s3_key_prefix.as_deref().unwrap_or(""),
+        Uuid::new_v4()
+    );
+    let probe_name = format!("rustscribe-permission-check-{}", Uuid::new_v4());
+    let mut checks = Vec::new();
+
+    let put = outcome(
+        s3.put_object()
+            .bucket(bucket)
+            .key(&probe_key)
+            .body(ByteStream::from_static(b"rustscribe permission check"))
+            .send()
+            .await,
+    );
+    let uploaded = put == Outcome::Allowed;
+    checks.push(required("s3:PutObject", "upload audio for transcription", put));
+
+    // Without the probe object a denied read cannot be told apart from a missing object
+    let get = if uploaded {
+        outcome(s3.get_object().bucket(bucket).key(&probe_key).send().await)
+    } else {
+        Outcome::Unknown("needs s3:PutObject to write the probe object first".to_string())
+    };
+    checks.push(required("s3:GetObject", "let Transcribe read uploaded audio", get));
+
+    let delete = outcome(s3.delete_object().bucket(bucket).key(&probe_key).send().await);
+    checks.push(required("s3:DeleteObject", "remove audio after transcription", delete));
+
+    let location = outcome(s3.get_bucket_location().bucket(bucket).send().await);
+    checks.push(optional("s3:GetBucketLocation", "detect cross-region s3:// inputs", location));
+
+    // Asking for a fixed language and language identification at once is always rejected
+    let start = transcribe
+        .start_transcription_job()
+        .transcription_job_name(&probe_name)
+        .media(Media::builder().media_file_uri(format!("s3://{}/{}", bucket, probe_key)).build())
+        .language_code(LanguageCode::EnUs)
+        .identify_language(true)
+        .send()
+        .await;
+    if start.is_ok() {
+        let _ = transcribe.delete_transcription_job().transcription_job_name(&probe_name).send().await;
+    }
+    checks.push(required("transcribe:StartTranscriptionJob", "start transcription jobs", outcome(start)));
+
+    let get_job = outcome(transcribe.get_transcription_job().transcription_job_name(&probe_name).send().await);
+    checks.push(required("transcribe:GetTranscriptionJob", "poll jobs and fetch transcripts", get_job));
+
+    let list_jobs = outcome(transcribe.list_transcription_jobs().max_results(1).send().await);
+    checks.push(optional("transcribe:ListTranscriptionJobs", "`jobs list`", list_jobs));
+
+    // The probe job never existed, so deleting it fails as not found when allowed
+    let delete_job = outcome(transcribe.delete_transcription_job().transcription_job_name(&probe_name).send().await);
+    checks.push(optional("transcribe:DeleteTranscriptionJob", "`jobs cancel`", delete_job));
+
+    let list = outcome(transcribe.list_vocabularies().max_results(1).send().await);
+    checks.push(optional("transcribe:ListVocabularies", "`vocab` and domain packs", list));
+
+    // Phrases and a vocabulary file together are always rejected
+    let create = transcribe
+        .create_vocabulary()
+        .vocabulary_name(&probe_name)
+        .language_code(LanguageCode::EnUs)
+        .phrases("rustscribe")
+        .vocabulary_file_uri(format!("s3://{}/{}", bucket, probe_key))
+        .send()
+        .await;
+    if create.is_ok() {
+        let _ = transcribe.delete_vocabulary().vocabulary_name(&probe_name).send().await;
+    }
+    checks.push(optional("transcribe:CreateVocabulary", "`vocab` and domain packs", outcome(create)));
+
+    let update = outcome(
+        transcribe
+            .update_vocabulary()
+            .vocabulary_name(&probe_name)
+            .language_code(LanguageCode::EnUs)
+            .phrases("rustscribe")
+            .send()
+            .await,
+    );
+    checks.push(optional("transcribe:UpdateVocabulary", "`vocab` and domain packs", update));
+
+    let get = outcome(transcribe.get_vocabulary().vocabulary_name(&probe_name).send().await);
+    checks.push(optional("transcribe:GetVocabulary", "`vocab create --wait`", get));
+
+    // Like the probe job, the probe vocabulary does not exist by now
+    let delete = outcome(transcribe.delete_vocabulary().vocabulary_name(&probe_name).send().await);
+    checks.push(optional("transcribe:DeleteVocabulary", "`vocab delete`", delete));
+
+    checks
+}
+
+fn required(action: &'static str, used_for: &'static str, outcome: Outcome) -> PermissionCheck {
+    PermissionCheck { action, used_for, required: true, outcome }
+}
+
+fn optional(action: &'static str, used_for: &'static str, outcome: Outcome) -> PermissionCheck {
+    PermissionCheck { action, used_for, required: false, outcome }
+}
+
+/// Classify the result of a probe call
+fn outcome<T, E>(result: Result<T, SdkError<E>>) -> Outcome
+where
+    E: ProvideErrorMetadata + std::error::Error + Send + Sync + 'static,
+{
+    match result {
+        Ok(_) => Outcome::Allowed,
+        Err(SdkError::ServiceError(e)) => outcome_for_code(e.err().code(), e.err().message()),
+        Err(e) => {
+            // The innermost cause says what went wrong ("dns error", "timed out")
+            let mut cause: &dyn std::error::Error = &e;
+            while let Some(source) = cause.source() {
+                cause = source;
+            }
+            Outcome::Unknown(cause.to_string())
+        }
+    }
+}
+
+/// Any service error other than a denial means the request got past authorization
+fn outcome_for_code(code: Option<&str>, message: Option<&str>) -> Outcome {
+    match code {
+        Some(code) if DENIED_CODES.contains(&code) => Outcome::Denied(message.unwrap_or(code).to_string()),
+        Some(code) if REJECTED_CODES.contains(&code) => {
+            Outcome::Unknown(format!("credentials were rejected ({})", message.unwrap_or(code)))
+        }
+        _ => Outcome::Allowed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outcome_for_code() {
+        assert_eq!(outcome_for_code(None, None), Outcome::Allowed);
+        assert_eq!(outcome_for_code(Some("BadRequestException"), Some("conflicting settings")), Outcome::Allowed);
+        assert_eq!(
+            outcome_for_code(Some("AccessDeniedException"), Some("not authorized")),
+            Outcome::Denied("not authorized".to_string())
+        );
+        assert_eq!(outcome_for_code(Some("AccessDenied"), None), Outcome::Denied("AccessDenied".to_string()));
+        assert!(matches!(outcome_for_code(Some("InvalidAccessKeyId"), None), Outcome::Unknown(_)));
+    }
+}
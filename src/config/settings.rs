@@ -0,0 +1,131 @@
+use anyhow::{Context, Result};
+use serde_yaml::{Mapping, Value};
+
+use super::Config;
+
+/// The value of a setting by its dotted path, e.g. `aws.s3_bucket`
+pub fn get(config: &Config, key: &str) -> Result<Value> {
+    let root = serde_yaml::to_value(config)?;
+    let mut value = &root;
+    for (depth, part) in key.split('.').enumerate() {
+        value = value
+            .get(part)
+            .with_context(|| unknown_key(key, &root, depth))?;
+    }
+    Ok(value.clone())
+}
+
+/// Change a setting by its dotted path
+///
+/// The value is read as YAML, so `true`, `3` and `[srt, vtt]` keep their types and `null`
+/// clears an optional setting; text settings take it as it is written.
+pub fn set(config: &mut Config, key: &str, raw: &str) -> Result<()> {
+    let mut root = serde_yaml::to_value(&*config)?;
+    let parts: Vec<&str> = key.split('.').collect();
+    let (last, parents) = parts.split_last().context("Empty setting name")?;
+
+    let mut section = &mut root;
+    for (depth, part) in parents.iter().enumerate() {
+        let known = section.get(part).is_some_and(Value::is_mapping);
+        if !known {
+            anyhow::bail!(unknown_key(key, &serde_yaml::to_value(&*config)?, depth));
+        }
+        section = section.get_mut(part).context("Setting vanished")?;
+    }
+    let mapping: &mut Mapping = section.as_mapping_mut().context("Not a settings section")?;
+
+    let text = Value::String(raw.to_string());
+    let value = match mapping.get(*last) {
+        _ if raw.trim() == "null" => Value::Null,
+        Some(Value::String(_)) => text.clone(),
+        _ => serde_yaml::from_str(raw).unwrap_or_else(|_| text.clone()),
+    };
+    let clears = value.is_null();
+    mapping.insert(Value::String(last.to_string()), value.clone());
+
+    // An unset text setting reads "2024" as a number, so try the value as text too
+    let updated: Config = match serde_yaml::from_value(root.clone()) {
+        Ok(updated) => updated,
+        Err(_) if value != text && !clears => {
+            set_at(&mut root, &parts, text);
+            serde_yaml::from_value(root).with_context(|| format!("Invalid value for {}: {}", key, raw))?
+        }
+        Err(e) => return Err(e).with_context(|| format!("Invalid value for {}: {}", key, raw)),
+    };
+    // Unknown settings are dropped when the config is read, so a typo would pass silently
+    if !clears && get(&updated, key).is_err() {
+        anyhow::bail!(unknown_key(key, &serde_yaml::to_value(&updated)?, parents.len()));
+    }
+    updated.validate_settings()?;
+
+    *config = updated;
+    Ok(())
+}
+
+fn set_at(root: &mut Value, parts: &[&str], value: Value) {
+    let mut section = root;
+    for part in &parts[..parts.len() - 1] {
+        match section.get_mut(part) {
+            Some(next) => section = next,
+            None => return,
+        }
+    }
+    if let Some(mapping) = section.as_mapping_mut() {
+        mapping.insert(Value::String(parts[parts.len() - 1].to_string()), value);
+    }
+}
+
+/// "Unknown setting" naming the settings next to the unknown part
+fn unknown_key(key: &str, root: &Value, depth: usize) -> String {
+    let parts: Vec<&str> = key.split('.').collect();
+    let section = parts[..depth].iter().try_fold(root, |value, part| value.get(part));
+    let names: Vec<String> = section
+        .and_then(Value::as_mapping)
+        .map(|mapping| mapping.keys().filter_map(Value::as_str).map(str::to_string).collect())
+        .unwrap_or_default();
+
+    let prefix = parts[..depth].join(".");
+    let names: Vec<String> = names
+        .iter()
+        .map(|name| if prefix.is_empty() { name.clone() } else { format!("{}.{}", prefix, name) })
+        .collect();
+    format!("Unknown setting '{}' (known here: {})", key, names.join(", "))
+}
+
+/// A setting's value the way scripts want it: scalars bare, unset as nothing, sections as YAML
+pub fn display(value: &Value) -> Result<String> {
+    Ok(match value {
+        Value::Null => String::new(),
+        Value::Bool(value) => value.to_string(),
+        Value::Number(value) => value.to_string(),
+        Value::String(value) => value.clone(),
+        value => serde_yaml::to_string(value)?.trim_end().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_and_set() {
+        let mut config = Config::default();
+
+        set(&mut config, "aws.s3_bucket", "my-bucket").unwrap();
+        set(&mut config, "app.keep_audio", "true").unwrap();
+        set(&mut config, "aws.s3_key_prefix", "2024").unwrap();
+        assert_eq!(config.aws.s3_bucket, "my-bucket");
+        assert!(config.app.keep_audio);
+        assert_eq!(config.aws.s3_key_prefix.as_deref(), Some("2024"));
+        assert_eq!(display(&get(&config, "aws.s3_key_prefix").unwrap()).unwrap(), "2024");
+
+        set(&mut config, "aws.s3_key_prefix", "null").unwrap();
+        assert_eq!(display(&get(&config, "aws.s3_key_prefix").unwrap()).unwrap(), "");
+
+        let error = set(&mut config, "aws.s3_buckets", "x").unwrap_err().to_string();
+        assert!(error.contains("known here:") && error.contains("aws.s3_bucket"), "{}", error);
+        assert!(set(&mut config, "awz.region", "x").is_err());
+        assert!(set(&mut config, "app.keep_audio", "sometimes").is_err());
+        assert!(get(&config, "aws.nothing").is_err());
+    }
+}
@@ -0,0 +1,215 @@
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::path::Path;
+
+use crate::extractors::{AudioFormat, AudioInfo, Chapter, DownloadMethod};
+use crate::output::formatters::WordTimestamp;
+use crate::transcribe::processor::ProcessedTranscription;
+use crate::transcribe::{TranscribeOptions, TranscriptSegment, TranscriptionMetadata, TranscriptionResult};
+
+/// Title every demo run reports for its input
+pub const SAMPLE_TITLE: &str = "RustScribe demo";
+
+/// Length of the sample audio in seconds
+pub const SAMPLE_SECS: f64 = 30.0;
+
+const SAMPLE_RATE: u32 = 16_000;
+
+/// The canned conversation: start, end, speaker and text of each line
+const SCRIPT: &[(f64, f64, &str, &str)] = &[
+    (0.0, 4.2, "spk_0", "Welcome to the RustScribe demo."),
+    (4.6, 9.8, "spk_0", "Everything here comes from a canned transcript, so nothing leaves your machine."),
+    (10.5, 15.0, "spk_1", "So I can try every output format without an AWS account?"),
+    (15.4, 21.0, "spk_0", "Exactly. Pick text, JSON, SRT, VTT or CSV and add whatever flags you like."),
+    (21.8, 26.5, "spk_1", "And when I am ready, I drop the demo flag and transcribe for real."),
+    (27.0, 30.0, "spk_0", "That is all there is to it. Thanks for listening!"),
+];
+
+/// Chapter markers of the sample, so chapter rendering can be tried too
+const CHAPTERS: &[(f64, &str)] = &[(0.0, "Introduction"), (10.5, "Trying it out")];
+
+/// Audio details for the sample standing in for `url`
+pub fn sample_audio_info(url: &str) -> AudioInfo {
+    let chapters = CHAPTERS
+        .iter()
+        .enumerate()
+        .map(|(i, &(start_time, title))| Chapter {
+            title: title.to_string(),
+            start_time,
+            end_time: Some(CHAPTERS.get(i + 1).map_or(SAMPLE_SECS, |next| next.0)),
+        })
+        .collect();
+
+    AudioInfo {
+        download_url: url.to_string(),
+        download_method: DownloadMethod::LocalFile,
+        duration: Some(chrono::Duration::milliseconds((SAMPLE_SECS * 1000.0) as i64)),
+        title: Some(SAMPLE_TITLE.to_string()),
+        format: AudioFormat::Wav,
+        sample_rate: Some(SAMPLE_RATE),
+        file_size: None,
+        original_url: url.to_string(),
+        chapters,
+    }
+}
+
+/// Write the sample audio: a quiet tone while someone speaks, silence in between
+///
+/// It is generated rather than shipped, and only needs to be a valid file for hooks and
+/// `--save-audio`; the transcript never comes from it.
+pub fn write_sample_audio(path: &Path) -> Result<()> {
+    let total = (SAMPLE_SECS * SAMPLE_RATE as f64) as u32;
+    let mut samples = Vec::with_capacity(total as usize * 2);
+    for n in 0..total {
+        let t = n as f64 / SAMPLE_RATE as f64;
+        let speaking = SCRIPT.iter().any(|&(start, end, _, _)| t >= start && t < end);
+        let value = if speaking { (t * 220.0 * std::f64::consts::TAU).sin() * 2000.0 } else { 0.0 };
+        samples.extend_from_slice(&(value as i16).to_le_bytes());
+    }
+
+    let mut file = fs_err::File::create(path)?;
+    let data_len = samples.len() as u32;
+    file.write_all(b"RIFF")?;
+    file.write_all(&(36 + data_len).to_le_bytes())?;
+    file.write_all(b"WAVEfmt ")?;
+    file.write_all(&16u32.to_le_bytes())?;
+    file.write_all(&1u16.to_le_bytes())?; // PCM
+    file.write_all(&1u16.to_le_bytes())?; // mono
+    file.write_all(&SAMPLE_RATE.to_le_bytes())?;
+    file.write_all(&(SAMPLE_RATE * 2).to_le_bytes())?;
+    file.write_all(&2u16.to_le_bytes())?;
+    file.write_all(&16u16.to_le_bytes())?;
+    file.write_all(b"data")?;
+    file.write_all(&data_len.to_le_bytes())?;
+    file.write_all(&samples).context("Failed to write demo audio")?;
+    Ok(())
+}
+
+/// The canned transcript, shaped by the same options a real backend would honour
+///
+/// Speaker labels only appear when they were asked for, and the requested language is
+/// reported back, so flags behave as they would against AWS.
+pub fn transcription(options: &TranscribeOptions) -> ProcessedTranscription {
+    transcribe_lines(SCRIPT, options)
+}
+
+/// The lines of the conversation's `channel`th speaker, as if each had a channel of their own
+/// (`--split-channels`)
+pub fn channel_result(channel: usize, audio_info: AudioInfo, options: &TranscribeOptions) -> TranscriptionResult {
+    let speaker = format!("spk_{}", channel);
+    let lines: Vec<_> = SCRIPT.iter().copied().filter(|line| line.2 == speaker).collect();
+    let processed = transcribe_lines(&lines, options);
+    TranscriptionResult {
+        transcript: processed.transcript,
+        segments: processed.segments,
+        audio_info,
+        audio_path: None,
+        metadata: processed.metadata,
+        words: processed.words,
+        raw: processed.raw,
+        chapters: Vec::new(),
+        redactions: processed.redactions,
+        keywords: None,
+    }
+}
+
+fn transcribe_lines(lines: &[(f64, f64, &str, &str)], options: &TranscribeOptions) -> ProcessedTranscription {
+    let with_speakers = options.speaker_labels || options.diarize_only;
+    let speaker = |label: &str| with_speakers.then(|| label.to_string());
+
+    let mut segments = Vec::new();
+    let mut words = Vec::new();
+    for &(start_time, end_time, label, text) in lines {
+        let tokens: Vec<&str> = text.split_whitespace().collect();
+        let step = (end_time - start_time) / tokens.len() as f64;
+        for (i, token) in tokens.iter().enumerate() {
+            words.push(WordTimestamp {
+                word: token.to_string(),
+                start_time: start_time + i as f64 * step,
+                end_time: start_time + (i + 1) as f64 * step,
+                confidence: Some(0.98),
+                speaker_id: speaker(label),
+                alternatives: Vec::new(),
+            });
+        }
+        segments.push(TranscriptSegment {
+            start_time,
+            end_time,
+            text: text.to_string(),
+            confidence: Some(0.97),
+            speaker_id: speaker(label),
+            toxicity: None,
+            language: None,
+            verbatim: None,
+        });
+    }
+
+    ProcessedTranscription {
+        transcript: lines.iter().map(|line| line.3).collect::<Vec<_>>().join(" "),
+        segments,
+        metadata: TranscriptionMetadata {
+            job_id: "demo".to_string(),
+            language: options.language.clone().unwrap_or_else(|| "en-US".to_string()),
+            processing_duration: Some(0.0),
+            audio_duration: Some(SAMPLE_SECS),
+            confidence: Some(0.97),
+            completed_at: chrono::Utc::now(),
+            speaker_count: None,
+        },
+        words: Some(words),
+        raw: None,
+        redactions: Vec::new(),
+    }
+}
+
+/// The canned transcript as a finished result for `url`, for tests that need one
+#[cfg(test)]
+pub fn result(url: &str) -> TranscriptionResult {
+    let processed = transcription(&TranscribeOptions::default());
+    TranscriptionResult {
+        transcript: processed.transcript,
+        segments: processed.segments,
+        audio_info: sample_audio_info(url),
+        audio_path: None,
+        metadata: processed.metadata,
+        words: processed.words,
+        raw: processed.raw,
+        chapters: Vec::new(),
+        redactions: processed.redactions,
+        keywords: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transcription_follows_options() {
+        let plain = transcription(&TranscribeOptions::default());
+        assert_eq!(plain.segments.len(), SCRIPT.len());
+        assert!(plain.segments.iter().all(|s| s.speaker_id.is_none()));
+        assert_eq!(plain.metadata.language, "en-US");
+        assert_eq!(plain.words.as_ref().unwrap()[0].word, "Welcome");
+
+        let options = TranscribeOptions {
+            speaker_labels: true,
+            language: Some("en-GB".to_string()),
+            ..Default::default()
+        };
+        let labelled = transcription(&options);
+        assert_eq!(labelled.segments[2].speaker_id.as_deref(), Some("spk_1"));
+        assert_eq!(labelled.metadata.language, "en-GB");
+    }
+
+    #[test]
+    fn test_sample_audio() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("demo.wav");
+        write_sample_audio(&path).unwrap();
+
+        let len = fs_err::metadata(&path).unwrap().len();
+        assert_eq!(len, 44 + (SAMPLE_SECS * SAMPLE_RATE as f64) as u64 * 2);
+        assert_eq!(sample_audio_info("demo").chapters[0].end_time, Some(10.5));
+    }
+}
@@ -0,0 +1,248 @@
+use anyhow::{Context, Result};
+use indicatif::{ProgressBar, ProgressStyle};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::extractors::AudioFormat;
+
+/// yt-dlp release installed by `deps install`
+pub const YT_DLP_VERSION: &str = "2024.12.13";
+
+/// Where yt-dlp releases are published
+const YT_DLP_RELEASES: &str = "https://github.com/yt-dlp/yt-dlp/releases/download";
+
+/// Formats yt-dlp may convert downloads to (all accepted by AWS Transcribe)
+pub const AUDIO_FORMATS: &[&str] = &["mp3", "m4a", "wav", "flac"];
+
+/// yt-dlp's default `--audio-quality`; small files that still transcribe well
+const COMPACT_QUALITY: &str = "9";
+
+/// Which yt-dlp binary YouTube and Twitter/X extraction runs, and what it downloads
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct YtDlpConfig {
+    /// yt-dlp binary; defaults to the one installed by `deps install`, then `yt-dlp` on PATH
+    pub path: Option<PathBuf>,
+
+    /// Format downloads are converted to (mp3, m4a, wav or flac)
+    pub audio_format: String,
+
+    /// yt-dlp `--audio-quality`: 0 (best) to 10 (smallest), or a bitrate such as 192K
+    pub audio_quality: String,
+}
+
+impl Default for YtDlpConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            audio_format: "mp3".to_string(),
+            audio_quality: COMPACT_QUALITY.to_string(),
+        }
+    }
+}
+
+impl YtDlpConfig {
+    /// The binary to run: the configured one, the installed one, or `yt-dlp` from PATH
+    pub fn binary(&self) -> PathBuf {
+        if let Some(path) = &self.path {
+            return path.clone();
+        }
+
+        match managed_yt_dlp() {
+            Ok(path) if path.exists() => path,
+            _ => PathBuf::from("yt-dlp"),
+        }
+    }
+
+    /// Check the audio format and quality before yt-dlp gets to reject them
+    pub fn validate(&self) -> Result<()> {
+        if !AUDIO_FORMATS.contains(&self.audio_format.as_str()) {
+            anyhow::bail!(
+                "Unsupported yt-dlp audio format '{}' (expected one of: {})",
+                self.audio_format,
+                AUDIO_FORMATS.join(", ")
+            );
+        }
+
+        let quality = self.audio_quality.trim_end_matches(['k', 'K']);
+        let valid = match quality.parse::<u32>() {
+            Ok(level) if quality.len() == self.audio_quality.len() => level <= 10,
+            Ok(bitrate) => bitrate > 0,
+            Err(_) => false,
+        };
+        if !valid {
+            anyhow::bail!(
+                "Invalid yt-dlp audio quality '{}' (expected 0-10 or a bitrate such as 192K)",
+                self.audio_quality
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Format yt-dlp downloads are converted to
+    pub fn format(&self) -> AudioFormat {
+        AudioFormat::from_extension(&self.audio_format).unwrap_or(AudioFormat::Mp3)
+    }
+
+    /// Whether downloads are already the small MP3s sent for transcription
+    ///
+    /// Anything else is kept as downloaded for `--save-audio` and compressed before upload.
+    pub fn is_compact(&self) -> bool {
+        self.audio_format == "mp3" && self.audio_quality == COMPACT_QUALITY
+    }
+}
+
+/// Where `deps install` puts yt-dlp
+pub fn managed_yt_dlp() -> Result<PathBuf> {
+    let file_name = if cfg!(windows) { "yt-dlp.exe" } else { "yt-dlp" };
+    Ok(Config::data_dir()?.join("bin").join(file_name))
+}
+
+/// Standalone release binary for this platform
+fn yt_dlp_asset() -> Result<&'static str> {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("linux", "x86_64") => Ok("yt-dlp_linux"),
+        ("linux", "aarch64") => Ok("yt-dlp_linux_aarch64"),
+        ("macos", _) => Ok("yt-dlp_macos"),
+        ("windows", _) => Ok("yt-dlp.exe"),
+        (os, arch) => anyhow::bail!(
+            "No yt-dlp release binary for {} {}; install yt-dlp with pip and set yt_dlp.path",
+            os,
+            arch
+        ),
+    }
+}
+
+/// Download the pinned yt-dlp release into the data directory, unless it is already there
+///
+/// The binary is checked against the release's published SHA-256 sums before it is used.
+pub async fn install_yt_dlp(client: &reqwest::Client, force: bool) -> Result<PathBuf> {
+    let path = managed_yt_dlp()?;
+    if path.exists() && !force {
+        tracing::info!("yt-dlp already installed: {}", path.display());
+        return Ok(path);
+    }
+
+    let asset = yt_dlp_asset()?;
+    let release = format!("{}/{}", YT_DLP_RELEASES, YT_DLP_VERSION);
+
+    let sums = client
+        .get(format!("{}/SHA2-256SUMS", release))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .context("Failed to download yt-dlp checksums")?
+        .text()
+        .await?;
+    let expected = expected_checksum(&sums, asset)
+        .with_context(|| format!("yt-dlp {} publishes no checksum for {}", YT_DLP_VERSION, asset))?;
+
+    let progress = crate::progress::add(ProgressBar::new(0).with_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta}) {msg}")
+            .unwrap()
+    ));
+    progress.set_message(format!("Downloading yt-dlp {}", YT_DLP_VERSION));
+
+    let url = format!("{}/{}", release, asset);
+    let response = client.get(&url).send().await.context("Failed to download yt-dlp")?;
+    if !response.status().is_success() {
+        anyhow::bail!("Failed to download yt-dlp: HTTP {}", response.status());
+    }
+
+    // Download next to the final file so an interrupted install never looks installed
+    if let Some(parent) = path.parent() {
+        fs_err::create_dir_all(parent)?;
+    }
+    let partial = path.with_extension("part");
+    crate::transcribe::download::stream_to_file(client.get(&url), response, &partial, &progress).await?;
+
+    let actual = format!("{:x}", Sha256::digest(fs_err::read(&partial)?));
+    if actual != expected {
+        let _ = fs_err::remove_file(&partial);
+        anyhow::bail!("Downloaded yt-dlp does not match its published checksum");
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs_err::set_permissions(&partial, std::fs::Permissions::from_mode(0o755))?;
+    }
+    fs_err::rename(&partial, &path)?;
+
+    progress.finish_with_message(format!("Installed yt-dlp {}", YT_DLP_VERSION));
+    Ok(path)
+}
+
+/// Version a binary reports with `--version`, if it runs
+pub async fn version_of(binary: &std::path::Path) -> Option<String> {
+    let output = tokio::process::Command::new(binary).arg("--version").output().await.ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).lines().next().unwrap_or("").trim().to_string())
+}
+
+/// Checksum of a file in a `sha256sum`-style listing
+fn expected_checksum(sums: &str, file_name: &str) -> Option<String> {
+    sums.lines().find_map(|line| {
+        let (hash, name) = line.split_once(char::is_whitespace)?;
+        (name.trim().trim_start_matches('*') == file_name).then(|| hash.to_lowercase())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expected_checksum() {
+        let sums = "ABC123  yt-dlp\n0f0f  yt-dlp_linux\n1e1e *yt-dlp.exe\n";
+        assert_eq!(expected_checksum(sums, "yt-dlp_linux").as_deref(), Some("0f0f"));
+        assert_eq!(expected_checksum(sums, "yt-dlp").as_deref(), Some("abc123"));
+        assert_eq!(expected_checksum(sums, "yt-dlp.exe").as_deref(), Some("1e1e"));
+        assert_eq!(expected_checksum(sums, "yt-dlp_macos"), None);
+    }
+
+    #[test]
+    fn test_configured_path_wins() {
+        let config = YtDlpConfig {
+            path: Some(PathBuf::from("/opt/yt-dlp/yt-dlp")),
+            ..Default::default()
+        };
+        assert_eq!(config.binary(), PathBuf::from("/opt/yt-dlp/yt-dlp"));
+    }
+
+    #[test]
+    fn test_audio_settings() {
+        let config = YtDlpConfig::default();
+        assert!(config.validate().is_ok());
+        assert!(config.is_compact());
+
+        let config = YtDlpConfig {
+            audio_format: "flac".to_string(),
+            audio_quality: "0".to_string(),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+        assert!(!config.is_compact());
+        assert_eq!(config.format().as_str(), "flac");
+
+        for (format, quality) in [("opus", "9"), ("mp3", "11"), ("mp3", "high"), ("mp3", "0K")] {
+            let config = YtDlpConfig {
+                audio_format: format.to_string(),
+                audio_quality: quality.to_string(),
+                ..Default::default()
+            };
+            assert!(config.validate().is_err(), "{} {}", format, quality);
+        }
+        let config = YtDlpConfig {
+            audio_quality: "192K".to_string(),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+    }
+}
@@ -0,0 +1,237 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::cli::OutputFormat;
+use crate::config::Config;
+use crate::postprocess::{replace, ReplacementRule, RuleSet};
+
+/// A bundle of vocabulary, corrections and formatting for a subject area
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainPack {
+    /// Pack name, used with `--domain <name>`
+    pub name: String,
+
+    /// Short human-readable description
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// Language the vocabulary is written for
+    #[serde(default)]
+    pub language: Option<String>,
+
+    /// AWS custom vocabulary used for transcription
+    #[serde(default)]
+    pub vocabulary: Option<DomainVocabulary>,
+
+    /// Find/replace corrections applied after transcription
+    #[serde(default)]
+    pub rules: Vec<ReplacementRule>,
+
+    /// Corrections file applied after `rules` (see `load_corrections`); relative to the pack file
+    #[serde(default)]
+    pub corrections: Option<PathBuf>,
+
+    /// Formatting preset
+    #[serde(default)]
+    pub format: FormatPreset,
+}
+
+/// Custom vocabulary settings of a domain pack
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainVocabulary {
+    /// Name of the AWS custom vocabulary
+    pub name: String,
+
+    /// Phrases to create the vocabulary from when the pack is installed
+    #[serde(default)]
+    pub phrases: Vec<String>,
+}
+
+/// Output defaults applied when the pack is selected
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FormatPreset {
+    /// Output format used unless `--format` is given
+    #[serde(default)]
+    pub output: Option<OutputFormat>,
+
+    /// Include timestamps in text output
+    #[serde(default)]
+    pub timestamps: bool,
+
+    /// Enable speaker identification
+    #[serde(default)]
+    pub speaker_labels: bool,
+}
+
+impl DomainPack {
+    /// Directory where installed packs are stored
+    pub fn packs_dir() -> Result<PathBuf> {
+        Ok(Config::config_dir()?.join("domains"))
+    }
+
+    /// Read a pack definition from a YAML file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = fs_err::read_to_string(path)?;
+
+        let mut pack: DomainPack = serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse domain pack: {}", path.display()))?;
+        // Installing copies the pack elsewhere, so keep the corrections file findable
+        if let Some(corrections) = pack.corrections.as_mut().filter(|p| p.is_relative()) {
+            let dir = path.parent().unwrap_or(Path::new("."));
+            *corrections = std::path::absolute(dir.join(&*corrections))?;
+        }
+
+        pack.validate()?;
+        Ok(pack)
+    }
+
+    /// Load an installed pack by name
+    pub fn load(name: &str) -> Result<Self> {
+        let path = Self::packs_dir()?.join(format!("{}.yaml", name));
+        if !path.exists() {
+            anyhow::bail!(
+                "Domain pack '{}' is not installed (see `rustscribe domain list`)",
+                name
+            );
+        }
+
+        Self::from_file(&path)
+    }
+
+    /// List all installed packs
+    pub fn list_installed() -> Result<Vec<DomainPack>> {
+        let dir = Self::packs_dir()?;
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut packs = Vec::new();
+        for entry in fs_err::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("yaml") {
+                continue;
+            }
+            match Self::from_file(&path) {
+                Ok(pack) => packs.push(pack),
+                Err(e) => tracing::warn!("Ignoring invalid domain pack {}: {:#}", path.display(), e),
+            }
+        }
+
+        packs.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(packs)
+    }
+
+    /// Copy a pack definition into the packs directory
+    pub fn install(source: &Path) -> Result<Self> {
+        let pack = Self::from_file(source)?;
+
+        let dir = Self::packs_dir()?;
+        fs_err::create_dir_all(&dir)?;
+
+        let content = serde_yaml::to_string(&pack)
+            .context("Failed to serialize domain pack")?;
+        fs_err::write(dir.join(format!("{}.yaml", pack.name)), content)?;
+
+        Ok(pack)
+    }
+
+    /// Remove an installed pack
+    pub fn remove(name: &str) -> Result<()> {
+        let path = Self::packs_dir()?.join(format!("{}.yaml", name));
+        if !path.exists() {
+            anyhow::bail!("Domain pack '{}' is not installed", name);
+        }
+
+        fs_err::remove_file(path)?;
+        Ok(())
+    }
+
+    /// The pack's correction rules, including those of its corrections file
+    pub fn correction_rules(&self) -> Result<Vec<ReplacementRule>> {
+        let mut rules = self.rules.clone();
+        if let Some(path) = &self.corrections {
+            rules.extend(replace::load_corrections(path)?);
+        }
+        Ok(rules)
+    }
+
+    /// Compile the pack's correction rules
+    pub fn rule_set(&self) -> Result<RuleSet> {
+        RuleSet::compile(&self.correction_rules()?)
+    }
+
+    /// Name of the AWS vocabulary to use, if any
+    pub fn vocabulary_name(&self) -> Option<&str> {
+        self.vocabulary.as_ref().map(|v| v.name.as_str())
+    }
+
+    fn validate(&self) -> Result<()> {
+        let valid_name = !self.name.is_empty()
+            && self.name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid_name {
+            anyhow::bail!(
+                "Invalid domain pack name '{}' (use letters, digits, '-' and '_')",
+                self.name
+            );
+        }
+
+        self.rule_set()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_pack(dir: &Path, yaml: &str) -> PathBuf {
+        let path = dir.join("pack.yaml");
+        fs_err::write(&path, yaml).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_parse_pack() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = "name: medical\nlanguage: en-US\nvocabulary:\n  name: medical-terms\n  phrases: [tachycardia]\ncorrections: fixes.txt\nformat:\n  output: srt\n  speaker_labels: true\n";
+        let pack = DomainPack::from_file(&write_pack(dir.path(), yaml));
+        // The corrections file does not exist yet, and validating the rules reads it
+        assert!(pack.is_err());
+
+        fs_err::write(dir.path().join("fixes.txt"), "tacky cardia => tachycardia\n").unwrap();
+        let pack = DomainPack::from_file(&dir.path().join("pack.yaml")).unwrap();
+        assert_eq!(pack.vocabulary_name(), Some("medical-terms"));
+        assert_eq!(pack.corrections.as_deref(), Some(std::path::absolute(dir.path().join("fixes.txt")).unwrap().as_path()));
+        assert!(matches!(pack.format.output, Some(OutputFormat::Srt)));
+        assert!(pack.format.speaker_labels && !pack.format.timestamps);
+
+        let error = DomainPack::from_file(&write_pack(dir.path(), "name: not a name\n")).unwrap_err();
+        assert!(error.to_string().contains("Invalid domain pack name"), "{}", error);
+    }
+
+    #[test]
+    fn test_unknown_pack() {
+        // Names with spaces cannot be installed, so this one never is
+        let error = DomainPack::load("no such pack").unwrap_err();
+        assert!(error.to_string().contains("'no such pack' is not installed"), "{}", error);
+    }
+
+    #[test]
+    fn test_rules_and_corrections_file_merge() {
+        let dir = tempfile::tempdir().unwrap();
+        fs_err::write(dir.path().join("fixes.txt"), "# Applied after the pack's own rules\nmy o cardial => myocardial\n").unwrap();
+        let yaml = "name: cardiology\nrules:\n  - find: e c g\n    replace: ECG\ncorrections: fixes.txt\n";
+        let pack = DomainPack::from_file(&write_pack(dir.path(), yaml)).unwrap();
+
+        let rules = pack.correction_rules().unwrap();
+        let finds: Vec<&str> = rules.iter().map(|rule| rule.find.as_str()).collect();
+        assert_eq!(finds, ["e c g", "my o cardial"]);
+        assert_eq!(
+            pack.rule_set().unwrap().apply_to_text("The E C G showed my o cardial damage"),
+            "The ECG showed myocardial damage"
+        );
+    }
+}
@@ -0,0 +1,230 @@
+//! Word and character error rates of a transcript against a reference (`eval`)
+
+use serde::Serialize;
+
+/// What is evened out on both sides before they are compared
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Normalization {
+    /// Count differences in letter case as errors
+    pub keep_case: bool,
+
+    /// Count punctuation as part of words
+    pub keep_punctuation: bool,
+
+    /// Spell out numbers written in digits ("42" as "forty two"; English)
+    pub spell_numbers: bool,
+}
+
+impl Normalization {
+    /// The text's words after normalization
+    pub fn words(&self, text: &str) -> Vec<String> {
+        let mut words = Vec::new();
+        // Hyphenated words are written apart as often as together
+        for token in text.split(|c: char| c.is_whitespace() || (!self.keep_punctuation && c == '-')) {
+            let spelled;
+            let token = match self.spell_numbers.then(|| spell_token(token)).flatten() {
+                Some(words) => {
+                    spelled = words;
+                    spelled.as_str()
+                }
+                None => token,
+            };
+            for word in token.split_whitespace() {
+                let word = if self.keep_punctuation {
+                    word.to_string()
+                } else {
+                    let kept: String = word.chars().filter(|c| c.is_alphanumeric() || *c == '\'').collect();
+                    kept.trim_matches('\'').to_string()
+                };
+                if word.is_empty() {
+                    continue;
+                }
+                words.push(if self.keep_case { word } else { word.to_lowercase() });
+            }
+        }
+        words
+    }
+}
+
+/// Edit operations that turn the reference into the transcript
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct ErrorRate {
+    /// Error rate: edits over the length of the reference
+    pub rate: f64,
+    pub substitutions: usize,
+    pub deletions: usize,
+    pub insertions: usize,
+    /// Words (or characters) in the reference
+    pub reference_length: usize,
+}
+
+/// Word and character error rates of a transcript
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Evaluation {
+    pub wer: ErrorRate,
+    pub cer: ErrorRate,
+}
+
+impl Evaluation {
+    /// Human-readable summary
+    pub fn render(&self) -> String {
+        let line = |name: &str, rate: &ErrorRate, unit: &str| {
+            format!(
+                "{}  {:>6.2}%  ({} substitutions, {} deletions, {} insertions in {} reference {})\n",
+                name,
+                rate.rate * 100.0,
+                rate.substitutions,
+                rate.deletions,
+                rate.insertions,
+                rate.reference_length,
+                unit
+            )
+        };
+        line("WER", &self.wer, "words") + &line("CER", &self.cer, "characters")
+    }
+}
+
+/// Score a transcript against the reference of what was said
+///
+/// Characters are compared on the normalized words joined by single spaces.
+pub fn evaluate(transcript: &str, reference: &str, normalization: &Normalization) -> Evaluation {
+    let hypothesis = normalization.words(transcript);
+    let reference = normalization.words(reference);
+    let hypothesis_chars: Vec<char> = hypothesis.join(" ").chars().collect();
+    let reference_chars: Vec<char> = reference.join(" ").chars().collect();
+
+    Evaluation {
+        wer: error_rate(&reference, &hypothesis),
+        cer: error_rate(&reference_chars, &hypothesis_chars),
+    }
+}
+
+/// Fewest edits from `reference` to `hypothesis` (Levenshtein), by kind
+///
+/// Only two rows of the table are kept, so long transcripts cost time but little memory.
+fn error_rate<T: PartialEq>(reference: &[T], hypothesis: &[T]) -> ErrorRate {
+    // Each cell holds the edit count and its (substitutions, deletions, insertions)
+    type Cell = (usize, usize, usize, usize);
+    let mut previous: Vec<Cell> = (0..=hypothesis.len()).map(|j| (j, 0, 0, j)).collect();
+    let mut current: Vec<Cell> = vec![(0, 0, 0, 0); hypothesis.len() + 1];
+
+    for (i, expected) in reference.iter().enumerate() {
+        current[0] = (i + 1, 0, i + 1, 0);
+        for (j, heard) in hypothesis.iter().enumerate() {
+            let (cost, s, d, n) = previous[j];
+            let diagonal = if expected == heard { (cost, s, d, n) } else { (cost + 1, s + 1, d, n) };
+            let (cost, s, d, n) = previous[j + 1];
+            let deletion = (cost + 1, s, d + 1, n);
+            let (cost, s, d, n) = current[j];
+            let insertion = (cost + 1, s, d, n + 1);
+            current[j + 1] = [diagonal, deletion, insertion].into_iter().min_by_key(|cell| cell.0).unwrap_or(diagonal);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    let (_, substitutions, deletions, insertions) = previous[hypothesis.len()];
+    let errors = substitutions + deletions + insertions;
+    ErrorRate {
+        rate: match reference.len() {
+            0 if errors == 0 => 0.0,
+            0 => 1.0,
+            length => errors as f64 / length as f64,
+        },
+        substitutions,
+        deletions,
+        insertions,
+        reference_length: reference.len(),
+    }
+}
+
+const ONES: [&str; 20] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "eleven", "twelve",
+    "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen", "nineteen",
+];
+
+const TENS: [&str; 10] = ["", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety"];
+
+const SCALES: [(u64, &str); 4] = [
+    (1_000_000_000_000, "trillion"),
+    (1_000_000_000, "billion"),
+    (1_000_000, "million"),
+    (1_000, "thousand"),
+];
+
+/// A token such as `1,500`, `3.25` or `(42)` with its number spelled out in words
+fn spell_token(token: &str) -> Option<String> {
+    let start = token.find(|c: char| c.is_ascii_digit())?;
+    let end = token.rfind(|c: char| c.is_ascii_digit())? + 1;
+    let (prefix, number, suffix) = (&token[..start], &token[start..end], &token[end..]);
+    if prefix.chars().chain(suffix.chars()).any(char::is_alphanumeric) {
+        return None;
+    }
+
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    let grouped_right = whole.split(',').skip(1).all(|group| group.len() == 3);
+    let whole: String = whole.chars().filter(|c| *c != ',').collect();
+    if !grouped_right || !whole.chars().all(|c| c.is_ascii_digit()) || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let mut words = spell(whole.parse().ok()?);
+    if !fraction.is_empty() {
+        words.push_str(" point");
+        for digit in fraction.chars() {
+            words.push(' ');
+            words.push_str(ONES[digit.to_digit(10)? as usize]);
+        }
+    }
+    Some(format!("{}{}{}", prefix, words, suffix))
+}
+
+fn spell(number: u64) -> String {
+    if number < 20 {
+        return ONES[number as usize].to_string();
+    }
+    if number < 100 {
+        return match number % 10 {
+            0 => TENS[(number / 10) as usize].to_string(),
+            ones => format!("{} {}", TENS[(number / 10) as usize], ONES[ones as usize]),
+        };
+    }
+    if number < 1000 {
+        return match number % 100 {
+            0 => format!("{} hundred", ONES[(number / 100) as usize]),
+            rest => format!("{} hundred {}", ONES[(number / 100) as usize], spell(rest)),
+        };
+    }
+    let (scale, name) = SCALES.iter().copied().find(|(scale, _)| number >= *scale).unwrap_or(SCALES[3]);
+    match number % scale {
+        0 => format!("{} {}", spell(number / scale), name),
+        rest => format!("{} {} {}", spell(number / scale), name, spell(rest)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate() {
+        let reference = "The meeting starts at 10, with 1,500 guests. Well-known speakers!";
+        let transcript = "the meeting starts at ten with fifteen hundred guest well known speakers and";
+
+        let plain = evaluate(transcript, reference, &Normalization::default());
+        // "10" and "1,500" miss, as does "guests"; "and" is extra
+        assert_eq!((plain.wer.substitutions, plain.wer.deletions, plain.wer.insertions), (3, 0, 2));
+        assert_eq!(plain.wer.reference_length, 11);
+        assert_eq!(plain.wer.rate, 5.0 / 11.0);
+
+        let spelled = Normalization { spell_numbers: true, ..Default::default() };
+        let scored = evaluate(transcript, reference, &spelled);
+        assert_eq!(spelled.words("1,500 3.25"), ["one", "thousand", "five", "hundred", "three", "point", "two", "five"]);
+        // "fifteen hundred" for "one thousand five hundred" still differs
+        assert_eq!((scored.wer.substitutions, scored.wer.deletions, scored.wer.insertions), (2, 2, 1));
+
+        let strict = Normalization { keep_case: true, keep_punctuation: true, spell_numbers: false };
+        assert!(evaluate(transcript, reference, &strict).wer.rate > plain.wer.rate);
+        assert_eq!(evaluate("abc", "abc", &strict).cer.rate, 0.0);
+        assert_eq!(evaluate("", "", &strict).wer.rate, 0.0);
+    }
+}
@@ -0,0 +1,289 @@
+use async_trait::async_trait;
+use chrono::Duration;
+use reqwest::Client;
+use serde::Deserialize;
+use url::Url;
+
+use super::{AudioFormat, AudioInfo, DownloadMethod, MediaExtractor};
+use crate::Result;
+
+/// archive.org file formats usable as audio, best first
+///
+/// Compact MP3 derivatives come first since they transcribe just as well as the
+/// lossless originals and are much faster to download; video is the last resort.
+const FORMAT_PREFERENCE: &[(&str, AudioFormat)] = &[
+    ("VBR MP3", AudioFormat::Mp3),
+    ("128Kbps MP3", AudioFormat::Mp3),
+    ("MP3", AudioFormat::Mp3),
+    ("64Kbps MP3", AudioFormat::Mp3),
+    ("Ogg Vorbis", AudioFormat::Ogg),
+    ("Flac", AudioFormat::Flac),
+    ("24bit Flac", AudioFormat::Flac),
+    ("WAVE", AudioFormat::Wav),
+    ("h.264", AudioFormat::M4a),
+    ("h.264 IA", AudioFormat::M4a),
+    ("MPEG4", AudioFormat::M4a),
+    ("512Kb MPEG4", AudioFormat::M4a),
+];
+
+/// Internet Archive (archive.org) item extractor
+pub struct ArchiveOrgExtractor {
+    client: Client,
+}
+
+#[derive(Debug, Deserialize)]
+struct ArchiveItem {
+    #[serde(default)]
+    files: Vec<ArchiveFile>,
+    #[serde(default)]
+    metadata: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ArchiveFile {
+    name: String,
+    #[serde(default)]
+    source: String,
+    #[serde(default)]
+    format: String,
+    /// Original file a derivative was made from
+    original: Option<String>,
+    size: Option<String>,
+    /// Duration as seconds (`123.45`) or clock time (`02:03`)
+    length: Option<String>,
+    title: Option<String>,
+    track: Option<String>,
+}
+
+impl ArchiveOrgExtractor {
+    pub fn new() -> Self {
+        Self::with_client(Client::new())
+    }
+
+    /// Create an extractor that shares an existing HTTP client
+    pub fn with_client(client: Client) -> Self {
+        Self { client }
+    }
+
+    /// Item identifier and optional file path from an item, download or embed URL
+    fn parse_url(url: &str) -> Option<(String, Option<String>)> {
+        let parsed = Url::parse(url).ok()?;
+        if !matches!(parsed.host_str()?, "archive.org" | "www.archive.org") {
+            return None;
+        }
+
+        let segments: Vec<String> = parsed
+            .path_segments()?
+            .filter(|s| !s.is_empty())
+            .map(|s| urlencoding::decode(s).map(|d| d.into_owned()).unwrap_or_else(|_| s.to_string()))
+            .collect();
+
+        match segments.as_slice() {
+            [kind, identifier, file @ ..] if matches!(kind.as_str(), "details" | "download" | "embed") => {
+                let file = (!file.is_empty()).then(|| file.join("/"));
+                Some((identifier.clone(), file))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Choose the track to transcribe and its best audio rendition
+fn pick_file<'a>(files: &'a [ArchiveFile], requested: Option<&str>) -> Option<(&'a ArchiveFile, &'a ArchiveFile, usize)> {
+    let rank = |file: &ArchiveFile| FORMAT_PREFERENCE.iter().position(|(format, _)| *format == file.format);
+
+    // Tracks are the uploaded originals in a usable format
+    let mut tracks: Vec<&ArchiveFile> = files
+        .iter()
+        .filter(|f| f.source == "original" && rank(f).is_some())
+        .collect();
+    tracks.sort_by_key(|f| (track_number(f), f.name.clone()));
+
+    let track = match requested {
+        Some(name) => {
+            let named = files.iter().find(|f| f.name == name)?;
+            // A derivative link means its original track
+            let original = named.original.as_deref().unwrap_or(&named.name);
+            files.iter().find(|f| f.name == original).unwrap_or(named)
+        }
+        None => *tracks.first()?,
+    };
+
+    let best = files
+        .iter()
+        .filter(|f| f.name == track.name || f.original.as_deref() == Some(track.name.as_str()))
+        .filter_map(|f| rank(f).map(|r| (r, f)))
+        .min_by_key(|(r, _)| *r)
+        .map(|(_, f)| f)?;
+
+    Some((track, best, tracks.len()))
+}
+
+fn track_number(file: &ArchiveFile) -> u32 {
+    // Tracks look like "3" or "3/12"
+    file.track
+        .as_deref()
+        .and_then(|t| t.split('/').next())
+        .and_then(|t| t.trim().parse().ok())
+        .unwrap_or(u32::MAX)
+}
+
+/// Parse `length` given as seconds or as `[hh:]mm:ss`
+fn parse_length(length: &str) -> Option<f64> {
+    if let Ok(seconds) = length.parse::<f64>() {
+        return Some(seconds);
+    }
+
+    length
+        .split(':')
+        .try_fold(0.0, |total, part| part.parse::<f64>().ok().map(|value| total * 60.0 + value))
+}
+
+/// Metadata fields can be a string or a list of strings
+fn metadata_text(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(text) => Some(text.clone()),
+        serde_json::Value::Array(items) => items.first().and_then(|item| item.as_str()).map(str::to_string),
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl MediaExtractor for ArchiveOrgExtractor {
+    async fn extract_audio_info(&self, url: &str) -> Result<AudioInfo> {
+        let (identifier, requested) = Self::parse_url(url)
+            .ok_or_else(|| anyhow::anyhow!("Not an archive.org item URL: {}", url))?;
+
+        let api_url = format!("https://archive.org/metadata/{}", urlencoding::encode(&identifier));
+        let response = self.client.get(&api_url).send().await?;
+        if !response.status().is_success() {
+            anyhow::bail!("archive.org metadata request failed: HTTP {}", response.status());
+        }
+        let item: ArchiveItem = response.json().await?;
+
+        if item.files.is_empty() {
+            anyhow::bail!("archive.org item not found or has no files: {}", identifier);
+        }
+
+        let (track, file, track_count) = pick_file(&item.files, requested.as_deref())
+            .ok_or_else(|| match &requested {
+                Some(name) => anyhow::anyhow!("No playable audio for '{}' in archive.org item {}", name, identifier),
+                None => anyhow::anyhow!("archive.org item {} has no audio or video files", identifier),
+            })?;
+
+        if requested.is_none() && track_count > 1 {
+            tracing::info!(
+                "Item has {} tracks; transcribing '{}' (link to a file in the item to pick another)",
+                track_count,
+                track.name
+            );
+        }
+
+        let format = FORMAT_PREFERENCE
+            .iter()
+            .find(|(name, _)| *name == file.format)
+            .map(|(_, format)| *format)
+            .unwrap_or(AudioFormat::Mp3);
+
+        let item_title = metadata_text(&item.metadata["title"]);
+        let title = match (&item_title, &track.title) {
+            (Some(item_title), Some(track_title)) if track_count > 1 => format!("{} - {}", item_title, track_title),
+            (Some(item_title), _) => item_title.clone(),
+            (None, Some(track_title)) => track_title.clone(),
+            (None, None) => identifier.clone(),
+        };
+
+        let file_path: Vec<String> = file.name.split('/').map(|s| urlencoding::encode(s).into_owned()).collect();
+        let duration = file
+            .length
+            .as_deref()
+            .or(track.length.as_deref())
+            .and_then(parse_length)
+            .map(|seconds| Duration::milliseconds((seconds * 1000.0) as i64));
+
+        Ok(AudioInfo {
+            download_url: format!(
+                "https://archive.org/download/{}/{}",
+                urlencoding::encode(&identifier),
+                file_path.join("/")
+            ),
+            download_method: DownloadMethod::DirectUrl,
+            duration,
+            title: Some(title),
+            format,
+            sample_rate: None,
+            file_size: file.size.as_deref().and_then(|s| s.parse().ok()),
+            original_url: url.to_string(),
+            chapters: Vec::new(),
+        })
+    }
+
+    fn supports_url(&self, url: &str) -> bool {
+        Self::parse_url(url).is_some()
+    }
+
+    fn platform_name(&self) -> &'static str {
+        "Internet Archive"
+    }
+}
+
+impl Default for ArchiveOrgExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files() -> Vec<ArchiveFile> {
+        serde_json::from_str(
+            r#"[
+                {"name": "02 Side B.flac", "source": "original", "format": "Flac", "track": "2"},
+                {"name": "01 Side A.flac", "source": "original", "format": "Flac", "track": "1", "title": "Side A", "length": "1805.2"},
+                {"name": "01 Side A.mp3", "source": "derivative", "format": "VBR MP3", "original": "01 Side A.flac", "size": "4200000", "length": "30:05"},
+                {"name": "01 Side A.ogg", "source": "derivative", "format": "Ogg Vorbis", "original": "01 Side A.flac"},
+                {"name": "02 Side B.mp3", "source": "derivative", "format": "VBR MP3", "original": "02 Side B.flac"},
+                {"name": "cover.jpg", "source": "original", "format": "JPEG"}
+            ]"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_url() {
+        assert_eq!(
+            ArchiveOrgExtractor::parse_url("https://archive.org/details/OTRR_Dragnet_Singles"),
+            Some(("OTRR_Dragnet_Singles".to_string(), None))
+        );
+        assert_eq!(
+            ArchiveOrgExtractor::parse_url("https://archive.org/download/gd1977/disc%201/01%20Intro.flac"),
+            Some(("gd1977".to_string(), Some("disc 1/01 Intro.flac".to_string())))
+        );
+        assert_eq!(ArchiveOrgExtractor::parse_url("https://archive.org/search?query=radio"), None);
+        assert_eq!(ArchiveOrgExtractor::parse_url("https://example.com/details/item"), None);
+    }
+
+    #[test]
+    fn test_picks_best_derivative_of_first_track() {
+        let files = files();
+
+        let (track, best, count) = pick_file(&files, None).unwrap();
+        assert_eq!(track.name, "01 Side A.flac");
+        assert_eq!(best.name, "01 Side A.mp3");
+        assert_eq!(count, 2);
+
+        let (track, best, _) = pick_file(&files, Some("02 Side B.flac")).unwrap();
+        assert_eq!(track.name, "02 Side B.flac");
+        assert_eq!(best.name, "02 Side B.mp3");
+    }
+
+    #[test]
+    fn test_parse_length() {
+        assert_eq!(parse_length("1805.2"), Some(1805.2));
+        assert_eq!(parse_length("30:05"), Some(1805.0));
+        assert_eq!(parse_length("1:00:00"), Some(3600.0));
+        assert_eq!(parse_length("n/a"), None);
+    }
+}
@@ -0,0 +1,261 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use std::path::Path;
+use url::Url;
+
+use super::{AudioFormat, AudioInfo, DownloadMethod, MediaExtractor};
+use crate::Result;
+
+/// Google Drive and Dropbox share link extractor
+///
+/// Share links point at a preview page rather than the file, so they are rewritten
+/// to the providers' direct download endpoints before downloading.
+pub struct CloudDriveExtractor {
+    client: Client,
+}
+
+impl CloudDriveExtractor {
+    pub fn new() -> Self {
+        Self::with_client(Client::new())
+    }
+
+    /// Create an extractor that shares an existing HTTP client
+    pub fn with_client(client: Client) -> Self {
+        Self { client }
+    }
+
+    /// Extract the file id from a Google Drive share link
+    pub fn google_drive_file_id(url: &str) -> Option<String> {
+        let parsed = Url::parse(url).ok()?;
+        let host = parsed.host_str()?;
+        if !host.ends_with("drive.google.com") && !host.ends_with("docs.google.com") {
+            return None;
+        }
+
+        // https://drive.google.com/file/d/<id>/view
+        let segments: Vec<&str> = parsed.path_segments()?.collect();
+        if let Some(pos) = segments.iter().position(|s| *s == "d") {
+            if let Some(id) = segments.get(pos + 1).filter(|id| !id.is_empty()) {
+                return Some(id.to_string());
+            }
+        }
+
+        // https://drive.google.com/open?id=<id> and https://drive.google.com/uc?id=<id>
+        parsed
+            .query_pairs()
+            .find(|(key, _)| key == "id")
+            .map(|(_, value)| value.into_owned())
+    }
+
+    /// Rewrite a Dropbox share link so it serves the file instead of the preview page
+    pub fn dropbox_direct_url(url: &str) -> Option<String> {
+        let mut parsed = Url::parse(url).ok()?;
+        if !parsed.host_str()?.ends_with("dropbox.com") {
+            return None;
+        }
+
+        let pairs: Vec<(String, String)> = parsed
+            .query_pairs()
+            .filter(|(key, _)| key != "dl" && key != "raw")
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect();
+
+        parsed
+            .query_pairs_mut()
+            .clear()
+            .extend_pairs(pairs)
+            .append_pair("dl", "1");
+
+        Some(parsed.to_string())
+    }
+
+    /// Build the confirmed download URL from Drive's "file is too large to scan" page
+    pub fn drive_confirm_url(html: &str, file_id: &str) -> String {
+        let field = |name: &str| -> Option<String> {
+            let marker = format!("name=\"{}\" value=\"", name);
+            let start = html.find(&marker)? + marker.len();
+            let end = html[start..].find('"')?;
+            Some(html[start..start + end].to_string())
+        };
+
+        let confirm = field("confirm").unwrap_or_else(|| "t".to_string());
+        let mut url = format!(
+            "https://drive.usercontent.google.com/download?id={}&export=download&confirm={}",
+            urlencoding::encode(file_id),
+            urlencoding::encode(&confirm)
+        );
+        if let Some(uuid) = field("uuid") {
+            url.push_str(&format!("&uuid={}", urlencoding::encode(&uuid)));
+        }
+
+        url
+    }
+
+    /// Resolve a share link to a URL that returns the file contents
+    async fn resolve_download_url(&self, url: &str) -> Result<String> {
+        if let Some(direct) = Self::dropbox_direct_url(url) {
+            return Ok(direct);
+        }
+
+        let file_id = Self::google_drive_file_id(url)
+            .ok_or_else(|| anyhow::anyhow!("Could not find a file id in Google Drive link: {}", url))?;
+        let direct = format!(
+            "https://drive.google.com/uc?export=download&id={}",
+            urlencoding::encode(&file_id)
+        );
+
+        // Small files are served directly; large ones return an HTML confirmation page
+        let response = self.client.get(&direct).send().await?;
+        if !response.status().is_success() {
+            anyhow::bail!("Failed to access Google Drive file: HTTP {}", response.status());
+        }
+
+        if !is_html(&response) {
+            return Ok(direct);
+        }
+
+        let html = response.text().await?;
+        if html.contains("ServiceLogin") {
+            anyhow::bail!("Google Drive file is not shared publicly (set link sharing to 'Anyone with the link')");
+        }
+
+        tracing::debug!("Google Drive returned a confirmation page, following it");
+        Ok(Self::drive_confirm_url(&html, &file_id))
+    }
+
+    /// Get the file name and size from a download response without reading the body
+    async fn get_file_info(&self, url: &str) -> Result<(Option<String>, Option<String>, Option<u64>)> {
+        let response = self.client.get(url).send().await?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Failed to access shared file: HTTP {}", response.status());
+        }
+        if is_html(&response) {
+            anyhow::bail!("Shared link did not return a media file (is the file shared publicly?)");
+        }
+
+        let filename = response
+            .headers()
+            .get("content-disposition")
+            .and_then(|cd| cd.to_str().ok())
+            .and_then(content_disposition_filename);
+        let content_type = response
+            .headers()
+            .get("content-type")
+            .and_then(|ct| ct.to_str().ok())
+            .map(|s| s.to_string());
+
+        Ok((filename, content_type, response.content_length()))
+    }
+}
+
+/// Check whether a response is an HTML page
+fn is_html(response: &reqwest::Response) -> bool {
+    response
+        .headers()
+        .get("content-type")
+        .and_then(|ct| ct.to_str().ok())
+        .is_some_and(|ct| ct.contains("text/html"))
+}
+
+/// Extract the file name from a Content-Disposition header
+fn content_disposition_filename(header: &str) -> Option<String> {
+    if let Some(encoded) = header.split("filename*=UTF-8''").nth(1) {
+        let encoded = encoded.split(';').next().unwrap_or(encoded);
+        return urlencoding::decode(encoded).ok().map(|s| s.into_owned());
+    }
+
+    header
+        .split("filename=")
+        .nth(1)
+        .map(|name| name.split(';').next().unwrap_or(name).trim_matches('"').to_string())
+}
+
+#[async_trait]
+impl MediaExtractor for CloudDriveExtractor {
+    async fn extract_audio_info(&self, url: &str) -> Result<AudioInfo> {
+        let download_url = self.resolve_download_url(url).await?;
+        let (filename, content_type, file_size) = self.get_file_info(&download_url).await?;
+
+        let extension_format = filename
+            .as_deref()
+            .and_then(|name| Path::new(name).extension())
+            .and_then(|ext| AudioFormat::from_extension(&ext.to_string_lossy()));
+        let format = extension_format.unwrap_or_else(|| match content_type.as_deref() {
+            Some(ct) if ct.contains("mp4") || ct.contains("m4a") => AudioFormat::M4a,
+            Some(ct) if ct.contains("wav") => AudioFormat::Wav,
+            Some(ct) if ct.contains("flac") => AudioFormat::Flac,
+            Some(ct) if ct.contains("ogg") => AudioFormat::Ogg,
+            Some(ct) if ct.contains("webm") => AudioFormat::Webm,
+            _ => AudioFormat::Mp3,
+        });
+
+        let title = filename.map(|name| match name.rfind('.') {
+            Some(dot_pos) => name[..dot_pos].to_string(),
+            None => name,
+        });
+
+        Ok(AudioInfo {
+            download_url,
+            download_method: DownloadMethod::DirectUrl,
+            duration: None,
+            title,
+            format,
+            sample_rate: None,
+            file_size,
+            original_url: url.to_string(),
+            chapters: Vec::new(),
+        })
+    }
+
+    fn supports_url(&self, url: &str) -> bool {
+        Self::google_drive_file_id(url).is_some() || Self::dropbox_direct_url(url).is_some()
+    }
+
+    fn platform_name(&self) -> &'static str {
+        "Google Drive / Dropbox"
+    }
+}
+
+impl Default for CloudDriveExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_google_drive_file_id() {
+        assert_eq!(
+            CloudDriveExtractor::google_drive_file_id("https://drive.google.com/file/d/1AbC_dEf/view?usp=sharing"),
+            Some("1AbC_dEf".to_string())
+        );
+        assert_eq!(
+            CloudDriveExtractor::google_drive_file_id("https://drive.google.com/open?id=1AbC_dEf"),
+            Some("1AbC_dEf".to_string())
+        );
+        assert_eq!(CloudDriveExtractor::google_drive_file_id("https://example.com/file/d/123"), None);
+    }
+
+    #[test]
+    fn test_dropbox_direct_url() {
+        assert_eq!(
+            CloudDriveExtractor::dropbox_direct_url("https://www.dropbox.com/scl/fi/abc/talk.mp3?rlkey=xyz&dl=0"),
+            Some("https://www.dropbox.com/scl/fi/abc/talk.mp3?rlkey=xyz&dl=1".to_string())
+        );
+        assert_eq!(CloudDriveExtractor::dropbox_direct_url("https://example.com/talk.mp3"), None);
+    }
+
+    #[test]
+    fn test_drive_confirm_url() {
+        let html = r#"<form action="https://drive.usercontent.google.com/download"><input type="hidden" name="confirm" value="t"><input type="hidden" name="uuid" value="1234-abcd"></form>"#;
+
+        assert_eq!(
+            CloudDriveExtractor::drive_confirm_url(html, "FILE"),
+            "https://drive.usercontent.google.com/download?id=FILE&export=download&confirm=t&uuid=1234-abcd"
+        );
+    }
+}
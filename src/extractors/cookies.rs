@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use url::Url;
+
+/// Login cookies for age-restricted, member-only and login-gated media
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CookiesConfig {
+    /// Netscape-format cookies file (as exported by browser extensions or `yt-dlp --cookies`)
+    pub file: Option<PathBuf>,
+
+    /// Browser to read cookies from, in yt-dlp syntax (e.g. `firefox`, `chrome:Profile 1`)
+    pub from_browser: Option<String>,
+}
+
+impl CookiesConfig {
+    /// Arguments that hand the cookies to yt-dlp
+    pub fn yt_dlp_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(file) = &self.file {
+            args.push("--cookies".to_string());
+            args.push(file.display().to_string());
+        }
+        if let Some(browser) = &self.from_browser {
+            args.push("--cookies-from-browser".to_string());
+            args.push(browser.clone());
+        }
+        args
+    }
+
+    /// `Cookie` header for a plain HTTP request, from the cookies file
+    ///
+    /// Browser cookie stores are encrypted, so only the file applies to direct downloads.
+    pub fn header_for(&self, url: &str) -> Option<String> {
+        let file = self.file.as_ref()?;
+        let contents = match fs_err::read_to_string(file) {
+            Ok(contents) => contents,
+            Err(e) => {
+                tracing::warn!("Could not read cookies file: {}", e);
+                return None;
+            }
+        };
+
+        let header = matching_cookies(&contents, url, chrono::Utc::now().timestamp());
+        (!header.is_empty()).then_some(header)
+    }
+}
+
+/// Cookies from a Netscape cookies file that apply to a URL, as a `Cookie` header value
+fn matching_cookies(contents: &str, url: &str, now: i64) -> String {
+    let Ok(url) = Url::parse(url) else {
+        return String::new();
+    };
+    let host = url.host_str().unwrap_or("").to_lowercase();
+
+    contents
+        .lines()
+        .filter_map(|line| {
+            // HttpOnly cookies are written with a `#HttpOnly_` prefix rather than commented out
+            let line = line.strip_prefix("#HttpOnly_").unwrap_or(line);
+            if line.starts_with('#') {
+                return None;
+            }
+
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [domain, include_subdomains, path, secure, expires, name, value] = fields.as_slice() else {
+                return None;
+            };
+
+            let domain = domain.trim_start_matches('.').to_lowercase();
+            let domain_matches = host == domain
+                || (*include_subdomains == "TRUE" && host.ends_with(&format!(".{}", domain)));
+            let expired = expires.parse::<i64>().is_ok_and(|expires| expires != 0 && expires < now);
+
+            (domain_matches
+                && url.path().starts_with(path)
+                && (*secure != "TRUE" || url.scheme() == "https")
+                && !expired)
+                .then(|| format!("{}={}", name, value))
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matching_cookies() {
+        let contents = "# Netscape HTTP Cookie File\n\
+            .example.com\tTRUE\t/\tTRUE\t0\tsession\tabc\n\
+            #HttpOnly_media.example.com\tFALSE\t/members\tFALSE\t2000000000\ttoken\txyz\n\
+            .example.com\tTRUE\t/\tFALSE\t1000\told\tgone\n\
+            other.org\tFALSE\t/\tFALSE\t0\tid\t1\n";
+        let now = 1_700_000_000;
+
+        assert_eq!(
+            matching_cookies(contents, "https://media.example.com/members/talk.mp4", now),
+            "session=abc; token=xyz"
+        );
+        assert_eq!(matching_cookies(contents, "http://media.example.com/public.mp4", now), "");
+        assert_eq!(matching_cookies(contents, "https://example.org/a.mp3", now), "");
+    }
+
+    #[test]
+    fn test_yt_dlp_args() {
+        let cookies = CookiesConfig {
+            file: Some(PathBuf::from("cookies.txt")),
+            from_browser: Some("firefox".to_string()),
+        };
+        assert_eq!(
+            cookies.yt_dlp_args(),
+            ["--cookies", "cookies.txt", "--cookies-from-browser", "firefox"]
+        );
+        assert!(CookiesConfig::default().yt_dlp_args().is_empty());
+    }
+}
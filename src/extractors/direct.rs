@@ -1,20 +1,44 @@
+use anyhow::Context;
 use async_trait::async_trait;
 use reqwest::Client;
 use std::path::Path;
+use std::time::Duration;
+use tokio::process::Command;
 use url::Url;
 
-use super::{AudioFormat, AudioInfo, MediaExtractor};
+use super::cookies::CookiesConfig;
+use super::{AudioFormat, AudioInfo, DownloadMethod, MediaExtractor};
+use crate::http::NetworkConfig;
 use crate::Result;
 
 /// Direct URL extractor for audio and video files
 pub struct DirectExtractor {
     client: Client,
+    cookies: CookiesConfig,
 }
 
 impl DirectExtractor {
     pub fn new() -> Self {
-        Self {
-            client: Client::new(),
+        Self::with_client(Client::new())
+    }
+    
+    /// Create an extractor that shares an existing HTTP client
+    pub fn with_client(client: Client) -> Self {
+        Self { client, cookies: CookiesConfig::default() }
+    }
+    
+    /// Send login cookies from the cookies file with every request
+    pub fn with_cookies(mut self, cookies: CookiesConfig) -> Self {
+        self.cookies = cookies;
+        self
+    }
+    
+    /// GET or HEAD request carrying any matching cookies
+    fn request(&self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
+        let request = self.client.request(method, url);
+        match self.cookies.header_for(url) {
+            Some(cookies) => request.header(reqwest::header::COOKIE, cookies),
+            None => request,
         }
     }
     
@@ -22,8 +46,8 @@ impl DirectExtractor {
     fn determine_format(&self, url: &str, content_type: Option<&str>) -> AudioFormat {
         // Try to determine from URL extension first
         if let Ok(parsed_url) = Url::parse(url) {
-            if let Some(path) = parsed_url.path_segments() {
-                if let Some(filename) = path.last() {
+            if let Some(mut path) = parsed_url.path_segments() {
+                if let Some(filename) = path.next_back() {
                     if let Some(extension) = Path::new(filename).extension() {
                         if let Some(format) = AudioFormat::from_extension(&extension.to_string_lossy()) {
                             return format;
@@ -63,9 +87,30 @@ impl DirectExtractor {
         media_extensions.iter().any(|ext| url_lower.contains(ext))
     }
     
+    /// Check if URL points to an HLS (.m3u8) or DASH (.mpd) manifest
+    fn is_stream_manifest(&self, url: &str) -> bool {
+        Url::parse(url)
+            .map(|parsed| {
+                let path = parsed.path().to_lowercase();
+                path.ends_with(".m3u8") || path.ends_with(".mpd")
+            })
+            .unwrap_or(false)
+    }
+    
+    /// Whether a manifest describes a live stream (HLS without an end tag, or dynamic DASH)
+    async fn is_live_stream(&self, url: &str) -> Result<bool> {
+        let response = self.request(reqwest::Method::GET, url).send().await?;
+        if !response.status().is_success() {
+            anyhow::bail!("Failed to fetch stream manifest: HTTP {}", response.status());
+        }
+        
+        let manifest = response.text().await?;
+        Ok(is_live_manifest(&manifest))
+    }
+    
     /// Get content information via HEAD request
     async fn get_content_info(&self, url: &str) -> Result<(Option<String>, Option<u64>)> {
-        let response = self.client.head(url).send().await?;
+        let response = self.request(reqwest::Method::HEAD, url).send().await?;
         
         if !response.status().is_success() {
             anyhow::bail!("Failed to access URL: HTTP {}", response.status());
@@ -94,6 +139,25 @@ impl MediaExtractor for DirectExtractor {
         let parsed_url = Url::parse(url)
             .map_err(|_| anyhow::anyhow!("Invalid URL: {}", url))?;
             
+        // Streams are captured by ffmpeg instead of downloaded
+        if self.is_stream_manifest(url) {
+            if self.is_live_stream(url).await? {
+                tracing::warn!("Live stream detected; capture runs until the stream ends unless --max-duration is set");
+            }
+            
+            return Ok(AudioInfo {
+                download_url: url.to_string(),
+                download_method: DownloadMethod::Stream,
+                duration: None,
+                title: title_from_url(&parsed_url),
+                format: AudioFormat::M4a,
+                sample_rate: None,
+                file_size: None,
+                original_url: url.to_string(),
+                chapters: Vec::new(),
+            });
+        }
+        
         // Get content information
         let (content_type, file_size) = self.get_content_info(url).await?;
         
@@ -101,30 +165,18 @@ impl MediaExtractor for DirectExtractor {
         let format = self.determine_format(url, content_type.as_deref());
         
         // Extract title from filename
-        let title = parsed_url
-            .path_segments()
-            .and_then(|segments| segments.last())
-            .filter(|filename| !filename.is_empty())
-            .map(|filename| {
-                // Remove extension and decode URL encoding
-                let name = if let Some(dot_pos) = filename.rfind('.') {
-                    &filename[..dot_pos]
-                } else {
-                    filename
-                };
-                urlencoding::decode(name)
-                    .unwrap_or_else(|_| name.into())
-                    .replace(['_', '-'], " ")
-            });
+        let title = title_from_url(&parsed_url);
         
         Ok(AudioInfo {
             download_url: url.to_string(),
+            download_method: DownloadMethod::DirectUrl,
             duration: None, // Can't determine without downloading
             title,
             format,
             sample_rate: None, // Unknown without analysis
             file_size,
             original_url: url.to_string(),
+            chapters: Vec::new(),
         })
     }
     
@@ -134,8 +186,8 @@ impl MediaExtractor for DirectExtractor {
             return false;
         }
         
-        // Check if it looks like a media file
-        self.is_media_url(url)
+        // Check if it looks like a media file or stream manifest
+        self.is_media_url(url) || self.is_stream_manifest(url)
     }
     
     fn platform_name(&self) -> &'static str {
@@ -147,4 +199,97 @@ impl Default for DirectExtractor {
     fn default() -> Self {
         Self::new()
     }
-} 
\ No newline at end of file
+}
+
+/// Title from the last path segment, without extension and URL encoding
+fn title_from_url(url: &Url) -> Option<String> {
+    url.path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|filename| !filename.is_empty())
+        .map(|filename| {
+            let name = filename.rfind('.').map_or(filename, |dot_pos| &filename[..dot_pos]);
+            urlencoding::decode(name)
+                .unwrap_or_else(|_| name.into())
+                .replace(['_', '-'], " ")
+        })
+}
+
+/// Whether manifest content describes a live stream
+fn is_live_manifest(manifest: &str) -> bool {
+    if manifest.trim_start().starts_with("#EXTM3U") {
+        // Master playlists only list variants; their media playlists carry the end tag
+        let is_master = manifest.contains("#EXT-X-STREAM-INF");
+        !is_master && !manifest.contains("#EXT-X-ENDLIST")
+    } else {
+        manifest.contains("type=\"dynamic\"")
+    }
+}
+
+/// Capture the audio track of an HLS/DASH stream into an audio file with ffmpeg
+///
+/// `max_duration` stops the capture early, which live streams otherwise need to end.
+pub async fn capture_stream(
+    url: &str,
+    output_path: &Path,
+    max_duration: Option<Duration>,
+    network: &NetworkConfig,
+) -> Result<()> {
+    let mut command = Command::new("ffmpeg");
+    command
+        .args(["-hide_banner", "-loglevel", "error", "-nostdin"])
+        .args(network.ffmpeg_args())
+        .args(["-i", url]);
+    if let Some(max_duration) = max_duration {
+        command.args(["-t", &max_duration.as_secs_f64().to_string()]);
+    }
+    command
+        .args(["-vn", "-map", "0:a:0", "-c:a", "aac", "-b:a", "128k", "-y"])
+        .arg(output_path);
+    
+    let output = command.output().await.context("Failed to run ffmpeg")?;
+    crate::logs::check("ffmpeg", &output, "Failed to capture stream with ffmpeg")?;
+    
+    Ok(())
+}
+
+/// Length of a media file or URL in seconds, read by ffprobe from its header
+///
+/// Only as much of a remote file is fetched as ffprobe needs; `None` if it does not say.
+pub async fn probe_duration(input: &str, network: &NetworkConfig) -> Result<Option<f64>> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "error"])
+        .args(network.ffmpeg_args())
+        .args(["-show_entries", "format=duration", "-of", "default=noprint_wrappers=1:nokey=1", input])
+        .output()
+        .await
+        .context("Failed to run ffprobe")?;
+    crate::logs::check("ffprobe", &output, "Failed to read the media's duration with ffprobe")?;
+    
+    Ok(String::from_utf8_lossy(&output.stdout).trim().parse::<f64>().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_recognizes_stream_manifests() {
+        let extractor = DirectExtractor::new();
+        
+        assert!(extractor.supports_url("https://cdn.example.com/live/index.m3u8?token=abc"));
+        assert!(extractor.supports_url("https://cdn.example.com/vod/talk/manifest.MPD"));
+        assert!(!extractor.is_stream_manifest("https://cdn.example.com/talk.mp4"));
+    }
+    
+    #[test]
+    fn test_is_live_manifest() {
+        let vod = "#EXTM3U\n#EXT-X-TARGETDURATION:6\n#EXTINF:6.0,\nseg0.ts\n#EXT-X-ENDLIST\n";
+        let live = "#EXTM3U\n#EXT-X-TARGETDURATION:6\n#EXTINF:6.0,\nseg0.ts\n";
+        let master = "#EXTM3U\n#EXT-X-STREAM-INF:BANDWIDTH=800000\nlow.m3u8\n";
+        
+        assert!(!is_live_manifest(vod));
+        assert!(is_live_manifest(live));
+        assert!(!is_live_manifest(master));
+        assert!(is_live_manifest("<MPD type=\"dynamic\" profiles=\"urn:mpeg:dash:profile:isoff-live:2011\">"));
+    }
+}
@@ -0,0 +1,307 @@
+use async_trait::async_trait;
+use chrono::Duration;
+use reqwest::Client;
+use serde::Deserialize;
+use std::path::Path;
+use url::Url;
+
+use super::{AudioFormat, AudioInfo, DownloadMethod, MediaExtractor};
+use crate::Result;
+
+/// PeerTube and Mastodon extractor
+///
+/// Fediverse servers run on arbitrary domains, so candidate links are recognised by
+/// their path and confirmed by querying the instance's API.
+pub struct FediverseExtractor {
+    client: Client,
+}
+
+/// Kind of fediverse link, with the id the instance's API needs
+#[derive(Debug, PartialEq)]
+enum FediverseLink {
+    /// PeerTube video (`/w/<id>`, `/videos/watch/<id>`, `/videos/embed/<id>`)
+    PeerTube(String),
+    /// Mastodon status (`/@user/<id>`, `/users/<user>/statuses/<id>`)
+    Mastodon(String),
+}
+
+#[derive(Debug, Deserialize)]
+struct PeerTubeVideo {
+    name: String,
+    duration: Option<i64>,
+    #[serde(default)]
+    files: Vec<PeerTubeFile>,
+    #[serde(default, rename = "streamingPlaylists")]
+    streaming_playlists: Vec<PeerTubePlaylist>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PeerTubePlaylist {
+    #[serde(default)]
+    files: Vec<PeerTubeFile>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct PeerTubeFile {
+    resolution: PeerTubeResolution,
+    size: Option<u64>,
+    #[serde(rename = "fileUrl")]
+    file_url: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct PeerTubeResolution {
+    id: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct MastodonStatus {
+    content: String,
+    account: MastodonAccount,
+    media_attachments: Vec<MastodonAttachment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MastodonAccount {
+    acct: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MastodonAttachment {
+    #[serde(rename = "type")]
+    kind: String,
+    url: String,
+    description: Option<String>,
+    meta: Option<serde_json::Value>,
+}
+
+impl FediverseExtractor {
+    pub fn new() -> Self {
+        Self::with_client(Client::new())
+    }
+
+    /// Create an extractor that shares an existing HTTP client
+    pub fn with_client(client: Client) -> Self {
+        Self { client }
+    }
+
+    /// Recognise PeerTube and Mastodon link shapes
+    fn classify(url: &str) -> Option<(Url, FediverseLink)> {
+        let parsed = Url::parse(url).ok()?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return None;
+        }
+
+        let segments: Vec<&str> = parsed.path_segments()?.filter(|s| !s.is_empty()).collect();
+        let link = match segments.as_slice() {
+            ["w", id] => FediverseLink::PeerTube(id.to_string()),
+            ["videos", "watch" | "embed", id] => FediverseLink::PeerTube(id.to_string()),
+            [user, id] if user.starts_with('@') && id.chars().all(|c| c.is_ascii_digit()) => {
+                FediverseLink::Mastodon(id.to_string())
+            }
+            ["users", _, "statuses", id] if id.chars().all(|c| c.is_ascii_digit()) => {
+                FediverseLink::Mastodon(id.to_string())
+            }
+            _ => return None,
+        };
+
+        Some((parsed, link))
+    }
+
+    /// Query an instance API endpoint, failing if the host is not that kind of server
+    async fn probe<T: serde::de::DeserializeOwned>(&self, api_url: Url, platform: &str) -> Result<T> {
+        let response = self.client.get(api_url.clone()).send().await?;
+        if !response.status().is_success() {
+            anyhow::bail!("{} API probe failed ({}): HTTP {}", platform, api_url, response.status());
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| anyhow::anyhow!("{} is not a {} instance: {}", api_url.host_str().unwrap_or(""), platform, e))
+    }
+
+    async fn extract_peertube(&self, url: &str, base: &Url, id: &str) -> Result<AudioInfo> {
+        let video: PeerTubeVideo = self.probe(base.join(&format!("/api/v1/videos/{}", id))?, "PeerTube").await?;
+
+        let file = pick_peertube_file(&video)
+            .ok_or_else(|| anyhow::anyhow!("PeerTube video has no downloadable files: {}", url))?;
+
+        Ok(AudioInfo {
+            download_url: file.file_url.clone(),
+            download_method: DownloadMethod::DirectUrl,
+            duration: video.duration.map(Duration::seconds),
+            title: Some(video.name),
+            format: format_from_url(&file.file_url),
+            sample_rate: None,
+            file_size: file.size,
+            original_url: url.to_string(),
+            chapters: Vec::new(),
+        })
+    }
+
+    async fn extract_mastodon(&self, url: &str, base: &Url, id: &str) -> Result<AudioInfo> {
+        let status: MastodonStatus = self.probe(base.join(&format!("/api/v1/statuses/{}", id))?, "Mastodon").await?;
+
+        let attachment = status
+            .media_attachments
+            .iter()
+            .find(|a| matches!(a.kind.as_str(), "video" | "audio" | "gifv"))
+            .ok_or_else(|| anyhow::anyhow!("Post has no video or audio attachment: {}", url))?;
+
+        let duration = attachment
+            .meta
+            .as_ref()
+            .and_then(|meta| meta["original"]["duration"].as_f64())
+            .map(|seconds| Duration::milliseconds((seconds * 1000.0) as i64));
+
+        let text = strip_html(&status.content);
+        let title = attachment
+            .description
+            .clone()
+            .filter(|d| !d.trim().is_empty())
+            .or_else(|| (!text.is_empty()).then(|| text.chars().take(80).collect()))
+            .unwrap_or_else(|| format!("Post by @{}", status.account.acct));
+
+        Ok(AudioInfo {
+            download_url: attachment.url.clone(),
+            download_method: DownloadMethod::DirectUrl,
+            duration,
+            title: Some(title),
+            format: format_from_url(&attachment.url),
+            sample_rate: None,
+            file_size: None,
+            original_url: url.to_string(),
+            chapters: Vec::new(),
+        })
+    }
+}
+
+/// Pick the cheapest file that still carries the audio track
+fn pick_peertube_file(video: &PeerTubeVideo) -> Option<PeerTubeFile> {
+    // Web-video files first, HLS-only videos list theirs under the playlist
+    let files: Vec<&PeerTubeFile> = video
+        .files
+        .iter()
+        .chain(video.streaming_playlists.iter().flat_map(|p| p.files.iter()))
+        .collect();
+
+    // Resolution 0 is an audio-only rendition; otherwise the smallest file is enough
+    files
+        .iter()
+        .find(|f| f.resolution.id == 0)
+        .or_else(|| files.iter().min_by_key(|f| (f.resolution.id, f.size.unwrap_or(u64::MAX))))
+        .map(|f| (*f).clone())
+}
+
+/// Guess the media format from the file extension in a URL
+fn format_from_url(url: &str) -> AudioFormat {
+    let path = Url::parse(url).map(|u| u.path().to_string()).unwrap_or_default();
+    match Path::new(&path).extension().and_then(|ext| ext.to_str()) {
+        Some("mp4" | "m4v" | "mov") => AudioFormat::M4a,
+        Some(ext) => AudioFormat::from_extension(ext).unwrap_or(AudioFormat::M4a),
+        None => AudioFormat::M4a,
+    }
+}
+
+/// Reduce a post's HTML content to plain text
+fn strip_html(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.replace("</p><p>", " ").replace("<br>", " ").chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+
+    text.replace("&amp;", "&")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[async_trait]
+impl MediaExtractor for FediverseExtractor {
+    async fn extract_audio_info(&self, url: &str) -> Result<AudioInfo> {
+        let (base, link) = Self::classify(url)
+            .ok_or_else(|| anyhow::anyhow!("Not a PeerTube or Mastodon link: {}", url))?;
+
+        match link {
+            FediverseLink::PeerTube(id) => self.extract_peertube(url, &base, &id).await,
+            FediverseLink::Mastodon(id) => self.extract_mastodon(url, &base, &id).await,
+        }
+    }
+
+    fn supports_url(&self, url: &str) -> bool {
+        Self::classify(url).is_some()
+    }
+
+    fn platform_name(&self) -> &'static str {
+        "PeerTube / Mastodon"
+    }
+}
+
+impl Default for FediverseExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(url: &str) -> Option<FediverseLink> {
+        FediverseExtractor::classify(url).map(|(_, link)| link)
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(
+            link("https://framatube.org/w/kkGMgK9ZtnKfYAgnEtQxbv"),
+            Some(FediverseLink::PeerTube("kkGMgK9ZtnKfYAgnEtQxbv".to_string()))
+        );
+        assert_eq!(
+            link("https://peertube.example/videos/watch/9c9de5e8-0a1e-484a-b099-e80766180a6d"),
+            Some(FediverseLink::PeerTube("9c9de5e8-0a1e-484a-b099-e80766180a6d".to_string()))
+        );
+        assert_eq!(
+            link("https://mastodon.social/@Gargron/109389120318123456"),
+            Some(FediverseLink::Mastodon("109389120318123456".to_string()))
+        );
+        assert_eq!(link("https://mastodon.social/@Gargron"), None);
+        assert_eq!(link("https://example.com/w/p/playlist"), None);
+        assert_eq!(link("https://example.com/talk.mp4"), None);
+    }
+
+    #[test]
+    fn test_peertube_prefers_audio_only_file() {
+        let json = r#"{
+            "name": "Talk",
+            "duration": 600,
+            "files": [],
+            "streamingPlaylists": [{"files": [
+                {"resolution": {"id": 720, "label": "720p"}, "size": 9000, "fileUrl": "https://v.example/720.mp4"},
+                {"resolution": {"id": 0, "label": "Audio"}, "size": 800, "fileUrl": "https://v.example/0.mp4"}
+            ]}]
+        }"#;
+        let video: PeerTubeVideo = serde_json::from_str(json).unwrap();
+
+        assert_eq!(pick_peertube_file(&video).unwrap().file_url, "https://v.example/0.mp4");
+    }
+
+    #[test]
+    fn test_strip_html() {
+        assert_eq!(
+            strip_html("<p>New episode &amp; <a href=\"x\">notes</a></p><p>Enjoy</p>"),
+            "New episode & notes Enjoy"
+        );
+    }
+}
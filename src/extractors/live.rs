@@ -0,0 +1,244 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::{Child, Command};
+
+use super::cookies::CookiesConfig;
+use super::{AudioFormat, AudioInfo, DownloadMethod};
+use crate::http::NetworkConfig;
+
+/// Size of the header ffmpeg writes for a PCM WAV file
+const WAV_HEADER_LEN: u64 = 44;
+
+/// 16 kHz, one channel, two bytes per sample
+const BYTES_PER_SECOND: f64 = 32_000.0;
+
+/// How a live stream is recorded for `transcribe --live`
+#[derive(Debug, Clone)]
+pub struct LiveOptions {
+    /// Stop recording after this long
+    pub duration: Duration,
+
+    /// Transcribe in chunks of this length while the stream is still being recorded
+    pub chunk: Option<Duration>,
+
+    /// Record from the start of the stream instead of the live edge (YouTube)
+    pub from_start: bool,
+
+    /// Wait for a scheduled stream to go live instead of failing
+    pub wait: bool,
+
+    /// Also write the whole recording to one file, for `--save-audio`
+    pub keep_recording: bool,
+}
+
+/// Whether yt-dlp's `live_status` describes a stream that can be recorded now (or soon, when waiting)
+fn check_live_status(status: Option<&str>, wait: bool) -> Result<()> {
+    match status {
+        Some("is_live") | None => Ok(()),
+        Some("is_upcoming") if wait => Ok(()),
+        Some("is_upcoming") => anyhow::bail!("The stream has not started yet (use --live-wait to wait for it)"),
+        Some(status) => anyhow::bail!(
+            "Not a live stream ({}); transcribe it without --live",
+            status.replace('_', " ")
+        ),
+    }
+}
+
+/// Audio details for a live stream, checking that it is live first
+pub async fn live_audio_info(
+    yt_dlp: &Path,
+    url: &str,
+    options: &LiveOptions,
+    cookies: &CookiesConfig,
+    network: &NetworkConfig,
+) -> Result<AudioInfo> {
+    let mut command = Command::new(yt_dlp);
+    command
+        .args(cookies.yt_dlp_args())
+        .args(network.yt_dlp_args())
+        .args(["--dump-json", "--no-playlist"]);
+    if options.wait {
+        command.args(["--wait-for-video", "30"]);
+    }
+    let output = command
+        .arg(url)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .context("Failed to run yt-dlp")?;
+    crate::logs::check("yt-dlp", &output, "yt-dlp failed")?;
+
+    let info: Value = serde_json::from_slice(&output.stdout).context("Failed to parse yt-dlp output")?;
+    check_live_status(info["live_status"].as_str(), options.wait)?;
+
+    Ok(AudioInfo {
+        download_url: url.to_string(),
+        download_method: DownloadMethod::Stream,
+        duration: None,
+        title: info["title"].as_str().map(str::to_string),
+        format: AudioFormat::Wav,
+        sample_rate: Some(16000),
+        file_size: None,
+        original_url: url.to_string(),
+        chapters: Vec::new(),
+    })
+}
+
+/// Length of a 16 kHz mono 16-bit WAV chunk as written by [`LiveRecording`]
+pub fn wav_duration(path: &Path) -> Option<f64> {
+    let len = fs_err::metadata(path).ok()?.len();
+    (len > WAV_HEADER_LEN).then(|| (len - WAV_HEADER_LEN) as f64 / BYTES_PER_SECOND)
+}
+
+/// A live stream being recorded into numbered 16 kHz WAV chunks
+///
+/// yt-dlp pulls the stream and pipes it into ffmpeg, which cuts it into chunks and stops
+/// after the requested duration. Ctrl-C ends the recording early; what was recorded is kept.
+pub struct LiveRecording {
+    yt_dlp: Child,
+    ffmpeg: Child,
+    dir: PathBuf,
+    next: usize,
+    finished: bool,
+}
+
+impl LiveRecording {
+    /// Start recording `url` into `dir`
+    pub fn start(
+        yt_dlp: &Path,
+        url: &str,
+        dir: &Path,
+        options: &LiveOptions,
+        cookies: &CookiesConfig,
+        network: &NetworkConfig,
+    ) -> Result<Self> {
+        let mut downloader = Command::new(yt_dlp);
+        downloader
+            .args(cookies.yt_dlp_args())
+            .args(network.yt_dlp_args())
+            .args(["--quiet", "--no-playlist", "--no-part", "--format", "bestaudio/best", "--output", "-"]);
+        if options.from_start {
+            downloader.arg("--live-from-start");
+        }
+        if options.wait {
+            downloader.args(["--wait-for-video", "30"]);
+        }
+        let mut yt_dlp = downloader
+            .arg(url)
+            .stdout(Stdio::piped())
+            .stderr(crate::logs::stderr_target("yt-dlp"))
+            .kill_on_drop(true)
+            .spawn()
+            .context("Failed to run yt-dlp")?;
+
+        let stream: Stdio = yt_dlp
+            .stdout
+            .take()
+            .context("yt-dlp has no output")?
+            .try_into()
+            .context("Failed to pipe yt-dlp into ffmpeg")?;
+
+        let duration = options.duration.as_secs_f64().to_string();
+        let chunk = options.chunk.unwrap_or(options.duration).as_secs_f64().to_string();
+        let mut recorder = Command::new("ffmpeg");
+        recorder
+            .args(["-hide_banner", "-loglevel", "error", "-i", "pipe:0", "-vn"])
+            .args(["-t", &duration, "-ac", "1", "-ar", "16000", "-c:a", "pcm_s16le"])
+            .args(["-f", "segment", "-segment_time", &chunk, "-reset_timestamps", "1"])
+            .arg(dir.join("live_%04d.wav"));
+        if options.keep_recording {
+            recorder
+                .args(["-t", &duration, "-ac", "1", "-ar", "16000", "-c:a", "pcm_s16le", "-y"])
+                .arg(dir.join("live.wav"));
+        }
+        let ffmpeg = recorder
+            .stdin(stream)
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .context("Failed to run ffmpeg")?;
+
+        Ok(Self { yt_dlp, ffmpeg, dir: dir.to_path_buf(), next: 0, finished: false })
+    }
+
+    fn chunk_path(&self, index: usize) -> PathBuf {
+        self.dir.join(format!("live_{:04}.wav", index))
+    }
+
+    /// The whole recording, when it was kept
+    pub fn recording_path(&self) -> PathBuf {
+        self.dir.join("live.wav")
+    }
+
+    /// Wait for the next chunk to be complete; `None` once the recording is over
+    ///
+    /// A chunk is complete once ffmpeg has started the one after it, or has exited.
+    pub async fn next_chunk(&mut self) -> Result<Option<PathBuf>> {
+        let current = self.chunk_path(self.next);
+        let following = self.chunk_path(self.next + 1);
+
+        while !self.finished && !following.exists() {
+            tokio::select! {
+                _ = self.ffmpeg.wait() => self.finished = true,
+                _ = tokio::signal::ctrl_c() => {
+                    tracing::info!("Stopping the recording");
+                    // ffmpeg finishes its files once the stream it reads from ends
+                    let _ = self.yt_dlp.start_kill();
+                }
+                _ = tokio::time::sleep(Duration::from_secs(1)) => {}
+            }
+        }
+
+        // A WAV header alone means ffmpeg got no audio into the chunk
+        if wav_duration(&current).is_none() {
+            if self.next == 0 {
+                let output = self.ffmpeg.stderr.take();
+                let mut error = String::new();
+                if let Some(mut stderr) = output {
+                    use tokio::io::AsyncReadExt;
+                    let _ = stderr.read_to_string(&mut error).await;
+                }
+                // yt-dlp's side of the story went to the run's tool log
+                match crate::logs::current().filter(|log| log.exists()) {
+                    Some(log) => anyhow::bail!(
+                        "Failed to record the live stream: {} (yt-dlp output in {})",
+                        error.trim(),
+                        log.display()
+                    ),
+                    None => anyhow::bail!("Failed to record the live stream: {}", error.trim()),
+                }
+            }
+            return Ok(None);
+        }
+
+        self.next += 1;
+        Ok(Some(current))
+    }
+
+    /// Stop recording, if the duration was not reached yet
+    pub async fn stop(mut self) {
+        let _ = self.yt_dlp.start_kill();
+        let _ = self.ffmpeg.wait().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_live_status() {
+        assert!(check_live_status(Some("is_live"), false).is_ok());
+        assert!(check_live_status(Some("is_upcoming"), true).is_ok());
+
+        let upcoming = check_live_status(Some("is_upcoming"), false).unwrap_err();
+        assert!(upcoming.to_string().contains("--live-wait"));
+        let ended = check_live_status(Some("was_live"), true).unwrap_err();
+        assert!(ended.to_string().contains("was live"));
+    }
+}
@@ -1,5 +1,5 @@
-use super::{AudioFormat, AudioInfo, MediaExtractor};
-use anyhow::{anyhow, Result};
+use super::{AudioFormat, AudioInfo, DownloadMethod, MediaExtractor};
+use anyhow::Result;
 use async_trait::async_trait;
 use chrono::Duration;
 use std::path::{Path, PathBuf};
@@ -51,10 +51,7 @@ impl LocalFileExtractor {
             .output()
             .await?;
 
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("Failed to analyze file with ffprobe: {}", error);
-        }
+        crate::logs::check("ffprobe", &output, "Failed to analyze file with ffprobe")?;
 
         let info: serde_json::Value = serde_json::from_slice(&output.stdout)?;
         
@@ -84,16 +81,14 @@ impl LocalFileExtractor {
         Ok((duration, title))
     }
 
-    /// Determine audio format from file extension
-    fn get_audio_format(&self, path: &Path) -> AudioFormat {
+    /// Format of the audio `prepare_audio` produces for a file
+    ///
+    /// MP3 and M4A/AAC files are used as they are; other audio formats and video files
+    /// are converted to MP3.
+    pub fn prepared_format(&self, path: &Path) -> AudioFormat {
         match path.extension().and_then(|ext| ext.to_str()) {
-            Some("mp3") => AudioFormat::Mp3,
             Some("m4a") | Some("aac") => AudioFormat::M4a,
-            Some("wav") => AudioFormat::Wav,
-            Some("flac") => AudioFormat::Flac,
-            Some("ogg") => AudioFormat::Ogg,
-            // For video files or unknown formats, we'll assume they need conversion
-            _ => AudioFormat::Mp3, // Default to MP3 for compatibility
+            _ => AudioFormat::Mp3,
         }
     }
 
@@ -101,31 +96,19 @@ impl LocalFileExtractor {
     pub async fn prepare_audio(&self, source_path: &Path, target_path: &Path) -> Result<AudioFormat> {
         tracing::debug!("Preparing local audio file: {} -> {}", source_path.display(), target_path.display());
 
-        let source_format = self.get_audio_format(source_path);
-        
-        // Check if it's already an audio file in a good format
-        let is_audio_file = matches!(source_path.extension().and_then(|ext| ext.to_str()), 
-            Some("mp3") | Some("m4a") | Some("aac") | Some("wav") | Some("flac") | Some("ogg"));
-
-        if is_audio_file {
-            // For pure audio files, we can often just copy them
-            match source_format {
-                AudioFormat::Mp3 | AudioFormat::M4a => {
-                    // These formats work well with AWS Transcribe, just copy
-                    tokio::fs::copy(source_path, target_path).await?;
-                    Ok(source_format)
-                }
-                _ => {
-                    // Convert other audio formats to MP3 for compatibility
-                    self.convert_to_mp3(source_path, target_path).await?;
-                    Ok(AudioFormat::Mp3)
-                }
-            }
+        let is_ready = matches!(
+            source_path.extension().and_then(|ext| ext.to_str()),
+            Some("mp3") | Some("m4a") | Some("aac")
+        );
+        if is_ready {
+            // These formats work well with AWS Transcribe, just copy
+            tokio::fs::copy(source_path, target_path).await?;
         } else {
-            // For video files or unknown formats, extract/convert to MP3
+            // Other audio formats, video files and unknown formats are converted to MP3
             self.convert_to_mp3(source_path, target_path).await?;
-            Ok(AudioFormat::Mp3)
         }
+
+        Ok(self.prepared_format(source_path))
     }
 
     /// Convert file to MP3 using ffmpeg
@@ -145,10 +128,7 @@ impl LocalFileExtractor {
             .output()
             .await?;
 
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("Failed to convert file with ffmpeg: {}", error);
-        }
+        crate::logs::check("ffmpeg", &output, "Failed to convert file with ffmpeg")?;
 
         Ok(())
     }
@@ -170,21 +150,23 @@ impl MediaExtractor for LocalFileExtractor {
         let metadata = fs::metadata(file_path).await?;
         let file_size = Some(metadata.len());
 
-        // Determine format
-        let format = self.get_audio_format(file_path);
+        // The format the file ends up in once prepared
+        let format = self.prepared_format(file_path);
 
-        // For local files, we'll use a special protocol with absolute path to avoid path issues
+        // Use the absolute path so the file is found regardless of the working directory
         let absolute_path = file_path.canonicalize().unwrap_or_else(|_| file_path.to_path_buf());
-        let download_url = format!("local-file://{}", absolute_path.display());
+        let download_url = absolute_path.display().to_string();
 
         Ok(AudioInfo {
             download_url,
+            download_method: DownloadMethod::LocalFile,
             duration,
             title: Some(title),
             format,
             sample_rate: Some(44100), // Will be normalized to this
             file_size,
             original_url: path.to_string(),
+            chapters: Vec::new(),
         })
     }
 
@@ -198,8 +180,68 @@ impl MediaExtractor for LocalFileExtractor {
         "Local File"
     }
 
-    async fn download_audio(&self, _audio_info: &AudioInfo, _output_path: &PathBuf) -> Result<()> {
-        // This method won't be called for local files since we handle it differently
-        Err(anyhow!("Local files use direct processing, not download"))
+    async fn download_audio(&self, audio_info: &AudioInfo, output_path: &PathBuf) -> Result<()> {
+        self.prepare_audio(Path::new(&audio_info.download_url), output_path).await?;
+        Ok(())
+    }
+}
+
+impl Default for LocalFileExtractor {
+    fn default() -> Self {
+        Self::new()
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn has_ffmpeg() -> bool {
+        std::process::Command::new("ffmpeg").arg("-version").output().is_ok_and(|o| o.status.success())
+    }
+
+    #[test]
+    fn test_prepared_format() {
+        let extractor = LocalFileExtractor::new();
+        assert!(matches!(extractor.prepared_format(Path::new("talk.mp3")), AudioFormat::Mp3));
+        assert!(matches!(extractor.prepared_format(Path::new("talk.m4a")), AudioFormat::M4a));
+        // Converted to MP3, so the temp file and the upload must say so too
+        assert!(matches!(extractor.prepared_format(Path::new("talk.wav")), AudioFormat::Mp3));
+        assert!(matches!(extractor.prepared_format(Path::new("talk.mp4")), AudioFormat::Mp3));
+    }
+
+    #[tokio::test]
+    async fn test_prepare_audio_copies_audio() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("talk.mp3");
+        let target = dir.path().join("prepared.mp3");
+        fs_err::write(&source, b"ID3 audio").unwrap();
+
+        let format = LocalFileExtractor::new().prepare_audio(&source, &target).await.unwrap();
+        assert!(matches!(format, AudioFormat::Mp3));
+        assert_eq!(fs_err::read(&target).unwrap(), b"ID3 audio");
+    }
+
+    #[tokio::test]
+    async fn test_prepare_audio_extracts_video_audio() {
+        if !has_ffmpeg() {
+            eprintln!("ffmpeg not installed, skipping");
+            return;
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("talk.mp4");
+        let status = std::process::Command::new("ffmpeg")
+            .args(["-v", "quiet", "-f", "lavfi", "-i", "testsrc=duration=1:size=64x64", "-f", "lavfi"])
+            .args(["-i", "sine=duration=1", "-shortest", "-y"])
+            .arg(&source)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let target = dir.path().join("prepared.mp3");
+        let format = LocalFileExtractor::new().prepare_audio(&source, &target).await.unwrap();
+        assert!(matches!(format, AudioFormat::Mp3));
+        assert!(fs_err::metadata(&target).unwrap().len() > 0);
+    }
+}
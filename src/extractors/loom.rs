@@ -0,0 +1,149 @@
+use async_trait::async_trait;
+use chrono::Duration;
+use reqwest::Client;
+use serde::Deserialize;
+use url::Url;
+
+use super::{AudioFormat, AudioInfo, DownloadMethod, MediaExtractor};
+use crate::Result;
+
+/// Loom (loom.com/share) video extractor
+pub struct LoomExtractor {
+    client: Client,
+}
+
+#[derive(Debug, Deserialize)]
+struct LoomOembed {
+    title: Option<String>,
+    /// Length in seconds
+    duration: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LoomMediaUrl {
+    url: Option<String>,
+}
+
+impl LoomExtractor {
+    pub fn new() -> Self {
+        Self::with_client(Client::new())
+    }
+
+    /// Create an extractor that shares an existing HTTP client
+    pub fn with_client(client: Client) -> Self {
+        Self { client }
+    }
+
+    /// Video id from a share or embed URL
+    fn video_id(url: &str) -> Option<String> {
+        let parsed = Url::parse(url).ok()?;
+        if !matches!(parsed.host_str()?, "loom.com" | "www.loom.com") {
+            return None;
+        }
+
+        let mut segments = parsed.path_segments()?.filter(|s| !s.is_empty());
+        match (segments.next(), segments.next()) {
+            (Some("share" | "embed"), Some(slug)) => {
+                // Share links may carry the title before the id: /share/Demo-title-<id>
+                let id = slug.rsplit('-').next()?;
+                (id.len() >= 16 && id.chars().all(|c| c.is_ascii_hexdigit())).then(|| id.to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// Signed media URL for a video (an MP4, or an HLS playlist for newer recordings)
+    async fn media_url(&self, id: &str) -> Result<String> {
+        for endpoint in ["transcoded-url", "raw-url"] {
+            let api_url = format!("https://www.loom.com/api/campaigns/sessions/{}/{}", id, endpoint);
+            let response = self.client.post(&api_url).json(&serde_json::json!({})).send().await?;
+            if !response.status().is_success() {
+                tracing::debug!("Loom {} request failed: HTTP {}", endpoint, response.status());
+                continue;
+            }
+
+            if let Some(url) = response.json::<LoomMediaUrl>().await?.url {
+                return Ok(url);
+            }
+        }
+
+        anyhow::bail!("Loom video {} not found, or it is private or password protected", id)
+    }
+}
+
+#[async_trait]
+impl MediaExtractor for LoomExtractor {
+    async fn extract_audio_info(&self, url: &str) -> Result<AudioInfo> {
+        let id = Self::video_id(url).ok_or_else(|| anyhow::anyhow!("Not a Loom video URL: {}", url))?;
+
+        let oembed_url = format!(
+            "https://www.loom.com/v1/oembed?url={}",
+            urlencoding::encode(&format!("https://www.loom.com/share/{}", id))
+        );
+        let oembed = match self.client.get(&oembed_url).send().await {
+            Ok(response) if response.status().is_success() => response.json::<LoomOembed>().await.ok(),
+            _ => None,
+        };
+
+        let media_url = self.media_url(&id).await?;
+        let extension = Url::parse(&media_url)
+            .ok()
+            .and_then(|parsed| parsed.path().rsplit_once('.').map(|(_, ext)| ext.to_lowercase()));
+        let is_playlist = extension.as_deref() == Some("m3u8");
+
+        Ok(AudioInfo {
+            download_url: media_url,
+            // Playlists are captured by ffmpeg like any other HLS stream
+            download_method: if is_playlist {
+                DownloadMethod::Stream
+            } else {
+                DownloadMethod::DirectUrl
+            },
+            duration: oembed
+                .as_ref()
+                .and_then(|o| o.duration)
+                .map(|seconds| Duration::milliseconds((seconds * 1000.0) as i64)),
+            title: oembed.and_then(|o| o.title),
+            format: extension
+                .as_deref()
+                .and_then(AudioFormat::from_extension)
+                .unwrap_or(AudioFormat::M4a),
+            sample_rate: None,
+            file_size: None,
+            original_url: url.to_string(),
+            chapters: Vec::new(),
+        })
+    }
+
+    fn supports_url(&self, url: &str) -> bool {
+        Self::video_id(url).is_some()
+    }
+
+    fn platform_name(&self) -> &'static str {
+        "Loom"
+    }
+}
+
+impl Default for LoomExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_video_id() {
+        let id = "0281766fa2d04bb788eaf19e65135184";
+        assert_eq!(LoomExtractor::video_id(&format!("https://www.loom.com/share/{}", id)), Some(id.to_string()));
+        assert_eq!(
+            LoomExtractor::video_id(&format!("https://www.loom.com/share/Q3-roadmap-demo-{}?sid=abc", id)),
+            Some(id.to_string())
+        );
+        assert_eq!(LoomExtractor::video_id(&format!("https://loom.com/embed/{}", id)), Some(id.to_string()));
+        assert_eq!(LoomExtractor::video_id("https://www.loom.com/looms/videos"), None);
+        assert_eq!(LoomExtractor::video_id(&format!("https://example.com/share/{}", id)), None);
+    }
+}
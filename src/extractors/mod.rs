@@ -1,22 +1,51 @@
+#[cfg(feature = "pipeline")]
 use async_trait::async_trait;
 use chrono::Duration;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "pipeline")]
 use std::path::PathBuf;
 use url::Url;
 
+// Only the audio description types are needed to read existing transcripts
+#[cfg(feature = "pipeline")]
 pub mod youtube;
+#[cfg(feature = "pipeline")]
 pub mod twitter;
+#[cfg(feature = "pipeline")]
+pub mod cloud_drive;
+#[cfg(feature = "pipeline")]
+pub mod cookies;
+#[cfg(feature = "pipeline")]
+pub mod fediverse;
+#[cfg(feature = "pipeline")]
+pub mod archive_org;
+#[cfg(feature = "pipeline")]
+pub mod loom;
+#[cfg(feature = "pipeline")]
+pub mod plugin;
+#[cfg(feature = "pipeline")]
+pub mod wistia;
+#[cfg(feature = "pipeline")]
 pub mod direct;
+#[cfg(feature = "pipeline")]
+pub mod live;
+#[cfg(feature = "pipeline")]
 pub mod local;
+#[cfg(feature = "pipeline")]
+pub mod s3;
 
 use crate::Result;
 
 /// Information about extracted audio
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AudioInfo {
-    /// Direct download URL for the audio
+    /// Where the audio comes from: a URL, or a local path for `LocalFile`
     pub download_url: String,
     
+    /// How `download_url` is fetched
+    #[serde(default)]
+    pub download_method: DownloadMethod,
+    
     /// Duration of the audio if available
     pub duration: Option<Duration>,
     
@@ -34,6 +63,40 @@ pub struct AudioInfo {
     
     /// Original URL that was processed
     pub original_url: String,
+    
+    /// Chapter markers the source provides (YouTube)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chapters: Vec<Chapter>,
+}
+
+/// A titled section of the media
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Chapter {
+    pub title: String,
+    
+    /// Start time in seconds
+    pub start_time: f64,
+    
+    /// End time in seconds, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<f64>,
+}
+
+/// How the audio behind an [`AudioInfo`] is fetched
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadMethod {
+    /// Plain HTTP download of the audio file
+    #[default]
+    DirectUrl,
+    /// yt-dlp downloads the page URL itself
+    YtDlp,
+    /// Copied or converted from a file on disk
+    LocalFile,
+    /// HLS/DASH stream captured with ffmpeg
+    Stream,
+    /// An extractor plugin's download command fetches the page URL
+    Plugin,
 }
 
 /// Supported audio formats
@@ -85,6 +148,7 @@ impl AudioFormat {
 }
 
 /// Trait for extracting audio from different platforms
+#[cfg(feature = "pipeline")]
 #[async_trait]
 pub trait MediaExtractor: Send + Sync {
     /// Extract audio information from a URL
@@ -94,7 +158,7 @@ pub trait MediaExtractor: Send + Sync {
     fn supports_url(&self, url: &str) -> bool;
     
     /// Get the name of this platform
-    fn platform_name(&self) -> &'static str;
+    fn platform_name(&self) -> &str;
     
     /// Download audio to a temporary file
     async fn download_audio(&self, audio_info: &AudioInfo, output_path: &PathBuf) -> Result<()> {
@@ -112,21 +176,56 @@ pub trait MediaExtractor: Send + Sync {
 }
 
 /// Registry for managing multiple extractors
+#[cfg(feature = "pipeline")]
 pub struct ExtractorRegistry {
     extractors: Vec<Box<dyn MediaExtractor>>,
 }
 
+#[cfg(feature = "pipeline")]
 impl ExtractorRegistry {
     /// Create a new registry with default extractors
     pub fn new() -> Self {
+        Self::with_client(reqwest::Client::new())
+    }
+    
+    /// Create a registry whose HTTP-based extractors share the given client
+    pub fn with_client(client: reqwest::Client) -> Self {
+        Self::from_config(client, &crate::config::Config::default())
+    }
+    
+    /// Create a registry whose extractors use the configured cookies, proxy and yt-dlp settings
+    ///
+    /// The client is expected to be built with the same proxy (see [`crate::http::build_client`]).
+    pub fn from_config(client: reqwest::Client, config: &crate::config::Config) -> Self {
         let mut registry = Self {
             extractors: Vec::new(),
         };
+        let cookies = &config.cookies;
+        let yt_dlp = config.yt_dlp.binary();
+        let audio_format = config.yt_dlp.format();
+        let audio_quality = &config.yt_dlp.audio_quality;
         
         // Register default extractors
-        registry.register(Box::new(youtube::YoutubeExtractor::new()));
-        registry.register(Box::new(twitter::TwitterExtractor::new()));
-        registry.register(Box::new(direct::DirectExtractor::new()));
+        registry.register(Box::new(
+            youtube::YoutubeExtractor::new()
+                .with_yt_dlp(yt_dlp.clone())
+                .with_audio(audio_format, audio_quality.clone())
+                .with_cookies(cookies.clone())
+                .with_network(config.network.clone()),
+        ));
+        registry.register(Box::new(
+            twitter::TwitterExtractor::new()
+                .with_yt_dlp(yt_dlp)
+                .with_audio(audio_format, audio_quality.clone())
+                .with_cookies(cookies.clone())
+                .with_network(config.network.clone()),
+        ));
+        registry.register(Box::new(cloud_drive::CloudDriveExtractor::with_client(client.clone())));
+        registry.register(Box::new(fediverse::FediverseExtractor::with_client(client.clone())));
+        registry.register(Box::new(archive_org::ArchiveOrgExtractor::with_client(client.clone())));
+        registry.register(Box::new(loom::LoomExtractor::with_client(client.clone())));
+        registry.register(Box::new(wistia::WistiaExtractor::with_client(client.clone())));
+        registry.register(Box::new(direct::DirectExtractor::with_client(client).with_cookies(cookies.clone())));
         
         registry
     }
@@ -141,6 +240,27 @@ impl ExtractorRegistry {
         self.extractors.push(extractor);
     }
     
+    /// Register configured extractor plugins ahead of the built-in extractors
+    pub fn register_plugins(&mut self, plugins: &[plugin::PluginConfig]) -> Result<()> {
+        for (index, config) in plugins.iter().enumerate() {
+            let extractor = plugin::PluginExtractor::new(config.clone())?;
+            self.extractors.insert(index, Box::new(extractor));
+        }
+        Ok(())
+    }
+    
+    /// Download audio with the extractor that produced it (yt-dlp, plugin and local file sources)
+    pub async fn download_audio(&self, audio_info: &AudioInfo, output_path: &PathBuf) -> Result<()> {
+        if audio_info.download_method == DownloadMethod::LocalFile {
+            return Self::create_local_extractor().download_audio(audio_info, output_path).await;
+        }
+        
+        let extractor = self
+            .find_extractor(&audio_info.original_url)
+            .ok_or_else(|| anyhow::anyhow!("No extractor found for URL: {}", audio_info.original_url))?;
+        extractor.download_audio(audio_info, output_path).await
+    }
+    
     /// Find an extractor that supports the given URL
     pub fn find_extractor(&self, url: &str) -> Option<&dyn MediaExtractor> {
         self.extractors
@@ -150,7 +270,7 @@ impl ExtractorRegistry {
     }
     
     /// List all supported platforms
-    pub fn list_platforms(&self) -> Vec<&'static str> {
+    pub fn list_platforms(&self) -> Vec<&str> {
         self.extractors
             .iter()
             .map(|extractor| extractor.platform_name())
@@ -160,7 +280,7 @@ impl ExtractorRegistry {
     /// Check if input is a local file path
     pub fn is_local_file(&self, input: &str) -> bool {
         // First, check if it's clearly a URL
-        if input.starts_with("http://") || input.starts_with("https://") {
+        if input.starts_with("http://") || input.starts_with("https://") || input.starts_with("s3://") {
             return false;
         }
         
@@ -195,6 +315,7 @@ impl ExtractorRegistry {
     }
 }
 
+#[cfg(feature = "pipeline")]
 impl Default for ExtractorRegistry {
     fn default() -> Self {
         Self::new()
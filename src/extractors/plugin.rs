@@ -0,0 +1,247 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::Duration;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+
+use super::{AudioFormat, AudioInfo, DownloadMethod, MediaExtractor};
+use crate::utils::command_from_template;
+use crate::Result;
+
+/// Extractors added without recompiling
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExtractorsConfig {
+    /// External extractors, tried before the built-in ones
+    pub plugins: Vec<PluginConfig>,
+}
+
+/// An extractor implemented by an external program
+///
+/// With `command`, the program speaks JSON: it gets `{"action": "extract", "url": ...}` on
+/// stdin and prints the audio details; if those have no `download_url` it is run again with
+/// `{"action": "download", "url": ..., "output": ...}` and writes the audio itself.
+/// With only `download`, that command template is run to fetch the audio.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginConfig {
+    /// Platform name shown by `platforms`
+    pub name: String,
+
+    /// Regular expressions; URLs matching any of them go to this plugin
+    pub patterns: Vec<String>,
+
+    /// Executable (plus arguments) speaking the JSON protocol
+    #[serde(default)]
+    pub command: Option<String>,
+
+    /// Download command with `{url}` and `{output}` placeholders
+    #[serde(default)]
+    pub download: Option<String>,
+
+    /// Audio format the download template produces
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// Audio details printed by a JSON plugin
+#[derive(Debug, Deserialize)]
+struct PluginResponse {
+    download_url: Option<String>,
+    title: Option<String>,
+    /// Length in seconds
+    duration: Option<f64>,
+    format: Option<String>,
+    sample_rate: Option<u32>,
+    file_size: Option<u64>,
+}
+
+/// Extractor backed by a configured plugin
+pub struct PluginExtractor {
+    config: PluginConfig,
+    patterns: Vec<Regex>,
+}
+
+impl PluginExtractor {
+    pub fn new(config: PluginConfig) -> Result<Self> {
+        if config.name.is_empty() || config.name.contains('/') {
+            anyhow::bail!("Extractor plugin names must be non-empty and contain no '/': '{}'", config.name);
+        }
+        if config.command.is_none() && config.download.is_none() {
+            anyhow::bail!("Extractor plugin '{}' needs a command or a download template", config.name);
+        }
+
+        let patterns = config
+            .patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern)
+                    .with_context(|| format!("Invalid pattern for extractor plugin '{}': {}", config.name, pattern))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { config, patterns })
+    }
+
+    fn format(&self, format: Option<&str>) -> AudioFormat {
+        format
+            .or(self.config.format.as_deref())
+            .and_then(AudioFormat::from_extension)
+            .unwrap_or(AudioFormat::Mp3)
+    }
+}
+
+/// Run a JSON plugin with one request, returning its stdout
+async fn run_json(config: &PluginConfig, line: &str, request: serde_json::Value) -> Result<String> {
+    let mut child = command_from_template(line, &[])?
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run extractor plugin '{}'", config.name))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(request.to_string().as_bytes()).await?;
+    }
+
+    let output = child.wait_with_output().await?;
+    crate::logs::check(&config.name, &output, &format!("Extractor plugin '{}' failed", config.name))?;
+
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Have a plugin fetch the audio for `url` into `output_path`
+pub async fn download(config: &PluginConfig, url: &str, output_path: &Path) -> Result<()> {
+    let output = output_path.to_string_lossy();
+
+    if let Some(command) = &config.command {
+        let request = serde_json::json!({"action": "download", "url": url, "output": output});
+        run_json(config, command, request).await?;
+    } else if let Some(template) = &config.download {
+        let result = command_from_template(template, &[("{url}", url), ("{output}", &output)])?
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()
+            .await
+            .with_context(|| format!("Failed to run extractor plugin '{}'", config.name))?;
+
+        crate::logs::check(&config.name, &result, &format!("Extractor plugin '{}' failed", config.name))?;
+    }
+
+    if !output_path.exists() {
+        anyhow::bail!("Extractor plugin '{}' did not write {}", config.name, output);
+    }
+
+    Ok(())
+}
+
+#[async_trait]
+impl MediaExtractor for PluginExtractor {
+    async fn extract_audio_info(&self, url: &str) -> Result<AudioInfo> {
+        let Some(command) = &self.config.command else {
+            // Template plugins only download, so there is nothing to ask up front
+            return Ok(AudioInfo {
+                download_url: url.to_string(),
+                download_method: DownloadMethod::Plugin,
+                duration: None,
+                title: None,
+                format: self.format(None),
+                sample_rate: None,
+                file_size: None,
+                original_url: url.to_string(),
+                chapters: Vec::new(),
+            });
+        };
+
+        let request = serde_json::json!({"action": "extract", "url": url});
+        let stdout = run_json(&self.config, command, request).await?;
+        let response: PluginResponse = serde_json::from_str(&stdout)
+            .with_context(|| format!("Extractor plugin '{}' printed invalid JSON", self.config.name))?;
+
+        let (download_url, download_method) = match response.download_url {
+            Some(download_url) => (download_url, DownloadMethod::DirectUrl),
+            None => (url.to_string(), DownloadMethod::Plugin),
+        };
+
+        Ok(AudioInfo {
+            download_url,
+            download_method,
+            duration: response
+                .duration
+                .map(|seconds| Duration::milliseconds((seconds * 1000.0) as i64)),
+            title: response.title,
+            format: self.format(response.format.as_deref()),
+            sample_rate: response.sample_rate,
+            file_size: response.file_size,
+            original_url: url.to_string(),
+            chapters: Vec::new(),
+        })
+    }
+
+    fn supports_url(&self, url: &str) -> bool {
+        self.patterns.iter().any(|pattern| pattern.is_match(url))
+    }
+
+    fn platform_name(&self) -> &str {
+        &self.config.name
+    }
+    
+    async fn download_audio(&self, audio_info: &AudioInfo, output_path: &PathBuf) -> Result<()> {
+        download(&self.config, &audio_info.download_url, output_path).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plugin(command: Option<&str>, download: Option<&str>) -> PluginConfig {
+        PluginConfig {
+            name: "Example".to_string(),
+            patterns: vec![r"^https://media\.example\.org/".to_string()],
+            command: command.map(str::to_string),
+            download: download.map(str::to_string),
+            format: Some("m4a".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_validation_and_matching() {
+        assert!(PluginExtractor::new(plugin(None, None)).is_err());
+
+        let extractor = PluginExtractor::new(plugin(None, Some("fetch {url} {output}"))).unwrap();
+        assert!(extractor.supports_url("https://media.example.org/talk/42"));
+        assert!(!extractor.supports_url("https://example.org/talk/42"));
+    }
+
+    #[tokio::test]
+    async fn test_template_plugin_downloads_itself() {
+        let extractor = PluginExtractor::new(plugin(None, Some("fetch {url} {output}"))).unwrap();
+        let info = extractor.extract_audio_info("https://media.example.org/talk/42").await.unwrap();
+        assert_eq!(info.download_method, DownloadMethod::Plugin);
+        assert_eq!(info.download_url, "https://media.example.org/talk/42");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_json_plugin() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("extract.sh");
+        fs_err::write(
+            &script,
+            "#!/bin/sh\nread request\necho '{\"download_url\": \"https://cdn.example.org/a.mp3\", \"title\": \"Talk\", \"duration\": 61.5, \"format\": \"mp3\"}'\n",
+        )
+        .unwrap();
+        let command = format!("sh {}", script.display());
+
+        let extractor = PluginExtractor::new(plugin(Some(&command), None)).unwrap();
+        let info = extractor.extract_audio_info("https://media.example.org/talk/42").await.unwrap();
+        assert_eq!(info.download_url, "https://cdn.example.org/a.mp3");
+        assert_eq!(info.download_method, DownloadMethod::DirectUrl);
+        assert_eq!(info.title.as_deref(), Some("Talk"));
+        assert!(matches!(info.format, AudioFormat::Mp3));
+        assert_eq!(info.duration, Some(Duration::milliseconds(61500)));
+    }
+}
@@ -0,0 +1,75 @@
+use std::path::Path;
+
+use super::{AudioFormat, AudioInfo, DownloadMethod};
+
+/// An audio object that already lives in S3 (`s3://bucket/key`)
+///
+/// Such inputs are handed to AWS Transcribe as-is, so they are neither
+/// downloaded nor uploaded again.
+#[derive(Debug, Clone, PartialEq)]
+pub struct S3Location {
+    pub bucket: String,
+    pub key: String,
+}
+
+impl S3Location {
+    /// Parse an `s3://bucket/key` URI
+    pub fn parse(uri: &str) -> Option<Self> {
+        let rest = uri.strip_prefix("s3://")?;
+        let (bucket, key) = rest.split_once('/')?;
+        if bucket.is_empty() || key.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+        })
+    }
+
+    /// URI of the object as accepted by AWS Transcribe
+    pub fn uri(&self) -> String {
+        format!("s3://{}/{}", self.bucket, self.key)
+    }
+
+    /// Describe the object, guessing the format from the key's extension
+    pub fn audio_info(&self, file_size: Option<u64>) -> AudioInfo {
+        let path = Path::new(&self.key);
+        let format = path
+            .extension()
+            .and_then(|ext| AudioFormat::from_extension(&ext.to_string_lossy()))
+            .unwrap_or(AudioFormat::Mp3);
+
+        AudioInfo {
+            download_url: self.uri(),
+            download_method: DownloadMethod::DirectUrl,
+            duration: None,
+            title: path.file_stem().map(|stem| stem.to_string_lossy().into_owned()),
+            format,
+            sample_rate: None,
+            file_size,
+            original_url: self.uri(),
+            chapters: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let location = S3Location::parse("s3://media-bucket/talks/2024/keynote.m4a").unwrap();
+        assert_eq!(location.bucket, "media-bucket");
+        assert_eq!(location.key, "talks/2024/keynote.m4a");
+
+        let info = location.audio_info(None);
+        assert_eq!(info.title.as_deref(), Some("keynote"));
+        assert_eq!(info.format.as_str(), "m4a");
+
+        assert_eq!(S3Location::parse("s3://media-bucket"), None);
+        assert_eq!(S3Location::parse("s3:///key.mp3"), None);
+        assert_eq!(S3Location::parse("https://example.com/key.mp3"), None);
+    }
+}
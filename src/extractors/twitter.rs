@@ -1,24 +1,61 @@
 use async_trait::async_trait;
 use chrono::Duration;
 use serde_json::Value;
+use std::path::PathBuf;
 use std::process::Stdio;
 use tokio::process::Command;
 
-use super::{AudioFormat, AudioInfo, MediaExtractor};
+use super::cookies::CookiesConfig;
+use super::{AudioFormat, AudioInfo, DownloadMethod, MediaExtractor};
+use crate::http::NetworkConfig;
 use crate::Result;
+use crate::utils::text;
 
 /// Twitter/X audio extractor using yt-dlp
 pub struct TwitterExtractor {
-    yt_dlp_path: String,
+    yt_dlp_path: PathBuf,
+    audio_format: AudioFormat,
+    audio_quality: String,
+    cookies: CookiesConfig,
+    network: NetworkConfig,
 }
 
 impl TwitterExtractor {
     pub fn new() -> Self {
         Self {
-            yt_dlp_path: "yt-dlp".to_string(),
+            yt_dlp_path: PathBuf::from("yt-dlp"),
+            audio_format: AudioFormat::Mp3,
+            audio_quality: "9".to_string(),
+            cookies: CookiesConfig::default(),
+            network: NetworkConfig::default(),
         }
     }
     
+    /// Run a specific yt-dlp binary instead of the one on PATH
+    pub fn with_yt_dlp(mut self, path: PathBuf) -> Self {
+        self.yt_dlp_path = path;
+        self
+    }
+    
+    /// Convert downloads to this format and `--audio-quality` instead of small MP3s
+    pub fn with_audio(mut self, format: AudioFormat, quality: String) -> Self {
+        self.audio_format = format;
+        self.audio_quality = quality;
+        self
+    }
+    
+    /// Pass login cookies to yt-dlp, for gated videos
+    pub fn with_cookies(mut self, cookies: CookiesConfig) -> Self {
+        self.cookies = cookies;
+        self
+    }
+    
+    /// Send yt-dlp through the configured proxy
+    pub fn with_network(mut self, network: NetworkConfig) -> Self {
+        self.network = network;
+        self
+    }
+    
     /// Check if yt-dlp is available
     pub async fn check_availability(&self) -> Result<bool> {
         let output = Command::new(&self.yt_dlp_path)
@@ -31,11 +68,19 @@ impl TwitterExtractor {
         Ok(output.is_ok() && output.unwrap().status.success())
     }
     
+    /// Check if the URL points to a Twitter/X Space rather than a tweet
+    pub fn is_spaces_url(url: &str) -> bool {
+        let url_lower = url.to_lowercase();
+        url_lower.contains("/i/spaces/")
+    }
+    
     /// Get tweet information using yt-dlp
     async fn get_tweet_info(&self, url: &str) -> Result<Value> {
         tracing::debug!("Extracting tweet info for: {}", url);
         
         let output = Command::new(&self.yt_dlp_path)
+            .args(self.cookies.yt_dlp_args())
+            .args(self.network.yt_dlp_args())
             .args([
                 "--dump-json",
                 "--no-playlist",
@@ -46,10 +91,7 @@ impl TwitterExtractor {
             .output()
             .await?;
             
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("yt-dlp failed to extract Twitter content: {}", error);
-        }
+        crate::logs::check("yt-dlp", &output, "yt-dlp failed to extract Twitter content")?;
         
         let json_str = String::from_utf8(output.stdout)?;
         let info: Value = serde_json::from_str(&json_str)?;
@@ -59,16 +101,22 @@ impl TwitterExtractor {
     
     /// Download audio directly using yt-dlp (similar to YouTube approach)
     pub async fn download_audio_direct(&self, url: &str, output_path: &std::path::Path) -> Result<AudioFormat> {
+        if Self::is_spaces_url(url) {
+            return self.download_spaces_audio(url, output_path).await;
+        }
+        
         tracing::debug!("Downloading Twitter audio directly for: {}", url);
         
         let output = Command::new(&self.yt_dlp_path)
+            .args(self.cookies.yt_dlp_args())
+            .args(self.network.yt_dlp_args())
             .args([
                 // Output to specific file
                 "--output", &output_path.to_string_lossy(),
-                // Extract audio in the most efficient format for transcription
+                // Extract audio; the defaults (mp3, quality 9) are smallest and still transcribe well
                 "--extract-audio",
-                "--audio-format", "mp3",
-                "--audio-quality", "9",  // Lowest quality for speed (still good for transcription)
+                "--audio-format", self.audio_format.as_str(),
+                "--audio-quality", &self.audio_quality,
                 // Better Twitter audio selection
                 "--format", "hls-audio-32000-Audio/bestaudio[ext=m4a]/bestaudio[ext=mp4]/bestaudio/best[height<=720]",
                 "--no-playlist",
@@ -82,7 +130,7 @@ impl TwitterExtractor {
             .output()
             .await?;
             
-        if !output.status.success() {
+        if let Err(e) = crate::logs::check("yt-dlp", &output, "Failed to download audio from Twitter") {
             let error = String::from_utf8_lossy(&output.stderr);
             
             // Check for common Twitter errors
@@ -94,10 +142,49 @@ impl TwitterExtractor {
                 anyhow::bail!("Tweet not found or has been deleted");
             }
             
-            anyhow::bail!("Failed to download audio from Twitter: {}", error);
+            return Err(e);
         }
         
-        Ok(AudioFormat::Mp3) // We're forcing MP3 conversion for speed
+        Ok(self.audio_format)
+    }
+    
+    /// Download a recorded Space, which is served as an audio-only HLS playlist
+    async fn download_spaces_audio(&self, url: &str, output_path: &std::path::Path) -> Result<AudioFormat> {
+        tracing::debug!("Downloading Twitter/X Space audio for: {}", url);
+        
+        let output = Command::new(&self.yt_dlp_path)
+            .args(self.cookies.yt_dlp_args())
+            .args(self.network.yt_dlp_args())
+            .args([
+                "--output", &output_path.to_string_lossy(),
+                "--extract-audio",
+                "--audio-format", self.audio_format.as_str(),
+                "--audio-quality", &self.audio_quality,
+                // Spaces only have audio renditions, so no video format selection
+                "--format", "bestaudio/best",
+                // Spaces recordings run for hours; fetch many HLS fragments at once
+                "--concurrent-fragments", "8",
+                "--newline",
+                url,
+            ])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .await?;
+            
+        if let Err(e) = crate::logs::check("yt-dlp", &output, "Failed to download Twitter/X Space") {
+            let error = String::from_utf8_lossy(&output.stderr);
+            
+            if error.contains("not available") || error.contains("no recording") {
+                anyhow::bail!("This Space has no recording available (it may not have been recorded, or is still live)");
+            } else if error.contains("not found") || error.contains("404") {
+                anyhow::bail!("Space not found or has been deleted");
+            }
+            
+            return Err(e);
+        }
+        
+        Ok(self.audio_format)
     }
 }
 
@@ -112,38 +199,39 @@ impl MediaExtractor for TwitterExtractor {
         // Get tweet information
         let info = self.get_tweet_info(url).await?;
         
+        // Spaces carry their name in the title; tweets carry the text in the description
+        let is_spaces = Self::is_spaces_url(url);
+        if is_spaces {
+            tracing::info!("Detected Twitter/X Space; use --speaker-labels to tell the hosts and speakers apart");
+        }
+        let (primary, fallback) = if is_spaces { ("title", "description") } else { ("description", "title") };
+        
         // Extract metadata
-        let title = info["description"]
+        let title = info[primary]
             .as_str()
-            .or_else(|| info["title"].as_str())
+            .or_else(|| info[fallback].as_str())
             .map(|s| {
                 // Truncate long descriptions and clean up
-                let cleaned = s.replace('\n', " ").trim().to_string();
-                if cleaned.len() > 100 {
-                    format!("{}...", &cleaned[..97])
-                } else {
-                    cleaned
-                }
+                let cleaned = s.replace('\n', " ");
+                text::truncate_to_width(cleaned.trim(), 100)
             });
             
         let duration_seconds = info["duration"].as_f64();
         let duration = duration_seconds.map(|d| Duration::seconds(d as i64));
         
-        // For Twitter, we'll use direct download, so we use a placeholder URL
-        // The actual download will be handled by download_audio_direct()
-        let download_url = format!("twitter-dlp://{}", url);
-        
-        // We'll always convert to MP3 for speed and compatibility
-        let format = AudioFormat::Mp3;
+        // yt-dlp converts to the configured format (MP3 unless set otherwise)
+        let format = self.audio_format;
         
         Ok(AudioInfo {
-            download_url,
+            download_url: url.to_string(),
+            download_method: DownloadMethod::YtDlp,
             duration,
             title,
             format,
             sample_rate: Some(44100),
             file_size: None, // Will be determined during download
             original_url: url.to_string(),
+            chapters: Vec::new(),
         })
     }
     
@@ -160,10 +248,54 @@ impl MediaExtractor for TwitterExtractor {
     fn platform_name(&self) -> &'static str {
         "Twitter/X"
     }
+    
+    async fn download_audio(&self, audio_info: &AudioInfo, output_path: &PathBuf) -> Result<()> {
+        self.download_audio_direct(&audio_info.download_url, output_path).await?;
+        Ok(())
+    }
 }
 
 impl Default for TwitterExtractor {
     fn default() -> Self {
         Self::new()
     }
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_is_spaces_url() {
+        let extractor = TwitterExtractor::new();
+        for url in ["https://x.com/i/spaces/1YqGoDdLqXoKv", "https://twitter.com/i/spaces/1YqGoDdLqXoKv?s=20"] {
+            assert!(extractor.supports_url(url));
+            assert!(TwitterExtractor::is_spaces_url(url), "{}", url);
+        }
+        
+        let status = "https://x.com/NASA/status/1769431526458511706";
+        assert!(extractor.supports_url(status));
+        assert!(!TwitterExtractor::is_spaces_url(status));
+    }
+    
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_space_without_recording() {
+        use std::os::unix::fs::PermissionsExt;
+        
+        // Stands in for yt-dlp, keeping the arguments it was run with
+        let dir = tempfile::tempdir().unwrap();
+        let args = dir.path().join("args");
+        let yt_dlp = dir.path().join("yt-dlp");
+        let script = format!("#!/bin/sh\necho \"$@\" > {}\necho 'ERROR: This Space has no recording' >&2\nexit 1\n", args.display());
+        fs_err::write(&yt_dlp, script).unwrap();
+        fs_err::set_permissions(&yt_dlp, std::fs::Permissions::from_mode(0o755)).unwrap();
+        
+        let extractor = TwitterExtractor::new().with_yt_dlp(yt_dlp);
+        let error = extractor
+            .download_audio_direct("https://x.com/i/spaces/1YqGoDdLqXoKv", &dir.path().join("space.mp3"))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("no recording available"), "{}", error);
+        assert!(fs_err::read_to_string(&args).unwrap().contains("--format bestaudio/best"));
+    }
+}
@@ -0,0 +1,172 @@
+use async_trait::async_trait;
+use chrono::Duration;
+use reqwest::Client;
+use serde::Deserialize;
+use url::Url;
+
+use super::{AudioFormat, AudioInfo, DownloadMethod, MediaExtractor};
+use crate::Result;
+
+/// Wistia embed and media page extractor
+pub struct WistiaExtractor {
+    client: Client,
+}
+
+#[derive(Debug, Deserialize)]
+struct WistiaResponse {
+    media: WistiaMedia,
+}
+
+#[derive(Debug, Deserialize)]
+struct WistiaMedia {
+    name: Option<String>,
+    /// Length in seconds
+    duration: Option<f64>,
+    #[serde(default)]
+    assets: Vec<WistiaAsset>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct WistiaAsset {
+    #[serde(rename = "type")]
+    kind: String,
+    url: String,
+    size: Option<u64>,
+    container: Option<String>,
+}
+
+impl WistiaExtractor {
+    pub fn new() -> Self {
+        Self::with_client(Client::new())
+    }
+
+    /// Create an extractor that shares an existing HTTP client
+    pub fn with_client(client: Client) -> Self {
+        Self { client }
+    }
+
+    /// Media id from an iframe embed, embed/medias or account media page URL
+    fn media_id(url: &str) -> Option<String> {
+        let parsed = Url::parse(url).ok()?;
+        let host = parsed.host_str()?;
+        if !(host.ends_with("wistia.com") || host.ends_with("wistia.net")) {
+            return None;
+        }
+
+        let segments: Vec<&str> = parsed.path_segments()?.filter(|s| !s.is_empty()).collect();
+        let id = match segments.as_slice() {
+            ["embed", "iframe" | "medias", id, ..] => id,
+            ["medias", id, ..] => id,
+            _ => return None,
+        };
+
+        // Embed script URLs look like /embed/medias/<id>.jsonp
+        let id = id.split('.').next()?;
+        (!id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric())).then(|| id.to_string())
+    }
+}
+
+/// The asset to download: audio-only if there is one, otherwise the smallest MP4 rendition
+fn pick_asset(assets: &[WistiaAsset]) -> Option<&WistiaAsset> {
+    let container = |asset: &WistiaAsset| asset.container.as_deref().unwrap_or("").to_lowercase();
+
+    assets
+        .iter()
+        .find(|asset| asset.kind.ends_with("_audio") || container(asset) == "mp3")
+        .or_else(|| {
+            assets
+                .iter()
+                .filter(|asset| asset.kind.ends_with("_video") && container(asset) == "mp4")
+                .min_by_key(|asset| asset.size.unwrap_or(u64::MAX))
+        })
+        .or_else(|| assets.iter().find(|asset| asset.kind == "original"))
+}
+
+#[async_trait]
+impl MediaExtractor for WistiaExtractor {
+    async fn extract_audio_info(&self, url: &str) -> Result<AudioInfo> {
+        let id = Self::media_id(url).ok_or_else(|| anyhow::anyhow!("Not a Wistia media URL: {}", url))?;
+
+        let api_url = format!("https://fast.wistia.com/embed/medias/{}.json", id);
+        let response = self.client.get(&api_url).send().await?;
+        if !response.status().is_success() {
+            anyhow::bail!("Wistia media {} not found: HTTP {}", id, response.status());
+        }
+        let media = response.json::<WistiaResponse>().await?.media;
+
+        let asset = pick_asset(&media.assets)
+            .ok_or_else(|| anyhow::anyhow!("Wistia media {} has no downloadable audio or video", id))?;
+
+        let format = match asset.container.as_deref() {
+            Some(container) => AudioFormat::from_extension(container).unwrap_or(AudioFormat::M4a),
+            None => AudioFormat::M4a,
+        };
+
+        Ok(AudioInfo {
+            download_url: asset.url.clone(),
+            download_method: DownloadMethod::DirectUrl,
+            duration: media
+                .duration
+                .map(|seconds| Duration::milliseconds((seconds * 1000.0) as i64)),
+            title: media.name,
+            format,
+            sample_rate: None,
+            file_size: asset.size,
+            original_url: url.to_string(),
+            chapters: Vec::new(),
+        })
+    }
+
+    fn supports_url(&self, url: &str) -> bool {
+        Self::media_id(url).is_some()
+    }
+
+    fn platform_name(&self) -> &'static str {
+        "Wistia"
+    }
+}
+
+impl Default for WistiaExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_media_id() {
+        for url in [
+            "https://fast.wistia.net/embed/iframe/j38ihh83m5?videoFoam=true",
+            "https://fast.wistia.com/embed/medias/j38ihh83m5.jsonp",
+            "https://acme.wistia.com/medias/j38ihh83m5",
+        ] {
+            assert_eq!(WistiaExtractor::media_id(url), Some("j38ihh83m5".to_string()), "{}", url);
+        }
+        assert_eq!(WistiaExtractor::media_id("https://wistia.com/pricing"), None);
+        assert_eq!(WistiaExtractor::media_id("https://example.com/medias/j38ihh83m5"), None);
+    }
+
+    #[test]
+    fn test_picks_smallest_mp4() {
+        let assets: Vec<WistiaAsset> = serde_json::from_str(
+            r#"[
+                {"type": "original", "url": "https://embed.wistia.com/deliveries/a.bin", "size": 90000000, "container": "mp4"},
+                {"type": "hd_mp4_video", "url": "https://embed.wistia.com/deliveries/b.bin", "size": 40000000, "container": "mp4"},
+                {"type": "iphone_video", "url": "https://embed.wistia.com/deliveries/c.bin", "size": 8000000, "container": "mp4"},
+                {"type": "hls_video", "url": "https://embed.wistia.com/deliveries/d.bin", "size": 1000, "container": "m3u8"},
+                {"type": "still_image", "url": "https://embed.wistia.com/deliveries/e.bin", "size": 100, "container": "jpg"}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(pick_asset(&assets).unwrap().kind, "iphone_video");
+
+        let audio = serde_json::from_str::<Vec<WistiaAsset>>(
+            r#"[{"type": "mp3_audio", "url": "https://embed.wistia.com/deliveries/f.bin", "container": "mp3"}]"#,
+        )
+        .unwrap();
+        assert_eq!(pick_asset(&audio).unwrap().kind, "mp3_audio");
+    }
+}
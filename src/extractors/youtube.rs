@@ -1,24 +1,60 @@
 use async_trait::async_trait;
 use chrono::Duration;
 use serde_json::Value;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use tokio::process::Command;
 
-use super::{AudioFormat, AudioInfo, MediaExtractor};
+use super::cookies::CookiesConfig;
+use super::{AudioFormat, AudioInfo, Chapter, DownloadMethod, MediaExtractor};
+use crate::http::NetworkConfig;
 use crate::Result;
 
 /// YouTube audio extractor using yt-dlp
 pub struct YoutubeExtractor {
-    yt_dlp_path: String,
+    yt_dlp_path: PathBuf,
+    audio_format: AudioFormat,
+    audio_quality: String,
+    cookies: CookiesConfig,
+    network: NetworkConfig,
 }
 
 impl YoutubeExtractor {
     pub fn new() -> Self {
         Self {
-            yt_dlp_path: "yt-dlp".to_string(),
+            yt_dlp_path: PathBuf::from("yt-dlp"),
+            audio_format: AudioFormat::Mp3,
+            audio_quality: "9".to_string(),
+            cookies: CookiesConfig::default(),
+            network: NetworkConfig::default(),
         }
     }
     
+    /// Run a specific yt-dlp binary instead of the one on PATH
+    pub fn with_yt_dlp(mut self, path: PathBuf) -> Self {
+        self.yt_dlp_path = path;
+        self
+    }
+    
+    /// Convert downloads to this format and `--audio-quality` instead of small MP3s
+    pub fn with_audio(mut self, format: AudioFormat, quality: String) -> Self {
+        self.audio_format = format;
+        self.audio_quality = quality;
+        self
+    }
+    
+    /// Pass login cookies to yt-dlp, for gated videos
+    pub fn with_cookies(mut self, cookies: CookiesConfig) -> Self {
+        self.cookies = cookies;
+        self
+    }
+    
+    /// Send yt-dlp through the configured proxy
+    pub fn with_network(mut self, network: NetworkConfig) -> Self {
+        self.network = network;
+        self
+    }
+    
     /// Check if yt-dlp is available
     pub async fn check_availability(&self) -> Result<bool> {
         let output = Command::new(&self.yt_dlp_path)
@@ -36,6 +72,8 @@ impl YoutubeExtractor {
         tracing::debug!("Extracting video info for: {}", url);
         
         let output = Command::new(&self.yt_dlp_path)
+            .args(self.cookies.yt_dlp_args())
+            .args(self.network.yt_dlp_args())
             .args([
                 "--dump-json",
                 "--no-playlist",
@@ -46,10 +84,7 @@ impl YoutubeExtractor {
             .output()
             .await?;
             
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("yt-dlp failed: {}", error);
-        }
+        crate::logs::check("yt-dlp", &output, "yt-dlp failed")?;
         
         let json_str = String::from_utf8(output.stdout)?;
         let info: Value = serde_json::from_str(&json_str)?;
@@ -57,18 +92,116 @@ impl YoutubeExtractor {
         Ok(info)
     }
     
+    /// Audio details from yt-dlp's video information
+    fn audio_info(&self, url: &str, info: &Value) -> AudioInfo {
+        // Extract metadata
+        let title = info["title"].as_str().map(|s| s.to_string());
+        let duration_seconds = info["duration"].as_f64();
+        let duration = duration_seconds.map(|d| Duration::seconds(d as i64));
+        
+        // yt-dlp converts to the configured format (MP3 unless set otherwise)
+        let format = self.audio_format;
+        
+        AudioInfo {
+            download_url: url.to_string(),
+            download_method: DownloadMethod::YtDlp,
+            duration,
+            title,
+            format,
+            sample_rate: Some(44100), // YouTube typically uses 44.1kHz  
+            file_size: None, // Will be determined during download
+            original_url: url.to_string(),
+            chapters: chapters_of(info),
+        }
+    }
+    
+    /// Download the uploader's own subtitles as WebVTT, if the video has them in the wanted language
+    ///
+    /// Without a requested language the video's own language is used. Automatic captions are
+    /// never used; they are no better than a transcription.
+    pub async fn download_captions(&self, url: &str, language: Option<&str>, dir: &Path) -> Result<Option<Captions>> {
+        let info = self.get_video_info(url).await?;
+        
+        let available: Vec<&str> = info["subtitles"]
+            .as_object()
+            .map(|tracks| tracks.keys().map(String::as_str).filter(|lang| *lang != "live_chat").collect())
+            .unwrap_or_default();
+        let wanted = language.or(info["language"].as_str());
+        let Some(track) = wanted.and_then(|wanted| pick_caption_track(&available, wanted)) else {
+            tracing::info!("No uploaded subtitles in {} (available: {})", wanted.unwrap_or("an unknown language"), available.join(", "));
+            return Ok(None);
+        };
+        
+        tracing::info!("Downloading uploaded {} subtitles", track);
+        let name = format!("captions_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+        let output = Command::new(&self.yt_dlp_path)
+            .args(self.cookies.yt_dlp_args())
+            .args(self.network.yt_dlp_args())
+            .args([
+                "--skip-download",
+                "--write-subs",
+                "--sub-langs", track,
+                "--sub-format", "vtt/best",
+                "--convert-subs", "vtt",
+                "--no-playlist",
+                "--output", &dir.join(&name).to_string_lossy(),
+                url,
+            ])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .await?;
+        
+        crate::logs::check("yt-dlp", &output, "Failed to download subtitles")?;
+        
+        // yt-dlp names the file <output>.<language>.vtt
+        let path = dir.join(format!("{}.{}.vtt", name, track));
+        if !path.exists() {
+            anyhow::bail!("yt-dlp did not write the {} subtitles", track);
+        }
+        
+        Ok(Some(Captions {
+            audio_info: self.audio_info(url, &info),
+            language: track.to_string(),
+            path,
+        }))
+    }
+    
+    /// Video URLs of a playlist, in playlist order, without fetching each video
+    pub async fn playlist_entries(&self, url: &str) -> Result<Vec<String>> {
+        let output = Command::new(&self.yt_dlp_path)
+            .args(self.cookies.yt_dlp_args())
+            .args(self.network.yt_dlp_args())
+            .args(["--flat-playlist", "--dump-json", url])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .await?;
+        
+        crate::logs::check("yt-dlp", &output, "Failed to list playlist")?;
+        
+        let entries = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            .filter_map(|entry| entry["url"].as_str().or(entry["webpage_url"].as_str()).map(str::to_string))
+            .collect();
+        Ok(entries)
+    }
+    
     /// Download audio directly using yt-dlp (much faster than URL extraction + separate download)
     pub async fn download_audio_direct(&self, url: &str, output_path: &std::path::Path) -> Result<AudioFormat> {
         tracing::debug!("Downloading audio directly for: {}", url);
         
         let output = Command::new(&self.yt_dlp_path)
+            .args(self.cookies.yt_dlp_args())
+            .args(self.network.yt_dlp_args())
             .args([
                 // Output to specific file
                 "--output", &output_path.to_string_lossy(),
-                // Extract audio in the most efficient format for transcription
+                // Extract audio; the defaults (mp3, quality 9) are smallest and still transcribe well
                 "--extract-audio",
-                "--audio-format", "mp3",
-                "--audio-quality", "9",  // Lowest quality for speed (still good for transcription)
+                "--audio-format", self.audio_format.as_str(),
+                "--audio-quality", &self.audio_quality,
                 // Prioritize original language track with good quality
                 "--format", "bestaudio[acodec^=mp4a]/bestaudio[ext=m4a]/bestaudio",
                 "--no-playlist",
@@ -83,12 +216,9 @@ impl YoutubeExtractor {
             .output()
             .await?;
             
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("Failed to download audio: {}", error);
-        }
+        crate::logs::check("yt-dlp", &output, "Failed to download audio")?;
         
-        Ok(AudioFormat::Mp3) // We're forcing MP3 conversion for speed
+        Ok(self.audio_format)
     }
 }
 
@@ -103,27 +233,7 @@ impl MediaExtractor for YoutubeExtractor {
         // Get video information
         let info = self.get_video_info(url).await?;
         
-        // Extract metadata
-        let title = info["title"].as_str().map(|s| s.to_string());
-        let duration_seconds = info["duration"].as_f64();
-        let duration = duration_seconds.map(|d| Duration::seconds(d as i64));
-        
-        // For YouTube, we'll use direct download, so we use a placeholder URL
-        // The actual download will be handled by download_audio_direct()
-        let download_url = format!("yt-dlp://{}", url);
-        
-        // We'll always convert to MP3 for speed and compatibility
-        let format = AudioFormat::Mp3;
-        
-        Ok(AudioInfo {
-            download_url,
-            duration,
-            title,
-            format,
-            sample_rate: Some(44100), // YouTube typically uses 44.1kHz  
-            file_size: None, // Will be determined during download
-            original_url: url.to_string(),
-        })
+        Ok(self.audio_info(url, &info))
     }
     
     fn supports_url(&self, url: &str) -> bool {
@@ -139,10 +249,101 @@ impl MediaExtractor for YoutubeExtractor {
     fn platform_name(&self) -> &'static str {
         "YouTube"
     }
+    
+    async fn download_audio(&self, audio_info: &AudioInfo, output_path: &PathBuf) -> Result<()> {
+        self.download_audio_direct(&audio_info.download_url, output_path).await?;
+        Ok(())
+    }
+}
+
+/// Subtitles a video's uploader provided, saved as a WebVTT file
+pub struct Captions {
+    pub audio_info: AudioInfo,
+    
+    /// YouTube's code for the track, e.g. `en` or `es-419`
+    pub language: String,
+    
+    pub path: PathBuf,
+}
+
+/// Chapter markers from yt-dlp's video information
+fn chapters_of(info: &Value) -> Vec<Chapter> {
+    info["chapters"]
+        .as_array()
+        .map(|chapters| {
+            chapters
+                .iter()
+                .filter_map(|chapter| {
+                    Some(Chapter {
+                        title: chapter["title"].as_str()?.trim().to_string(),
+                        start_time: chapter["start_time"].as_f64()?,
+                        end_time: chapter["end_time"].as_f64(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The subtitle track for a language: an exact match, else the plain or a regional
+/// variant of its base language (`en-US` takes `en`, then `en-GB`)
+fn pick_caption_track<'a>(available: &[&'a str], wanted: &str) -> Option<&'a str> {
+    let base = |code: &str| code.split(['-', '_']).next().unwrap_or("").to_lowercase();
+    let wanted_base = base(wanted);
+    
+    available
+        .iter()
+        .find(|track| track.eq_ignore_ascii_case(wanted))
+        .or_else(|| available.iter().find(|track| track.eq_ignore_ascii_case(&wanted_base)))
+        .or_else(|| available.iter().find(|track| base(track) == wanted_base))
+        .copied()
+}
+
+/// Whether a URL names a YouTube playlist rather than a single video
+pub fn is_playlist_url(url: &str) -> bool {
+    let url = url.to_lowercase();
+    url.contains("youtube.com/playlist?") && url.contains("list=")
 }
 
 impl Default for YoutubeExtractor {
     fn default() -> Self {
         Self::new()
     }
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_pick_caption_track() {
+        let available = ["de", "en-GB", "en", "es-419"];
+        assert_eq!(pick_caption_track(&available, "en-GB"), Some("en-GB"));
+        assert_eq!(pick_caption_track(&available, "en-US"), Some("en"));
+        assert_eq!(pick_caption_track(&["en-GB"], "en-US"), Some("en-GB"));
+        assert_eq!(pick_caption_track(&available, "es-ES"), Some("es-419"));
+        assert_eq!(pick_caption_track(&available, "fr-FR"), None);
+    }
+    
+    #[test]
+    fn test_is_playlist_url() {
+        assert!(is_playlist_url("https://www.youtube.com/playlist?list=PL590L5WQmH8fJ54F369BLDSqIwcs-TCfs"));
+        assert!(!is_playlist_url("https://www.youtube.com/watch?v=dQw4w9WgXcQ&list=PL590L5WQmH8fJ54F369BLDSqIwcs-TCfs"));
+        assert!(!is_playlist_url("https://youtu.be/dQw4w9WgXcQ"));
+    }
+    
+    #[test]
+    fn test_chapters_of() {
+        let info = serde_json::json!({
+            "chapters": [
+                {"title": "Intro", "start_time": 0.0, "end_time": 42.5},
+                {"title": " Demo ", "start_time": 42.5},
+                {"start_time": 90.0}
+            ]
+        });
+        let chapters = chapters_of(&info);
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[0].end_time, Some(42.5));
+        assert_eq!(chapters[1].title, "Demo");
+        assert!(chapters_of(&serde_json::json!({"chapters": null})).is_empty());
+    }
+}
@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+tokio::task_local! {
+    /// Told about every stage the current task goes through, see [`watch_stages`]
+    static STAGE_WATCHER: Box<dyn Fn(&str) + Send + Sync>;
+}
+
+/// Run `future`, calling `watcher` with each stage it reports
+///
+/// A pipeline shared by several jobs has one heartbeat, so this is how one job's stages are
+/// told apart (`serve` events).
+pub async fn watch_stages<F: Future>(watcher: impl Fn(&str) + Send + Sync + 'static, future: F) -> F::Output {
+    STAGE_WATCHER.scope(Box::new(watcher), future).await
+}
+
+/// Liveness reporting for long unattended runs (systemd, Kubernetes)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthConfig {
+    /// Seconds between heartbeat log lines (0 disables the heartbeat)
+    pub heartbeat_secs: u64,
+
+    /// File rewritten with the current time on every heartbeat, for liveness probes
+    pub liveness_file: Option<PathBuf>,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_secs: 60,
+            liveness_file: None,
+        }
+    }
+}
+
+/// Background task that logs what the run is doing and refreshes the liveness file
+///
+/// The task stops when the heartbeat is dropped.
+pub struct Heartbeat {
+    stage: Arc<Mutex<String>>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl Heartbeat {
+    /// Start beating; does nothing when the heartbeat is disabled
+    pub fn start(config: &HealthConfig) -> Self {
+        let stage = Arc::new(Mutex::new("Starting".to_string()));
+        if config.heartbeat_secs == 0 {
+            return Self { stage, task: None };
+        }
+
+        let interval = Duration::from_secs(config.heartbeat_secs);
+        let liveness_file = config.liveness_file.clone();
+        let shared_stage = stage.clone();
+        let started = Instant::now();
+
+        if let Some(path) = &liveness_file {
+            touch(path);
+        }
+
+        let task = tokio::spawn(async move {
+            let mut ticks = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+            loop {
+                ticks.tick().await;
+
+                let stage = shared_stage.lock().map(|s| s.clone()).unwrap_or_default();
+                tracing::info!("{}", beat_message(&stage, started.elapsed()));
+                if let Some(path) = &liveness_file {
+                    touch(path);
+                }
+            }
+        });
+
+        Self { stage, task: Some(task) }
+    }
+
+    /// Describe what the run is currently doing
+    pub fn set_stage(&self, stage: impl Into<String>) {
+        let stage = stage.into();
+        tracing::debug!("Stage: {}", stage);
+        crate::crash::set_stage(&stage);
+        let _ = STAGE_WATCHER.try_with(|watcher| watcher(&stage));
+        if let Ok(mut current) = self.stage.lock() {
+            *current = stage;
+        }
+    }
+}
+
+impl Drop for Heartbeat {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
+
+fn beat_message(stage: &str, elapsed: Duration) -> String {
+    format!("Heartbeat: {} ({} elapsed)", stage, crate::utils::format_duration(elapsed.as_secs_f64()))
+}
+
+fn touch(path: &Path) {
+    if let Err(e) = fs_err::write(path, chrono::Utc::now().to_rfc3339()) {
+        tracing::warn!("Failed to update liveness file: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_heartbeat_touches_liveness_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alive");
+        let heartbeat = Heartbeat::start(&HealthConfig {
+            heartbeat_secs: 30,
+            liveness_file: Some(path.clone()),
+        });
+        assert!(path.exists());
+
+        fs_err::remove_file(&path).unwrap();
+        heartbeat.set_stage("Waiting for job");
+        tokio::time::sleep(Duration::from_secs(31)).await;
+        assert!(path.exists());
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let watcher = seen.clone();
+        watch_stages(move |stage| watcher.lock().unwrap().push(stage.to_string()), async {
+            heartbeat.set_stage("Uploading audio to S3");
+        })
+        .await;
+        heartbeat.set_stage("Not watched");
+        assert_eq!(*seen.lock().unwrap(), ["Uploading audio to S3"]);
+
+        drop(heartbeat);
+        fs_err::remove_file(&path).unwrap();
+        tokio::time::sleep(Duration::from_secs(61)).await;
+        assert!(!path.exists());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_disabled_heartbeat() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alive");
+        let heartbeat = Heartbeat::start(&HealthConfig {
+            heartbeat_secs: 0,
+            liveness_file: Some(path.clone()),
+        });
+        assert!(heartbeat.task.is_none());
+
+        // Stages are still kept, for crash reports and `serve` events
+        heartbeat.set_stage("Downloading audio");
+        assert_eq!(*heartbeat.stage.lock().unwrap(), "Downloading audio");
+        tokio::time::sleep(Duration::from_secs(3600)).await;
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_stages_are_told_apart_per_job() {
+        let heartbeat = Arc::new(Heartbeat::start(&HealthConfig {
+            heartbeat_secs: 0,
+            liveness_file: None,
+        }));
+        let jobs = ["first", "second"].map(|job| {
+            let heartbeat = heartbeat.clone();
+            tokio::spawn(async move {
+                let seen = Arc::new(Mutex::new(Vec::new()));
+                let watcher = seen.clone();
+                watch_stages(move |stage| watcher.lock().unwrap().push(stage.to_string()), async {
+                    heartbeat.set_stage(format!("Uploading {}", job));
+                    tokio::task::yield_now().await;
+                    heartbeat.set_stage(format!("Transcribing {}", job));
+                })
+                .await;
+                seen
+            })
+        });
+        let [first, second] = jobs;
+        assert_eq!(*first.await.unwrap().lock().unwrap(), ["Uploading first", "Transcribing first"]);
+        assert_eq!(*second.await.unwrap().lock().unwrap(), ["Uploading second", "Transcribing second"]);
+    }
+
+    #[test]
+    fn test_beat_message() {
+        assert_eq!(beat_message("Starting", Duration::from_secs(42)), "Heartbeat: Starting (42s elapsed)");
+        assert_eq!(
+            beat_message("Waiting for job", Duration::from_secs(3725)),
+            "Heartbeat: Waiting for job (1h 2m 5s elapsed)"
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use serde::Serialize;
+
+use super::HistoryConfig;
+use crate::cli::Backend;
+
+/// AWS Transcribe bills at least this many seconds per job
+const MIN_BILLED_SECS: f64 = 15.0;
+
+/// Uploads are removed once their job is done; storage is charged as if they stayed a day
+const STORED_DAYS: f64 = 1.0;
+
+/// Bitrate assumed for the upload when the source does not report its size (128 kbit/s)
+const ASSUMED_BYTES_PER_SEC: f64 = 16_000.0;
+
+/// Projected spend of transcribing some audio, before anything is uploaded
+#[derive(Debug, Clone, Serialize)]
+pub struct CostEstimate {
+    pub audio_secs: f64,
+    pub upload_bytes: u64,
+    /// AWS Transcribe, or local Whisper time at its configured price
+    pub transcription: f64,
+    /// Keeping the upload in S3 while the job runs
+    pub storage: f64,
+}
+
+impl CostEstimate {
+    /// The cost of `audio_secs` of audio with `backend`
+    ///
+    /// Hybrid runs are estimated as if every span went to AWS, so the figure is an upper bound.
+    pub fn new(audio_secs: f64, size: Option<u64>, backend: Backend, prices: &HistoryConfig) -> Self {
+        let upload_bytes = size.unwrap_or((audio_secs * ASSUMED_BYTES_PER_SEC) as u64);
+        let aws = audio_secs.max(MIN_BILLED_SECS) / 60.0 * prices.aws_per_minute;
+        let whisper = audio_secs / 60.0 * prices.whisper_per_minute;
+        let storage = upload_bytes as f64 / 1e9 * prices.s3_per_gb_month * STORED_DAYS / 30.0;
+
+        let (transcription, storage) = match backend {
+            Backend::Aws => (aws, storage),
+            Backend::Whisper => (whisper, 0.0),
+            Backend::Hybrid => (aws + whisper, storage),
+        };
+        Self {
+            audio_secs,
+            upload_bytes,
+            transcription,
+            storage,
+        }
+    }
+
+    pub fn total(&self) -> f64 {
+        self.transcription + self.storage
+    }
+
+    /// Fail if the estimate is above `max_cost` (USD)
+    pub fn check_limit(&self, max_cost: Option<f64>) -> anyhow::Result<()> {
+        match max_cost {
+            Some(max_cost) if self.total() > max_cost => anyhow::bail!(
+                "Estimated cost ${:.2} for {} of audio exceeds --max-cost ${:.2}; nothing was uploaded",
+                self.total(),
+                crate::utils::format_duration(self.audio_secs),
+                max_cost
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_and_limit() {
+        let prices = HistoryConfig::default();
+
+        let hour = CostEstimate::new(3600.0, Some(57_600_000), Backend::Aws, &prices);
+        assert!((hour.transcription - 1.44).abs() < 1e-9);
+        assert!(hour.storage > 0.0 && hour.storage < 0.001);
+        assert!(hour.check_limit(Some(1.5)).is_ok());
+        assert!(hour.check_limit(Some(1.0)).is_err());
+        assert!(hour.check_limit(None).is_ok());
+
+        // Short jobs are billed for the minimum, and Whisper uploads nothing
+        let short = CostEstimate::new(5.0, None, Backend::Aws, &prices);
+        assert!((short.transcription - 0.006).abs() < 1e-9);
+        assert_eq!(CostEstimate::new(3600.0, None, Backend::Whisper, &prices).total(), 0.0);
+    }
+}
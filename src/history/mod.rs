@@ -0,0 +1,467 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::ValueEnum;
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::cli::Backend;
+use crate::config::Config;
+use crate::transcribe::TranscriptionResult;
+
+pub mod estimate;
+pub mod report;
+
+pub use estimate::CostEstimate;
+pub use report::{Report, ReportFormat};
+
+/// Run history and the prices used to estimate spend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// Record every completed transcription for `history` and `report`
+    pub enabled: bool,
+
+    /// AWS Transcribe price per audio minute (USD)
+    pub aws_per_minute: f64,
+
+    /// Cost of a local Whisper minute (USD), e.g. amortised GPU time
+    pub whisper_per_minute: f64,
+
+    /// S3 storage price per GB-month (USD), for `estimate` and `--max-cost`
+    pub s3_per_gb_month: f64,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            aws_per_minute: 0.024,
+            whisper_per_minute: 0.0,
+            s3_per_gb_month: 0.023,
+        }
+    }
+}
+
+/// One completed transcription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Row in the history database, once recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+
+    pub completed_at: DateTime<Utc>,
+
+    /// URL or file that was transcribed
+    pub source: String,
+
+    pub title: Option<String>,
+
+    /// Platform the source came from, e.g. "YouTube" or "Local File"
+    #[serde(default)]
+    pub platform: Option<String>,
+
+    pub backend: Backend,
+
+    /// Language of the transcript
+    #[serde(default)]
+    pub language: Option<String>,
+
+    /// Length of the transcribed audio in seconds
+    pub audio_secs: f64,
+
+    /// Seconds of audio billed by AWS Transcribe (all of it for AWS, only re-sent spans for hybrid)
+    pub aws_secs: f64,
+
+    /// Wall-clock time the transcription took
+    pub processing_secs: Option<f64>,
+
+    /// Project tag given with `--tag`
+    #[serde(default)]
+    pub tag: Option<String>,
+
+    /// File the transcript was written to, if the command saved one
+    #[serde(default)]
+    pub output: Option<PathBuf>,
+
+    /// Estimated spend in USD at the prices configured when the run completed
+    #[serde(default)]
+    pub cost_estimate: Option<f64>,
+
+    /// Accuracy estimated by the latest `qa` review of the transcript
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qa_accuracy: Option<f64>,
+}
+
+impl HistoryEntry {
+    /// Seconds of audio transcribed locally by Whisper
+    pub fn local_secs(&self) -> f64 {
+        match self.backend {
+            Backend::Aws => 0.0,
+            Backend::Whisper | Backend::Hybrid => self.audio_secs,
+        }
+    }
+
+    /// Estimated spend in USD
+    pub fn estimated_cost(&self, prices: &HistoryConfig) -> f64 {
+        self.aws_secs / 60.0 * prices.aws_per_minute + self.local_secs() / 60.0 * prices.whisper_per_minute
+    }
+}
+
+/// Which runs `history list` shows
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    /// First day to include (UTC)
+    pub since: Option<NaiveDate>,
+
+    /// Last day to include (UTC)
+    pub until: Option<NaiveDate>,
+
+    /// Part of the platform name, case-insensitive (e.g. "youtube")
+    pub platform: Option<String>,
+
+    /// Most recent runs to return
+    pub limit: Option<usize>,
+}
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS runs (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        completed_at TEXT NOT NULL,
+        source TEXT NOT NULL,
+        title TEXT,
+        platform TEXT,
+        backend TEXT NOT NULL,
+        language TEXT,
+        audio_secs REAL NOT NULL,
+        aws_secs REAL NOT NULL,
+        processing_secs REAL,
+        tag TEXT,
+        output TEXT,
+        cost_estimate REAL,
+        transcript TEXT
+    );
+    CREATE INDEX IF NOT EXISTS runs_completed_at ON runs (completed_at);
+    CREATE TABLE IF NOT EXISTS qa_reviews (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        run_id INTEGER NOT NULL REFERENCES runs (id),
+        reviewed_at TEXT NOT NULL,
+        reviewed INTEGER NOT NULL,
+        correct INTEGER NOT NULL
+    );
+";
+
+const COLUMNS: &str = "id, completed_at, source, title, platform, backend, language, audio_secs, aws_secs, \
+                       processing_secs, tag, output, cost_estimate, \
+                       (SELECT CAST(correct AS REAL) / reviewed FROM qa_reviews \
+                        WHERE run_id = runs.id AND reviewed > 0 ORDER BY id DESC LIMIT 1)";
+
+/// Completed transcriptions with their transcripts, in an SQLite database
+pub struct History {
+    connection: Connection,
+}
+
+impl History {
+    /// Open the history in the application data directory
+    ///
+    /// Runs logged by older versions to `history.jsonl` are imported the first time.
+    pub fn open() -> Result<Self> {
+        let data_dir = Config::data_dir()?;
+        let history = Self::at(&data_dir.join("history.sqlite"))?;
+
+        let legacy = data_dir.join("history.jsonl");
+        if legacy.exists() {
+            history.import_jsonl(&legacy)?;
+            fs_err::rename(&legacy, legacy.with_extension("jsonl.imported"))?;
+        }
+
+        Ok(history)
+    }
+
+    /// Open a history stored in a specific file
+    pub fn at(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs_err::create_dir_all(parent)?;
+        }
+
+        let connection = Connection::open(path)
+            .with_context(|| format!("Failed to open run history {}", path.display()))?;
+        connection.execute_batch(SCHEMA).context("Failed to create run history tables")?;
+
+        Ok(Self { connection })
+    }
+
+    /// Record a completed transcription, with its transcript if there is one; returns its id
+    pub fn record(&self, entry: &HistoryEntry, transcript: Option<&TranscriptionResult>) -> Result<i64> {
+        let transcript = transcript.map(serde_json::to_string).transpose()?;
+
+        self.connection
+            .execute(
+                "INSERT INTO runs (completed_at, source, title, platform, backend, language, audio_secs, aws_secs, \
+                 processing_secs, tag, output, cost_estimate, transcript) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                params![
+                    entry.completed_at,
+                    entry.source,
+                    entry.title,
+                    entry.platform,
+                    backend_name(entry.backend),
+                    entry.language,
+                    entry.audio_secs,
+                    entry.aws_secs,
+                    entry.processing_secs,
+                    entry.tag,
+                    entry.output.as_ref().map(|path| path.to_string_lossy().into_owned()),
+                    entry.cost_estimate,
+                    transcript,
+                ],
+            )
+            .context("Failed to write run history")?;
+
+        Ok(self.connection.last_insert_rowid())
+    }
+
+    /// Entries completed between two dates (inclusive, UTC), newest first
+    pub fn load(&self, since: Option<NaiveDate>, until: Option<NaiveDate>) -> Result<Vec<HistoryEntry>> {
+        self.search(&HistoryFilter {
+            since,
+            until,
+            ..Default::default()
+        })
+    }
+
+    /// Entries matching a filter, newest first
+    pub fn search(&self, filter: &HistoryFilter) -> Result<Vec<HistoryEntry>> {
+        // Times are stored as "YYYY-MM-DD HH:MM:SS...", so whole days compare as text
+        let since = filter.since.map(|date| date.to_string());
+        let before = filter.until.and_then(|date| date.succ_opt()).map(|date| date.to_string());
+        let platform = filter.platform.as_ref().map(|platform| format!("%{}%", platform));
+        let limit = filter.limit.map_or(-1, |limit| limit as i64);
+
+        let mut statement = self.connection.prepare(&format!(
+            "SELECT {} FROM runs \
+             WHERE (?1 IS NULL OR completed_at >= ?1) AND (?2 IS NULL OR completed_at < ?2) \
+             AND (?3 IS NULL OR platform LIKE ?3) \
+             ORDER BY completed_at DESC, id DESC LIMIT ?4",
+            COLUMNS
+        ))?;
+        let entries = statement
+            .query_map(params![since, before, platform, limit], entry_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .context("Failed to read run history")?;
+
+        Ok(entries)
+    }
+
+    /// One recorded run
+    pub fn get(&self, id: i64) -> Result<HistoryEntry> {
+        self.connection
+            .query_row(&format!("SELECT {} FROM runs WHERE id = ?1", COLUMNS), [id], entry_from_row)
+            .optional()?
+            .with_context(|| format!("No run #{} in the history (see `rustscribe history list`)", id))
+    }
+
+    /// The transcript saved with a run
+    pub fn transcript(&self, id: i64) -> Result<TranscriptionResult> {
+        let transcript: Option<String> = self
+            .connection
+            .query_row("SELECT transcript FROM runs WHERE id = ?1", [id], |row| row.get(0))
+            .optional()?
+            .with_context(|| format!("No run #{} in the history (see `rustscribe history list`)", id))?;
+
+        let transcript = transcript.with_context(|| format!("Run #{} was recorded without its transcript", id))?;
+        serde_json::from_str(&transcript).with_context(|| format!("Corrupt transcript for run #{}", id))
+    }
+
+    /// Record how many of a run's sampled segments a reviewer checked and found correct
+    pub fn record_qa(&self, id: i64, reviewed: usize, correct: usize) -> Result<()> {
+        self.get(id)?;
+        self.connection
+            .execute(
+                "INSERT INTO qa_reviews (run_id, reviewed_at, reviewed, correct) VALUES (?1, ?2, ?3, ?4)",
+                params![id, Utc::now(), reviewed as i64, correct as i64],
+            )
+            .context("Failed to write QA review")?;
+        Ok(())
+    }
+
+    /// Import runs from the JSON lines log older versions kept
+    fn import_jsonl(&self, path: &Path) -> Result<()> {
+        let content = fs_err::read_to_string(path)?;
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            match serde_json::from_str::<HistoryEntry>(line) {
+                Ok(entry) => {
+                    self.record(&entry, None)?;
+                }
+                Err(e) => tracing::warn!("Skipping unreadable history entry: {}", e),
+            }
+        }
+
+        tracing::info!("Imported run history from {}", path.display());
+        Ok(())
+    }
+}
+
+fn backend_name(backend: Backend) -> String {
+    backend.to_possible_value().map_or_else(String::new, |value| value.get_name().to_string())
+}
+
+fn entry_from_row(row: &Row) -> rusqlite::Result<HistoryEntry> {
+    let backend: String = row.get(5)?;
+    let backend = Backend::from_str(&backend, true)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(5, Type::Text, e.into()))?;
+
+    Ok(HistoryEntry {
+        id: Some(row.get(0)?),
+        completed_at: row.get(1)?,
+        source: row.get(2)?,
+        title: row.get(3)?,
+        platform: row.get(4)?,
+        backend,
+        language: row.get(6)?,
+        audio_secs: row.get(7)?,
+        aws_secs: row.get(8)?,
+        processing_secs: row.get(9)?,
+        tag: row.get(10)?,
+        output: row.get::<_, Option<String>>(11)?.map(PathBuf::from),
+        cost_estimate: row.get(12)?,
+        qa_accuracy: row.get(13)?,
+    })
+}
+
+/// First and last day of a `YYYY-MM` month
+pub fn parse_month(value: &str) -> Result<(NaiveDate, NaiveDate), String> {
+    let first = NaiveDate::parse_from_str(&format!("{}-01", value.trim()), "%Y-%m-%d")
+        .map_err(|_| format!("invalid month '{}', expected YYYY-MM", value))?;
+    let last = first
+        .checked_add_months(chrono::Months::new(1))
+        .and_then(|next| next.pred_opt())
+        .ok_or_else(|| format!("invalid month '{}'", value))?;
+
+    Ok((first, last))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(day: u32, backend: Backend, tag: Option<&str>) -> HistoryEntry {
+        HistoryEntry {
+            id: None,
+            completed_at: NaiveDate::from_ymd_opt(2024, 3, day).unwrap().and_hms_opt(12, 0, 0).unwrap().and_utc(),
+            source: "talk.mp3".to_string(),
+            title: None,
+            platform: Some("Local File".to_string()),
+            backend,
+            language: Some("en-US".to_string()),
+            audio_secs: 3600.0,
+            aws_secs: if backend == Backend::Whisper { 0.0 } else { 600.0 },
+            processing_secs: Some(120.0),
+            tag: tag.map(str::to_string),
+            output: None,
+            cost_estimate: None,
+            qa_accuracy: None,
+        }
+    }
+
+    #[test]
+    fn test_record_and_load_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::at(&dir.path().join("history.sqlite")).unwrap();
+        assert!(history.load(None, None).unwrap().is_empty());
+
+        for day in [1, 15, 31] {
+            history.record(&entry(day, Backend::Aws, None), None).unwrap();
+        }
+
+        let (first, last) = parse_month("2024-03").unwrap();
+        assert_eq!(history.load(Some(first), Some(last)).unwrap().len(), 3);
+        assert_eq!(history.load(NaiveDate::from_ymd_opt(2024, 3, 2), None).unwrap().len(), 2);
+        assert_eq!(last, NaiveDate::from_ymd_opt(2024, 3, 31).unwrap());
+        assert!(parse_month("March").is_err());
+    }
+
+    #[test]
+    fn test_search_and_transcript() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::at(&dir.path().join("history.sqlite")).unwrap();
+
+        let result: TranscriptionResult = serde_json::from_value(serde_json::json!({
+            "transcript": "Hello there.",
+            "segments": [],
+            "audio_info": crate::demo::sample_audio_info("https://youtu.be/dQw4w9WgXcQ"),
+            "audio_path": null,
+            "metadata": {
+                "job_id": "transcriptor_1234",
+                "language": "en-US",
+                "processing_duration": null,
+                "audio_duration": 4.2,
+                "confidence": null,
+                "completed_at": "2024-03-02T12:00:00Z"
+            },
+            "words": null
+        }))
+        .unwrap();
+        let youtube = HistoryEntry {
+            platform: Some("YouTube".to_string()),
+            output: Some(PathBuf::from("talk.srt")),
+            ..entry(2, Backend::Whisper, Some("acme"))
+        };
+        let id = history.record(&youtube, Some(&result)).unwrap();
+        let local = history.record(&entry(3, Backend::Aws, None), None).unwrap();
+
+        let found = history
+            .search(&HistoryFilter {
+                platform: Some("youtube".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, Some(id));
+        assert_eq!(found[0].backend, Backend::Whisper);
+        assert_eq!(found[0].output, Some(PathBuf::from("talk.srt")));
+        assert_eq!(found[0].completed_at, youtube.completed_at);
+
+        let newest = history.search(&HistoryFilter { limit: Some(1), ..Default::default() }).unwrap();
+        assert_eq!(newest[0].id, Some(local));
+
+        assert_eq!(history.transcript(id).unwrap().transcript, "Hello there.");
+        assert!(history.transcript(local).is_err());
+        assert!(history.get(999).is_err());
+
+        // The latest review sets the run's accuracy
+        history.record_qa(id, 10, 7).unwrap();
+        history.record_qa(id, 20, 18).unwrap();
+        assert_eq!(history.get(id).unwrap().qa_accuracy, Some(0.9));
+        assert_eq!(history.get(local).unwrap().qa_accuracy, None);
+        assert!(history.record_qa(999, 1, 1).is_err());
+    }
+
+    #[test]
+    fn test_import_jsonl() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::at(&dir.path().join("history.sqlite")).unwrap();
+
+        let legacy = dir.path().join("history.jsonl");
+        let line = r#"{"completed_at":"2024-03-01T12:00:00Z","source":"talk.mp3","title":null,"backend":"hybrid","audio_secs":60.0,"aws_secs":6.0,"processing_secs":null}"#;
+        fs_err::write(&legacy, format!("{}\nnot json\n", line)).unwrap();
+        history.import_jsonl(&legacy).unwrap();
+
+        let entries = history.load(None, None).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].backend, Backend::Hybrid);
+        assert!(entries[0].tag.is_none());
+    }
+
+    #[test]
+    fn test_estimated_cost() {
+        let prices = HistoryConfig {
+            aws_per_minute: 0.024,
+            whisper_per_minute: 0.001,
+            ..Default::default()
+        };
+        assert!((entry(1, Backend::Hybrid, None).estimated_cost(&prices) - (10.0 * 0.024 + 60.0 * 0.001)).abs() < 1e-9);
+        assert!((entry(1, Backend::Whisper, None).estimated_cost(&prices) - 0.06).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,214 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use super::{HistoryConfig, HistoryEntry};
+
+/// Label for runs without a project tag
+const UNTAGGED: &str = "(untagged)";
+
+/// How a report is printed
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum ReportFormat {
+    /// Aligned table for the terminal
+    #[default]
+    Table,
+    /// One row per group, for spreadsheets
+    Csv,
+    /// Structured totals
+    Json,
+}
+
+/// Totals for a group of runs
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Totals {
+    pub runs: usize,
+    pub audio_hours: f64,
+    pub aws_hours: f64,
+    /// Estimated spend in USD
+    pub estimated_cost: f64,
+}
+
+impl Totals {
+    fn add(&mut self, entry: &HistoryEntry, prices: &HistoryConfig) {
+        self.runs += 1;
+        self.audio_hours += entry.audio_secs / 3600.0;
+        self.aws_hours += entry.aws_secs / 3600.0;
+        self.estimated_cost += entry.estimated_cost(prices);
+    }
+}
+
+/// Usage and spend over a date range, in total, per backend and per project tag
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+    pub total: Totals,
+    pub by_backend: BTreeMap<String, Totals>,
+    pub by_tag: BTreeMap<String, Totals>,
+}
+
+impl Report {
+    pub fn build(
+        entries: &[HistoryEntry],
+        prices: &HistoryConfig,
+        since: Option<NaiveDate>,
+        until: Option<NaiveDate>,
+    ) -> Self {
+        let mut report = Self {
+            since,
+            until,
+            total: Totals::default(),
+            by_backend: BTreeMap::new(),
+            by_tag: BTreeMap::new(),
+        };
+
+        for entry in entries {
+            report.total.add(entry, prices);
+
+            let backend = format!("{:?}", entry.backend).to_lowercase();
+            report.by_backend.entry(backend).or_default().add(entry, prices);
+
+            let tag = entry.tag.clone().unwrap_or_else(|| UNTAGGED.to_string());
+            report.by_tag.entry(tag).or_default().add(entry, prices);
+        }
+
+        report
+    }
+
+    /// Render the report in the given format
+    pub fn render(&self, format: ReportFormat) -> Result<String> {
+        Ok(match format {
+            ReportFormat::Table => self.to_table(),
+            ReportFormat::Csv => self.to_csv(),
+            ReportFormat::Json => serde_json::to_string_pretty(self)?,
+        })
+    }
+
+    fn period(&self) -> String {
+        match (self.since, self.until) {
+            (Some(since), Some(until)) => format!("{} to {}", since, until),
+            (Some(since), None) => format!("since {}", since),
+            (None, Some(until)) => format!("until {}", until),
+            (None, None) => "all time".to_string(),
+        }
+    }
+
+    fn to_table(&self) -> String {
+        let row = |name: &str, totals: &Totals| {
+            format!(
+                "  {:<24} {:>6} {:>10.2} {:>10.2} {:>11}\n",
+                name,
+                totals.runs,
+                totals.audio_hours,
+                totals.aws_hours,
+                format!("${:.2}", totals.estimated_cost)
+            )
+        };
+
+        let mut output = format!("Transcription report ({})\n\n", self.period());
+        output.push_str(&format!(
+            "  {:<24} {:>6} {:>10} {:>10} {:>11}\n",
+            "", "Runs", "Hours", "AWS hours", "Est. cost"
+        ));
+        output.push_str(&row("Total", &self.total));
+
+        output.push_str("\nBy backend\n");
+        for (backend, totals) in &self.by_backend {
+            output.push_str(&row(backend, totals));
+        }
+
+        output.push_str("\nBy project tag\n");
+        for (tag, totals) in &self.by_tag {
+            output.push_str(&row(tag, totals));
+        }
+
+        output
+    }
+
+    fn to_csv(&self) -> String {
+        let mut output = String::from("group,name,runs,audio_hours,aws_hours,estimated_cost_usd\n");
+        let mut row = |group: &str, name: &str, totals: &Totals| {
+            output.push_str(&format!(
+                "{},{},{},{:.3},{:.3},{:.2}\n",
+                group,
+                csv_field(name),
+                totals.runs,
+                totals.audio_hours,
+                totals.aws_hours,
+                totals.estimated_cost
+            ));
+        };
+
+        row("total", "all", &self.total);
+        for (backend, totals) in &self.by_backend {
+            row("backend", backend, totals);
+        }
+        for (tag, totals) in &self.by_tag {
+            row("tag", tag, totals);
+        }
+
+        output
+    }
+}
+
+/// Quote a CSV field when it contains separators or quotes
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Backend;
+
+    fn entry(backend: Backend, audio_secs: f64, aws_secs: f64, tag: Option<&str>) -> HistoryEntry {
+        HistoryEntry {
+            id: None,
+            completed_at: chrono::Utc::now(),
+            source: "talk.mp3".to_string(),
+            title: None,
+            platform: None,
+            backend,
+            language: None,
+            audio_secs,
+            aws_secs,
+            processing_secs: None,
+            tag: tag.map(str::to_string),
+            output: None,
+            cost_estimate: None,
+            qa_accuracy: None,
+        }
+    }
+
+    #[test]
+    fn test_rollup() {
+        let entries = [
+            entry(Backend::Aws, 3600.0, 3600.0, Some("acme")),
+            entry(Backend::Aws, 1800.0, 1800.0, None),
+            entry(Backend::Hybrid, 3600.0, 360.0, Some("acme")),
+        ];
+        let report = Report::build(&entries, &HistoryConfig::default(), None, None);
+
+        assert_eq!(report.total.runs, 3);
+        assert!((report.total.audio_hours - 2.5).abs() < 1e-9);
+        assert!((report.by_backend["aws"].estimated_cost - 90.0 * 0.024).abs() < 1e-9);
+        assert_eq!(report.by_tag["acme"].runs, 2);
+        assert_eq!(report.by_tag[UNTAGGED].runs, 1);
+
+        let csv = report.render(ReportFormat::Csv).unwrap();
+        assert!(csv.contains("\ntag,acme,2,2.000,1.100,1.58\n"));
+    }
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("acme"), "acme");
+        assert_eq!(csv_field("acme, inc"), "\"acme, inc\"");
+    }
+}
@@ -0,0 +1,190 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Stdio;
+
+use crate::transcribe::TranscriptionResult;
+use crate::utils::command_from_template;
+
+pub mod vars;
+
+pub use vars::TemplateVars;
+
+/// External commands run at fixed points of a transcription
+///
+/// Each entry is a command line; `{file}` is replaced with the file the hook works on,
+/// or the path is appended as the last argument. The other [`vars::VARIABLES`] (`{title}`,
+/// `{url}`, `{job_id}`, ...) are expanded too. A failing hook stops the run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// Run on the downloaded audio before it is uploaded or transcribed; may rewrite it in place
+    pub pre_upload: Vec<String>,
+
+    /// Run on the result as JSON; edits to the file are read back
+    pub post_transcribe: Vec<String>,
+
+    /// Run on each saved output file
+    pub post_format: Vec<String>,
+}
+
+impl HooksConfig {
+    /// Run the `pre_upload` hooks on an audio file
+    pub async fn pre_upload(&self, audio_path: &Path, vars: &TemplateVars) -> Result<()> {
+        run_all(&self.pre_upload, "pre_upload", audio_path, vars).await
+    }
+
+    /// Run the `post_transcribe` hooks, letting them edit the result through a JSON file
+    pub async fn post_transcribe(&self, result: &mut TranscriptionResult, json_path: &Path) -> Result<()> {
+        if self.post_transcribe.is_empty() {
+            return Ok(());
+        }
+
+        fs_err::write(json_path, serde_json::to_string_pretty(result)?)?;
+        let vars = TemplateVars::from_result(result);
+        run_all(&self.post_transcribe, "post_transcribe", json_path, &vars).await?;
+
+        let content = fs_err::read_to_string(json_path)?;
+        *result = serde_json::from_str(&content).context("post_transcribe hook left invalid result JSON")?;
+        let _ = fs_err::remove_file(json_path);
+
+        Ok(())
+    }
+
+    /// Run the `post_format` hooks on a saved output file
+    pub async fn post_format(&self, output_path: &Path, result: &TranscriptionResult) -> Result<()> {
+        let vars = TemplateVars::from_result(result).with_output(output_path);
+        run_all(&self.post_format, "post_format", output_path, &vars).await
+    }
+}
+
+/// Launch a program on a finished output file for review, e.g. an editor or `code --diff`
+///
+/// Unlike hooks it shares the terminal and the run waits for it to exit. `{file}` and the
+/// other variables are expanded, and `{previous}` is the earlier version `--backup` kept;
+/// without `{file}` the path is appended.
+pub async fn open_with(line: &str, output_path: &Path, previous: Option<&Path>, result: &TranscriptionResult) -> Result<()> {
+    let vars = TemplateVars::from_result(result).with_output(output_path).with_file(output_path);
+    let mut placeholders = vars.placeholders();
+    if line.contains("{previous}") {
+        let previous = previous.context("{previous} needs an earlier version of the output to compare with (use --backup)")?;
+        placeholders.push(("{previous}".to_string(), previous.to_string_lossy().into_owned()));
+    }
+
+    let placeholders: Vec<(&str, &str)> = placeholders.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+    let mut command = command_from_template(line, &placeholders)?;
+    if !line.contains("{file}") {
+        command.arg(output_path);
+    }
+
+    let status = command
+        .envs(vars.env())
+        .status()
+        .await
+        .with_context(|| format!("Failed to run '{}'", line))?;
+    if !status.success() {
+        anyhow::bail!("'{}' exited with {}", line, status);
+    }
+    Ok(())
+}
+
+async fn run_all(commands: &[String], hook: &str, file: &Path, vars: &TemplateVars) -> Result<()> {
+    let vars = vars.clone().with_file(file);
+    for line in commands {
+        run(line, hook, file, &vars).await?;
+    }
+    Ok(())
+}
+
+/// Run one hook command, with the hook name in `RUSTSCRIBE_HOOK` and the variables in `RUSTSCRIBE_<NAME>`
+async fn run(line: &str, hook: &str, file: &Path, vars: &TemplateVars) -> Result<()> {
+    tracing::info!("Running {} hook: {}", hook, line);
+
+    let placeholders = vars.placeholders();
+    let placeholders: Vec<(&str, &str)> = placeholders.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+    let mut command = command_from_template(line, &placeholders)?;
+    if !line.contains("{file}") {
+        command.arg(file);
+    }
+
+    let output = command
+        .envs(vars.env())
+        .env("RUSTSCRIBE_HOOK", hook)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .with_context(|| format!("Failed to run {} hook '{}'", hook, line))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !stdout.trim().is_empty() {
+        tracing::debug!("{} hook output: {}", hook, stdout.trim());
+    }
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("{} hook '{}' failed ({}): {}", hook, line, output.status, error.trim());
+    }
+
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_hooks_get_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("audio.wav");
+        fs_err::write(&file, "").unwrap();
+        let marker = dir.path().join("seen");
+
+        let hooks = HooksConfig {
+            pre_upload: vec![format!("cp {{file}} {}", marker.display())],
+            ..Default::default()
+        };
+        hooks.pre_upload(&file, &TemplateVars::default()).await.unwrap();
+        assert!(marker.exists());
+
+        let hooks = HooksConfig {
+            pre_upload: vec!["false".to_string()],
+            ..Default::default()
+        };
+        let error = hooks.pre_upload(&file, &TemplateVars::default()).await.unwrap_err().to_string();
+        assert!(error.contains("pre_upload hook 'false' failed"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_open_with_previous_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("talk.srt");
+        let previous = dir.path().join("talk.srt.bak");
+        fs_err::write(&output, "new").unwrap();
+        fs_err::write(&previous, "old").unwrap();
+        let result = crate::transcribe::fixture::result(&[(0.0, 2.5, "Hello world")]);
+
+        open_with("cmp -s {previous}", &output, Some(&previous), &result).await.unwrap_err();
+        fs_err::write(&previous, "new").unwrap();
+        open_with("cmp -s {previous}", &output, Some(&previous), &result).await.unwrap();
+
+        let error = open_with("cat {previous} {file}", &output, None, &result).await.unwrap_err().to_string();
+        assert!(error.contains("use --backup"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_hooks_expand_variables() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("audio.wav");
+        fs_err::write(&file, "").unwrap();
+
+        let hooks = HooksConfig {
+            pre_upload: vec![format!("cp {{file}} {}/{{status}}.wav", dir.path().display())],
+            ..Default::default()
+        };
+        let vars = TemplateVars::default().with_status("processing");
+        hooks.pre_upload(&file, &vars).await.unwrap();
+        assert!(dir.path().join("processing.wav").exists());
+    }
+}
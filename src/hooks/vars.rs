@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::extractors::AudioInfo;
+use crate::transcribe::TranscriptionResult;
+
+/// Variables expanded as `{name}` in hook commands and integration templates
+///
+/// Each is also exported to hook commands as `RUSTSCRIBE_<NAME>` (e.g. `RUSTSCRIBE_TITLE`).
+/// Variables that do not apply at a given point expand to an empty string.
+pub const VARIABLES: &[(&str, &str)] = &[
+    ("title", "Title of the media, if the source has one"),
+    ("url", "URL or file that was transcribed"),
+    ("duration", "Audio length, e.g. 1h 2m 3s"),
+    ("duration_secs", "Audio length in whole seconds"),
+    ("language", "Language code the transcript is in"),
+    ("job_id", "Transcription job id"),
+    ("status", "processing, completed or failed"),
+    ("output", "Saved output file"),
+    ("audio", "Saved audio file (--save-audio / keep_audio)"),
+    ("file", "The file a hook works on"),
+];
+
+/// Values for [`VARIABLES`] at one point of a run
+#[derive(Debug, Clone, Default)]
+pub struct TemplateVars {
+    values: BTreeMap<&'static str, String>,
+}
+
+impl TemplateVars {
+    /// Variables known once the audio has been extracted
+    pub fn from_audio(audio_info: &AudioInfo) -> Self {
+        let mut vars = Self::default();
+        vars.set("url", &audio_info.original_url);
+        if let Some(title) = &audio_info.title {
+            vars.set("title", title);
+        }
+        if let Some(duration) = audio_info.duration {
+            vars.set_duration(duration.num_milliseconds() as f64 / 1000.0);
+        }
+        vars.with_status("processing")
+    }
+
+    /// Variables for a finished transcription
+    pub fn from_result(result: &TranscriptionResult) -> Self {
+        let mut vars = Self::from_audio(&result.audio_info);
+        if !vars.values.contains_key("duration") {
+            if let Some(secs) = result.metadata.audio_duration {
+                vars.set_duration(secs);
+            }
+        }
+        vars.set("language", &result.metadata.language);
+        vars.set("job_id", &result.metadata.job_id);
+        if let Some(audio_path) = &result.audio_path {
+            vars.set("audio", &audio_path.to_string_lossy());
+        }
+        vars.with_status("completed")
+    }
+
+    pub fn with_status(mut self, status: &str) -> Self {
+        self.set("status", status);
+        self
+    }
+
+    pub fn with_output(mut self, path: &Path) -> Self {
+        self.set("output", &path.to_string_lossy());
+        self
+    }
+
+    pub fn with_file(mut self, path: &Path) -> Self {
+        self.set("file", &path.to_string_lossy());
+        self
+    }
+
+    /// The value of a variable; unknown or unset variables are empty
+    pub fn get(&self, name: &str) -> &str {
+        self.values.get(name).map(String::as_str).unwrap_or("")
+    }
+
+    /// Replace every `{name}` of a known variable in a template
+    pub fn expand(&self, template: &str) -> String {
+        VARIABLES.iter().fold(template.to_string(), |text, (name, _)| {
+            text.replace(&format!("{{{}}}", name), self.get(name))
+        })
+    }
+
+    /// `{name}` placeholders and their values, for [`crate::utils::command_from_template`]
+    pub fn placeholders(&self) -> Vec<(String, String)> {
+        VARIABLES
+            .iter()
+            .map(|(name, _)| (format!("{{{}}}", name), self.get(name).to_string()))
+            .collect()
+    }
+
+    /// `RUSTSCRIBE_<NAME>` environment variables for commands
+    pub fn env(&self) -> Vec<(String, String)> {
+        VARIABLES
+            .iter()
+            .map(|(name, _)| (format!("RUSTSCRIBE_{}", name.to_uppercase()), self.get(name).to_string()))
+            .collect()
+    }
+
+    fn set(&mut self, name: &str, value: &str) {
+        if let Some((name, _)) = VARIABLES.iter().find(|(known, _)| *known == name) {
+            self.values.insert(name, value.to_string());
+        }
+    }
+
+    fn set_duration(&mut self, secs: f64) {
+        self.set("duration", &crate::utils::format_duration(secs));
+        self.set("duration_secs", &(secs.round() as u64).to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extractors::AudioFormat;
+
+    #[test]
+    fn test_expand() {
+        let audio_info = AudioInfo {
+            download_url: "https://cdn.example.com/a.mp3".to_string(),
+            download_method: crate::extractors::DownloadMethod::DirectUrl,
+            duration: Some(chrono::Duration::seconds(3723)),
+            title: Some("Keynote".to_string()),
+            format: AudioFormat::Mp3,
+            sample_rate: None,
+            file_size: None,
+            original_url: "https://example.com/talk".to_string(),
+            chapters: Vec::new(),
+        };
+        let vars = TemplateVars::from_audio(&audio_info).with_output(Path::new("/tmp/talk.srt"));
+
+        assert_eq!(
+            vars.expand("{title} ({duration}, {duration_secs}s) {status} -> {output}{language} {unknown}"),
+            "Keynote (1h 2m 3s, 3723s) processing -> /tmp/talk.srt {unknown}"
+        );
+        assert!(vars.env().contains(&("RUSTSCRIBE_URL".to_string(), "https://example.com/talk".to_string())));
+    }
+}
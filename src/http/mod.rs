@@ -0,0 +1,236 @@
+use anyhow::{Context, Result};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Settings for the HTTP client shared by extractors and the pipeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpConfig {
+    /// Seconds to wait for a connection to be established
+    pub connect_timeout_secs: u64,
+
+    /// Overall per-request timeout in seconds (unlimited if not set, as downloads can be large)
+    pub timeout_secs: Option<u64>,
+
+    /// Seconds an idle pooled connection is kept open
+    pub pool_idle_timeout_secs: u64,
+
+    /// Maximum idle connections kept per host
+    pub pool_max_idle_per_host: usize,
+
+    /// Let HTTP/2 connections grow their flow-control window for faster large downloads
+    pub http2_adaptive_window: bool,
+
+    /// Retries for requests that fail to connect or get a 429/5xx response
+    pub retries: u32,
+
+    /// User agent sent with every request
+    pub user_agent: Option<String>,
+
+    /// Proxy URL for HTTP requests only; prefer `network.proxy`, which also covers yt-dlp and ffmpeg
+    pub proxy: Option<String>,
+
+    /// Extra headers sent with every request
+    pub headers: BTreeMap<String, String>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: 30,
+            timeout_secs: None,
+            pool_idle_timeout_secs: 90,
+            pool_max_idle_per_host: 8,
+            http2_adaptive_window: true,
+            retries: 3,
+            user_agent: None,
+            proxy: None,
+            headers: BTreeMap::new(),
+        }
+    }
+}
+
+/// Proxy settings for everything that fetches media: the HTTP client, yt-dlp and ffmpeg
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// Proxy URL (e.g. `http://proxy:3128` or `socks5://127.0.0.1:1080`);
+    /// falls back to the HTTPS_PROXY/HTTP_PROXY/ALL_PROXY environment variables
+    pub proxy: Option<String>,
+
+    /// Comma-separated hosts that bypass the proxy (e.g. `localhost,.internal.example.com`)
+    pub no_proxy: Option<String>,
+}
+
+/// Proxy environment variables, in order of preference
+const PROXY_ENV_VARS: &[&str] = &["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"];
+
+impl NetworkConfig {
+    /// The configured proxy, or the one set in the environment
+    pub fn proxy_url(&self) -> Option<String> {
+        self.proxy.clone().or_else(|| {
+            PROXY_ENV_VARS
+                .iter()
+                .filter_map(|name| std::env::var(name).ok())
+                .find(|value| !value.trim().is_empty())
+        })
+    }
+
+    /// Arguments that send yt-dlp through the proxy
+    pub fn yt_dlp_args(&self) -> Vec<String> {
+        self.proxy_url()
+            .map(|proxy| vec!["--proxy".to_string(), proxy])
+            .unwrap_or_default()
+    }
+
+    /// ffmpeg input options for the proxy; ffmpeg only speaks to HTTP proxies
+    pub fn ffmpeg_args(&self) -> Vec<String> {
+        match self.proxy_url() {
+            Some(proxy) if proxy.starts_with("http://") => vec!["-http_proxy".to_string(), proxy],
+            Some(proxy) => {
+                tracing::warn!("ffmpeg cannot use proxy {}; only http:// proxies are supported for streams", proxy);
+                Vec::new()
+            }
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Build the shared client from the HTTP and proxy settings
+///
+/// Without a configured proxy or `no_proxy` list, reqwest picks up the proxy environment variables itself.
+pub fn build_client(config: &HttpConfig, network: &NetworkConfig) -> Result<Client> {
+    let user_agent = config
+        .user_agent
+        .clone()
+        .unwrap_or_else(|| format!("rustscribe/{}", env!("CARGO_PKG_VERSION")));
+
+    let mut headers = HeaderMap::new();
+    for (name, value) in &config.headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .with_context(|| format!("Invalid HTTP header name: {}", name))?;
+        let value = HeaderValue::from_str(value)
+            .with_context(|| format!("Invalid value for HTTP header {}", name))?;
+        headers.insert(name, value);
+    }
+
+    let mut builder = Client::builder()
+        .user_agent(user_agent)
+        .default_headers(headers)
+        .connect_timeout(Duration::from_secs(config.connect_timeout_secs))
+        .pool_idle_timeout(Duration::from_secs(config.pool_idle_timeout_secs))
+        .pool_max_idle_per_host(config.pool_max_idle_per_host)
+        .http2_adaptive_window(config.http2_adaptive_window)
+        .tcp_keepalive(Duration::from_secs(60));
+
+    if let Some(timeout) = config.timeout_secs {
+        builder = builder.timeout(Duration::from_secs(timeout));
+    }
+
+    let proxy = match (&network.proxy, &config.proxy) {
+        (Some(proxy), _) | (None, Some(proxy)) => Some(proxy.clone()),
+        (None, None) if network.no_proxy.is_some() => network.proxy_url(),
+        (None, None) => None,
+    };
+    if let Some(proxy) = &proxy {
+        let no_proxy = network
+            .no_proxy
+            .as_deref()
+            .and_then(reqwest::NoProxy::from_string)
+            .or_else(reqwest::NoProxy::from_env);
+        let proxy = reqwest::Proxy::all(proxy)
+            .with_context(|| format!("Invalid proxy URL: {}", proxy))?
+            .no_proxy(no_proxy);
+        builder = builder.proxy(proxy);
+    }
+
+    builder.build().context("Failed to build HTTP client")
+}
+
+/// Build a client for posting to third-party services such as webhooks and Slack
+///
+/// The `http.headers` are meant for media downloads (a gated CDN's token, say), so they are left out.
+pub fn build_service_client(config: &HttpConfig, network: &NetworkConfig) -> Result<Client> {
+    let config = HttpConfig { headers: BTreeMap::new(), ..config.clone() };
+    build_client(&config, network)
+}
+
+/// Send a request, retrying connection failures and 429/5xx responses with backoff
+pub async fn send_with_retry(request: RequestBuilder, retries: u32) -> Result<Response> {
+    let mut attempt = 0;
+
+    loop {
+        // Streaming bodies cannot be replayed, so those requests are sent once
+        let Some(this_try) = request.try_clone() else {
+            return Ok(request.send().await?);
+        };
+
+        let retryable = match this_try.send().await {
+            Ok(response) if !is_retryable_status(response.status()) => return Ok(response),
+            Ok(response) if attempt >= retries => return Ok(response),
+            Err(e) if attempt >= retries || !(e.is_connect() || e.is_timeout()) => return Err(e.into()),
+            Ok(response) => format!("HTTP {}", response.status()),
+            Err(e) => e.to_string(),
+        };
+
+        attempt += 1;
+        let delay = Duration::from_millis(500 << attempt);
+        tracing::warn!("Request failed ({}), retrying in {:?} ({}/{})", retryable, delay, attempt, retries);
+        tokio::time::sleep(delay).await;
+    }
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_client_rejects_invalid_settings() {
+        let network = NetworkConfig::default();
+        assert!(build_client(&HttpConfig::default(), &network).is_ok());
+
+        let mut config = HttpConfig::default();
+        config.headers.insert("Bad Header".to_string(), "x".to_string());
+        assert!(build_client(&config, &network).is_err());
+        // Download headers never reach third-party services
+        assert!(build_service_client(&config, &network).is_ok());
+
+        let network = NetworkConfig {
+            proxy: Some("not a url".to_string()),
+            no_proxy: None,
+        };
+        assert!(build_client(&HttpConfig::default(), &network).is_err());
+    }
+
+    #[test]
+    fn test_proxy_args() {
+        let network = NetworkConfig {
+            proxy: Some("http://proxy:3128".to_string()),
+            no_proxy: None,
+        };
+        assert_eq!(network.yt_dlp_args(), ["--proxy", "http://proxy:3128"]);
+        assert_eq!(network.ffmpeg_args(), ["-http_proxy", "http://proxy:3128"]);
+
+        let network = NetworkConfig {
+            proxy: Some("socks5://127.0.0.1:1080".to_string()),
+            no_proxy: None,
+        };
+        assert_eq!(network.yt_dlp_args(), ["--proxy", "socks5://127.0.0.1:1080"]);
+        assert!(network.ffmpeg_args().is_empty());
+    }
+
+    #[test]
+    fn test_retryable_status() {
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::BAD_GATEWAY));
+        assert!(!is_retryable_status(StatusCode::NOT_FOUND));
+        assert!(!is_retryable_status(StatusCode::NOT_MODIFIED));
+    }
+}
@@ -0,0 +1,156 @@
+use anyhow::{Context, Result};
+use aws_sdk_transcribe::types::{TranscriptionJob, TranscriptionJobStatus, TranscriptionJobSummary};
+use aws_sdk_transcribe::Client as TranscribeClient;
+use chrono::{DateTime, Utc};
+
+use super::JOB_NAME_PREFIX;
+
+/// What `jobs list` and `jobs status` show about a Transcribe job
+#[derive(Debug, Clone)]
+pub struct JobInfo {
+    pub name: String,
+    pub status: String,
+    pub created: Option<DateTime<Utc>>,
+    pub started: Option<DateTime<Utc>>,
+    pub completed: Option<DateTime<Utc>>,
+    pub language: Option<String>,
+
+    /// Audio the job reads (only known for single jobs, not in listings)
+    pub media_uri: Option<String>,
+    pub failure_reason: Option<String>,
+}
+
+fn timestamp(time: Option<&aws_sdk_transcribe::primitives::DateTime>) -> Option<DateTime<Utc>> {
+    time.and_then(|t| DateTime::from_timestamp(t.secs(), t.subsec_nanos()))
+}
+
+impl From<&TranscriptionJobSummary> for JobInfo {
+    fn from(job: &TranscriptionJobSummary) -> Self {
+        Self {
+            name: job.transcription_job_name().unwrap_or_default().to_string(),
+            status: job.transcription_job_status().map_or("UNKNOWN", |s| s.as_str()).to_string(),
+            created: timestamp(job.creation_time()),
+            started: timestamp(job.start_time()),
+            completed: timestamp(job.completion_time()),
+            language: job.language_code().map(|l| l.as_str().to_string()),
+            media_uri: None,
+            failure_reason: job.failure_reason().map(str::to_string),
+        }
+    }
+}
+
+impl From<&TranscriptionJob> for JobInfo {
+    fn from(job: &TranscriptionJob) -> Self {
+        Self {
+            name: job.transcription_job_name().unwrap_or_default().to_string(),
+            status: job.transcription_job_status().map_or("UNKNOWN", |s| s.as_str()).to_string(),
+            created: timestamp(job.creation_time()),
+            started: timestamp(job.start_time()),
+            completed: timestamp(job.completion_time()),
+            language: job.language_code().map(|l| l.as_str().to_string()),
+            media_uri: job.media().and_then(|m| m.media_file_uri()).map(str::to_string),
+            failure_reason: job.failure_reason().map(str::to_string),
+        }
+    }
+}
+
+/// Lists, inspects and cancels the Transcribe jobs this tool started
+pub struct JobManager {
+    client: TranscribeClient,
+}
+
+impl JobManager {
+    pub fn new(client: TranscribeClient) -> Self {
+        Self { client }
+    }
+
+    /// Our jobs, newest first, optionally only those with one status
+    pub async fn list(&self, status: Option<TranscriptionJobStatus>, limit: usize) -> Result<Vec<JobInfo>> {
+        let mut jobs = Vec::new();
+        let mut next_token = None;
+        loop {
+            let response = self
+                .client
+                .list_transcription_jobs()
+                .job_name_contains(JOB_NAME_PREFIX)
+                .set_status(status.clone())
+                .set_next_token(next_token)
+                .max_results(100)
+                .send()
+                .await
+                .context("Failed to list transcription jobs")?;
+
+            // The filter matches anywhere in the name; only our own prefix counts
+            jobs.extend(
+                response
+                    .transcription_job_summaries()
+                    .iter()
+                    .filter(|job| job.transcription_job_name().is_some_and(|name| name.starts_with(JOB_NAME_PREFIX)))
+                    .map(JobInfo::from),
+            );
+
+            next_token = response.next_token().map(str::to_string);
+            if next_token.is_none() || jobs.len() >= limit {
+                break;
+            }
+        }
+
+        jobs.truncate(limit);
+        Ok(jobs)
+    }
+
+    /// Details of one job
+    pub async fn status(&self, name: &str) -> Result<JobInfo> {
+        let response = self
+            .client
+            .get_transcription_job()
+            .transcription_job_name(name)
+            .send()
+            .await
+            .with_context(|| format!("Failed to get transcription job {}", name))?;
+
+        response
+            .transcription_job()
+            .map(JobInfo::from)
+            .context("No transcription job in response")
+    }
+
+    /// Delete a job; AWS has no way to stop one, so a running job finishes unseen
+    pub async fn cancel(&self, name: &str) -> Result<()> {
+        if !name.starts_with(JOB_NAME_PREFIX) {
+            anyhow::bail!("'{}' was not started by RustScribe (its jobs are named {}…)", name, JOB_NAME_PREFIX);
+        }
+
+        self.client
+            .delete_transcription_job()
+            .transcription_job_name(name)
+            .send()
+            .await
+            .with_context(|| format!("Failed to delete transcription job {}", name))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_transcribe::types::LanguageCode;
+
+    #[test]
+    fn test_job_info_from_summary() {
+        let summary = TranscriptionJobSummary::builder()
+            .transcription_job_name("transcriptor_1234")
+            .transcription_job_status(TranscriptionJobStatus::Failed)
+            .creation_time(aws_sdk_transcribe::primitives::DateTime::from_secs(1_700_000_000))
+            .language_code(LanguageCode::EnUs)
+            .failure_reason("Unsupported media format")
+            .build();
+
+        let info = JobInfo::from(&summary);
+        assert_eq!(info.name, "transcriptor_1234");
+        assert_eq!(info.status, "FAILED");
+        assert_eq!(info.created.unwrap().timestamp(), 1_700_000_000);
+        assert_eq!(info.language.as_deref(), Some("en-US"));
+        assert!(info.completed.is_none());
+    }
+}
@@ -0,0 +1,152 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::extractors::AudioInfo;
+use crate::queue::OutputTarget;
+use crate::transcribe::TranscribeOptions;
+
+pub mod manager;
+
+/// Every Transcribe job this tool starts is named with this prefix
+pub const JOB_NAME_PREFIX: &str = "transcriptor_";
+
+/// An AWS Transcribe job that was started but whose result has not been collected yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InFlightJob {
+    /// Transcribe job name
+    pub job_name: String,
+
+    /// When the job was started
+    pub started_at: chrono::DateTime<chrono::Utc>,
+
+    /// Original input (URL or file path)
+    pub input: String,
+
+    /// Our upload the job reads from, removed once the job is done
+    pub s3_key: Option<String>,
+
+    /// Audio information from extraction
+    pub audio_info: AudioInfo,
+
+    /// Transcription options
+    pub options: TranscribeOptions,
+
+    /// Where the run was going to write its output, if the command said
+    #[serde(default)]
+    pub output: Option<OutputTarget>,
+}
+
+/// Jobs still running on AWS, one JSON file per job, so `resume` can collect them later
+pub struct JobStore {
+    dir: PathBuf,
+}
+
+impl JobStore {
+    /// Open the store in the application data directory
+    pub fn open() -> Result<Self> {
+        Ok(Self::at(Config::data_dir()?.join("jobs")))
+    }
+
+    /// Open a store kept in a specific directory
+    pub fn at(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn path(&self, job_name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", job_name))
+    }
+
+    /// Remember a started job
+    pub fn save(&self, job: &InFlightJob) -> Result<()> {
+        fs_err::create_dir_all(&self.dir)?;
+        let content = serde_json::to_string_pretty(job)?;
+        fs_err::write(self.path(&job.job_name), content).context("Failed to save job state")
+    }
+
+    /// A remembered job by name
+    pub fn get(&self, job_name: &str) -> Result<InFlightJob> {
+        let path = self.path(job_name);
+        if !path.exists() {
+            anyhow::bail!("No interrupted job named '{}' (see `rustscribe resume --list`)", job_name);
+        }
+        let content = fs_err::read_to_string(&path)?;
+        serde_json::from_str(&content).with_context(|| format!("Corrupt job state {}", path.display()))
+    }
+
+    /// Remembered jobs, oldest first
+    pub fn list(&self) -> Result<Vec<InFlightJob>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut jobs = Vec::new();
+        for entry in fs_err::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+
+            let content = fs_err::read_to_string(&path)?;
+            match serde_json::from_str::<InFlightJob>(&content) {
+                Ok(job) => jobs.push(job),
+                Err(e) => tracing::warn!("Ignoring corrupt job state {}: {}", path.display(), e),
+            }
+        }
+
+        jobs.sort_by_key(|job| job.started_at);
+        Ok(jobs)
+    }
+
+    /// Forget a job once its result was collected or it cannot be
+    pub fn remove(&self, job_name: &str) -> Result<()> {
+        let path = self.path(job_name);
+        if path.exists() {
+            fs_err::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extractors::{AudioFormat, DownloadMethod};
+
+    #[test]
+    fn test_save_list_remove() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = JobStore::at(dir.path().join("jobs"));
+        assert!(store.list().unwrap().is_empty());
+
+        let job = InFlightJob {
+            job_name: "transcriptor_1234".to_string(),
+            started_at: chrono::Utc::now(),
+            input: "https://youtu.be/dQw4w9WgXcQ".to_string(),
+            s3_key: Some("transcriptor/audio_1234.mp3".to_string()),
+            audio_info: AudioInfo {
+                download_url: "https://youtu.be/dQw4w9WgXcQ".to_string(),
+                download_method: DownloadMethod::YtDlp,
+                duration: None,
+                title: Some("Video".to_string()),
+                format: AudioFormat::Mp3,
+                sample_rate: None,
+                file_size: None,
+                original_url: "https://youtu.be/dQw4w9WgXcQ".to_string(),
+                chapters: Vec::new(),
+            },
+            options: TranscribeOptions::default(),
+            output: None,
+        };
+        store.save(&job).unwrap();
+
+        let jobs = store.list().unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(store.get("transcriptor_1234").unwrap().s3_key, job.s3_key);
+
+        store.remove("transcriptor_1234").unwrap();
+        assert!(store.list().unwrap().is_empty());
+        assert!(store.get("transcriptor_1234").is_err());
+    }
+}
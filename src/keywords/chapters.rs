@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+
+use super::rake;
+use crate::extractors::Chapter;
+use crate::postprocess::paragraphs::{group_paragraphs, paragraph_text};
+use crate::transcribe::TranscriptionResult;
+
+/// Chapters aimed for per second of audio (one per five minutes)
+const SECONDS_PER_CHAPTER: f64 = 300.0;
+
+/// Most chapters generated for one recording
+const MAX_CHAPTERS: usize = 12;
+
+/// YouTube ignores chapter lists with chapters shorter than this
+const MIN_CHAPTER_SECS: f64 = 10.0;
+
+/// Paragraphs compared on each side of a possible chapter break
+const WINDOW: usize = 3;
+
+/// Split a transcript into topical chapters titled with their most repeated phrase
+///
+/// Paragraphs are compared by the words they share (TextTiling): breaks go where the
+/// vocabulary changes most, keeping chapters at least a fraction of the average length.
+/// The first chapter starts at 0:00, as YouTube requires.
+pub fn auto_chapters(result: &TranscriptionResult) -> Vec<Chapter> {
+    let paragraphs = group_paragraphs(&result.segments, &[]);
+    let Some(end) = result.segments.last().map(|s| s.end_time) else {
+        return Vec::new();
+    };
+    let stopwords = rake::stopwords(&result.metadata.language);
+
+    let texts: Vec<String> = paragraphs.iter().map(|p| paragraph_text(p)).collect();
+    let bags: Vec<HashMap<String, f64>> = texts.iter().map(|text| word_counts(text, stopwords)).collect();
+
+    // Similarity of the windows before and after each gap between paragraphs
+    let similarities: Vec<f64> = (1..paragraphs.len())
+        .map(|gap| {
+            let before = merge(&bags[gap.saturating_sub(WINDOW)..gap]);
+            let after = merge(&bags[gap..(gap + WINDOW).min(bags.len())]);
+            cosine(&before, &after)
+        })
+        .collect();
+    // How far each gap dips below the peaks around it
+    let depths: Vec<f64> = (0..similarities.len())
+        .map(|i| {
+            let left = similarities[..=i].iter().rev().copied().fold(similarities[i], f64::max);
+            let right = similarities[i..].iter().copied().fold(similarities[i], f64::max);
+            (left - similarities[i]) + (right - similarities[i])
+        })
+        .collect();
+
+    let target = ((end / SECONDS_PER_CHAPTER).round() as usize).clamp(1, MAX_CHAPTERS);
+    let min_length = (end / target as f64 / 2.0).max(MIN_CHAPTER_SECS);
+    let is_valley = |i: usize| {
+        (i == 0 || similarities[i] <= similarities[i - 1])
+            && similarities.get(i + 1).is_none_or(|&next| similarities[i] <= next)
+    };
+    let mut gaps: Vec<usize> = (0..depths.len()).filter(|&i| depths[i] > 0.0 && is_valley(i)).collect();
+    gaps.sort_by(|&a, &b| depths[b].total_cmp(&depths[a]));
+
+    let mut starts = vec![0.0];
+    for gap in gaps {
+        if starts.len() >= target {
+            break;
+        }
+        let start = paragraphs[gap + 1][0].start_time;
+        let fits = starts.iter().all(|&other: &f64| (other - start).abs() >= min_length) && end - start >= min_length;
+        if fits {
+            starts.push(start);
+        }
+    }
+    starts.sort_by(f64::total_cmp);
+
+    let mut used: Vec<String> = Vec::new();
+    starts
+        .iter()
+        .enumerate()
+        .map(|(index, &start_time)| {
+            let end_time = starts.get(index + 1).copied().unwrap_or(end);
+            let text: Vec<&str> = paragraphs
+                .iter()
+                .zip(&texts)
+                .filter(|(p, _)| p[0].start_time >= start_time && p[0].start_time < end_time)
+                .map(|(_, text)| text.as_str())
+                .collect();
+            let title = title(&text.join(" "), stopwords, &used).unwrap_or_else(|| format!("Part {}", index + 1));
+            used.push(title.to_lowercase());
+            Chapter { title, start_time, end_time: Some(end_time) }
+        })
+        .collect()
+}
+
+/// The chapter's most repeated two-word phrase, or else its most repeated word, capitalised
+///
+/// Phrases already used as a title are skipped; ties go to the earliest.
+fn title(text: &str, stopwords: &[&str], used: &[String]) -> Option<String> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    let mut count = |phrase: String| match counts.iter_mut().find(|(p, _)| *p == phrase) {
+        Some((_, n)) => *n += 1,
+        None => counts.push((phrase, 1)),
+    };
+
+    let mut previous: Option<String> = None;
+    for token in text.split_whitespace() {
+        let word = token.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase();
+        let is_content = word.chars().count() > 2 && !stopwords.contains(&word.as_str());
+        if !is_content {
+            previous = None;
+            continue;
+        }
+        if let Some(previous) = &previous {
+            count(format!("{} {}", previous, word));
+        }
+        count(word.clone());
+        let ends_phrase = token.ends_with(|c: char| !c.is_alphanumeric());
+        previous = (!ends_phrase).then_some(word);
+    }
+
+    let best = |two_words: bool, min: usize| {
+        counts
+            .iter()
+            .filter(|(phrase, n)| phrase.contains(' ') == two_words && *n >= min && !used.contains(phrase))
+            .fold(None, |best: Option<&(String, usize)>, entry| match best {
+                Some(best) if best.1 >= entry.1 => Some(best),
+                _ => Some(entry),
+            })
+            .map(|(phrase, _)| phrase.clone())
+    };
+    let phrase = best(true, 2).or_else(|| best(false, 1))?;
+
+    let mut chars = phrase.chars();
+    chars.next().map(|first| first.to_uppercase().chain(chars).collect())
+}
+
+fn word_counts(text: &str, stopwords: &[&str]) -> HashMap<String, f64> {
+    let mut counts = HashMap::new();
+    for token in text.split_whitespace() {
+        let word = token.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase();
+        if word.chars().count() > 2 && !stopwords.contains(&word.as_str()) {
+            *counts.entry(word).or_default() += 1.0;
+        }
+    }
+    counts
+}
+
+fn merge(bags: &[HashMap<String, f64>]) -> HashMap<String, f64> {
+    let mut merged = HashMap::new();
+    for bag in bags {
+        for (word, count) in bag {
+            *merged.entry(word.clone()).or_default() += count;
+        }
+    }
+    merged
+}
+
+fn cosine(a: &HashMap<String, f64>, b: &HashMap<String, f64>) -> f64 {
+    let dot: f64 = a.iter().filter_map(|(word, x)| b.get(word).map(|y| x * y)).sum();
+    let norm = |bag: &HashMap<String, f64>| bag.values().map(|x| x * x).sum::<f64>().sqrt();
+    match norm(a) * norm(b) {
+        0.0 => 0.0,
+        product => dot / product,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_chapters() {
+        let mut result = TranscriptionResult {
+            transcript: String::new(),
+            words: None,
+            ..crate::demo::result("talk")
+        };
+        let topics = [
+            "Sourdough bread needs a starter. Feed the sourdough starter flour and water daily.",
+            "Bake the sourdough bread hot. The bread crust darkens as the sourdough bakes.",
+            "Tomato plants want sun. Water tomato plants deeply and stake the tomato plants.",
+            "Prune tomato plants weekly. Ripe tomato plants give sweet tomatoes.",
+        ];
+        let template = result.segments[0].clone();
+        result.segments = topics
+            .iter()
+            .enumerate()
+            .map(|(index, text)| crate::transcribe::TranscriptSegment {
+                start_time: index as f64 * 300.0,
+                end_time: index as f64 * 300.0 + 290.0,
+                text: text.to_string(),
+                ..template.clone()
+            })
+            .collect();
+
+        let chapters = auto_chapters(&result);
+        let chapters: Vec<(&str, f64)> = chapters.iter().map(|c| (c.title.as_str(), c.start_time)).collect();
+        assert_eq!(chapters, [("Sourdough bread", 0.0), ("Tomato plants", 600.0)]);
+    }
+}
@@ -0,0 +1,235 @@
+//! Key phrases, named entities (`--extract-keywords`) and topical chapters (`--chapters auto`)
+
+use anyhow::{Context, Result};
+use aws_sdk_comprehend::types::LanguageCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::transcribe::{Entity, Keyword, KeywordMethod, Keywords, TranscriptionResult};
+
+pub mod chapters;
+pub mod rake;
+
+/// Comprehend takes at most 100 KB of UTF-8 per request
+const COMPREHEND_MAX_BYTES: usize = 90_000;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Languages Comprehend detects key phrases and entities in
+const COMPREHEND_LANGUAGES: &[&str] = &["en", "es", "fr", "de", "it", "pt", "ar", "hi", "ja", "ko", "zh", "zh-TW"];
+
+/// How many key phrases and entities `--extract-keywords` keeps
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeywordsConfig {
+    /// Most key phrases kept
+    pub max_keywords: usize,
+
+    /// Most named entities kept
+    pub max_entities: usize,
+
+    /// Region Comprehend is called in (defaults to aws.region)
+    pub region: Option<String>,
+}
+
+impl Default for KeywordsConfig {
+    fn default() -> Self {
+        Self {
+            max_keywords: 20,
+            max_entities: 30,
+            region: None,
+        }
+    }
+}
+
+impl KeywordsConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_keywords == 0 {
+            anyhow::bail!("keywords.max_keywords must be at least 1");
+        }
+        Ok(())
+    }
+}
+
+/// Finds the key phrases and entities of transcripts
+pub struct KeywordExtractor {
+    settings: KeywordsConfig,
+    comprehend: Option<aws_sdk_comprehend::Client>,
+}
+
+impl KeywordExtractor {
+    pub async fn new(config: &Config, method: KeywordMethod) -> Result<Self> {
+        let settings = config.keywords.clone();
+        let comprehend = match method {
+            KeywordMethod::Local => None,
+            KeywordMethod::Comprehend if config.app.demo => {
+                anyhow::bail!("--extract-keywords=comprehend calls Amazon Comprehend, so it is not available with --demo")
+            }
+            KeywordMethod::Comprehend => {
+                let timeouts = aws_sdk_comprehend::config::timeout::TimeoutConfig::builder()
+                    .operation_timeout(REQUEST_TIMEOUT)
+                    .build();
+                let mut comprehend = aws_sdk_comprehend::config::Builder::from(&config.aws_sdk_config().await)
+                    .timeout_config(timeouts);
+                if let Some(region) = &settings.region {
+                    comprehend = comprehend.region(aws_types::region::Region::new(region.clone()));
+                }
+                Some(aws_sdk_comprehend::Client::from_conf(comprehend.build()))
+            }
+        };
+
+        Ok(Self { settings, comprehend })
+    }
+
+    pub async fn extract(&self, result: &TranscriptionResult) -> Result<Keywords> {
+        let language = &result.metadata.language;
+        let Some(client) = &self.comprehend else {
+            return Ok(Keywords {
+                method: KeywordMethod::Local,
+                keywords: rake::extract(&result.transcript, language, self.settings.max_keywords),
+                entities: Vec::new(),
+            });
+        };
+
+        let language = comprehend_language(language).with_context(|| {
+            format!("Comprehend does not support {}; use --extract-keywords=local", language)
+        })?;
+        let language = LanguageCode::from(language);
+        let mut phrases = Vec::new();
+        let mut entities = Vec::new();
+        for chunk in chunks(&result.transcript, COMPREHEND_MAX_BYTES) {
+            let response = client
+                .detect_key_phrases()
+                .text(chunk)
+                .language_code(language.clone())
+                .send()
+                .await
+                .context("Comprehend DetectKeyPhrases failed")?;
+            phrases.extend(response.key_phrases().iter().map(|phrase| Detected {
+                text: phrase.text().unwrap_or_default().to_string(),
+                score: phrase.score().unwrap_or_default().into(),
+                entity_type: String::new(),
+            }));
+
+            let response = client
+                .detect_entities()
+                .text(chunk)
+                .language_code(language.clone())
+                .send()
+                .await
+                .context("Comprehend DetectEntities failed")?;
+            entities.extend(response.entities().iter().map(|entity| Detected {
+                text: entity.text().unwrap_or_default().to_string(),
+                score: entity.score().unwrap_or_default().into(),
+                entity_type: entity.r#type().map(|kind| kind.as_str().to_string()).unwrap_or_default(),
+            }));
+        }
+
+        Ok(Keywords {
+            method: KeywordMethod::Comprehend,
+            keywords: rank_phrases(phrases, self.settings.max_keywords),
+            entities: rank_entities(entities, self.settings.max_entities),
+        })
+    }
+}
+
+/// A key phrase or entity in a Comprehend response
+struct Detected {
+    text: String,
+    score: f64,
+    /// Empty for key phrases
+    entity_type: String,
+}
+
+/// Comprehend's code for a language code such as `en-US` or `zh-TW`
+fn comprehend_language(code: &str) -> Option<&'static str> {
+    if code.eq_ignore_ascii_case("zh-TW") {
+        return Some("zh-TW");
+    }
+    let primary = code.split(['-', '_']).next().unwrap_or(code).to_lowercase();
+    COMPREHEND_LANGUAGES.iter().copied().find(|language| *language == primary)
+}
+
+/// Cut text into pieces of at most `max_bytes`, at whitespace where there is some
+fn chunks(text: &str, max_bytes: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut rest = text.trim();
+    while rest.len() > max_bytes {
+        let mut cut = max_bytes;
+        while !rest.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        let cut = rest[..cut].rfind(char::is_whitespace).filter(|&space| space > 0).unwrap_or(cut);
+        pieces.push(&rest[..cut]);
+        rest = rest[cut..].trim_start();
+    }
+    if !rest.is_empty() {
+        pieces.push(rest);
+    }
+    pieces
+}
+
+/// Merge repeated key phrases, most frequent first
+fn rank_phrases(phrases: Vec<Detected>, max: usize) -> Vec<Keyword> {
+    let mut merged: HashMap<String, Keyword> = HashMap::new();
+    for phrase in phrases {
+        let keyword = merged.entry(phrase.text.to_lowercase()).or_insert_with(|| Keyword {
+            text: phrase.text.clone(),
+            score: 0.0,
+            count: 0,
+        });
+        keyword.score = keyword.score.max(phrase.score);
+        keyword.count += 1;
+    }
+
+    let mut keywords: Vec<Keyword> = merged.into_values().collect();
+    keywords.sort_by(|a, b| b.count.cmp(&a.count).then(b.score.total_cmp(&a.score)).then_with(|| a.text.cmp(&b.text)));
+    keywords.truncate(max);
+    keywords
+}
+
+/// Merge repeated mentions of an entity, most mentioned first
+fn rank_entities(detected: Vec<Detected>, max: usize) -> Vec<Entity> {
+    let mut merged: HashMap<(String, String), Entity> = HashMap::new();
+    for mention in detected {
+        let key = (mention.entity_type.clone(), mention.text.to_lowercase());
+        let entity = merged.entry(key).or_insert_with(|| Entity {
+            text: mention.text.clone(),
+            entity_type: mention.entity_type.clone(),
+            score: 0.0,
+            count: 0,
+        });
+        entity.score = entity.score.max(mention.score);
+        entity.count += 1;
+    }
+
+    let mut entities: Vec<Entity> = merged.into_values().collect();
+    entities.sort_by(|a, b| b.count.cmp(&a.count).then(b.score.total_cmp(&a.score)).then_with(|| a.text.cmp(&b.text)));
+    entities.truncate(max);
+    entities
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks_and_ranking() {
+        assert_eq!(chunks("one two three four", 9), ["one two", "three", "four"]);
+        assert_eq!(chunks("  héllo ", 90), ["héllo"]);
+        assert_eq!(comprehend_language("pt-BR"), Some("pt"));
+        assert_eq!(comprehend_language("sv-SE"), None);
+
+        let mention = |text: &str, entity_type: &str, score: f64| Detected {
+            text: text.to_string(),
+            score,
+            entity_type: entity_type.to_string(),
+        };
+        let detected = vec![mention("Alice", "PERSON", 0.9), mention("Acme", "ORGANIZATION", 0.8), mention("alice", "PERSON", 0.95)];
+        let entities = rank_entities(detected, 10);
+        assert_eq!(entities[0], Entity { text: "Alice".to_string(), entity_type: "PERSON".to_string(), score: 0.95, count: 2 });
+        assert_eq!(entities[1].text, "Acme");
+    }
+}
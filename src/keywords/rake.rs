@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use crate::transcribe::Keyword;
+
+/// Words that separate candidate phrases, per primary language code
+const STOPWORDS: &[(&str, &[&str])] = &[
+    ("en", &[
+        "a", "about", "after", "again", "all", "also", "am", "an", "and", "any", "are", "as", "at", "be",
+        "because", "been", "before", "being", "but", "by", "can", "could", "did", "do", "does", "doing",
+        "don't", "down", "each", "even", "few", "for", "from", "get", "go", "going", "got", "had", "has",
+        "have", "he", "her", "here", "him", "his", "how", "i", "i'm", "if", "in", "into", "is", "it", "it's",
+        "its", "just", "know", "let's", "like", "me", "more", "most", "my", "no", "not", "now", "of", "off",
+        "oh", "ok", "okay", "on", "one", "only", "or", "other", "our", "out", "over", "really", "right",
+        "said", "say", "see", "she", "should", "so", "some", "still", "such", "than", "that", "that's", "the",
+        "their", "them", "then", "there", "there's", "these", "they", "thing", "things", "think", "this",
+        "those", "through", "to", "too", "um", "uh", "up", "us", "very", "want", "was", "way", "we", "we're",
+        "well", "were", "what", "when", "where", "which", "while", "who", "why", "will", "with", "would",
+        "yeah", "yes", "you", "you're", "your",
+    ]),
+    ("es", &[
+        "a", "al", "algo", "como", "con", "de", "del", "el", "ella", "en", "entonces", "es", "esa", "ese",
+        "eso", "esta", "este", "esto", "hay", "la", "las", "le", "lo", "los", "me", "mi", "muy", "no", "nos",
+        "o", "para", "pero", "por", "que", "qué", "se", "si", "sí", "sin", "su", "sus", "también", "te",
+        "tiene", "un", "una", "uno", "y", "ya", "yo",
+    ]),
+    ("fr", &[
+        "à", "au", "aux", "avec", "c'est", "ce", "ces", "dans", "de", "des", "du", "elle", "en", "est", "et",
+        "il", "ils", "je", "la", "le", "les", "leur", "mais", "me", "mon", "ne", "nous", "on", "ou", "où",
+        "par", "pas", "pour", "qu'il", "que", "qui", "sa", "se", "son", "sur", "très", "tu", "un", "une",
+        "vous", "y",
+    ]),
+    ("de", &[
+        "aber", "als", "am", "an", "auch", "auf", "aus", "bei", "das", "dass", "dem", "den", "der", "des",
+        "die", "du", "ein", "eine", "einen", "er", "es", "für", "hat", "ich", "ihr", "im", "in", "ist", "ja",
+        "mit", "nicht", "noch", "nur", "oder", "schon", "sie", "sind", "so", "und", "von", "war", "was", "wie",
+        "wir", "zu", "zum", "zur",
+    ]),
+];
+
+/// Stopwords of a language code such as `en-US`; English for languages without a list
+pub fn stopwords(language: &str) -> &'static [&'static str] {
+    let primary = language.split(['-', '_']).next().unwrap_or(language).to_lowercase();
+    STOPWORDS
+        .iter()
+        .find(|(code, _)| *code == primary)
+        .or_else(|| STOPWORDS.first())
+        .map(|(_, words)| *words)
+        .unwrap_or_default()
+}
+
+/// Candidate phrases longer than this are run-ons rather than key phrases
+const MAX_PHRASE_WORDS: usize = 4;
+
+/// Key phrases of a text with RAKE (Rapid Automatic Keyword Extraction)
+///
+/// Phrases are the runs of words between stopwords and punctuation. Each word scores its
+/// degree over its frequency, so words that keep company in longer phrases rank high, and
+/// a phrase scores the sum of its words.
+pub fn extract(text: &str, language: &str, max: usize) -> Vec<Keyword> {
+    let stopwords = stopwords(language);
+
+    let mut phrases: Vec<Vec<String>> = Vec::new();
+    let mut current: Vec<String> = Vec::new();
+    for token in text.split_whitespace() {
+        let word = token
+            .trim_matches(|c: char| !c.is_alphanumeric() && c != '\'')
+            .to_lowercase();
+        let is_word = word.chars().any(char::is_alphabetic) && !stopwords.contains(&word.as_str());
+        if is_word {
+            current.push(word);
+        }
+        let breaks = !is_word || token.ends_with(|c: char| !c.is_alphanumeric() && c != '\'');
+        if breaks && !current.is_empty() {
+            phrases.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        phrases.push(current);
+    }
+    phrases.retain(|phrase| {
+        phrase.len() <= MAX_PHRASE_WORDS && (phrase.len() > 1 || phrase[0].chars().count() > 2)
+    });
+
+    let mut frequency: HashMap<&str, f64> = HashMap::new();
+    let mut degree: HashMap<&str, f64> = HashMap::new();
+    for phrase in &phrases {
+        for word in phrase {
+            *frequency.entry(word).or_default() += 1.0;
+            *degree.entry(word).or_default() += phrase.len() as f64;
+        }
+    }
+
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for phrase in &phrases {
+        *counts.entry(phrase.join(" ")).or_default() += 1;
+    }
+    let mut keywords: Vec<Keyword> = counts
+        .into_iter()
+        .map(|(text, count)| {
+            let score = text.split(' ').map(|word| degree[word] / frequency[word]).sum();
+            Keyword { text, score, count }
+        })
+        .collect();
+    keywords.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(b.count.cmp(&a.count))
+            .then_with(|| a.text.cmp(&b.text))
+    });
+    keywords.truncate(max);
+    keywords
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract() {
+        let text = "Custom vocabulary helps with product names. We added a custom vocabulary \
+                    for the launch, and the product names now come out right. Speaker labels too.";
+        let keywords = extract(text, "en-US", 3);
+        let texts: Vec<&str> = keywords.iter().map(|k| k.text.as_str()).collect();
+        assert_eq!(texts, ["custom vocabulary helps", "custom vocabulary", "product names"]);
+        assert_eq!(keywords[1].count, 1);
+        assert_eq!(keywords[2].count, 2);
+    }
+}
@@ -0,0 +1,208 @@
+use anyhow::Result;
+use aws_sdk_transcribe::types::LanguageCode;
+use serde::Serialize;
+
+use crate::cli::Backend;
+
+/// Languages whisper.cpp knows, by the code it takes for `--language`
+const WHISPER: &[&str] = &[
+    "af", "am", "ar", "as", "az", "ba", "be", "bg", "bn", "bo", "br", "bs", "ca", "cs", "cy", "da", "de", "el", "en", "es",
+    "et", "eu", "fa", "fi", "fo", "fr", "gl", "gu", "ha", "haw", "he", "hi", "hr", "ht", "hu", "hy", "id", "is", "it", "ja",
+    "jw", "ka", "kk", "km", "kn", "ko", "la", "lb", "ln", "lo", "lt", "lv", "mg", "mi", "mk", "ml", "mn", "mr", "ms", "mt",
+    "my", "ne", "nl", "nn", "no", "oc", "pa", "pl", "ps", "pt", "ro", "ru", "sa", "sd", "si", "sk", "sl", "sn", "so", "sq",
+    "sr", "su", "sv", "sw", "ta", "te", "tg", "th", "tk", "tl", "tr", "tt", "uk", "ur", "uz", "vi", "yi", "yo", "yue", "zh",
+];
+
+/// English names of the languages either backend knows
+const NAMES: &[(&str, &str)] = &[
+    ("ab", "Abkhaz"), ("af", "Afrikaans"), ("am", "Amharic"), ("ar", "Arabic"), ("as", "Assamese"),
+    ("ast", "Asturian"), ("az", "Azerbaijani"), ("ba", "Bashkir"), ("be", "Belarusian"), ("bg", "Bulgarian"),
+    ("bn", "Bengali"), ("bo", "Tibetan"), ("br", "Breton"), ("bs", "Bosnian"), ("ca", "Catalan"),
+    ("ckb", "Central Kurdish"), ("cs", "Czech"), ("cy", "Welsh"), ("da", "Danish"), ("de", "German"),
+    ("el", "Greek"), ("en", "English"), ("es", "Spanish"), ("et", "Estonian"), ("eu", "Basque"),
+    ("fa", "Persian"), ("fi", "Finnish"), ("fo", "Faroese"), ("fr", "French"), ("ga", "Irish"),
+    ("gd", "Scottish Gaelic"), ("gl", "Galician"), ("gu", "Gujarati"), ("ha", "Hausa"), ("haw", "Hawaiian"),
+    ("he", "Hebrew"), ("hi", "Hindi"), ("hr", "Croatian"), ("ht", "Haitian Creole"), ("hu", "Hungarian"),
+    ("hy", "Armenian"), ("id", "Indonesian"), ("is", "Icelandic"), ("it", "Italian"), ("ja", "Japanese"),
+    ("jv", "Javanese"), ("jw", "Javanese"), ("ka", "Georgian"), ("kab", "Kabyle"), ("kk", "Kazakh"),
+    ("km", "Khmer"), ("kn", "Kannada"), ("ko", "Korean"), ("ky", "Kyrgyz"), ("la", "Latin"),
+    ("lb", "Luxembourgish"), ("lg", "Ganda"), ("ln", "Lingala"), ("lo", "Lao"), ("lt", "Lithuanian"),
+    ("lv", "Latvian"), ("mg", "Malagasy"), ("mhr", "Meadow Mari"), ("mi", "Maori"), ("mk", "Macedonian"),
+    ("ml", "Malayalam"), ("mn", "Mongolian"), ("mr", "Marathi"), ("ms", "Malay"), ("mt", "Maltese"),
+    ("my", "Burmese"), ("ne", "Nepali"), ("nl", "Dutch"), ("nn", "Norwegian Nynorsk"), ("no", "Norwegian"),
+    ("oc", "Occitan"), ("or", "Odia"), ("pa", "Punjabi"), ("pl", "Polish"), ("ps", "Pashto"),
+    ("pt", "Portuguese"), ("ro", "Romanian"), ("ru", "Russian"), ("rw", "Kinyarwanda"), ("sa", "Sanskrit"),
+    ("sd", "Sindhi"), ("si", "Sinhala"), ("sk", "Slovak"), ("sl", "Slovenian"), ("sn", "Shona"),
+    ("so", "Somali"), ("sq", "Albanian"), ("sr", "Serbian"), ("su", "Sundanese"), ("sv", "Swedish"),
+    ("sw", "Swahili"), ("ta", "Tamil"), ("te", "Telugu"), ("tg", "Tajik"), ("th", "Thai"),
+    ("tk", "Turkmen"), ("tl", "Tagalog"), ("tr", "Turkish"), ("tt", "Tatar"), ("ug", "Uyghur"),
+    ("uk", "Ukrainian"), ("ur", "Urdu"), ("uz", "Uzbek"), ("vi", "Vietnamese"), ("wo", "Wolof"),
+    ("yi", "Yiddish"), ("yo", "Yoruba"), ("yue", "Cantonese"), ("zh", "Chinese"), ("zu", "Zulu"),
+];
+
+/// A language code a backend accepts for `--language`
+#[derive(Debug, Clone, Serialize)]
+pub struct Language {
+    pub code: String,
+    pub name: String,
+}
+
+/// Every language code `backend` accepts, sorted by code
+///
+/// Hybrid runs send spans to AWS, so they take AWS codes whose language Whisper knows too.
+pub fn supported(backend: Backend) -> Vec<Language> {
+    let codes: Vec<&str> = match backend {
+        Backend::Aws => LanguageCode::values().to_vec(),
+        Backend::Whisper => WHISPER.to_vec(),
+        Backend::Hybrid => LanguageCode::values()
+            .iter()
+            .copied()
+            .filter(|code| WHISPER.contains(&base(code)))
+            .collect(),
+    };
+
+    let mut languages: Vec<Language> = codes
+        .into_iter()
+        .map(|code| Language {
+            code: code.to_string(),
+            name: name(code),
+        })
+        .collect();
+    languages.sort_by(|a, b| a.code.cmp(&b.code));
+    languages
+}
+
+/// The code `backend` expects for `language`, or an error naming the closest one it knows
+///
+/// Case does not matter, and common short forms ("en", "english", "en-UK") become full AWS codes.
+pub fn validate(language: &str, backend: Backend) -> Result<String> {
+    let supported = supported(backend);
+    let find = |code: &str| supported.iter().find(|l| l.code.eq_ignore_ascii_case(code)).map(|l| l.code.clone());
+
+    let language = language.trim();
+    let found = match backend {
+        // whisper.cpp only takes the language part of a code
+        Backend::Whisper if language.eq_ignore_ascii_case("auto") => Some("auto".to_string()),
+        Backend::Whisper => find(base(language)).map(|_| language.to_string()),
+        Backend::Aws | Backend::Hybrid => find(language)
+            .or_else(|| find(&crate::utils::normalize_language_code(language)))
+            // The UK's ISO code is GB
+            .or_else(|| find(&language.to_uppercase().replace("-UK", "-GB"))),
+    };
+    if let Some(code) = found {
+        return Ok(code);
+    }
+
+    let backend_name = match backend {
+        Backend::Aws => "AWS Transcribe",
+        Backend::Whisper => "Whisper",
+        Backend::Hybrid => "the hybrid backend",
+    };
+    let wanted = language.to_lowercase();
+    let closest = supported.iter().min_by_key(|l| {
+        edit_distance(&wanted, &l.code.to_lowercase()).min(edit_distance(&wanted, &l.name.to_lowercase()))
+    });
+    match closest {
+        Some(closest) => anyhow::bail!(
+            "{} does not support language '{}'; did you mean {} ({})? See `rustscribe languages`",
+            backend_name,
+            language,
+            closest.code,
+            closest.name
+        ),
+        None => anyhow::bail!("{} does not support language '{}'", backend_name, language),
+    }
+}
+
+/// The AWS codes auto-detection may choose from: `requested`, or else `configured`
+///
+/// AWS needs at least two; none means any language it supports.
+pub fn candidates(requested: &[String], configured: &[String]) -> Result<Vec<String>> {
+    let candidates = if requested.is_empty() { configured } else { requested };
+    if candidates.len() == 1 {
+        anyhow::bail!("Language candidates need at least two languages; use --language for one");
+    }
+    candidates.iter().map(|language| validate(language, Backend::Aws)).collect()
+}
+
+/// The AWS code for a language Whisper reports ("nl" becomes "nl-NL"), if AWS knows it
+///
+/// Where a language has several AWS codes, the one for the country it is named after wins.
+pub fn aws_code(language: &str) -> Option<String> {
+    if let Ok(code) = validate(language, Backend::Aws) {
+        return Some(code);
+    }
+    let codes: Vec<String> = supported(Backend::Aws)
+        .into_iter()
+        .map(|l| l.code)
+        .filter(|code| base(code).eq_ignore_ascii_case(base(language)))
+        .collect();
+    let home = format!("{}-{}", base(language), base(language)).to_lowercase();
+    codes.iter().find(|code| code.eq_ignore_ascii_case(&home)).or(codes.first()).cloned()
+}
+
+/// The language part of a code: "en" for "en-US"
+fn base(code: &str) -> &str {
+    code.split('-').next().unwrap_or(code)
+}
+
+/// "English (US)" for "en-US"
+fn name(code: &str) -> String {
+    let language = NAMES
+        .iter()
+        .find(|(prefix, _)| prefix.eq_ignore_ascii_case(base(code)))
+        .map_or(code, |(_, name)| name);
+    match code.split_once('-') {
+        Some((_, region)) => format!("{} ({})", language, region),
+        None => language.to_string(),
+    }
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_and_suggest() {
+        assert_eq!(validate("en-us", Backend::Aws).unwrap(), "en-US");
+        assert_eq!(validate("english", Backend::Aws).unwrap(), "en-US");
+        assert_eq!(validate("de-DE", Backend::Whisper).unwrap(), "de-DE");
+        assert_eq!(validate("auto", Backend::Whisper).unwrap(), "auto");
+
+        assert_eq!(validate("en-UK", Backend::Aws).unwrap(), "en-GB");
+
+        let error = validate("fr-FX", Backend::Aws).unwrap_err().to_string();
+        assert!(error.contains("did you mean fr-FR (French (FR))"), "{}", error);
+        let error = validate("Portugese", Backend::Whisper).unwrap_err().to_string();
+        assert!(error.contains("did you mean pt (Portuguese)"), "{}", error);
+        assert!(validate("ab-GE", Backend::Hybrid).is_err());
+
+        assert!(supported(Backend::Aws).iter().all(|l| !l.name.starts_with(|c: char| c.is_lowercase())));
+
+        let configured = vec!["en-US".to_string(), "fr-FR".to_string()];
+        assert_eq!(candidates(&[], &configured).unwrap(), configured);
+        assert_eq!(candidates(&["english".to_string(), "de".to_string()], &configured).unwrap(), ["en-US", "de-DE"]);
+        assert!(candidates(&["en-US".to_string()], &[]).is_err());
+
+        assert_eq!(aws_code("en").as_deref(), Some("en-US"));
+        assert_eq!(aws_code("nl").as_deref(), Some("nl-NL"));
+        assert_eq!(aws_code("sv").as_deref(), Some("sv-SE"));
+        assert_eq!(aws_code("haw"), None);
+    }
+}
@@ -3,17 +3,80 @@
 //! This library provides functionality to extract and transcribe audio from platforms like
 //! YouTube, Twitter/X, and direct media URLs using AWS Transcribe service.
 
+// Modules without the `pipeline` feature only deal with transcripts that already exist
+#[cfg(feature = "pipeline")]
+pub mod archive;
+#[cfg(feature = "pipeline")]
+pub mod cache;
+pub mod captions;
 pub mod cli;
+#[cfg(feature = "pipeline")]
 pub mod config;
+#[cfg(feature = "pipeline")]
+pub mod crash;
+#[cfg(feature = "pipeline")]
+pub mod demo;
+#[cfg(feature = "pipeline")]
+pub mod deps;
+#[cfg(feature = "pipeline")]
+pub mod domain;
+pub mod eval;
 pub mod extractors;
+#[cfg(feature = "pipeline")]
+pub mod health;
+#[cfg(feature = "pipeline")]
+pub mod history;
+#[cfg(feature = "pipeline")]
+pub mod hooks;
+#[cfg(feature = "pipeline")]
+pub mod http;
+#[cfg(feature = "pipeline")]
+pub mod jobs;
+#[cfg(feature = "pipeline")]
+pub mod keywords;
+#[cfg(feature = "pipeline")]
+pub mod languages;
+#[cfg(feature = "pipeline")]
+pub mod logs;
 pub mod output;
+pub mod postprocess;
+#[cfg(feature = "pipeline")]
+pub mod notify;
+#[cfg(feature = "pipeline")]
+pub mod progress;
+#[cfg(feature = "pipeline")]
+pub mod qa;
+#[cfg(feature = "pipeline")]
+pub mod queue;
+#[cfg(feature = "pipeline")]
+pub mod record;
+#[cfg(feature = "pipeline")]
+pub mod server;
+#[cfg(feature = "pipeline")]
+pub mod speakers;
+#[cfg(feature = "pipeline")]
+pub mod summarize;
 pub mod transcribe;
+pub mod transcript;
 pub mod utils;
+#[cfg(feature = "pipeline")]
+pub mod vocabulary;
+#[cfg(feature = "pipeline")]
+pub mod whisper;
+#[cfg(feature = "pipeline")]
+pub mod worker;
 
-pub use cli::{Cli, Commands, OutputFormat};
+#[cfg(feature = "pipeline")]
+pub use cli::{Cli, Commands};
+pub use cli::{Backend, OutputFormat};
+#[cfg(feature = "pipeline")]
 pub use config::Config;
-pub use extractors::{AudioInfo, MediaExtractor};
-pub use transcribe::{TranscriptionPipeline, TranscriptionResult};
+pub use extractors::AudioInfo;
+#[cfg(feature = "pipeline")]
+pub use extractors::MediaExtractor;
+#[cfg(feature = "pipeline")]
+pub use transcribe::TranscriptionPipeline;
+pub use transcribe::{TranscribeOptions, TranscriptionResult};
 
 /// Result type used throughout the library
 pub type Result<T> = anyhow::Result<T>;
@@ -0,0 +1,168 @@
+use anyhow::Result;
+use std::future::Future;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Output, Stdio};
+use std::sync::LazyLock;
+
+use crate::config::Config;
+
+/// How many tool logs are kept in the data directory
+const KEPT_LOGS: usize = 100;
+
+/// The log of a run outside a batch: one file per process, created on first use
+static PROCESS_LOG: LazyLock<Option<PathBuf>> = LazyLock::new(|| new_log_path(&std::process::id().to_string()));
+
+tokio::task_local! {
+    /// The log of the batch input the current task works on
+    static INPUT_LOG: Option<PathBuf>;
+}
+
+/// Run one batch input with its own tool log, named after `name`
+pub async fn input<F: Future>(name: &str, run: F) -> F::Output {
+    INPUT_LOG.scope(new_log_path(name), run).await
+}
+
+/// File that external tools' output goes to for the current run, if there is a data directory
+pub fn current() -> Option<PathBuf> {
+    INPUT_LOG.try_with(Clone::clone).unwrap_or_else(|_| PROCESS_LOG.clone())
+}
+
+fn new_log_path(name: &str) -> Option<PathBuf> {
+    let dir = Config::data_dir().ok()?.join("logs");
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    Some(dir.join(format!("{}-{}.log", stamp, crate::utils::sanitize_filename(name))))
+}
+
+/// Keep a finished tool's stderr in the run's log, and fail with `context` if the tool failed
+///
+/// The error carries the tool's last line of output and where the rest of it was saved.
+pub fn check(tool: &str, output: &Output, context: &str) -> Result<()> {
+    let log = current();
+    if let Some(log) = &log {
+        if let Err(e) = append(log, tool, output) {
+            tracing::debug!("Could not write tool log {}: {}", log.display(), e);
+        }
+    }
+
+    if output.status.success() {
+        return Ok(());
+    }
+
+    Err(failure(context, &output.stderr, log.as_deref().filter(|log| log.exists())))
+}
+
+/// The error for a failed tool, with the full output referenced rather than inlined
+fn failure(context: &str, stderr: &[u8], log: Option<&Path>) -> anyhow::Error {
+    let stderr = String::from_utf8_lossy(stderr);
+    let summary = stderr.lines().map(str::trim).rfind(|line| !line.is_empty()).unwrap_or("no error output");
+
+    match log {
+        Some(log) => anyhow::anyhow!("{}: {} (full output in {})", context, summary, log.display()),
+        None => anyhow::anyhow!("{}: {}", context, summary),
+    }
+}
+
+/// Where a long-running tool writes its stderr: appended to the run's log, or discarded
+pub fn stderr_target(tool: &str) -> Stdio {
+    let opened = current().and_then(|log| {
+        let mut file = open(&log).ok()?;
+        writeln!(file, "== {} {} (streaming) ==", chrono::Local::now().format("%H:%M:%S"), tool).ok()?;
+        Some(file)
+    });
+
+    match opened {
+        Some(file) => Stdio::from(file.into_parts().0),
+        None => Stdio::null(),
+    }
+}
+
+fn append(log: &Path, tool: &str, output: &Output) -> Result<()> {
+    if output.stderr.iter().all(u8::is_ascii_whitespace) && output.status.success() {
+        return Ok(());
+    }
+
+    let mut file = open(log)?;
+    writeln!(file, "== {} {} ({}) ==", chrono::Local::now().format("%H:%M:%S"), tool, output.status)?;
+    file.write_all(&output.stderr)?;
+    if !output.stderr.ends_with(b"\n") {
+        writeln!(file)?;
+    }
+    Ok(())
+}
+
+fn open(log: &Path) -> Result<fs_err::File> {
+    // A new log makes room for itself first
+    if !log.exists() {
+        let dir = log.parent().unwrap_or(Path::new("."));
+        fs_err::create_dir_all(dir)?;
+        prune(dir);
+    }
+
+    Ok(fs_err::OpenOptions::new().create(true).append(true).open(log)?)
+}
+
+/// Remove the oldest logs beyond the ones kept
+fn prune(dir: &Path) {
+    let Ok(entries) = fs_err::read_dir(dir) else {
+        return;
+    };
+
+    // Names start with the time they were made, so they sort oldest first
+    let mut logs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "log"))
+        .collect();
+    logs.sort();
+
+    let excess = (logs.len() + 1).saturating_sub(KEPT_LOGS);
+    for old in logs.into_iter().take(excess) {
+        let _ = fs_err::remove_file(old);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_check_logs_and_summarises() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("logs").join("run.log");
+
+        let error = INPUT_LOG
+            .scope(Some(log.clone()), async {
+                let output = std::process::Command::new("sh")
+                    .args(["-c", "echo 'frame 1' >&2; echo 'ERROR: Video unavailable' >&2; exit 1"])
+                    .output()
+                    .unwrap();
+                check("yt-dlp", &output, "yt-dlp failed").unwrap_err()
+            })
+            .await;
+
+        assert_eq!(
+            error.to_string(),
+            format!("yt-dlp failed: ERROR: Video unavailable (full output in {})", log.display())
+        );
+        let content = fs_err::read_to_string(&log).unwrap();
+        assert!(content.starts_with("== "));
+        assert!(content.contains("frame 1\nERROR: Video unavailable\n"));
+    }
+
+    #[test]
+    fn test_prune_keeps_newest() {
+        let dir = tempfile::tempdir().unwrap();
+        for index in 0..KEPT_LOGS + 5 {
+            fs_err::write(dir.path().join(format!("20240301-{:06}-1.log", index)), "").unwrap();
+        }
+
+        prune(dir.path());
+
+        let mut left: Vec<_> = fs_err::read_dir(dir.path()).unwrap().map(|e| e.unwrap().file_name()).collect();
+        left.sort();
+        // Room is left for the log about to be written
+        assert_eq!(left.len(), KEPT_LOGS - 1);
+        assert_eq!(left[0].to_string_lossy(), "20240301-000006-1.log");
+    }
+}
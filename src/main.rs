@@ -1,100 +1,1685 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Parser;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use aws_sdk_transcribe::types::TranscriptionJobStatus;
+use futures_util::StreamExt;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer};
 
-mod cli;
-mod config;
-mod extractors;
-mod output;
-mod transcribe;
-mod utils;
-
-use cli::{Cli, Commands};
-use config::Config;
-use transcribe::TranscriptionPipeline;
+use clap::ValueEnum;
+use rustscribe::cli::{
+    Backend, CacheCommands, CaptionsCommands, Cli, Commands, ConfigCommands, DepsCommands, DomainArgs, DomainCommands, HistoryCommands,
+    JobStatus, JobsCommands, FinishArgs, LanguageArgs, LiveArgs, ModelsCommands, ModerationArgs, OutputFormat, QueueCommands,
+    SavedAudioArgs, SpeakersCommands, VocabCommands,
+};
+use rustscribe::archive::Archive;
+use rustscribe::cache::{ArtifactKey, ArtifactKind, ArtifactStore};
+use rustscribe::config::permissions::{self, Outcome};
+use rustscribe::config::settings;
+use rustscribe::config::Config;
+use rustscribe::domain::DomainPack;
+use rustscribe::eval;
+use rustscribe::extractors::live::LiveOptions;
+use rustscribe::extractors::s3::S3Location;
+use rustscribe::history::{History, HistoryFilter, Report};
+use rustscribe::jobs::manager::JobManager;
+use rustscribe::jobs::JobStore;
+use rustscribe::hooks::{self, HooksConfig};
+use rustscribe::notify::{Announcement, Announcer, Notifier};
+use rustscribe::output::summary::{DeliveredFiles, RunStatus, RunSummary};
+use rustscribe::output::{review, ExistingOutput, RenderOptions, Timecode, TimestampStyle};
+use rustscribe::postprocess::RuleSet;
+use rustscribe::queue::{OfflineQueue, OutputTarget, QueuedRun};
+use rustscribe::server::Server;
+use rustscribe::summarize::{self, SummarizeFormat, Summarizer};
+use rustscribe::worker::Worker;
+use rustscribe::speakers::SpeakerStore;
+use rustscribe::transcribe::{
+    self, align, boost, merge, shift, PiiRedaction, TranscribeOptions, TranscriptionPipeline, TranscriptionResult,
+};
+use rustscribe::whisper::ModelManager;
+use rustscribe::{captions, crash, deps, http, languages, logs, output, progress, qa, record, utils, vocabulary};
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize tracing
+    // Initialize tracing; crash reports keep recent debug lines even when the console shows less
+    let terminal = progress::Terminal::new();
     tracing_subscriber::registry()
-        .with(
+        .with(tracing_subscriber::fmt::layer().with_writer(terminal.log_writer()).with_filter(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "rustscribe=info".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
+        ))
+        .with(crash::log_layer())
         .init();
+    crash::install_panic_hook();
+    progress::set_default(std::sync::Arc::new(terminal.clone()));
 
     let cli = Cli::parse();
     
+    // Scripts set up the config before it is complete, so these do not need a valid one
+    match &cli.command {
+        Commands::Config { action: Some(ConfigCommands::Get { key }), .. } => {
+            let config = if cli.demo { Config::demo() } else { Config::load_unchecked()? };
+            println!("{}", settings::display(&settings::get(&config, key)?)?);
+            return Ok(());
+        }
+        Commands::Config { action: Some(ConfigCommands::Set { key, value }), .. } => {
+            if cli.demo {
+                anyhow::bail!("--demo never changes the config file");
+            }
+            let mut config = Config::load_unchecked()?;
+            settings::set(&mut config, key, value)?;
+            config.save().await?;
+            return Ok(());
+        }
+        _ => {}
+    }
+    
+    let mut config = if cli.demo {
+        Config::demo()
+    } else if matches!(&cli.command, Commands::Extract { .. } | Commands::Eval { .. })
+        || matches!(&cli.command, Commands::Summarize { input, .. } if std::path::Path::new(input).is_file())
+    {
+        Config::load_local().await?
+    } else {
+        Config::load().await?
+    };
+    
     // Check for required external dependencies (non-fatal in Docker)
-    let missing_deps = utils::check_dependencies().await;
-    if !missing_deps.is_empty() {
-        eprintln!("⚠️  Dependency check warnings:");
-        for dep in missing_deps {
-            eprintln!("   • {}", dep);
+    if !cli.demo && !matches!(cli.command, Commands::Deps { .. }) {
+        let missing_deps = utils::check_dependencies(&config.yt_dlp.binary()).await;
+        if !missing_deps.is_empty() {
+            eprintln!("⚠️  Dependency check warnings:");
+            for dep in missing_deps {
+                eprintln!("   • {}", dep);
+            }
+            eprintln!("   (Continuing anyway - tools may be available)");
         }
-        eprintln!("   (Continuing anyway - tools may be available)");
     }
     
-    let config = Config::load().await?;
+    if let Some(file) = cli.cookies {
+        config.cookies.file = Some(file);
+    }
+    if let Some(browser) = cli.cookies_from_browser {
+        config.cookies.from_browser = Some(browser);
+    }
+    if let Some(proxy) = cli.proxy {
+        config.network.proxy = Some(proxy);
+    }
 
     match cli.command {
         Commands::Transcribe {
             url,
             output,
+            no_clobber,
+            backup,
+            backend,
             format,
-            language,
-            save_audio,
+            languages: language_args,
+            mut save_audio,
+            audio_format,
+            audio_quality,
+            saved_audio,
             speaker_labels,
             max_speakers,
+            diarize_only,
+            split_channels,
+            clean_speech,
+            extract_keywords,
+            chapters,
             timestamps,
             detailed_timestamps,
+            timestamp_format,
+            paragraph_timestamps,
+            paragraphs,
+            review_threshold,
+            json_detail,
+            include_alternatives,
+            granularity,
+            template,
+            subtitles,
             max_segment_length,
+            domain: domain_args,
+            queue_if_offline,
+            no_cache,
+            max_duration,
+            live,
+            copy_cross_region,
+            prefer_captions,
+            timecode_offset,
+            fps,
+            tag,
+            max_cost,
+            moderation,
+            finish,
         } => {
-            let pipeline = TranscriptionPipeline::new(config).await?;
+            let mut config = config;
+            if no_cache {
+                config.app.download_cache = false;
+            }
+            if let Some(max_duration) = max_duration {
+                config.app.max_stream_secs = Some(max_duration.as_secs());
+            }
+            let FinishArgs {
+                open_with,
+                notify_url,
+                notify,
+                summary,
+            } = *finish;
+            let LiveArgs {
+                live,
+                live_from_start,
+                live_wait,
+                live_chunk,
+                live_boost,
+                live_boost_file,
+            } = *live;
+            let live = match (live, config.app.max_stream_secs) {
+                (false, _) => None,
+                (true, _) if config.app.demo => anyhow::bail!("--live is not available with --demo"),
+                (true, None) => anyhow::bail!("--live needs --max-duration (or app.max_stream_secs) to know when to stop"),
+                (true, Some(secs)) => Some(LiveOptions {
+                    duration: std::time::Duration::from_secs(secs),
+                    chunk: live_chunk.filter(|chunk| !chunk.is_zero()),
+                    from_start: live_from_start,
+                    wait: live_wait,
+                    keep_recording: false,
+                }),
+            };
+            if let Some(audio_format) = audio_format {
+                config.yt_dlp.audio_format = audio_format;
+            }
+            if let Some(audio_quality) = audio_quality {
+                config.yt_dlp.audio_quality = audio_quality;
+            }
+            config.yt_dlp.validate()?;
+            let SavedAudioArgs {
+                save_audio_dir,
+                save_audio_format,
+                archive_original,
+            } = *saved_audio;
+            if save_audio_dir.is_some() || save_audio_format.is_some() {
+                save_audio = true;
+                config.saved_audio.dir = save_audio_dir.or(config.saved_audio.dir);
+                config.saved_audio.format = save_audio_format.or(config.saved_audio.format);
+            }
+            if let Some(archive) = archive_original {
+                config.saved_audio.archive_originals = Some(archive);
+            }
+            
+            let DomainArgs { domain, corrections, vocabulary: vocabulary_name } = *domain_args;
+            let pack = domain.as_deref().map(DomainPack::load).transpose()?;
+            let preset = pack.as_ref().map(|d| d.format.clone()).unwrap_or_default();
+            
+            // Explicit flag wins, then the domain preset, then the configured default
+            let mut formats = format.into_iter();
+            let format = formats
+                .next()
+                .or(preset.output)
+                .or_else(|| OutputFormat::from_str(&config.app.default_output_format, true).ok())
+                .unwrap_or(OutputFormat::Text);
+            
+            let backend = backend.unwrap_or(config.app.backend);
+            let LanguageArgs { language, languages: language_options, multi_language } = *language_args;
+            // A domain pack is written in one language, so that is what it transcribes unless told otherwise
+            let language = language
+                .or_else(|| pack.as_ref().and_then(|d| d.language.clone()))
+                .map(|language| languages::validate(&language, backend))
+                .transpose()?;
+            if (multi_language || !language_options.is_empty()) && backend != Backend::Aws {
+                anyhow::bail!("--languages and --multi-language need the AWS backend");
+            }
+            let language_options = match (&language, backend) {
+                (None, Backend::Aws) => {
+                    languages::candidates(&language_options, &config.aws.transcription.language_candidates)?
+                }
+                _ => Vec::new(),
+            };
+            if diarize_only && backend != Backend::Aws {
+                anyhow::bail!("--diarize-only needs the AWS backend; Whisper cannot tell speakers apart");
+            }
+            // AWS takes no custom vocabulary when it identifies the language, and only says so after the upload
+            let language_known = language.is_some() || config.aws.transcription.default_language.is_some();
+            if backend != Backend::Whisper && !diarize_only && !language_known {
+                if vocabulary_name.is_some() {
+                    anyhow::bail!("--vocabulary needs --language (or aws.transcription.default_language)");
+                }
+                if let Some(pack) = pack.as_ref().filter(|d| d.vocabulary.is_some()) {
+                    anyhow::bail!(
+                        "Domain pack '{}' has a vocabulary but no language; add --language or set `language` in the pack",
+                        pack.name
+                    );
+                }
+            }
+            let ModerationArgs { redact_pii, redaction_output, detect_toxicity } = *moderation;
+            if detect_toxicity {
+                if backend != Backend::Aws {
+                    anyhow::bail!("--detect-toxicity needs the AWS backend");
+                }
+                let language = language.as_deref().or(config.aws.transcription.default_language.as_deref());
+                if language != Some("en-US") {
+                    anyhow::bail!("AWS detects toxicity in US English only; add --language en-US");
+                }
+            }
+            let redact_pii = match redact_pii {
+                None => None,
+                Some(_) if backend != Backend::Aws => anyhow::bail!("--redact-pii needs the AWS backend"),
+                Some(types) => {
+                    let types: Vec<String> = types.iter().map(|t| t.trim().to_uppercase()).filter(|t| !t.is_empty()).collect();
+                    let known = aws_sdk_transcribe::types::PiiEntityType::values();
+                    if let Some(unknown) = types.iter().find(|t| !known.contains(&t.as_str())) {
+                        anyhow::bail!("Unknown PII type {}; expected one of {}", unknown, known.join(", "));
+                    }
+                    Some(PiiRedaction {
+                        // An empty list redacts every type
+                        entity_types: if types.iter().any(|t| t == "ALL") { Vec::new() } else { types },
+                        output: redaction_output.unwrap_or_default(),
+                    })
+                }
+            };
+            
+            let mut boost_phrases = boost::load_phrases(&live_boost, live_boost_file.as_deref())?;
+            if live.is_some() {
+                // A domain pack's vocabulary names the terms a live event needs too
+                boost_phrases.extend(pack.iter().flat_map(|d| d.vocabulary.iter()).flat_map(|v| v.phrases.clone()));
+            }
+            let options = TranscribeOptions {
+                language,
+                speaker_labels: speaker_labels || preset.speaker_labels,
+                max_speakers,
+                max_segment_length,
+                save_audio,
+                vocabulary: vocabulary_name.or_else(|| pack.as_ref().and_then(|d| d.vocabulary_name()).map(str::to_string)),
+                copy_cross_region,
+                backend,
+                tag,
+                prefer_captions,
+                diarize_only,
+                max_cost,
+                boost_phrases,
+                redact_pii,
+                detect_toxicity,
+                language_options,
+                multi_language,
+                split_channels,
+                clean_speech,
+                extract_keywords,
+                chapters,
+            };
+            
+            let target = OutputTarget {
+                path: output,
+                format,
+                extra_formats: formats.collect(),
+                domain,
+                // Queued runs are flushed from anywhere
+                corrections: corrections.map(std::path::absolute).transpose()?,
+                render: RenderOptions {
+                    timestamps: timestamps || detailed_timestamps || preset.timestamps || diarize_only,
+                    detailed_timestamps,
+                    timecode: Timecode::new(timecode_offset.as_deref(), fps)?,
+                    timestamp_style: TimestampStyle {
+                        format: timestamp_format,
+                        per_paragraph: paragraph_timestamps,
+                    },
+                    json_detail,
+                    paragraphs,
+                    review_threshold,
+                    include_alternatives,
+                    granularity,
+                    template: template.map(std::path::absolute).transpose()?,
+                    subtitles: subtitles.layout(),
+                },
+                existing: ExistingOutput::from_flags(no_clobber, backup),
+            };
+            if let Some(path) = &target.path {
+                target.existing.check(path)?;
+                if review_threshold.is_some() {
+                    target.existing.check(&review::review_path(path))?;
+                }
+            }
+            if !target.extra_formats.is_empty() {
+                check_extra_outputs(&target)?;
+            }
+            // A broken template should fail before the audio is transcribed
+            if let Some(template) = &target.render.template {
+                output::template::Template::load(template)?;
+            }
+            let rules = target.rule_set()?;
+            
+            let queue_when_offline = queue_if_offline || config.app.queue_when_offline;
+            let demo = config.app.demo;
+            let prices = config.history.clone();
+            if notify_url.is_some() {
+                config.notify.url = notify_url;
+            }
+            if !notify.is_empty() {
+                config.notify.channels = notify;
+            }
+            let notifier = Notifier::from_config(&config)?;
+            let announcer = Announcer::from_config(&config)?;
+            let mut announcement = None;
+            let run = async {
+                let delivery = Delivery {
+                    open_with,
+                    ..Delivery::new(&config).await?
+                };
+                let pipeline = TranscriptionPipeline::new(config).await?.with_output(target.clone());
+            
+                tracing::info!("Starting transcription for URL: {}", url);
+            
+                let result = if let Some(live) = live {
+                    pipeline
+                        .transcribe_live(&url, &options, &live, |text, offset| {
+                            eprintln!("[{}] {}", utils::format_duration(offset), text);
+                        })
+                        .await?
+                } else if let Some(location) = S3Location::parse(&url).filter(|_| !demo) {
+                    // Objects already in S3 go straight to Transcribe
+                    pipeline.transcribe_s3_object(&location, &options).await?
+                } else if let Some(result) = pipeline.transcribe_from_captions(&url, &options).await? {
+                    result
+                } else {
+                    let (audio_info, audio_path) = pipeline.prepare_audio(&url).await?;
+                    match pipeline.transcribe_audio(audio_info.clone(), &audio_path, &options).await {
+                        Ok(result) => result,
+                        Err(e) if queue_when_offline && transcribe::is_connectivity_error(&e) => {
+                            let run = OfflineQueue::open()?.enqueue(&url, audio_info, &audio_path, options, target)?;
+                            eprintln!("⚠️  AWS is unreachable, run queued as {}", run.id);
+                            eprintln!("   Submit it later with: rustscribe queue flush");
+                            return Ok(RunSummary::queued(&run.id));
+                        }
+                        Err(e) => return Err(e),
+                    }
+                };
             
-            tracing::info!("Starting transcription for URL: {}", url);
+                let summary = RunSummary::completed(&result, backend, &prices);
+                announcement = Some(Announcement::completed(&result));
+                let outputs = deliver_result(result, &target, rules.as_ref(), &delivery).await?;
+                anyhow::Ok(RunSummary { outputs, ..summary })
+            }
+            .await;
             
-            let result = pipeline
-                .transcribe_from_url(&url, language.as_deref(), speaker_labels, max_speakers, max_segment_length, save_audio)
-                .await?;
+            let outcome = match &run {
+                Ok(summary) => summary.clone(),
+                Err(e) => RunSummary::failed(e),
+            };
+            // A queued run has neither finished nor failed yet
+            if outcome.status != RunStatus::Queued {
+                if let Some(notifier) = &notifier {
+                    if let Err(e) = notifier.send(&url, &outcome).await {
+                        tracing::warn!("Could not send the notification: {:#}", e);
+                    }
+                }
+                if let Some(announcer) = &announcer {
+                    let announcement = match &run {
+                        Ok(_) => announcement.unwrap_or_default(),
+                        Err(e) => Announcement::failed(&url, e),
+                    };
+                    announcer.announce(&announcement).await;
+                }
+            }
+            // Printed last, so wrappers read the final line of stdout whatever the transcript format
+            if let Some(format) = summary {
+                progress::suspend(|| println!("{}", outcome.render(format)));
+            }
+            run?;
+        }
+        Commands::Summarize { input, output, format, model } => {
+            if let Some(model) = model {
+                config.summarize.model_id = model;
+            }
+            let format = format.unwrap_or_else(|| {
+                match output.as_deref().and_then(|path| path.extension()).and_then(|ext| ext.to_str()) {
+                    Some(ext) if ext.eq_ignore_ascii_case("json") => SummarizeFormat::Json,
+                    _ => SummarizeFormat::Markdown,
+                }
+            });
+            let summarizer = Summarizer::new(&config).await?;
+
+            let path = std::path::Path::new(&input);
+            let result = if path.is_file() {
+                output::load_transcript(path)?
+            } else {
+                let options = TranscribeOptions {
+                    backend: config.app.backend,
+                    ..Default::default()
+                };
+                TranscriptionPipeline::new(config).await?.transcribe_from_url(&input, &options).await?
+            };
 
-            // Handle output
-            let show_timestamps = timestamps || detailed_timestamps;
+            let summary = summarizer.summarize(&result).await?;
+            let content = summarize::render(&result, &summary, format)?;
             match output {
                 Some(path) => {
-                    output::save_to_file(&result, &path, &format, show_timestamps, detailed_timestamps).await?;
-                    println!("Transcription saved to: {}", path.display());
+                    fs_err::write(&path, content)?;
+                    println!("Summary saved to: {}", path.display());
                 }
-                None => {
-                    output::print_to_console(&result, &format, show_timestamps, detailed_timestamps)?;
+                None => println!("{}", content),
+            }
+        }
+        Commands::Extract { url, output, no_clobber, max_duration } => {
+            if let Some(path) = &output {
+                if path.extension().is_none() {
+                    anyhow::bail!("{} needs an extension to tell the audio format, e.g. .mp3", path.display());
                 }
+                ExistingOutput::from_flags(no_clobber, false).check(path)?;
             }
-
-            // Save audio if requested
-            if save_audio {
-                if let Some(audio_path) = result.audio_path {
-                    println!("Audio saved to: {}", audio_path.display());
+            if let Some(max_duration) = max_duration {
+                config.app.max_stream_secs = Some(max_duration.as_secs());
+            }
+            // yt-dlp can produce the wanted format itself, which saves a second conversion
+            let extension = output.as_deref().and_then(|path| path.extension()).map(|ext| ext.to_string_lossy().to_lowercase());
+            if let Some(extension) = extension.filter(|ext| deps::AUDIO_FORMATS.contains(&ext.as_str())) {
+                config.yt_dlp.audio_format = extension;
+            }
+            
+            let pipeline = TranscriptionPipeline::new(config).await?;
+            let (audio_info, path) = pipeline.extract_audio(&url, output.as_deref()).await?;
+            
+            println!("{}", audio_info.title.as_deref().unwrap_or(&url));
+            println!("Audio saved to: {}", path.display());
+        }
+        Commands::Serve { host, port } => {
+            if let Some(host) = host {
+                config.server.host = host;
+            }
+            if let Some(port) = port {
+                config.server.port = port;
+            }
+            let server = Server::bind(config).await?;
+            println!("Listening on http://{} (Ctrl+C to stop)", server.local_addr()?);
+            server.run().await?;
+        }
+        Commands::Worker { queue, output, drain } => {
+            if output.is_some() {
+                config.worker.output = output;
+            }
+            let queue = queue
+                .or_else(|| config.worker.queue.clone())
+                .context("No queue to read from: pass --queue <sqs-url> or set worker.queue")?;
+            let worker = Worker::new(&queue, config).await?;
+            eprintln!("Waiting for messages on {} (Ctrl+C to stop)", queue);
+            worker.run(drain).await?;
+        }
+        Commands::Estimate { url, backend, json } => {
+            let backend = backend.unwrap_or(config.app.backend);
+            let pipeline = TranscriptionPipeline::new(config).await?;
+            let (audio_info, estimate) = pipeline.estimate(&url, backend).await?;
+            
+            if json {
+                println!("{}", serde_json::to_string_pretty(&estimate)?);
+            } else {
+                println!("{}", audio_info.title.as_deref().unwrap_or(&url));
+                println!(
+                    "  Audio:          {} (about {} to upload)",
+                    utils::format_duration(estimate.audio_secs),
+                    utils::format_file_size(estimate.upload_bytes)
+                );
+                println!("  Transcription:  ${:.2} ({})", estimate.transcription, format!("{:?}", backend).to_lowercase());
+                println!("  S3 storage:     ${:.4}", estimate.storage);
+                println!("  Total:          ${:.2}", estimate.total());
+                if backend == Backend::Hybrid {
+                    println!("  (upper bound: hybrid runs only send low-confidence spans to AWS)");
                 }
             }
         }
-        Commands::Config { show } => {
+        Commands::Languages { backend, json } => {
+            let languages = languages::supported(backend.unwrap_or(config.app.backend));
+            if json {
+                println!("{}", serde_json::to_string_pretty(&languages)?);
+            } else {
+                for language in languages {
+                    println!("  {:<8} {}", language.code, language.name);
+                }
+            }
+        }
+        Commands::Record {
+            duration,
+            output,
+            no_clobber,
+            backup,
+            backend,
+            format,
+            language,
+            save_audio,
+            speaker_labels,
+            max_speakers,
+            timestamps,
+            tag,
+        } => {
+            let format = format
+                .or_else(|| OutputFormat::from_str(&config.app.default_output_format, true).ok())
+                .unwrap_or(OutputFormat::Text);
+            
+            let backend = backend.unwrap_or(config.app.backend);
+            let options = TranscribeOptions {
+                language: language.map(|language| languages::validate(&language, backend)).transpose()?,
+                speaker_labels,
+                max_speakers,
+                save_audio,
+                backend,
+                tag,
+                ..Default::default()
+            };
+            
+            let target = OutputTarget {
+                path: output,
+                format,
+                extra_formats: Vec::new(),
+                domain: None,
+                corrections: None,
+                render: RenderOptions {
+                    timestamps,
+                    ..Default::default()
+                },
+                existing: ExistingOutput::from_flags(no_clobber, backup),
+            };
+            if let Some(path) = &target.path {
+                target.existing.check(path)?;
+            }
+            
+            let recording_dir = config.app.temp_dir.clone().unwrap_or_else(std::env::temp_dir);
+            fs_err::create_dir_all(&recording_dir)?;
+            let recording = record::recording_path(&recording_dir, chrono::Local::now());
+            
+            record::record(&config.record, &recording, duration).await?;
+            
+            let transcribed = async {
+                let delivery = Delivery::new(&config).await?;
+                let pipeline = TranscriptionPipeline::new(config).await?.with_output(target.clone());
+                let result = pipeline
+                    .transcribe_from_url(&recording.to_string_lossy(), &options)
+                    .await?;
+                deliver_result(result, &target, None, &delivery).await
+            }
+            .await;
+            // A recording cannot be made again, so it is only removed once its transcript is out
+            transcribed.with_context(|| {
+                format!(
+                    "Recording kept at {0}; run `rustscribe transcribe {0}` to try again",
+                    recording.display()
+                )
+            })?;
+            let _ = fs_err::remove_file(&recording);
+        }
+        Commands::Batch {
+            mut inputs,
+            from,
+            output_dir,
+            backend,
+            format,
+            language,
+            save_audio,
+            speaker_labels,
+            timestamps,
+            jobs,
+            tag,
+        } => {
+            if let Some(from) = from {
+                let list = fs_err::read_to_string(&from)?;
+                inputs.extend(
+                    list.lines()
+                        .map(str::trim)
+                        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                        .map(str::to_string),
+                );
+            }
+            if inputs.is_empty() {
+                anyhow::bail!("Nothing to transcribe: give inputs or --from FILE");
+            }
+            
+            let mut config = config;
+            if let Some(jobs) = jobs {
+                config.app.max_concurrent_jobs = jobs;
+            }
+            let jobs = config.app.max_concurrent_jobs.max(1);
+            let format = format
+                .or_else(|| OutputFormat::from_str(&config.app.default_output_format, true).ok())
+                .unwrap_or(OutputFormat::Text);
+            let backend = backend.unwrap_or(config.app.backend);
+            let options = TranscribeOptions {
+                language: language.map(|language| languages::validate(&language, backend)).transpose()?,
+                speaker_labels,
+                save_audio,
+                backend,
+                tag,
+                ..Default::default()
+            };
+            fs_err::create_dir_all(&output_dir)?;
+            
+            let delivery = Delivery::new(&config).await?;
+            let pipeline = TranscriptionPipeline::new(config).await?;
+            
+            let mut urls = Vec::new();
+            for input in &inputs {
+                urls.extend(pipeline.expand_playlist(input).await?);
+            }
+            println!("Transcribing {} inputs, {} at a time", urls.len(), jobs);
+            
+            let target = OutputTarget {
+                path: None,
+                format,
+                extra_formats: Vec::new(),
+                domain: None,
+                corrections: None,
+                render: RenderOptions {
+                    timestamps,
+                    ..Default::default()
+                },
+                existing: ExistingOutput::from_flags(false, false),
+            };
+            let batch = terminal.batch(urls.len() as u64);
+            let runs = futures_util::stream::iter(urls.iter().enumerate())
+                .map(|(index, url)| {
+                    let (pipeline, options, target, delivery, output_dir) = (&pipeline, &options, &target, &delivery, &output_dir);
+                    let batch = &batch;
+                    let label = format!("[{}/{}]", index + 1, urls.len());
+                    let log_name = format!("batch-{:03}", index + 1);
+                    progress::input(label, async move {
+                        let run = transcribe_into_dir(pipeline, url, index, options, target, output_dir, delivery);
+                        let outcome = logs::input(&log_name, run).await;
+                        batch.input_done(outcome.is_err());
+                        (url, outcome)
+                    })
+                })
+                .buffer_unordered(jobs)
+                .collect::<Vec<_>>()
+                .await;
+            drop(batch);
+            
+            let failed: Vec<_> = runs.iter().filter(|(_, outcome)| outcome.is_err()).collect();
+            for (url, outcome) in &failed {
+                if let Err(e) = outcome {
+                    eprintln!("✗ {}: {:#}", url, e);
+                }
+            }
+            println!("{} of {} inputs transcribed", runs.len() - failed.len(), runs.len());
+            if !failed.is_empty() {
+                anyhow::bail!("{} inputs failed", failed.len());
+            }
+        }
+        Commands::DetectLanguage { url, sample, backend, multiple, language_candidates, json } => {
+            if sample.is_zero() {
+                anyhow::bail!("--sample must be longer than zero");
+            }
+            // Streams only need to be captured for as long as the sample
+            config.app.max_stream_secs = Some(sample.as_secs().max(1));
+            let backend = backend.unwrap_or(config.app.backend);
+            let candidates = match backend {
+                Backend::Aws => languages::candidates(&language_candidates, &config.aws.transcription.language_candidates)?,
+                _ if !language_candidates.is_empty() => anyhow::bail!("--language-candidates needs the AWS backend"),
+                _ => Vec::new(),
+            };
+            let pipeline = TranscriptionPipeline::new(config).await?;
+            
+            let languages = pipeline.detect_language(&url, sample, backend, multiple, &candidates).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&languages)?);
+            } else if languages.is_empty() {
+                println!("No language detected");
+            } else {
+                for detected in &languages {
+                    let mut line = detected.language.clone();
+                    if let Some(confidence) = detected.confidence {
+                        line.push_str(&format!("  {:.0}% confidence", confidence * 100.0));
+                    }
+                    if let Some(duration) = detected.duration {
+                        line.push_str(&format!("  {:.0}s spoken", duration));
+                    }
+                    println!("{}", line);
+                }
+            }
+        }
+        Commands::Config { action: Some(ConfigCommands::CheckPermissions), .. } => {
+            println!(
+                "Checking AWS permissions in {} with bucket '{}':",
+                config.aws.region, config.aws.s3_bucket
+            );
+            let checks = permissions::check(&config).await;
+            
+            let width = checks.iter().map(|c| c.action.len()).max().unwrap_or(0);
+            for check in &checks {
+                match &check.outcome {
+                    Outcome::Allowed => println!("  ✅ {:width$}  {}", check.action, check.used_for),
+                    Outcome::Denied(reason) => println!("  ❌ {:width$}  {} ({})", check.action, check.used_for, reason),
+                    Outcome::Unknown(reason) => println!("  ⚠️  {:width$}  could not be checked: {}", check.action, reason),
+                }
+            }
+            
+            let missing: Vec<&str> = checks
+                .iter()
+                .filter(|c| matches!(c.outcome, Outcome::Denied(_)))
+                .map(|c| c.action)
+                .collect();
+            if !missing.is_empty() {
+                println!("\nMissing permissions: {}", missing.join(", "));
+            }
+            
+            let required = checks
+                .iter()
+                .filter(|c| c.required && c.outcome != Outcome::Allowed)
+                .count();
+            if required > 0 {
+                anyhow::bail!("{} permission(s) every transcription needs are missing or could not be checked", required);
+            }
+            println!("\nAll permissions needed for transcription are granted");
+        }
+        Commands::Config { action: Some(ConfigCommands::Get { .. } | ConfigCommands::Set { .. }), .. } => {
+            // Handled before the config is loaded
+        }
+        Commands::Config { show, action: None } => {
             if show {
                 config.display();
             } else {
                 config.interactive_setup().await?;
             }
         }
+        Commands::Merge {
+            inputs,
+            offsets,
+            gap,
+            keep_speakers,
+            output,
+            format,
+            timestamps,
+        } => {
+            let parts = inputs
+                .iter()
+                .map(|path| output::load_result(path))
+                .collect::<Result<Vec<_>>>()?;
+            let offsets = if offsets.is_empty() {
+                merge::back_to_back_offsets(&parts, gap)
+            } else {
+                offsets.iter().map(|offset| offset.as_secs_f64()).collect()
+            };
+            let merged = merge::merge_results(parts, &offsets, keep_speakers)?;
+            
+            let format = format
+                .or_else(|| OutputFormat::from_str(&config.app.default_output_format, true).ok())
+                .unwrap_or(OutputFormat::Text);
+            let target = OutputTarget {
+                path: output,
+                format,
+                extra_formats: Vec::new(),
+                domain: None,
+                corrections: None,
+                render: RenderOptions {
+                    timestamps,
+                    ..Default::default()
+                },
+                existing: ExistingOutput::Overwrite,
+            };
+            
+            // Reworked transcripts were archived when they were first made
+            let delivery = Delivery {
+                hooks: config.hooks.clone(),
+                archive: None,
+                artifacts: None,
+                open_with: None,
+            };
+            deliver_result(merged, &target, None, &delivery).await?;
+        }
+        Commands::Shift {
+            input,
+            offset,
+            stretch,
+            output,
+            format,
+            timestamps,
+        } => {
+            let mut result = output::load_transcript(&input)?;
+            shift::TimeShift { offset, stretch }.apply(&mut result);
+            
+            let input_format = input
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .filter(|ext| ext.eq_ignore_ascii_case("srt") || ext.eq_ignore_ascii_case("vtt"))
+                .and_then(|ext| OutputFormat::from_str(ext, true).ok());
+            let format = format
+                .or(input_format)
+                .or_else(|| OutputFormat::from_str(&config.app.default_output_format, true).ok())
+                .unwrap_or(OutputFormat::Text);
+            let target = OutputTarget {
+                path: output,
+                format,
+                extra_formats: Vec::new(),
+                domain: None,
+                corrections: None,
+                render: RenderOptions {
+                    timestamps,
+                    ..Default::default()
+                },
+                existing: ExistingOutput::Overwrite,
+            };
+            
+            // Reworked transcripts were archived when they were first made
+            let delivery = Delivery {
+                hooks: config.hooks.clone(),
+                archive: None,
+                artifacts: None,
+                open_with: None,
+            };
+            deliver_result(result, &target, None, &delivery).await?;
+        }
+        Commands::Align { audio, transcript, backend, language, output, format } => {
+            let script = fs_err::read_to_string(&transcript)?;
+            let backend = backend.unwrap_or(config.app.backend);
+            let options = TranscribeOptions {
+                language: language.map(|language| languages::validate(&language, backend)).transpose()?,
+                backend,
+                ..Default::default()
+            };
+            let target = OutputTarget {
+                path: output,
+                format,
+                extra_formats: Vec::new(),
+                domain: None,
+                corrections: None,
+                render: RenderOptions::default(),
+                existing: ExistingOutput::Overwrite,
+            };
+            
+            let delivery = Delivery::new(&config).await?;
+            let heard = TranscriptionPipeline::new(config).await?.transcribe_from_url(&audio, &options).await?;
+            let aligned = align::align(&heard, &script)?;
+            deliver_result(aligned, &target, None, &delivery).await?;
+        }
+        Commands::Convert(args) => {
+            let result = output::load_transcript(&args.input)?;
+            let format = args
+                .format
+                .clone()
+                .or_else(|| OutputFormat::from_str(&config.app.default_output_format, true).ok())
+                .unwrap_or(OutputFormat::Text);
+            let options = args.render_options()?;
+            
+            match &args.output {
+                Some(path) => {
+                    let backup = output::save_to_file(&result, path, &format, &options, args.existing()).await?;
+                    println!("Transcription saved to: {}", path.display());
+                    if let Some(backup) = backup {
+                        println!("Previous output kept as: {}", backup.display());
+                    }
+                    if let Some(threshold) = options.review_threshold {
+                        review::save_review(&result, path, threshold, args.existing())?;
+                        println!("Review list saved to: {}", review::review_path(path).display());
+                    }
+                }
+                None => {
+                    output::print_to_console(&result, &format, &options)?;
+                    if let Some(threshold) = options.review_threshold {
+                        eprint!("{}", review::format_review(&result, threshold));
+                    }
+                }
+            }
+        }
+        Commands::Eval { input, reference, keep_case, keep_punctuation, spell_numbers, json } => {
+            let transcript = output::load_transcript(&input)?.transcript;
+            let is_text = reference
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_none_or(|ext| !["json", "srt", "vtt"].contains(&ext.to_lowercase().as_str()));
+            let reference = if is_text {
+                fs_err::read_to_string(&reference)?
+            } else {
+                output::load_transcript(&reference)?.transcript
+            };
+            
+            let normalization = eval::Normalization { keep_case, keep_punctuation, spell_numbers };
+            let evaluation = eval::evaluate(&transcript, &reference, &normalization);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&evaluation)?);
+            } else {
+                print!("{}", evaluation.render());
+            }
+        }
+        Commands::History { action } => match action {
+            HistoryCommands::List { since, until, month, platform, limit, json } => {
+                let (since, until) = match month {
+                    Some((first, last)) => (Some(first), Some(last)),
+                    None => (since, until),
+                };
+                let entries = History::open()?.search(&HistoryFilter {
+                    since,
+                    until,
+                    platform,
+                    limit: Some(limit),
+                })?;
+                
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&entries)?);
+                } else if entries.is_empty() {
+                    println!("No transcriptions recorded");
+                } else {
+                    for entry in entries {
+                        println!(
+                            "  #{:<4} {}  {:<10} {:<7} {:<6} {:>9}  ${:.2}  {:>4}  {}",
+                            entry.id.unwrap_or_default(),
+                            entry.completed_at.format("%Y-%m-%d %H:%M"),
+                            entry.platform.as_deref().unwrap_or("-"),
+                            format!("{:?}", entry.backend).to_lowercase(),
+                            entry.language.as_deref().unwrap_or("-"),
+                            utils::format_duration(entry.audio_secs),
+                            entry.cost_estimate.unwrap_or_else(|| entry.estimated_cost(&config.history)),
+                            entry.qa_accuracy.map_or_else(|| "-".to_string(), |accuracy| format!("{:.0}%", accuracy * 100.0)),
+                            entry.title.as_deref().unwrap_or(&entry.source),
+                        );
+                    }
+                }
+            }
+            HistoryCommands::Show { id, format, output, timestamps } => {
+                let result = History::open()?.transcript(id)?;
+                let options = RenderOptions {
+                    timestamps,
+                    ..Default::default()
+                };
+                
+                match output {
+                    Some(path) => {
+                        output::save_to_file(&result, &path, &format, &options, ExistingOutput::Overwrite).await?;
+                        println!("Transcript saved to: {}", path.display());
+                    }
+                    None => output::print_to_console(&result, &format, &options)?,
+                }
+            }
+        },
+        Commands::Qa { id, samples, audio, seed } => {
+            let history = History::open()?;
+            let entry = history.get(id)?;
+            let result = history.transcript(id)?;
+
+            let audio = audio
+                .or_else(|| result.audio_path.clone().filter(|path| path.exists()))
+                .or_else(|| Some(std::path::PathBuf::from(&entry.source)).filter(|path| path.is_file()))
+                .with_context(|| format!("No audio found for run #{}; pass it with --audio", id))?;
+
+            let mut rng = seed.map_or_else(fastrand::Rng::new, fastrand::Rng::with_seed);
+            let segments = qa::sample_segments(&result, samples, &mut rng);
+            if segments.is_empty() {
+                anyhow::bail!("Run #{} has no segments to review", id);
+            }
+
+            let score = qa::review(&segments, &audio).await?;
+            match (score.accuracy(), score.margin()) {
+                (Some(accuracy), Some(margin)) => {
+                    history.record_qa(id, score.reviewed, score.correct)?;
+                    println!();
+                    println!(
+                        "Estimated accuracy: {:.0}% ± {:.0}% ({} of {} segments correct)",
+                        accuracy * 100.0,
+                        margin * 100.0,
+                        score.correct,
+                        score.reviewed
+                    );
+                }
+                _ => println!("No segments were reviewed; nothing recorded"),
+            }
+        }
+        Commands::Report { since, until, month, format, output } => {
+            let (since, until) = match month {
+                Some((first, last)) => (Some(first), Some(last)),
+                None => (since, until),
+            };
+            
+            let entries = History::open()?.load(since, until)?;
+            let report = Report::build(&entries, &config.history, since, until).render(format)?;
+            
+            match output {
+                Some(path) => {
+                    fs_err::write(&path, report)?;
+                    println!("Report saved to: {}", path.display());
+                }
+                None => print!("{}", report),
+            }
+        }
         Commands::Platforms => {
             println!("Supported platforms:");
             println!("  • YouTube (youtube.com, youtu.be)");
-            println!("  • Twitter/X (twitter.com, x.com)");
+            println!("  • Twitter/X (twitter.com, x.com, including recorded Spaces)");
+            println!("  • Google Drive and Dropbox share links");
+            println!("  • PeerTube videos and Mastodon posts with video/audio");
+            println!("  • Internet Archive items (archive.org/details/...)");
+            println!("  • Loom videos (loom.com/share/...) and Wistia embeds");
             println!("  • Direct audio/video URLs");
+            println!("  • HLS (.m3u8) and DASH (.mpd) streams, including live streams");
+            println!("  • Objects already in S3 (s3://bucket/key)");
             println!("  • Local audio files (mp3, m4a, wav, flac, ogg)");
             println!("  • Local video files (mp4, mkv, avi, mov, wmv, etc.)");
+            for plugin in &config.extractors.plugins {
+                println!("  • {} (extractor plugin)", plugin.name);
+            }
             println!("  • More platforms coming soon!");
         }
+        Commands::Captions { action } => match action {
+            CaptionsCommands::Lint(args) => {
+                let rules = args.rules(config.captions.clone());
+                
+                let cues = captions::parse_file(&args.file)?;
+                let violations = captions::lint_cues(&cues, &rules);
+                
+                if args.json {
+                    println!("{}", serde_json::to_string_pretty(&violations)?);
+                } else {
+                    for violation in &violations {
+                        println!("{}", violation);
+                    }
+                }
+                
+                if !violations.is_empty() {
+                    anyhow::bail!("{} violation(s) found in {} cue(s)", violations.len(), cues.len());
+                }
+                if !args.json {
+                    println!("✓ {} cue(s) checked, no violations", cues.len());
+                }
+            }
+        },
+        Commands::Domain { action } => match action {
+            DomainCommands::Install { file, sync_vocabulary } => {
+                let pack = DomainPack::install(&file)?;
+                println!("Installed domain pack '{}'", pack.name);
+                
+                if sync_vocabulary {
+                    let vocabulary = pack.vocabulary
+                        .as_ref()
+                        .filter(|v| !v.phrases.is_empty())
+                        .ok_or_else(|| anyhow::anyhow!("Domain pack '{}' has no vocabulary phrases to sync", pack.name))?;
+                    let language = pack.language.clone()
+                        .or_else(|| config.aws.transcription.default_language.clone())
+                        .ok_or_else(|| anyhow::anyhow!("Domain pack '{}' does not declare a language", pack.name))?;
+                    
+                    let client = aws_sdk_transcribe::Client::new(&config.aws_sdk_config().await);
+                    vocabulary::VocabularyManager::new(client)
+                        .create_or_update(&vocabulary.name, &language, vocabulary.phrases.clone())
+                        .await?;
+                    println!("AWS vocabulary '{}' submitted", vocabulary.name);
+                }
+            }
+            DomainCommands::List => {
+                let packs = DomainPack::list_installed()?;
+                if packs.is_empty() {
+                    println!("No domain packs installed ({})", DomainPack::packs_dir()?.display());
+                }
+                for pack in packs {
+                    println!(
+                        "  • {} - {}",
+                        pack.name,
+                        pack.description.as_deref().unwrap_or("(no description)")
+                    );
+                }
+            }
+            DomainCommands::Show { name } => {
+                let pack = DomainPack::load(&name)?;
+                print!("{}", serde_yaml::to_string(&pack)?);
+            }
+            DomainCommands::Remove { name } => {
+                DomainPack::remove(&name)?;
+                println!("Removed domain pack '{}'", name);
+            }
+        },
+        Commands::Resume { job, list, discard, output, format } => {
+            let store = JobStore::open()?;
+            let jobs = match &job {
+                Some(name) => vec![store.get(name)?],
+                None => store.list()?,
+            };
+            if list || jobs.is_empty() {
+                if jobs.is_empty() {
+                    println!("No interrupted jobs");
+                }
+                for job in &jobs {
+                    println!(
+                        "  • {}  started {}  {}",
+                        job.job_name,
+                        job.started_at.format("%Y-%m-%d %H:%M"),
+                        job.audio_info.title.as_deref().unwrap_or(&job.input)
+                    );
+                }
+                return Ok(());
+            }
+            
+            let default_format = OutputFormat::from_str(&config.app.default_output_format, true).unwrap_or(OutputFormat::Text);
+            let delivery = Delivery::new(&config).await?;
+            let pipeline = TranscriptionPipeline::new(config).await?;
+            
+            let mut failed = 0;
+            for job in jobs {
+                if discard {
+                    pipeline.discard_job(&job).await?;
+                    println!("Discarded job {}", job.job_name);
+                    continue;
+                }
+                
+                let mut target = job.output.clone().unwrap_or(OutputTarget {
+                    path: None,
+                    format: default_format.clone(),
+                    extra_formats: Vec::new(),
+                    domain: None,
+                    corrections: None,
+                    render: RenderOptions::default(),
+                    existing: ExistingOutput::default(),
+                });
+                if output.is_some() {
+                    target.path = output.clone();
+                }
+                if let Some(format) = &format {
+                    target.format = format.clone();
+                    target.extra_formats.clear();
+                }
+                
+                println!("Resuming job {} ({})", job.job_name, job.input);
+                let delivered = async {
+                    let result = pipeline.resume_job(&job).await?;
+                    deliver_result(result, &target, target.rule_set()?.as_ref(), &delivery).await
+                }
+                .await;
+                if let Err(e) = delivered {
+                    eprintln!("✗ Job {}: {:#}", job.job_name, e);
+                    failed += 1;
+                }
+            }
+            if failed > 0 {
+                anyhow::bail!("{} jobs could not be resumed", failed);
+            }
+        }
+        Commands::Jobs { action } => {
+            let manager = JobManager::new(aws_sdk_transcribe::Client::new(&config.aws_sdk_config().await));
+            match action {
+                JobsCommands::List { status, limit } => {
+                    let status = status.map(|status| match status {
+                        JobStatus::Queued => TranscriptionJobStatus::Queued,
+                        JobStatus::InProgress => TranscriptionJobStatus::InProgress,
+                        JobStatus::Failed => TranscriptionJobStatus::Failed,
+                        JobStatus::Completed => TranscriptionJobStatus::Completed,
+                    });
+                    let jobs = manager.list(status, limit).await?;
+                    if jobs.is_empty() {
+                        println!("No transcription jobs");
+                    }
+                    let resumable = JobStore::open()?.list()?;
+                    for job in jobs {
+                        let created = job.created.map(|t| t.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default();
+                        let note = if resumable.iter().any(|r| r.job_name == job.name) { "  (resumable)" } else { "" };
+                        println!("  • {}  {:<11}  {}  {}{}", job.name, job.status, created, job.language.unwrap_or_default(), note);
+                    }
+                }
+                JobsCommands::Status { name } => {
+                    let job = manager.status(&name).await?;
+                    let time = |t: Option<chrono::DateTime<chrono::Utc>>| t.map(|t| t.to_rfc3339()).unwrap_or_else(|| "-".to_string());
+                    println!("Job:       {}", job.name);
+                    println!("Status:    {}", job.status);
+                    println!("Created:   {}", time(job.created));
+                    println!("Started:   {}", time(job.started));
+                    println!("Completed: {}", time(job.completed));
+                    println!("Language:  {}", job.language.as_deref().unwrap_or("-"));
+                    println!("Media:     {}", job.media_uri.as_deref().unwrap_or("-"));
+                    if let Some(reason) = &job.failure_reason {
+                        println!("Failure:   {}", reason);
+                    }
+                }
+                JobsCommands::Cancel { name } => {
+                    manager.cancel(&name).await?;
+                    // A resumable job also left its upload behind
+                    let store = JobStore::open()?;
+                    if let Ok(job) = store.get(&name) {
+                        TranscriptionPipeline::new(config).await?.discard_job(&job).await?;
+                    }
+                    println!("Deleted job {}", name);
+                }
+            }
+        }
+        Commands::Cache { action } => {
+            let store = ArtifactStore::open(&config.artifacts)?;
+            match action {
+                CacheCommands::List => {
+                    let entries = store.entries()?;
+                    if entries.is_empty() {
+                        println!("No stored artifacts");
+                    }
+                    for entry in entries {
+                        println!(
+                            "  • {:<14} {:>10}  used {}  {}",
+                            format!("{:?}", entry.kind).to_lowercase(),
+                            utils::format_file_size(entry.size),
+                            entry.last_used.format("%Y-%m-%d %H:%M"),
+                            entry.source
+                        );
+                    }
+                }
+                CacheCommands::Gc => {
+                    let summary = store.gc()?;
+                    println!(
+                        "Removed {} artifact(s) and {} stored file(s), freeing {}",
+                        summary.entries,
+                        summary.objects,
+                        utils::format_file_size(summary.bytes)
+                    );
+                }
+            }
+        }
+        Commands::Queue { action } => match action {
+            QueueCommands::List => {
+                let queue = OfflineQueue::open()?;
+                let runs = queue.list()?;
+                if runs.is_empty() {
+                    println!("No queued runs");
+                }
+                for run in &runs {
+                    let transcribed = if queue.kept_result(run).is_some() { "  (transcribed, not saved yet)" } else { "" };
+                    println!(
+                        "  • {}  queued {}  {}{}",
+                        run.id,
+                        run.queued_at.format("%Y-%m-%d %H:%M"),
+                        run.audio_info.title.as_deref().unwrap_or(&run.input),
+                        transcribed
+                    );
+                }
+            }
+            QueueCommands::Flush { watch } => {
+                let queue = OfflineQueue::open()?;
+                let delivery = Delivery::new(&config).await?;
+                let pipeline = TranscriptionPipeline::new(config).await?;
+                
+                loop {
+                    let runs = queue.list()?;
+                    if runs.is_empty() {
+                        println!("Queue is empty");
+                        break;
+                    }
+                    
+                    let mut offline = false;
+                    for run in runs {
+                        let result = match queue.kept_result(&run) {
+                            Some(result) => {
+                                println!("Saving transcribed run {} ({})", run.id, run.input);
+                                result
+                            }
+                            None => {
+                                println!("Submitting queued run {} ({})", run.id, run.input);
+                                let audio_path = queue.audio_path(&run);
+                                match pipeline.transcribe_audio(run.audio_info.clone(), &audio_path, &run.options).await {
+                                    Ok(result) => result,
+                                    Err(e) if transcribe::is_connectivity_error(&e) => {
+                                        eprintln!("⚠️  AWS is still unreachable");
+                                        offline = true;
+                                        break;
+                                    }
+                                    Err(e) => {
+                                        eprintln!("✗ Run {} failed and stays queued: {:#}", run.id, e);
+                                        continue;
+                                    }
+                                }
+                            }
+                        };
+                        
+                        // The transcription is paid for by now, so a run whose output cannot be
+                        // saved keeps its transcript and is not submitted again
+                        let rules = match run.output.rule_set() {
+                            Ok(rules) => rules,
+                            Err(e) => {
+                                keep_queued_result(&queue, &run, &result, &e);
+                                continue;
+                            }
+                        };
+                        match deliver_result(result.clone(), &run.output, rules.as_ref(), &delivery).await {
+                            Ok(_) => queue.remove(&run.id)?,
+                            Err(e) => keep_queued_result(&queue, &run, &result, &e),
+                        }
+                    }
+                    
+                    match watch {
+                        Some(seconds) if offline => {
+                            tokio::time::sleep(std::time::Duration::from_secs(seconds)).await;
+                        }
+                        _ => break,
+                    }
+                }
+            }
+            QueueCommands::Remove { id } => {
+                OfflineQueue::open()?.remove(&id)?;
+                println!("Removed queued run {}", id);
+            }
+        },
+        Commands::Vocab { action } => match action {
+            VocabCommands::Create { name, file, language, wait } => {
+                upload_vocabulary(&config, &name, &file, language, wait, true).await?;
+            }
+            VocabCommands::Update { name, file, language, wait } => {
+                upload_vocabulary(&config, &name, &file, language, wait, false).await?;
+            }
+            VocabCommands::List => {
+                let manager = vocabulary::VocabularyManager::new(aws_sdk_transcribe::Client::new(&config.aws_sdk_config().await));
+                let vocabularies = manager.list().await?;
+                if vocabularies.is_empty() {
+                    println!("No custom vocabularies (see `rustscribe vocab create`)");
+                }
+                for v in vocabularies {
+                    let modified = v.last_modified.map(|t| t.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default();
+                    println!("  • {}  {}  {}  {}", v.name, v.language, v.state, modified);
+                }
+            }
+            VocabCommands::Delete { name } => {
+                let manager = vocabulary::VocabularyManager::new(aws_sdk_transcribe::Client::new(&config.aws_sdk_config().await));
+                manager.delete(&name).await?;
+                println!("Deleted vocabulary '{}'", name);
+            }
+            VocabCommands::Suggest {
+                inputs,
+                threshold,
+                min_count,
+                limit,
+                output,
+                apply,
+                language,
+            } => {
+                let files = vocabulary::collect_transcript_files(&inputs)?;
+                let results = vocabulary::load_transcripts(&files);
+                if results.is_empty() {
+                    anyhow::bail!("No transcription results found in the given paths");
+                }
+                
+                let mut suggestions = vocabulary::suggest_terms(&results, threshold, min_count);
+                suggestions.truncate(limit);
+                
+                eprintln!("Analyzed {} transcript(s), {} term(s) proposed", results.len(), suggestions.len());
+                for suggestion in &suggestions {
+                    eprintln!(
+                        "   • {} ({}/{} low confidence, mean {:.1}%)",
+                        suggestion.phrase,
+                        suggestion.low_confidence,
+                        suggestion.occurrences,
+                        suggestion.mean_confidence * 100.0
+                    );
+                }
+                
+                let phrase_list = vocabulary::format_phrase_list(&suggestions);
+                match output {
+                    Some(path) => {
+                        fs_err::write(&path, &phrase_list)?;
+                        println!("Vocabulary saved to: {}", path.display());
+                    }
+                    None => print!("{}", phrase_list),
+                }
+                
+                if let Some(name) = apply {
+                    if suggestions.is_empty() {
+                        anyhow::bail!("No terms to add to vocabulary '{}'", name);
+                    }
+                    let language = language
+                        .or_else(|| config.aws.transcription.default_language.clone())
+                        .ok_or_else(|| anyhow::anyhow!("A language code is required to create an AWS vocabulary (use --language)"))?;
+                    
+                    let client = aws_sdk_transcribe::Client::new(&config.aws_sdk_config().await);
+                    let phrases = suggestions.into_iter().map(|s| s.phrase).collect();
+                    vocabulary::VocabularyManager::new(client)
+                        .create_or_update(&name, &language, phrases)
+                        .await?;
+                    println!("AWS vocabulary '{}' submitted (it may take a few minutes to become READY)", name);
+                }
+            }
+        },
+        Commands::Models { action } => {
+            let models = ModelManager::from_config(&config.whisper)?;
+            match action {
+                ModelsCommands::Pull { name, quantization } => {
+                    let quantization = quantization.or_else(|| config.whisper.quantization.clone());
+                    let client = http::build_client(&config.http, &config.network)?;
+                    let path = models.pull(&client, &name, quantization.as_deref()).await?;
+                    println!("Model ready: {}", path.display());
+                }
+                ModelsCommands::List => {
+                    let installed = models.installed()?;
+                    if installed.is_empty() {
+                        println!("No models downloaded (see `rustscribe models pull`)");
+                    }
+                    for model in installed {
+                        println!("  • {}  ({:.0} MB)", model.name, model.size as f64 / 1_048_576.0);
+                    }
+                }
+                ModelsCommands::Remove { name, quantization } => {
+                    models.remove(&name, quantization.as_deref())?;
+                    println!("Removed model {}", ModelManager::file_name(&name, quantization.as_deref()));
+                }
+            }
+        }
+        Commands::Deps { action } => match action {
+            DepsCommands::Install { force } => {
+                let client = http::build_client(&config.http, &config.network)?;
+                let path = deps::install_yt_dlp(&client, force).await?;
+                println!("yt-dlp {} ready: {}", deps::YT_DLP_VERSION, path.display());
+                if let Some(configured) = &config.yt_dlp.path {
+                    println!("Note: yt_dlp.path is set, so {} is used instead", configured.display());
+                }
+            }
+            DepsCommands::Check => {
+                let yt_dlp = config.yt_dlp.binary();
+                let tools = [
+                    ("yt-dlp", yt_dlp.as_path()),
+                    ("ffmpeg", std::path::Path::new("ffmpeg")),
+                    // Only `qa` plays audio
+                    ("ffplay", std::path::Path::new("ffplay")),
+                ];
+                for (name, binary) in tools {
+                    match deps::version_of(binary).await {
+                        Some(version) => println!("  ✅ {}: {} ({})", name, binary.display(), version),
+                        None => println!("  ❌ {}: {} not found", name, binary.display()),
+                    }
+                }
+            }
+        },
+        Commands::Speakers { action } => {
+            let store = SpeakerStore::open()?;
+            match action {
+                SpeakersCommands::Enroll { name, samples } => {
+                    let profile = store.enroll(&name, &samples).await?;
+                    println!("Enrolled {} from {} sample(s)", profile.name, profile.samples);
+                }
+                SpeakersCommands::List => {
+                    let profiles = store.list()?;
+                    if profiles.is_empty() {
+                        println!("No enrolled speakers (see `rustscribe speakers enroll`)");
+                    }
+                    for profile in profiles {
+                        println!(
+                            "  • {}  ({} sample(s), enrolled {})",
+                            profile.name,
+                            profile.samples,
+                            profile.enrolled_at.format("%Y-%m-%d")
+                        );
+                    }
+                }
+                SpeakersCommands::Remove { name } => {
+                    store.remove(&name)?;
+                    println!("Removed speaker {}", name);
+                }
+            }
+        }
     }
 
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// Create (or with `create` unset, update) an AWS vocabulary from a phrase file
+async fn upload_vocabulary(
+    config: &Config,
+    name: &str,
+    file: &std::path::Path,
+    language: Option<String>,
+    wait: bool,
+    create: bool,
+) -> Result<()> {
+    let language = language
+        .or_else(|| config.aws.transcription.default_language.clone())
+        .ok_or_else(|| anyhow::anyhow!("A language code is required to create an AWS vocabulary (use --language)"))?;
+    let phrases = vocabulary::read_phrase_file(file)?;
+    
+    let manager = vocabulary::VocabularyManager::new(aws_sdk_transcribe::Client::new(&config.aws_sdk_config().await));
+    if create {
+        manager.create(name, &language, phrases).await?;
+    } else {
+        manager.update(name, &language, phrases).await?;
+    }
+    if wait {
+        manager.wait_until_ready(name).await?;
+        println!("AWS vocabulary '{}' is ready: rustscribe transcribe URL --vocabulary {}", name, name);
+    } else {
+        println!("AWS vocabulary '{}' submitted (it may take a few minutes to become READY)", name);
+    }
+    Ok(())
+}
+
+/// Transcribe one batch input into `dir`, named after its position and title
+async fn transcribe_into_dir(
+    pipeline: &TranscriptionPipeline,
+    url: &str,
+    index: usize,
+    options: &TranscribeOptions,
+    target: &OutputTarget,
+    dir: &std::path::Path,
+    delivery: &Delivery,
+) -> Result<()> {
+    let result = pipeline.transcribe_from_url(url, options).await?;
+    
+    let title = result.audio_info.title.clone().unwrap_or_else(|| {
+        url.rsplit(['/', '\\']).find(|part| !part.is_empty()).unwrap_or(url).to_string()
+    });
+    let name = format!("{:03}-{}.{}", index + 1, utils::sanitize_filename(&title), target.format.extension());
+    let target = OutputTarget {
+        path: Some(dir.join(name)),
+        ..target.clone()
+    };
+    deliver_result(result, &target, None, delivery).await?;
+    Ok(())
+}
+
+/// Several formats are saved next to one output, each to a file of its own
+fn check_extra_outputs(target: &OutputTarget) -> Result<()> {
+    let Some(path) = &target.path else {
+        anyhow::bail!("Several formats need --output; the others are saved next to it");
+    };
+    if target.render.template.is_some() {
+        anyhow::bail!("--template replaces --format, so it renders one output");
+    }
+    let mut paths = vec![path.clone()];
+    for (extra, format) in target.extra_outputs() {
+        if paths.contains(&extra) {
+            anyhow::bail!("The {} output would be written to {} twice; list each format once", format, extra.display());
+        }
+        target.existing.check(&extra)?;
+        paths.push(extra);
+    }
+    Ok(())
+}
+
+/// What happens to a result besides being written out
+struct Delivery {
+    hooks: HooksConfig,
+    archive: Option<Archive>,
+    artifacts: Option<ArtifactStore>,
+    /// Command the saved output is opened with for review (`--open-with`)
+    open_with: Option<String>,
+}
+
+impl Delivery {
+    async fn new(config: &Config) -> Result<Self> {
+        Ok(Self {
+            hooks: config.hooks.clone(),
+            archive: Archive::from_config(config).await?,
+            artifacts: config.artifacts.enabled.then(|| ArtifactStore::open(&config.artifacts)).transpose()?,
+            open_with: None,
+        })
+    }
+}
+
+/// Apply post-processing and write the result where the user asked for it
+/// Report a queued run whose output could not be saved, keeping its transcript for the next flush
+///
+/// If even that fails the run is dropped rather than transcribed (and paid for) again.
+fn keep_queued_result(queue: &OfflineQueue, run: &QueuedRun, result: &TranscriptionResult, error: &anyhow::Error) {
+    eprintln!("✗ Run {} was transcribed but its output could not be saved: {:#}", run.id, error);
+    match queue.keep_result(run, result) {
+        Ok(()) => eprintln!("  The transcript stays queued and is saved by the next flush without transcribing again"),
+        Err(e) => {
+            eprintln!("  The transcript could not be kept either ({:#}), so the run is removed from the queue", e);
+            if let Err(e) = queue.remove(&run.id) {
+                eprintln!("  Could not remove run {}: {:#}", run.id, e);
+            }
+        }
+    }
+}
+
+async fn deliver_result(
+    mut result: TranscriptionResult,
+    target: &OutputTarget,
+    rules: Option<&RuleSet>,
+    delivery: &Delivery,
+) -> Result<DeliveredFiles> {
+    if let Some(rules) = rules {
+        rules.apply(&mut result);
+    }
+
+    let mut delivered = DeliveredFiles {
+        transcript: target.path.clone(),
+        audio: result.audio_path.clone(),
+        ..Default::default()
+    };
+
+    match &target.path {
+        Some(path) => {
+            let backup = output::save_to_file(&result, path, &target.format, &target.render, target.existing).await?;
+            delivery.hooks.post_format(path, &result).await?;
+            if let Some(store) = &delivery.artifacts {
+                let options = (&result.metadata.job_id, &target.format, &target.render);
+                let key = ArtifactKey::new(ArtifactKind::Rendered, &result.audio_info.original_url, &options);
+                if let Err(e) = store.put_file(&key, path, serde_json::Value::Null) {
+                    tracing::warn!("Could not keep the rendered output: {:#}", e);
+                }
+            }
+            // Batch runs print this while other inputs' bars are drawn
+            progress::suspend(|| {
+                println!("Transcription saved to: {}", path.display());
+                if let Some(backup) = &backup {
+                    println!("Previous output kept as: {}", backup.display());
+                }
+            });
+            if let Some(line) = &delivery.open_with {
+                if let Err(e) = hooks::open_with(line, path, backup.as_deref(), &result).await {
+                    tracing::warn!("Could not open the output: {:#}", e);
+                }
+            }
+            delivered.previous = backup;
+            for (extra, format) in target.extra_outputs() {
+                output::save_to_file(&result, &extra, format, &target.render, target.existing).await?;
+                delivery.hooks.post_format(&extra, &result).await?;
+                progress::suspend(|| println!("Transcription saved to: {}", extra.display()));
+                delivered.other_formats.push(extra);
+            }
+            if let Some(threshold) = target.render.review_threshold {
+                review::save_review(&result, path, threshold, target.existing)?;
+                progress::suspend(|| println!("Review list saved to: {}", review::review_path(path).display()));
+            }
+        }
+        None => {
+            output::print_to_console(&result, &target.format, &target.render)?;
+            // Kept off stdout, which carries the transcript
+            if let Some(threshold) = target.render.review_threshold {
+                eprint!("{}", review::format_review(&result, threshold));
+            }
+        }
+    }
+
+    if let Some(audio_path) = &result.audio_path {
+        progress::suspend(|| println!("Audio saved to: {}", audio_path.display()));
+    }
+    
+    // The transcript is delivered by now, so a failed archive copy only costs the copy
+    if let Some(archive) = &delivery.archive {
+        match archive.store(&result).await {
+            Ok(location) => {
+                progress::suspend(|| println!("Archived to: {}", location));
+                delivered.archive = Some(location);
+            }
+            Err(e) => tracing::warn!("Could not archive the transcript: {:#}", e),
+        }
+    }
+
+    Ok(delivered)
+}
@@ -0,0 +1,195 @@
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::process::Stdio;
+use tokio::process::Command;
+
+use crate::config::Config;
+use crate::transcribe::TranscriptionResult;
+
+/// Characters of the transcript shown in an announcement
+const SNIPPET_CHARS: usize = 200;
+
+/// Where `--notify` announces a finished run to a person
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    /// A native notification (notify-send on Linux, Notification Center on macOS)
+    Desktop,
+    /// A message to the Slack incoming webhook in notify.slack_webhook
+    Slack,
+}
+
+/// What a person is told about a finished run
+#[derive(Debug, Clone, Default)]
+pub struct Announcement {
+    pub title: String,
+    /// Audio length in seconds
+    pub duration: Option<f64>,
+    /// The start of the transcript
+    pub snippet: Option<String>,
+    pub error: Option<String>,
+}
+
+impl Announcement {
+    pub fn completed(result: &TranscriptionResult) -> Self {
+        let duration = result
+            .metadata
+            .audio_duration
+            .or_else(|| result.audio_info.duration.map(|d| d.num_milliseconds() as f64 / 1000.0));
+        Self {
+            title: result.audio_info.title.clone().unwrap_or_else(|| result.audio_info.original_url.clone()),
+            duration,
+            snippet: Some(snippet(&result.transcript, SNIPPET_CHARS)).filter(|snippet| !snippet.is_empty()),
+            error: None,
+        }
+    }
+
+    pub fn failed(source: &str, error: &anyhow::Error) -> Self {
+        Self {
+            title: source.to_string(),
+            error: Some(format!("{:#}", error)),
+            ..Default::default()
+        }
+    }
+
+    fn headline(&self) -> String {
+        match (&self.error, self.duration) {
+            (Some(_), _) => format!("Transcription failed: {}", self.title),
+            (None, Some(duration)) => format!("Transcribed: {} ({})", self.title, crate::utils::format_duration(duration)),
+            (None, None) => format!("Transcribed: {}", self.title),
+        }
+    }
+
+    fn body(&self) -> &str {
+        self.error.as_deref().or(self.snippet.as_deref()).unwrap_or_default()
+    }
+}
+
+/// Announces finished runs on the configured channels
+pub struct Announcer {
+    channels: Vec<Channel>,
+    slack: Option<(reqwest::Client, String)>,
+}
+
+impl Announcer {
+    /// The channels in `config.notify`, if any; Slack needs `notify.slack_webhook`
+    pub fn from_config(config: &Config) -> Result<Option<Self>> {
+        let channels = config.notify.channels.clone();
+        if channels.is_empty() {
+            return Ok(None);
+        }
+
+        let slack = match &config.notify.slack_webhook {
+            Some(webhook) if channels.contains(&Channel::Slack) => {
+                Some((crate::http::build_service_client(&config.http, &config.network)?, webhook.clone()))
+            }
+            None if channels.contains(&Channel::Slack) => anyhow::bail!(
+                "Slack notifications need an incoming webhook: rustscribe config set notify.slack_webhook URL"
+            ),
+            _ => None,
+        };
+        Ok(Some(Self { channels, slack }))
+    }
+
+    /// Tell every channel; one that fails is logged and does not stop the others
+    pub async fn announce(&self, announcement: &Announcement) {
+        for channel in &self.channels {
+            let sent = match channel {
+                Channel::Desktop => desktop(&announcement.headline(), announcement.body()).await,
+                Channel::Slack => match &self.slack {
+                    Some((client, webhook)) => slack(client, webhook, announcement).await,
+                    None => Ok(()),
+                },
+            };
+            if let Err(e) = sent {
+                tracing::warn!("Could not send the {} notification: {:#}", format!("{:?}", channel).to_lowercase(), e);
+            }
+        }
+    }
+}
+
+async fn desktop(headline: &str, body: &str) -> Result<()> {
+    let (program, args) = if cfg!(target_os = "macos") {
+        // Passed as arguments, so titles need no AppleScript quoting
+        let script = "display notification (item 2 of argv) with title \"RustScribe\" subtitle (item 1 of argv)";
+        ("osascript", vec!["-e", "on run argv", "-e", script, "-e", "end run", headline, body])
+    } else if cfg!(target_os = "windows") {
+        anyhow::bail!("Desktop notifications are not supported on Windows yet; use --notify slack");
+    } else {
+        ("notify-send", vec!["--app-name=RustScribe", headline, body])
+    };
+
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .with_context(|| format!("Failed to run {}", program))?;
+    if !output.status.success() {
+        anyhow::bail!("{} failed: {}", program, String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+async fn slack(client: &reqwest::Client, webhook: &str, announcement: &Announcement) -> Result<()> {
+    let response = client
+        .post(webhook)
+        .json(&slack_message(announcement))
+        .send()
+        .await
+        .context("Failed to reach the Slack webhook")?;
+    if !response.status().is_success() {
+        anyhow::bail!("Slack answered HTTP {}", response.status());
+    }
+    Ok(())
+}
+
+fn slack_message(announcement: &Announcement) -> serde_json::Value {
+    // Slack reads &, < and > as markup
+    let escape = |text: &str| text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+    let mut text = format!("*{}*", escape(&announcement.headline()));
+    let body = announcement.body();
+    if !body.is_empty() {
+        text.push_str(&format!("\n> {}", escape(body).replace('\n', "\n> ")));
+    }
+    json!({ "text": text })
+}
+
+/// The first `max_chars` of `text`, cut at a word and marked with an ellipsis
+fn snippet(text: &str, max_chars: usize) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= max_chars {
+        return text;
+    }
+    let cut: String = text.chars().take(max_chars).collect();
+    let cut = cut.rsplit_once(' ').map_or(cut.as_str(), |(words, _)| words);
+    format!("{}…", cut.trim_end_matches([',', '.', ';', ':']))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_announcements() {
+        assert_eq!(snippet("Short  and\nsweet.", 50), "Short and sweet.");
+        assert_eq!(snippet("Welcome back, everyone. Today we talk", 24), "Welcome back, everyone…");
+
+        let announcement = Announcement {
+            title: "Q&A <live>".to_string(),
+            duration: Some(754.0),
+            snippet: Some("Thanks for joining".to_string()),
+            error: None,
+        };
+        let message = slack_message(&announcement);
+        let text = message["text"].as_str().unwrap();
+        assert!(text.starts_with("*Transcribed: Q&amp;A &lt;live&gt; ("), "{}", text);
+        assert!(text.ends_with("\n> Thanks for joining"), "{}", text);
+
+        let failed = Announcement::failed("talk.mp4", &anyhow::anyhow!("no network"));
+        assert_eq!(failed.headline(), "Transcription failed: talk.mp4");
+        assert_eq!(failed.body(), "no network");
+    }
+}
@@ -0,0 +1,175 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use ring::hmac;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::output::summary::RunSummary;
+
+mod channels;
+
+pub use channels::{Announcement, Announcer, Channel};
+
+/// Header with the Unix time a notification was signed at
+pub const TIMESTAMP_HEADER: &str = "X-RustScribe-Timestamp";
+
+/// Header with `sha256=<hex HMAC of "<timestamp>.<body>">`
+pub const SIGNATURE_HEADER: &str = "X-RustScribe-Signature";
+
+/// Who is told when a transcription finishes or fails
+///
+/// `url` is a webhook for other services; `channels` are for people. The webhook payload is the run summary (`--summary json`) plus the source URL. With a `secret`,
+/// receivers can check the signature header and reject old timestamps to stop replays.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotifyConfig {
+    /// URL the notification is POSTed to (unset: no webhook)
+    pub url: Option<String>,
+
+    /// Shared secret the payload is signed with (HMAC-SHA256)
+    pub secret: Option<String>,
+
+    /// Seconds to wait for the webhook to answer
+    pub timeout_secs: u64,
+
+    /// Where finished runs are announced to a person (`--notify`): desktop, slack
+    pub channels: Vec<Channel>,
+
+    /// Slack incoming webhook URL for the slack channel
+    pub slack_webhook: Option<String>,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            secret: None,
+            timeout_secs: 10,
+            channels: Vec::new(),
+            slack_webhook: None,
+        }
+    }
+}
+
+impl NotifyConfig {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(url) = &self.url {
+            let parsed = url::Url::parse(url).with_context(|| format!("Invalid notify URL: {}", url))?;
+            if !matches!(parsed.scheme(), "http" | "https") {
+                anyhow::bail!("notify.url must be an http(s) URL, got {}", url);
+            }
+        }
+        if let Some(webhook) = &self.slack_webhook {
+            if !webhook.starts_with("https://") {
+                anyhow::bail!("notify.slack_webhook must be an https:// URL, got {}", webhook);
+            }
+        }
+        if self.secret.as_deref().is_some_and(|secret| secret.trim().is_empty()) {
+            anyhow::bail!("notify.secret must not be empty");
+        }
+        Ok(())
+    }
+}
+
+/// What a webhook receives
+#[derive(Debug, Serialize)]
+pub struct Notification<'a> {
+    /// The URL or file that was transcribed
+    pub source: &'a str,
+    pub finished_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub summary: &'a RunSummary,
+}
+
+/// Sends notifications to the configured webhook
+pub struct Notifier {
+    client: reqwest::Client,
+    url: String,
+    secret: Option<String>,
+    timeout: Duration,
+    retries: u32,
+}
+
+impl Notifier {
+    /// The webhook configured in `config`, if there is one
+    pub fn from_config(config: &Config) -> Result<Option<Self>> {
+        let Some(url) = &config.notify.url else {
+            return Ok(None);
+        };
+        config.notify.validate()?;
+
+        Ok(Some(Self {
+            client: crate::http::build_service_client(&config.http, &config.network)?,
+            url: url.clone(),
+            secret: config.notify.secret.clone(),
+            timeout: Duration::from_secs(config.notify.timeout_secs),
+            retries: config.http.retries,
+        }))
+    }
+
+    /// POST the outcome of a run; anything but a 2xx answer is an error
+    pub async fn send(&self, source: &str, summary: &RunSummary) -> Result<()> {
+        let notification = Notification {
+            source,
+            finished_at: Utc::now(),
+            summary,
+        };
+        let body = serde_json::to_string(&notification)?;
+
+        let mut request = self
+            .client
+            .post(&self.url)
+            .timeout(self.timeout)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(secret) = &self.secret {
+            let timestamp = notification.finished_at.timestamp();
+            request = request
+                .header(TIMESTAMP_HEADER, timestamp)
+                .header(SIGNATURE_HEADER, signature(secret, timestamp, &body));
+        }
+
+        let response = crate::http::send_with_retry(request.body(body), self.retries)
+            .await
+            .with_context(|| format!("Failed to reach {}", self.url))?;
+        if !response.status().is_success() {
+            anyhow::bail!("{} answered HTTP {}", self.url, response.status());
+        }
+        Ok(())
+    }
+}
+
+/// The signature header value for a payload sent at `timestamp`
+pub fn signature(secret: &str, timestamp: i64, body: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let tag = hmac::sign(&key, format!("{}.{}", timestamp, body).as_bytes());
+    let hex: String = tag.as_ref().iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("sha256={}", hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_and_payload() {
+        // Same as `echo -n '0.what do ya want for nothing?' | openssl dgst -sha256 -hmac Jefe`
+        let signature = signature("Jefe", 0, "what do ya want for nothing?");
+        assert_eq!(signature, "sha256=37f471929915ccd2cbbe79feb84ffcff4f2bb25e15fc41c2506687331ae179cc");
+        assert_ne!(super::signature("Jefe", 1, "what do ya want for nothing?"), signature);
+
+        let summary = RunSummary::failed(&anyhow::anyhow!("no network"));
+        let notification = Notification {
+            source: "https://example.com/talk.mp3",
+            finished_at: Utc::now(),
+            summary: &summary,
+        };
+        let payload = serde_json::to_value(&notification).unwrap();
+        assert_eq!(payload["source"], "https://example.com/talk.mp3");
+        assert_eq!(payload["status"], "failed");
+        assert_eq!(payload["error"], "no network");
+
+        assert!(NotifyConfig { url: Some("ftp://hooks".to_string()), ..Default::default() }.validate().is_err());
+        assert!(NotifyConfig { url: Some("https://hooks.example.com/t".to_string()), ..Default::default() }.validate().is_ok());
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use crate::transcribe::TranscriptionResult;
+
+/// Tier name used when the transcript has no speaker labels
+const NO_SPEAKER: &str = "transcript";
+
+/// One interval tier: a speaker's segments, or their words
+struct Tier {
+    name: String,
+    speaker: Option<String>,
+    words: bool,
+    /// Start, end and text, in order and never overlapping
+    intervals: Vec<(f64, f64, String)>,
+}
+
+/// Format transcription as a Praat TextGrid
+///
+/// Each speaker gets an interval tier of segments and, when word timings are known, one of
+/// words. Praat tiers cover the whole recording, so the gaps between intervals are empty ones.
+pub fn format_as_textgrid(result: &TranscriptionResult) -> String {
+    let tiers = tiers(result);
+    let end = tiers
+        .iter()
+        .filter_map(|tier| tier.intervals.last().map(|(_, end, _)| *end))
+        .fold(result.metadata.audio_duration.unwrap_or(0.0), f64::max);
+
+    let mut output = String::from("File type = \"ooTextFile\"\nObject class = \"TextGrid\"\n\n");
+    output.push_str(&format!("xmin = 0\nxmax = {}\ntiers? <exists>\nsize = {}\nitem []:\n", seconds(end), tiers.len()));
+    for (index, tier) in tiers.iter().enumerate() {
+        // Empty intervals fill the silences, so the tier runs from 0 to the end
+        let mut intervals: Vec<(f64, f64, &str)> = Vec::new();
+        let mut position = 0.0;
+        for (start, stop, text) in &tier.intervals {
+            if *start > position {
+                intervals.push((position, *start, ""));
+            }
+            intervals.push((*start, *stop, text));
+            position = *stop;
+        }
+        if end > position || intervals.is_empty() {
+            intervals.push((position, end, ""));
+        }
+
+        output.push_str(&format!("    item [{}]:\n", index + 1));
+        output.push_str("        class = \"IntervalTier\"\n");
+        output.push_str(&format!("        name = \"{}\"\n", quote(&tier.name)));
+        output.push_str(&format!("        xmin = 0\n        xmax = {}\n", seconds(end)));
+        output.push_str(&format!("        intervals: size = {}\n", intervals.len()));
+        for (number, (start, stop, text)) in intervals.iter().enumerate() {
+            output.push_str(&format!(
+                "        intervals [{}]:\n            xmin = {}\n            xmax = {}\n            text = \"{}\"\n",
+                number + 1,
+                seconds(*start),
+                seconds(*stop),
+                quote(text)
+            ));
+        }
+    }
+
+    output
+}
+
+/// Format transcription as an ELAN annotation document (EAF 3.0)
+///
+/// Tiers are as in the TextGrid, with each speaker as the participant of their tiers. The
+/// saved audio (`--save-audio`) is linked as the media file.
+pub fn format_as_eaf(result: &TranscriptionResult) -> String {
+    let tiers = tiers(result);
+    let millis = |seconds: f64| (seconds.max(0.0) * 1000.0).round() as u64;
+    let slots: Vec<u64> = tiers
+        .iter()
+        .flat_map(|tier| tier.intervals.iter().flat_map(|(start, end, _)| [millis(*start), millis(*end)]))
+        .collect::<BTreeSet<u64>>()
+        .into_iter()
+        .collect();
+    let slot = |seconds: f64| slots.binary_search(&millis(seconds)).unwrap_or_default() + 1;
+
+    let mut output = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    output.push_str(&format!(
+        "<ANNOTATION_DOCUMENT AUTHOR=\"RustScribe\" DATE=\"{}\" FORMAT=\"3.0\" VERSION=\"3.0\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:noNamespaceSchemaLocation=\"http://www.mpi.nl/tools/elan/EAFv3.0.xsd\">\n",
+        result.metadata.completed_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, false)
+    ));
+    output.push_str("  <HEADER MEDIA_FILE=\"\" TIME_UNITS=\"milliseconds\">\n");
+    if let Some(path) = result.audio_path.as_deref() {
+        if let Some(url) = super::html::audio_source(path) {
+            output.push_str(&format!(
+                "    <MEDIA_DESCRIPTOR MEDIA_URL=\"{}\" MIME_TYPE=\"{}\"/>\n",
+                escape_xml(&url),
+                media_type(path)
+            ));
+        }
+    }
+    output.push_str("  </HEADER>\n  <TIME_ORDER>\n");
+    for (index, value) in slots.iter().enumerate() {
+        output.push_str(&format!("    <TIME_SLOT TIME_SLOT_ID=\"ts{}\" TIME_VALUE=\"{}\"/>\n", index + 1, value));
+    }
+    output.push_str("  </TIME_ORDER>\n");
+
+    let mut annotation = 0;
+    for tier in &tiers {
+        let participant = tier.speaker.as_deref().map(escape_xml).unwrap_or_default();
+        let kind = if tier.words { "word" } else { "utterance" };
+        output.push_str(&format!(
+            "  <TIER LINGUISTIC_TYPE_REF=\"{}\" PARTICIPANT=\"{}\" TIER_ID=\"{}\">\n",
+            kind,
+            participant,
+            escape_xml(&tier.name)
+        ));
+        for (start, end, text) in &tier.intervals {
+            annotation += 1;
+            output.push_str(&format!(
+                "    <ANNOTATION>\n      <ALIGNABLE_ANNOTATION ANNOTATION_ID=\"a{}\" TIME_SLOT_REF1=\"ts{}\" \
+                 TIME_SLOT_REF2=\"ts{}\">\n        <ANNOTATION_VALUE>{}</ANNOTATION_VALUE>\n      \
+                 </ALIGNABLE_ANNOTATION>\n    </ANNOTATION>\n",
+                annotation,
+                slot(*start),
+                slot(*end),
+                escape_xml(text)
+            ));
+        }
+        output.push_str("  </TIER>\n");
+    }
+
+    for kind in ["utterance", "word"] {
+        output.push_str(&format!(
+            "  <LINGUISTIC_TYPE GRAPHIC_REFERENCES=\"false\" LINGUISTIC_TYPE_ID=\"{}\" TIME_ALIGNABLE=\"true\"/>\n",
+            kind
+        ));
+    }
+    output.push_str("</ANNOTATION_DOCUMENT>\n");
+    output
+}
+
+/// A segment tier and, with word timings, a word tier for each speaker in order of appearance
+///
+/// Words without a speaker of their own belong to the speaker of the segment they fall in.
+fn tiers(result: &TranscriptionResult) -> Vec<Tier> {
+    let words = super::formatters::words_with_speakers(result).unwrap_or_default();
+
+    let mut speakers: Vec<Option<String>> = Vec::new();
+    for speaker in result.segments.iter().map(|s| &s.speaker_id).chain(words.iter().map(|w| &w.speaker_id)) {
+        if !speakers.contains(speaker) {
+            speakers.push(speaker.clone());
+        }
+    }
+
+    let mut tiers = Vec::new();
+    for speaker in speakers {
+        let name = speaker.as_deref().unwrap_or(NO_SPEAKER);
+        let segments = result
+            .segments
+            .iter()
+            .filter(|s| s.speaker_id == speaker)
+            .map(|s| (s.start_time, s.end_time, s.text.trim().to_string()));
+        tiers.push(Tier {
+            name: name.to_string(),
+            speaker: speaker.clone(),
+            words: false,
+            intervals: without_overlaps(segments),
+        });
+
+        if !words.is_empty() {
+            let words = words
+                .iter()
+                .filter(|w| w.speaker_id == speaker)
+                .map(|w| (w.start_time, w.end_time, w.word.clone()));
+            let intervals = without_overlaps(words);
+            tiers.push(Tier {
+                name: format!("{} words", name),
+                speaker,
+                words: true,
+                intervals,
+            });
+        }
+    }
+    tiers
+}
+
+/// Intervals in time order, each starting no earlier than the one before ends
+///
+/// Neither Praat nor ELAN allows overlaps within a tier; intervals left empty are dropped.
+fn without_overlaps(intervals: impl Iterator<Item = (f64, f64, String)>) -> Vec<(f64, f64, String)> {
+    let mut intervals: Vec<(f64, f64, String)> = intervals.collect();
+    intervals.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut kept: Vec<(f64, f64, String)> = Vec::with_capacity(intervals.len());
+    for (start, end, text) in intervals {
+        let start = start.max(kept.last().map_or(0.0, |(_, end, _)| *end));
+        if end > start {
+            kept.push((start, end, text));
+        }
+    }
+    kept
+}
+
+/// MIME type ELAN expects for the linked media
+fn media_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()).map(str::to_lowercase).as_deref() {
+        Some("wav") => "audio/x-wav",
+        Some("mp3") => "audio/mpeg",
+        Some("mp4") | Some("m4a") => "video/mp4",
+        _ => "audio/*",
+    }
+}
+
+/// Seconds to the millisecond, without trailing zeros
+fn seconds(value: f64) -> String {
+    format!("{}", (value * 1000.0).round() / 1000.0)
+}
+
+/// TextGrid strings double their quotes
+fn quote(text: &str) -> String {
+    text.replace('"', "\"\"")
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::formatters::WordTimestamp;
+
+    #[test]
+    fn test_textgrid_and_eaf_tiers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("interview.srt");
+        let srt = "1\n00:00:01,000 --> 00:00:02,000\nSay \"hi\".\n\n\
+                   2\n00:00:02,500 --> 00:00:03,000\nHi & bye.\n";
+        fs_err::write(&path, srt).unwrap();
+        let mut result = crate::output::load_transcript(&path).unwrap();
+        result.metadata.audio_duration = Some(4.0);
+        result.segments[0].speaker_id = Some("spk_0".to_string());
+        result.segments[1].speaker_id = Some("spk_1".to_string());
+        let word = |word: &str, start_time: f64, end_time: f64| WordTimestamp {
+            word: word.to_string(),
+            start_time,
+            end_time,
+            confidence: None,
+            speaker_id: None,
+            alternatives: Vec::new(),
+        };
+        result.words = Some(vec![word("Say", 1.0, 1.4), word("\"hi\".", 1.5, 2.0), word("Hi", 2.5, 2.7)]);
+
+        let textgrid = format_as_textgrid(&result);
+        assert!(textgrid.contains("xmax = 4\ntiers? <exists>\nsize = 4\n"));
+        assert!(textgrid.contains("        name = \"spk_0 words\"\n"));
+        assert!(textgrid.contains("name = \"spk_0\"\n        xmin = 0\n        xmax = 4\n        intervals: size = 3\n"));
+        assert!(textgrid.contains(
+            "        intervals [2]:\n            xmin = 1\n            xmax = 2\n            text = \"Say \"\"hi\"\".\"\n"
+        ));
+        // The last speaker's word tier fills up to the end of the audio
+        assert!(textgrid.ends_with("            xmin = 2.7\n            xmax = 4\n            text = \"\"\n"));
+
+        let eaf = format_as_eaf(&result);
+        assert!(eaf.contains("<TIME_SLOT TIME_SLOT_ID=\"ts1\" TIME_VALUE=\"1000\"/>"));
+        assert!(eaf.contains("<TIER LINGUISTIC_TYPE_REF=\"utterance\" PARTICIPANT=\"spk_1\" TIER_ID=\"spk_1\">"));
+        assert!(eaf.contains("<TIER LINGUISTIC_TYPE_REF=\"word\" PARTICIPANT=\"spk_1\" TIER_ID=\"spk_1 words\">"));
+        assert!(eaf.contains("<ANNOTATION_VALUE>Hi &amp; bye.</ANNOTATION_VALUE>"));
+        assert!(!eaf.contains("MEDIA_DESCRIPTOR"));
+    }
+}
@@ -0,0 +1,122 @@
+use anyhow::Result;
+
+use super::formatters::words_with_speakers;
+use super::karaoke::{time_words, KaraokeWord};
+use super::layout::{shape_cues, ShapedCue, SubtitleLayout};
+use crate::transcribe::TranscriptionResult;
+
+/// White text that starts out grey under `--karaoke` and turns white as each word is spoken
+const STYLE: &str = "Style: Default,Arial,64,&H00FFFFFF,&H00A0A0A0,&H00000000,&H80000000,\
+                     0,0,0,0,100,100,0,0,1,3,1,2,60,60,50,1";
+
+/// Format transcription as Advanced SubStation Alpha (ASS) subtitles
+///
+/// Lines are wrapped as for SRT, so the script turns off the renderer's own wrapping. With
+/// `--karaoke` every word carries a `\k` tag, which karaoke renderers (libass, Aegisub) use
+/// to highlight words as they are spoken.
+pub fn format_as_ass(result: &TranscriptionResult, layout: &SubtitleLayout) -> Result<String> {
+    // Speakers go in the Name field rather than the text
+    let cues = shape_cues(&result.segments, layout, |_| 0);
+    let timed = if layout.karaoke { Some(time_words(&cues, &words_with_speakers(result)?)) } else { None };
+
+    let mut output = String::from("[Script Info]\n; Transcribed by RustScribe\n");
+    let title = result.audio_info.title.as_deref().unwrap_or("Transcript");
+    output.push_str(&format!("Title: {}\n", title.replace(['\n', '\r'], " ")));
+    output.push_str("ScriptType: v4.00+\nWrapStyle: 2\nScaledBorderAndShadow: yes\nPlayResX: 1920\nPlayResY: 1080\n\n");
+
+    output.push_str("[V4+ Styles]\n");
+    output.push_str(
+        "Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, \
+         Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, \
+         MarginL, MarginR, MarginV, Encoding\n",
+    );
+    output.push_str(STYLE);
+    output.push_str("\n\n[Events]\n");
+    output.push_str("Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n");
+
+    for (index, cue) in cues.iter().enumerate() {
+        let text = match &timed {
+            Some(timed) => karaoke_text(cue, &timed[index]),
+            None => cue.lines.iter().map(|line| escape(line)).collect::<Vec<_>>().join("\\N"),
+        };
+        output.push_str(&format!(
+            "Dialogue: 0,{},{},Default,{},0,0,0,,{}\n",
+            format_ass_timestamp(cue.start_time),
+            format_ass_timestamp(cue.end_time),
+            cue.speaker.as_deref().map(|speaker| speaker.replace(',', " ")).unwrap_or_default(),
+            text
+        ));
+    }
+
+    Ok(output)
+}
+
+/// Cue text with each word's `\k` tag, its time until the next word in centiseconds
+fn karaoke_text(cue: &ShapedCue, lines: &[Vec<KaraokeWord>]) -> String {
+    let centiseconds = |seconds: f64| ((seconds - cue.start_time).max(0.0) * 100.0).round() as u64;
+    let words: Vec<(usize, &KaraokeWord)> =
+        lines.iter().enumerate().flat_map(|(line, words)| words.iter().map(move |word| (line, word))).collect();
+
+    let mut text = String::new();
+    for (position, (line, word)) in words.iter().enumerate() {
+        let until = words.get(position + 1).map_or(cue.end_time, |(_, next)| next.start_time);
+        // The first word is highlighted from the start of the cue
+        let from = if position == 0 { 0 } else { centiseconds(word.start_time) };
+        if position > 0 {
+            text.push_str(if words[position - 1].0 == *line { " " } else { "\\N" });
+        }
+        text.push_str(&format!("{{\\k{}}}{}", centiseconds(until).saturating_sub(from), escape(&word.text)));
+    }
+    text
+}
+
+/// Format timestamp for ASS format (H:MM:SS.cc)
+fn format_ass_timestamp(seconds: f64) -> String {
+    let total_centiseconds = (seconds.max(0.0) * 100.0).round() as u64;
+    let hours = total_centiseconds / 360_000;
+    let minutes = (total_centiseconds % 360_000) / 6_000;
+    let secs = (total_centiseconds % 6_000) / 100;
+    let centis = total_centiseconds % 100;
+
+    format!("{}:{:02}:{:02}.{:02}", hours, minutes, secs, centis)
+}
+
+/// Braces would start an override block, and ASS has no way to escape them
+fn escape(text: &str) -> String {
+    text.replace('{', "(").replace('}', ")")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::formatters::WordTimestamp;
+
+    #[test]
+    fn test_format_as_ass() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("song.srt");
+        let srt = "1\n00:00:01,000 --> 00:00:03,000\nOne {two} three\n";
+        fs_err::write(&path, srt).unwrap();
+        let mut result = crate::output::load_transcript(&path).unwrap();
+        result.segments[0].speaker_id = Some("spk_0".to_string());
+
+        let ass = format_as_ass(&result, &SubtitleLayout::default()).unwrap();
+        assert!(ass.starts_with("[Script Info]\n; Transcribed by RustScribe\nTitle: song\nScriptType: v4.00+\n"));
+        assert!(ass.ends_with("Dialogue: 0,0:00:01.00,0:00:03.00,Default,spk_0,0,0,0,,One (two) three\n"));
+        assert_eq!(format_ass_timestamp(3725.257), "1:02:05.26");
+
+        let karaoke = SubtitleLayout { karaoke: true, max_chars_per_line: 9, ..Default::default() };
+        assert!(format_as_ass(&result, &karaoke).is_err());
+        let word = |word: &str, start_time: f64, end_time: f64| WordTimestamp {
+            word: word.to_string(),
+            start_time,
+            end_time,
+            confidence: None,
+            speaker_id: None,
+            alternatives: Vec::new(),
+        };
+        result.words = Some(vec![word("one", 1.2, 1.5), word("two", 1.5, 2.0), word("three", 2.25, 2.8)]);
+        let ass = format_as_ass(&result, &karaoke).unwrap();
+        assert!(ass.ends_with(",,{\\k50}One {\\k75}(two)\\N{\\k75}three\n"));
+    }
+}
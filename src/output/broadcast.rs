@@ -0,0 +1,163 @@
+use super::retime::retime_segments;
+use super::timecode::{FrameRate, Timecode};
+use crate::transcribe::TranscriptionResult;
+use crate::utils::text;
+
+/// Frame rate EDL events are counted in when `--fps` is not given
+const DEFAULT_EDL_FPS: FrameRate = FrameRate {
+    fps: 30.0,
+    drop_frame: false,
+};
+
+/// Maximum characters of an EDL title or clip name, per CMX 3600
+const EDL_NAME_LENGTH: usize = 70;
+
+/// Format transcription as a CMX 3600 edit decision list
+///
+/// Each segment becomes an event whose source and record timecodes cover the segment,
+/// with the transcript text as a comment, so editors can import it as markers.
+pub fn format_as_edl(result: &TranscriptionResult, timecode: &Timecode) -> String {
+    let rate = timecode.fps.unwrap_or(DEFAULT_EDL_FPS);
+    let title = result.audio_info.title.as_deref().unwrap_or("Transcript");
+
+    let mut output = format!("TITLE: {}\n", edl_name(title));
+    output.push_str(if rate.drop_frame { "FCM: DROP FRAME\n\n" } else { "FCM: NON-DROP FRAME\n\n" });
+
+    for (i, segment) in retime_segments(&result.segments).iter().enumerate() {
+        let start = timecode.label(segment.start_time, rate);
+        let end = timecode.label(segment.end_time, rate);
+
+        output.push_str(&format!(
+            "{:03}  AX       AA/V  C        {} {} {} {}\n",
+            i + 1,
+            start,
+            end,
+            start,
+            end
+        ));
+        output.push_str(&format!("* FROM CLIP NAME: {}\n", edl_name(title)));
+
+        let comment = match &segment.speaker_id {
+            Some(speaker) => format!("{}: {}", speaker, segment.text),
+            None => segment.text.clone(),
+        };
+        output.push_str(&format!("* COMMENT: {}\n\n", comment.replace('\n', " ")));
+    }
+
+    output
+}
+
+/// Format transcription as TTML (Timed Text Markup Language)
+///
+/// With a frame rate, times are SMPTE timecodes and the document declares the frame
+/// rate and drop mode; otherwise they are media clock times in milliseconds.
+pub fn format_as_ttml(result: &TranscriptionResult, timecode: &Timecode) -> String {
+    let mut output = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+
+    output.push_str("<tt xmlns=\"http://www.w3.org/ns/ttml\" xmlns:ttp=\"http://www.w3.org/ns/ttml#parameter\"");
+    output.push_str(&format!(" xml:lang=\"{}\"", escape_xml(&result.metadata.language)));
+    if let Some(rate) = timecode.fps {
+        let ntsc = rate.fps.fract() != 0.0;
+        output.push_str(&format!(" ttp:timeBase=\"smpte\" ttp:frameRate=\"{}\"", rate.nominal()));
+        if ntsc {
+            output.push_str(" ttp:frameRateMultiplier=\"1000 1001\"");
+        }
+        output.push_str(if rate.drop_frame { " ttp:dropMode=\"dropNTSC\"" } else { " ttp:dropMode=\"nonDrop\"" });
+    }
+    output.push_str(">\n  <body>\n    <div>\n");
+
+    for segment in &retime_segments(&result.segments) {
+        let (begin, end) = match timecode.fps {
+            // TTML writes SMPTE timecodes with colons even in drop-frame
+            Some(rate) => (
+                timecode.label(segment.start_time, rate).replace(';', ":"),
+                timecode.label(segment.end_time, rate).replace(';', ":"),
+            ),
+            None => (
+                clock_time(timecode.apply(segment.start_time)),
+                clock_time(timecode.apply(segment.end_time)),
+            ),
+        };
+
+        let lines: Vec<String> = text::wrap_to_width(&segment.text, super::formatters::SUBTITLE_LINE_WIDTH)
+            .iter()
+            .map(|line| escape_xml(line))
+            .collect();
+        let speaker = segment
+            .speaker_id
+            .as_ref()
+            .map(|speaker| format!("Speaker {}: ", escape_xml(speaker)))
+            .unwrap_or_default();
+
+        output.push_str(&format!(
+            "      <p begin=\"{}\" end=\"{}\">{}{}</p>\n",
+            begin,
+            end,
+            speaker,
+            lines.join("<br/>")
+        ));
+    }
+
+    output.push_str("    </div>\n  </body>\n</tt>\n");
+    output
+}
+
+/// Media clock time (HH:MM:SS.mmm)
+fn clock_time(seconds: f64) -> String {
+    let total_milliseconds = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        total_milliseconds / 3_600_000,
+        (total_milliseconds % 3_600_000) / 60_000,
+        (total_milliseconds % 60_000) / 1_000,
+        total_milliseconds % 1_000
+    )
+}
+
+/// Clip names are plain ASCII of limited length in CMX 3600
+fn edl_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii() && !c.is_ascii_control() { c } else { '_' })
+        .take(EDL_NAME_LENGTH)
+        .collect()
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_result() -> TranscriptionResult {
+        crate::transcribe::fixture::result(&[(0.0, 2.5, "Hello world")])
+    }
+
+    #[test]
+    fn test_edl_uses_drop_frame_timecode() {
+        let result = create_test_result();
+        let timecode = Timecode::new(Some("01:00:00;00"), Some("29.97".parse().unwrap())).unwrap();
+
+        let edl = format_as_edl(&result, &timecode);
+
+        assert!(edl.contains("FCM: DROP FRAME"));
+        assert!(edl.contains("001  AX       AA/V  C        01:00:00;00 01:00:02;15"));
+        assert!(edl.contains("* COMMENT: Hello world"));
+    }
+
+    #[test]
+    fn test_ttml_with_and_without_frame_rate() {
+        let result = create_test_result();
+
+        let media = format_as_ttml(&result, &Timecode::default());
+        assert!(media.contains("<p begin=\"00:00:00.000\" end=\"00:00:02.500\">Hello world</p>"));
+
+        let smpte = format_as_ttml(&result, &Timecode::new(Some("10:00:00:00"), Some("25".parse().unwrap())).unwrap());
+        assert!(smpte.contains("ttp:timeBase=\"smpte\" ttp:frameRate=\"25\" ttp:dropMode=\"nonDrop\""));
+        assert!(smpte.contains("<p begin=\"10:00:00:00\" end=\"10:00:02:13\">"));
+    }
+}
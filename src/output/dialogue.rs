@@ -0,0 +1,66 @@
+use super::legal::{format_clock, speaker_caption};
+use crate::postprocess::paragraphs::{group_paragraphs, paragraph_text};
+use crate::transcribe::{TranscriptSegment, TranscriptionResult};
+
+/// Format transcription as a dialogue, screenplay style
+///
+/// Consecutive segments of one speaker are merged into a turn led by the speaker's name in
+/// capitals (`SPEAKER 1: ...`). With timestamps, each turn gets a header line with its start
+/// time instead. Transcripts without speakers are split into paragraphs.
+pub fn format_as_dialogue(result: &TranscriptionResult, include_timestamps: bool) -> String {
+    let turns: Vec<&[TranscriptSegment]> = if result.segments.iter().any(|s| s.speaker_id.is_some()) {
+        result.segments.chunk_by(|a, b| a.speaker_id == b.speaker_id).collect()
+    } else {
+        group_paragraphs(&result.segments, &[])
+    };
+
+    let mut output = String::new();
+    for turn in turns {
+        let caption = turn[0].speaker_id.as_deref().map(speaker_caption);
+        let text = paragraph_text(turn);
+        let start = format_clock(turn[0].start_time);
+        match (include_timestamps, caption) {
+            (true, Some(caption)) => output.push_str(&format!("[{}] {}\n{}\n\n", start, caption, text)),
+            (true, None) => output.push_str(&format!("[{}]\n{}\n\n", start, text)),
+            (false, Some(caption)) => output.push_str(&format!("{}: {}\n\n", caption, text)),
+            (false, None) => output.push_str(&format!("{}\n\n", text)),
+        }
+    }
+
+    if result.segments.is_empty() {
+        output.push_str(result.transcript.trim());
+        output.push('\n');
+    }
+    output.truncate(output.trim_end().len());
+    output.push('\n');
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_as_dialogue() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("podcast.srt");
+        let srt = "1\n00:00:01,000 --> 00:00:02,000\nWelcome back.\n\n\
+                   2\n00:00:02,000 --> 00:00:04,000\nToday we talk about bees.\n\n\
+                   3\n00:01:05,000 --> 00:01:06,000\nBuzz.\n";
+        fs_err::write(&path, srt).unwrap();
+        let mut result = crate::output::load_transcript(&path).unwrap();
+        assert_eq!(format_as_dialogue(&result, false), "Welcome back. Today we talk about bees.\n\nBuzz.\n");
+
+        result.segments[0].speaker_id = Some("spk_0".to_string());
+        result.segments[1].speaker_id = Some("spk_0".to_string());
+        result.segments[2].speaker_id = Some("spk_1".to_string());
+        assert_eq!(
+            format_as_dialogue(&result, false),
+            "SPEAKER 1: Welcome back. Today we talk about bees.\n\nSPEAKER 2: Buzz.\n"
+        );
+        assert_eq!(
+            format_as_dialogue(&result, true),
+            "[00:00:01] SPEAKER 1\nWelcome back. Today we talk about bees.\n\n[00:01:05] SPEAKER 2\nBuzz.\n"
+        );
+    }
+}
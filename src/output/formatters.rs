@@ -1,8 +1,20 @@
 use anyhow::Result;
+use clap::ValueEnum;
 use serde_json;
 use serde::{Serialize, Deserialize};
 
-use crate::transcribe::TranscriptionResult;
+use super::karaoke::{time_words, vtt_cue_text};
+use super::layout::{shape_cues, SubtitleLayout};
+use super::schema::JsonDetail;
+use super::timecode::Timecode;
+use super::timestamps::TimestampStyle;
+use crate::extractors::Chapter;
+use crate::postprocess::paragraphs::{group_paragraphs, paragraph_text};
+use crate::transcribe::{TranscriptSegment, TranscriptionResult, TOXICITY_CATEGORIES};
+use crate::utils::text;
+
+/// Subtitle line width in display cells; CJK characters take two
+pub(crate) const SUBTITLE_LINE_WIDTH: usize = 42;
 
 /// Word-level timestamp information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,20 +29,40 @@ pub struct WordTimestamp {
     pub confidence: Option<f64>,
     /// Speaker ID (if available)
     pub speaker_id: Option<String>,
+    /// Other words the recogniser heard here, most likely first (AWS only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternatives: Vec<WordAlternative>,
 }
 
-/// Enhanced JSON output with word-level timestamps
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct EnhancedJsonOutput {
-    /// Basic transcription result
-    #[serde(flatten)]
-    pub result: TranscriptionResult,
-    /// Word-level timestamps (if available)
-    pub words: Option<Vec<WordTimestamp>>,
+/// A lower-ranked candidate for a word
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WordAlternative {
+    pub word: String,
+    /// Confidence score (0.0 to 1.0)
+    pub confidence: Option<f64>,
+}
+
+/// Whether JSON and CSV output list segments or single words
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    /// One entry per segment
+    #[default]
+    Segments,
+    /// One entry per word, with its timing, confidence and speaker
+    Words,
 }
 
 /// Format transcription as plain text
-pub fn format_as_text(result: &TranscriptionResult, include_timestamps: bool, detailed_timestamps: bool) -> String {
+///
+/// With `paragraphs` (or paragraph timestamps) segments are grouped into paragraphs of prose.
+pub fn format_as_text(
+    result: &TranscriptionResult,
+    include_timestamps: bool,
+    detailed_timestamps: bool,
+    style: &TimestampStyle,
+    paragraphs: bool,
+) -> String {
     if result.segments.is_empty() {
         return result.transcript.clone();
     }
@@ -46,61 +78,150 @@ pub fn format_as_text(result: &TranscriptionResult, include_timestamps: bool, de
     if let Some(confidence) = result.metadata.confidence {
         output.push_str(&format!("# Confidence: {:.1}%\n", confidence * 100.0));
     }
+    if let Some(speakers) = result.metadata.speaker_count {
+        output.push_str(&format!("# Speakers: {}\n", speakers));
+    }
     output.push_str("# Generated by RustScribe\n\n");
     
+    let include_timestamps = include_timestamps || style.is_custom();
+    let stamp = |seconds: f64| match &style.format {
+        Some(format) => format.render(seconds),
+        None if detailed_timestamps => format!("[{}]", format_detailed_timestamp(seconds)),
+        None => format!("[{}]", format_timestamp(seconds)),
+    };
+    // Speaker turns from --diarize-only have no text to introduce
+    let speaker = |segment: &TranscriptSegment| match &segment.speaker_id {
+        Some(s) if segment.text.is_empty() => format!("Speaker {}", s),
+        Some(s) => format!("Speaker {}: ", s),
+        None => String::new(),
+    };
+    
+    let mut chapters = result.chapters.iter().peekable();
+    
+    if style.per_paragraph || paragraphs {
+        // Stamp only where a paragraph starts
+        let starts: Vec<f64> = result.chapters.iter().map(|c| c.start_time).collect();
+        for (index, paragraph) in group_paragraphs(&result.segments, &starts).into_iter().enumerate() {
+            let first = &paragraph[0];
+            if index > 0 {
+                output.push_str("\n\n");
+            }
+            for chapter in due_chapters(&mut chapters, first.start_time) {
+                push_chapter_heading(&mut output, &chapter.title);
+            }
+            if include_timestamps {
+                output.push_str(&format!("{} ", stamp(first.start_time)));
+            }
+            output.push_str(&format!("{}{}", speaker(first), paragraph_text(paragraph)));
+        }
+        output.push('\n');
+        
+        return output;
+    }
+    
     // Add transcript with or without timestamps
     for segment in &result.segments {
+        for chapter in due_chapters(&mut chapters, segment.start_time) {
+            push_chapter_heading(&mut output, &chapter.title);
+        }
         if include_timestamps {
-            let timestamp = if detailed_timestamps {
-                format_detailed_timestamp(segment.start_time)
-            } else {
-                format_timestamp(segment.start_time)
-            };
-            
-            if let Some(speaker) = &segment.speaker_id {
-                output.push_str(&format!("[{}] Speaker {}: {}\n", timestamp, speaker, segment.text));
-            } else {
-                output.push_str(&format!("[{}] {}\n", timestamp, segment.text));
-            }
+            output.push_str(&format!("{} {}{}\n", stamp(segment.start_time), speaker(segment), segment.text));
         } else {
-            // Just text without timestamps
-            if let Some(speaker) = &segment.speaker_id {
-                output.push_str(&format!("Speaker {}: {}\n", speaker, segment.text));
-            } else {
-                output.push_str(&format!("{}\n", segment.text));
-            }
+            output.push_str(&format!("{}{}\n", speaker(segment), segment.text));
         }
     }
     
     output
 }
 
-/// Format transcription as JSON
-pub fn format_as_json(result: &TranscriptionResult) -> Result<String> {
-    let enhanced = EnhancedJsonOutput {
-        result: result.clone(),
-        words: result.words.clone(),
+/// Format transcription as JSON at the requested detail level
+///
+/// Word alternatives are only kept with `alternatives`, for tools that offer corrections.
+pub fn format_as_json(result: &TranscriptionResult, detail: JsonDetail, alternatives: bool) -> Result<String> {
+    // Every level keeps the result's shape, so the output can be loaded back later
+    let json = serde_json::to_string_pretty(&super::schema::to_value(result, detail, alternatives)?)?;
+    Ok(json)
+}
+
+/// Format the words as a JSON array, each with its timing, confidence and speaker
+pub fn format_words_as_json(result: &TranscriptionResult, alternatives: bool) -> Result<String> {
+    let mut words = words_with_speakers(result)?;
+    if !alternatives {
+        words.iter_mut().for_each(|word| word.alternatives.clear());
+    }
+    Ok(serde_json::to_string_pretty(&words)?)
+}
+
+/// Format the words as CSV, one row per word
+pub fn format_words_as_csv(result: &TranscriptionResult) -> Result<String> {
+    let mut output = String::from("start_time,end_time,duration,word,confidence,speaker\n");
+    
+    for word in words_with_speakers(result)? {
+        let confidence = word.confidence
+            .map(|c| format!("{:.3}", c))
+            .unwrap_or_default();
+        
+        output.push_str(&format!("{:.3},{:.3},{:.3},{},{},{}\n",
+            word.start_time,
+            word.end_time,
+            word.end_time - word.start_time,
+            escape_csv_field(&word.word),
+            confidence,
+            word.speaker_id.as_deref().unwrap_or("")
+        ));
+    }
+    
+    Ok(output)
+}
+
+/// The result's words, each given the speaker of the segment it falls in when it has none
+pub(super) fn words_with_speakers(result: &TranscriptionResult) -> Result<Vec<WordTimestamp>> {
+    let Some(words) = result.words.as_deref().filter(|words| !words.is_empty()) else {
+        anyhow::bail!("The transcript has no word timings (subtitles and some backends only have segments)");
     };
     
-    let json = serde_json::to_string_pretty(&enhanced)?;
-    Ok(json)
+    Ok(words
+        .iter()
+        .map(|word| {
+            let middle = (word.start_time + word.end_time) / 2.0;
+            let speaker_id = word.speaker_id.clone().or_else(|| {
+                result.segments
+                    .iter()
+                    .find(|s| middle >= s.start_time && middle <= s.end_time)
+                    .and_then(|s| s.speaker_id.clone())
+            });
+            WordTimestamp { speaker_id, ..word.clone() }
+        })
+        .collect())
+}
+
+/// Cells the "Speaker X: " label takes before the first line of an SRT or SBV cue
+fn speaker_label_width(speaker: &str) -> usize {
+    text::display_width(&format!("Speaker {}: ", speaker))
 }
 
 /// Format transcription as SRT subtitles
-pub fn format_as_srt(result: &TranscriptionResult, detailed_timestamps: bool) -> String {
+///
+/// Times are shifted by the timecode offset and snapped to frames when a frame rate is set.
+pub fn format_as_srt(
+    result: &TranscriptionResult,
+    _detailed_timestamps: bool,
+    timecode: &Timecode,
+    layout: &SubtitleLayout,
+) -> String {
     let mut output = String::new();
     
-    for (i, segment) in result.segments.iter().enumerate() {
+    for (i, cue) in shape_cues(&result.segments, layout, speaker_label_width).iter().enumerate() {
         output.push_str(&format!("{}\n", i + 1));
         output.push_str(&format!("{} --> {}\n", 
-            format_srt_timestamp(segment.start_time),
-            format_srt_timestamp(segment.end_time)
+            format_srt_timestamp(timecode.apply(cue.start_time)),
+            format_srt_timestamp(timecode.apply(cue.end_time))
         ));
         
-        let text = if let Some(speaker) = &segment.speaker_id {
-            format!("<i>Speaker {}:</i> {}", speaker, segment.text)
-        } else {
-            segment.text.clone()
+        let text = cue.lines.join("\n");
+        let text = match &cue.speaker {
+            Some(speaker) if !cue.continues => format!("<i>Speaker {}:</i> {}", speaker, text),
+            _ => text,
         };
         
         output.push_str(&format!("{}\n\n", text));
@@ -110,25 +231,68 @@ pub fn format_as_srt(result: &TranscriptionResult, detailed_timestamps: bool) ->
 }
 
 /// Format transcription as WebVTT
-pub fn format_as_vtt(result: &TranscriptionResult, detailed_timestamps: bool) -> String {
+///
+/// With `--karaoke` each word is tagged with when it is spoken, which fails without word timings.
+pub fn format_as_vtt(
+    result: &TranscriptionResult,
+    _detailed_timestamps: bool,
+    layout: &SubtitleLayout,
+) -> Result<String> {
     let mut output = String::from("WEBVTT\n\n");
     
     // Add metadata
-    output.push_str(&format!("NOTE\nTranscribed by RustScribe\n"));
+    output.push_str("NOTE\nTranscribed by RustScribe\n");
     output.push_str(&format!("Source: {}\n", 
         result.audio_info.title.as_deref().unwrap_or("Unknown")));
     output.push_str(&format!("Language: {}\n\n", result.metadata.language));
     
-    for segment in &result.segments {
+    // The voice tag is markup, so it takes no room on the line
+    let cues = shape_cues(&result.segments, layout, |_| 0);
+    let timed = if layout.karaoke { Some(time_words(&cues, &words_with_speakers(result)?)) } else { None };
+    let mut chapters = result.chapters.iter().peekable();
+    for (index, cue) in cues.iter().enumerate() {
+        // Chapters go in as comments, which players ignore; "-->" is not allowed in them
+        for chapter in due_chapters(&mut chapters, cue.start_time) {
+            output.push_str(&format!("NOTE Chapter: {}\n\n", chapter.title.replace("-->", "->")));
+        }
+        
         output.push_str(&format!("{} --> {}\n", 
-            format_vtt_timestamp(segment.start_time),
-            format_vtt_timestamp(segment.end_time)
+            format_vtt_timestamp(cue.start_time),
+            format_vtt_timestamp(cue.end_time)
         ));
         
-        let text = if let Some(speaker) = &segment.speaker_id {
-            format!("<v Speaker {}>{}",  speaker, segment.text)
+        let text = match &timed {
+            Some(timed) => vtt_cue_text(cue, &timed[index]),
+            None => cue.lines.join("\n"),
+        };
+        let text = if let Some(speaker) = &cue.speaker {
+            format!("<v Speaker {}>{}", speaker, text)
         } else {
-            segment.text.clone()
+            text
+        };
+        
+        output.push_str(&format!("{}\n\n", text));
+    }
+    
+    Ok(output)
+}
+
+/// Format transcription as SubViewer (SBV) captions, which YouTube Studio takes as uploaded
+///
+/// SBV has no markup, so speakers are named in the text.
+pub fn format_as_sbv(result: &TranscriptionResult, layout: &SubtitleLayout) -> String {
+    let mut output = String::new();
+    
+    for cue in &shape_cues(&result.segments, layout, speaker_label_width) {
+        output.push_str(&format!("{},{}\n",
+            format_sbv_timestamp(cue.start_time),
+            format_sbv_timestamp(cue.end_time)
+        ));
+        
+        let text = cue.lines.join("\n");
+        let text = match &cue.speaker {
+            Some(speaker) if !cue.continues => format!("Speaker {}: {}", speaker, text),
+            _ => text,
         };
         
         output.push_str(&format!("{}\n\n", text));
@@ -137,12 +301,44 @@ pub fn format_as_vtt(result: &TranscriptionResult, detailed_timestamps: bool) ->
     output
 }
 
+/// Format the chapters as a YouTube chapter list, to paste into a video description
+///
+/// YouTube needs the first chapter at 0:00, so a list that starts later gets an intro.
+pub fn format_as_youtube_chapters(result: &TranscriptionResult) -> Result<String> {
+    if result.chapters.is_empty() {
+        anyhow::bail!("The transcript has no chapters (add --chapters auto)");
+    }
+    
+    let mut output = String::new();
+    if result.chapters[0].start_time >= 1.0 {
+        output.push_str("0:00 Intro\n");
+    }
+    for chapter in &result.chapters {
+        let seconds = chapter.start_time as u64;
+        let stamp = match (seconds / 3600, seconds % 3600 / 60, seconds % 60) {
+            (0, minutes, secs) => format!("{}:{:02}", minutes, secs),
+            (hours, minutes, secs) => format!("{}:{:02}:{:02}", hours, minutes, secs),
+        };
+        output.push_str(&format!("{} {}\n", stamp, chapter.title.trim()));
+    }
+    
+    Ok(output)
+}
+
 /// Format transcription as CSV
 pub fn format_as_csv(result: &TranscriptionResult) -> Result<String> {
     let mut output = String::new();
     
-    // CSV header
-    output.push_str("start_time,end_time,duration,text,confidence,speaker\n");
+    // CSV header; toxicity columns only when the run detected it
+    let toxicity = result.segments.iter().any(|s| s.toxicity.is_some());
+    output.push_str("start_time,end_time,duration,text,confidence,speaker");
+    if toxicity {
+        output.push_str(",toxicity");
+        for category in TOXICITY_CATEGORIES {
+            output.push_str(&format!(",{}", category));
+        }
+    }
+    output.push('\n');
     
     for segment in &result.segments {
         let duration = segment.end_time - segment.start_time;
@@ -154,7 +350,7 @@ pub fn format_as_csv(result: &TranscriptionResult) -> Result<String> {
         // Escape text for CSV
         let escaped_text = escape_csv_field(&segment.text);
         
-        output.push_str(&format!("{:.3},{:.3},{:.3},{},{},{}\n",
+        output.push_str(&format!("{:.3},{:.3},{:.3},{},{},{}",
             segment.start_time,
             segment.end_time,
             duration,
@@ -162,11 +358,36 @@ pub fn format_as_csv(result: &TranscriptionResult) -> Result<String> {
             confidence,
             speaker
         ));
+        if toxicity {
+            let rating = segment.toxicity.as_ref();
+            let score = |score: Option<f64>| score.map(|s| format!("{:.3}", s)).unwrap_or_default();
+            output.push_str(&format!(",{}", score(rating.map(|t| t.score))));
+            for category in TOXICITY_CATEGORIES {
+                output.push_str(&format!(",{}", score(rating.and_then(|t| t.categories.get(*category).copied()))));
+            }
+        }
+        output.push('\n');
     }
     
     Ok(output)
 }
 
+/// Chapters that start at or before a segment and have not been shown yet
+fn due_chapters<'a>(
+    chapters: &mut std::iter::Peekable<std::slice::Iter<'a, Chapter>>,
+    start_time: f64,
+) -> Vec<&'a Chapter> {
+    std::iter::from_fn(|| chapters.next_if(|chapter| chapter.start_time <= start_time)).collect()
+}
+
+/// Start a chapter on its own line, set off by a blank line
+fn push_chapter_heading(output: &mut String, title: &str) {
+    if !output.is_empty() && !output.ends_with("\n\n") {
+        output.push('\n');
+    }
+    output.push_str(&format!("## {}\n\n", title));
+}
+
 /// Format timestamp as MM:SS or HH:MM:SS
 fn format_timestamp(seconds: f64) -> String {
     let total_seconds = seconds as u64;
@@ -182,7 +403,7 @@ fn format_timestamp(seconds: f64) -> String {
 }
 
 /// Format detailed timestamp with milliseconds as MM:SS.mmm or HH:MM:SS.mmm
-fn format_detailed_timestamp(seconds: f64) -> String {
+pub(super) fn format_detailed_timestamp(seconds: f64) -> String {
     let total_milliseconds = (seconds * 1000.0) as u64;
     let hours = total_milliseconds / 3_600_000;
     let minutes = (total_milliseconds % 3_600_000) / 60_000;
@@ -197,7 +418,7 @@ fn format_detailed_timestamp(seconds: f64) -> String {
 }
 
 /// Format timestamp for SRT format (HH:MM:SS,mmm)
-fn format_srt_timestamp(seconds: f64) -> String {
+pub fn format_srt_timestamp(seconds: f64) -> String {
     let total_milliseconds = (seconds * 1000.0) as u64;
     let hours = total_milliseconds / 3_600_000;
     let minutes = (total_milliseconds % 3_600_000) / 60_000;
@@ -208,7 +429,7 @@ fn format_srt_timestamp(seconds: f64) -> String {
 }
 
 /// Format timestamp for VTT format (HH:MM:SS.mmm)
-fn format_vtt_timestamp(seconds: f64) -> String {
+pub(super) fn format_vtt_timestamp(seconds: f64) -> String {
     let total_milliseconds = (seconds * 1000.0) as u64;
     let hours = total_milliseconds / 3_600_000;
     let minutes = (total_milliseconds % 3_600_000) / 60_000;
@@ -218,6 +439,17 @@ fn format_vtt_timestamp(seconds: f64) -> String {
     format!("{:02}:{:02}:{:02}.{:03}", hours, minutes, secs, millis)
 }
 
+/// Format timestamp for SBV format (H:MM:SS.mmm)
+fn format_sbv_timestamp(seconds: f64) -> String {
+    let total_milliseconds = (seconds * 1000.0) as u64;
+    let hours = total_milliseconds / 3_600_000;
+    let minutes = (total_milliseconds % 3_600_000) / 60_000;
+    let secs = (total_milliseconds % 60_000) / 1_000;
+    let millis = total_milliseconds % 1_000;
+    
+    format!("{}:{:02}:{:02}.{:03}", hours, minutes, secs, millis)
+}
+
 /// Escape field for CSV format
 fn escape_csv_field(field: &str) -> String {
     if field.contains('"') || field.contains(',') || field.contains('\n') {
@@ -230,7 +462,7 @@ fn escape_csv_field(field: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::transcribe::{TranscriptSegment, TranscriptionMetadata};
+    use crate::transcribe::TranscriptionMetadata;
     use crate::extractors::{AudioInfo, AudioFormat};
     
     fn create_test_result() -> TranscriptionResult {
@@ -243,6 +475,9 @@ mod tests {
                     text: "Hello world.".to_string(),
                     confidence: Some(0.98),
                     speaker_id: Some("1".to_string()),
+                    toxicity: None,
+                    language: None,
+                    verbatim: None,
                 },
                 TranscriptSegment {
                     start_time: 2.5,
@@ -250,16 +485,21 @@ mod tests {
                     text: "This is a test.".to_string(),
                     confidence: Some(0.95),
                     speaker_id: Some("2".to_string()),
+                    toxicity: None,
+                    language: None,
+                    verbatim: None,
                 },
             ],
             audio_info: AudioInfo {
                 download_url: "https://example.com/audio.mp3".to_string(),
+                download_method: crate::extractors::DownloadMethod::DirectUrl,
                 duration: None,
                 title: Some("Test Audio".to_string()),
                 format: AudioFormat::Mp3,
                 sample_rate: Some(44100),
                 file_size: Some(1024),
                 original_url: "https://example.com/video".to_string(),
+                chapters: Vec::new(),
             },
             audio_path: None,
             metadata: TranscriptionMetadata {
@@ -269,15 +509,20 @@ mod tests {
                 audio_duration: Some(5.0),
                 confidence: Some(0.965),
                 completed_at: chrono::Utc::now(),
+                speaker_count: None,
             },
             words: None,
+            raw: None,
+            chapters: Vec::new(),
+            redactions: Vec::new(),
+            keywords: None,
         }
     }
     
     #[test]
     fn test_format_as_text() {
         let result = create_test_result();
-        let text = format_as_text(&result, false, false);
+        let text = format_as_text(&result, false, false, &TimestampStyle::default(), false);
         
         assert!(text.contains("Hello world."));
         assert!(text.contains("This is a test."));
@@ -285,15 +530,108 @@ mod tests {
         assert!(text.contains("Speaker 2"));
     }
     
+    #[test]
+    fn test_format_as_text_with_custom_timestamps() {
+        let mut result = create_test_result();
+        let per_line = TimestampStyle {
+            format: Some("({M}:{ss})".parse().unwrap()),
+            per_paragraph: false,
+        };
+        let text = format_as_text(&result, false, false, &per_line, false);
+        assert!(text.contains("(0:02) Speaker 2: This is a test."));
+        
+        result.segments[1].speaker_id = result.segments[0].speaker_id.clone();
+        let per_paragraph = TimestampStyle {
+            per_paragraph: true,
+            ..per_line
+        };
+        let text = format_as_text(&result, false, false, &per_paragraph, false);
+        assert!(text.ends_with("(0:00) Speaker 1: Hello world. This is a test.\n"));
+    }
+    
+    #[test]
+    fn test_chapters_in_text_and_vtt() {
+        let mut result = create_test_result();
+        result.chapters = vec![
+            Chapter { title: "Intro".to_string(), start_time: 0.0, end_time: Some(2.5) },
+            Chapter { title: "Test --> run".to_string(), start_time: 2.0, end_time: None },
+        ];
+        
+        let text = format_as_text(&result, false, false, &TimestampStyle::default(), false);
+        assert!(text.contains("## Intro\n\nSpeaker 1: Hello world.\n\n## Test --> run\n\nSpeaker 2: This is a test.\n"));
+        
+        let vtt = format_as_vtt(&result, false, &SubtitleLayout::default()).unwrap();
+        assert!(vtt.contains("NOTE Chapter: Intro\n\n00:00:00.000 --> 00:00:02.500"));
+        assert!(vtt.contains("NOTE Chapter: Test -> run\n\n00:00:02.500"));
+        assert_eq!(crate::captions::parse_vtt(&vtt).unwrap().len(), 2);
+        
+        result.chapters[1].start_time = 3725.0;
+        assert_eq!(format_as_youtube_chapters(&result).unwrap(), "0:00 Intro\n1:02:05 Test --> run\n");
+    }
+    
     #[test]
     fn test_format_as_srt() {
         let result = create_test_result();
-        let srt = format_as_srt(&result, false);
+        let srt = format_as_srt(&result, false, &Timecode::default(), &SubtitleLayout::default());
         
         assert!(srt.contains("1\n"));
         assert!(srt.contains("2\n"));
         assert!(srt.contains("00:00:00,000 --> 00:00:02,500"));
         assert!(srt.contains("00:00:02,500 --> 00:00:05,000"));
+        
+        // The label counts towards the first line, and only the segment's first cue has it
+        let narrow = SubtitleLayout { max_chars_per_line: 16, max_lines: Some(1), ..Default::default() };
+        let srt = format_as_srt(&result, false, &Timecode::default(), &narrow);
+        assert!(srt.contains("\n<i>Speaker 2:</i> This\n\n"));
+        assert!(srt.ends_with("\nis a test.\n\n"));
+        // Every cue keeps its voice
+        let vtt = format_as_vtt(&result, false, &SubtitleLayout { max_chars_per_line: 8, ..narrow }).unwrap();
+        assert!(vtt.contains("<v Speaker 2>This is\n\n") && vtt.ends_with("<v Speaker 2>a test.\n\n"));
+    }
+    
+    #[test]
+    fn test_word_level_json_and_csv() {
+        let mut result = create_test_result();
+        assert!(format_words_as_csv(&result).is_err());
+        
+        result.words = Some(vec![
+            WordTimestamp {
+                word: "Hello".to_string(),
+                start_time: 0.0,
+                end_time: 0.5,
+                confidence: Some(0.99),
+                speaker_id: None,
+                alternatives: vec![WordAlternative { word: "Yellow".to_string(), confidence: Some(0.4) }],
+            },
+            WordTimestamp {
+                word: "test,".to_string(),
+                start_time: 4.0,
+                end_time: 4.5,
+                confidence: None,
+                speaker_id: None,
+                alternatives: Vec::new(),
+            },
+        ]);
+        
+        let csv = format_words_as_csv(&result).unwrap();
+        assert_eq!(csv, "start_time,end_time,duration,word,confidence,speaker\n\
+                         0.000,0.500,0.500,Hello,0.990,1\n4.000,4.500,0.500,\"test,\",,2\n");
+        
+        let json: serde_json::Value = serde_json::from_str(&format_words_as_json(&result, false).unwrap()).unwrap();
+        assert_eq!(json[1]["speaker_id"], "2");
+        assert!(json[0].get("alternatives").is_none());
+        let json: serde_json::Value = serde_json::from_str(&format_words_as_json(&result, true).unwrap()).unwrap();
+        assert_eq!(json[0]["alternatives"][0]["word"], "Yellow");
+    }
+    
+    #[test]
+    fn test_format_as_sbv() {
+        let result = create_test_result();
+        let sbv = format_as_sbv(&result, &SubtitleLayout::default());
+        
+        assert!(sbv.starts_with("0:00:00.000,0:00:02.500\nSpeaker 1: Hello world.\n\n"));
+        assert!(sbv.ends_with("0:00:02.500,0:00:05.000\nSpeaker 2: This is a test.\n\n"));
+        assert_eq!(format_sbv_timestamp(3725.25), "1:02:05.250");
     }
     
     #[test]
@@ -0,0 +1,160 @@
+use std::path::Path;
+
+use crate::transcribe::TranscriptionResult;
+use crate::utils::format_duration;
+
+/// Speaker colours, given out in order of first appearance
+const SPEAKER_COLORS: &[&str] = &["#1f77b4", "#d62728", "#2ca02c", "#9467bd", "#ff7f0e", "#17becf", "#8c564b", "#e377c2"];
+
+const STYLE: &str = "body{font:17px/1.6 system-ui,sans-serif;max-width:46em;margin:0 auto;padding:0 1em 4em;color:#222}
+header{position:sticky;top:0;background:#fff;padding:1em 0 .5em;border-bottom:1px solid #ddd}
+h1{margin:0 0 .2em;font-size:1.6em}.details{margin:0;color:#666}audio{width:100%;margin-top:.6em}
+h2{margin:1.6em 0 .4em}.segment{margin:.3em 0;padding:.1em .5em;border-left:4px solid transparent;border-radius:3px}
+.segment.current{background:#fff5c2}.time{color:#888;font:13px monospace;text-decoration:none;margin-right:.5em}
+.time:hover{color:#000;text-decoration:underline}.speaker{font-weight:600;margin-right:.3em}";
+
+/// Clicking a time plays from there; the segment being played is highlighted and kept in view
+const SCRIPT: &str = "const audio = document.querySelector('audio');
+const segments = [...document.querySelectorAll('.segment')];
+document.addEventListener('click', event => {
+  const link = event.target.closest('a.time');
+  if (!link || !audio) return;
+  event.preventDefault();
+  audio.currentTime = parseFloat(link.parentElement.dataset.start);
+  audio.play();
+});
+if (audio) audio.addEventListener('timeupdate', () => {
+  const now = audio.currentTime;
+  for (const segment of segments) {
+    const playing = now >= parseFloat(segment.dataset.start) && now < parseFloat(segment.dataset.end);
+    if (playing !== segment.classList.contains('current')) {
+      segment.classList.toggle('current', playing);
+      if (playing) segment.scrollIntoView({ block: 'nearest', behavior: 'smooth' });
+    }
+  }
+});";
+
+/// Format transcription as a standalone HTML page
+///
+/// Every segment has a clickable timestamp and speakers get a colour each. When the audio was
+/// saved (`--save-audio`) the page embeds a player: clicking a timestamp plays from there, and
+/// the segment being played is highlighted as it goes.
+pub fn format_as_html(result: &TranscriptionResult) -> String {
+    let title = result.audio_info.title.as_deref().unwrap_or("Transcript");
+    let language = result.metadata.language.split(['-', '_']).next().unwrap_or("und");
+
+    let mut speakers: Vec<&str> = Vec::new();
+    for speaker in result.segments.iter().filter_map(|s| s.speaker_id.as_deref()) {
+        if !speakers.contains(&speaker) {
+            speakers.push(speaker);
+        }
+    }
+    let mut style = STYLE.to_string();
+    for (index, color) in SPEAKER_COLORS.iter().enumerate().take(speakers.len()) {
+        style.push_str(&format!("\n.speaker-{0}{{border-left-color:{1}}}.speaker-{0} .speaker{{color:{1}}}", index, color));
+    }
+
+    let mut output = String::from("<!DOCTYPE html>\n");
+    output.push_str(&format!("<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n", escape(language)));
+    output.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    output.push_str(&format!("<title>{}</title>\n<style>\n{}\n</style>\n</head>\n<body>\n", escape(title), style));
+
+    output.push_str(&format!("<header>\n<h1>{}</h1>\n", escape(title)));
+    let mut details = Vec::new();
+    if let Some(duration) = result.metadata.audio_duration {
+        details.push(format_duration(duration));
+    }
+    details.push(result.metadata.language.clone());
+    if let Some(speakers) = result.metadata.speaker_count {
+        details.push(format!("{} speakers", speakers));
+    }
+    output.push_str(&format!("<p class=\"details\">{}</p>\n", escape(&details.join(" · "))));
+    if let Some(source) = result.audio_path.as_deref().and_then(audio_source) {
+        output.push_str(&format!("<audio controls preload=\"metadata\" src=\"{}\"></audio>\n", escape(&source)));
+    }
+    output.push_str("</header>\n<main>\n");
+
+    if result.segments.is_empty() {
+        output.push_str(&format!("<p>{}</p>\n", escape(result.transcript.trim())));
+    }
+    let mut chapters = result.chapters.iter().peekable();
+    for (index, segment) in result.segments.iter().enumerate() {
+        while let Some(chapter) = chapters.next_if(|c| c.start_time <= segment.start_time) {
+            output.push_str(&format!("<h2>{}</h2>\n", escape(&chapter.title)));
+        }
+        let speaker = segment.speaker_id.as_deref();
+        let class = match speaker.and_then(|s| speakers.iter().position(|known| *known == s)) {
+            Some(position) => format!("segment speaker-{}", position % SPEAKER_COLORS.len()),
+            None => "segment".to_string(),
+        };
+        output.push_str(&format!(
+            "<p id=\"s{}\" class=\"{}\" data-start=\"{:.2}\" data-end=\"{:.2}\"><a class=\"time\" href=\"#s{}\">{}</a>",
+            index + 1,
+            class,
+            segment.start_time,
+            segment.end_time,
+            index + 1,
+            timestamp(segment.start_time)
+        ));
+        if let Some(speaker) = speaker {
+            output.push_str(&format!("<span class=\"speaker\">Speaker {}:</span>", escape(speaker)));
+        }
+        output.push_str(&format!("{}</p>\n", escape(segment.text.trim())));
+    }
+
+    output.push_str(&format!("</main>\n<script>\n{}\n</script>\n</body>\n</html>\n", SCRIPT));
+    output
+}
+
+/// `file://` URL of the saved audio, so the page plays it wherever the page itself is saved
+pub(super) fn audio_source(path: &Path) -> Option<String> {
+    let path = std::path::absolute(path).ok()?;
+    url::Url::from_file_path(path).ok().map(String::from)
+}
+
+fn timestamp(seconds: f64) -> String {
+    let seconds = seconds as u64;
+    match seconds / 3600 {
+        0 => format!("{}:{:02}", seconds / 60, seconds % 60),
+        hours => format!("{}:{:02}:{:02}", hours, seconds % 3600 / 60, seconds % 60),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_as_html() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Q&A.srt");
+        let srt = "1\n00:00:01,000 --> 00:00:02,000\nFish & chips?\n\n\
+                   2\n00:01:05,500 --> 00:01:07,000\nIt is.\n";
+        fs_err::write(&path, srt).unwrap();
+        let mut result = crate::output::load_transcript(&path).unwrap();
+        result.segments[0].speaker_id = Some("spk_0".to_string());
+        result.segments[1].speaker_id = Some("spk_1".to_string());
+
+        let html = format_as_html(&result);
+        assert!(html.contains("<title>Q&amp;A</title>"));
+        assert!(html.contains(
+            "<p id=\"s1\" class=\"segment speaker-0\" data-start=\"1.00\" data-end=\"2.00\"><a class=\"time\" \
+             href=\"#s1\">0:01</a><span class=\"speaker\">Speaker spk_0:</span>Fish &amp; chips?</p>"
+        ));
+        assert!(html.contains("class=\"segment speaker-1\" data-start=\"65.50\""));
+        assert!(!html.contains("<audio"));
+
+        result.audio_path = Some(dir.path().join("Q&A.mp3"));
+        let html = format_as_html(&result);
+        assert!(html.contains("<audio controls preload=\"metadata\" src=\"file:///"));
+        assert!(html.contains("/Q&amp;A.mp3\"></audio>"));
+    }
+}
@@ -0,0 +1,120 @@
+use super::formatters::{format_vtt_timestamp, WordTimestamp};
+use super::layout::ShapedCue;
+
+/// Timed words looked through for each word of the text, past those it has no match for
+const LOOKAHEAD: usize = 4;
+
+/// A word of a cue's text and when it starts being spoken
+#[derive(Debug, Clone, PartialEq)]
+pub struct KaraokeWord {
+    pub start_time: f64,
+    pub text: String,
+}
+
+/// Each cue's lines as words, timed from the transcript's word timings
+///
+/// Cue text is matched to the timed words in order, ignoring case and punctuation. A word with
+/// no match (a number written in digits, say) starts with the word before it, and every time
+/// is kept within its cue.
+pub fn time_words(cues: &[ShapedCue], words: &[WordTimestamp]) -> Vec<Vec<Vec<KaraokeWord>>> {
+    let mut words: Vec<&WordTimestamp> = words.iter().collect();
+    words.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+
+    let mut next = 0;
+    let mut timed = Vec::with_capacity(cues.len());
+    for cue in cues {
+        // Words over before the cue starts belong to earlier cues
+        while words.get(next).is_some_and(|word| word.end_time <= cue.start_time) {
+            next += 1;
+        }
+        let mut start_time = cue.start_time;
+        let mut lines = Vec::with_capacity(cue.lines.len());
+        for line in &cue.lines {
+            let mut line_words = Vec::new();
+            for text in line.split_whitespace() {
+                let wanted = match_key(text);
+                let end = words.len().min(next + LOOKAHEAD);
+                let found = (next..end).find(|&i| !wanted.is_empty() && match_key(&words[i].word) == wanted);
+                if let Some(found) = found {
+                    start_time = words[found].start_time.clamp(start_time, cue.end_time);
+                    next = found + 1;
+                }
+                line_words.push(KaraokeWord { start_time, text: text.to_string() });
+            }
+            lines.push(line_words);
+        }
+        timed.push(lines);
+    }
+    timed
+}
+
+/// Cue text with a WebVTT timestamp tag before each word spoken after the cue starts
+///
+/// Players show the words before the current time as past (`::cue(:past)`), so they can be
+/// styled apart from those still to come.
+pub fn vtt_cue_text(cue: &ShapedCue, lines: &[Vec<KaraokeWord>]) -> String {
+    // Tags have to increase and fall strictly inside the cue
+    let mut last = cue.start_time;
+    let lines: Vec<String> = lines
+        .iter()
+        .map(|line| {
+            let mut text = Vec::with_capacity(line.len());
+            for word in line {
+                if word.start_time > last && word.start_time < cue.end_time {
+                    text.push(format!("<{}>{}", format_vtt_timestamp(word.start_time), word.text));
+                    last = word.start_time;
+                } else {
+                    text.push(word.text.clone());
+                }
+            }
+            text.join(" ")
+        })
+        .collect();
+    lines.join("\n")
+}
+
+/// Words compare by their letters and digits alone
+fn match_key(word: &str) -> String {
+    word.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_words() {
+        let word = |word: &str, start_time: f64, end_time: f64| WordTimestamp {
+            word: word.to_string(),
+            start_time,
+            end_time,
+            confidence: None,
+            speaker_id: None,
+            alternatives: Vec::new(),
+        };
+        let words = [
+            word("hello", 1.0, 1.4),
+            word("there", 1.5, 2.0),
+            word("it's", 2.2, 2.5),
+            word("ten", 2.6, 3.0),
+            word("past", 3.1, 3.5),
+        ];
+        let cue = |start_time: f64, end_time: f64, lines: &[&str]| ShapedCue {
+            start_time,
+            end_time,
+            lines: lines.iter().map(|line| line.to_string()).collect(),
+            speaker: None,
+            continues: false,
+        };
+        let cues = [cue(1.0, 2.1, &["Hello there."]), cue(2.1, 3.0, &["It's 10", "past!"])];
+
+        let timed = time_words(&cues, &words);
+        let starts: Vec<Vec<f64>> =
+            timed.iter().map(|lines| lines.iter().flatten().map(|w| w.start_time).collect()).collect();
+        // "10" is not heard as written, and "past" starts after its cue has ended
+        assert_eq!(starts, [vec![1.0, 1.5], vec![2.2, 2.2, 3.0]]);
+
+        assert_eq!(vtt_cue_text(&cues[0], &timed[0]), "Hello <00:00:01.500>there.");
+        assert_eq!(vtt_cue_text(&cues[1], &timed[1]), "<00:00:02.200>It's 10\npast!");
+    }
+}
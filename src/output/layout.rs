@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+
+use super::formatters::SUBTITLE_LINE_WIDTH;
+use super::retime::{retime_segments, MIN_CUE_DURATION};
+use crate::transcribe::TranscriptSegment;
+use crate::utils::text;
+
+/// How segments are shaped into subtitle cues (SRT, VTT and SBV)
+///
+/// By default lines are wrapped at 42 cells and each segment is one cue, however long.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SubtitleLayout {
+    /// Line width in display cells (CJK characters count as two)
+    pub max_chars_per_line: usize,
+
+    /// Lines per cue; longer segments are split into several cues
+    pub max_lines: Option<usize>,
+
+    /// Seconds a cue may last; longer segments are split into several cues
+    pub max_cue_duration: Option<f64>,
+
+    /// Seconds kept free between consecutive cues
+    pub min_cue_gap: Option<f64>,
+
+    /// Time every word in VTT and ASS cues, so players highlight words as they are spoken
+    pub karaoke: bool,
+}
+
+impl Default for SubtitleLayout {
+    fn default() -> Self {
+        Self {
+            max_chars_per_line: SUBTITLE_LINE_WIDTH,
+            max_lines: None,
+            max_cue_duration: None,
+            min_cue_gap: None,
+            karaoke: false,
+        }
+    }
+}
+
+/// A subtitle cue cut from a segment
+#[derive(Debug, Clone)]
+pub struct ShapedCue {
+    pub start_time: f64,
+    pub end_time: f64,
+    /// Text wrapped into lines
+    pub lines: Vec<String>,
+    /// Speaker of the segment the cue was cut from
+    pub speaker: Option<String>,
+    /// Whether the cue carries on a segment begun in an earlier cue
+    pub continues: bool,
+}
+
+/// Wrap segments into cues that keep to the layout
+///
+/// Segments are re-timed first (see `retime_segments`). A segment with more lines than fit
+/// in one cue, or lasting longer than a cue may, is split between lines into cues of even
+/// length, each timed by its share of the characters (lines are wrapped shorter when there
+/// are too few to split between). Cues that follow each other too closely are then
+/// shortened to keep the gap, unless that would make them too short.
+///
+/// `label_width` gives the cells a speaker label takes before a segment's first line, which
+/// is wrapped that much shorter.
+pub fn shape_cues(
+    segments: &[TranscriptSegment],
+    layout: &SubtitleLayout,
+    label_width: impl Fn(&str) -> usize,
+) -> Vec<ShapedCue> {
+    let mut cues = Vec::new();
+    for segment in retime_segments(segments) {
+        let duration = segment.end_time - segment.start_time;
+        let by_duration = layout.max_cue_duration.map_or(1, |max| (duration / max).ceil() as usize);
+        let indent = segment.speaker_id.as_deref().map_or(0, &label_width);
+        let mut lines = text::wrap_after_indent(&segment.text, layout.max_chars_per_line.max(1), indent);
+        let by_lines = layout.max_lines.map_or(1, |max| lines.len().div_ceil(max.max(1)));
+        let mut pieces = by_lines.max(by_duration).clamp(1, lines.len().max(1));
+        if let (Some(max), true) = (layout.max_cue_duration, by_duration > lines.len()) {
+            // Too few lines to split between, so they are made shorter, one per cue
+            let even = text::display_width(&segment.text).div_ceil(by_duration);
+            let mut width = even.min(layout.max_chars_per_line);
+            loop {
+                lines = text::wrap_after_indent(&segment.text, width.max(1), indent);
+                let total: usize = lines.iter().map(|line| line.chars().count()).sum();
+                let longest = lines.iter().map(|line| line.chars().count()).max().unwrap_or_default();
+                if width <= 1 || duration * longest as f64 <= max * total as f64 {
+                    break;
+                }
+                width -= 1;
+            }
+            pieces = lines.len().max(1);
+        }
+
+        let total: usize = lines.iter().map(|line| line.chars().count()).sum();
+        let mut start = segment.start_time;
+        let mut taken = 0;
+        // Earlier cues take the extra line when the lines do not divide evenly
+        let first_line = |piece: usize| (piece * lines.len()).div_ceil(pieces);
+        for piece in 0..pieces {
+            let piece_lines = lines[first_line(piece)..first_line(piece + 1)].to_vec();
+            taken += piece_lines.iter().map(|line| line.chars().count()).sum::<usize>();
+            let end = if piece + 1 == pieces {
+                segment.end_time
+            } else {
+                segment.start_time + duration * taken as f64 / total.max(1) as f64
+            };
+            cues.push(ShapedCue {
+                start_time: start,
+                end_time: end,
+                lines: piece_lines,
+                speaker: segment.speaker_id.clone(),
+                continues: piece > 0,
+            });
+            start = end;
+        }
+    }
+
+    if let Some(gap) = layout.min_cue_gap {
+        for i in 1..cues.len() {
+            // Rounded down to the millisecond, so the written times keep the whole gap
+            let end = ((cues[i].start_time - gap) * 1000.0 + 1e-6).floor() / 1000.0;
+            if cues[i - 1].end_time > end && end - cues[i - 1].start_time >= MIN_CUE_DURATION {
+                cues[i - 1].end_time = end;
+            }
+        }
+    }
+
+    cues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shape_cues() {
+        let segment = TranscriptSegment {
+            start_time: 0.0,
+            end_time: 12.0,
+            text: "one two three four five six seven eight nine ten eleven twelve".to_string(),
+            confidence: None,
+            speaker_id: Some("spk_0".to_string()),
+            toxicity: None,
+            language: None,
+            verbatim: None,
+        };
+        let next = TranscriptSegment {
+            start_time: 12.0,
+            end_time: 13.0,
+            text: "Done.".to_string(),
+            speaker_id: None,
+            ..segment.clone()
+        };
+        let segments = [segment, next];
+
+        let unchanged = shape_cues(&segments, &SubtitleLayout::default(), |_| 0);
+        assert_eq!(unchanged.len(), 2);
+        assert_eq!(unchanged[0].lines.len(), 2);
+
+        let layout = SubtitleLayout {
+            max_chars_per_line: 16,
+            max_lines: Some(2),
+            max_cue_duration: None,
+            min_cue_gap: Some(0.1),
+            karaoke: false,
+        };
+        let cues = shape_cues(&segments, &layout, |_| 0);
+        let lines: Vec<Vec<&str>> = cues.iter().map(|c| c.lines.iter().map(String::as_str).collect()).collect();
+        assert_eq!(
+            lines,
+            [
+                vec!["one two three", "four five six"],
+                vec!["seven eight nine", "ten eleven"],
+                vec!["twelve"],
+                vec!["Done."]
+            ]
+        );
+        assert_eq!(cues[1].speaker.as_deref(), Some("spk_0"));
+        assert!(cues[1].continues && !cues[0].continues);
+        assert_eq!(cues[3].speaker, None);
+        // Timed by their share of the characters, with a gap before the next cue
+        assert_eq!(cues[1].start_time, 12.0 * 26.0 / 58.0);
+        let gap = cues[1].start_time - cues[0].end_time;
+        assert!((0.1..0.101).contains(&gap));
+        assert_eq!(cues[2].end_time, 11.9);
+
+        let by_duration = SubtitleLayout { max_cue_duration: Some(3.0), ..Default::default() };
+        let cues = shape_cues(&segments, &by_duration, |_| 0);
+        assert_eq!(cues.len(), 6);
+        assert_eq!(cues[0].lines, ["one two three"]);
+        assert!(cues.iter().all(|cue| cue.end_time - cue.start_time <= 3.0));
+
+        // The first line leaves room for a "spk_0: " label
+        let cues = shape_cues(&segments, &layout, |speaker| speaker.len() + 2);
+        assert_eq!(cues[0].lines, ["one two", "three four five"]);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::transcribe::TranscriptionResult;
+use crate::utils::text;
+
+/// Numbered lines per page, following court-reporting convention
+const LINES_PER_PAGE: usize = 25;
+
+/// Maximum display width of text per numbered line
+const LINE_WIDTH: usize = 56;
+
+/// Format transcription as a deposition-style legal transcript
+///
+/// Every page carries a header and 25 numbered lines, speaker turns start with the
+/// speaker name in capitals, and a certification page is appended for signing.
+pub fn format_as_legal(result: &TranscriptionResult, include_timestamps: bool) -> String {
+    let title = result.audio_info.title.as_deref().unwrap_or("Untitled Recording");
+
+    let mut lines = Vec::new();
+    for segment in &result.segments {
+        let mut prefix = String::new();
+        if include_timestamps {
+            prefix.push_str(&format!("[{}] ", format_clock(segment.start_time)));
+        }
+        if let Some(speaker) = &segment.speaker_id {
+            prefix.push_str(&format!("{}:  ", speaker_caption(speaker)));
+        }
+
+        lines.extend(text::wrap_to_width(&format!("{}{}", prefix, segment.text), LINE_WIDTH));
+    }
+
+    if result.segments.is_empty() {
+        lines.extend(text::wrap_to_width(&result.transcript, LINE_WIDTH));
+    }
+
+    let mut output = String::new();
+    let pages: Vec<&[String]> = lines.chunks(LINES_PER_PAGE).collect();
+    let total_pages = pages.len() + 1; // plus the certification page
+
+    for (page_index, page) in pages.iter().enumerate() {
+        output.push_str(&page_header(title, page_index + 1, total_pages));
+        for line_number in 1..=LINES_PER_PAGE {
+            let text = page.get(line_number - 1).map(String::as_str).unwrap_or("");
+            output.push_str(format!("{:>2}  {}", line_number, text).trim_end());
+            output.push('\n');
+        }
+        output.push('\n');
+    }
+
+    output.push_str(&page_header(title, total_pages, total_pages));
+    output.push_str(&certification_page(result));
+
+    output
+}
+
+/// Render a speaker id as an all-caps caption (`spk_0` becomes `SPEAKER 1`)
+pub(super) fn speaker_caption(speaker: &str) -> String {
+    speaker
+        .strip_prefix("spk_")
+        .and_then(|n| n.parse::<u32>().ok())
+        .map(|n| format!("SPEAKER {}", n + 1))
+        .unwrap_or_else(|| speaker.to_uppercase())
+}
+
+/// Page header with the recording title and page counter
+fn page_header(title: &str, page: usize, total_pages: usize) -> String {
+    let counter = format!("Page {} of {}", page, total_pages);
+    let width = LINE_WIDTH + 4;
+    let title = text::truncate_to_width(title, width.saturating_sub(counter.len() + 2));
+    let padding = width.saturating_sub(text::display_width(&title) + counter.len());
+
+    format!("{}{}{}\n\n", title, " ".repeat(padding.max(1)), counter)
+}
+
+/// Certification page with placeholders for the transcriber to complete
+fn certification_page(result: &TranscriptionResult) -> String {
+    let mut page = String::new();
+
+    page.push_str("                 CERTIFICATE OF TRANSCRIPTION\n\n");
+    page.push_str("I, ______________________________, hereby certify that the\n");
+    page.push_str("foregoing pages are a true and accurate transcription of the\n");
+    page.push_str("recording identified below, to the best of my ability.\n\n");
+    page.push_str(&format!(
+        "Recording:   {}\n",
+        result.audio_info.title.as_deref().unwrap_or("Untitled Recording")
+    ));
+    page.push_str(&format!("Source:      {}\n", result.audio_info.original_url));
+    page.push_str(&format!("Language:    {}\n", result.metadata.language));
+    page.push_str(&format!(
+        "Transcribed: {}\n\n",
+        result.metadata.completed_at.format("%B %-d, %Y")
+    ));
+    page.push_str("Signature:   ______________________________\n\n");
+    page.push_str("Date:        ______________________________\n");
+
+    page
+}
+
+/// Format seconds as HH:MM:SS
+pub(super) fn format_clock(seconds: f64) -> String {
+    let total_seconds = seconds as u64;
+    format!(
+        "{:02}:{:02}:{:02}",
+        total_seconds / 3600,
+        (total_seconds % 3600) / 60,
+        total_seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_speaker_caption() {
+        assert_eq!(speaker_caption("spk_0"), "SPEAKER 1");
+        assert_eq!(speaker_caption("spk_3"), "SPEAKER 4");
+        assert_eq!(speaker_caption("witness"), "WITNESS");
+    }
+}
@@ -0,0 +1,116 @@
+use crate::postprocess::paragraphs::{group_paragraphs, paragraph_text};
+use crate::transcribe::TranscriptionResult;
+use crate::utils::format_duration;
+
+/// Format transcription as a Markdown article
+///
+/// The title is the top heading, chapters become `##` headings and the segments are
+/// grouped into paragraphs, each led by its speaker in bold when the speaker changes.
+pub fn format_as_markdown(result: &TranscriptionResult, include_timestamps: bool) -> String {
+    let title = result.audio_info.title.as_deref().unwrap_or("Transcript");
+    let mut output = format!("# {}\n\n", escape(title));
+
+    let mut details = Vec::new();
+    if let Some(duration) = result.metadata.audio_duration {
+        details.push(format_duration(duration));
+    }
+    details.push(result.metadata.language.clone());
+    if let Some(speakers) = result.metadata.speaker_count {
+        details.push(format!("{} speakers", speakers));
+    }
+    output.push_str(&format!("*{}*\n\n", details.join(" · ")));
+
+    if result.segments.is_empty() {
+        output.push_str(&escape(result.transcript.trim()));
+        output.push_str("\n\n");
+        push_keywords(&mut output, result);
+        output.truncate(output.trim_end().len());
+        output.push('\n');
+        return output;
+    }
+
+    let starts: Vec<f64> = result.chapters.iter().map(|c| c.start_time).collect();
+    let mut chapters = result.chapters.iter().peekable();
+    let mut previous_speaker = None;
+    for paragraph in group_paragraphs(&result.segments, &starts) {
+        let first = &paragraph[0];
+        while let Some(chapter) = chapters.next_if(|c| c.start_time <= first.start_time) {
+            output.push_str(&format!("## {}\n\n", escape(&chapter.title)));
+            previous_speaker = None;
+        }
+
+        if include_timestamps {
+            output.push_str(&format!("`{}` ", format_duration(first.start_time)));
+        }
+        if let Some(speaker) = first.speaker_id.as_ref().filter(|s| previous_speaker != Some(*s)) {
+            output.push_str(&format!("**Speaker {}:** ", escape(speaker)));
+        }
+        previous_speaker = first.speaker_id.as_ref();
+        output.push_str(&escape(&paragraph_text(paragraph)));
+        output.push_str("\n\n");
+    }
+    push_keywords(&mut output, result);
+    output.truncate(output.trim_end().len());
+    output.push('\n');
+
+    output
+}
+
+/// Keywords and Entities sections, when `--extract-keywords` found some
+fn push_keywords(output: &mut String, result: &TranscriptionResult) {
+    let Some(keywords) = &result.keywords else {
+        return;
+    };
+    if !keywords.keywords.is_empty() {
+        output.push_str("## Keywords\n\n");
+        for keyword in &keywords.keywords {
+            output.push_str(&format!("- {}\n", escape(&keyword.text)));
+        }
+        output.push('\n');
+    }
+    if !keywords.entities.is_empty() {
+        output.push_str("## Entities\n\n");
+        for entity in &keywords.entities {
+            output.push_str(&format!("- {} ({})\n", escape(&entity.text), entity.entity_type.to_lowercase()));
+        }
+        output.push('\n');
+    }
+}
+
+/// Keep transcribed text from being read as Markdown syntax
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extractors::Chapter;
+
+    #[test]
+    fn test_format_as_markdown() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("talk_*notes*.srt");
+        let srt = "1\n00:00:00,000 --> 00:00:02,000\nHello and welcome.\n\n\
+                   2\n00:00:02,000 --> 00:00:04,000\nToday: paragraphs.\n\n\
+                   3\n00:00:04,500 --> 00:00:06,000\nThanks!\n";
+        fs_err::write(&path, srt).unwrap();
+        let mut result = crate::output::load_transcript(&path).unwrap();
+        result.segments[2].speaker_id = Some("2".to_string());
+        result.chapters = vec![Chapter { title: "Intro".to_string(), start_time: 0.0, end_time: None }];
+
+        let markdown = format_as_markdown(&result, true);
+        assert_eq!(
+            markdown,
+            "# talk\\_\\*notes\\*\n\n*6s · und*\n\n## Intro\n\n`0s` Hello and welcome. Today: paragraphs.\n\n\
+             `4s` **Speaker 2:** Thanks!\n"
+        );
+    }
+}
@@ -1,48 +1,340 @@
-use anyhow::Result;
-use std::path::Path;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
 
 use crate::cli::OutputFormat;
-use crate::transcribe::TranscriptionResult;
+use crate::extractors::{AudioFormat, AudioInfo, DownloadMethod};
+use crate::transcribe::{TranscriptSegment, TranscriptionMetadata, TranscriptionResult};
 
+pub mod annotation;
+pub mod ass;
+pub mod broadcast;
+pub mod dialogue;
 pub mod formatters;
+pub mod html;
+pub mod karaoke;
+pub mod layout;
+pub mod legal;
+pub mod markdown;
+pub mod retime;
+pub mod review;
+pub mod schema;
+#[cfg(feature = "pipeline")]
+pub mod summary;
+pub mod template;
+pub mod timecode;
+pub mod timestamps;
 
+pub use annotation::{format_as_eaf, format_as_textgrid};
+pub use ass::format_as_ass;
+pub use broadcast::{format_as_edl, format_as_ttml};
+pub use dialogue::format_as_dialogue;
 pub use formatters::*;
+pub use html::format_as_html;
+pub use layout::SubtitleLayout;
+pub use legal::format_as_legal;
+pub use markdown::format_as_markdown;
+pub use schema::JsonDetail;
+pub use timecode::{FrameRate, Timecode};
+pub use timestamps::{TimestampFormat, TimestampStyle};
+
+/// Settings that shape rendered output
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RenderOptions {
+    /// Include timestamps in text output
+    pub timestamps: bool,
+    
+    /// Use detailed timestamps
+    pub detailed_timestamps: bool,
+    
+    /// Timecode offset and frame rate for SRT/EDL/TTML output
+    #[serde(default)]
+    pub timecode: Timecode,
+    
+    /// Custom timestamp rendering for text output
+    #[serde(default)]
+    pub timestamp_style: TimestampStyle,
+    
+    /// How much the JSON export includes
+    #[serde(default)]
+    pub json_detail: JsonDetail,
+    
+    /// Group text output into paragraphs
+    #[serde(default)]
+    pub paragraphs: bool,
+    
+    /// Mark words below this confidence with `[?]` in text output, and list them in a review file
+    #[serde(default)]
+    pub review_threshold: Option<f64>,
+    
+    /// Keep the words' alternatives in JSON output
+    #[serde(default)]
+    pub include_alternatives: bool,
+    
+    /// List segments or single words in JSON and CSV output
+    #[serde(default)]
+    pub granularity: Granularity,
+    
+    /// Render through this Tera template instead of the output format
+    #[serde(default)]
+    pub template: Option<PathBuf>,
+    
+    /// Line wrapping, cue splitting and word timing for subtitle output
+    #[serde(default)]
+    pub subtitles: SubtitleLayout,
+}
+
+/// What happens when the output file already exists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExistingOutput {
+    /// Replace it
+    #[default]
+    Overwrite,
+    /// Refuse to replace it (`--no-clobber`)
+    NoClobber,
+    /// Keep it as `<name>.bak`, or `<name>.bak.N` if that is taken too (`--backup`)
+    Backup,
+}
+
+impl ExistingOutput {
+    pub fn from_flags(no_clobber: bool, backup: bool) -> Self {
+        match (no_clobber, backup) {
+            (true, _) => ExistingOutput::NoClobber,
+            (false, true) => ExistingOutput::Backup,
+            (false, false) => ExistingOutput::Overwrite,
+        }
+    }
+    
+    /// Fail if the output would be refused, so a run can stop before doing any work
+    pub fn check(self, path: &Path) -> Result<()> {
+        if self == ExistingOutput::NoClobber && path.exists() {
+            anyhow::bail!("{} already exists (remove it, or drop --no-clobber)", path.display());
+        }
+        Ok(())
+    }
+}
+
+/// Render a transcription result in the requested format
+pub fn render(result: &TranscriptionResult, format: &OutputFormat, options: &RenderOptions) -> Result<String> {
+    if let Some(template) = &options.template {
+        return template::render_template(result, template);
+    }
+    
+    let content = match format {
+        OutputFormat::Text => {
+            let marked = options.review_threshold.map(|threshold| review::mark_low_confidence(result, threshold));
+            format_as_text(
+                marked.as_ref().unwrap_or(result),
+                options.timestamps,
+                options.detailed_timestamps,
+                &options.timestamp_style,
+                options.paragraphs,
+            )
+        }
+        OutputFormat::Json => match options.granularity {
+            Granularity::Segments => format_as_json(result, options.json_detail, options.include_alternatives)?,
+            Granularity::Words => format_words_as_json(result, options.include_alternatives)?,
+        },
+        OutputFormat::Srt => format_as_srt(result, options.detailed_timestamps, &options.timecode, &options.subtitles),
+        OutputFormat::Vtt => format_as_vtt(result, options.detailed_timestamps, &options.subtitles)?,
+        OutputFormat::Sbv => format_as_sbv(result, &options.subtitles),
+        OutputFormat::Ass => format_as_ass(result, &options.subtitles)?,
+        OutputFormat::Csv => match options.granularity {
+            Granularity::Segments => format_as_csv(result)?,
+            Granularity::Words => format_words_as_csv(result)?,
+        },
+        OutputFormat::Legal => format_as_legal(result, options.timestamps),
+        OutputFormat::Markdown => format_as_markdown(result, options.timestamps),
+        OutputFormat::Dialogue => format_as_dialogue(result, options.timestamps),
+        OutputFormat::Chapters => format_as_youtube_chapters(result)?,
+        OutputFormat::Html => format_as_html(result),
+        OutputFormat::Edl => format_as_edl(result, &options.timecode),
+        OutputFormat::Ttml => format_as_ttml(result, &options.timecode),
+        OutputFormat::Textgrid => format_as_textgrid(result),
+        OutputFormat::Eaf => format_as_eaf(result),
+    };
+    
+    Ok(content)
+}
 
 /// Save transcription result to file
+///
+/// The file is written under a temporary name and renamed into place, so an interrupted
+/// run never leaves a truncated output. Returns where a replaced file was backed up to.
 pub async fn save_to_file(
     result: &TranscriptionResult,
     path: &Path,
     format: &OutputFormat,
-    include_timestamps: bool,
-    detailed_timestamps: bool,
-) -> Result<()> {
-    let content = match format {
-        OutputFormat::Text => format_as_text(result, include_timestamps, detailed_timestamps),
-        OutputFormat::Json => format_as_json(result)?,
-        OutputFormat::Srt => format_as_srt(result, detailed_timestamps),
-        OutputFormat::Vtt => format_as_vtt(result, detailed_timestamps),
-        OutputFormat::Csv => format_as_csv(result)?,
+    options: &RenderOptions,
+    existing: ExistingOutput,
+) -> Result<Option<PathBuf>> {
+    let content = render(result, format, options)?;
+    
+    write_atomic(path, content.as_bytes(), existing)
+}
+
+/// Write a file via a temporary sibling and a rename, handling an existing file as asked
+fn write_atomic(path: &Path, content: &[u8], existing: ExistingOutput) -> Result<Option<PathBuf>> {
+    existing.check(path)?;
+    
+    let file_name = path
+        .file_name()
+        .with_context(|| format!("Not a file path: {}", path.display()))?
+        .to_string_lossy();
+    let temp_path = path.with_file_name(format!(".{}.{}.tmp", file_name, &Uuid::new_v4().to_string()[..8]));
+    
+    let written = fs_err::File::create(&temp_path).and_then(|mut file| {
+        file.write_all(content)?;
+        file.sync_all()
+    });
+    if let Err(e) = written {
+        let _ = fs_err::remove_file(&temp_path);
+        return Err(e.into());
+    }
+    
+    let backup = match existing {
+        ExistingOutput::Backup if path.exists() => {
+            let backup = backup_path(path);
+            fs_err::rename(path, &backup)?;
+            Some(backup)
+        }
+        _ => None,
     };
     
-    fs_err::write(path, content)?;
-    Ok(())
+    if let Err(e) = fs_err::rename(&temp_path, path) {
+        let _ = fs_err::remove_file(&temp_path);
+        return Err(e.into());
+    }
+    
+    Ok(backup)
+}
+
+/// First free `<name>.bak`, `<name>.bak.1`, `<name>.bak.2`, ... next to a file
+fn backup_path(path: &Path) -> PathBuf {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let mut candidate = path.with_file_name(format!("{}.bak", file_name));
+    let mut index = 1;
+    while candidate.exists() {
+        candidate = path.with_file_name(format!("{}.bak.{}", file_name, index));
+        index += 1;
+    }
+    candidate
 }
 
 /// Print transcription result to console
 pub fn print_to_console(
     result: &TranscriptionResult, 
     format: &OutputFormat,
-    include_timestamps: bool,
-    detailed_timestamps: bool,
+    options: &RenderOptions,
 ) -> Result<()> {
-    let content = match format {
-        OutputFormat::Text => format_as_text(result, include_timestamps, detailed_timestamps),
-        OutputFormat::Json => format_as_json(result)?,
-        OutputFormat::Srt => format_as_srt(result, detailed_timestamps),
-        OutputFormat::Vtt => format_as_vtt(result, detailed_timestamps),
-        OutputFormat::Csv => format_as_csv(result)?,
-    };
+    let content = render(result, format, options)?;
     
     println!("{}", content);
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// Load a transcription result previously saved with `--format json`
+pub fn load_result(path: &Path) -> Result<TranscriptionResult> {
+    let content = fs_err::read_to_string(path)?;
+    
+    serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse transcription result: {}", path.display()))
+}
+
+/// Load a saved transcript: result JSON, or SRT/VTT subtitles as one segment per cue
+pub fn load_transcript(path: &Path) -> Result<TranscriptionResult> {
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("").to_lowercase();
+    if !matches!(extension.as_str(), "srt" | "vtt") {
+        return load_result(path);
+    }
+    
+    let segments: Vec<TranscriptSegment> = crate::captions::parse_file(path)?
+        .into_iter()
+        .map(|cue| TranscriptSegment {
+            start_time: cue.start_time,
+            end_time: cue.end_time,
+            text: cue.plain_lines().join(" "),
+            confidence: None,
+            speaker_id: None,
+            toxicity: None,
+            language: None,
+            verbatim: None,
+        })
+        .collect();
+    let end = segments.iter().map(|s| s.end_time).fold(0.0, f64::max);
+    
+    Ok(TranscriptionResult {
+        transcript: segments.iter().map(|s| s.text.trim()).collect::<Vec<_>>().join(" "),
+        segments,
+        audio_info: AudioInfo {
+            download_url: path.to_string_lossy().to_string(),
+            download_method: DownloadMethod::LocalFile,
+            duration: None,
+            title: path.file_stem().map(|stem| stem.to_string_lossy().to_string()),
+            format: AudioFormat::Mp3,
+            sample_rate: None,
+            file_size: None,
+            original_url: path.to_string_lossy().to_string(),
+            chapters: Vec::new(),
+        },
+        audio_path: None,
+        metadata: TranscriptionMetadata {
+            job_id: path.file_stem().unwrap_or_default().to_string_lossy().to_string(),
+            // Subtitle files do not say what language they are in
+            language: "und".to_string(),
+            processing_duration: None,
+            audio_duration: Some(end),
+            confidence: None,
+            completed_at: chrono::Utc::now(),
+            speaker_count: None,
+        },
+        words: None,
+        raw: None,
+        chapters: Vec::new(),
+        redactions: Vec::new(),
+        keywords: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomic_existing_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("talk.srt");
+
+        assert_eq!(write_atomic(&path, b"first", ExistingOutput::NoClobber).unwrap(), None);
+        assert!(write_atomic(&path, b"second", ExistingOutput::NoClobber).is_err());
+        assert_eq!(fs_err::read_to_string(&path).unwrap(), "first");
+
+        let backup = write_atomic(&path, b"second", ExistingOutput::Backup).unwrap();
+        assert_eq!(backup, Some(dir.path().join("talk.srt.bak")));
+        let backup = write_atomic(&path, b"third", ExistingOutput::Backup).unwrap();
+        assert_eq!(backup, Some(dir.path().join("talk.srt.bak.1")));
+        assert_eq!(fs_err::read_to_string(dir.path().join("talk.srt.bak")).unwrap(), "first");
+
+        write_atomic(&path, b"fourth", ExistingOutput::Overwrite).unwrap();
+        assert_eq!(fs_err::read_to_string(&path).unwrap(), "fourth");
+        // Only the output and its two backups; no temporary files are left behind
+        assert_eq!(fs_err::read_dir(dir.path()).unwrap().count(), 3);
+    }
+
+    #[test]
+    fn test_load_transcript_from_srt() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("talk.srt");
+        fs_err::write(&path, "1\n00:00:01,000 --> 00:00:02,500\n<i>Hello</i>\nthere\n\n2\n00:00:03,000 --> 00:00:04,000\nBye\n").unwrap();
+
+        let result = load_transcript(&path).unwrap();
+        assert_eq!(result.segments.len(), 2);
+        assert_eq!(result.segments[0].text, "Hello there");
+        assert_eq!(result.segments[1].start_time, 3.0);
+        assert_eq!(result.transcript, "Hello there Bye");
+        assert_eq!(result.metadata.audio_duration, Some(4.0));
+    }
+}
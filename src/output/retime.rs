@@ -0,0 +1,86 @@
+use crate::transcribe::TranscriptSegment;
+
+/// Shortest cue duration players reliably display, in seconds
+pub const MIN_CUE_DURATION: f64 = 0.2;
+
+/// Make segment timings valid for subtitle players
+///
+/// AWS timestamps occasionally produce overlapping, out-of-order or near zero-length
+/// segments. Cues are sorted by start time, inverted or too-short cues are extended to
+/// `MIN_CUE_DURATION`, and overlaps are resolved by trimming the earlier cue when it
+/// stays long enough, otherwise by delaying the later one. Valid cues are untouched.
+pub fn retime_segments(segments: &[TranscriptSegment]) -> Vec<TranscriptSegment> {
+    let mut cues = segments.to_vec();
+    cues.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+    
+    let mut adjusted = 0;
+    for i in 0..cues.len() {
+        let original = (cues[i].start_time, cues[i].end_time);
+        
+        if cues[i].end_time - cues[i].start_time < MIN_CUE_DURATION {
+            cues[i].end_time = cues[i].start_time + MIN_CUE_DURATION;
+        }
+        
+        if let Some(next_start) = cues.get(i + 1).map(|next| next.start_time) {
+            if cues[i].end_time > next_start {
+                if next_start - cues[i].start_time >= MIN_CUE_DURATION {
+                    cues[i].end_time = next_start;
+                } else {
+                    cues[i + 1].start_time = cues[i].end_time;
+                }
+            }
+        }
+        
+        if (cues[i].start_time, cues[i].end_time) != original {
+            adjusted += 1;
+        }
+    }
+    
+    if adjusted > 0 {
+        tracing::debug!("Re-timed {} of {} cues", adjusted, cues.len());
+    }
+    
+    cues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn segment(start_time: f64, end_time: f64) -> TranscriptSegment {
+        TranscriptSegment {
+            start_time,
+            end_time,
+            text: "text".to_string(),
+            confidence: None,
+            speaker_id: None,
+            toxicity: None,
+            language: None,
+            verbatim: None,
+        }
+    }
+    
+    fn timings(segments: &[TranscriptSegment]) -> Vec<(f64, f64)> {
+        segments.iter().map(|s| (s.start_time, s.end_time)).collect()
+    }
+    
+    #[test]
+    fn test_valid_cues_are_unchanged() {
+        let segments = vec![segment(0.0, 1.0), segment(1.5, 3.0)];
+        
+        assert_eq!(timings(&retime_segments(&segments)), vec![(0.0, 1.0), (1.5, 3.0)]);
+    }
+    
+    #[test]
+    fn test_fixes_overlap_order_and_zero_length() {
+        let segments = vec![segment(2.0, 2.0), segment(0.0, 2.5), segment(2.1, 3.0)];
+        let retimed = timings(&retime_segments(&segments));
+        
+        // Sorted, overlap trimmed, zero-length cue extended and the next one delayed
+        assert_eq!(retimed[0], (0.0, 2.0));
+        assert_eq!(retimed[1].0, 2.0);
+        assert!((retimed[1].1 - 2.2).abs() < 1e-9);
+        assert!((retimed[2].0 - 2.2).abs() < 1e-9);
+        assert_eq!(retimed[2].1, 3.0);
+    }
+}
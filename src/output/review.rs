@@ -0,0 +1,204 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use super::formatters::{format_detailed_timestamp, WordTimestamp};
+use super::ExistingOutput;
+use crate::transcribe::{TranscriptSegment, TranscriptionResult};
+
+/// Placed after words below the review threshold in text output
+pub const MARKER: &str = "[?]";
+
+/// Transcript words searched ahead for a text token, so words the transcript lost
+/// (cleaned fillers, merged corrections) do not throw the alignment off
+const LOOKAHEAD: usize = 4;
+
+/// A segment a corrector should listen to again
+struct Flagged<'a> {
+    segment: &'a TranscriptSegment,
+    /// Segment text with the low-confidence words marked
+    text: String,
+    /// Its words below the threshold; none when only the segment's confidence is known
+    words: Vec<&'a WordTimestamp>,
+}
+
+/// Parse a `--review-threshold` confidence between 0 and 1
+pub fn parse_threshold(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(threshold) if threshold > 0.0 && threshold <= 1.0 => Ok(threshold),
+        _ => Err(format!("expected a confidence between 0 and 1 (e.g. 0.85), got {}", value)),
+    }
+}
+
+/// The result with `[?]` after every word below the threshold
+///
+/// Segments without word timings are marked as a whole when their own confidence is low.
+pub fn mark_low_confidence(result: &TranscriptionResult, threshold: f64) -> TranscriptionResult {
+    let mut marked = result.clone();
+    for (segment, flagged) in marked.segments.iter_mut().zip(flag(result, threshold)) {
+        if let Some(flagged) = flagged {
+            segment.text = flagged.text;
+        }
+    }
+    marked
+}
+
+/// Review report: every segment with words below the threshold, and where to listen
+pub fn format_review(result: &TranscriptionResult, threshold: f64) -> String {
+    let flagged: Vec<Flagged> = flag(result, threshold).into_iter().flatten().collect();
+    let words: usize = flagged.iter().map(|f| f.words.len()).sum();
+
+    let mut output = format!(
+        "# Review of: {}\n# Below {:.0}% confidence: {} words in {} segments\n",
+        result.audio_info.title.as_deref().unwrap_or("Unknown"),
+        threshold * 100.0,
+        words,
+        flagged.len()
+    );
+    for flagged in &flagged {
+        let segment = flagged.segment;
+        output.push_str(&format!(
+            "\n[{} - {}] {}{}\n",
+            format_detailed_timestamp(segment.start_time),
+            format_detailed_timestamp(segment.end_time),
+            segment.speaker_id.as_ref().map(|s| format!("Speaker {}: ", s)).unwrap_or_default(),
+            flagged.text
+        ));
+        if flagged.words.is_empty() {
+            let confidence = segment.confidence.unwrap_or_default();
+            output.push_str(&format!("    whole segment  {:.0}%\n", confidence * 100.0));
+        }
+        for word in &flagged.words {
+            output.push_str(&format!(
+                "    {}  {:.0}%  {}\n",
+                format_detailed_timestamp(word.start_time),
+                word.confidence.unwrap_or_default() * 100.0,
+                word.word
+            ));
+        }
+    }
+
+    output
+}
+
+/// Where the review of an output file goes: `talk.txt` is reviewed in `talk.review.txt`
+pub fn review_path(output: &Path) -> PathBuf {
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    output.with_file_name(format!("{}.review.txt", stem))
+}
+
+/// Save the review report next to an output file; returns where a replaced file was backed up to
+pub fn save_review(
+    result: &TranscriptionResult,
+    output: &Path,
+    threshold: f64,
+    existing: ExistingOutput,
+) -> Result<Option<PathBuf>> {
+    super::write_atomic(&review_path(output), format_review(result, threshold).as_bytes(), existing)
+}
+
+/// Flag each segment (None when it needs no review), aligning the result's words to its text
+fn flag(result: &TranscriptionResult, threshold: f64) -> Vec<Option<Flagged<'_>>> {
+    let low = |confidence: Option<f64>| confidence.is_some_and(|c| c < threshold);
+    let words = result.words.as_deref().unwrap_or_default();
+
+    result
+        .segments
+        .iter()
+        .map(|segment| {
+            // Each text token of a word, with the word it belongs to
+            let tokens: Vec<(String, &WordTimestamp)> = words
+                .iter()
+                .filter(|w| {
+                    let middle = (w.start_time + w.end_time) / 2.0;
+                    middle >= segment.start_time && middle <= segment.end_time
+                })
+                .flat_map(|w| w.word.split_whitespace().map(move |token| (normalize(token), w)))
+                .collect();
+
+            if tokens.is_empty() {
+                return low(segment.confidence).then(|| Flagged {
+                    segment,
+                    text: format!("{} {}", MARKER, segment.text),
+                    words: Vec::new(),
+                });
+            }
+
+            let mut next = 0;
+            let mut flagged: Vec<&WordTimestamp> = Vec::new();
+            let mut text = Vec::new();
+            for token in segment.text.split_whitespace() {
+                text.push(token.to_string());
+                let key = normalize(token);
+                let Some(offset) = tokens[next..].iter().take(LOOKAHEAD).position(|(k, _)| *k == key) else {
+                    continue;
+                };
+                let word = tokens[next + offset].1;
+                next += offset + 1;
+                // A word spanning several tokens is marked once, after its last
+                let ends_word = tokens.get(next).is_none_or(|(_, w)| !std::ptr::eq(*w, word));
+                if ends_word && low(word.confidence) {
+                    text.push(MARKER.to_string());
+                    flagged.push(word);
+                }
+            }
+
+            (!flagged.is_empty()).then(|| Flagged {
+                segment,
+                text: text.join(" "),
+                words: flagged,
+            })
+        })
+        .collect()
+}
+
+fn normalize(token: &str) -> String {
+    token.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(word: &str, start_time: f64, confidence: f64) -> WordTimestamp {
+        WordTimestamp {
+            word: word.to_string(),
+            start_time,
+            end_time: start_time + 0.4,
+            confidence: Some(confidence),
+            speaker_id: None,
+            alternatives: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_review_low_confidence_words() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("call.srt");
+        let srt = "1\n00:00:00,000 --> 00:00:02,000\nWe ship to New York, right?\n\n\
+                   2\n00:00:02,000 --> 00:00:04,000\nYes.\n\n\
+                   3\n00:00:04,000 --> 00:00:06,000\nGreat.\n";
+        fs_err::write(&path, srt).unwrap();
+        let mut result = crate::output::load_transcript(&path).unwrap();
+        result.segments[2].confidence = Some(0.5);
+        result.words = Some(vec![
+            word("We", 0.0, 0.99),
+            word("ship", 0.4, 0.6),
+            word("to", 0.8, 0.97),
+            // Merged into one word by a correction
+            word("New York", 1.2, 0.7),
+            word("right", 1.6, 0.95),
+            word("Yes", 2.2, 0.99),
+        ]);
+
+        let marked = mark_low_confidence(&result, 0.85);
+        assert_eq!(marked.segments[0].text, "We ship [?] to New York, [?] right?");
+        assert_eq!(marked.segments[1].text, "Yes.");
+        assert_eq!(marked.segments[2].text, "[?] Great.");
+
+        let review = format_review(&result, 0.85);
+        assert!(review.contains("Below 85% confidence: 2 words in 2 segments"));
+        assert!(review.contains("\n[00:00.000 - 00:02.000] We ship [?] to New York, [?] right?\n    00:00.400  60%  ship\n"));
+        assert!(review.contains("    whole segment  50%\n"));
+        assert_eq!(review_path(&path), dir.path().join("call.review.txt"));
+    }
+}
@@ -0,0 +1,122 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::transcribe::TranscriptionResult;
+
+/// How much of a result the JSON export includes
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonDetail {
+    /// Transcript and timed segments only
+    Minimal,
+    /// Everything except the raw backend output
+    #[default]
+    Standard,
+    /// Also the raw backend output (AWS items with alternatives, speaker label blocks)
+    Full,
+}
+
+/// Top-level fields left out of a detail level
+const OMITTED_FIELDS: &[(JsonDetail, &[&str])] = &[
+    (JsonDetail::Minimal, &["words", "raw", "audio_path"]),
+    (JsonDetail::Standard, &["raw"]),
+    (JsonDetail::Full, &[]),
+];
+
+/// Segment fields left out of minimal exports
+const MINIMAL_SEGMENT_OMITS: &[&str] = &["confidence"];
+
+/// Metadata fields left out of minimal exports
+const MINIMAL_METADATA_OMITS: &[&str] = &["processing_duration", "confidence"];
+
+/// JSON document for a result at the given detail level, with or without word alternatives
+///
+/// Every level stays loadable with `load_result`: only optional fields are dropped.
+pub fn to_value(result: &TranscriptionResult, detail: JsonDetail, alternatives: bool) -> Result<Value> {
+    let mut value = serde_json::to_value(result)?;
+
+    let omitted = OMITTED_FIELDS
+        .iter()
+        .find(|(level, _)| *level == detail)
+        .map(|(_, fields)| *fields)
+        .unwrap_or_default();
+    remove_fields(&mut value, omitted);
+
+    if detail == JsonDetail::Minimal {
+        if let Some(segments) = value["segments"].as_array_mut() {
+            segments.iter_mut().for_each(|segment| remove_fields(segment, MINIMAL_SEGMENT_OMITS));
+        }
+        remove_fields(&mut value["metadata"], MINIMAL_METADATA_OMITS);
+    }
+    if !alternatives {
+        if let Some(words) = value.get_mut("words").and_then(Value::as_array_mut) {
+            words.iter_mut().for_each(|word| remove_fields(word, &["alternatives"]));
+        }
+    }
+
+    Ok(value)
+}
+
+fn remove_fields(value: &mut Value, fields: &[&str]) {
+    if let Some(object) = value.as_object_mut() {
+        for field in fields {
+            object.remove(*field);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::formatters::{WordAlternative, WordTimestamp};
+    use crate::transcribe::TranscriptionMetadata;
+
+    fn result() -> TranscriptionResult {
+        let mut result = crate::transcribe::fixture::result(&[(0.0, 1.0, "Hello world")]);
+        result.segments[0].confidence = Some(0.9);
+        let word = WordTimestamp {
+            word: "Hello".to_string(),
+            start_time: 0.0,
+            end_time: 0.5,
+            confidence: Some(0.9),
+            speaker_id: None,
+            alternatives: vec![WordAlternative { word: "Yellow".to_string(), confidence: Some(0.4) }],
+        };
+        TranscriptionResult {
+            words: Some(vec![word]),
+            metadata: TranscriptionMetadata {
+                processing_duration: Some(3.0),
+                ..result.metadata
+            },
+            raw: Some(serde_json::json!({ "items": [] })),
+            ..result
+        }
+    }
+
+    #[test]
+    fn test_detail_levels() {
+        let result = result();
+
+        let full = to_value(&result, JsonDetail::Full, false).unwrap();
+        assert!(full.get("raw").is_some());
+
+        let standard = to_value(&result, JsonDetail::Standard, false).unwrap();
+        assert!(standard.get("raw").is_none());
+        assert!(standard.get("words").is_some());
+        assert!(standard["words"][0].get("alternatives").is_none());
+        let alternatives = to_value(&result, JsonDetail::Standard, true).unwrap();
+        assert_eq!(alternatives["words"][0]["alternatives"][0]["word"], "Yellow");
+
+        let minimal = to_value(&result, JsonDetail::Minimal, false).unwrap();
+        assert!(minimal.get("words").is_none());
+        assert!(minimal["segments"][0].get("confidence").is_none());
+        assert!(minimal["metadata"].get("processing_duration").is_none());
+
+        // Still loadable as a result
+        let reloaded: TranscriptionResult = serde_json::from_value(minimal).unwrap();
+        assert_eq!(reloaded.segments.len(), 1);
+        assert!(reloaded.words.is_none());
+    }
+}
@@ -0,0 +1,139 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::cli::Backend;
+use crate::history::{CostEstimate, HistoryConfig};
+use crate::transcribe::TranscriptionResult;
+
+/// Format of the summary printed at the end of a run
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum SummaryFormat {
+    /// A single line of JSON
+    Json,
+}
+
+/// Where a run's files ended up
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DeliveredFiles {
+    /// The transcript, unless it was printed to the console
+    pub transcript: Option<PathBuf>,
+    /// The output it replaced (`--backup`)
+    pub previous: Option<PathBuf>,
+    /// Further formats saved next to the transcript (`--format srt,json`)
+    pub other_formats: Vec<PathBuf>,
+    pub audio: Option<PathBuf>,
+    /// Archive copy location (`archive` config section)
+    pub archive: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunStatus {
+    Completed,
+    Queued,
+    Failed,
+}
+
+/// Outcome of a run for scripts; every field is always present, null when it does not apply
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    pub status: RunStatus,
+    pub outputs: DeliveredFiles,
+    /// Audio length in seconds
+    pub duration: Option<f64>,
+    pub language: Option<String>,
+    pub confidence: Option<f64>,
+    /// Estimated cost in USD at the `history` prices
+    pub cost_estimate: Option<f64>,
+    pub job_id: Option<String>,
+    /// Offline queue ID of a queued run (`queue flush`)
+    pub queue_id: Option<String>,
+    pub error: Option<String>,
+}
+
+impl RunSummary {
+    /// A finished run; its `outputs` are filled in once the result is delivered
+    pub fn completed(result: &TranscriptionResult, backend: Backend, prices: &HistoryConfig) -> Self {
+        let metadata = &result.metadata;
+        let duration = metadata
+            .audio_duration
+            .or_else(|| result.audio_info.duration.map(|d| d.num_milliseconds() as f64 / 1000.0));
+
+        Self {
+            duration,
+            language: Some(metadata.language.clone()).filter(|language| !language.is_empty()),
+            confidence: metadata.confidence,
+            cost_estimate: duration.map(|secs| CostEstimate::new(secs, None, backend, prices).total()),
+            job_id: Some(metadata.job_id.clone()).filter(|id| !id.is_empty()),
+            ..Self::new(RunStatus::Completed)
+        }
+    }
+
+    pub fn queued(queue_id: &str) -> Self {
+        Self {
+            queue_id: Some(queue_id.to_string()),
+            ..Self::new(RunStatus::Queued)
+        }
+    }
+
+    pub fn failed(error: &anyhow::Error) -> Self {
+        Self {
+            error: Some(format!("{:#}", error)),
+            ..Self::new(RunStatus::Failed)
+        }
+    }
+
+    fn new(status: RunStatus) -> Self {
+        Self {
+            status,
+            outputs: DeliveredFiles::default(),
+            duration: None,
+            language: None,
+            confidence: None,
+            cost_estimate: None,
+            job_id: None,
+            queue_id: None,
+            error: None,
+        }
+    }
+
+    pub fn render(&self, format: SummaryFormat) -> String {
+        match format {
+            SummaryFormat::Json => serde_json::to_string(self).unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_line() {
+        let result = crate::demo::result("demo");
+        let outputs = DeliveredFiles {
+            transcript: Some(PathBuf::from("talk.srt")),
+            ..Default::default()
+        };
+        let summary = RunSummary {
+            outputs,
+            ..RunSummary::completed(&result, Backend::Aws, &HistoryConfig::default())
+        };
+        let line = summary.render(SummaryFormat::Json);
+        assert!(!line.contains('\n'));
+
+        let summary: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(summary["status"], "completed");
+        assert_eq!(summary["outputs"]["transcript"], "talk.srt");
+        assert!(summary["outputs"]["audio"].is_null());
+        assert!(summary["cost_estimate"].as_f64().unwrap() > 0.0);
+        assert_eq!(summary["job_id"], result.metadata.job_id.as_str());
+
+        let failed = RunSummary::failed(&anyhow::anyhow!("no network")).render(SummaryFormat::Json);
+        let failed: serde_json::Value = serde_json::from_str(&failed).unwrap();
+        assert_eq!(failed["status"], "failed");
+        assert_eq!(failed["error"], "no network");
+        assert!(failed["language"].is_null() && failed.get("queue_id").is_some());
+    }
+}
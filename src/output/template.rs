@@ -0,0 +1,89 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::formatters::{format_srt_timestamp, format_vtt_timestamp};
+use super::legal::format_clock;
+use super::schema::{self, JsonDetail};
+use crate::transcribe::TranscriptionResult;
+
+/// A user's Tera template, read and checked
+pub struct Template {
+    tera: tera::Tera,
+    name: String,
+}
+
+impl Template {
+    /// Read and parse a template file, so mistakes show before anything is transcribed
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = fs_err::read_to_string(path)?;
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+
+        let mut tera = tera::Tera::default();
+        tera.register_filter("timestamp", timestamp);
+        tera.add_raw_template(&name, &source)
+            .with_context(|| format!("Invalid template: {}", path.display()))?;
+        Ok(Self { tera, name })
+    }
+
+    /// Render a result through the template
+    ///
+    /// The template sees the result as `--format json` saves it (`transcript`, `segments`,
+    /// `words`, `metadata`, `audio_info`, `chapters`, ...), word alternatives included.
+    pub fn render(&self, result: &TranscriptionResult) -> Result<String> {
+        let context = tera::Context::from_value(schema::to_value(result, JsonDetail::Standard, true)?)?;
+        self.tera
+            .render(&self.name, &context)
+            .with_context(|| format!("Failed to render template {}", self.name))
+    }
+}
+
+/// Render a result through the template at `path`
+pub fn render_template(result: &TranscriptionResult, path: &Path) -> Result<String> {
+    Template::load(path)?.render(result)
+}
+
+/// `{{ segment.start_time | timestamp }}`: seconds as `HH:MM:SS`, or with
+/// `format="srt"` / `format="vtt"` as subtitle times with milliseconds
+fn timestamp(value: &tera::Value, args: &HashMap<String, tera::Value>) -> tera::Result<tera::Value> {
+    let seconds = value.as_f64().ok_or_else(|| tera::Error::msg("timestamp expects a number of seconds"))?;
+    let stamp = match args.get("format").and_then(tera::Value::as_str).unwrap_or("clock") {
+        "clock" => format_clock(seconds),
+        "srt" => format_srt_timestamp(seconds),
+        "vtt" => format_vtt_timestamp(seconds),
+        other => return Err(tera::Error::msg(format!("unknown timestamp format {:?} (clock, srt or vtt)", other))),
+    };
+    Ok(tera::Value::String(stamp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_template() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wiki.srt");
+        let srt = "1\n00:00:01,000 --> 00:00:02,500\nHello.\n\n2\n00:01:05,000 --> 00:01:06,000\nBye.\n";
+        fs_err::write(&path, srt).unwrap();
+        let result = crate::output::load_transcript(&path).unwrap();
+
+        let template = dir.path().join("wiki.tera");
+        fs_err::write(
+            &template,
+            "== {{ audio_info.title }} ==\n{% for segment in segments %}\
+             * {{ segment.start_time | timestamp }} ({{ segment.end_time | timestamp(format=\"srt\") }}) {{ segment.text }}\n\
+             {% endfor %}",
+        )
+        .unwrap();
+        assert_eq!(
+            render_template(&result, &template).unwrap(),
+            "== wiki ==\n* 00:00:01 (00:00:02,500) Hello.\n* 00:01:05 (00:01:06,000) Bye.\n"
+        );
+
+        fs_err::write(&template, "{% for segment in segments %}").unwrap();
+        assert!(Template::load(&template).is_err());
+        fs_err::write(&template, "{{ transcript | timestamp }}").unwrap();
+        assert!(render_template(&result, &template).is_err());
+    }
+}
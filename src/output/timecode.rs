@@ -0,0 +1,221 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Video frame rate, with SMPTE drop-frame counting for the NTSC rates
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FrameRate {
+    /// Actual frames per second (29.97 is stored as 30000/1001)
+    pub fps: f64,
+
+    /// Count timecode in drop-frame (only meaningful for 29.97 and 59.94)
+    pub drop_frame: bool,
+}
+
+impl FrameRate {
+    /// Whole frames per timecode second (30 for 29.97)
+    pub fn nominal(&self) -> u64 {
+        self.fps.round() as u64
+    }
+
+    /// Frame numbers skipped at the start of each minute (except every tenth) in drop-frame
+    fn dropped_per_minute(&self) -> u64 {
+        if self.drop_frame {
+            self.nominal() / 15
+        } else {
+            0
+        }
+    }
+
+    /// Seconds from the start of the timeline to a frame
+    fn frames_to_seconds(&self, frames: u64) -> f64 {
+        frames as f64 / self.fps
+    }
+
+    /// Frame on which a moment falls
+    fn seconds_to_frames(&self, seconds: f64) -> u64 {
+        (seconds.max(0.0) * self.fps).round() as u64
+    }
+
+    /// Timecode label (`HH:MM:SS:FF`, or `HH:MM:SS;FF` in drop-frame) of a frame
+    pub fn label(&self, frames: u64) -> String {
+        let nominal = self.nominal();
+        let drop = self.dropped_per_minute();
+
+        // Re-insert the frame numbers drop-frame skips so the label can be split evenly
+        let mut frames = frames;
+        if drop > 0 {
+            let per_ten_minutes = nominal * 600 - drop * 9;
+            let per_minute = nominal * 60 - drop;
+            let tens = frames / per_ten_minutes;
+            let rest = frames % per_ten_minutes;
+            frames += drop * 9 * tens;
+            if rest > drop {
+                frames += drop * ((rest - drop) / per_minute);
+            }
+        }
+
+        let separator = if drop > 0 { ';' } else { ':' };
+        format!(
+            "{:02}:{:02}:{:02}{}{:02}",
+            frames / (nominal * 3600),
+            frames / (nominal * 60) % 60,
+            frames / nominal % 60,
+            separator,
+            frames % nominal
+        )
+    }
+
+    /// Frame a timecode label refers to
+    fn label_to_frames(&self, hours: u64, minutes: u64, seconds: u64, frames: u64) -> Result<u64> {
+        let nominal = self.nominal();
+        if frames >= nominal {
+            anyhow::bail!("Frame {} is out of range at {} fps", frames, self.fps);
+        }
+
+        let total_minutes = hours * 60 + minutes;
+        let drop = self.dropped_per_minute();
+        if drop > 0 && seconds == 0 && frames < drop && !minutes.is_multiple_of(10) {
+            anyhow::bail!("{:02}:{:02}:00;{:02} does not exist in drop-frame timecode", hours, minutes, frames);
+        }
+
+        Ok((total_minutes * 60 + seconds) * nominal + frames - drop * (total_minutes - total_minutes / 10))
+    }
+}
+
+impl FromStr for FrameRate {
+    type Err = String;
+
+    /// Parse `25`, `23.976`, `29.97` (drop-frame) or `29.97ndf` (non-drop)
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let lower = value.trim().to_lowercase();
+        let (number, non_drop) = match lower.strip_suffix("ndf") {
+            Some(number) => (number, true),
+            None => (lower.strip_suffix("df").unwrap_or(&lower), false),
+        };
+
+        let fps = match number {
+            "23.976" | "23.98" => 24000.0 / 1001.0,
+            "29.97" => 30000.0 / 1001.0,
+            "47.952" => 48000.0 / 1001.0,
+            "59.94" => 60000.0 / 1001.0,
+            other => other
+                .parse::<f64>()
+                .ok()
+                .filter(|fps| *fps >= 1.0 && *fps <= 120.0)
+                .ok_or_else(|| format!("invalid frame rate '{}': use e.g. 25, 29.97 or 29.97ndf", value))?,
+        };
+
+        let ntsc_drop_rate = matches!(number, "29.97" | "59.94");
+        Ok(Self {
+            fps,
+            drop_frame: ntsc_drop_rate && !non_drop,
+        })
+    }
+}
+
+/// How output timestamps line up with the programme's timecode
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Timecode {
+    /// Timecode of the first moment of the recording, in seconds
+    pub offset_secs: f64,
+
+    /// Frame rate timestamps are snapped to and labelled in
+    pub fps: Option<FrameRate>,
+}
+
+impl Timecode {
+    /// Build from `--timecode-offset` (`HH:MM:SS:FF`, `HH:MM:SS;FF` or `HH:MM:SS.mmm`) and `--fps`
+    pub fn new(offset: Option<&str>, fps: Option<FrameRate>) -> Result<Self> {
+        let offset_secs = match offset {
+            Some(offset) => parse_offset(offset, fps)?,
+            None => 0.0,
+        };
+
+        Ok(Self { offset_secs, fps })
+    }
+
+    /// Position on the programme timeline, snapped to a frame when a frame rate is set
+    pub fn apply(&self, seconds: f64) -> f64 {
+        let seconds = seconds + self.offset_secs;
+        match self.fps {
+            Some(rate) => rate.frames_to_seconds(rate.seconds_to_frames(seconds)),
+            None => seconds,
+        }
+    }
+
+    /// Timecode label of a moment in the recording, in `rate` when no frame rate is set
+    pub fn label(&self, seconds: f64, rate: FrameRate) -> String {
+        let rate = self.fps.unwrap_or(rate);
+        rate.label(rate.seconds_to_frames(seconds + self.offset_secs))
+    }
+}
+
+fn parse_offset(offset: &str, fps: Option<FrameRate>) -> Result<f64> {
+    let invalid = || anyhow::anyhow!("Invalid timecode offset '{}': use HH:MM:SS:FF or HH:MM:SS.mmm", offset);
+
+    let parts: Vec<&str> = offset.trim().split([':', ';']).collect();
+    let number = |s: &str| s.parse::<u64>().map_err(|_| invalid());
+
+    match parts.as_slice() {
+        [h, m, s, f] => {
+            let rate = fps.ok_or_else(|| anyhow::anyhow!("A frame-based --timecode-offset requires --fps"))?;
+            let (h, m, s, f) = (number(h)?, number(m)?, number(s)?, number(f)?);
+            if m >= 60 || s >= 60 {
+                return Err(invalid());
+            }
+            Ok(rate.frames_to_seconds(rate.label_to_frames(h, m, s, f)?))
+        }
+        [h, m, s] => {
+            let seconds: f64 = s.parse().map_err(|_| invalid())?;
+            let (h, m) = (number(h)?, number(m)?);
+            if m >= 60 || !(0.0..60.0).contains(&seconds) {
+                return Err(invalid());
+            }
+            Ok((h * 3600 + m * 60) as f64 + seconds)
+        }
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_frame_rate() {
+        let ntsc: FrameRate = "29.97".parse().unwrap();
+        assert!(ntsc.drop_frame);
+        assert_eq!(ntsc.nominal(), 30);
+        assert!(!"29.97ndf".parse::<FrameRate>().unwrap().drop_frame);
+        assert!(!"25".parse::<FrameRate>().unwrap().drop_frame);
+        assert!("fast".parse::<FrameRate>().is_err());
+    }
+
+    #[test]
+    fn test_drop_frame_labels() {
+        let rate: FrameRate = "29.97".parse().unwrap();
+
+        assert_eq!(rate.label(1799), "00:00:59;29");
+        assert_eq!(rate.label(1800), "00:01:00;02");
+        assert_eq!(rate.label(17982), "00:10:00;00");
+        assert_eq!(rate.label(107892), "01:00:00;00");
+        assert_eq!(rate.label_to_frames(0, 1, 0, 2).unwrap(), 1800);
+        assert!(rate.label_to_frames(0, 1, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_offset_round_trips_through_labels() {
+        let rate: FrameRate = "29.97".parse().unwrap();
+        let timecode = Timecode::new(Some("01:00:00;00"), Some(rate)).unwrap();
+
+        assert_eq!(timecode.label(0.0, rate), "01:00:00;00");
+        assert_eq!(timecode.label(60.0, rate), "01:00:59;28");
+        assert_eq!(timecode.label(60.06, rate), "01:01:00;02");
+
+        let pal = Timecode::new(Some("10:00:00:00"), Some("25".parse().unwrap())).unwrap();
+        assert_eq!(pal.apply(1.52), 36001.52);
+        assert!(Timecode::new(Some("01:00:00:00"), None).is_err());
+        assert_eq!(Timecode::new(Some("00:00:10.5"), None).unwrap().offset_secs, 10.5);
+    }
+}
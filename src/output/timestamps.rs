@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Placeholders understood in a timestamp format string
+const PLACEHOLDERS: &[&str] = &["h", "hh", "m", "mm", "M", "s", "ss", "S", "ms"];
+
+/// How text output shows timestamps
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimestampStyle {
+    /// Custom rendering such as `[{hh}:{mm}:{ss}]` or `({M}:{ss})`; the built-in style if unset
+    pub format: Option<TimestampFormat>,
+
+    /// Group segments into paragraphs and stamp only the start of each
+    pub per_paragraph: bool,
+}
+
+impl TimestampStyle {
+    /// Whether the style asks for timestamps even without `--timestamps`
+    pub fn is_custom(&self) -> bool {
+        self.format.is_some() || self.per_paragraph
+    }
+}
+
+/// Validated timestamp format string
+///
+/// `{h}`/`{hh}` hours, `{m}`/`{mm}` minutes of the hour, `{M}` total minutes,
+/// `{s}`/`{ss}` seconds of the minute, `{S}` total seconds and `{ms}` milliseconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimestampFormat(String);
+
+impl TimestampFormat {
+    /// Render a time in seconds with this format
+    pub fn render(&self, seconds: f64) -> String {
+        let total_milliseconds = (seconds.max(0.0) * 1000.0) as u64;
+        let total_seconds = total_milliseconds / 1000;
+
+        let mut output = String::new();
+        let mut rest = self.0.as_str();
+        while let Some(open) = rest.find('{') {
+            output.push_str(&rest[..open]);
+            let close = open + rest[open..].find('}').unwrap_or(0);
+            let value = match &rest[open + 1..close] {
+                "h" => (total_seconds / 3600).to_string(),
+                "hh" => format!("{:02}", total_seconds / 3600),
+                "m" => (total_seconds / 60 % 60).to_string(),
+                "mm" => format!("{:02}", total_seconds / 60 % 60),
+                "M" => (total_seconds / 60).to_string(),
+                "s" => (total_seconds % 60).to_string(),
+                "ss" => format!("{:02}", total_seconds % 60),
+                "S" => total_seconds.to_string(),
+                "ms" => format!("{:03}", total_milliseconds % 1000),
+                _ => unreachable!("placeholders are validated on parse"),
+            };
+            output.push_str(&value);
+            rest = &rest[close + 1..];
+        }
+        output.push_str(rest);
+
+        output
+    }
+}
+
+impl FromStr for TimestampFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        let mut rest = format;
+        let mut placeholders = 0;
+        while let Some(open) = rest.find('{') {
+            let close = rest[open..]
+                .find('}')
+                .map(|offset| open + offset)
+                .ok_or_else(|| format!("unclosed '{{' in timestamp format '{}'", format))?;
+
+            let name = &rest[open + 1..close];
+            if !PLACEHOLDERS.contains(&name) {
+                return Err(format!(
+                    "unknown placeholder '{{{}}}' in timestamp format (use {})",
+                    name,
+                    PLACEHOLDERS.iter().map(|p| format!("{{{}}}", p)).collect::<Vec<_>>().join(", ")
+                ));
+            }
+            placeholders += 1;
+            rest = &rest[close + 1..];
+        }
+
+        if placeholders == 0 {
+            return Err(format!("timestamp format '{}' has no placeholders, e.g. [{{hh}}:{{mm}}:{{ss}}]", format));
+        }
+
+        Ok(Self(format.to_string()))
+    }
+}
+
+impl TryFrom<String> for TimestampFormat {
+    type Error = String;
+
+    fn try_from(format: String) -> Result<Self, Self::Error> {
+        format.parse()
+    }
+}
+
+impl From<TimestampFormat> for String {
+    fn from(format: TimestampFormat) -> Self {
+        format.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(format: &str, seconds: f64) -> String {
+        format.parse::<TimestampFormat>().unwrap().render(seconds)
+    }
+
+    #[test]
+    fn test_render_formats() {
+        assert_eq!(render("[{hh}:{mm}:{ss}]", 3725.0), "[01:02:05]");
+        assert_eq!(render("({M}:{ss})", 3725.0), "(62:05)");
+        assert_eq!(render("{S}s", 3725.4), "3725s");
+        assert_eq!(render("{m}:{ss}.{ms}", 65.25), "1:05.250");
+    }
+
+    #[test]
+    fn test_rejects_invalid_formats() {
+        assert!("[{hours}]".parse::<TimestampFormat>().is_err());
+        assert!("[{hh}:{mm".parse::<TimestampFormat>().is_err());
+        assert!("no placeholders".parse::<TimestampFormat>().is_err());
+    }
+}
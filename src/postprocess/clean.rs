@@ -0,0 +1,192 @@
+use crate::transcribe::TranscriptionResult;
+
+use super::language::{capitalize, core_word};
+
+/// Filler words and phrases per primary language code, matched as whole words
+const FILLERS: &[(&str, &[&str])] = &[
+    ("en", &["um", "umm", "uh", "uhm", "erm", "er", "ah", "hmm", "mm", "you know"]),
+    ("es", &["eh", "em", "ehm", "mmm", "o sea"]),
+    ("fr", &["euh", "heu", "bah", "hum"]),
+    ("de", &["äh", "ähm", "öh", "öhm", "hm", "hmm"]),
+];
+
+/// Fillers used for languages without a list of their own
+const GENERIC_FILLERS: &[&str] = &["um", "uh", "hmm", "mm"];
+
+/// Longest run of words collapsed when it is repeated straight away ("I think I think")
+const MAX_REPEAT_WORDS: usize = 3;
+
+/// Removes fillers, repetitions and false starts so a transcript reads as prose
+#[derive(Debug, Clone)]
+pub struct SpeechCleaner {
+    /// Filler phrases split into lower-case words
+    fillers: Vec<Vec<String>>,
+}
+
+impl SpeechCleaner {
+    /// Use the filler list of a language code such as `en-US` or `de`
+    pub fn for_language(code: &str) -> Self {
+        let primary = code.split(['-', '_']).next().unwrap_or(code).to_lowercase();
+        let phrases = FILLERS
+            .iter()
+            .find(|(language, _)| *language == primary)
+            .map(|(_, phrases)| *phrases)
+            .unwrap_or(GENERIC_FILLERS);
+
+        let mut fillers: Vec<Vec<String>> = phrases
+            .iter()
+            .map(|phrase| phrase.split_whitespace().map(str::to_string).collect())
+            .collect();
+        // Longest first, so "you know" wins over a one-word filler it starts with
+        fillers.sort_by_key(|words| std::cmp::Reverse(words.len()));
+        Self { fillers }
+    }
+
+    /// Clean one piece of text
+    pub fn clean(&self, text: &str) -> String {
+        let tokens: Vec<&str> = text.split_whitespace().collect();
+        let cores: Vec<String> = tokens.iter().map(|token| core_word(token)).collect();
+        let mut keep = vec![true; tokens.len()];
+
+        let mut index = 0;
+        while index < tokens.len() {
+            match self.filler_at(&cores[index..]) {
+                Some(len) => {
+                    keep[index..index + len].fill(false);
+                    index += len;
+                }
+                None => index += 1,
+            }
+        }
+
+        // A word cut off with a dash is a false start ("we- we went")
+        let last_kept = keep.iter().rposition(|&k| k);
+        for index in 0..tokens.len() {
+            if keep[index] && Some(index) != last_kept && is_cut_off(tokens[index]) {
+                keep[index] = false;
+            }
+        }
+
+        // Collapse immediate repeats, keeping the last copy and its punctuation
+        let mut kept: Vec<usize> = Vec::new();
+        for index in (0..tokens.len()).filter(|&i| keep[i] && !cores[i].is_empty()) {
+            kept.push(index);
+            for size in 1..=MAX_REPEAT_WORDS {
+                let len = kept.len();
+                if len < 2 * size {
+                    break;
+                }
+                let repeated =
+                    (0..size).all(|offset| cores[kept[len - 2 * size + offset]] == cores[kept[len - size + offset]]);
+                if repeated {
+                    kept.drain(len - 2 * size..len - size);
+                    break;
+                }
+            }
+        }
+        keep.fill(false);
+        for &index in &kept {
+            keep[index] = true;
+        }
+
+        rebuild(&tokens, &keep)
+    }
+
+    /// Words of the filler phrase starting at the first of `cores`, if one does
+    fn filler_at(&self, cores: &[String]) -> Option<usize> {
+        self.fillers
+            .iter()
+            .find(|words| words.len() <= cores.len() && words.iter().zip(cores).all(|(word, core)| word == core))
+            .map(Vec::len)
+    }
+}
+
+/// Clean segment texts and the transcript of a result, keeping the original text on changed segments
+///
+/// Segments left without words are dropped; word timings stay verbatim.
+pub fn clean_speech(result: &mut TranscriptionResult) {
+    let language = result.metadata.language.clone();
+    let cleaner = SpeechCleaner::for_language(&language);
+
+    for segment in &mut result.segments {
+        let cleaned = match segment.language.as_deref() {
+            Some(code) if code != language => SpeechCleaner::for_language(code).clean(&segment.text),
+            _ => cleaner.clean(&segment.text),
+        };
+        if cleaned != segment.text.trim() {
+            segment.verbatim = Some(std::mem::replace(&mut segment.text, cleaned));
+        }
+    }
+    result.segments.retain(|segment| !segment.text.is_empty());
+    result.transcript = cleaner.clean(&result.transcript);
+}
+
+/// Whether a token is a word broken off with a trailing dash
+fn is_cut_off(token: &str) -> bool {
+    token.ends_with(['-', '—', '–']) && !core_word(token).is_empty()
+}
+
+/// Join the kept tokens, moving sentence punctuation and capitals off removed ones
+fn rebuild(tokens: &[&str], keep: &[bool]) -> String {
+    let mut words: Vec<String> = Vec::new();
+    let mut removed: Vec<&str> = Vec::new();
+
+    for (token, &kept) in tokens.iter().zip(keep) {
+        if !kept {
+            removed.push(token);
+            continue;
+        }
+        let mut word = token.to_string();
+        if removed.first().is_some_and(|first| starts_upper(first)) {
+            word = capitalize(&word);
+        }
+        if let Some(previous) = words.last_mut() {
+            carry_punctuation(previous, &removed);
+        }
+        removed.clear();
+        words.push(word);
+    }
+    if let Some(previous) = words.last_mut() {
+        carry_punctuation(previous, &removed);
+    }
+
+    words.join(" ")
+}
+
+/// Fix the punctuation of `previous` for the removed run after it
+///
+/// A run ending a sentence passes its stop on; a run set off by commas takes the comma before it along.
+fn carry_punctuation(previous: &mut String, removed: &[&str]) {
+    let Some(last) = removed.last().and_then(|last| last.chars().last()) else {
+        return;
+    };
+    let ends_sentence = matches!(last, '.' | '?' | '!' | '…');
+    if ends_sentence && !previous.ends_with(['.', '?', '!', '…']) {
+        let trimmed = previous.trim_end_matches([',', ';', ':']).len();
+        previous.truncate(trimmed);
+        previous.push(last);
+    } else if last == ',' && previous.ends_with(',') {
+        previous.pop();
+    }
+}
+
+fn starts_upper(token: &str) -> bool {
+    token.chars().find(|c| c.is_alphabetic()).is_some_and(char::is_uppercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean() {
+        let english = SpeechCleaner::for_language("en-US");
+        assert_eq!(english.clean("Um, so we- we went to the, uh, the store."), "So we went to the store.");
+        assert_eq!(english.clean("I think I think it works, you know."), "I think it works.");
+        assert_eq!(english.clean("Uh."), "");
+
+        let german = SpeechCleaner::for_language("de-DE");
+        assert_eq!(german.clean("Das ist, äh, gut."), "Das ist gut.");
+        assert_eq!(german.clean("Well, um, yes."), "Well, um, yes.");
+    }
+}
@@ -0,0 +1,214 @@
+use crate::transcribe::TranscriptionResult;
+
+/// Noun endings that are reliable enough to capitalise German words by
+const GERMAN_NOUN_SUFFIXES: &[&str] = &["ung", "heit", "keit", "schaft", "tion", "ität", "ismus", "nis"];
+
+/// Narrow no-break space used in French typography before `? ! ; :`
+const NARROW_NBSP: char = '\u{202F}';
+
+/// Punctuation and casing conventions of a language
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LanguageRules {
+    English,
+    Spanish,
+    German,
+    French,
+    /// Only sentence-initial capitalisation
+    Generic,
+}
+
+impl LanguageRules {
+    /// Pick rules from a language code such as `es-ES`, `de` or `fr-CA`
+    pub fn for_language(code: &str) -> Self {
+        let primary = code.split(['-', '_']).next().unwrap_or(code).to_lowercase();
+        match primary.as_str() {
+            "en" => Self::English,
+            "es" => Self::Spanish,
+            "de" => Self::German,
+            "fr" => Self::French,
+            _ => Self::Generic,
+        }
+    }
+
+    /// Apply the rules to the transcript and segment texts of a result
+    pub fn apply(&self, result: &mut TranscriptionResult) {
+        let mut segment_texts: Vec<String> = result.segments.iter().map(|s| s.text.clone()).collect();
+        self.apply_to_texts(&mut segment_texts);
+        for (segment, text) in result.segments.iter_mut().zip(segment_texts) {
+            segment.text = text;
+        }
+
+        let mut transcript = vec![std::mem::take(&mut result.transcript)];
+        self.apply_to_texts(&mut transcript);
+        result.transcript = transcript.remove(0);
+    }
+
+    /// Apply the rules to consecutive pieces of text, where sentences may span pieces
+    pub fn apply_to_texts(&self, texts: &mut [String]) {
+        // (piece index, token) for every whitespace-separated token
+        let mut tokens: Vec<(usize, String)> = texts
+            .iter()
+            .enumerate()
+            .flat_map(|(i, text)| text.split_whitespace().map(move |t| (i, t.to_string())))
+            .collect();
+
+        let mut sentence_start = 0;
+        for index in 0..tokens.len() {
+            let is_start = index == sentence_start;
+            let ends_sentence = ends_sentence(&tokens[index].1);
+
+            let token = &mut tokens[index].1;
+            if is_start {
+                *token = capitalize(token);
+            } else {
+                self.fix_casing(token);
+            }
+            if *self == Self::French {
+                *token = french_spacing(token);
+            }
+
+            if ends_sentence {
+                if *self == Self::Spanish {
+                    invert_punctuation(&mut tokens[sentence_start..=index]);
+                }
+                sentence_start = index + 1;
+            }
+        }
+
+        let mut rebuilt = vec![Vec::new(); texts.len()];
+        for (piece, token) in tokens {
+            rebuilt[piece].push(token);
+        }
+        for (text, words) in texts.iter_mut().zip(rebuilt) {
+            *text = words.join(" ");
+        }
+    }
+
+    /// Casing fixes for words inside a sentence
+    fn fix_casing(&self, token: &mut String) {
+        match self {
+            Self::English => {
+                let core = core_word(token);
+                if core == "i" || core.starts_with("i'") {
+                    *token = capitalize(token);
+                }
+            }
+            Self::German => {
+                let core = core_word(token);
+                let is_noun = core.chars().count() > 5
+                    && core.chars().next().is_some_and(char::is_lowercase)
+                    && GERMAN_NOUN_SUFFIXES.iter().any(|suffix| core.ends_with(suffix));
+                if is_noun {
+                    *token = capitalize(token);
+                }
+            }
+            Self::Spanish | Self::French | Self::Generic => {}
+        }
+    }
+}
+
+/// Apply the rules matching the result's language
+pub fn apply_language_rules(result: &mut TranscriptionResult) {
+    LanguageRules::for_language(&result.metadata.language).apply(result);
+}
+
+/// Whether a token closes a sentence
+fn ends_sentence(token: &str) -> bool {
+    let trimmed = token.trim_end_matches(['"', '\'', '»', ')', NARROW_NBSP]);
+    trimmed.ends_with(['.', '?', '!', '…'])
+}
+
+/// Upper-case the first letter, skipping leading punctuation
+pub(super) fn capitalize(token: &str) -> String {
+    let mut result = String::with_capacity(token.len());
+    let mut done = false;
+    for c in token.chars() {
+        if !done && c.is_alphabetic() {
+            result.extend(c.to_uppercase());
+            done = true;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// The word without surrounding punctuation, lower-cased
+pub(super) fn core_word(token: &str) -> String {
+    token
+        .trim_matches(|c: char| !c.is_alphanumeric() && c != '\'')
+        .to_lowercase()
+}
+
+/// Add opening `¿`/`¡` to Spanish questions and exclamations
+fn invert_punctuation(sentence: &mut [(usize, String)]) {
+    let Some(last) = sentence.last().map(|(_, t)| t.clone()) else {
+        return;
+    };
+
+    let opening = if last.contains('?') {
+        '¿'
+    } else if last.contains('!') {
+        '¡'
+    } else {
+        return;
+    };
+
+    let first = &mut sentence[0].1;
+    if !first.starts_with(opening) {
+        first.insert(0, opening);
+    }
+}
+
+/// Put a narrow no-break space before French high punctuation
+fn french_spacing(token: &str) -> String {
+    let body = token.trim_end_matches(['?', '!', ';', ':']);
+    if body.len() == token.len() || body.is_empty() || body.ends_with(NARROW_NBSP) {
+        return token.to_string();
+    }
+
+    format!("{}{}{}", body, NARROW_NBSP, &token[body.len()..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(rules: LanguageRules, texts: &[&str]) -> Vec<String> {
+        let mut texts: Vec<String> = texts.iter().map(|t| t.to_string()).collect();
+        rules.apply_to_texts(&mut texts);
+        texts
+    }
+
+    #[test]
+    fn test_for_language() {
+        assert_eq!(LanguageRules::for_language("es-MX"), LanguageRules::Spanish);
+        assert_eq!(LanguageRules::for_language("de"), LanguageRules::German);
+        assert_eq!(LanguageRules::for_language("ja-JP"), LanguageRules::Generic);
+    }
+
+    #[test]
+    fn test_spanish_inverted_punctuation_across_segments() {
+        assert_eq!(
+            apply(LanguageRules::Spanish, &["hola. cómo", "estás? qué bien!"]),
+            vec!["Hola. ¿Cómo", "estás? ¡Qué bien!"]
+        );
+    }
+
+    #[test]
+    fn test_german_noun_capitalization() {
+        assert_eq!(
+            apply(LanguageRules::German, &["die zeitung berichtet über die freiheit."]),
+            vec!["Die Zeitung berichtet über die Freiheit."]
+        );
+    }
+
+    #[test]
+    fn test_english_and_french() {
+        assert_eq!(apply(LanguageRules::English, &["yes i'm sure i did"]), vec!["Yes I'm sure I did"]);
+        assert_eq!(
+            apply(LanguageRules::French, &["vraiment? oui: bien sûr!"]),
+            vec![format!("Vraiment{}? Oui{}: bien sûr{}!", NARROW_NBSP, NARROW_NBSP, NARROW_NBSP)]
+        );
+    }
+}
@@ -0,0 +1,8 @@
+//! Post-processing passes applied to a finished transcription
+
+pub mod clean;
+pub mod language;
+pub mod paragraphs;
+pub mod replace;
+
+pub use replace::{ReplacementRule, RuleSet};
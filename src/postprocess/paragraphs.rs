@@ -0,0 +1,112 @@
+use crate::transcribe::TranscriptSegment;
+use crate::utils::text;
+
+/// Pause that always starts a new paragraph
+pub const PARAGRAPH_PAUSE_SECS: f64 = 2.0;
+
+/// Sentences after which a paragraph ends at the next sentence boundary
+const MAX_SENTENCES: usize = 5;
+
+/// Characters after which a paragraph ends at the next sentence boundary
+const MAX_CHARS: usize = 600;
+
+/// Group consecutive segments into paragraphs
+///
+/// A paragraph ends when the speaker changes, after a long pause, and before any of
+/// `boundaries` (such as chapter starts). Long monologues are also split, but only
+/// where a sentence ends, so paragraphs never break mid-sentence.
+pub fn group_paragraphs<'a>(segments: &'a [TranscriptSegment], boundaries: &[f64]) -> Vec<&'a [TranscriptSegment]> {
+    let mut paragraphs = Vec::new();
+    let mut start = 0;
+    let mut sentences = 0;
+    let mut chars = 0;
+
+    for (index, segment) in segments.iter().enumerate() {
+        if index > start {
+            let previous = &segments[index - 1];
+            let crosses_boundary = boundaries
+                .iter()
+                .any(|&time| time > previous.start_time && time <= segment.start_time);
+            let long_enough = sentences >= MAX_SENTENCES || chars >= MAX_CHARS;
+            let breaks = previous.speaker_id != segment.speaker_id
+                || segment.start_time - previous.end_time >= PARAGRAPH_PAUSE_SECS
+                || crosses_boundary
+                || (long_enough && ends_sentence(&previous.text));
+            if breaks {
+                paragraphs.push(&segments[start..index]);
+                start = index;
+                sentences = 0;
+                chars = 0;
+            }
+        }
+        sentences += count_sentences(&segment.text);
+        chars += segment.text.chars().count();
+    }
+    if start < segments.len() {
+        paragraphs.push(&segments[start..]);
+    }
+
+    paragraphs
+}
+
+/// Text of a paragraph's segments, joined as running prose
+pub fn paragraph_text(segments: &[TranscriptSegment]) -> String {
+    let mut joined = String::new();
+    for segment in segments {
+        text::push_token(&mut joined, segment.text.trim());
+    }
+    joined
+}
+
+/// Whether text ends with sentence-final punctuation
+fn ends_sentence(text: &str) -> bool {
+    text.trim_end().trim_end_matches(['"', '\'', '»', ')']).ends_with(['.', '?', '!', '…', '。', '？', '！'])
+}
+
+fn count_sentences(text: &str) -> usize {
+    text.split_whitespace().filter(|token| ends_sentence(token)).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(start_time: f64, speaker: &str, text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            start_time,
+            end_time: start_time + 1.0,
+            text: text.to_string(),
+            confidence: None,
+            speaker_id: Some(speaker.to_string()),
+            toxicity: None,
+            language: None,
+            verbatim: None,
+        }
+    }
+
+    #[test]
+    fn test_group_paragraphs() {
+        let mut segments = vec![
+            segment(0.0, "1", "Welcome back."),
+            segment(1.0, "1", "Today we look at"),
+            segment(2.0, "2", "paragraphs."),
+            // Pause of three seconds
+            segment(6.0, "2", "They help."),
+            segment(7.0, "2", "A chapter starts here."),
+        ];
+        for n in 0..6 {
+            segments.push(segment(8.0 + n as f64, "2", &format!("Sentence {} of a long turn", n)));
+            segments.push(segment(8.5 + n as f64, "2", "ends here."));
+        }
+
+        let paragraphs = group_paragraphs(&segments, &[7.0]);
+        let texts: Vec<String> = paragraphs.iter().map(|p| paragraph_text(p)).collect();
+        assert_eq!(texts[0], "Welcome back. Today we look at");
+        assert_eq!(texts[1], "paragraphs.");
+        assert_eq!(texts[2], "They help.");
+        assert!(texts[3].starts_with("A chapter starts here. Sentence 0"));
+        // Split after the fifth sentence, at a sentence end
+        assert!(texts[3].ends_with("Sentence 3 of a long turn ends here."));
+        assert_eq!(texts[4], "Sentence 4 of a long turn ends here. Sentence 5 of a long turn ends here.");
+    }
+}
@@ -0,0 +1,250 @@
+use anyhow::{Context, Result};
+use regex::{NoExpand, Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::output::formatters::WordTimestamp;
+use crate::transcribe::TranscriptionResult;
+
+/// A find/replace correction applied to transcribed text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplacementRule {
+    /// Text to look for (matched on word boundaries)
+    pub find: String,
+    
+    /// Replacement text
+    pub replace: String,
+    
+    /// Match case exactly (case-insensitive by default)
+    #[serde(default)]
+    pub case_sensitive: bool,
+    
+    /// `find` is a regular expression and `replace` may use its groups (`$1`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub regex: bool,
+}
+
+/// A compiled set of replacement rules
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    regex: Regex,
+    replace: String,
+    expand: bool,
+}
+
+impl Rule {
+    fn replace_all(&self, text: &str) -> String {
+        if self.expand {
+            self.regex.replace_all(text, self.replace.as_str()).into_owned()
+        } else {
+            self.regex.replace_all(text, NoExpand(&self.replace)).into_owned()
+        }
+    }
+}
+
+impl RuleSet {
+    /// Compile rules into matchers
+    pub fn compile(rules: &[ReplacementRule]) -> Result<Self> {
+        let rules = rules
+            .iter()
+            .filter(|rule| !rule.find.trim().is_empty())
+            .map(|rule| {
+                let pattern = if rule.regex { rule.find.clone() } else { word_bounded_pattern(&rule.find) };
+                let regex = RegexBuilder::new(&pattern)
+                    .case_insensitive(!rule.case_sensitive)
+                    .build()
+                    .with_context(|| format!("Invalid replacement rule: {}", rule.find))?;
+                Ok(Rule { regex, replace: rule.replace.clone(), expand: rule.regex })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        
+        Ok(Self { rules })
+    }
+    
+    /// Whether the rule set contains no rules
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+    
+    /// Apply all rules to a piece of text
+    pub fn apply_to_text(&self, text: &str) -> String {
+        self.rules
+            .iter()
+            .fold(text.to_string(), |text, rule| rule.replace_all(&text))
+    }
+    
+    /// Apply all rules to a run of timed words
+    ///
+    /// A match spanning several words becomes one word timed from the first to the last,
+    /// so the words keep lining up with the corrected text.
+    pub fn apply_to_words(&self, words: &mut Vec<WordTimestamp>) {
+        for rule in &self.rules {
+            let mut joined = String::new();
+            let mut spans = Vec::with_capacity(words.len());
+            for word in words.iter() {
+                if !joined.is_empty() {
+                    joined.push(' ');
+                }
+                spans.push((joined.len(), joined.len() + word.word.len()));
+                joined.push_str(&word.word);
+            }
+            
+            let matches: Vec<(usize, usize)> = rule.regex.find_iter(&joined).map(|m| (m.start(), m.end())).collect();
+            // Back to front, so earlier indices stay valid
+            for (start, end) in matches.into_iter().rev() {
+                let first = spans.iter().position(|&(_, word_end)| word_end > start);
+                let last = spans.iter().rposition(|&(word_start, _)| word_start < end.max(start + 1));
+                let (Some(first), Some(last)) = (first, last) else {
+                    continue;
+                };
+                if first > last {
+                    continue;
+                }
+                
+                let (first_start, _) = spans[first];
+                let (_, last_end) = spans[last];
+                let replaced = rule.replace_all(&joined[first_start..last_end]);
+                let end_time = words[last].end_time;
+                let confidence = words[first..=last].iter().filter_map(|w| w.confidence).reduce(f64::min);
+                words.drain(first + 1..=last);
+                spans.drain(first + 1..=last);
+                
+                let word = &mut words[first];
+                word.end_time = end_time;
+                word.confidence = confidence;
+                // They were candidates for the text the rule replaced
+                word.alternatives.clear();
+                if replaced.trim().is_empty() {
+                    words.remove(first);
+                    spans.remove(first);
+                } else {
+                    word.word = replaced.trim().to_string();
+                }
+            }
+        }
+    }
+    
+    /// Apply all rules to the transcript, its segments and individual words
+    pub fn apply(&self, result: &mut TranscriptionResult) {
+        if self.is_empty() {
+            return;
+        }
+        
+        result.transcript = self.apply_to_text(&result.transcript);
+        for segment in &mut result.segments {
+            segment.text = self.apply_to_text(&segment.text);
+        }
+        if let Some(words) = &mut result.words {
+            self.apply_to_words(words);
+        }
+    }
+}
+
+/// Read a corrections file
+///
+/// YAML files (`.yaml`/`.yml`) hold a list of rules. Any other file holds one
+/// `find => replace` pair per line; `/pattern/ => replace` makes a regex rule and
+/// lines starting with `#` are comments.
+pub fn load_corrections(path: &Path) -> Result<Vec<ReplacementRule>> {
+    let content = fs_err::read_to_string(path)?;
+    
+    let is_yaml = path.extension().and_then(|ext| ext.to_str()).is_some_and(|ext| matches!(ext, "yaml" | "yml"));
+    if is_yaml {
+        return serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse corrections file: {}", path.display()));
+    }
+    
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(index, line)| {
+            let (find, replace) = line
+                .split_once("=>")
+                .with_context(|| format!("{}:{}: expected `find => replace`", path.display(), index + 1))?;
+            let find = find.trim();
+            let regex = find.len() > 1 && find.starts_with('/') && find.ends_with('/');
+            Ok(ReplacementRule {
+                find: if regex { find[1..find.len() - 1].to_string() } else { find.to_string() },
+                replace: replace.trim().to_string(),
+                case_sensitive: false,
+                regex,
+            })
+        })
+        .collect()
+}
+
+/// Build a pattern that only matches whole words at the edges of `find`
+fn word_bounded_pattern(find: &str) -> String {
+    let escaped = regex::escape(find.trim());
+    let starts_with_word = find.trim().chars().next().is_some_and(|c| c.is_alphanumeric());
+    let ends_with_word = find.trim().chars().last().is_some_and(|c| c.is_alphanumeric());
+    
+    format!(
+        "{}{}{}",
+        if starts_with_word { r"\b" } else { "" },
+        escaped,
+        if ends_with_word { r"\b" } else { "" }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn rule(find: &str, replace: &str) -> ReplacementRule {
+        ReplacementRule {
+            find: find.to_string(),
+            replace: replace.to_string(),
+            case_sensitive: false,
+            regex: false,
+        }
+    }
+    
+    #[test]
+    fn test_replaces_whole_words_only() {
+        let rules = RuleSet::compile(&[rule("your honor", "Your Honor"), rule("cat", "dog")]).unwrap();
+        
+        assert_eq!(
+            rules.apply_to_text("yes YOUR HONOR, the cat sat on a catalog"),
+            "yes Your Honor, the dog sat on a catalog"
+        );
+    }
+    
+    #[test]
+    fn test_replacement_is_literal() {
+        let rules = RuleSet::compile(&[rule("dollars", "$1")]).unwrap();
+        
+        assert_eq!(rules.apply_to_text("ten dollars"), "ten $1");
+    }
+    
+    #[test]
+    fn test_corrections_file_keeps_words_aligned() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("corrections.txt");
+        let corrections = "# misheard product names\nrust scribe => RustScribe\n/v(\\d+) point (\\d+)/ => v$1.$2\n";
+        fs_err::write(&path, corrections).unwrap();
+        let rules = RuleSet::compile(&load_corrections(&path).unwrap()).unwrap();
+        
+        let word = |word: &str, start_time: f64| WordTimestamp {
+            word: word.to_string(),
+            start_time,
+            end_time: start_time + 0.5,
+            confidence: Some(0.9),
+            speaker_id: None,
+            alternatives: Vec::new(),
+        };
+        let mut words =
+            vec![word("try", 0.0), word("rust", 1.0), word("scribe", 1.5), word("v2", 2.0), word("point", 2.5), word("1.", 3.0)];
+        rules.apply_to_words(&mut words);
+        
+        let timed: Vec<(&str, f64, f64)> = words.iter().map(|w| (w.word.as_str(), w.start_time, w.end_time)).collect();
+        assert_eq!(timed, [("try", 0.0, 0.5), ("RustScribe", 1.0, 2.0), ("v2.1.", 2.0, 3.5)]);
+        assert_eq!(rules.apply_to_text("try rust scribe v2 point 1."), "try RustScribe v2.1.");
+    }
+}
@@ -0,0 +1,301 @@
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressFinish, ProgressStyle};
+use std::borrow::Cow;
+use std::future::Future;
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Where progress goes outside [`report_to`]; the CLI sets it to the terminal
+static DEFAULT_SINK: OnceLock<Arc<dyn ProgressSink>> = OnceLock::new();
+
+tokio::task_local! {
+    /// Where the current task's progress goes, see [`report_to`]
+    static SINK: Arc<dyn ProgressSink>;
+
+    /// Label of the batch input the current task works on, shown in front of its bars
+    static INPUT: String;
+}
+
+/// Where the progress of a run is shown: bars on the terminal, or a `serve` job's events
+pub trait ProgressSink: Send + Sync {
+    /// Take on a stage's bar, already styled, and return the one to update
+    fn add(&self, bar: ProgressBar) -> ProgressBar;
+
+    /// The stage's message changed
+    fn message(&self, _message: &str) {}
+
+    /// Print to the console without tearing the bars
+    fn suspend(&self, print: &mut dyn FnMut()) {
+        print()
+    }
+}
+
+/// Send progress outside [`report_to`] to `sink`; only the first call has an effect
+pub fn set_default(sink: Arc<dyn ProgressSink>) {
+    let _ = DEFAULT_SINK.set(sink);
+}
+
+/// Run `future`, sending the progress it shows to `sink`
+///
+/// A pipeline shared by several `serve` jobs reports each job's progress to that job.
+pub async fn report_to<F: Future>(sink: Arc<dyn ProgressSink>, future: F) -> F::Output {
+    SINK.scope(sink, future).await
+}
+
+fn current() -> Arc<dyn ProgressSink> {
+    SINK.try_with(Arc::clone)
+        .ok()
+        .or_else(|| DEFAULT_SINK.get().cloned())
+        .unwrap_or_else(|| Arc::new(Hidden))
+}
+
+/// Show a stage's bar wherever the current task's progress goes
+pub fn add(bar: ProgressBar) -> Progress {
+    let sink = current();
+    Progress {
+        bar: sink.add(bar.with_finish(ProgressFinish::AndClear)),
+        sink,
+    }
+}
+
+/// Run one input of a batch, labelling the bars it shows with `label`
+pub async fn input<F: Future>(label: String, run: F) -> F::Output {
+    INPUT.scope(label, run).await
+}
+
+/// Print to the console without tearing the bars
+pub fn suspend(print: impl FnOnce()) {
+    let mut print = Some(print);
+    current().suspend(&mut || {
+        if let Some(print) = print.take() {
+            print()
+        }
+    });
+}
+
+/// A stage's spinner or bar, shown by the sink it was added to
+///
+/// A bar dropped without being finished, because its stage failed, is removed instead of
+/// left half-drawn.
+pub struct Progress {
+    bar: ProgressBar,
+    sink: Arc<dyn ProgressSink>,
+}
+
+impl Progress {
+    pub fn set_message(&self, message: impl Into<Cow<'static, str>>) {
+        let message = message.into();
+        self.sink.message(&message);
+        self.bar.set_message(message);
+    }
+
+    pub fn enable_steady_tick(&self, interval: Duration) {
+        self.bar.enable_steady_tick(interval);
+    }
+
+    pub fn set_length(&self, length: u64) {
+        self.bar.set_length(length);
+    }
+
+    pub fn set_position(&self, position: u64) {
+        self.bar.set_position(position);
+    }
+
+    pub fn finish_with_message(&self, message: impl Into<Cow<'static, str>>) {
+        let message = message.into();
+        self.sink.message(&message);
+        self.bar.finish_with_message(message);
+    }
+
+    pub fn finish_and_clear(&self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+/// Progress nobody sees, for library use and tests
+struct Hidden;
+
+impl ProgressSink for Hidden {
+    fn add(&self, bar: ProgressBar) -> ProgressBar {
+        bar.set_draw_target(ProgressDrawTarget::hidden());
+        bar
+    }
+}
+
+/// Bars on the terminal, drawn together so concurrent stages stack instead of overwriting each other
+#[derive(Clone)]
+pub struct Terminal {
+    bars: MultiProgress,
+    /// The batch's overall bar, kept below the per-input bars
+    overall: Arc<Mutex<Option<ProgressBar>>>,
+}
+
+impl Terminal {
+    pub fn new() -> Self {
+        Self {
+            bars: MultiProgress::new(),
+            overall: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Log writer that prints each event above the bars
+    pub fn log_writer(&self) -> LogWriter {
+        LogWriter(self.bars.clone())
+    }
+
+    /// Show the overall progress of a batch of `inputs` below the inputs' bars
+    pub fn batch(&self, inputs: u64) -> BatchProgress {
+        let bar = self.bars.add(ProgressBar::new(inputs).with_finish(ProgressFinish::AndClear));
+        bar.set_style(
+            ProgressStyle::default_bar()
+                .template("[{elapsed_precise}] [{bar:40.green/white}] {pos}/{len} inputs done {msg}")
+                .unwrap(),
+        );
+        bar.tick();
+        if let Ok(mut overall) = self.overall.lock() {
+            *overall = Some(bar.clone());
+        }
+        BatchProgress {
+            bar,
+            failed: AtomicU64::new(0),
+            overall: self.overall.clone(),
+        }
+    }
+}
+
+impl Default for Terminal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressSink for Terminal {
+    /// Within [`input`] the bar is labelled with the input
+    fn add(&self, bar: ProgressBar) -> ProgressBar {
+        if let Ok(label) = INPUT.try_with(|label| format!("{} ", label)) {
+            bar.set_prefix(label);
+        }
+
+        match self.overall.lock().ok().and_then(|overall| overall.clone()) {
+            Some(overall) => self.bars.insert_before(&overall, bar),
+            None => self.bars.add(bar),
+        }
+    }
+
+    fn suspend(&self, print: &mut dyn FnMut()) {
+        self.bars.suspend(print)
+    }
+}
+
+/// Overall progress of a batch, shown below the inputs' bars until it is dropped
+pub struct BatchProgress {
+    bar: ProgressBar,
+    failed: AtomicU64,
+    overall: Arc<Mutex<Option<ProgressBar>>>,
+}
+
+impl BatchProgress {
+    /// Count a finished input, noting failures in the bar
+    pub fn input_done(&self, failed: bool) {
+        if failed {
+            let failures = self.failed.fetch_add(1, Ordering::Relaxed) + 1;
+            self.bar.set_message(format!("({} failed)", failures));
+        }
+        self.bar.inc(1);
+    }
+}
+
+impl Drop for BatchProgress {
+    fn drop(&mut self) {
+        if let Ok(mut overall) = self.overall.lock() {
+            *overall = None;
+        }
+    }
+}
+
+/// Log writer that prints each event above a terminal's bars
+pub struct LogWriter(MultiProgress);
+
+impl<'a> MakeWriter<'a> for LogWriter {
+    type Writer = LogLine;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        LogLine(Vec::new(), self.0.clone())
+    }
+}
+
+/// One formatted event, printed when the formatter is done with it
+pub struct LogLine(Vec<u8>, MultiProgress);
+
+impl Write for LogLine {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for LogLine {
+    fn drop(&mut self) {
+        self.1.suspend(|| {
+            let _ = std::io::stdout().write_all(&self.0);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Remembers the messages it is told about
+    #[derive(Default)]
+    struct Messages(Mutex<Vec<String>>);
+
+    impl ProgressSink for Messages {
+        fn add(&self, bar: ProgressBar) -> ProgressBar {
+            Hidden.add(bar)
+        }
+
+        fn message(&self, message: &str) {
+            self.0.lock().unwrap().push(message.to_string());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_input_labels_and_failures() {
+        let terminal = Terminal::new();
+        let batch = terminal.batch(3);
+
+        let bar = input("[2/3]".to_string(), async { terminal.add(ProgressBar::hidden()) }).await;
+        assert_eq!(bar.prefix(), "[2/3] ");
+        assert_eq!(terminal.add(ProgressBar::hidden()).prefix(), "");
+
+        batch.input_done(true);
+        batch.input_done(false);
+        batch.input_done(true);
+        assert_eq!(batch.bar.position(), 3);
+        assert_eq!(batch.bar.message(), "(2 failed)");
+
+        drop(batch);
+        assert!(terminal.overall.lock().unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_report_to() {
+        let sink = Arc::new(Messages::default());
+        report_to(sink.clone(), async {
+            let progress = add(ProgressBar::new_spinner());
+            progress.set_message("Downloading audio...");
+            progress.finish_with_message("Download complete");
+        })
+        .await;
+        add(ProgressBar::new_spinner()).set_message("Elsewhere");
+
+        assert_eq!(*sink.0.lock().unwrap(), ["Downloading audio...", "Download complete"]);
+    }
+}
@@ -0,0 +1,126 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::io::Write;
+use std::path::Path;
+
+use crate::transcribe::{TranscriptSegment, TranscriptionResult};
+
+/// Audio played before and after a segment, so its first and last words are not clipped
+const PADDING_SECS: f64 = 0.3;
+
+/// Outcome of a QA review: how many sampled segments the reviewer judged correct
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct QaScore {
+    pub reviewed: usize,
+    pub correct: usize,
+}
+
+impl QaScore {
+    /// Share of reviewed segments that were correct
+    pub fn accuracy(&self) -> Option<f64> {
+        (self.reviewed > 0).then(|| self.correct as f64 / self.reviewed as f64)
+    }
+
+    /// Half-width of the 95% confidence interval around the accuracy
+    pub fn margin(&self) -> Option<f64> {
+        let accuracy = self.accuracy()?;
+        Some(1.96 * (accuracy * (1.0 - accuracy) / self.reviewed as f64).sqrt())
+    }
+}
+
+/// Up to `count` randomly chosen segments with text, in transcript order
+pub fn sample_segments<'a>(result: &'a TranscriptionResult, count: usize, rng: &mut fastrand::Rng) -> Vec<&'a TranscriptSegment> {
+    let mut candidates: Vec<&TranscriptSegment> = result
+        .segments
+        .iter()
+        .filter(|segment| !segment.text.trim().is_empty())
+        .collect();
+    rng.shuffle(&mut candidates);
+    candidates.truncate(count);
+    candidates.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+    candidates
+}
+
+/// Play each sampled segment and ask the reviewer whether its text is right
+///
+/// Quitting early keeps the answers given so far.
+pub async fn review(segments: &[&TranscriptSegment], audio: &Path) -> Result<QaScore> {
+    let mut score = QaScore::default();
+
+    for (index, segment) in segments.iter().enumerate() {
+        println!();
+        println!(
+            "[{}/{}] {} - {}{}",
+            index + 1,
+            segments.len(),
+            crate::utils::format_duration(segment.start_time),
+            crate::utils::format_duration(segment.end_time),
+            segment.speaker_id.as_deref().map(|speaker| format!("  {}", speaker)).unwrap_or_default()
+        );
+        println!("  {}", segment.text.trim());
+
+        loop {
+            play(audio, segment.start_time, segment.end_time).await?;
+            print!("  Correct? [y]es / [n]o / [r]eplay / [s]kip / [q]uit: ");
+            std::io::stdout().flush()?;
+
+            let mut answer = String::new();
+            if std::io::stdin().read_line(&mut answer)? == 0 {
+                return Ok(score);
+            }
+            match answer.trim().to_lowercase().as_str() {
+                "y" | "yes" => {
+                    score.reviewed += 1;
+                    score.correct += 1;
+                }
+                "n" | "no" => score.reviewed += 1,
+                "s" | "skip" => {}
+                "q" | "quit" => return Ok(score),
+                _ => continue,
+            }
+            break;
+        }
+    }
+
+    Ok(score)
+}
+
+/// Play a stretch of an audio file with ffplay
+async fn play(audio: &Path, start: f64, end: f64) -> Result<()> {
+    let start = (start - PADDING_SECS).max(0.0);
+    let length = end + PADDING_SECS - start;
+
+    let output = tokio::process::Command::new("ffplay")
+        .args(["-nodisp", "-autoexit", "-hide_banner", "-loglevel", "error"])
+        .args(["-ss", &format!("{:.3}", start), "-t", &format!("{:.3}", length)])
+        .arg(audio)
+        .output()
+        .await
+        .context("Failed to run ffplay (it comes with ffmpeg)")?;
+
+    crate::logs::check("ffplay", &output, "Failed to play the segment")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_segments_and_score() {
+        let mut result = crate::demo::result("demo");
+        result.segments.truncate(4);
+        result.segments[1].text = " ".to_string();
+
+        let mut rng = fastrand::Rng::with_seed(7);
+        let sample = sample_segments(&result, 2, &mut rng);
+        assert_eq!(sample.len(), 2);
+        assert!(sample.iter().all(|segment| !segment.text.trim().is_empty()));
+        assert!(sample[0].start_time < sample[1].start_time);
+        assert_eq!(sample_segments(&result, 10, &mut rng).len(), 3);
+
+        let score = QaScore { reviewed: 20, correct: 18 };
+        assert_eq!(score.accuracy(), Some(0.9));
+        assert!((score.margin().unwrap() - 0.1315).abs() < 1e-3);
+        assert_eq!(QaScore::default().accuracy(), None);
+    }
+}
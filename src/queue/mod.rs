@@ -0,0 +1,278 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::cli::OutputFormat;
+use crate::config::Config;
+use crate::domain::DomainPack;
+use crate::extractors::AudioInfo;
+use crate::output::{ExistingOutput, RenderOptions};
+use crate::postprocess::{replace, RuleSet};
+use crate::transcribe::{TranscribeOptions, TranscriptionResult};
+
+/// Where and how a queued run writes its output once transcribed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputTarget {
+    /// Output file (console if not set)
+    pub path: Option<PathBuf>,
+
+    /// Output format
+    pub format: OutputFormat,
+
+    /// Further formats saved next to `path` (`--format srt,json`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_formats: Vec<OutputFormat>,
+
+    /// Domain pack whose corrections are applied to the result
+    pub domain: Option<String>,
+
+    /// Corrections file applied after the domain pack's
+    #[serde(default)]
+    pub corrections: Option<PathBuf>,
+
+    /// Timestamp, timecode and JSON settings
+    #[serde(flatten)]
+    pub render: RenderOptions,
+
+    /// What happens when the output file already exists
+    #[serde(default)]
+    pub existing: ExistingOutput,
+}
+
+impl OutputTarget {
+    /// Where each extra format is saved: next to the output, named after it
+    /// (`talk.srt` with json becomes `talk.json`)
+    pub fn extra_outputs(&self) -> Vec<(PathBuf, &OutputFormat)> {
+        let Some(path) = &self.path else {
+            return Vec::new();
+        };
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        self.extra_formats
+            .iter()
+            .map(|format| (path.with_file_name(format!("{}.{}", stem, format.file_suffix())), format))
+            .collect()
+    }
+
+    /// Corrections of the domain pack and corrections file, if there are any
+    pub fn rule_set(&self) -> Result<Option<RuleSet>> {
+        let mut rules = match &self.domain {
+            Some(name) => DomainPack::load(name)?.correction_rules()?,
+            None => Vec::new(),
+        };
+        if let Some(path) = &self.corrections {
+            rules.extend(replace::load_corrections(path)?);
+        }
+        if rules.is_empty() {
+            return Ok(None);
+        }
+        RuleSet::compile(&rules).map(Some)
+    }
+}
+
+/// A run whose audio is ready but has not been submitted to AWS yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedRun {
+    /// Queue entry id
+    pub id: String,
+
+    /// When the run was queued
+    pub queued_at: chrono::DateTime<chrono::Utc>,
+
+    /// Original input (URL or file path)
+    pub input: String,
+
+    /// Audio information from extraction
+    pub audio_info: AudioInfo,
+
+    /// Transcription options
+    pub options: TranscribeOptions,
+
+    /// Output settings
+    pub output: OutputTarget,
+}
+
+/// Local queue of prepared runs, stored as one directory per entry
+pub struct OfflineQueue {
+    dir: PathBuf,
+}
+
+impl OfflineQueue {
+    /// Open the queue in the application data directory
+    pub fn open() -> Result<Self> {
+        Ok(Self::at(Config::data_dir()?.join("queue")))
+    }
+
+    /// Open a queue stored in a specific directory
+    pub fn at(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Store prepared audio and run settings in the queue
+    pub fn enqueue(
+        &self,
+        input: &str,
+        audio_info: AudioInfo,
+        audio_path: &Path,
+        options: TranscribeOptions,
+        output: OutputTarget,
+    ) -> Result<QueuedRun> {
+        let id = format!(
+            "{}_{}",
+            chrono::Utc::now().format("%Y%m%d_%H%M%S"),
+            &uuid::Uuid::new_v4().to_string()[..8]
+        );
+        let entry_dir = self.dir.join(&id);
+        fs_err::create_dir_all(&entry_dir)?;
+
+        fs_err::copy(audio_path, entry_dir.join(audio_file_name(&audio_info)))
+            .context("Failed to copy audio into the offline queue")?;
+
+        let run = QueuedRun {
+            id,
+            queued_at: chrono::Utc::now(),
+            input: input.to_string(),
+            audio_info,
+            options,
+            output,
+        };
+
+        let content = serde_json::to_string_pretty(&run)?;
+        fs_err::write(entry_dir.join("run.json"), content)?;
+
+        Ok(run)
+    }
+
+    /// List queued runs, oldest first
+    pub fn list(&self) -> Result<Vec<QueuedRun>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut runs = Vec::new();
+        for entry in fs_err::read_dir(&self.dir)? {
+            let manifest = entry?.path().join("run.json");
+            if !manifest.exists() {
+                continue;
+            }
+
+            let content = fs_err::read_to_string(&manifest)?;
+            match serde_json::from_str::<QueuedRun>(&content) {
+                Ok(run) => runs.push(run),
+                Err(e) => tracing::warn!("Ignoring corrupt queue entry {}: {}", manifest.display(), e),
+            }
+        }
+
+        runs.sort_by_key(|run| run.queued_at);
+        Ok(runs)
+    }
+
+    /// Path of the queued audio file for a run
+    pub fn audio_path(&self, run: &QueuedRun) -> PathBuf {
+        self.dir.join(&run.id).join(audio_file_name(&run.audio_info))
+    }
+
+    /// Keep the transcript of a run whose output could not be saved, so it is not paid for twice
+    pub fn keep_result(&self, run: &QueuedRun, result: &TranscriptionResult) -> Result<()> {
+        let content = serde_json::to_string(result)?;
+        fs_err::write(self.dir.join(&run.id).join("result.json"), content)?;
+        Ok(())
+    }
+
+    /// Transcript kept for a run by [`keep_result`](Self::keep_result), if there is one
+    pub fn kept_result(&self, run: &QueuedRun) -> Option<TranscriptionResult> {
+        let path = self.dir.join(&run.id).join("result.json");
+        let content = fs_err::read_to_string(&path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(result) => Some(result),
+            Err(e) => {
+                tracing::warn!("Ignoring corrupt transcript {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Remove a run and its audio from the queue
+    pub fn remove(&self, id: &str) -> Result<()> {
+        let entry_dir = self.dir.join(id);
+        if !entry_dir.join("run.json").exists() {
+            anyhow::bail!("No queued run with id '{}'", id);
+        }
+
+        fs_err::remove_dir_all(entry_dir)?;
+        Ok(())
+    }
+}
+
+fn audio_file_name(audio_info: &AudioInfo) -> String {
+    format!("audio.{}", audio_info.format.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extractors::AudioFormat;
+
+    #[test]
+    fn test_enqueue_list_remove() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = OfflineQueue::at(dir.path().join("queue"));
+        let audio = dir.path().join("input.mp3");
+        fs_err::write(&audio, b"audio").unwrap();
+
+        let audio_info = AudioInfo {
+            download_url: "/tmp/input.mp3".to_string(),
+            download_method: crate::extractors::DownloadMethod::LocalFile,
+            duration: None,
+            title: Some("Input".to_string()),
+            format: AudioFormat::Mp3,
+            sample_rate: None,
+            file_size: Some(5),
+            original_url: "input.mp3".to_string(),
+            chapters: Vec::new(),
+        };
+        let output = OutputTarget {
+            path: None,
+            format: OutputFormat::Srt,
+            extra_formats: Vec::new(),
+            domain: None,
+            corrections: None,
+            render: RenderOptions::default(),
+            existing: ExistingOutput::Backup,
+        };
+
+        let run = queue
+            .enqueue("input.mp3", audio_info, &audio, TranscribeOptions::default(), output)
+            .unwrap();
+
+        let runs = queue.list().unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].id, run.id);
+        assert_eq!(fs_err::read(queue.audio_path(&runs[0])).unwrap(), b"audio");
+
+        assert!(queue.kept_result(&run).is_none());
+        let result = crate::transcribe::fixture::result(&[(0.0, 2.5, "Hello world")]);
+        queue.keep_result(&run, &result).unwrap();
+        assert_eq!(queue.kept_result(&run).unwrap().transcript, result.transcript);
+
+        queue.remove(&run.id).unwrap();
+        assert!(queue.list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_extra_outputs() {
+        let mut target = OutputTarget {
+            path: Some(PathBuf::from("out/talk.final.srt")),
+            format: OutputFormat::Srt,
+            extra_formats: vec![OutputFormat::Json, OutputFormat::Text, OutputFormat::Legal],
+            domain: None,
+            corrections: None,
+            render: RenderOptions::default(),
+            existing: ExistingOutput::Overwrite,
+        };
+        let paths: Vec<PathBuf> = target.extra_outputs().into_iter().map(|(path, _)| path).collect();
+        assert_eq!(paths, ["out/talk.final.json", "out/talk.final.txt", "out/talk.final.legal.txt"].map(PathBuf::from));
+
+        target.path = None;
+        assert!(target.extra_outputs().is_empty());
+    }
+}
@@ -0,0 +1,160 @@
+use anyhow::{Context, Result};
+use indicatif::{ProgressBar, ProgressStyle};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Microphone capture settings for `record`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordConfig {
+    /// ffmpeg input format (pulse, alsa, avfoundation, dshow); chosen per platform if unset
+    pub input_format: Option<String>,
+
+    /// Capture device in the input format's syntax; the system default if unset
+    pub device: Option<String>,
+}
+
+impl RecordConfig {
+    /// ffmpeg input format and device for this platform
+    fn input(&self) -> Result<(String, String)> {
+        let format = self.input_format.clone().unwrap_or_else(|| default_input_format().to_string());
+
+        let device = match (&self.device, format.as_str()) {
+            (Some(device), _) => device.clone(),
+            (None, "pulse" | "alsa") => "default".to_string(),
+            (None, "avfoundation") => ":0".to_string(),
+            (None, _) => anyhow::bail!(
+                "No capture device configured for '{}'; set record.device in the config \
+                 (list devices with: ffmpeg -list_devices true -f dshow -i dummy)",
+                format
+            ),
+        };
+
+        Ok((format, device))
+    }
+}
+
+fn default_input_format() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "avfoundation"
+    } else if cfg!(target_os = "windows") {
+        "dshow"
+    } else {
+        "pulse"
+    }
+}
+
+/// Where a recording made now is kept until it is transcribed
+pub fn recording_path(dir: &Path, now: chrono::DateTime<chrono::Local>) -> PathBuf {
+    dir.join(format!("recording-{}.wav", now.format("%Y%m%d-%H%M%S")))
+}
+
+/// ffmpeg capturing `device` into a 16 kHz mono WAV file, for `duration` if given
+fn capture_command(format: &str, device: &str, duration: Option<Duration>, output_path: &Path) -> Command {
+    let mut command = Command::new("ffmpeg");
+    command.args(["-hide_banner", "-loglevel", "error", "-f", format, "-i", device]);
+    if let Some(duration) = duration {
+        command.args(["-t", &duration.as_secs_f64().to_string()]);
+    }
+    command.args(["-ac", "1", "-ar", "16000", "-y"]).arg(output_path);
+    command
+}
+
+/// Record from the microphone into a 16 kHz mono WAV file
+///
+/// Stops after `duration`, or when Ctrl-C is pressed; either way the recording is kept.
+pub async fn record(config: &RecordConfig, output_path: &Path, duration: Option<Duration>) -> Result<()> {
+    let (format, device) = config.input()?;
+
+    let mut command = capture_command(&format, &device, duration, output_path);
+    command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+
+    let mut child = command.spawn().context("Failed to run ffmpeg")?;
+
+    let progress = crate::progress::add(ProgressBar::new_spinner().with_style(
+        ProgressStyle::default_spinner()
+            .template("{spinner:.red} [{elapsed_precise}] {msg}")
+            .unwrap()
+    ));
+    progress.set_message(match duration {
+        Some(duration) => format!("Recording for {} (Ctrl-C to stop early)...", crate::utils::format_duration(duration.as_secs_f64())),
+        None => "Recording (Ctrl-C to stop)...".to_string(),
+    });
+    progress.enable_steady_tick(Duration::from_millis(250));
+
+    tokio::select! {
+        _ = child.wait() => {}
+        _ = tokio::signal::ctrl_c() => {
+            // `q` makes ffmpeg finish the file properly instead of leaving a truncated header
+            if let Some(mut stdin) = child.stdin.take() {
+                let _ = stdin.write_all(b"q").await;
+            }
+        }
+    }
+
+    let output = child.wait_with_output().await?;
+    progress.finish_with_message("Recording stopped");
+
+    let recorded = fs_err::metadata(output_path).map(|m| m.len() > 44).unwrap_or(false);
+    if !recorded {
+        let error = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Microphone capture failed ({} input '{}'): {}", format, device, error.trim());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn args(command: &Command) -> Vec<String> {
+        command.as_std().get_args().map(|arg| arg.to_string_lossy().into_owned()).collect()
+    }
+
+    #[test]
+    fn test_input() {
+        let config = |format: &str, device: Option<&str>| RecordConfig {
+            input_format: Some(format.to_string()),
+            device: device.map(str::to_string),
+        };
+        assert_eq!(config("pulse", None).input().unwrap(), ("pulse".to_string(), "default".to_string()));
+        assert_eq!(config("avfoundation", None).input().unwrap().1, ":0");
+        assert_eq!(config("alsa", Some("hw:1")).input().unwrap().1, "hw:1");
+
+        // DirectShow has no default device to fall back to
+        let error = config("dshow", None).input().unwrap_err();
+        assert!(error.to_string().contains("set record.device"), "{}", error);
+        assert_eq!(config("dshow", Some("audio=Microphone")).input().unwrap().1, "audio=Microphone");
+    }
+
+    #[test]
+    fn test_capture_command() {
+        let output = Path::new("/tmp/recording.wav");
+        let duration = crate::utils::parse_duration("1m30s").unwrap();
+        let command = capture_command("pulse", "default", Some(duration), output);
+        assert_eq!(command.as_std().get_program(), "ffmpeg");
+        assert_eq!(
+            args(&command).join(" "),
+            "-hide_banner -loglevel error -f pulse -i default -t 90 -ac 1 -ar 16000 -y /tmp/recording.wav"
+        );
+
+        // Without a duration it records until stopped
+        let command = capture_command("avfoundation", ":0", None, output);
+        assert!(!args(&command).contains(&"-t".to_string()));
+    }
+
+    #[test]
+    fn test_recording_path() {
+        let now = chrono::Local.with_ymd_and_hms(2024, 3, 9, 14, 5, 7).unwrap();
+        assert_eq!(recording_path(Path::new("/tmp"), now), Path::new("/tmp/recording-20240309-140507.wav"));
+    }
+}
@@ -0,0 +1,149 @@
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::{ApiError, AppState};
+
+/// Jobs per key are counted over this window
+const RATE_WINDOW: Duration = Duration::from_secs(3600);
+
+/// A client allowed to use the API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+    /// Who the key belongs to, for logs and job records
+    pub name: String,
+
+    /// The secret, sent as `Authorization: Bearer <key>` or `X-API-Key: <key>`
+    pub key: String,
+
+    /// Jobs this key may submit per hour, instead of `server.jobs_per_hour`
+    #[serde(default)]
+    pub jobs_per_hour: Option<u32>,
+}
+
+/// The key a request was made with, available to handlers
+#[derive(Debug, Clone)]
+pub struct Caller {
+    pub name: String,
+    pub jobs_per_hour: u32,
+}
+
+/// Reject requests without a configured key
+///
+/// The events stream also takes `?api_key=`, since browsers cannot set headers on an `EventSource`.
+pub(super) async fn authenticate(State(state): State<AppState>, mut request: Request, next: Next) -> Response {
+    let from_query = request
+        .uri()
+        .path()
+        .ends_with("/events")
+        .then(|| query_key(request.uri().query().unwrap_or_default()))
+        .flatten();
+    let given = presented_key(request.headers()).or(from_query);
+
+    let Some(key) = given.as_deref().and_then(|given| state.api_keys.iter().find(|key| same_secret(&key.key, given))) else {
+        let message = match given {
+            Some(_) => "Unknown API key",
+            None => "Missing API key (send Authorization: Bearer <key>)",
+        };
+        let mut response = ApiError(StatusCode::UNAUTHORIZED, message.to_string()).into_response();
+        response.headers_mut().insert(header::WWW_AUTHENTICATE, header::HeaderValue::from_static("Bearer"));
+        return response;
+    };
+
+    request.extensions_mut().insert(Caller {
+        name: key.name.clone(),
+        jobs_per_hour: key.jobs_per_hour.unwrap_or(state.jobs_per_hour),
+    });
+    next.run(request).await
+}
+
+fn presented_key(headers: &HeaderMap) -> Option<String> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let api_key = headers.get("x-api-key").and_then(|value| value.to_str().ok());
+    bearer.or(api_key).map(|key| key.trim().to_string())
+}
+
+fn query_key(query: &str) -> Option<String> {
+    url::form_urlencoded::parse(query.as_bytes())
+        .find(|(name, _)| name == "api_key")
+        .map(|(_, key)| key.into_owned())
+}
+
+/// Compare secrets in time that does not depend on where they differ
+fn same_secret(expected: &str, given: &str) -> bool {
+    expected.len() == given.len() && expected.bytes().zip(given.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Jobs submitted per key over the last hour
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    submitted: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl RateLimiter {
+    /// Count a job for `caller`, or say how long until it may submit another
+    pub fn admit(&self, caller: &Caller, now: Instant) -> Result<(), Duration> {
+        if caller.jobs_per_hour == 0 {
+            return Ok(());
+        }
+
+        let mut submitted = self.submitted.lock().unwrap_or_else(|e| e.into_inner());
+        let times = submitted.entry(caller.name.clone()).or_default();
+        while times.front().is_some_and(|&at| now.duration_since(at) >= RATE_WINDOW) {
+            times.pop_front();
+        }
+        if times.len() >= caller.jobs_per_hour as usize {
+            let oldest = times.front().copied().unwrap_or(now);
+            return Err(RATE_WINDOW.saturating_sub(now.duration_since(oldest)));
+        }
+        times.push_back(now);
+        Ok(())
+    }
+}
+
+/// 429 with `Retry-After`
+pub(super) fn too_many_jobs(caller: &Caller, retry_after: Duration) -> Response {
+    let seconds = retry_after.as_secs().max(1);
+    let message = format!(
+        "Key '{}' has submitted {} jobs in the last hour; try again in {}s",
+        caller.name, caller.jobs_per_hour, seconds
+    );
+    let mut response = ApiError(StatusCode::TOO_MANY_REQUESTS, message).into_response();
+    response.headers_mut().insert(header::RETRY_AFTER, seconds.into());
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limit_and_keys() {
+        let limiter = RateLimiter::default();
+        let caller = Caller {
+            name: "ci".to_string(),
+            jobs_per_hour: 2,
+        };
+        let start = Instant::now();
+
+        assert!(limiter.admit(&caller, start).is_ok());
+        assert!(limiter.admit(&caller, start + Duration::from_secs(60)).is_ok());
+        let wait = limiter.admit(&caller, start + Duration::from_secs(600)).unwrap_err();
+        assert_eq!(wait, Duration::from_secs(3000));
+        // Other keys have their own budget, and the first job drops out after an hour
+        assert!(limiter.admit(&Caller { name: "web".to_string(), ..caller.clone() }, start).is_ok());
+        assert!(limiter.admit(&caller, start + RATE_WINDOW).is_ok());
+
+        assert!(same_secret("s3cret", "s3cret"));
+        assert!(!same_secret("s3cret", "s3cres") && !same_secret("s3cret", "s3cre"));
+        assert_eq!(query_key("format=srt&api_key=a%2Bb").as_deref(), Some("a+b"));
+    }
+}
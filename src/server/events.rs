@@ -0,0 +1,108 @@
+use axum::extract::{Extension, Path, State};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures_util::stream::{self, Stream, StreamExt};
+use indicatif::{ProgressBar, ProgressDrawTarget};
+use serde::Serialize;
+use std::convert::Infallible;
+use tokio::sync::broadcast::error::RecvError;
+
+use super::{ApiError, AppState, Caller, Job, JobStatus};
+use crate::progress::ProgressSink;
+
+/// Something a job reports while it runs, sent on GET /jobs/{id}/events
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum JobEvent {
+    /// The job was queued, started, completed or failed
+    Status { status: JobStatus, error: Option<String> },
+    /// What the job does now: downloading, uploading, transcribing, ...
+    Stage { stage: String },
+    /// How a stage is getting on, as the CLI's progress bars put it
+    Progress { message: String },
+    /// A finished sentence of a live job, with where it starts in the recording (seconds)
+    Segment { text: String, start: f64 },
+}
+
+impl JobEvent {
+    pub(super) fn status(job: &Job) -> Self {
+        JobEvent::Status {
+            status: job.status,
+            error: job.error.clone(),
+        }
+    }
+
+    fn is_final(&self) -> bool {
+        matches!(self, JobEvent::Status { status: JobStatus::Completed | JobStatus::Failed, .. })
+    }
+
+    fn to_sse(&self) -> Event {
+        let name = match self {
+            JobEvent::Status { .. } => "status",
+            JobEvent::Stage { .. } => "stage",
+            JobEvent::Progress { .. } => "progress",
+            JobEvent::Segment { .. } => "segment",
+        };
+        Event::default().event(name).json_data(self).unwrap_or_else(|_| Event::default().event(name))
+    }
+}
+
+/// Sends the progress a job's pipeline shows as its `progress` events
+pub(super) struct JobProgress {
+    pub(super) state: AppState,
+    pub(super) id: String,
+}
+
+impl ProgressSink for JobProgress {
+    /// Nothing is drawn on the server's terminal
+    fn add(&self, bar: ProgressBar) -> ProgressBar {
+        bar.set_draw_target(ProgressDrawTarget::hidden());
+        bar
+    }
+
+    fn message(&self, message: &str) {
+        let event = JobEvent::Progress { message: message.to_string() };
+        self.state.update(&self.id, |job| job.send(event));
+    }
+}
+
+/// GET /jobs/{id}/events: server-sent events until the job is done
+///
+/// The stream opens with the job's current status (and stage), so late subscribers and
+/// finished jobs need no second request.
+pub(super) async fn stream(
+    State(state): State<AppState>,
+    Extension(caller): Extension<Caller>,
+    Path(id): Path<String>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    // Subscribing under the lock means no event falls between the snapshot and the stream
+    let (current, receiver, finished) = {
+        let jobs = state.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        let job = jobs
+            .get(&id)
+            .filter(|job| job.is_visible_to(&caller))
+            .ok_or_else(|| ApiError::not_found(&id))?;
+        let mut current = vec![JobEvent::status(job)];
+        current.extend(job.stage.clone().map(|stage| JobEvent::Stage { stage }));
+        (current, job.events.subscribe(), job.is_finished())
+    };
+
+    let later = stream::unfold((receiver, finished), |(mut receiver, done)| async move {
+        if done {
+            return None;
+        }
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    let done = event.is_final();
+                    return Some((event, (receiver, done)));
+                }
+                // A slow reader misses some stages or sentences, but still learns how the job ends
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    let events = stream::iter(current).chain(later).map(|event| Ok(event.to_sse()));
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
@@ -0,0 +1,601 @@
+use anyhow::{Context, Result};
+use axum::extract::{Extension, Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{middleware, Json, Router};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, Semaphore};
+use uuid::Uuid;
+
+use crate::cli::{Backend, OutputFormat};
+use crate::config::Config;
+use crate::extractors::live::LiveOptions;
+use crate::history::HistoryConfig;
+use crate::output::summary::RunSummary;
+use crate::output::RenderOptions;
+use crate::transcribe::{TranscribeOptions, TranscriptionPipeline, TranscriptionResult};
+
+mod auth;
+mod events;
+
+pub use auth::{ApiKey, Caller};
+pub use events::JobEvent;
+
+/// Events kept for a subscriber that reads slower than the job reports them
+const EVENT_BUFFER: usize = 256;
+
+/// The HTTP API of `serve`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// Address to listen on
+    pub host: String,
+
+    /// Port to listen on
+    pub port: u16,
+
+    /// Jobs transcribed at the same time; later ones wait in the queue
+    pub workers: usize,
+
+    /// Hours finished jobs and their transcripts are kept for GET /jobs/{id}
+    pub keep_finished_hours: u64,
+
+    /// Keys clients must send; `serve` does not start without one
+    pub api_keys: Vec<ApiKey>,
+
+    /// Jobs a key may submit per hour, unless it sets its own limit (0 = unlimited)
+    pub jobs_per_hour: u32,
+
+    /// Let jobs (and `worker` messages) name files on this host instead of http(s) URLs
+    pub allow_local_inputs: bool,
+
+    /// Let jobs (and `worker` messages) name s3:// objects, read with this host's AWS credentials
+    pub allow_s3_inputs: bool,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".to_string(),
+            port: 8080,
+            workers: 2,
+            keep_finished_hours: 24,
+            api_keys: Vec::new(),
+            jobs_per_hour: 30,
+            allow_local_inputs: false,
+            allow_s3_inputs: false,
+        }
+    }
+}
+
+/// Where the `url` of a job may point besides http(s)
+///
+/// Local paths and s3:// URIs read whatever the server itself can, so callers only get them
+/// when `server.allow_local_inputs` / `server.allow_s3_inputs` say so.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InputSources {
+    pub local: bool,
+    pub s3: bool,
+}
+
+impl InputSources {
+    pub fn of(settings: &ServerConfig) -> Self {
+        Self {
+            local: settings.allow_local_inputs,
+            s3: settings.allow_s3_inputs,
+        }
+    }
+
+    fn check(&self, url: &str) -> Result<()> {
+        let scheme = url::Url::parse(url).map(|url| url.scheme().to_string()).unwrap_or_default();
+        match scheme.as_str() {
+            "http" | "https" => Ok(()),
+            "s3" if self.s3 => Ok(()),
+            "s3" => anyhow::bail!("s3:// inputs are turned off (server.allow_s3_inputs)"),
+            _ if self.local => Ok(()),
+            _ => anyhow::bail!("url must be an http(s) URL; local files are turned off (server.allow_local_inputs)"),
+        }
+    }
+}
+
+/// Body of POST /jobs: what to transcribe and how
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JobRequest {
+    pub url: String,
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub backend: Option<Backend>,
+    #[serde(default)]
+    pub speaker_labels: bool,
+    #[serde(default)]
+    pub max_speakers: Option<u8>,
+    #[serde(default)]
+    pub prefer_captions: bool,
+    #[serde(default)]
+    pub max_cost: Option<f64>,
+    #[serde(default)]
+    pub tag: Option<String>,
+    /// Record a live stream instead, with its sentences sent as `segment` events as they are done
+    #[serde(default)]
+    pub live: Option<LiveRequest>,
+}
+
+/// How a live stream is recorded for a job
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LiveRequest {
+    /// Seconds to record for
+    pub duration_secs: u64,
+    /// Seconds of stream transcribed at a time
+    #[serde(default = "default_chunk_secs")]
+    pub chunk_secs: u64,
+    /// Record from the start of the stream instead of the live edge (YouTube)
+    #[serde(default)]
+    pub from_start: bool,
+}
+
+fn default_chunk_secs() -> u64 {
+    30
+}
+
+impl JobRequest {
+    /// The checked options of the request; `backend` is used unless it names one
+    pub fn options(
+        &self,
+        backend: Backend,
+        demo: bool,
+        sources: InputSources,
+    ) -> Result<(TranscribeOptions, Option<LiveOptions>)> {
+        if self.url.trim().is_empty() {
+            anyhow::bail!("url is required");
+        }
+        sources.check(self.url.trim())?;
+        let backend = self.backend.unwrap_or(backend);
+        let language = self
+            .language
+            .as_deref()
+            .map(|language| crate::languages::validate(language, backend))
+            .transpose()?;
+        let live = match &self.live {
+            Some(_) if demo => anyhow::bail!("live jobs are not available with --demo"),
+            Some(live) if live.duration_secs == 0 => anyhow::bail!("live.duration_secs must be above 0"),
+            Some(live) => Some(LiveOptions {
+                duration: Duration::from_secs(live.duration_secs),
+                chunk: Some(Duration::from_secs(live.chunk_secs)).filter(|chunk| !chunk.is_zero()),
+                from_start: live.from_start,
+                wait: false,
+                keep_recording: false,
+            }),
+            None => None,
+        };
+
+        let options = TranscribeOptions {
+            language,
+            speaker_labels: self.speaker_labels || self.max_speakers.is_some(),
+            max_speakers: self.max_speakers,
+            backend,
+            tag: self.tag.clone(),
+            prefer_captions: self.prefer_captions,
+            max_cost: self.max_cost,
+            ..Default::default()
+        };
+        Ok((options, live))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+/// A submitted job, as GET /jobs/{id} reports it
+#[derive(Debug, Clone, Serialize)]
+pub struct Job {
+    pub id: String,
+    pub url: String,
+    /// Name of the API key that submitted the job
+    pub submitted_by: String,
+    pub status: JobStatus,
+    /// What a running job is doing, e.g. "Uploading audio to S3"
+    pub stage: Option<String>,
+    pub submitted_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+    /// Language, duration, confidence and cost of a completed job, as `--summary json` prints them
+    pub summary: Option<RunSummary>,
+    #[serde(skip)]
+    result: Option<Arc<TranscriptionResult>>,
+    #[serde(skip)]
+    events: broadcast::Sender<JobEvent>,
+}
+
+impl Job {
+    /// Only the key that submitted a job may see it
+    fn is_visible_to(&self, caller: &Caller) -> bool {
+        self.submitted_by == caller.name
+    }
+
+    fn is_finished(&self) -> bool {
+        matches!(self.status, JobStatus::Completed | JobStatus::Failed)
+    }
+
+    /// Tell the job's event subscribers, if there are any
+    fn send(&self, event: JobEvent) {
+        let _ = self.events.send(event);
+    }
+
+    fn set_status(&mut self, status: JobStatus) {
+        self.status = status;
+        if self.is_finished() {
+            self.stage = None;
+            self.finished_at = Some(Utc::now());
+        }
+        self.send(JobEvent::status(self));
+    }
+}
+
+#[derive(Clone)]
+struct AppState {
+    pipeline: Arc<TranscriptionPipeline>,
+    jobs: Arc<Mutex<HashMap<String, Job>>>,
+    workers: Arc<Semaphore>,
+    backend: Backend,
+    default_format: OutputFormat,
+    prices: HistoryConfig,
+    keep_finished: chrono::Duration,
+    demo: bool,
+    sources: InputSources,
+    api_keys: Arc<Vec<ApiKey>>,
+    jobs_per_hour: u32,
+    limiter: Arc<auth::RateLimiter>,
+}
+
+impl AppState {
+    /// Change a job; events are sent from here, so subscribers see them in order
+    fn update(&self, id: &str, change: impl FnOnce(&mut Job)) {
+        if let Some(job) = self.jobs.lock().unwrap_or_else(|e| e.into_inner()).get_mut(id) {
+            change(job);
+        }
+    }
+
+    /// A job the caller submitted; other keys' jobs look as if they did not exist
+    fn job(&self, id: &str, caller: &Caller) -> Result<Job, ApiError> {
+        let jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        jobs.get(id)
+            .filter(|job| job.is_visible_to(caller))
+            .cloned()
+            .ok_or_else(|| ApiError::not_found(id))
+    }
+
+    /// Forget finished jobs past `keep_finished_hours`, so a long-running server does not grow
+    fn prune(&self) {
+        let cutoff = Utc::now() - self.keep_finished;
+        let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        jobs.retain(|_, job| job.finished_at.is_none_or(|finished| finished > cutoff));
+    }
+}
+
+/// An error response: the status code and `{"error": message}`
+struct ApiError(StatusCode, String);
+
+impl ApiError {
+    fn not_found(id: &str) -> Self {
+        ApiError(StatusCode::NOT_FOUND, format!("No job {}", id))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+/// A bound listener with the pipeline jobs run on
+pub struct Server {
+    listener: TcpListener,
+    state: AppState,
+}
+
+impl Server {
+    pub async fn bind(config: Config) -> Result<Self> {
+        let settings = config.server.clone();
+        if settings.workers == 0 {
+            anyhow::bail!("server.workers must be at least 1");
+        }
+        // Every job can start paid AWS work, so there is no unauthenticated mode
+        if settings.api_keys.is_empty() {
+            anyhow::bail!(
+                "serve needs at least one API key in server.api_keys, e.g.\n  \
+                 rustscribe config set server.api_keys '[{{name: ci, key: {}}}]'",
+                Uuid::new_v4().simple()
+            );
+        }
+        if let Some(key) = settings.api_keys.iter().find(|key| key.key.trim().len() < 16) {
+            anyhow::bail!("API key '{}' is too short; use at least 16 characters", key.name);
+        }
+        // Jobs belong to the name of the key that submitted them
+        let mut names = std::collections::HashSet::new();
+        if let Some(key) = settings.api_keys.iter().find(|key| !names.insert(key.name.as_str())) {
+            anyhow::bail!("API key name '{}' is used twice; each key needs its own name", key.name);
+        }
+        let address = format!("{}:{}", settings.host, settings.port);
+        let listener = TcpListener::bind(&address)
+            .await
+            .with_context(|| format!("Could not listen on {}", address))?;
+
+        let state = AppState {
+            backend: config.app.backend,
+            default_format: clap::ValueEnum::from_str(&config.app.default_output_format, true)
+                .unwrap_or(OutputFormat::Text),
+            prices: config.history.clone(),
+            keep_finished: chrono::Duration::hours(settings.keep_finished_hours as i64),
+            demo: config.app.demo,
+            sources: InputSources::of(&settings),
+            api_keys: Arc::new(settings.api_keys),
+            jobs_per_hour: settings.jobs_per_hour,
+            limiter: Arc::default(),
+            jobs: Arc::default(),
+            workers: Arc::new(Semaphore::new(settings.workers)),
+            pipeline: Arc::new(TranscriptionPipeline::new(config).await?),
+        };
+        Ok(Self { listener, state })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Answer requests until Ctrl+C; running AWS jobs can be collected with `resume` afterwards
+    pub async fn run(self) -> Result<()> {
+        let app = Router::new()
+            .route("/jobs", post(submit))
+            .route("/jobs/{id}", get(status))
+            .route("/jobs/{id}/transcript", get(transcript))
+            .route("/jobs/{id}/events", get(events::stream))
+            .layer(middleware::from_fn_with_state(self.state.clone(), auth::authenticate))
+            .with_state(self.state);
+
+        axum::serve(self.listener, app)
+            .with_graceful_shutdown(async {
+                let _ = tokio::signal::ctrl_c().await;
+            })
+            .await
+            .context("HTTP server failed")
+    }
+}
+
+/// POST /jobs: queue a transcription and answer 202 with the job
+async fn submit(
+    State(state): State<AppState>,
+    Extension(caller): Extension<Caller>,
+    Json(request): Json<JobRequest>,
+) -> Result<Response, ApiError> {
+    let (options, live) = request
+        .options(state.backend, state.demo, state.sources)
+        .map_err(|e| ApiError(StatusCode::BAD_REQUEST, format!("{:#}", e)))?;
+    let backend = options.backend;
+
+    if let Err(retry_after) = state.limiter.admit(&caller, Instant::now()) {
+        return Ok(auth::too_many_jobs(&caller, retry_after));
+    }
+
+    state.prune();
+    let job = Job {
+        id: Uuid::new_v4().to_string(),
+        url: request.url,
+        submitted_by: caller.name,
+        status: JobStatus::Queued,
+        stage: None,
+        submitted_at: Utc::now(),
+        finished_at: None,
+        error: None,
+        summary: None,
+        result: None,
+        events: broadcast::channel(EVENT_BUFFER).0,
+    };
+    state.jobs.lock().unwrap_or_else(|e| e.into_inner()).insert(job.id.clone(), job.clone());
+    tracing::info!("Job {} queued by {}: {}", job.id, job.submitted_by, job.url);
+
+    let (id, url) = (job.id.clone(), job.url.clone());
+    let worker = state.clone();
+    tokio::spawn(async move {
+        let Ok(_slot) = worker.workers.acquire().await else {
+            return;
+        };
+        worker.update(&id, |job| job.set_status(JobStatus::Running));
+
+        let watcher = {
+            let (state, id) = (worker.clone(), id.clone());
+            move |stage: &str| {
+                state.update(&id, |job| {
+                    job.stage = Some(stage.to_string());
+                    job.send(JobEvent::Stage { stage: stage.to_string() });
+                })
+            }
+        };
+        let on_text = |text: &str, start: f64| {
+            worker.update(&id, |job| job.send(JobEvent::Segment { text: text.to_string(), start }))
+        };
+        let run = async {
+            match &live {
+                Some(live) => worker.pipeline.transcribe_live(&url, &options, live, on_text).await,
+                None => worker.pipeline.transcribe_from_url(&url, &options).await,
+            }
+        };
+        let progress = Arc::new(events::JobProgress { state: worker.clone(), id: id.clone() });
+        let outcome = crate::health::watch_stages(watcher, crate::progress::report_to(progress, run)).await;
+
+        let summary = outcome.as_ref().ok().map(|result| RunSummary::completed(result, backend, &worker.prices));
+        worker.update(&id, |job| match outcome {
+            Ok(result) => {
+                tracing::info!("Job {} completed", job.id);
+                job.summary = summary;
+                job.result = Some(Arc::new(result));
+                job.set_status(JobStatus::Completed);
+            }
+            Err(e) => {
+                tracing::warn!("Job {} failed: {:#}", job.id, e);
+                job.error = Some(format!("{:#}", e));
+                job.set_status(JobStatus::Failed);
+            }
+        });
+    });
+
+    let location = format!("/jobs/{}", job.id);
+    Ok((StatusCode::ACCEPTED, [(header::LOCATION, location)], Json(job)).into_response())
+}
+
+/// GET /jobs/{id}
+async fn status(
+    State(state): State<AppState>,
+    Extension(caller): Extension<Caller>,
+    Path(id): Path<String>,
+) -> Result<Json<Job>, ApiError> {
+    state.job(&id, &caller).map(Json)
+}
+
+#[derive(Debug, Deserialize)]
+struct TranscriptQuery {
+    format: Option<OutputFormat>,
+}
+
+/// GET /jobs/{id}/transcript?format=srt: the transcript of a completed job
+async fn transcript(
+    State(state): State<AppState>,
+    Extension(caller): Extension<Caller>,
+    Path(id): Path<String>,
+    Query(query): Query<TranscriptQuery>,
+) -> Result<Response, ApiError> {
+    let job = state.job(&id, &caller)?;
+    let Some(result) = &job.result else {
+        let status = format!("{:?}", job.status).to_lowercase();
+        return Err(ApiError(StatusCode::CONFLICT, format!("Job {} is {}, not completed", id, status)));
+    };
+
+    let format = query.format.unwrap_or_else(|| state.default_format.clone());
+    let content = crate::output::render(result, &format, &RenderOptions::default())
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)))?;
+    Ok(([(header::CONTENT_TYPE, format.content_type())], content).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_submit_and_fetch() {
+        let mut config = Config::demo();
+        config.server.port = 0;
+        config.health.heartbeat_secs = 0;
+        assert!(Server::bind(config.clone()).await.is_err(), "started without API keys");
+        let key = "0123456789abcdef0123";
+        config.server.jobs_per_hour = 2;
+        let other_key = "fedcba9876543210fedc";
+        config.server.api_keys = vec![
+            ApiKey { name: "test".to_string(), key: key.to_string(), jobs_per_hour: None },
+            ApiKey { name: "other".to_string(), key: other_key.to_string(), jobs_per_hour: None },
+        ];
+        let server = Server::bind(config).await.unwrap();
+        let base = format!("http://{}", server.local_addr().unwrap());
+        tokio::spawn(server.run());
+
+        let anonymous = reqwest::Client::new();
+        let unauthorized = anonymous
+            .post(format!("{}/jobs", base))
+            .json(&serde_json::json!({ "url": "https://example.com/talk.mp3" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(unauthorized.status(), 401);
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("authorization", format!("Bearer {}", key).parse().unwrap());
+        let client = reqwest::Client::builder().default_headers(headers).build().unwrap();
+        let submitted = client
+            .post(format!("{}/jobs", base))
+            .json(&serde_json::json!({ "url": "https://example.com/talk.mp3", "language": "en-us" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(submitted.status(), 202);
+        let job: serde_json::Value = submitted.json().await.unwrap();
+        let id = job["id"].as_str().unwrap().to_string();
+
+        let mut job = job;
+        for _ in 0..100 {
+            if job["status"] != "queued" && job["status"] != "running" {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            job = client.get(format!("{}/jobs/{}", base, id)).send().await.unwrap().json().await.unwrap();
+        }
+        assert_eq!(job["status"], "completed", "{}", job);
+        assert_eq!(job["summary"]["language"], "en-US");
+
+        let srt = client.get(format!("{}/jobs/{}/transcript?format=srt", base, id)).send().await.unwrap();
+        assert_eq!(srt.headers()["content-type"], "application/x-subrip");
+        assert!(srt.text().await.unwrap().contains(" --> "));
+
+        // A finished job's event stream says how it ended, then closes; browsers pass the key in the URL
+        let events = anonymous.get(format!("{}/jobs/{}/events?api_key={}", base, id, key)).send().await.unwrap();
+        assert_eq!(events.headers()["content-type"], "text/event-stream");
+        let events = events.text().await.unwrap();
+        assert!(events.starts_with("event: status\ndata: {\"status\":\"completed\""), "{}", events);
+
+        let missing = client.get(format!("{}/jobs/nope", base)).send().await.unwrap();
+        assert_eq!(missing.status(), 404);
+        // Another key cannot see the job, its transcript or its events
+        for path in ["", "/transcript", "/events"] {
+            let url = format!("{}/jobs/{}{}", base, id, path);
+            let foreign = anonymous.get(url).bearer_auth(other_key).send().await.unwrap();
+            assert_eq!(foreign.status(), 404, "{}", path);
+        }
+        let invalid = client
+            .post(format!("{}/jobs", base))
+            .json(&serde_json::json!({ "url": "https://example.com/talk.mp3", "language": "klingon" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(invalid.status(), 400);
+        // Files on the server and its S3 buckets are not for API clients unless configured
+        for url in ["/etc/passwd", "file:///etc/passwd", "s3://private-bucket/audio.mp3"] {
+            let rejected = client.post(format!("{}/jobs", base)).json(&serde_json::json!({ "url": url })).send().await.unwrap();
+            assert_eq!(rejected.status(), 400, "{}", url);
+        }
+
+        // Rejected requests do not count against the key's jobs per hour
+        let talk = serde_json::json!({ "url": "https://example.com/talk.mp3" });
+        let second = client.post(format!("{}/jobs", base)).json(&talk).send().await.unwrap();
+        assert_eq!(second.status(), 202);
+        let third = client.post(format!("{}/jobs", base)).json(&talk).send().await.unwrap();
+        assert_eq!(third.status(), 429);
+        assert!(third.headers().contains_key("retry-after"));
+    }
+
+    #[test]
+    fn test_input_sources() {
+        let request = |url: &str| serde_json::from_value::<JobRequest>(serde_json::json!({ "url": url })).unwrap();
+        let closed = InputSources::default();
+        assert!(request("https://example.com/talk.mp3").options(Backend::Aws, true, closed).is_ok());
+        assert!(request("/etc/passwd").options(Backend::Aws, true, closed).is_err());
+        assert!(request("recording.wav").options(Backend::Aws, true, closed).is_err());
+        assert!(request("s3://bucket/talk.mp3").options(Backend::Aws, true, closed).is_err());
+
+        let open = InputSources { local: true, s3: true };
+        assert!(request("/srv/audio/talk.mp3").options(Backend::Aws, true, open).is_ok());
+        assert!(request("s3://bucket/talk.mp3").options(Backend::Aws, true, open).is_ok());
+    }
+}
@@ -0,0 +1,265 @@
+use anyhow::{Context, Result};
+use std::f32::consts::PI;
+use std::path::Path;
+use tokio::process::Command;
+
+/// Sample rate audio is decoded to before analysis
+pub const SAMPLE_RATE: usize = 16000;
+
+/// 25 ms analysis window, zero-padded to the FFT size
+const FRAME_LEN: usize = 400;
+const FFT_SIZE: usize = 512;
+
+/// 10 ms hop between frames
+const HOP_LEN: usize = 160;
+
+const MEL_FILTERS: usize = 26;
+const CEPSTRA: usize = 13;
+
+/// Fraction of the loudest frames treated as speech
+const VOICED_FRACTION: f32 = 0.6;
+
+/// Length of an embedding (mean and standard deviation of each cepstral coefficient)
+pub const EMBEDDING_LEN: usize = 2 * (CEPSTRA - 1);
+
+/// Decode any audio/video file to 16 kHz mono samples with ffmpeg
+pub async fn load_samples(path: &Path) -> Result<Vec<f32>> {
+    let output = Command::new("ffmpeg")
+        .args([
+            "-i", &path.to_string_lossy(),
+            "-vn",
+            "-ac", "1",
+            "-ar", &SAMPLE_RATE.to_string(),
+            "-f", "f32le",
+            "-",
+        ])
+        .output()
+        .await
+        .context("Failed to run ffmpeg")?;
+
+    crate::logs::check("ffmpeg", &output, &format!("Failed to decode {} with ffmpeg", path.display()))?;
+
+    Ok(output
+        .stdout
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect())
+}
+
+/// Compute a voice embedding: statistics of MFCCs over the voiced frames
+///
+/// This is a lightweight, dependency-free voiceprint. It separates distinct voices
+/// recorded in similar conditions well, but is not a neural speaker model.
+pub fn embed(samples: &[f32]) -> Option<Vec<f32>> {
+    if samples.len() < FRAME_LEN {
+        return None;
+    }
+
+    let filters = mel_filterbank();
+    let window: Vec<f32> = (0..FRAME_LEN)
+        .map(|n| 0.54 - 0.46 * (2.0 * PI * n as f32 / (FRAME_LEN - 1) as f32).cos())
+        .collect();
+
+    let mut frames: Vec<(f32, Vec<f32>)> = Vec::new();
+    let mut start = 0;
+    while start + FRAME_LEN <= samples.len() {
+        let frame = &samples[start..start + FRAME_LEN];
+        start += HOP_LEN;
+
+        // Pre-emphasis and windowing
+        let mut buffer = vec![(0.0f32, 0.0f32); FFT_SIZE];
+        let mut energy = 0.0;
+        for n in 0..FRAME_LEN {
+            let previous = if n == 0 { 0.0 } else { frame[n - 1] };
+            let value = (frame[n] - 0.97 * previous) * window[n];
+            buffer[n].0 = value;
+            energy += value * value;
+        }
+
+        fft(&mut buffer);
+        let power: Vec<f32> = buffer[..FFT_SIZE / 2 + 1]
+            .iter()
+            .map(|(re, im)| (re * re + im * im) / FFT_SIZE as f32)
+            .collect();
+
+        let log_mel: Vec<f32> = filters
+            .iter()
+            .map(|filter| {
+                let sum: f32 = filter.iter().zip(&power).map(|(w, p)| w * p).sum();
+                sum.max(1e-10).ln()
+            })
+            .collect();
+
+        frames.push((energy, dct(&log_mel)));
+    }
+
+    // Keep the loudest frames so silence and background noise do not dominate
+    frames.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let voiced = ((frames.len() as f32 * VOICED_FRACTION).ceil() as usize).max(1);
+    let cepstra: Vec<&Vec<f32>> = frames[..voiced].iter().map(|(_, c)| c).collect();
+
+    let count = cepstra.len() as f32;
+    let mut embedding = Vec::with_capacity(EMBEDDING_LEN);
+    // c0 only reflects loudness, so it is left out
+    for k in 1..CEPSTRA {
+        let mean = cepstra.iter().map(|c| c[k]).sum::<f32>() / count;
+        embedding.push(mean);
+    }
+    for k in 1..CEPSTRA {
+        let mean = embedding[k - 1];
+        let variance = cepstra.iter().map(|c| (c[k] - mean).powi(2)).sum::<f32>() / count;
+        embedding.push(variance.sqrt());
+    }
+
+    Some(normalize(embedding))
+}
+
+/// Average several embeddings of the same speaker
+pub fn average(embeddings: &[Vec<f32>]) -> Option<Vec<f32>> {
+    let first = embeddings.first()?;
+    let mut sum = vec![0.0; first.len()];
+    for embedding in embeddings {
+        for (total, value) in sum.iter_mut().zip(embedding) {
+            *total += value;
+        }
+    }
+
+    Some(normalize(sum))
+}
+
+/// Cosine similarity of two embeddings
+pub fn similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let denominator = norm(a) * norm(b);
+
+    if denominator == 0.0 {
+        0.0
+    } else {
+        dot / denominator
+    }
+}
+
+fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|x| *x /= norm);
+    }
+    vector
+}
+
+/// Triangular filters spaced evenly on the mel scale
+fn mel_filterbank() -> Vec<Vec<f32>> {
+    let to_mel = |hz: f32| 2595.0 * (1.0 + hz / 700.0).log10();
+    let to_hz = |mel: f32| 700.0 * (10f32.powf(mel / 2595.0) - 1.0);
+
+    let max_mel = to_mel(SAMPLE_RATE as f32 / 2.0);
+    let bins: Vec<usize> = (0..MEL_FILTERS + 2)
+        .map(|i| {
+            let hz = to_hz(max_mel * i as f32 / (MEL_FILTERS + 1) as f32);
+            ((FFT_SIZE + 1) as f32 * hz / SAMPLE_RATE as f32).floor() as usize
+        })
+        .collect();
+
+    (1..=MEL_FILTERS)
+        .map(|m| {
+            let mut filter = vec![0.0; FFT_SIZE / 2 + 1];
+            for (k, weight) in filter.iter_mut().enumerate() {
+                if k >= bins[m - 1] && k < bins[m] {
+                    *weight = (k - bins[m - 1]) as f32 / (bins[m] - bins[m - 1]).max(1) as f32;
+                } else if k >= bins[m] && k <= bins[m + 1] {
+                    *weight = (bins[m + 1] - k) as f32 / (bins[m + 1] - bins[m]).max(1) as f32;
+                }
+            }
+            filter
+        })
+        .collect()
+}
+
+/// DCT-II of the log mel energies, keeping the first cepstral coefficients
+fn dct(input: &[f32]) -> Vec<f32> {
+    let n = input.len() as f32;
+    (0..CEPSTRA)
+        .map(|k| {
+            input
+                .iter()
+                .enumerate()
+                .map(|(i, x)| x * (PI * k as f32 * (i as f32 + 0.5) / n).cos())
+                .sum()
+        })
+        .collect()
+}
+
+/// In-place iterative radix-2 FFT on (re, im) pairs; the length must be a power of two
+fn fft(buffer: &mut [(f32, f32)]) {
+    let n = buffer.len();
+
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            buffer.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let (re, im) = buffer[start + k + len / 2];
+                let twiddled = (re * cos - im * sin, re * sin + im * cos);
+                let even = buffer[start + k];
+                buffer[start + k] = (even.0 + twiddled.0, even.1 + twiddled.1);
+                buffer[start + k + len / 2] = (even.0 - twiddled.0, even.1 - twiddled.1);
+            }
+        }
+        len <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(frequencies: &[f32], seconds: f32) -> Vec<f32> {
+        (0..(SAMPLE_RATE as f32 * seconds) as usize)
+            .map(|n| {
+                let t = n as f32 / SAMPLE_RATE as f32;
+                frequencies.iter().map(|f| (2.0 * PI * f * t).sin()).sum::<f32>() * 0.2
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fft_finds_tone() {
+        let mut buffer: Vec<(f32, f32)> = (0..64)
+            .map(|n| ((2.0 * PI * 8.0 * n as f32 / 64.0).cos(), 0.0))
+            .collect();
+        fft(&mut buffer);
+
+        let peak = (0..32)
+            .max_by(|&a, &b| {
+                let magnitude = |i: usize| buffer[i].0.hypot(buffer[i].1);
+                magnitude(a).total_cmp(&magnitude(b))
+            })
+            .unwrap();
+        assert_eq!(peak, 8);
+    }
+
+    #[test]
+    fn test_embeddings_separate_different_voices() {
+        let low = embed(&tone(&[120.0, 240.0, 480.0], 2.0)).unwrap();
+        let low_again = embed(&tone(&[118.0, 236.0, 472.0], 2.0)).unwrap();
+        let high = embed(&tone(&[310.0, 1250.0, 2900.0], 2.0)).unwrap();
+
+        assert_eq!(low.len(), EMBEDDING_LEN);
+        assert!(similarity(&low, &low_again) > similarity(&low, &high));
+    }
+}
@@ -0,0 +1,259 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+pub mod embedding;
+
+use crate::config::Config;
+use crate::transcribe::TranscriptionResult;
+
+/// Settings for recognising enrolled speakers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpeakerConfig {
+    /// Rename speaker labels to enrolled names when their voices match
+    pub recognize: bool,
+
+    /// Minimum similarity (0-1) for a label to be renamed
+    pub threshold: f32,
+
+    /// Seconds of each speaker's audio compared against the enrolled voices
+    pub max_sample_secs: f64,
+}
+
+impl Default for SpeakerConfig {
+    fn default() -> Self {
+        Self {
+            recognize: true,
+            threshold: 0.9,
+            max_sample_secs: 60.0,
+        }
+    }
+}
+
+/// An enrolled speaker's voiceprint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeakerProfile {
+    /// Name used in transcripts
+    pub name: String,
+
+    /// Voice embedding averaged over the enrollment samples
+    pub embedding: Vec<f32>,
+
+    /// Number of samples the embedding was built from
+    pub samples: usize,
+
+    /// When the speaker was enrolled
+    pub enrolled_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Enrolled speakers stored as one JSON file per speaker
+pub struct SpeakerStore {
+    dir: PathBuf,
+}
+
+impl SpeakerStore {
+    /// Open the store in the application data directory
+    pub fn open() -> Result<Self> {
+        Ok(Self::at(Config::data_dir()?.join("speakers")))
+    }
+
+    /// Open a store in a specific directory
+    pub fn at(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Build a voiceprint from sample recordings and save it under `name`
+    pub async fn enroll(&self, name: &str, samples: &[PathBuf]) -> Result<SpeakerProfile> {
+        if name.trim().is_empty() {
+            anyhow::bail!("Speaker name must not be empty");
+        }
+
+        let mut embeddings = Vec::new();
+        for sample in samples {
+            let audio = embedding::load_samples(sample).await?;
+            let voiceprint = embedding::embed(&audio)
+                .with_context(|| format!("Sample is too short to enroll: {}", sample.display()))?;
+            embeddings.push(voiceprint);
+        }
+
+        let profile = SpeakerProfile {
+            name: name.to_string(),
+            embedding: embedding::average(&embeddings)
+                .context("At least one voice sample is required")?,
+            samples: embeddings.len(),
+            enrolled_at: chrono::Utc::now(),
+        };
+
+        fs_err::create_dir_all(&self.dir)?;
+        fs_err::write(self.path(name), serde_json::to_string_pretty(&profile)?)?;
+
+        Ok(profile)
+    }
+
+    /// List enrolled speakers
+    pub fn list(&self) -> Result<Vec<SpeakerProfile>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut profiles = Vec::new();
+        for entry in fs_err::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+
+            let content = fs_err::read_to_string(&path)?;
+            match serde_json::from_str::<SpeakerProfile>(&content) {
+                Ok(profile) => profiles.push(profile),
+                Err(e) => tracing::warn!("Ignoring corrupt speaker profile {}: {}", path.display(), e),
+            }
+        }
+
+        profiles.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(profiles)
+    }
+
+    /// Remove an enrolled speaker
+    pub fn remove(&self, name: &str) -> Result<()> {
+        let path = self.path(name);
+        if !path.exists() {
+            anyhow::bail!("No enrolled speaker named '{}'", name);
+        }
+
+        fs_err::remove_file(path)?;
+        Ok(())
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        let file_name: String = name
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        self.dir.join(format!("{}.json", file_name))
+    }
+}
+
+/// Rename speaker labels (spk_0, spk_1, ...) to the names of matching enrolled speakers
+///
+/// Returns the applied label-to-name mapping.
+pub async fn label_known_speakers(
+    result: &mut TranscriptionResult,
+    audio_path: &Path,
+    profiles: &[SpeakerProfile],
+    config: &SpeakerConfig,
+) -> Result<HashMap<String, String>> {
+    if profiles.is_empty() || result.segments.iter().all(|s| s.speaker_id.is_none()) {
+        return Ok(HashMap::new());
+    }
+
+    let audio = embedding::load_samples(audio_path).await?;
+
+    // Gather up to max_sample_secs of audio per label
+    let mut voices: Vec<(String, Vec<f32>)> = Vec::new();
+    for segment in &result.segments {
+        let Some(label) = &segment.speaker_id else {
+            continue;
+        };
+
+        let index = match voices.iter().position(|(l, _)| l == label) {
+            Some(index) => index,
+            None => {
+                voices.push((label.clone(), Vec::new()));
+                voices.len() - 1
+            }
+        };
+        let collected = &mut voices[index].1;
+        if collected.len() as f64 >= config.max_sample_secs * embedding::SAMPLE_RATE as f64 {
+            continue;
+        }
+
+        let start = ((segment.start_time * embedding::SAMPLE_RATE as f64) as usize).min(audio.len());
+        let end = ((segment.end_time * embedding::SAMPLE_RATE as f64) as usize).min(audio.len());
+        collected.extend_from_slice(&audio[start..end]);
+    }
+
+    let voiceprints: Vec<(String, Vec<f32>)> = voices
+        .into_iter()
+        .filter_map(|(label, samples)| embedding::embed(&samples).map(|e| (label, e)))
+        .collect();
+
+    let mapping = match_speakers(&voiceprints, profiles, config.threshold);
+
+    for segment in &mut result.segments {
+        if let Some(name) = segment.speaker_id.as_ref().and_then(|label| mapping.get(label)) {
+            segment.speaker_id = Some(name.clone());
+        }
+    }
+    for word in result.words.iter_mut().flatten() {
+        if let Some(name) = word.speaker_id.as_ref().and_then(|label| mapping.get(label)) {
+            word.speaker_id = Some(name.clone());
+        }
+    }
+
+    Ok(mapping)
+}
+
+/// Pair speaker labels with enrolled speakers, best matches first, each used at most once
+fn match_speakers(
+    voiceprints: &[(String, Vec<f32>)],
+    profiles: &[SpeakerProfile],
+    threshold: f32,
+) -> HashMap<String, String> {
+    let mut candidates: Vec<(f32, &str, &str)> = Vec::new();
+    for (label, voiceprint) in voiceprints {
+        for profile in profiles {
+            let score = embedding::similarity(voiceprint, &profile.embedding);
+            if score >= threshold {
+                candidates.push((score, label, &profile.name));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut mapping = HashMap::new();
+    let mut used_names = HashSet::new();
+    for (score, label, name) in candidates {
+        if mapping.contains_key(label) || used_names.contains(name) {
+            continue;
+        }
+
+        tracing::info!("Recognised {} as {} (similarity {:.2})", label, name, score);
+        mapping.insert(label.to_string(), name.to_string());
+        used_names.insert(name);
+    }
+
+    mapping
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str, embedding: Vec<f32>) -> SpeakerProfile {
+        SpeakerProfile {
+            name: name.to_string(),
+            embedding,
+            samples: 1,
+            enrolled_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_match_speakers_is_one_to_one() {
+        let profiles = vec![profile("Alice", vec![1.0, 0.0]), profile("Bob", vec![0.0, 1.0])];
+        let voiceprints = vec![
+            ("spk_0".to_string(), vec![0.1, 1.0]),
+            ("spk_1".to_string(), vec![0.2, 1.0]),
+            ("spk_2".to_string(), vec![1.0, 0.05]),
+        ];
+
+        let mapping = match_speakers(&voiceprints, &profiles, 0.9);
+
+        assert_eq!(mapping.get("spk_0").map(String::as_str), Some("Bob"));
+        assert_eq!(mapping.get("spk_1"), None);
+        assert_eq!(mapping.get("spk_2").map(String::as_str), Some("Alice"));
+    }
+}
@@ -0,0 +1,220 @@
+use anyhow::{Context, Result};
+use aws_sdk_bedrockruntime::error::ProvideErrorMetadata;
+use aws_sdk_bedrockruntime::types::{
+    ContentBlock, ConversationRole, InferenceConfiguration, Message, StopReason, SystemContentBlock,
+};
+use clap::ValueEnum;
+use indicatif::{ProgressBar, ProgressStyle};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::cli::OutputFormat;
+use crate::config::Config;
+use crate::output::{self, RenderOptions};
+use crate::transcribe::TranscriptionResult;
+
+/// Bedrock can take a while on long transcripts
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+
+const INSTRUCTIONS: &str = "You summarize transcripts of recordings. Reply with only a JSON object with the keys \
+    executive_summary (one paragraph), key_points (a list of short strings) and action_items (a list of short \
+    strings naming who does what, empty when there are none). Write in the language of the transcript.";
+
+/// The Amazon Bedrock model `summarize` uses
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SummarizeConfig {
+    /// Bedrock model or inference profile ID; it must support the Converse API
+    pub model_id: String,
+
+    /// Region Bedrock is called in (defaults to aws.region)
+    pub region: Option<String>,
+
+    /// Most tokens the model may write
+    pub max_tokens: u32,
+
+    /// Transcripts longer than this are cut before they are sent, to stay within the model's context
+    pub max_transcript_chars: usize,
+}
+
+impl Default for SummarizeConfig {
+    fn default() -> Self {
+        Self {
+            model_id: "amazon.nova-lite-v1:0".to_string(),
+            region: None,
+            max_tokens: 2048,
+            max_transcript_chars: 300_000,
+        }
+    }
+}
+
+/// What a model made of a transcript
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptSummary {
+    pub executive_summary: String,
+    #[serde(default)]
+    pub key_points: Vec<String>,
+    #[serde(default)]
+    pub action_items: Vec<String>,
+    /// Bedrock model that wrote it
+    #[serde(default)]
+    pub model: String,
+}
+
+/// How `summarize` writes the transcript with its summary
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum SummarizeFormat {
+    /// The transcript followed by Summary, Key points and Action items sections
+    Markdown,
+    /// The result JSON with a `summary` object added
+    Json,
+}
+
+/// Asks a Bedrock model for summaries
+pub struct Summarizer {
+    client: aws_sdk_bedrockruntime::Client,
+    settings: SummarizeConfig,
+}
+
+impl Summarizer {
+    pub async fn new(config: &Config) -> Result<Self> {
+        if config.app.demo {
+            anyhow::bail!("summarize calls Amazon Bedrock, so it is not available with --demo");
+        }
+        let settings = config.summarize.clone();
+        let timeouts = aws_sdk_bedrockruntime::config::timeout::TimeoutConfig::builder()
+            .operation_timeout(REQUEST_TIMEOUT)
+            .build();
+        let mut bedrock = aws_sdk_bedrockruntime::config::Builder::from(&config.aws_sdk_config().await)
+            .timeout_config(timeouts);
+        if let Some(region) = &settings.region {
+            bedrock = bedrock.region(aws_types::region::Region::new(region.clone()));
+        }
+
+        Ok(Self {
+            client: aws_sdk_bedrockruntime::Client::from_conf(bedrock.build()),
+            settings,
+        })
+    }
+
+    pub async fn summarize(&self, result: &TranscriptionResult) -> Result<TranscriptSummary> {
+        let transcript = output::render(result, &OutputFormat::Text, &RenderOptions::default())?;
+        let transcript = match transcript.char_indices().nth(self.settings.max_transcript_chars) {
+            Some((cut, _)) => {
+                tracing::warn!(
+                    "Transcript is longer than summarize.max_transcript_chars ({}); only its start is summarized",
+                    self.settings.max_transcript_chars
+                );
+                &transcript[..cut]
+            }
+            None => transcript.as_str(),
+        };
+        let title = result.audio_info.title.as_deref().unwrap_or("(untitled)");
+
+        let message = Message::builder()
+            .role(ConversationRole::User)
+            .content(ContentBlock::Text(format!("Title: {}\n\nTranscript:\n{}", title, transcript)))
+            .build()?;
+        let inference = InferenceConfiguration::builder()
+            .max_tokens(self.settings.max_tokens.try_into().unwrap_or(i32::MAX))
+            .temperature(0.2)
+            .build();
+
+        let progress = crate::progress::add(ProgressBar::new_spinner().with_style(
+            ProgressStyle::default_spinner().template("{spinner:.green} [{elapsed_precise}] {msg}").unwrap()
+        ));
+        progress.set_message(format!("Summarizing with {}...", self.settings.model_id));
+        progress.enable_steady_tick(Duration::from_millis(250));
+
+        let response = self.client
+            .converse()
+            .model_id(&self.settings.model_id)
+            .system(SystemContentBlock::Text(INSTRUCTIONS.to_string()))
+            .messages(message)
+            .inference_config(inference)
+            .send()
+            .await
+            .map_err(|e| {
+                let hint = match e.code() {
+                    Some("AccessDeniedException") => " (is access to the model enabled in the Bedrock console?)",
+                    _ => "",
+                };
+                anyhow::Error::new(e).context(format!("Amazon Bedrock failed{}", hint))
+            })?;
+        progress.finish_and_clear();
+
+        if *response.stop_reason() == StopReason::MaxTokens {
+            anyhow::bail!("The summary was cut off; raise summarize.max_tokens");
+        }
+        let reply: String = response
+            .output()
+            .and_then(|output| output.as_message().ok())
+            .map(|message| message.content().iter().filter_map(|part| part.as_text().ok()).map(String::as_str).collect())
+            .unwrap_or_default();
+
+        let mut summary = parse_reply(&reply)?;
+        summary.model = self.settings.model_id.clone();
+        Ok(summary)
+    }
+}
+
+/// The summary in a model's reply; models sometimes wrap the JSON in prose or a code fence
+fn parse_reply(reply: &str) -> Result<TranscriptSummary> {
+    let object = reply
+        .find('{')
+        .zip(reply.rfind('}'))
+        .filter(|(start, end)| start < end)
+        .map(|(start, end)| &reply[start..=end])
+        .with_context(|| format!("The model did not reply with a summary: {}", reply.trim()))?;
+    serde_json::from_str(object).with_context(|| format!("The model's summary is not valid JSON: {}", object))
+}
+
+/// The transcript with its summary
+pub fn render(result: &TranscriptionResult, summary: &TranscriptSummary, format: SummarizeFormat) -> Result<String> {
+    match format {
+        SummarizeFormat::Json => {
+            let mut value = serde_json::to_value(result)?;
+            value["summary"] = serde_json::to_value(summary)?;
+            Ok(serde_json::to_string_pretty(&value)?)
+        }
+        SummarizeFormat::Markdown => {
+            let list = |items: &[String]| match items {
+                [] => "None.".to_string(),
+                items => items.iter().map(|item| format!("- {}", item)).collect::<Vec<_>>().join("\n"),
+            };
+            let title = result.audio_info.title.as_deref().unwrap_or("Transcript");
+            let transcript = output::render(result, &OutputFormat::Text, &RenderOptions::default())?;
+            Ok(format!(
+                "# {}\n\n{}\n\n## Summary\n\n{}\n\n## Key points\n\n{}\n\n## Action items\n\n{}\n",
+                title,
+                transcript.trim(),
+                summary.executive_summary.trim(),
+                list(&summary.key_points),
+                list(&summary.action_items)
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reply_and_render() {
+        let reply = "Here it is:\n```json\n{\"executive_summary\": \"The team agreed to ship.\", \"key_points\": [\"Ship Friday\"], \"action_items\": []}\n```";
+        let summary = parse_reply(reply).unwrap();
+        assert_eq!(summary.executive_summary, "The team agreed to ship.");
+        assert_eq!(summary.key_points, ["Ship Friday"]);
+        assert!(parse_reply("Sorry.").is_err());
+
+        let result = crate::demo::result("demo");
+        let markdown = render(&result, &summary, SummarizeFormat::Markdown).unwrap();
+        assert!(markdown.contains("## Summary\n\nThe team agreed to ship."));
+        assert!(markdown.ends_with("## Action items\n\nNone.\n"));
+
+        let json: serde_json::Value = serde_json::from_str(&render(&result, &summary, SummarizeFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["summary"]["key_points"][0], "Ship Friday");
+        assert_eq!(json["transcript"], result.transcript.as_str());
+    }
+}
@@ -0,0 +1,288 @@
+use anyhow::Result;
+
+use super::{TranscriptSegment, TranscriptionResult};
+use crate::output::formatters::WordTimestamp;
+
+/// Script words each row of the alignment may stray from the diagonal, at least
+const MIN_BAND: usize = 200;
+
+/// Seconds given to each script word that has to be placed without any heard word nearby
+const UNHEARD_WORD_SECS: f64 = 0.3;
+
+/// Share of the script that has to be heard before the alignment is trusted without a warning
+const MIN_MATCHED: f64 = 0.5;
+
+/// One step back through the alignment grid
+#[derive(Clone, Copy, PartialEq)]
+enum Step {
+    /// Script word and heard word line up (the same word, or a mishearing of it)
+    Diagonal,
+    /// Script word with no heard word
+    Script,
+    /// Heard word with no script word
+    Heard,
+}
+
+/// Time a human-written script against the words a backend heard in the same audio
+///
+/// The script's words are lined up with the heard words by edit distance (dynamic time
+/// warping over words, kept to a band around the diagonal so hour-long recordings stay
+/// cheap). Matched words take the heard timings, the rest are spread over the gaps between
+/// them. Each non-empty line of the script becomes a segment, split further at sentence ends
+/// when long; a segment's confidence is the share of its words that were heard as written.
+pub fn align(heard: &TranscriptionResult, script: &str) -> Result<TranscriptionResult> {
+    let heard_words = heard.words.as_deref().unwrap_or_default();
+    if heard_words.is_empty() {
+        anyhow::bail!("No words were recognised in the audio, so there is nothing to align the script to");
+    }
+    let cues = script_cues(script);
+    let script_words: Vec<(usize, &str)> = cues
+        .iter()
+        .enumerate()
+        .flat_map(|(cue, text)| text.split_whitespace().map(move |word| (cue, word)))
+        .collect();
+    if script_words.is_empty() {
+        anyhow::bail!("The script is empty");
+    }
+
+    let keys: Vec<String> = script_words.iter().map(|(_, word)| normalize(word)).collect();
+    let heard_keys: Vec<String> = heard_words.iter().map(|w| normalize(&w.word)).collect();
+    let pairs = best_path(&keys, &heard_keys);
+
+    let end_of_audio = heard.metadata.audio_duration.unwrap_or(heard_words[heard_words.len() - 1].end_time);
+    let mut times: Vec<Option<(f64, f64)>> = vec![None; script_words.len()];
+    let mut exact = vec![false; script_words.len()];
+    for &(script, heard_index) in &pairs {
+        let word = &heard_words[heard_index];
+        times[script] = Some((word.start_time, word.end_time));
+        exact[script] = keys[script] == heard_keys[heard_index];
+    }
+    fill_gaps(&mut times, &script_words, end_of_audio);
+
+    let words: Vec<WordTimestamp> = script_words
+        .iter()
+        .zip(&times)
+        .zip(&pairs_by_script(&pairs, script_words.len()))
+        .map(|(((_, word), time), heard_index)| {
+            let (start_time, end_time) = time.unwrap_or_default();
+            let heard_word = heard_index.map(|index| &heard_words[index]);
+            WordTimestamp {
+                word: word.to_string(),
+                start_time,
+                end_time,
+                confidence: heard_word.and_then(|w| w.confidence),
+                speaker_id: heard_word.and_then(|w| w.speaker_id.clone()),
+                alternatives: Vec::new(),
+            }
+        })
+        .collect();
+
+    let mut segments: Vec<TranscriptSegment> = Vec::with_capacity(cues.len());
+    for (cue, text) in cues.iter().enumerate() {
+        let indices: Vec<usize> = (0..script_words.len()).filter(|&i| script_words[i].0 == cue).collect();
+        let (Some(&first), Some(&last)) = (indices.first(), indices.last()) else {
+            continue;
+        };
+        // Cues never overlap, even where a mishearing pulled a word's timing back
+        let start_time = words[first].start_time.max(segments.last().map_or(0.0, |s| s.end_time));
+        let matched = indices.iter().filter(|&&i| exact[i]).count();
+        segments.push(TranscriptSegment {
+            start_time,
+            end_time: words[last].end_time.max(start_time),
+            text: text.clone(),
+            confidence: Some(matched as f64 / indices.len() as f64),
+            speaker_id: words[first].speaker_id.clone(),
+            toxicity: None,
+            language: None,
+            verbatim: None,
+        });
+    }
+
+    let matched = exact.iter().filter(|&&e| e).count() as f64 / exact.len() as f64;
+    if matched < MIN_MATCHED {
+        tracing::warn!(
+            "Only {:.0}% of the script was heard in the audio; check that they belong together",
+            matched * 100.0
+        );
+    }
+
+    let mut aligned = heard.clone();
+    aligned.transcript = cues.join(" ");
+    aligned.segments = segments;
+    aligned.words = Some(words);
+    aligned.metadata.confidence = Some(matched);
+    aligned.raw = None;
+    aligned.redactions.clear();
+    aligned.keywords = None;
+    Ok(aligned)
+}
+
+/// The script's lines, with long lines split into sentences
+fn script_cues(script: &str) -> Vec<String> {
+    let mut cues = Vec::new();
+    for line in script.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if line.chars().count() <= 2 * crate::output::formatters::SUBTITLE_LINE_WIDTH {
+            cues.push(line.to_string());
+            continue;
+        }
+        let mut sentence = String::new();
+        for token in line.split_whitespace() {
+            crate::utils::text::push_token(&mut sentence, token);
+            if token.ends_with(['.', '?', '!', '…']) {
+                cues.push(std::mem::take(&mut sentence));
+            }
+        }
+        if !sentence.is_empty() {
+            cues.push(sentence);
+        }
+    }
+    cues
+}
+
+/// Pairs of (script word, heard word) that line up on the cheapest alignment
+fn best_path(script: &[String], heard: &[String]) -> Vec<(usize, usize)> {
+    let (n, m) = (script.len(), heard.len());
+    let band = MIN_BAND.max(n.abs_diff(m)).max(n.max(m) / 10);
+    // Columns of row i run from lows[i] to highs[i], around where the diagonal crosses it
+    let lows: Vec<usize> = (0..=n).map(|i| (i * m / n).saturating_sub(band)).collect();
+    let highs: Vec<usize> = (0..=n).map(|i| (i * m / n + band).min(m)).collect();
+
+    // Only the steps are kept for every row; costs only for the row before
+    let mut previous: Vec<u32> = Vec::new();
+    let mut steps: Vec<Vec<Step>> = Vec::with_capacity(n + 1);
+    for i in 0..=n {
+        let width = highs[i] - lows[i] + 1;
+        let mut row_costs = vec![u32::MAX; width];
+        let mut row_steps = vec![Step::Diagonal; width];
+        for j in lows[i]..=highs[i] {
+            let cost_above = |column: usize| -> u32 {
+                if column < lows[i - 1] || column > highs[i - 1] {
+                    u32::MAX
+                } else {
+                    previous[column - lows[i - 1]]
+                }
+            };
+            let best = if i == 0 {
+                (j as u32, Step::Heard)
+            } else {
+                let diagonal = (j > 0).then(|| {
+                    let substitution = u32::from(script[i - 1] != heard[j - 1]);
+                    (cost_above(j - 1).saturating_add(substitution), Step::Diagonal)
+                });
+                let script_only = Some((cost_above(j).saturating_add(1), Step::Script));
+                let heard_only = (j > lows[i]).then(|| (row_costs[j - 1 - lows[i]].saturating_add(1), Step::Heard));
+                // Ties go to the diagonal, which comes first
+                [diagonal, script_only, heard_only]
+                    .into_iter()
+                    .flatten()
+                    .fold((u32::MAX, Step::Script), |best, candidate| if candidate.0 < best.0 { candidate } else { best })
+            };
+            row_costs[j - lows[i]] = best.0;
+            row_steps[j - lows[i]] = best.1;
+        }
+        previous = row_costs;
+        steps.push(row_steps);
+    }
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (n, m.clamp(lows[n], highs[n]));
+    while i > 0 {
+        match steps[i][j - lows[i]] {
+            Step::Diagonal => {
+                pairs.push((i - 1, j - 1));
+                i -= 1;
+                j -= 1;
+            }
+            Step::Script => i -= 1,
+            Step::Heard => j -= 1,
+        }
+    }
+    pairs.reverse();
+    pairs
+}
+
+/// The heard word each script word lined up with, if any
+fn pairs_by_script(pairs: &[(usize, usize)], len: usize) -> Vec<Option<usize>> {
+    let mut by_script = vec![None; len];
+    for &(script, heard) in pairs {
+        by_script[script] = Some(heard);
+    }
+    by_script
+}
+
+/// Spread script words that lined up with nothing over the time between their neighbours
+///
+/// Time is shared by length, so longer words get longer. Words before the first or after
+/// the last timed word get a fixed duration each, within the audio.
+fn fill_gaps(times: &mut [Option<(f64, f64)>], script_words: &[(usize, &str)], end_of_audio: f64) {
+    let mut index = 0;
+    while index < times.len() {
+        if times[index].is_some() {
+            index += 1;
+            continue;
+        }
+        let run_end = (index..times.len()).find(|&i| times[i].is_some()).unwrap_or(times.len());
+        let count = run_end - index;
+        let before = index.checked_sub(1).and_then(|i| times[i]).map(|(_, end)| end);
+        let after = times.get(run_end).copied().flatten().map(|(start, _)| start);
+        let (from, to) = match (before, after) {
+            (Some(from), Some(to)) => (from, to.max(from)),
+            (None, Some(to)) => ((to - count as f64 * UNHEARD_WORD_SECS).max(0.0), to),
+            (Some(from), None) => (from, (from + count as f64 * UNHEARD_WORD_SECS).min(end_of_audio).max(from)),
+            (None, None) => (0.0, end_of_audio),
+        };
+
+        let lengths: Vec<f64> = script_words[index..run_end].iter().map(|(_, w)| w.chars().count() as f64 + 1.0).collect();
+        let total: f64 = lengths.iter().sum();
+        let mut start = from;
+        for (offset, length) in lengths.iter().enumerate() {
+            let end = start + (to - from) * length / total;
+            times[index + offset] = Some((start, end));
+            start = end;
+        }
+        index = run_end;
+    }
+}
+
+fn normalize(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_align_script_to_heard_words() {
+        let mut heard = crate::transcribe::fixture::result(&[(1.0, 11.8, "the quick brown fox jumped over the um dog. Good night.")]);
+        // "jumps" was misheard and "lazy" not heard at all; "um" is not in the script
+        let spoken = ["the", "quick", "brown", "fox", "jumped", "over", "the", "um", "dog.", "Good", "night."];
+        heard.words = Some(
+            spoken
+                .iter()
+                .enumerate()
+                .map(|(i, word)| WordTimestamp {
+                    word: word.to_string(),
+                    start_time: 1.0 + i as f64,
+                    end_time: 1.8 + i as f64,
+                    confidence: Some(0.9),
+                    speaker_id: None,
+                    alternatives: Vec::new(),
+                })
+                .collect(),
+        );
+
+        let script = "The quick brown fox jumps over the lazy dog.\n\n  Good night.\n";
+        let aligned = align(&heard, script).unwrap();
+        let cues: Vec<(&str, f64, f64)> =
+            aligned.segments.iter().map(|s| (s.text.as_str(), s.start_time, s.end_time)).collect();
+        assert_eq!(cues, [("The quick brown fox jumps over the lazy dog.", 1.0, 9.8), ("Good night.", 10.0, 11.8)]);
+        // Seven of nine words heard as written
+        assert_eq!(aligned.segments[0].confidence, Some(7.0 / 9.0));
+
+        let words = aligned.words.unwrap();
+        assert_eq!((words[4].word.as_str(), words[4].start_time), ("jumps", 5.0));
+        // "lazy" fills the time of the filler it lined up with
+        assert_eq!((words[7].word.as_str(), words[7].start_time, words[7].end_time), ("lazy", 8.0, 8.8));
+    }
+}
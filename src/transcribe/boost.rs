@@ -0,0 +1,217 @@
+use anyhow::{Context, Result};
+use regex::{NoExpand, Regex, RegexBuilder};
+use std::path::Path;
+
+use super::TranscriptionResult;
+
+/// Phrases a live run should get right: names, products, jargon of the event
+///
+/// Whisper is primed with them as a prompt, and every chunk's text is corrected to their
+/// spelling afterwards, so "rust scribe" or "Rust-Scribe" come out as "RustScribe".
+#[derive(Debug, Clone, Default)]
+pub struct PhraseBoost {
+    phrases: Vec<(Regex, String)>,
+}
+
+impl PhraseBoost {
+    pub fn new(phrases: &[String]) -> Result<Self> {
+        let phrases = phrases
+            .iter()
+            .map(|phrase| phrase.trim())
+            .filter(|phrase| !phrase.is_empty())
+            .map(|phrase| {
+                let regex = RegexBuilder::new(&spelling_pattern(phrase))
+                    .case_insensitive(true)
+                    .build()
+                    .with_context(|| format!("Invalid boost phrase: {}", phrase))?;
+                Ok((regex, phrase.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { phrases })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.phrases.is_empty()
+    }
+
+    /// Whisper prompt listing the phrases
+    pub fn prompt(&self) -> Option<String> {
+        let phrases: Vec<&str> = self.phrases.iter().map(|(_, phrase)| phrase.as_str()).collect();
+        (!phrases.is_empty()).then(|| format!("Glossary: {}.", phrases.join(", ")))
+    }
+
+    /// Spell the phrases the way they were given
+    pub fn apply_to_text(&self, text: &str) -> String {
+        self.phrases
+            .iter()
+            .fold(text.to_string(), |text, (regex, phrase)| {
+                regex.replace_all(&text, NoExpand(phrase)).into_owned()
+            })
+    }
+
+    /// Correct the transcript and its segments; words are single tokens and keep their spelling
+    pub fn apply(&self, result: &mut TranscriptionResult) {
+        if self.is_empty() {
+            return;
+        }
+
+        result.transcript = self.apply_to_text(&result.transcript);
+        for segment in &mut result.segments {
+            segment.text = self.apply_to_text(&segment.text);
+        }
+    }
+}
+
+/// Phrases given with `--live-boost` (comma-separated) and in a file, one per line (`#` starts a comment)
+pub fn load_phrases(phrases: &[String], file: Option<&Path>) -> Result<Vec<String>> {
+    let mut all: Vec<String> = phrases
+        .iter()
+        .flat_map(|phrases| phrases.split(','))
+        .map(|phrase| phrase.trim().to_string())
+        .collect();
+    if let Some(file) = file {
+        let content = fs_err::read_to_string(file)?;
+        all.extend(
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_string),
+        );
+    }
+    all.retain(|phrase| !phrase.is_empty());
+    all.dedup();
+    Ok(all)
+}
+
+/// Matches a phrase in any case, with its words (and camel-case parts) run together or
+/// split by a space or hyphen
+fn spelling_pattern(phrase: &str) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for word in phrase.split(|c: char| c.is_whitespace() || c == '-').filter(|word| !word.is_empty()) {
+        let mut part = String::new();
+        let mut previous: Option<char> = None;
+        for c in word.chars() {
+            if previous.is_some_and(|p| p.is_lowercase()) && c.is_uppercase() {
+                parts.push(std::mem::take(&mut part));
+            }
+            part.push(c);
+            previous = Some(c);
+        }
+        parts.push(part);
+    }
+
+    let body = parts.iter().map(|part| regex::escape(part)).collect::<Vec<_>>().join(r"[\s-]?");
+    let starts_with_word = phrase.chars().next().is_some_and(|c| c.is_alphanumeric());
+    let ends_with_word = phrase.chars().last().is_some_and(|c| c.is_alphanumeric());
+    format!(
+        "{}{}{}",
+        if starts_with_word { r"\b" } else { "" },
+        body,
+        if ends_with_word { r"\b" } else { "" }
+    )
+}
+
+/// Holds back the unfinished sentence at the end of a live chunk, so captions are only shown
+/// once they will not change
+///
+/// A chunk usually ends mid-sentence; the tail is shown with the next chunk instead. A tail is
+/// never held back for more than one chunk, so a speaker who never ends a sentence still shows up.
+#[derive(Debug, Default)]
+pub struct Stabilizer {
+    pending: Vec<String>,
+    pending_start: f64,
+}
+
+impl Stabilizer {
+    /// The stable text of a chunk starting `offset` seconds into the stream, and where it starts
+    pub fn push(&mut self, part: &TranscriptionResult, offset: f64) -> Option<(String, f64)> {
+        let texts: Vec<(f64, &str)> = part
+            .segments
+            .iter()
+            .map(|segment| (offset + segment.start_time, segment.text.trim()))
+            .filter(|(_, text)| !text.is_empty())
+            .collect();
+        let held = !self.pending.is_empty();
+        let start = if held {
+            self.pending_start
+        } else {
+            texts.first().map_or(offset, |(start, _)| *start)
+        };
+
+        // Everything up to the last finished sentence is stable
+        let stable = match texts.iter().rposition(|(_, text)| ends_sentence(text)) {
+            Some(last) => last + 1,
+            None if held => texts.len(),
+            None => 0,
+        };
+        let (shown, tail) = texts.split_at(stable);
+
+        let mut text = std::mem::take(&mut self.pending);
+        text.extend(shown.iter().map(|(_, text)| text.to_string()));
+        self.pending = tail.iter().map(|(_, text)| text.to_string()).collect();
+        self.pending_start = tail.first().map_or(0.0, |(start, _)| *start);
+
+        (!text.is_empty()).then(|| (text.join(" "), start))
+    }
+
+    /// Whatever is still held back once the stream has ended
+    pub fn finish(&mut self) -> Option<(String, f64)> {
+        let text = std::mem::take(&mut self.pending);
+        (!text.is_empty()).then(|| (text.join(" "), self.pending_start))
+    }
+}
+
+fn ends_sentence(text: &str) -> bool {
+    text.trim_end_matches(['"', '\'', ')', '»', '”']).ends_with(['.', '!', '?', '…', '。', '？', '！'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcribe::TranscriptSegment;
+
+    fn part(texts: &[&str]) -> TranscriptionResult {
+        TranscriptionResult {
+            transcript: texts.join(" "),
+            segments: texts
+                .iter()
+                .enumerate()
+                .map(|(i, text)| TranscriptSegment {
+                    start_time: i as f64 * 2.0,
+                    end_time: i as f64 * 2.0 + 2.0,
+                    text: text.to_string(),
+                    confidence: None,
+                    speaker_id: None,
+                    toxicity: None,
+                    language: None,
+                    verbatim: None,
+                })
+                .collect(),
+            words: None,
+            ..crate::demo::result("demo")
+        }
+    }
+
+    #[test]
+    fn test_boost_and_stabilize() {
+        let boost = PhraseBoost::new(&["RustScribe".to_string(), "Kube Con".to_string()]).unwrap();
+        assert_eq!(
+            boost.apply_to_text("welcome to rust scribe at kubecon, Rust-Scribe rocks; rustscriber"),
+            "welcome to RustScribe at Kube Con, RustScribe rocks; rustscriber"
+        );
+        assert_eq!(boost.prompt().unwrap(), "Glossary: RustScribe, Kube Con.");
+
+        let mut stabilizer = Stabilizer::default();
+        let first = stabilizer.push(&part(&["Hello all.", "Today we"]), 0.0);
+        assert_eq!(first, Some(("Hello all.".to_string(), 0.0)));
+
+        // The held tail comes first in the next chunk, even without a sentence end
+        let second = stabilizer.push(&part(&["talk about", "captions"]), 10.0);
+        assert_eq!(second, Some(("Today we talk about captions".to_string(), 2.0)));
+        assert_eq!(stabilizer.push(&part(&["Thanks!", "Bye"]), 20.0), Some(("Thanks!".to_string(), 20.0)));
+        assert_eq!(stabilizer.finish(), Some(("Bye".to_string(), 22.0)));
+        assert_eq!(stabilizer.finish(), None);
+    }
+}
@@ -0,0 +1,119 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+use super::{merge, TranscriptionResult};
+
+/// Speaker IDs of the left and right channel
+pub const CHANNEL_SPEAKERS: [&str; 2] = ["left", "right"];
+
+/// Number of channels of the first audio stream of a file
+pub async fn channel_count(path: &Path) -> Result<u32> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v", "quiet",
+            "-select_streams", "a:0",
+            "-show_entries", "stream=channels",
+            "-of", "csv=p=0",
+            &path.to_string_lossy(),
+        ])
+        .output()
+        .await
+        .context("Failed to run ffprobe")?;
+    crate::logs::check("ffprobe", &output, "Failed to read the channels with ffprobe")?;
+
+    let channels = String::from_utf8_lossy(&output.stdout);
+    channels
+        .trim()
+        .parse()
+        .with_context(|| format!("ffprobe did not report the channels of {}", path.display()))
+}
+
+/// Write the left and right channel of a stereo file to mono WAV files in `dir`
+pub async fn split_stereo(source: &Path, dir: &Path) -> Result<[PathBuf; 2]> {
+    let targets = CHANNEL_SPEAKERS.map(|side| dir.join(format!("channel_{}_{}.wav", side, uuid::Uuid::new_v4())));
+    let output = Command::new("ffmpeg")
+        .args([
+            "-i", &source.to_string_lossy(),
+            "-filter_complex", "[0:a:0]channelsplit=channel_layout=stereo[left][right]",
+            "-map", "[left]", "-c:a", "pcm_s16le", &targets[0].to_string_lossy(),
+            "-map", "[right]", "-c:a", "pcm_s16le", &targets[1].to_string_lossy(),
+            "-y",
+        ])
+        .output()
+        .await
+        .context("Failed to run ffmpeg")?;
+    crate::logs::check("ffmpeg", &output, "Failed to split the channels with ffmpeg")?;
+
+    Ok(targets)
+}
+
+/// Combine per-channel results into one, ordered by time, with each channel as its speaker
+///
+/// The channels play at the same time, so nothing is shifted; the transcript is rebuilt from
+/// the interleaved segments. Chapters come from the source, so only the first part's are kept.
+pub fn interleave(mut parts: Vec<TranscriptionResult>) -> Result<TranscriptionResult> {
+    for (part, speaker) in parts.iter_mut().zip(CHANNEL_SPEAKERS) {
+        for segment in &mut part.segments {
+            segment.speaker_id = Some(speaker.to_string());
+        }
+        for word in part.words.iter_mut().flatten() {
+            word.speaker_id = Some(speaker.to_string());
+        }
+    }
+    let chapters = parts.first().map(|part| part.chapters.clone()).unwrap_or_default();
+    for part in parts.iter_mut().skip(1) {
+        part.chapters.clear();
+    }
+
+    let offsets = vec![0.0; parts.len()];
+    let mut result = merge::merge_results(parts, &offsets, true)?;
+    result.chapters = chapters;
+    result.transcript = result
+        .segments
+        .iter()
+        .map(|s| s.text.trim())
+        .collect::<Vec<_>>()
+        .join(" ");
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn part(texts: &[(f64, &str)]) -> TranscriptionResult {
+        let demo = crate::demo::result("demo");
+        let template = demo.segments[0].clone();
+        TranscriptionResult {
+            transcript: String::new(),
+            segments: texts
+                .iter()
+                .map(|&(start_time, text)| crate::transcribe::TranscriptSegment {
+                    start_time,
+                    end_time: start_time + 1.0,
+                    text: text.to_string(),
+                    speaker_id: Some("spk_0".to_string()),
+                    ..template.clone()
+                })
+                .collect(),
+            words: None,
+            ..demo
+        }
+    }
+
+    #[test]
+    fn test_interleave() {
+        let caller = part(&[(0.0, "Hello?"), (4.0, "I have a question.")]);
+        let agent = part(&[(1.5, "Support, how can I help?")]);
+
+        let result = interleave(vec![caller, agent]).unwrap();
+        let turns: Vec<(&str, &str)> = result
+            .segments
+            .iter()
+            .map(|s| (s.speaker_id.as_deref().unwrap(), s.text.as_str()))
+            .collect();
+        assert_eq!(turns, [("left", "Hello?"), ("right", "Support, how can I help?"), ("left", "I have a question.")]);
+        assert_eq!(result.transcript, "Hello? Support, how can I help? I have a question.");
+    }
+}
@@ -0,0 +1,76 @@
+use aws_sdk_transcribe::types::TranscriptionJob;
+use serde::Serialize;
+
+/// A language identified in an audio sample
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DetectedLanguage {
+    /// Language code as the backend reports it (`en-US` for AWS, `en` for Whisper)
+    pub language: String,
+
+    /// How sure the backend is, from 0 to 1, if it says
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+
+    /// Seconds of the sample spoken in this language, when several were identified
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f64>,
+}
+
+/// Languages a finished AWS language identification job found, most likely first
+///
+/// AWS only scores the dominant language; with multiple-language identification the
+/// others come with the time spoken in each instead.
+pub fn from_aws_job(job: &TranscriptionJob) -> Vec<DetectedLanguage> {
+    let score = job.identified_language_score().map(f64::from);
+    let dominant = job.language_code().map(|code| code.as_str().to_string());
+
+    let mut languages: Vec<DetectedLanguage> = job
+        .language_codes()
+        .iter()
+        .filter_map(|item| {
+            let language = item.language_code()?.as_str().to_string();
+            Some(DetectedLanguage {
+                confidence: score.filter(|_| Some(&language) == dominant.as_ref()),
+                duration: item.duration_in_seconds().map(f64::from),
+                language,
+            })
+        })
+        .collect();
+    languages.sort_by(|a, b| b.duration.unwrap_or(0.0).total_cmp(&a.duration.unwrap_or(0.0)));
+
+    if let Some(language) = dominant.filter(|code| !languages.iter().any(|l| &l.language == code)) {
+        languages.insert(0, DetectedLanguage { language, confidence: score, duration: None });
+    }
+    languages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_transcribe::types::{LanguageCode, LanguageCodeItem};
+
+    #[test]
+    fn test_from_aws_job() {
+        let single = TranscriptionJob::builder()
+            .language_code(LanguageCode::DeDe)
+            .identified_language_score(0.93)
+            .build();
+        let languages = from_aws_job(&single);
+        assert_eq!(languages.len(), 1);
+        assert_eq!(languages[0].language, "de-DE");
+        assert!((languages[0].confidence.unwrap() - 0.93).abs() < 1e-6);
+
+        let item = |code, seconds| LanguageCodeItem::builder().language_code(code).duration_in_seconds(seconds).build();
+        let mixed = TranscriptionJob::builder()
+            .language_code(LanguageCode::EnUs)
+            .identified_language_score(0.8)
+            .language_codes(item(LanguageCode::EsUs, 20.0))
+            .language_codes(item(LanguageCode::EnUs, 95.0))
+            .build();
+        let languages: Vec<(String, Option<f64>)> = from_aws_job(&mixed)
+            .into_iter()
+            .map(|l| (l.language, l.duration))
+            .collect();
+        assert_eq!(languages, vec![("en-US".to_string(), Some(95.0)), ("es-US".to_string(), Some(20.0))]);
+    }
+}
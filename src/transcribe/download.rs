@@ -0,0 +1,286 @@
+use anyhow::{Context, Result};
+use futures_util::StreamExt;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_RANGE, CONTENT_TYPE, ETAG, IF_RANGE, LAST_MODIFIED, RANGE};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use sha2::{Digest, Sha256};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::progress::Progress;
+
+/// How many times an interrupted download is resumed before giving up
+const MAX_RESUME_ATTEMPTS: u32 = 5;
+
+/// Stream a download to disk, resuming from the last written byte if the connection drops
+///
+/// Resumed requests carry `If-Range` with the original validator, so a file that changed
+/// on the server is downloaded again from the start instead of being stitched together.
+/// The finished file must match the announced length, and the SHA-256 digest the server
+/// announced for it (`x-amz-checksum-sha256`, `Repr-Digest` or `Digest`) if there is one.
+/// Other digests, such as `Content-MD5`, are not checked.
+///
+/// `request` is sent again, with a `Range`, for every resume, so it must carry what the
+/// first request needed to be let in (cookies) but none of its conditions.
+pub(crate) async fn stream_to_file(
+    request: RequestBuilder,
+    response: Response,
+    path: &Path,
+    progress: &Progress,
+) -> Result<()> {
+    // Weak ETags are not allowed in If-Range, fall back to the modification date
+    let validator = response
+        .headers()
+        .get(ETAG)
+        .filter(|etag| !etag.as_bytes().starts_with(b"W/"))
+        .or_else(|| response.headers().get(LAST_MODIFIED))
+        .cloned();
+    let mut expected = response.content_length();
+    let mut digest = announced_sha256(response.headers());
+    progress.set_length(expected.unwrap_or(0));
+
+    let mut file = fs_err::File::create(path)?;
+    let mut downloaded = 0u64;
+    let mut attempts = 0;
+    let mut response = response;
+
+    loop {
+        let interruption = match write_body(response, &mut file, &mut downloaded, progress).await {
+            Ok(()) => match expected {
+                Some(total) if downloaded < total => {
+                    anyhow::anyhow!("connection closed after {} of {} bytes", downloaded, total)
+                }
+                Some(total) if downloaded > total => {
+                    anyhow::bail!("Downloaded {} bytes but the server announced {}", downloaded, total)
+                }
+                _ => return verify_sha256(path, digest.as_deref()),
+            },
+            Err(e) => e,
+        };
+
+        tracing::warn!("Download interrupted ({:#}), resuming from byte {}", interruption, downloaded);
+        response = loop {
+            attempts += 1;
+            if attempts > MAX_RESUME_ATTEMPTS {
+                return Err(interruption)
+                    .with_context(|| format!("Download failed after {} resume attempts", MAX_RESUME_ATTEMPTS));
+            }
+
+            progress.set_message(format!("Resuming download (attempt {})...", attempts));
+            tokio::time::sleep(Duration::from_secs(1 << attempts)).await;
+
+            match resume_request(&request, downloaded, validator.as_ref()).await {
+                Ok(response) => break response,
+                Err(e) => tracing::warn!("Resume request failed: {:#}", e),
+            }
+        };
+
+        match response.status() {
+            StatusCode::PARTIAL_CONTENT => {
+                let start = response
+                    .headers()
+                    .get(CONTENT_RANGE)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(content_range_start);
+                if start != Some(downloaded) {
+                    anyhow::bail!("Server resumed the download at the wrong offset ({:?} instead of {})", start, downloaded);
+                }
+            }
+            StatusCode::OK => {
+                // A login or error page must not replace the audio
+                let is_page = response
+                    .headers()
+                    .get(CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .is_some_and(|content_type| content_type.starts_with("text/html"));
+                if is_page {
+                    anyhow::bail!("Server answered the resumed download with a web page; is a login needed (--cookies)?");
+                }
+                // Range ignored or the file changed since the first request: start over
+                tracing::warn!("Server does not support resuming this download, restarting it");
+                file.set_len(0)?;
+                file.seek(SeekFrom::Start(0))?;
+                downloaded = 0;
+                expected = response.content_length();
+                digest = announced_sha256(response.headers());
+                progress.set_length(expected.unwrap_or(0));
+                progress.set_position(0);
+            }
+            status => anyhow::bail!("Failed to resume download: HTTP {}", status),
+        }
+    }
+}
+
+/// Write a response body to the file, tracking progress
+async fn write_body(
+    response: Response,
+    file: &mut fs_err::File,
+    downloaded: &mut u64,
+    progress: &Progress,
+) -> Result<()> {
+    let mut stream = response.bytes_stream();
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        file.write_all(&chunk)?;
+        *downloaded += chunk.len() as u64;
+        progress.set_position(*downloaded);
+    }
+
+    file.flush()?;
+    Ok(())
+}
+
+/// Request the rest of a file starting at `offset`
+async fn resume_request(
+    request: &RequestBuilder,
+    offset: u64,
+    validator: Option<&HeaderValue>,
+) -> Result<Response> {
+    let mut request = request
+        .try_clone()
+        .context("Download request cannot be repeated")?
+        .header(RANGE, format!("bytes={}-", offset));
+    if let Some(validator) = validator {
+        request = request.header(IF_RANGE, validator.clone());
+    }
+
+    Ok(request.send().await?)
+}
+
+/// The SHA-256 a server announced for the whole file
+///
+/// S3's `x-amz-checksum-sha256` of multipart uploads (`<digest>-<parts>`) covers the parts'
+/// checksums rather than the file, so it is left out.
+fn announced_sha256(headers: &HeaderMap) -> Option<Vec<u8>> {
+    let amz = headers
+        .get("x-amz-checksum-sha256")
+        .and_then(|v| v.to_str().ok())
+        .filter(|value| !value.contains('-'));
+    // Repr-Digest: sha-256=:<base64>:, or the older Digest: SHA-256=<base64>
+    let http = ["repr-digest", "digest"]
+        .into_iter()
+        .filter_map(|name| headers.get(name)?.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|entry| entry.trim().split_once('='))
+        .find(|(algorithm, _)| algorithm.trim().eq_ignore_ascii_case("sha-256"))
+        .map(|(_, value)| value.trim().trim_matches(':'));
+    amz.or(http).and_then(|value| BASE64.decode(value).ok())
+}
+
+/// Check a finished download against the digest its server announced
+fn verify_sha256(path: &Path, expected: Option<&[u8]>) -> Result<()> {
+    let Some(expected) = expected else {
+        return Ok(());
+    };
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut fs_err::File::open(path)?, &mut hasher)?;
+    if hasher.finalize().as_slice() != expected {
+        anyhow::bail!("Downloaded file does not match the SHA-256 digest the server announced");
+    }
+    Ok(())
+}
+
+/// Parse the first byte position from a `Content-Range: bytes <start>-<end>/<total>` header
+fn content_range_start(header: &str) -> Option<u64> {
+    header
+        .strip_prefix("bytes ")?
+        .split('-')
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use reqwest::Client;
+    use tokio::net::TcpListener;
+
+    const BODY: &[u8] = b"0123456789abcdefghij";
+
+    /// A server that drops the first download after 8 bytes and sends the rest on a Range
+    /// request carrying the session cookie; any other request is answered with `refusal`.
+    /// `announce` is added to the headers of the first response.
+    async fn flaky_server(announce: String, refusal: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = vec![0; 4096];
+                let read = socket.read(&mut request).await.unwrap();
+                let request = String::from_utf8_lossy(&request[..read]).to_lowercase();
+                let head = if !request.contains("range:") {
+                    format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nETag: \"v1\"\r\n{}\r\n", BODY.len(), announce)
+                } else if request.contains("range: bytes=8-") && request.contains("cookie: session=abc") {
+                    format!("HTTP/1.1 206 Partial Content\r\nContent-Length: 12\r\nContent-Range: bytes 8-19/{}\r\n\r\n", BODY.len())
+                } else {
+                    let _ = socket.write_all(refusal.as_bytes()).await;
+                    continue;
+                };
+                let body = if request.contains("range:") { &BODY[8..] } else { &BODY[..8] };
+                let _ = socket.write_all(head.as_bytes()).await;
+                let _ = socket.write_all(body).await;
+            }
+        });
+        format!("http://{}/audio.mp3", address)
+    }
+
+    #[tokio::test]
+    async fn test_resume_keeps_cookies() {
+        let url = flaky_server(String::new(), "HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\n\r\n").await;
+        let client = Client::new();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audio.mp3");
+
+        let request = client.get(&url).header(reqwest::header::COOKIE, "session=abc");
+        let response = request.try_clone().unwrap().send().await.unwrap();
+        let progress = crate::progress::add(indicatif::ProgressBar::hidden());
+        stream_to_file(request, response, &path, &progress).await.unwrap();
+        assert_eq!(fs_err::read(&path).unwrap(), BODY);
+
+        // Without the cookie the resume is refused instead of saving whatever came back
+        let login = "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: 6\r\n\r\nlog in";
+        let url = flaky_server(String::new(), login).await;
+        let response = client.get(&url).send().await.unwrap();
+        assert!(stream_to_file(client.get(&url), response, &path, &progress).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resume_checks_digest() {
+        let sha256 = BASE64.encode(Sha256::digest(BODY));
+        let client = Client::new();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audio.mp3");
+        let progress = crate::progress::add(indicatif::ProgressBar::hidden());
+        let refusal = "HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\n\r\n";
+
+        for (announce, matches) in [
+            (format!("x-amz-checksum-sha256: {}\r\n", sha256), true),
+            (format!("Repr-Digest: sha-256=:{}:\r\n", sha256), true),
+            (format!("Digest: SHA-256={}\r\n", BASE64.encode(Sha256::digest(b"something else"))), false),
+        ] {
+            let url = flaky_server(announce.clone(), refusal).await;
+            let request = client.get(&url).header(reqwest::header::COOKIE, "session=abc");
+            let response = request.try_clone().unwrap().send().await.unwrap();
+            let outcome = stream_to_file(request, response, &path, &progress).await;
+            assert_eq!(outcome.is_ok(), matches, "{}", announce);
+        }
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-amz-checksum-sha256", HeaderValue::from_static("AAAA-3"));
+        assert_eq!(announced_sha256(&headers), None, "multipart checksums cover the parts");
+    }
+
+    #[test]
+    fn test_content_range_start() {
+        assert_eq!(content_range_start("bytes 1048576-2097151/2097152"), Some(1048576));
+        assert_eq!(content_range_start("bytes 0-99/*"), Some(0));
+        assert_eq!(content_range_start("bytes */2097152"), None);
+        assert_eq!(content_range_start("items 0-1/2"), None);
+    }
+}
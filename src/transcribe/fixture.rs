@@ -0,0 +1,20 @@
+//! A result for tests that also run without the `pipeline` feature, where `crate::demo` is not built
+
+use super::TranscriptionResult;
+use crate::output::formatters::format_srt_timestamp;
+
+/// `talk.srt` with one cue per `(start, end, text)`, read back the way `load_transcript` reads subtitles
+pub fn result(cues: &[(f64, f64, &str)]) -> TranscriptionResult {
+    let srt: String = cues
+        .iter()
+        .enumerate()
+        .map(|(i, &(start, end, text))| {
+            format!("{}\n{} --> {}\n{}\n\n", i + 1, format_srt_timestamp(start), format_srt_timestamp(end), text)
+        })
+        .collect();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("talk.srt");
+    fs_err::write(&path, srt).unwrap();
+    crate::output::load_transcript(&path).unwrap()
+}
@@ -0,0 +1,255 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::path::Path;
+use tokio::process::Command;
+
+use super::processor::ProcessedTranscription;
+use crate::output::formatters::WordTimestamp;
+
+/// Settings for hybrid mode (local Whisper draft, AWS re-transcription of weak spans)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HybridConfig {
+    /// Draft segments below this confidence are re-transcribed by AWS
+    pub confidence_threshold: f64,
+
+    /// Audio added before and after each span so AWS hears whole words
+    pub padding_secs: f64,
+
+    /// Low-confidence segments closer than this are sent as one span (fewer, cheaper jobs)
+    pub merge_gap_secs: f64,
+}
+
+impl Default for HybridConfig {
+    fn default() -> Self {
+        Self {
+            confidence_threshold: 0.6,
+            padding_secs: 0.5,
+            merge_gap_secs: 3.0,
+        }
+    }
+}
+
+/// A stretch of consecutive draft segments to re-transcribe
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    /// Draft segments covered by the span
+    pub segments: Range<usize>,
+
+    /// Start of the covered segments in seconds
+    pub start_time: f64,
+
+    /// End of the covered segments in seconds
+    pub end_time: f64,
+}
+
+/// Find runs of low-confidence draft segments, merging runs separated by short gaps
+pub fn low_confidence_spans(draft: &ProcessedTranscription, config: &HybridConfig) -> Vec<Span> {
+    let mut spans: Vec<Span> = Vec::new();
+
+    for (index, segment) in draft.segments.iter().enumerate() {
+        let weak = segment.confidence.is_none_or(|c| c < config.confidence_threshold);
+        if !weak {
+            continue;
+        }
+
+        match spans.last_mut() {
+            Some(span) if segment.start_time - span.end_time <= config.merge_gap_secs => {
+                span.segments.end = index + 1;
+                span.end_time = segment.end_time;
+            }
+            _ => spans.push(Span {
+                segments: index..index + 1,
+                start_time: segment.start_time,
+                end_time: segment.end_time,
+            }),
+        }
+    }
+
+    spans
+}
+
+/// Replace the draft text of a span with the words AWS recognised in the clip
+///
+/// `clip_start` is where the clip begins in the full recording. Words are assigned
+/// to the draft segment they fall into, so segment timing stays intact; words heard
+/// only in the padding are dropped, and segments AWS found no words for keep their draft.
+pub fn merge_span(draft: &mut ProcessedTranscription, span: &Span, clip_start: f64, clip: &ProcessedTranscription) {
+    let words: Vec<WordTimestamp> = clip
+        .words
+        .iter()
+        .flatten()
+        .map(|w| WordTimestamp {
+            start_time: w.start_time + clip_start,
+            end_time: w.end_time + clip_start,
+            ..w.clone()
+        })
+        .filter(|w| {
+            let middle = (w.start_time + w.end_time) / 2.0;
+            middle >= span.start_time && middle <= span.end_time
+        })
+        .collect();
+
+    if words.is_empty() {
+        return;
+    }
+
+    for index in span.segments.clone() {
+        let is_first = index == span.segments.start;
+        let next_start = draft.segments.get(index + 1).map(|s| s.start_time);
+        let is_last = index + 1 == span.segments.end;
+        let segment = &mut draft.segments[index];
+
+        let owned: Vec<&WordTimestamp> = words
+            .iter()
+            .filter(|w| {
+                let middle = (w.start_time + w.end_time) / 2.0;
+                (is_first || middle >= segment.start_time)
+                    && (is_last || next_start.is_none_or(|next| middle < next))
+            })
+            .collect();
+        if owned.is_empty() {
+            continue;
+        }
+
+        segment.text = owned.iter().map(|w| w.word.as_str()).collect::<Vec<_>>().join(" ");
+        let confidences: Vec<f64> = owned.iter().filter_map(|w| w.confidence).collect();
+        if !confidences.is_empty() {
+            segment.confidence = Some(confidences.iter().sum::<f64>() / confidences.len() as f64);
+        }
+    }
+
+    if let Some(draft_words) = &mut draft.words {
+        draft_words.retain(|w| {
+            let middle = (w.start_time + w.end_time) / 2.0;
+            middle < span.start_time || middle > span.end_time
+        });
+        draft_words.extend(words);
+        draft_words.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+    }
+
+    draft.transcript = draft
+        .segments
+        .iter()
+        .map(|s| s.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+}
+
+/// Cut `start..end` seconds out of a WAV file
+pub async fn cut_clip(source: &Path, start: f64, end: f64, target: &Path) -> Result<()> {
+    let output = Command::new("ffmpeg")
+        .args([
+            "-ss", &format!("{:.3}", start),
+            "-to", &format!("{:.3}", end),
+            "-i", &source.to_string_lossy(),
+            "-c:a", "pcm_s16le",
+            "-y",
+            &target.to_string_lossy(),
+        ])
+        .output()
+        .await
+        .context("Failed to run ffmpeg")?;
+
+    crate::logs::check("ffmpeg", &output, "Failed to cut audio span with ffmpeg")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcribe::{TranscriptSegment, TranscriptionMetadata};
+
+    fn segment(start_time: f64, end_time: f64, text: &str, confidence: f64) -> TranscriptSegment {
+        TranscriptSegment {
+            start_time,
+            end_time,
+            text: text.to_string(),
+            confidence: Some(confidence),
+            speaker_id: None,
+            toxicity: None,
+            language: None,
+            verbatim: None,
+        }
+    }
+
+    fn word(word: &str, start_time: f64, end_time: f64) -> WordTimestamp {
+        WordTimestamp {
+            word: word.to_string(),
+            start_time,
+            end_time,
+            confidence: Some(0.95),
+            speaker_id: None,
+            alternatives: Vec::new(),
+        }
+    }
+
+    fn transcription(segments: Vec<TranscriptSegment>, words: Vec<WordTimestamp>) -> ProcessedTranscription {
+        ProcessedTranscription {
+            transcript: String::new(),
+            segments,
+            metadata: TranscriptionMetadata {
+                job_id: "test".to_string(),
+                language: "en".to_string(),
+                processing_duration: None,
+                audio_duration: None,
+                confidence: None,
+                completed_at: chrono::Utc::now(),
+                speaker_count: None,
+            },
+            words: Some(words),
+            raw: None,
+            redactions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_low_confidence_spans_merge_close_segments() {
+        let draft = transcription(
+            vec![
+                segment(0.0, 4.0, "fine", 0.9),
+                segment(4.0, 8.0, "weak one", 0.3),
+                segment(9.0, 12.0, "weak two", 0.4),
+                segment(12.0, 20.0, "fine", 0.95),
+                segment(30.0, 34.0, "weak three", 0.2),
+            ],
+            Vec::new(),
+        );
+
+        let spans = low_confidence_spans(&draft, &HybridConfig::default());
+
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].segments, 1..3);
+        assert_eq!((spans[0].start_time, spans[0].end_time), (4.0, 12.0));
+        assert_eq!(spans[1].segments, 4..5);
+    }
+
+    #[test]
+    fn test_merge_span_replaces_text_and_words() {
+        let mut draft = transcription(
+            vec![
+                segment(0.0, 2.0, "hello there", 0.9),
+                segment(2.0, 4.0, "cube and eddies", 0.3),
+            ],
+            vec![
+                word("hello", 0.0, 1.0),
+                word("there", 1.0, 2.0),
+                word("cube", 2.0, 2.5),
+                word("and", 2.5, 3.0),
+                word("eddies", 3.0, 4.0),
+            ],
+        );
+        let span = Span { segments: 1..2, start_time: 2.0, end_time: 4.0 };
+
+        // Clip starts 0.5s early, so "there" is heard again in the padding
+        let clip = transcription(Vec::new(), vec![word("there", 0.0, 0.4), word("Kubernetes", 0.6, 2.3)]);
+        merge_span(&mut draft, &span, 1.5, &clip);
+
+        assert_eq!(draft.segments[1].text, "Kubernetes");
+        assert_eq!(draft.transcript, "hello there Kubernetes");
+        let words: Vec<&str> = draft.words.as_ref().unwrap().iter().map(|w| w.word.as_str()).collect();
+        assert_eq!(words, vec!["hello", "there", "Kubernetes"]);
+    }
+}
@@ -0,0 +1,239 @@
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+
+use super::TranscriptionResult;
+
+/// Length of a part: its audio duration, or the end of its last segment or word
+pub fn part_duration(result: &TranscriptionResult) -> f64 {
+    result
+        .metadata
+        .audio_duration
+        .or_else(|| result.audio_info.duration.map(|d| d.num_milliseconds() as f64 / 1000.0))
+        .unwrap_or_else(|| {
+            let segments = result.segments.iter().map(|s| s.end_time);
+            let words = result.words.iter().flatten().map(|w| w.end_time);
+            segments.chain(words).fold(0.0, f64::max)
+        })
+}
+
+/// Offsets for parts recorded back to back, with `gap` seconds between them
+pub fn back_to_back_offsets(parts: &[TranscriptionResult], gap: f64) -> Vec<f64> {
+    let mut offsets = Vec::with_capacity(parts.len());
+    let mut next = 0.0;
+    for part in parts {
+        offsets.push(next);
+        next += part_duration(part) + gap;
+    }
+    offsets
+}
+
+/// Combine the transcripts of consecutive recordings into one continuous result
+///
+/// Each part's segments and words are shifted by its offset, so SRT/VTT cues come out
+/// renumbered in order. Generic labels (`spk_0`, `spk_1`, ...) only mean something within
+/// one recording, so unless `keep_speakers` is set they are renumbered so that no two
+/// parts share one; enrolled speaker names identify the same person everywhere and are kept.
+pub fn merge_results(parts: Vec<TranscriptionResult>, offsets: &[f64], keep_speakers: bool) -> Result<TranscriptionResult> {
+    if parts.len() != offsets.len() {
+        anyhow::bail!("Got {} offsets for {} transcripts", offsets.len(), parts.len());
+    }
+
+    let total_duration = parts
+        .iter()
+        .zip(offsets)
+        .map(|(part, offset)| offset + part_duration(part))
+        .fold(0.0, f64::max);
+
+    let mut parts = parts.into_iter().zip(offsets.iter().copied());
+    let (first, first_offset) = parts.next().ok_or_else(|| anyhow::anyhow!("Nothing to merge"))?;
+
+    let mut speakers = SpeakerMap::default();
+    let mut merged = first;
+    shift(&mut merged, first_offset);
+    if !keep_speakers {
+        speakers.remap(&mut merged);
+    }
+
+    let mut job_ids = vec![merged.metadata.job_id.clone()];
+    let mut speakers_counted = merged.metadata.speaker_count.is_some();
+    let mut confidences: Vec<f64> = merged.metadata.confidence.into_iter().collect();
+
+    for (mut part, offset) in parts {
+        shift(&mut part, offset);
+        if !keep_speakers {
+            speakers.remap(&mut part);
+        }
+
+        merged.transcript = format!("{}\n\n{}", merged.transcript.trim_end(), part.transcript.trim_start());
+        merged.segments.extend(part.segments);
+        merged.chapters.extend(part.chapters);
+        merged.redactions.extend(part.redactions);
+        merged.words = match (merged.words.take(), part.words) {
+            (Some(mut words), Some(more)) => {
+                words.extend(more);
+                Some(words)
+            }
+            (words, more) => words.or(more),
+        };
+
+        speakers_counted |= part.metadata.speaker_count.is_some();
+        job_ids.push(part.metadata.job_id);
+        confidences.extend(part.metadata.confidence);
+        merged.metadata.processing_duration = match (merged.metadata.processing_duration, part.metadata.processing_duration) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+        merged.metadata.completed_at = merged.metadata.completed_at.max(part.metadata.completed_at);
+    }
+
+    merged.segments.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+    if let Some(words) = &mut merged.words {
+        words.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+    }
+
+    if speakers_counted {
+        let speakers: HashSet<&str> = merged.segments.iter().filter_map(|s| s.speaker_id.as_deref()).collect();
+        merged.metadata.speaker_count = Some(speakers.len() as u32);
+    }
+    merged.metadata.job_id = job_ids.join("+");
+    merged.metadata.audio_duration = Some(total_duration);
+    merged.metadata.confidence =
+        (!confidences.is_empty()).then(|| confidences.iter().sum::<f64>() / confidences.len() as f64);
+    merged.audio_info.duration = Some(chrono::Duration::milliseconds((total_duration * 1000.0) as i64));
+    merged.audio_info.file_size = None;
+    merged.audio_path = None;
+    // Raw backend output describes a single job and cannot be merged
+    merged.raw = None;
+
+    Ok(merged)
+}
+
+fn shift(result: &mut TranscriptionResult, offset: f64) {
+    for segment in &mut result.segments {
+        segment.start_time += offset;
+        segment.end_time += offset;
+    }
+    for word in result.words.iter_mut().flatten() {
+        word.start_time += offset;
+        word.end_time += offset;
+    }
+    for chapter in &mut result.chapters {
+        chapter.start_time += offset;
+        chapter.end_time = chapter.end_time.map(|end| end + offset);
+    }
+    for redaction in &mut result.redactions {
+        redaction.start_time += offset;
+        redaction.end_time += offset;
+    }
+}
+
+/// Gives every part's generic speaker labels their own numbers
+#[derive(Default)]
+struct SpeakerMap {
+    next: usize,
+}
+
+impl SpeakerMap {
+    fn remap(&mut self, part: &mut TranscriptionResult) {
+        let mut mapping: HashMap<String, String> = HashMap::new();
+        let mut relabel = |speaker: &mut Option<String>| {
+            let Some(label) = speaker.as_mut() else {
+                return;
+            };
+            if !is_generic_label(label) {
+                return;
+            }
+            let new_label = mapping.entry(label.clone()).or_insert_with(|| {
+                self.next += 1;
+                format!("spk_{}", self.next - 1)
+            });
+            *label = new_label.clone();
+        };
+
+        for segment in &mut part.segments {
+            relabel(&mut segment.speaker_id);
+        }
+        for word in part.words.iter_mut().flatten() {
+            relabel(&mut word.speaker_id);
+        }
+    }
+}
+
+fn is_generic_label(label: &str) -> bool {
+    label
+        .strip_prefix("spk_")
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::formatters::WordTimestamp;
+    use crate::transcribe::TranscriptionMetadata;
+
+    fn part(job_id: &str, duration: f64, speakers: &[&str]) -> TranscriptionResult {
+        let texts: Vec<String> = (0..speakers.len()).map(|i| format!("{} line {}", job_id, i)).collect();
+        let cues: Vec<_> = texts.iter().enumerate().map(|(i, text)| (i as f64, i as f64 + 1.0, text.as_str())).collect();
+        let mut part = crate::transcribe::fixture::result(&cues);
+        for (segment, speaker) in part.segments.iter_mut().zip(speakers) {
+            segment.confidence = Some(0.9);
+            segment.speaker_id = Some(speaker.to_string());
+        }
+        let words = part
+            .segments
+            .iter()
+            .map(|s| WordTimestamp {
+                word: "line".to_string(),
+                start_time: s.start_time,
+                end_time: s.end_time,
+                confidence: None,
+                speaker_id: s.speaker_id.clone(),
+                alternatives: Vec::new(),
+            })
+            .collect();
+
+        TranscriptionResult {
+            transcript: format!("{} text", job_id),
+            metadata: TranscriptionMetadata {
+                job_id: job_id.to_string(),
+                language: "en-US".to_string(),
+                audio_duration: Some(duration),
+                confidence: Some(0.9),
+                ..part.metadata
+            },
+            words: Some(words),
+            ..part
+        }
+    }
+
+    #[test]
+    fn test_merge_offsets_and_speakers() {
+        let parts = vec![
+            part("part1", 60.0, &["spk_0", "spk_1"]),
+            part("part2", 30.0, &["spk_1", "Dana", "spk_0"]),
+        ];
+        let offsets = back_to_back_offsets(&parts, 5.0);
+        assert_eq!(offsets, vec![0.0, 65.0]);
+
+        let merged = merge_results(parts, &offsets, false).unwrap();
+        let starts: Vec<f64> = merged.segments.iter().map(|s| s.start_time).collect();
+        assert_eq!(starts, vec![0.0, 1.0, 65.0, 66.0, 67.0]);
+
+        let speakers: Vec<&str> = merged.segments.iter().filter_map(|s| s.speaker_id.as_deref()).collect();
+        assert_eq!(speakers, vec!["spk_0", "spk_1", "spk_2", "Dana", "spk_3"]);
+        assert_eq!(merged.words.as_ref().unwrap()[2].speaker_id.as_deref(), Some("spk_2"));
+
+        assert_eq!(merged.metadata.audio_duration, Some(95.0));
+        assert_eq!(merged.metadata.job_id, "part1+part2");
+        assert_eq!(merged.transcript, "part1 text\n\npart2 text");
+    }
+
+    #[test]
+    fn test_merge_keeps_speakers_and_checks_offsets() {
+        let parts = vec![part("a", 10.0, &["spk_0"]), part("b", 10.0, &["spk_0"])];
+        assert!(merge_results(parts.clone(), &[0.0], false).is_err());
+
+        let merged = merge_results(parts, &[0.0, 10.0], true).unwrap();
+        assert!(merged.segments.iter().all(|s| s.speaker_id.as_deref() == Some("spk_0")));
+    }
+}
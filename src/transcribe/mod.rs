@@ -1,16 +1,36 @@
-use anyhow::{Context, Result};
-use aws_sdk_s3::Client as S3Client;
-use aws_sdk_transcribe::Client as TranscribeClient;
-use indicatif::{ProgressBar, ProgressStyle};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
-use tempfile::TempDir;
-use uuid::Uuid;
 
-use crate::config::Config;
-use crate::extractors::{AudioInfo, ExtractorRegistry};
+use crate::cli::Backend;
+use crate::extractors::{AudioInfo, Chapter};
 
+pub mod align;
+#[cfg(feature = "pipeline")]
+pub mod boost;
+#[cfg(feature = "pipeline")]
+pub mod channels;
+#[cfg(feature = "pipeline")]
+pub mod detect;
+#[cfg(test)]
+pub(crate) mod fixture;
+#[cfg(feature = "pipeline")]
+pub(crate) mod download;
+#[cfg(feature = "pipeline")]
+pub mod hybrid;
+pub mod merge;
+#[cfg(feature = "pipeline")]
+mod pipeline;
+#[cfg(feature = "pipeline")]
 pub mod processor;
+#[cfg(feature = "pipeline")]
+pub mod saved_audio;
+#[cfg(feature = "pipeline")]
+pub mod scheduler;
+pub mod shift;
+
+#[cfg(feature = "pipeline")]
+pub use pipeline::{is_connectivity_error, is_expired_credentials, TranscriptionPipeline};
 
 /// Transcription result with metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,6 +52,103 @@ pub struct TranscriptionResult {
     
     /// Word-level timestamps (if available)
     pub words: Option<Vec<crate::output::formatters::WordTimestamp>>,
+    
+    /// Backend output as received (AWS items and speaker label blocks), kept for full JSON exports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<serde_json::Value>,
+    
+    /// Chapter markers of the source, rendered as headings in text and notes in VTT
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chapters: Vec<Chapter>,
+    
+    /// Spans AWS replaced with `[PII]` (`--redact-pii`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redactions: Vec<Redaction>,
+    
+    /// Key phrases and named entities of the transcript (`--extract-keywords`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<Keywords>,
+}
+
+/// Personal information AWS redacted from the transcript
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Redaction {
+    /// Start time in seconds
+    pub start_time: f64,
+    
+    /// End time in seconds
+    pub end_time: f64,
+    
+    /// PII type, e.g. NAME, EMAIL or CREDIT_DEBIT_NUMBER
+    pub entity_type: String,
+    
+    /// How sure AWS is that the span is this type (0.0 to 1.0)
+    pub confidence: Option<f64>,
+}
+
+/// How `--extract-keywords` finds key phrases
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeywordMethod {
+    /// Amazon Comprehend key phrases and named entities
+    Comprehend,
+    /// RAKE key phrases computed locally (no entities, nothing leaves the machine)
+    Local,
+}
+
+/// Where a transcript's chapters come from
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChapterMode {
+    /// The source's chapter markers, such as YouTube chapters
+    #[default]
+    Source,
+    /// Topical chapters found in the transcript, titled with their most repeated phrase
+    Auto,
+    /// No chapters
+    None,
+}
+
+/// Key phrases and named entities of a transcript
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Keywords {
+    /// How they were found
+    pub method: KeywordMethod,
+    
+    /// Key phrases, most relevant first
+    pub keywords: Vec<Keyword>,
+    
+    /// People, organizations, places and other named entities, most mentioned first
+    #[serde(default)]
+    pub entities: Vec<Entity>,
+}
+
+/// A key phrase of a transcript
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Keyword {
+    pub text: String,
+    
+    /// Relevance; Comprehend's confidence (0.0 to 1.0) or the RAKE score
+    pub score: f64,
+    
+    /// Times it occurs
+    pub count: usize,
+}
+
+/// A named entity of a transcript
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entity {
+    pub text: String,
+    
+    /// Entity type, e.g. PERSON, ORGANIZATION, LOCATION or DATE
+    #[serde(rename = "type")]
+    pub entity_type: String,
+    
+    /// Comprehend's confidence (0.0 to 1.0)
+    pub score: f64,
+    
+    /// Times it is mentioned
+    pub count: usize,
 }
 
 /// Individual transcript segment with timing
@@ -51,6 +168,49 @@ pub struct TranscriptSegment {
     
     /// Speaker ID (if speaker identification is enabled)
     pub speaker_id: Option<String>,
+    
+    /// How toxic AWS rated the speech (`--detect-toxicity`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toxicity: Option<Toxicity>,
+    
+    /// Language spoken in the segment, when AWS identified several (`--multi-language`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    
+    /// Text as spoken, when `--clean-speech` changed it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verbatim: Option<String>,
+}
+
+/// Categories AWS toxicity detection scores speech in
+pub const TOXICITY_CATEGORIES: &[&str] =
+    &["profanity", "hate_speech", "sexual", "insult", "violence_or_threat", "graphic", "harassment_or_abuse"];
+
+/// Toxicity scores of a stretch of speech
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Toxicity {
+    /// Overall score (0.0 to 1.0)
+    pub score: f64,
+    
+    /// Score per category (see `TOXICITY_CATEGORIES`)
+    pub categories: BTreeMap<String, f64>,
+}
+
+impl Toxicity {
+    /// The highest overall and per-category scores of `ratings`, if there are any
+    pub fn max_of<'a>(ratings: impl IntoIterator<Item = &'a Toxicity>) -> Option<Toxicity> {
+        ratings.into_iter().fold(None, |max: Option<Toxicity>, rating| {
+            let Some(mut max) = max else {
+                return Some(rating.clone());
+            };
+            max.score = max.score.max(rating.score);
+            for (category, score) in &rating.categories {
+                let entry = max.categories.entry(category.clone()).or_insert(*score);
+                *entry = entry.max(*score);
+            }
+            Some(max)
+        })
+    }
 }
 
 /// Metadata about the transcription process
@@ -73,361 +233,138 @@ pub struct TranscriptionMetadata {
     
     /// Timestamp when transcription completed
     pub completed_at: chrono::DateTime<chrono::Utc>,
+    
+    /// Number of speakers the backend told apart, when it identified speakers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker_count: Option<u32>,
 }
 
-/// Main transcription pipeline
-pub struct TranscriptionPipeline {
-    config: Config,
-    extractor_registry: ExtractorRegistry,
-    s3_client: S3Client,
-    transcribe_client: TranscribeClient,
-    temp_dir: TempDir,
-}
-
-impl TranscriptionPipeline {
-    /// Create a new transcription pipeline
-    pub async fn new(config: Config) -> Result<Self> {
-        // Load AWS configuration
-        let aws_config = aws_config::defaults(aws_config::BehaviorVersion::latest())
-            .region(config.aws_region())
-            .load()
-            .await;
-            
-        let s3_client = S3Client::new(&aws_config);
-        let transcribe_client = TranscribeClient::new(&aws_config);
-        
-        // Create temporary directory
-        let temp_dir = TempDir::new()
-            .context("Failed to create temporary directory")?;
-        
-        Ok(Self {
-            config,
-            extractor_registry: ExtractorRegistry::new(),
-            s3_client,
-            transcribe_client,
-            temp_dir,
-        })
-    }
+/// Per-run options for a transcription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscribeOptions {
+    /// Language code (auto-detect if not specified)
+    pub language: Option<String>,
     
-    /// Transcribe audio from a URL
-    pub async fn transcribe_from_url(
-        &self,
-        url: &str,
-        language: Option<&str>,
-        speaker_labels: bool,
-        max_speakers: Option<u8>,
-        max_segment_length: f64,
-        save_audio: bool,
-    ) -> Result<TranscriptionResult> {
-        // Extract audio information
-        tracing::info!("Extracting audio information from URL: {}", url);
-        let audio_info = self.extractor_registry.extract_audio_info(url).await?;
-        
-        // Download audio file
-        let audio_path = self.download_audio(&audio_info).await?;
-        
-        // Upload to S3
-        let s3_key = self.upload_to_s3(&audio_path, &audio_info).await?;
-        
-        // Start transcription job
-        let job_id = self.start_transcription_job(&s3_key, &audio_info, language, speaker_labels, max_speakers).await?;
-        
-        // Wait for completion
-        let result = self.wait_for_transcription(&job_id, max_segment_length).await?;
-        
-        // Clean up S3 object
-        self.cleanup_s3(&s3_key).await?;
-        
-        // Preserve audio file if requested via CLI flag or configured in config
-        let preserved_audio_path = if save_audio || self.config.app.keep_audio {
-            Some(self.preserve_audio_file(&audio_path, &audio_info).await?)
-        } else {
-            None
-        };
-        
-        Ok(TranscriptionResult {
-            transcript: result.transcript,
-            segments: result.segments,
-            audio_info,
-            audio_path: preserved_audio_path,
-            metadata: result.metadata,
-            words: result.words,
-        })
-    }
+    /// Enable speaker identification
+    pub speaker_labels: bool,
     
-    /// Download audio file to temporary location
-    async fn download_audio(&self, audio_info: &AudioInfo) -> Result<PathBuf> {
-        let filename = format!(
-            "audio_{}.{}",
-            &Uuid::new_v4().to_string()[..8],
-            audio_info.format.as_str()
-        );
-        let audio_path = self.temp_dir.path().join(filename);
-        
-        tracing::info!("Downloading audio to: {}", audio_path.display());
-        
-        // Check if this is a YouTube URL (yt-dlp protocol)
-        if audio_info.download_url.starts_with("yt-dlp://") {
-            // Use optimized YouTube download
-            let youtube_url = &audio_info.download_url[9..]; // Remove "yt-dlp://" prefix
-            let youtube_extractor = crate::extractors::youtube::YoutubeExtractor::new();
-            
-            let progress = ProgressBar::new_spinner();
-            progress.set_style(ProgressStyle::default_spinner()
-                .template("{spinner:.green} [{elapsed_precise}] {msg}")
-                .unwrap()
-            );
-            progress.set_message("Downloading audio with yt-dlp (optimized)...");
-            
-            // Let yt-dlp handle the download directly (much faster!)
-            youtube_extractor.download_audio_direct(youtube_url, &audio_path).await?;
-            
-            progress.finish_with_message("Download complete");
-            return Ok(audio_path);
-        }
-        
-        // Check if this is a Twitter URL (twitter-dlp protocol)
-        if audio_info.download_url.starts_with("twitter-dlp://") {
-            // Use optimized Twitter download
-            let twitter_url = &audio_info.download_url[14..]; // Remove "twitter-dlp://" prefix
-            let twitter_extractor = crate::extractors::twitter::TwitterExtractor::new();
-            
-            let progress = ProgressBar::new_spinner();
-            progress.set_style(ProgressStyle::default_spinner()
-                .template("{spinner:.green} [{elapsed_precise}] {msg}")
-                .unwrap()
-            );
-            progress.set_message("Downloading Twitter/X audio with yt-dlp (optimized)...");
-            
-            // Let yt-dlp handle the download directly
-            twitter_extractor.download_audio_direct(twitter_url, &audio_path).await?;
-            
-            progress.finish_with_message("Download complete");
-            return Ok(audio_path);
-        }
-        
-        // Check if this is a local file
-        if audio_info.download_url.starts_with("local-file://") {
-            // Use local file processing
-            let local_file_path = &audio_info.download_url[12..]; // Remove "local-file://" prefix
-            let local_extractor = crate::extractors::local::LocalFileExtractor::new();
-            
-            let progress = ProgressBar::new_spinner();
-            progress.set_style(ProgressStyle::default_spinner()
-                .template("{spinner:.green} [{elapsed_precise}] {msg}")
-                .unwrap()
-            );
-            progress.set_message("Processing local audio file...");
-            
-            // Process the local file (copy or convert as needed)
-            local_extractor.prepare_audio(std::path::Path::new(local_file_path), &audio_path).await?;
-            
-            progress.finish_with_message("File processing complete");
-            return Ok(audio_path);
-        }
-        
-        // Create progress bar for regular downloads
-        let progress = ProgressBar::new(audio_info.file_size.unwrap_or(0));
-        progress.set_style(
-            ProgressStyle::default_bar()
-                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} {msg}")
-                .unwrap()
-        );
-        progress.set_message("Downloading audio...");
-        
-        // Download with progress tracking for non-YouTube URLs
-        let response = reqwest::get(&audio_info.download_url).await?;
-        
-        if !response.status().is_success() {
-            anyhow::bail!("Failed to download audio: HTTP {}", response.status());
-        }
-        
-        let total_size = response.content_length().unwrap_or(0);
-        progress.set_length(total_size);
-        
-        let mut file = fs_err::File::create(&audio_path)?;
-        let mut downloaded = 0u64;
-        let mut stream = response.bytes_stream();
-        
-        use futures_util::StreamExt;
-        use std::io::Write;
-        
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk?;
-            file.write_all(&chunk)?;
-            downloaded += chunk.len() as u64;
-            progress.set_position(downloaded);
-        }
-        
-        progress.finish_with_message("Download complete");
-        
-        Ok(audio_path)
-    }
+    /// Maximum number of speakers to identify
+    pub max_speakers: Option<u8>,
     
-    /// Upload audio file to S3
-    async fn upload_to_s3(&self, audio_path: &PathBuf, audio_info: &AudioInfo) -> Result<String> {
-        let key = format!(
-            "{}audio_{}_{}.{}",
-            self.config.aws.s3_key_prefix.as_deref().unwrap_or(""),
-            Uuid::new_v4(),
-            chrono::Utc::now().format("%Y%m%d_%H%M%S"),
-            audio_info.format.as_str()
-        );
-        
-        tracing::info!("Uploading audio to S3: s3://{}/{}", self.config.aws.s3_bucket, key);
-        
-        let content = fs_err::read(audio_path)?;
-        
-        self.s3_client
-            .put_object()
-            .bucket(&self.config.aws.s3_bucket)
-            .key(&key)
-            .body(content.into())
-            .content_type(audio_info.format.mime_type())
-            .send()
-            .await
-            .context("Failed to upload audio to S3")?;
-            
-        Ok(key)
-    }
+    /// Maximum segment length in seconds
+    pub max_segment_length: f64,
     
-    /// Start AWS Transcribe job with auto language detection and speaker identification
-    async fn start_transcription_job(
-        &self,
-        s3_key: &str,
-        audio_info: &AudioInfo,
-        language: Option<&str>,
-        speaker_labels: bool,
-        max_speakers: Option<u8>,
-    ) -> Result<String> {
-        let job_name = format!("transcriptor_{}", Uuid::new_v4());
-        let media_uri = format!("s3://{}/{}", self.config.aws.s3_bucket, s3_key);
-        
-        tracing::info!("Starting transcription job: {}", job_name);
-        
-        use aws_sdk_transcribe::types::{Media, MediaFormat, Settings};
-        
-        let media_format = match audio_info.format {
-            crate::extractors::AudioFormat::Mp3 => MediaFormat::Mp3,
-            crate::extractors::AudioFormat::M4a => MediaFormat::Mp4,
-            crate::extractors::AudioFormat::Wav => MediaFormat::Wav,
-            crate::extractors::AudioFormat::Flac => MediaFormat::Flac,
-            crate::extractors::AudioFormat::Ogg => MediaFormat::Ogg,
-            crate::extractors::AudioFormat::Webm => MediaFormat::Webm,
-        };
-        
-        let media = Media::builder()
-            .media_file_uri(media_uri)
-            .build();
-        
-        let mut job_builder = self.transcribe_client
-            .start_transcription_job()
-            .transcription_job_name(&job_name)
-            .media_format(media_format)
-            .media(media);
-        
-        // Handle language detection
-        if let Some(lang) = language.or(self.config.aws.transcription.default_language.as_deref()) {
-            tracing::info!("Using specified language: {}", lang);
-            job_builder = job_builder.language_code(lang.parse()?);
-        } else {
-            tracing::info!("Using automatic language detection");
-            job_builder = job_builder.identify_language(true);
-        }
-        
-        // Add sample rate to job builder
-        if let Some(sample_rate) = audio_info.sample_rate {
-            job_builder = job_builder.media_sample_rate_hertz(sample_rate as i32);
-        }
-        
-        // Add optional settings for speaker identification and word-level timestamps
-        let mut settings = Settings::builder();
-        
-        // Enable word-level timestamps for more granular segments
-        tracing::info!("Enabling word-level timestamps for better granularity");
-        settings = settings.show_alternatives(true);
-        settings = settings.max_alternatives(2); // AWS requires minimum of 2
-        
-        // Configure speaker identification
-        let enable_speaker_id = speaker_labels || self.config.aws.transcription.speaker_identification;
-        if enable_speaker_id {
-            tracing::info!("Enabling speaker identification");
-            settings = settings.show_speaker_labels(true);
-            
-            // Set max speakers (AWS supports 2-10 speakers)
-            let max_speakers_count = max_speakers
-                .or(self.config.aws.transcription.max_speakers.map(|s| s as u8))
-                .unwrap_or(10); // Default to 10 if not specified
-                
-            let clamped_speakers = max_speakers_count.clamp(2, 10);
-            settings = settings.max_speaker_labels(clamped_speakers as i32);
-            
-            if max_speakers_count != clamped_speakers {
-                tracing::warn!("Max speakers clamped from {} to {} (AWS supports 2-10)", max_speakers_count, clamped_speakers);
-            }
-        }
-        
-        job_builder = job_builder.settings(settings.build());
-        
-        job_builder.send().await
-            .context("Failed to start transcription job")?;
-            
-        Ok(job_name)
-    }
+    /// Preserve the extracted audio file
+    pub save_audio: bool,
     
-    /// Wait for transcription job completion
-    async fn wait_for_transcription(&self, job_id: &str, max_segment_length: f64) -> Result<processor::ProcessedTranscription> {
-        processor::TranscriptionProcessor::new(
-            self.transcribe_client.clone(),
-            job_id.to_string(),
-            max_segment_length,
-        )
-        .wait_for_completion()
-        .await
-    }
+    /// AWS custom vocabulary to use for the job
+    pub vocabulary: Option<String>,
     
-    /// Clean up S3 object
-    async fn cleanup_s3(&self, s3_key: &str) -> Result<()> {
-        tracing::debug!("Cleaning up S3 object: {}", s3_key);
-        
-        self.s3_client
-            .delete_object()
-            .bucket(&self.config.aws.s3_bucket)
-            .key(s3_key)
-            .send()
-            .await
-            .context("Failed to clean up S3 object")?;
-            
-        Ok(())
-    }
+    /// Copy `s3://` inputs into the configured bucket when they live in another region
+    #[serde(default)]
+    pub copy_cross_region: bool,
     
-    /// Preserve audio file in user's directory
-    async fn preserve_audio_file(
-        &self,
-        temp_path: &PathBuf,
-        audio_info: &AudioInfo,
-    ) -> Result<PathBuf> {
-        let filename = audio_info
-            .title
-            .as_ref()
-            .map(|title| {
-                let sanitized = title
-                    .chars()
-                    .map(|c| if c.is_alphanumeric() || c == ' ' { c } else { '_' })
-                    .collect::<String>();
-                format!("{}.{}", sanitized, audio_info.format.as_str())
-            })
-            .unwrap_or_else(|| {
-                format!(
-                    "audio_{}.{}",
-                    chrono::Utc::now().format("%Y%m%d_%H%M%S"),
-                    audio_info.format.as_str()
-                )
-            });
-            
-        let output_path = std::env::current_dir()?.join(filename);
-        fs_err::copy(temp_path, &output_path)?;
-        
-        Ok(output_path)
+    /// Backend that performs the transcription
+    #[serde(default)]
+    pub backend: Backend,
+    
+    /// Project tag recorded in the run history
+    #[serde(default)]
+    pub tag: Option<String>,
+    
+    /// Use a YouTube video's uploaded subtitles instead of transcribing, when it has them
+    #[serde(default)]
+    pub prefer_captions: bool,
+    
+    /// Keep only who spoke when: speaker turns without text
+    #[serde(default)]
+    pub diarize_only: bool,
+    
+    /// Stop before uploading if the estimated cost (USD) is higher
+    #[serde(default)]
+    pub max_cost: Option<f64>,
+    
+    /// Phrases live runs prime Whisper with and correct the spelling of
+    #[serde(default)]
+    pub boost_phrases: Vec<String>,
+    
+    /// Have AWS redact personal information from the transcript
+    #[serde(default)]
+    pub redact_pii: Option<PiiRedaction>,
+    
+    /// Have AWS rate segments for toxic speech
+    #[serde(default)]
+    pub detect_toxicity: bool,
+    
+    /// Languages AWS may identify, when `language` is not set (at least two)
+    #[serde(default)]
+    pub language_options: Vec<String>,
+    
+    /// Identify every language spoken rather than the dominant one, and label segments with theirs
+    #[serde(default)]
+    pub multi_language: bool,
+    
+    /// Transcribe the channels of a stereo file on their own, with each channel as a speaker
+    #[serde(default)]
+    pub split_channels: bool,
+    
+    /// Strip filler words, repetitions and false starts from the text
+    #[serde(default)]
+    pub clean_speech: bool,
+    
+    /// Find key phrases (and with Comprehend, named entities) of the transcript
+    #[serde(default)]
+    pub extract_keywords: Option<KeywordMethod>,
+    
+    /// Where the result's chapters come from
+    #[serde(default)]
+    pub chapters: ChapterMode,
+}
+
+/// AWS Transcribe content redaction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PiiRedaction {
+    /// PII entity types to redact, e.g. NAME or EMAIL; empty redacts every type
+    pub entity_types: Vec<String>,
+    
+    pub output: RedactionOutput,
+}
+
+/// Which transcripts AWS keeps when redacting
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactionOutput {
+    /// Only the redacted transcript
+    #[default]
+    Redacted,
+    /// The unredacted transcript too, kept in full JSON exports as `raw.unredacted`
+    RedactedAndUnredacted,
+}
+
+impl Default for TranscribeOptions {
+    fn default() -> Self {
+        Self {
+            language: None,
+            speaker_labels: false,
+            max_speakers: None,
+            max_segment_length: 10.0,
+            save_audio: false,
+            vocabulary: None,
+            copy_cross_region: false,
+            backend: Backend::Aws,
+            tag: None,
+            prefer_captions: false,
+            diarize_only: false,
+            max_cost: None,
+            boost_phrases: Vec::new(),
+            redact_pii: None,
+            detect_toxicity: false,
+            language_options: Vec::new(),
+            multi_language: false,
+            split_channels: false,
+            clean_speech: false,
+            extract_keywords: None,
+            chapters: ChapterMode::Source,
+        }
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,1718 @@
+use anyhow::{Context, Result};
+use aws_sdk_s3::Client as S3Client;
+use aws_sdk_transcribe::Client as TranscribeClient;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tempfile::TempDir;
+use uuid::Uuid;
+
+use super::boost::{PhraseBoost, Stabilizer};
+use super::scheduler::Scheduler;
+use super::{channels, detect, download, hybrid, merge, processor, saved_audio};
+use super::{ChapterMode, KeywordMethod, TranscribeOptions, TranscriptionResult};
+use crate::cache::{ArtifactKey, ArtifactKind, ArtifactStore, DownloadCache};
+use crate::cli::Backend;
+use crate::config::Config;
+use crate::extractors::live::{self, LiveOptions, LiveRecording};
+use crate::extractors::s3::S3Location;
+use crate::extractors::youtube::YoutubeExtractor;
+use crate::extractors::{AudioFormat, AudioInfo, DownloadMethod, ExtractorRegistry, MediaExtractor};
+use crate::health::Heartbeat;
+use crate::history::{CostEstimate, History, HistoryEntry};
+use crate::jobs::{InFlightJob, JobStore};
+use crate::keywords::{chapters, KeywordExtractor};
+use crate::postprocess::clean::clean_speech;
+use crate::postprocess::language::apply_language_rules;
+use crate::queue::OutputTarget;
+use crate::speakers::{self, SpeakerStore};
+use crate::whisper::WhisperBackend;
+
+/// Main transcription pipeline
+pub struct TranscriptionPipeline {
+    config: Config,
+    extractor_registry: ExtractorRegistry,
+    http_client: reqwest::Client,
+    aws: RwLock<AwsClients>,
+    temp_dir: TempDir,
+    heartbeat: Heartbeat,
+    scheduler: Scheduler,
+    output: Option<OutputTarget>,
+}
+
+/// AWS clients built from one set of credentials
+#[derive(Clone)]
+struct AwsClients {
+    s3: S3Client,
+    transcribe: TranscribeClient,
+}
+
+impl AwsClients {
+    async fn load(config: &Config) -> Self {
+        let aws_config = config.aws_sdk_config().await;
+        Self {
+            s3: S3Client::new(&aws_config),
+            transcribe: TranscribeClient::new(&aws_config),
+        }
+    }
+}
+
+/// How often a single job may reload expired credentials before giving up
+const MAX_CREDENTIAL_REFRESHES: u32 = 3;
+
+impl TranscriptionPipeline {
+    /// Create a new transcription pipeline
+    pub async fn new(config: Config) -> Result<Self> {
+        // Load AWS configuration
+        let aws = AwsClients::load(&config).await;
+        
+        let http_client = crate::http::build_client(&config.http, &config.network)?;
+        
+        // Create temporary directory
+        let temp_dir = TempDir::new()
+            .context("Failed to create temporary directory")?;
+        
+        let heartbeat = Heartbeat::start(&config.health);
+        let scheduler = Scheduler::new(&config.concurrency, config.app.max_concurrent_jobs);
+        
+        let mut extractor_registry = ExtractorRegistry::from_config(http_client.clone(), &config);
+        extractor_registry.register_plugins(&config.extractors.plugins)?;
+        
+        Ok(Self {
+            config,
+            extractor_registry,
+            http_client,
+            aws: RwLock::new(aws),
+            temp_dir,
+            heartbeat,
+            scheduler,
+            output: None,
+        })
+    }
+    
+    /// Remember where results go, so interrupted AWS jobs can be resumed into the same output
+    pub fn with_output(mut self, target: OutputTarget) -> Self {
+        self.output = Some(target);
+        self
+    }
+    
+    /// Current AWS clients
+    fn aws(&self) -> AwsClients {
+        self.aws.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+    
+    /// Reload credentials and rebuild the AWS clients, e.g. after a session token expired
+    async fn refresh_aws_clients(&self) {
+        tracing::warn!("AWS credentials expired, reloading them");
+        let aws = AwsClients::load(&self.config).await;
+        *self.aws.write().unwrap_or_else(|e| e.into_inner()) = aws;
+    }
+    
+    /// Run an AWS call, retrying it once with reloaded credentials if they expired
+    async fn with_fresh_credentials<T, F, Fut>(&self, call: F) -> Result<T>
+    where
+        F: Fn(AwsClients) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        match call(self.aws()).await {
+            Err(e) if is_expired_credentials(&e) => {
+                self.refresh_aws_clients().await;
+                call(self.aws()).await
+            }
+            result => result,
+        }
+    }
+    
+    /// Transcribe audio from a URL
+    pub async fn transcribe_from_url(
+        &self,
+        url: &str,
+        options: &TranscribeOptions,
+    ) -> Result<TranscriptionResult> {
+        if let Some(location) = S3Location::parse(url).filter(|_| !self.config.app.demo) {
+            return self.transcribe_s3_object(&location, options).await;
+        }
+        
+        // Uploaded captions have no channels to split
+        if !options.split_channels {
+            if let Some(result) = self.transcribe_from_captions(url, options).await? {
+                return Ok(result);
+            }
+        }
+        
+        let (audio_info, audio_path) = self.prepare_audio(url).await?;
+        
+        self.transcribe_audio(audio_info, &audio_path, options).await
+    }
+    
+    /// Transcribe the left and right channel of stereo audio on their own and interleave them
+    ///
+    /// Each channel becomes a speaker, which stands in for diarization on recordings with one
+    /// person per channel, such as phone calls and podcasts recorded on separate microphones.
+    /// Only the backend runs per channel; hooks, text processing and the history see the
+    /// interleaved transcript, as they would a single-channel one.
+    async fn transcribe_channels(
+        &self,
+        audio_info: AudioInfo,
+        audio_path: &Path,
+        options: &TranscribeOptions,
+    ) -> Result<TranscriptionResult> {
+        let started_at = std::time::Instant::now();
+        
+        let hook_vars = crate::hooks::TemplateVars::from_audio(&audio_info);
+        self.config.hooks.pre_upload(audio_path, &hook_vars).await?;
+        
+        // One person per channel, so each is transcribed without speaker identification
+        let channel_options = TranscribeOptions {
+            speaker_labels: false,
+            max_speakers: None,
+            ..options.clone()
+        };
+        
+        let (parts, aws_secs) = if self.config.app.demo {
+            // Each speaker of the demo conversation stands in for a channel
+            let parts = (0..channels::CHANNEL_SPEAKERS.len())
+                .map(|channel| crate::demo::channel_result(channel, audio_info.clone(), &channel_options))
+                .collect();
+            (parts, Some(0.0))
+        } else {
+            self.transcribe_each_channel(&audio_info, audio_path, &channel_options).await?
+        };
+        
+        let mut audio_info = audio_info;
+        let mut result = channels::interleave(parts)?;
+        result.chapters = std::mem::take(&mut audio_info.chapters);
+        result.audio_path = if options.save_audio || self.config.app.keep_audio {
+            Some(self.preserve_audio_file(audio_path, &audio_info).await?)
+        } else {
+            None
+        };
+        result.audio_info = audio_info;
+        self.finish_text(&mut result, options).await;
+        
+        self.run_post_transcribe_hooks(&mut result).await?;
+        self.record_history(&result, options, aws_secs, started_at);
+        self.keep_raw_transcript(&result, options);
+        self.archive_original(&result.audio_info);
+        Ok(result)
+    }
+    
+    /// Split stereo audio and run the backend on its channels one after the other
+    ///
+    /// Returns the channels' results with the seconds AWS bills for all of them together.
+    async fn transcribe_each_channel(
+        &self,
+        audio_info: &AudioInfo,
+        audio_path: &Path,
+        options: &TranscribeOptions,
+    ) -> Result<(Vec<TranscriptionResult>, Option<f64>)> {
+        let source = &audio_info.original_url;
+        match channels::channel_count(audio_path).await? {
+            2 => {}
+            count => anyhow::bail!("--split-channels needs stereo audio; {} has {} channel(s)", source, count),
+        }
+        
+        if options.max_cost.is_some() {
+            // Every channel is billed as a recording of its own
+            let audio_secs = self.audio_secs(audio_info, &audio_path.to_string_lossy()).await?;
+            CostEstimate::new(audio_secs * 2.0, None, options.backend, &self.config.history).check_limit(options.max_cost)?;
+        }
+        
+        self.heartbeat.set_stage("Splitting channels");
+        let channel_paths = channels::split_stereo(audio_path, self.temp_dir.path()).await?;
+        let mut parts = Vec::with_capacity(channel_paths.len());
+        let mut billed = 0.0;
+        for (speaker, channel_path) in channels::CHANNEL_SPEAKERS.iter().zip(&channel_paths) {
+            self.heartbeat.set_stage(format!("Transcribing the {} channel", speaker));
+            tracing::info!("Transcribing the {} channel", speaker);
+            let channel_info = AudioInfo {
+                format: AudioFormat::Wav,
+                sample_rate: None,
+                file_size: None,
+                chapters: Vec::new(),
+                ..audio_info.clone()
+            };
+            let transcribed = self.run_backend(&channel_info, channel_path, options, false).await;
+            let _ = fs_err::remove_file(channel_path);
+            let (processed, sent) = transcribed?;
+            billed += sent.or(processed.metadata.audio_duration).unwrap_or(0.0);
+            parts.push(processed.into_result(channel_info, None));
+        }
+        Ok((parts, Some(billed)))
+    }
+    
+    /// The videos of a YouTube playlist, or just `url` for anything else
+    pub async fn expand_playlist(&self, url: &str) -> Result<Vec<String>> {
+        if self.config.app.demo || !crate::extractors::youtube::is_playlist_url(url) {
+            return Ok(vec![url.to_string()]);
+        }
+        
+        let youtube = YoutubeExtractor::new()
+            .with_yt_dlp(self.config.yt_dlp.binary())
+            .with_cookies(self.config.cookies.clone())
+            .with_network(self.config.network.clone());
+        let entries = youtube.playlist_entries(url).await?;
+        if entries.is_empty() {
+            anyhow::bail!("Playlist {} has no videos", url);
+        }
+        Ok(entries)
+    }
+    
+    /// Record a live stream for a bounded time and transcribe it
+    ///
+    /// With chunking, each chunk is transcribed as soon as it is recorded, so partial transcripts
+    /// are available while the stream is still running. Its finished sentences are handed to
+    /// `on_text` with where they start in the recording; a sentence cut off by the end of a chunk
+    /// waits for the next one. Boost phrases are spelled as given. The chunks are merged into one result.
+    pub async fn transcribe_live(
+        &self,
+        url: &str,
+        options: &TranscribeOptions,
+        live: &LiveOptions,
+        mut on_text: impl FnMut(&str, f64),
+    ) -> Result<TranscriptionResult> {
+        let yt_dlp = self.config.yt_dlp.binary();
+        self.heartbeat.set_stage("Checking live stream");
+        let audio_info = live::live_audio_info(&yt_dlp, url, live, &self.config.cookies, &self.config.network).await?;
+        
+        let dir = self.temp_dir.path().join(format!("live_{}", &Uuid::new_v4().to_string()[..8]));
+        fs_err::create_dir_all(&dir)?;
+        let live = LiveOptions {
+            keep_recording: options.save_audio || self.config.app.keep_audio,
+            ..live.clone()
+        };
+        let mut recording = LiveRecording::start(&yt_dlp, url, &dir, &live, &self.config.cookies, &self.config.network)?;
+        
+        tracing::info!(
+            "Recording live stream for up to {} (Ctrl-C to stop early)",
+            crate::utils::format_duration(live.duration.as_secs_f64())
+        );
+        
+        // Chunks are kept apart in the history, and the whole recording is saved once at the end
+        let chunk_options = TranscribeOptions {
+            save_audio: false,
+            ..options.clone()
+        };
+        let boost = PhraseBoost::new(&options.boost_phrases)?;
+        let mut stabilizer = Stabilizer::default();
+        let mut parts = Vec::new();
+        let mut offset = 0.0;
+        while let Some(chunk) = recording.next_chunk().await? {
+            self.heartbeat.set_stage(format!("Transcribing live chunk {}", parts.len() + 1));
+            let mut part = self
+                .transcribe_prepared(audio_info.clone(), &chunk, &chunk_options, live.chunk.is_none())
+                .await?;
+            // Speech can stop before the chunk does, so the offsets come from the audio itself
+            part.metadata.audio_duration = live::wav_duration(&chunk).or(part.metadata.audio_duration);
+            boost.apply(&mut part);
+            if let Some((text, start)) = stabilizer.push(&part, offset) {
+                on_text(&text, start);
+            }
+            offset += merge::part_duration(&part);
+            parts.push(part);
+            let _ = fs_err::remove_file(&chunk);
+        }
+        if let Some((text, start)) = stabilizer.finish() {
+            on_text(&text, start);
+        }
+        let recording_path = recording.recording_path();
+        recording.stop().await;
+        
+        let offsets = merge::back_to_back_offsets(&parts, 0.0);
+        let mut result = merge::merge_results(parts, &offsets, false)?;
+        if live.keep_recording {
+            result.audio_path = Some(self.preserve_audio_file(&recording_path, &result.audio_info).await?);
+        }
+        
+        Ok(result)
+    }
+    
+    /// Turn a YouTube video's uploaded subtitles into a result, if it has them in the wanted language
+    ///
+    /// Returns `None` without looking unless `--prefer-captions` was given.
+    pub async fn transcribe_from_captions(&self, url: &str, options: &TranscribeOptions) -> Result<Option<TranscriptionResult>> {
+        if !options.prefer_captions || self.config.app.demo {
+            return Ok(None);
+        }
+        
+        let youtube = YoutubeExtractor::new()
+            .with_yt_dlp(self.config.yt_dlp.binary())
+            .with_audio(self.config.yt_dlp.format(), self.config.yt_dlp.audio_quality.clone())
+            .with_cookies(self.config.cookies.clone())
+            .with_network(self.config.network.clone());
+        if !youtube.supports_url(url) {
+            tracing::info!("--prefer-captions only applies to YouTube videos; transcribing instead");
+            return Ok(None);
+        }
+        
+        let started_at = std::time::Instant::now();
+        self.heartbeat.set_stage("Checking for uploaded subtitles");
+        let captions = match youtube.download_captions(url, options.language.as_deref(), self.temp_dir.path()).await {
+            Ok(Some(captions)) => captions,
+            Ok(None) => return Ok(None),
+            Err(e) => {
+                tracing::warn!("Could not use the video's subtitles, transcribing instead: {:#}", e);
+                return Ok(None);
+            }
+        };
+        
+        let mut result = crate::output::load_transcript(&captions.path)?;
+        let duration = captions.audio_info.duration.map(|d| d.num_milliseconds() as f64 / 1000.0);
+        result.metadata.job_id = format!("youtube-captions-{}", captions.language);
+        result.metadata.language = options.language.clone().unwrap_or(captions.language);
+        result.metadata.audio_duration = duration.or(result.metadata.audio_duration);
+        result.audio_info = captions.audio_info;
+        result.chapters = std::mem::take(&mut result.audio_info.chapters);
+        
+        // Subtitles need no audio, but --save-audio still gets it
+        if options.save_audio || self.config.app.keep_audio {
+            self.heartbeat.set_stage("Downloading audio");
+            let audio_path = self.download_audio(&result.audio_info).await?;
+            result.audio_path = Some(self.preserve_audio_file(&audio_path, &result.audio_info).await?);
+        }
+        
+        self.run_post_transcribe_hooks(&mut result).await?;
+        self.record_history(&result, options, Some(0.0), started_at);
+        
+        Ok(Some(result))
+    }
+    
+    /// Extract and download the audio for a URL without transcribing it
+    pub async fn prepare_audio(&self, url: &str) -> Result<(AudioInfo, PathBuf)> {
+        if self.config.app.demo {
+            let audio_path = self.temp_dir.path().join(format!("demo_{}.wav", &Uuid::new_v4().to_string()[..8]));
+            crate::demo::write_sample_audio(&audio_path)?;
+            return Ok((crate::demo::sample_audio_info(url), audio_path));
+        }
+        
+        // Extract audio information
+        tracing::info!("Extracting audio information from URL: {}", url);
+        self.heartbeat.set_stage("Extracting audio information");
+        let audio_info = self.extractor_registry.extract_audio_info(url).await?;
+        
+        // Download audio file
+        self.heartbeat.set_stage("Downloading audio");
+        let audio_path = self.download_audio(&audio_info).await?;
+        
+        Ok((audio_info, audio_path))
+    }
+    
+    /// Download the audio for a URL and keep it, without transcribing it (`extract`)
+    ///
+    /// It is written to `output` in the format its extension names, or saved as the
+    /// `saved_audio` settings say without one.
+    pub async fn extract_audio(&self, url: &str, output: Option<&Path>) -> Result<(AudioInfo, PathBuf)> {
+        let (audio_info, audio_path) = self.prepare_audio(url).await?;
+        
+        let saved = match output {
+            Some(output) => {
+                if let Some(parent) = output.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                    fs_err::create_dir_all(parent)?;
+                }
+                saved_audio::write_as(&audio_path, &audio_info, output).await?;
+                output.to_path_buf()
+            }
+            None => self.preserve_audio_file(&audio_path, &audio_info).await?,
+        };
+        Ok((audio_info, saved))
+    }
+    
+    /// Project the cost of transcribing a URL or file from its metadata, without downloading it
+    pub async fn estimate(&self, url: &str, backend: Backend) -> Result<(AudioInfo, CostEstimate)> {
+        self.heartbeat.set_stage("Extracting audio information");
+        let audio_info = self.extractor_registry.extract_audio_info(url).await?;
+        let audio_secs = self.audio_secs(&audio_info, &audio_info.download_url).await?;
+        
+        let estimate = CostEstimate::new(audio_secs, audio_info.file_size, backend, &self.config.history);
+        Ok((audio_info, estimate))
+    }
+    
+    /// Length of some audio: as the source reported it, or read from `media` by ffprobe
+    async fn audio_secs(&self, audio_info: &AudioInfo, media: &str) -> Result<f64> {
+        if let Some(duration) = audio_info.duration {
+            return Ok(duration.num_milliseconds() as f64 / 1000.0);
+        }
+        
+        // yt-dlp and plugin sources are pages rather than media; they report durations themselves
+        let probed = match audio_info.download_method {
+            DownloadMethod::YtDlp | DownloadMethod::Plugin if media == audio_info.download_url => None,
+            _ => crate::extractors::direct::probe_duration(media, &self.config.network).await?,
+        };
+        probed.with_context(|| format!("Could not tell how long {} is", audio_info.original_url))
+    }
+    
+    /// Transcribe already downloaded audio with the selected backend
+    pub async fn transcribe_audio(
+        &self,
+        audio_info: AudioInfo,
+        audio_path: &Path,
+        options: &TranscribeOptions,
+    ) -> Result<TranscriptionResult> {
+        if options.split_channels {
+            return self.transcribe_channels(audio_info, audio_path, options).await;
+        }
+        
+        if options.max_cost.is_some() && !self.config.app.demo {
+            let audio_secs = self.audio_secs(&audio_info, &audio_path.to_string_lossy()).await?;
+            let size = fs_err::metadata(audio_path).ok().map(|metadata| metadata.len());
+            CostEstimate::new(audio_secs, size, options.backend, &self.config.history).check_limit(options.max_cost)?;
+        }
+        
+        let result = self.transcribe_prepared(audio_info, audio_path, options, true).await?;
+        self.keep_raw_transcript(&result, options);
+        self.archive_original(&result.audio_info);
+        Ok(result)
+    }
+    
+    /// Transcribe prepared audio; `resumable` is false for pieces of a larger run, such as live chunks
+    async fn transcribe_prepared(
+        &self,
+        audio_info: AudioInfo,
+        audio_path: &Path,
+        options: &TranscribeOptions,
+        resumable: bool,
+    ) -> Result<TranscriptionResult> {
+        let started_at = std::time::Instant::now();
+        
+        let hook_vars = crate::hooks::TemplateVars::from_audio(&audio_info);
+        self.config.hooks.pre_upload(audio_path, &hook_vars).await?;
+        
+        let (processed, aws_secs) = self.run_backend(&audio_info, audio_path, options, resumable).await?;
+        
+        // Preserve audio file if requested via CLI flag or configured in config
+        let preserved_audio_path = if options.save_audio || self.config.app.keep_audio {
+            Some(self.preserve_audio_file(audio_path, &audio_info).await?)
+        } else {
+            None
+        };
+        let mut result = processed.into_result(audio_info, preserved_audio_path);
+        
+        if self.config.speakers.recognize {
+            self.recognize_speakers(&mut result, audio_path).await;
+        }
+        self.finish_text(&mut result, options).await;
+        
+        self.run_post_transcribe_hooks(&mut result).await?;
+        self.record_history(&result, options, aws_secs, started_at);
+        
+        Ok(result)
+    }
+    
+    /// Run the selected backend on audio, with the seconds of it AWS bills for where that is
+    /// not simply the whole file
+    async fn run_backend(
+        &self,
+        audio_info: &AudioInfo,
+        audio_path: &Path,
+        options: &TranscribeOptions,
+        resumable: bool,
+    ) -> Result<(processor::ProcessedTranscription, Option<f64>)> {
+        Ok(match options.backend {
+            _ if self.config.app.demo => (crate::demo::transcription(options), Some(0.0)),
+            Backend::Aws => (self.transcribe_with_aws(audio_info, audio_path, options, resumable).await?, None),
+            Backend::Whisper => {
+                self.heartbeat.set_stage("Transcribing with Whisper");
+                let result = WhisperBackend::new(self.config.whisper.clone())?
+                    .transcribe(audio_path, options)
+                    .await?;
+                (result, Some(0.0))
+            }
+            Backend::Hybrid => {
+                let (result, sent) = self.transcribe_hybrid(audio_path, options).await?;
+                (result, Some(sent))
+            }
+        })
+    }
+    
+    /// Let `post_transcribe` hooks edit the result through a JSON file
+    async fn run_post_transcribe_hooks(&self, result: &mut TranscriptionResult) -> Result<()> {
+        let json_path = self.temp_dir.path().join(format!("result_{}.json", &Uuid::new_v4().to_string()[..8]));
+        self.config.hooks.post_transcribe(result, &json_path).await
+    }
+    
+    /// Keep the backend's raw output in the artifact store; failures only cost the copy
+    fn keep_raw_transcript(&self, result: &TranscriptionResult, options: &TranscribeOptions) {
+        let Some(raw) = result.raw.as_ref().filter(|_| self.config.artifacts.enabled) else {
+            return;
+        };
+        
+        let key = ArtifactKey::new(ArtifactKind::RawTranscript, &result.audio_info.original_url, options);
+        let stored = ArtifactStore::open(&self.config.artifacts)
+            .and_then(|store| store.put_bytes(&key, &serde_json::to_vec(raw)?, serde_json::Value::Null));
+        if let Err(e) = stored {
+            tracing::warn!("Could not keep the raw transcript: {:#}", e);
+        }
+    }
+    
+    /// Add a completed run and its transcript to the history; failures only cost the entry
+    fn record_history(
+        &self,
+        result: &TranscriptionResult,
+        options: &TranscribeOptions,
+        aws_secs: Option<f64>,
+        started_at: std::time::Instant,
+    ) {
+        if !self.config.history.enabled {
+            return;
+        }
+        
+        let audio_secs = result
+            .audio_info
+            .duration
+            .map(|d| d.num_milliseconds() as f64 / 1000.0)
+            .or(result.metadata.audio_duration)
+            .unwrap_or(0.0);
+        
+        let source = &result.audio_info.original_url;
+        let platform = match result.audio_info.download_method {
+            DownloadMethod::LocalFile => Some("Local File"),
+            _ if S3Location::parse(source).is_some() => Some("S3"),
+            _ => self.extractor_registry.find_extractor(source).map(|e| e.platform_name()),
+        };
+        
+        let mut entry = HistoryEntry {
+            id: None,
+            completed_at: chrono::Utc::now(),
+            source: source.clone(),
+            title: result.audio_info.title.clone(),
+            platform: platform.map(str::to_string),
+            backend: options.backend,
+            language: Some(result.metadata.language.clone()).filter(|language| !language.is_empty()),
+            audio_secs,
+            aws_secs: aws_secs.unwrap_or(audio_secs),
+            processing_secs: Some(started_at.elapsed().as_secs_f64()),
+            tag: options.tag.clone(),
+            output: self.output.as_ref().and_then(|target| target.path.clone()),
+            cost_estimate: None,
+            qa_accuracy: None,
+        };
+        entry.cost_estimate = Some(entry.estimated_cost(&self.config.history));
+        
+        if let Err(e) = History::open().and_then(|history| history.record(&entry, Some(result))) {
+            tracing::warn!("Could not record run history: {:#}", e);
+        }
+    }
+    
+    /// Replace generic speaker labels with enrolled speakers' names; failures only cost the names
+    async fn recognize_speakers(&self, result: &mut TranscriptionResult, audio_path: &Path) {
+        let profiles = match SpeakerStore::open().and_then(|store| store.list()) {
+            Ok(profiles) => profiles,
+            Err(e) => {
+                tracing::warn!("Could not load enrolled speakers: {:#}", e);
+                return;
+            }
+        };
+        
+        if let Err(e) = speakers::label_known_speakers(result, audio_path, &profiles, &self.config.speakers).await {
+            tracing::warn!("Speaker recognition failed: {:#}", e);
+        }
+    }
+    
+    /// Text post-processing every transcription path ends with
+    async fn finish_text(&self, result: &mut TranscriptionResult, options: &TranscribeOptions) {
+        if options.diarize_only {
+            keep_speaker_turns(result);
+        } else {
+            if options.clean_speech {
+                clean_speech(result);
+            }
+            if self.config.app.language_rules {
+                apply_language_rules(result);
+            }
+        }
+        self.analyze(result, options).await;
+    }
+    
+    /// Chapters and keywords, which need the whole transcript
+    async fn analyze(&self, result: &mut TranscriptionResult, options: &TranscribeOptions) {
+        match options.chapters {
+            ChapterMode::Source => {}
+            ChapterMode::Auto => result.chapters = chapters::auto_chapters(result),
+            ChapterMode::None => result.chapters.clear(),
+        }
+        if let Some(method) = options.extract_keywords {
+            self.extract_keywords(result, method).await;
+        }
+    }
+    
+    /// Attach the transcript's key phrases and entities; failures only cost the keywords
+    async fn extract_keywords(&self, result: &mut TranscriptionResult, method: KeywordMethod) {
+        let keywords = match KeywordExtractor::new(&self.config, method).await {
+            Ok(extractor) => extractor.extract(result).await,
+            Err(e) => Err(e),
+        };
+        match keywords {
+            Ok(keywords) => result.keywords = Some(keywords),
+            Err(e) => tracing::warn!("Keyword extraction failed: {:#}", e),
+        }
+    }
+    
+    /// Upload the audio, run an AWS Transcribe job and remove the upload again
+    async fn transcribe_with_aws(
+        &self,
+        audio_info: &AudioInfo,
+        audio_path: &Path,
+        options: &TranscribeOptions,
+        resumable: bool,
+    ) -> Result<processor::ProcessedTranscription> {
+        let compressed = self.compress_for_upload(audio_info, audio_path).await?;
+        let (audio_info, audio_path) = match &compressed {
+            Some((info, path)) => (info, path.as_path()),
+            None => (audio_info, audio_path),
+        };
+        
+        // Upload to S3
+        self.heartbeat.set_stage("Uploading audio to S3");
+        let s3_key = self.upload_to_s3(audio_path, audio_info).await?;
+        
+        // Start transcription job
+        let media_uri = format!("s3://{}/{}", self.config.aws.s3_bucket, s3_key);
+        let result = self.run_transcription_job(&media_uri, Some(&s3_key), resumable, audio_info, options).await?;
+        
+        // Clean up S3 object
+        self.cleanup_s3(&s3_key).await?;
+        
+        Ok(result)
+    }
+    
+    /// Identify the language of the first `sample` of a source without transcribing it
+    ///
+    /// Only the sample is uploaded (AWS) or decoded (Whisper and hybrid), so this is a cheap
+    /// check to route content before committing to a full transcription. With `multiple`,
+    /// AWS reports every language spoken in the sample rather than the dominant one; non-empty
+    /// `candidates` restrict what it may choose from.
+    pub async fn detect_language(
+        &self,
+        url: &str,
+        sample: std::time::Duration,
+        backend: Backend,
+        multiple: bool,
+        candidates: &[String],
+    ) -> Result<Vec<detect::DetectedLanguage>> {
+        let (audio_info, audio_path) = self.prepare_audio(url).await?;
+        
+        self.heartbeat.set_stage("Cutting language sample");
+        let clip = self.temp_dir.path().join(format!("sample_{}.wav", Uuid::new_v4()));
+        hybrid::cut_clip(&audio_path, 0.0, sample.as_secs_f64(), &clip).await?;
+        
+        let languages = match backend {
+            Backend::Aws => self.identify_language_with_aws(&clip, &audio_info, multiple, candidates).await?,
+            Backend::Whisper | Backend::Hybrid => {
+                if multiple {
+                    tracing::warn!("Whisper only reports the dominant language");
+                }
+                vec![WhisperBackend::new(self.config.whisper.clone())?.detect_language(&clip).await?]
+            }
+        };
+        
+        let _ = fs_err::remove_file(&clip);
+        Ok(languages)
+    }
+    
+    /// Upload a sample, run an AWS language identification job on it and remove the upload again
+    async fn identify_language_with_aws(
+        &self,
+        clip: &Path,
+        audio_info: &AudioInfo,
+        multiple: bool,
+        candidates: &[String],
+    ) -> Result<Vec<detect::DetectedLanguage>> {
+        use aws_sdk_transcribe::types::{Media, MediaFormat};
+        
+        let clip_info = AudioInfo {
+            format: AudioFormat::Wav,
+            sample_rate: None,
+            ..audio_info.clone()
+        };
+        self.heartbeat.set_stage("Uploading language sample to S3");
+        let s3_key = self.upload_to_s3(clip, &clip_info).await?;
+        let media_uri = format!("s3://{}/{}", self.config.aws.s3_bucket, s3_key);
+        
+        let job_name = format!("{}language_{}", crate::jobs::JOB_NAME_PREFIX, Uuid::new_v4());
+        let _slot = self.scheduler.aws_job().await;
+        tracing::info!("Starting language identification job: {}", job_name);
+        self.with_fresh_credentials(|aws| {
+            let request = aws
+                .transcribe
+                .start_transcription_job()
+                .transcription_job_name(&job_name)
+                .media_format(MediaFormat::Wav)
+                .media(Media::builder().media_file_uri(&media_uri).build());
+            let mut request = if multiple {
+                request.identify_multiple_languages(true)
+            } else {
+                request.identify_language(true)
+            };
+            for code in candidates {
+                request = request.language_options(code.as_str().into());
+            }
+            async move {
+                request.send().await.context("Failed to start language identification job")?;
+                Ok(())
+            }
+        })
+        .await?;
+        
+        self.heartbeat.set_stage(format!("Waiting for language identification job {}", job_name));
+        let job = self
+            .with_fresh_credentials(|aws| {
+                let processor = processor::TranscriptionProcessor::new(
+                    aws.transcribe,
+                    self.http_client.clone(),
+                    self.config.http.retries,
+                    job_name.clone(),
+                    0.0,
+                );
+                async move { processor.wait_for_job().await }
+            })
+            .await?;
+        
+        self.cleanup_s3(&s3_key).await?;
+        
+        Ok(detect::from_aws_job(&job))
+    }
+    
+    /// Small MP3 copy of a yt-dlp download made at a higher quality, so the kept copy is not what gets uploaded
+    async fn compress_for_upload(&self, audio_info: &AudioInfo, audio_path: &Path) -> Result<Option<(AudioInfo, PathBuf)>> {
+        if audio_info.download_method != DownloadMethod::YtDlp || self.config.yt_dlp.is_compact() {
+            return Ok(None);
+        }
+        
+        self.heartbeat.set_stage("Compressing audio for upload");
+        let upload_path = self.temp_dir.path().join(format!("upload_{}.mp3", &Uuid::new_v4().to_string()[..8]));
+        let output = tokio::process::Command::new("ffmpeg")
+            .args([
+                "-i", &audio_path.to_string_lossy(),
+                "-vn",
+                "-ac", "1",
+                "-c:a", "libmp3lame",
+                "-q:a", "9",
+                "-y",
+                &upload_path.to_string_lossy(),
+            ])
+            .output()
+            .await
+            .context("Failed to run ffmpeg")?;
+        
+        crate::logs::check("ffmpeg", &output, "Failed to compress audio for upload")?;
+        
+        let upload_info = AudioInfo {
+            format: AudioFormat::Mp3,
+            ..audio_info.clone()
+        };
+        Ok(Some((upload_info, upload_path)))
+    }
+    
+    /// Draft locally with Whisper, then re-transcribe only the low-confidence spans with AWS
+    async fn transcribe_hybrid(
+        &self,
+        audio_path: &Path,
+        options: &TranscribeOptions,
+    ) -> Result<(processor::ProcessedTranscription, f64)> {
+        self.heartbeat.set_stage("Drafting with Whisper");
+        let mut draft = WhisperBackend::new(self.config.whisper.clone())?
+            .transcribe(audio_path, options)
+            .await?;
+        
+        let hybrid = &self.config.hybrid;
+        let spans = hybrid::low_confidence_spans(&draft, hybrid);
+        if spans.is_empty() {
+            tracing::info!("Whisper draft is confident throughout, nothing sent to AWS");
+            return Ok((draft, 0.0));
+        }
+        
+        // Spans are cut from one 16 kHz WAV so clips need no further conversion; both live in a
+        // directory of this run's own, removed when it is dropped, whether or not the spans succeed
+        let run_dir = TempDir::new_in(self.temp_dir.path()).context("Failed to create a directory for hybrid clips")?;
+        let wav_path = run_dir.path().join("hybrid.wav");
+        crate::whisper::convert_to_wav(audio_path, &wav_path).await?;
+        
+        // Spans are sent in the language Whisper heard, since AWS cannot identify one in a short clip
+        let language = options.language.clone().or_else(|| crate::languages::aws_code(&draft.metadata.language));
+        if language.is_none() {
+            tracing::warn!(
+                "AWS does not know the language Whisper heard ({}), so it will guess it per span",
+                draft.metadata.language
+            );
+        }
+        let span_options = TranscribeOptions { language, ..options.clone() };
+        
+        // Spans run side by side, as many at once as the scheduler allows uploads and AWS jobs
+        let count = spans.len();
+        let clips = spans.iter().enumerate().map(|(index, span)| {
+            let wav_path = &wav_path;
+            let span_options = &span_options;
+            async move {
+                let clip_start = (span.start_time - hybrid.padding_secs).max(0.0);
+                let clip_end = span.end_time + hybrid.padding_secs;
+                let clip_path = wav_path.with_file_name(format!("span_{}.wav", index));
+                hybrid::cut_clip(wav_path, clip_start, clip_end, &clip_path).await?;
+                
+                tracing::info!(
+                    "Re-transcribing span {}/{} ({:.1}s-{:.1}s) with AWS",
+                    index + 1,
+                    count,
+                    span.start_time,
+                    span.end_time
+                );
+                let clip_info = AudioInfo {
+                    download_url: clip_path.display().to_string(),
+                    download_method: crate::extractors::DownloadMethod::LocalFile,
+                    duration: None,
+                    title: None,
+                    format: crate::extractors::AudioFormat::Wav,
+                    sample_rate: Some(16000),
+                    file_size: None,
+                    original_url: clip_path.display().to_string(),
+                    chapters: Vec::new(),
+                };
+                let clip = self.transcribe_with_aws(&clip_info, &clip_path, span_options, false).await?;
+                anyhow::Ok((clip_start, clip_end, clip))
+            }
+        });
+        let clips = futures_util::future::try_join_all(clips).await?;
+        
+        let total = draft.metadata.audio_duration.unwrap_or(0.0);
+        let mut sent = 0.0;
+        for (span, (clip_start, clip_end, clip)) in spans.iter().zip(clips) {
+            hybrid::merge_span(&mut draft, span, clip_start, &clip);
+            sent += clip_end - clip_start;
+        }
+        
+        if total > 0.0 {
+            tracing::info!(
+                "Hybrid mode sent {:.0}s of {:.0}s ({:.0}%) to AWS",
+                sent,
+                total,
+                sent / total * 100.0
+            );
+        }
+        
+        Ok((draft, sent))
+    }
+    
+    /// Transcribe an object that already lives in S3, skipping download and upload
+    pub async fn transcribe_s3_object(
+        &self,
+        location: &S3Location,
+        options: &TranscribeOptions,
+    ) -> Result<TranscriptionResult> {
+        if options.backend != Backend::Aws {
+            anyhow::bail!("s3:// inputs can only be transcribed with the AWS backend");
+        }
+        if options.max_cost.is_some() {
+            tracing::warn!("--max-cost is not checked for s3:// inputs, whose length is unknown before transcription");
+        }
+        
+        let (audio_info, copied_key) = self.stage_s3_object(location, options).await?;
+        
+        let media_uri = match &copied_key {
+            Some(key) => format!("s3://{}/{}", self.config.aws.s3_bucket, key),
+            None => location.uri(),
+        };
+        
+        let started_at = std::time::Instant::now();
+        let result = self.run_transcription_job(&media_uri, copied_key.as_deref(), true, &audio_info, options).await?;
+        
+        // Only our own copy is removed, never the caller's object
+        if let Some(key) = &copied_key {
+            self.cleanup_s3(key).await?;
+        }
+        
+        if options.save_audio {
+            tracing::warn!("--save-audio has no effect for s3:// inputs; the audio stays in S3");
+        }
+        
+        let mut result = TranscriptionResult {
+            transcript: result.transcript,
+            segments: result.segments,
+            audio_info,
+            audio_path: None,
+            metadata: result.metadata,
+            words: result.words,
+            raw: result.raw,
+            chapters: Vec::new(),
+            redactions: result.redactions,
+            keywords: None,
+        };
+        
+        self.finish_text(&mut result, options).await;
+        
+        self.run_post_transcribe_hooks(&mut result).await?;
+        self.record_history(&result, options, None, started_at);
+        self.keep_raw_transcript(&result, options);
+        
+        Ok(result)
+    }
+    
+    /// Check that an S3 input exists, copying it into the configured bucket if Transcribe cannot read it where it is
+    ///
+    /// Returns the key of the copy, if one was made.
+    async fn stage_s3_object(
+        &self,
+        location: &S3Location,
+        options: &TranscribeOptions,
+    ) -> Result<(AudioInfo, Option<String>)> {
+        // Transcribe can only read objects from buckets in its own region, and S3 only
+        // answers for an object from the region its bucket is in
+        let foreign_region = self
+            .bucket_region(&location.bucket)
+            .await
+            .filter(|region| *region != self.config.aws.region);
+        let s3 = match &foreign_region {
+            Some(region) if !options.copy_cross_region => {
+                anyhow::bail!(
+                    "Bucket '{}' is in {} but transcription runs in {} (use --copy-cross-region to copy the object into '{}')",
+                    location.bucket,
+                    region,
+                    self.config.aws.region,
+                    self.config.aws.s3_bucket
+                );
+            }
+            Some(region) => S3Client::from_conf(
+                self.aws().s3.config().to_builder().region(aws_sdk_s3::config::Region::new(region.clone())).build(),
+            ),
+            None => self.aws().s3,
+        };
+        
+        let head = s3
+            .head_object()
+            .bucket(&location.bucket)
+            .key(&location.key)
+            .send()
+            .await
+            .with_context(|| format!("Failed to access {}", location.uri()))?;
+        let audio_info = location.audio_info(head.content_length().map(|len| len as u64));
+        
+        let copied_key = match foreign_region {
+            Some(_) => Some(self.copy_to_bucket(location, &audio_info).await?),
+            None => None,
+        };
+        Ok((audio_info, copied_key))
+    }
+    
+    /// Look up the region a bucket lives in, if we are allowed to ask
+    async fn bucket_region(&self, bucket: &str) -> Option<String> {
+        match self.aws().s3.get_bucket_location().bucket(bucket).send().await {
+            // An empty location constraint means us-east-1
+            Ok(output) => Some(
+                output
+                    .location_constraint()
+                    .map(|c| c.as_str())
+                    .filter(|c| !c.is_empty())
+                    .unwrap_or("us-east-1")
+                    .to_string(),
+            ),
+            Err(e) => {
+                tracing::debug!("Could not determine region of bucket {}: {}", bucket, e);
+                None
+            }
+        }
+    }
+    
+    /// Server-side copy of an S3 object into the configured bucket
+    async fn copy_to_bucket(&self, location: &S3Location, audio_info: &AudioInfo) -> Result<String> {
+        let key = format!(
+            "{}audio_{}_{}.{}",
+            self.config.aws.s3_key_prefix.as_deref().unwrap_or(""),
+            Uuid::new_v4(),
+            chrono::Utc::now().format("%Y%m%d_%H%M%S"),
+            audio_info.format.as_str()
+        );
+        
+        tracing::info!(
+            "Copying {} to s3://{}/{}",
+            location.uri(),
+            self.config.aws.s3_bucket,
+            key
+        );
+        
+        self.aws().s3
+            .copy_object()
+            .copy_source(format!("{}/{}", location.bucket, urlencoding::encode(&location.key)))
+            .bucket(&self.config.aws.s3_bucket)
+            .key(&key)
+            .send()
+            .await
+            .context("Failed to copy S3 object into the transcription bucket")?;
+        
+        Ok(key)
+    }
+    
+    /// Download audio file to temporary location
+    async fn download_audio(&self, audio_info: &AudioInfo) -> Result<PathBuf> {
+        let filename = format!(
+            "audio_{}.{}",
+            &Uuid::new_v4().to_string()[..8],
+            audio_info.format.as_str()
+        );
+        let audio_path = self.temp_dir.path().join(filename);
+        
+        let _slot = self.scheduler.download().await;
+        tracing::info!("Downloading audio to: {}", audio_path.display());
+        
+        let message = match audio_info.download_method {
+            DownloadMethod::DirectUrl => return self.download_direct(audio_info, audio_path).await,
+            DownloadMethod::Stream => return self.capture_stream(audio_info, audio_path).await,
+            DownloadMethod::YtDlp => format!("Downloading {} audio with yt-dlp...", self.platform_of(audio_info)),
+            DownloadMethod::Plugin => format!("Downloading audio with the {} plugin...", self.platform_of(audio_info)),
+            DownloadMethod::LocalFile => return self.prepare_local_file(audio_info, audio_path).await,
+        };
+        
+        let progress = crate::progress::add(ProgressBar::new_spinner().with_style(
+            ProgressStyle::default_spinner()
+                .template("{spinner:.green} [{elapsed_precise}] {prefix}{msg}")
+                .unwrap()
+        ));
+        progress.set_message(message);
+        progress.enable_steady_tick(std::time::Duration::from_millis(250));
+        
+        // yt-dlp and plugin sources are fetched by the extractor that produced them
+        self.extractor_registry.download_audio(audio_info, &audio_path).await?;
+        
+        progress.finish_with_message("Download complete");
+        Ok(audio_path)
+    }
+    
+    /// Name of the platform a source URL belongs to
+    fn platform_of(&self, audio_info: &AudioInfo) -> String {
+        self.extractor_registry
+            .find_extractor(&audio_info.original_url)
+            .map(|extractor| extractor.platform_name().to_string())
+            .unwrap_or_default()
+    }
+    
+    /// Copy a local audio file into the temp dir, or convert it (and video files) with ffmpeg
+    async fn prepare_local_file(&self, audio_info: &AudioInfo, audio_path: PathBuf) -> Result<PathBuf> {
+        let progress = crate::progress::add(ProgressBar::new_spinner().with_style(
+            ProgressStyle::default_spinner()
+                .template("{spinner:.green} [{elapsed_precise}] {prefix}{msg}")
+                .unwrap()
+        ));
+        progress.set_message("Processing local audio file...");
+        progress.enable_steady_tick(std::time::Duration::from_millis(250));
+        
+        ExtractorRegistry::create_local_extractor()
+            .prepare_audio(Path::new(&audio_info.download_url), &audio_path)
+            .await?;
+        
+        progress.finish_with_message("File processing complete");
+        Ok(audio_path)
+    }
+    
+    /// Capture an HLS/DASH stream with ffmpeg
+    async fn capture_stream(&self, audio_info: &AudioInfo, audio_path: PathBuf) -> Result<PathBuf> {
+        let max_duration = self.config.app.max_stream_secs.map(std::time::Duration::from_secs);
+        
+        let progress = crate::progress::add(ProgressBar::new_spinner().with_style(
+            ProgressStyle::default_spinner()
+                .template("{spinner:.green} [{elapsed_precise}] {prefix}{msg}")
+                .unwrap()
+        ));
+        progress.set_message(match max_duration {
+            Some(max) => format!("Capturing stream audio with ffmpeg (up to {})...", crate::utils::format_duration(max.as_secs_f64())),
+            None => "Capturing stream audio with ffmpeg...".to_string(),
+        });
+        progress.enable_steady_tick(std::time::Duration::from_millis(250));
+        
+        crate::extractors::direct::capture_stream(&audio_info.download_url, &audio_path, max_duration, &self.config.network).await?;
+        
+        progress.finish_with_message("Capture complete");
+        Ok(audio_path)
+    }
+    
+    /// Download a media file over HTTP, reusing an unchanged cached copy
+    async fn download_direct(&self, audio_info: &AudioInfo, audio_path: PathBuf) -> Result<PathBuf> {
+        // Create progress bar for regular downloads
+        let progress = crate::progress::add(ProgressBar::new(audio_info.file_size.unwrap_or(0)).with_style(
+            ProgressStyle::default_bar()
+                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} {prefix}{msg}")
+                .unwrap()
+        ));
+        progress.set_message("Downloading audio...");
+        
+        // Revalidate a previously cached copy instead of downloading it again
+        let cache = if self.config.app.download_cache && self.config.artifacts.enabled {
+            DownloadCache::open(&self.config.artifacts).ok()
+        } else {
+            None
+        };
+        let cached = cache.as_ref().and_then(|c| c.lookup(&audio_info.download_url));
+        
+        // Resumes are sent with the same cookies, so gated downloads stay let in
+        let mut resume = self.http_client.get(&audio_info.download_url);
+        if let Some(cookies) = self.config.cookies.header_for(&audio_info.download_url) {
+            resume = resume.header(reqwest::header::COOKIE, cookies);
+        }
+        let mut request = resume.try_clone().context("Download request cannot be repeated")?;
+        if let Some((source, _)) = &cached {
+            if let Some(etag) = &source.etag {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &source.last_modified {
+                request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        
+        // Download with progress tracking for non-YouTube URLs
+        let response = crate::http::send_with_retry(request, self.config.http.retries).await?;
+        
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            if let Some((_, cached_audio)) = &cached {
+                fs_err::copy(cached_audio, &audio_path)?;
+                progress.finish_with_message("Source unchanged, using cached audio");
+                return Ok(audio_path);
+            }
+        }
+        
+        if !response.status().is_success() {
+            anyhow::bail!("Failed to download audio: HTTP {}", response.status());
+        }
+        
+        let header = |name: reqwest::header::HeaderName| {
+            response.headers().get(name).and_then(|v| v.to_str().ok()).map(str::to_string)
+        };
+        let etag = header(reqwest::header::ETAG);
+        let last_modified = header(reqwest::header::LAST_MODIFIED);
+        
+        download::stream_to_file(resume, response, &audio_path, &progress).await?;
+        
+        progress.finish_with_message("Download complete");
+        
+        // Only sources with validators can be revalidated later
+        if let Some(cache) = &cache {
+            if etag.is_some() || last_modified.is_some() {
+                if let Err(e) = cache.store(&audio_info.download_url, etag, last_modified, &audio_path) {
+                    tracing::warn!("Failed to cache downloaded audio: {:#}", e);
+                }
+            }
+        }
+        
+        Ok(audio_path)
+    }
+    
+    /// Upload audio file to S3
+    async fn upload_to_s3(&self, audio_path: &Path, audio_info: &AudioInfo) -> Result<String> {
+        let key = format!(
+            "{}audio_{}_{}.{}",
+            self.config.aws.s3_key_prefix.as_deref().unwrap_or(""),
+            Uuid::new_v4(),
+            chrono::Utc::now().format("%Y%m%d_%H%M%S"),
+            audio_info.format.as_str()
+        );
+        
+        let _slot = self.scheduler.upload().await;
+        tracing::info!("Uploading audio to S3: s3://{}/{}", self.config.aws.s3_bucket, key);
+        
+        // In hybrid mode uploads happen hours into a run, so they get the expiry retry too
+        self.with_fresh_credentials(|aws| {
+            let key = &key;
+            async move {
+                let body = aws_sdk_s3::primitives::ByteStream::from_path(audio_path)
+                    .await
+                    .with_context(|| format!("Failed to read {}", audio_path.display()))?;
+                
+                aws.s3
+                    .put_object()
+                    .bucket(&self.config.aws.s3_bucket)
+                    .key(key)
+                    .body(body)
+                    .content_type(audio_info.format.mime_type())
+                    .send()
+                    .await
+                    .context("Failed to upload audio to S3")?;
+                Ok(())
+            }
+        })
+        .await?;
+            
+        Ok(key)
+    }
+    
+    /// Start a transcription job and wait for it, holding an AWS job slot throughout
+    ///
+    /// A `resumable` job is remembered until its result is in, together with `s3_key`, our
+    /// upload it reads from, so `resume` can collect it if this process is interrupted while
+    /// waiting. Pieces of a larger run (hybrid spans, live chunks) are not resumable, since
+    /// resuming one on its own would only give a fragment.
+    async fn run_transcription_job(
+        &self,
+        media_uri: &str,
+        s3_key: Option<&str>,
+        resumable: bool,
+        audio_info: &AudioInfo,
+        options: &TranscribeOptions,
+    ) -> Result<processor::ProcessedTranscription> {
+        let _slot = self.scheduler.aws_job().await;
+        let job_id = self.start_transcription_job(media_uri, audio_info, options).await?;
+        
+        let tracked = resumable && self.track_job(&job_id, s3_key, audio_info, options);
+        let result = self.wait_for_transcription(&job_id, options.max_segment_length).await;
+        if tracked {
+            match &result {
+                Err(e) if !is_job_failure(e) => {
+                    tracing::warn!("Job {} keeps running on AWS; collect it with `rustscribe resume {}`", job_id, job_id);
+                }
+                _ => self.forget_job(&job_id),
+            }
+        }
+        result
+    }
+    
+    /// Save a started job for `resume`; failures only cost the ability to resume
+    fn track_job(&self, job_name: &str, s3_key: Option<&str>, audio_info: &AudioInfo, options: &TranscribeOptions) -> bool {
+        let job = InFlightJob {
+            job_name: job_name.to_string(),
+            started_at: chrono::Utc::now(),
+            input: audio_info.original_url.clone(),
+            s3_key: s3_key.map(str::to_string),
+            audio_info: audio_info.clone(),
+            options: options.clone(),
+            output: self.output.clone(),
+        };
+        match JobStore::open().and_then(|store| store.save(&job)) {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!("Could not save job state, the job cannot be resumed: {:#}", e);
+                false
+            }
+        }
+    }
+    
+    fn forget_job(&self, job_name: &str) {
+        if let Err(e) = JobStore::open().and_then(|store| store.remove(job_name)) {
+            tracing::warn!("Could not remove job state for {}: {:#}", job_name, e);
+        }
+    }
+    
+    /// Collect the result of a job an earlier run started but did not wait for
+    ///
+    /// The job's upload is removed and the job forgotten once it has finished, successfully or
+    /// not. If AWS cannot be reached the job is kept for another attempt.
+    pub async fn resume_job(&self, job: &InFlightJob) -> Result<TranscriptionResult> {
+        let started_at = std::time::Instant::now();
+        tracing::info!("Reattaching to transcription job {}", job.job_name);
+        
+        let processed = match self.wait_for_transcription(&job.job_name, job.options.max_segment_length).await {
+            Err(e) if !is_job_failure(&e) => return Err(e),
+            processed => processed,
+        };
+        self.discard_job(job).await?;
+        let processed = processed?;
+        
+        let mut audio_info = job.audio_info.clone();
+        let chapters = std::mem::take(&mut audio_info.chapters);
+        let mut result = TranscriptionResult {
+            transcript: processed.transcript,
+            segments: processed.segments,
+            audio_info,
+            audio_path: None,
+            metadata: processed.metadata,
+            words: processed.words,
+            raw: processed.raw,
+            chapters,
+            redactions: processed.redactions,
+            keywords: None,
+        };
+        
+        self.finish_text(&mut result, &job.options).await;
+        
+        self.run_post_transcribe_hooks(&mut result).await?;
+        self.record_history(&result, &job.options, None, started_at);
+        self.keep_raw_transcript(&result, &job.options);
+        
+        Ok(result)
+    }
+    
+    /// Remove an interrupted job's upload and forget it; the job itself is left to AWS
+    pub async fn discard_job(&self, job: &InFlightJob) -> Result<()> {
+        if let Some(key) = &job.s3_key {
+            self.cleanup_s3(key).await?;
+        }
+        JobStore::open()?.remove(&job.job_name)
+    }
+    
+    /// Start AWS Transcribe job with auto language detection and speaker identification
+    async fn start_transcription_job(
+        &self,
+        media_uri: &str,
+        audio_info: &AudioInfo,
+        options: &TranscribeOptions,
+    ) -> Result<String> {
+        let job_name = format!("{}{}", crate::jobs::JOB_NAME_PREFIX, Uuid::new_v4());
+        
+        tracing::info!("Starting transcription job: {}", job_name);
+        
+        self.with_fresh_credentials(|aws| {
+            self.send_transcription_job(aws.transcribe, &job_name, media_uri, audio_info, options)
+        })
+        .await?;
+        
+        Ok(job_name)
+    }
+    
+    /// Build and send the StartTranscriptionJob request
+    async fn send_transcription_job(
+        &self,
+        client: TranscribeClient,
+        job_name: &str,
+        media_uri: &str,
+        audio_info: &AudioInfo,
+        options: &TranscribeOptions,
+    ) -> Result<()> {
+        use aws_sdk_transcribe::types::{Media, MediaFormat, Settings};
+        
+        let media_format = match audio_info.format {
+            crate::extractors::AudioFormat::Mp3 => MediaFormat::Mp3,
+            crate::extractors::AudioFormat::M4a => MediaFormat::Mp4,
+            crate::extractors::AudioFormat::Wav => MediaFormat::Wav,
+            crate::extractors::AudioFormat::Flac => MediaFormat::Flac,
+            crate::extractors::AudioFormat::Ogg => MediaFormat::Ogg,
+            crate::extractors::AudioFormat::Webm => MediaFormat::Webm,
+        };
+        
+        let media = Media::builder()
+            .media_file_uri(media_uri)
+            .build();
+        
+        let mut job_builder = client
+            .start_transcription_job()
+            .transcription_job_name(job_name)
+            .media_format(media_format)
+            .media(media);
+        
+        // Handle language detection
+        if let Some(lang) = options.language.as_deref().or(self.config.aws.transcription.default_language.as_deref()) {
+            tracing::info!("Using specified language: {}", lang);
+            job_builder = job_builder.language_code(lang.parse()?);
+        } else {
+            if options.multi_language {
+                tracing::info!("Identifying every language spoken");
+                job_builder = job_builder.identify_multiple_languages(true);
+            } else {
+                tracing::info!("Using automatic language detection");
+                job_builder = job_builder.identify_language(true);
+            }
+            for code in &options.language_options {
+                job_builder = job_builder.language_options(code.parse()?);
+            }
+        }
+        
+        // Add sample rate to job builder
+        if let Some(sample_rate) = audio_info.sample_rate {
+            job_builder = job_builder.media_sample_rate_hertz(sample_rate as i32);
+        }
+        
+        // Add optional settings for speaker identification and word-level timestamps
+        let mut settings = Settings::builder();
+        
+        // Enable word-level timestamps for more granular segments; only speaker turns are kept otherwise
+        if !options.diarize_only {
+            tracing::info!("Enabling word-level timestamps for better granularity");
+            settings = settings.show_alternatives(true);
+            settings = settings.max_alternatives(2); // AWS requires minimum of 2
+        }
+        
+        // Configure speaker identification
+        let enable_speaker_id =
+            options.speaker_labels || options.diarize_only || self.config.aws.transcription.speaker_identification;
+        if enable_speaker_id {
+            tracing::info!("Enabling speaker identification");
+            settings = settings.show_speaker_labels(true);
+            
+            // Set max speakers (AWS supports 2-10 speakers)
+            let max_speakers_count = options.max_speakers
+                .or(self.config.aws.transcription.max_speakers)
+                .unwrap_or(10); // Default to 10 if not specified
+                
+            let clamped_speakers = max_speakers_count.clamp(2, 10);
+            settings = settings.max_speaker_labels(clamped_speakers as i32);
+            
+            if max_speakers_count != clamped_speakers {
+                tracing::warn!("Max speakers clamped from {} to {} (AWS supports 2-10)", max_speakers_count, clamped_speakers);
+            }
+        }
+        
+        // Bias recognition towards domain-specific terms
+        if let Some(vocabulary) = options.vocabulary.as_ref().filter(|_| !options.diarize_only) {
+            tracing::info!("Using custom vocabulary: {}", vocabulary);
+            settings = settings.vocabulary_name(vocabulary);
+        }
+        
+        job_builder = job_builder.settings(settings.build());
+        
+        if let Some(redaction) = &options.redact_pii {
+            use aws_sdk_transcribe::types::{ContentRedaction, PiiEntityType, RedactionType};
+            
+            tracing::info!("Redacting personal information");
+            let output = match redaction.output {
+                super::RedactionOutput::Redacted => aws_sdk_transcribe::types::RedactionOutput::Redacted,
+                super::RedactionOutput::RedactedAndUnredacted => aws_sdk_transcribe::types::RedactionOutput::RedactedAndUnredacted,
+            };
+            let entity_types = (!redaction.entity_types.is_empty())
+                .then(|| redaction.entity_types.iter().map(|t| PiiEntityType::from(t.as_str())).collect());
+            job_builder = job_builder.content_redaction(
+                ContentRedaction::builder()
+                    .redaction_type(RedactionType::Pii)
+                    .redaction_output(output)
+                    .set_pii_entity_types(entity_types)
+                    .build()?,
+            );
+        }
+        
+        if options.detect_toxicity {
+            use aws_sdk_transcribe::types::{ToxicityCategory, ToxicityDetectionSettings};
+            
+            tracing::info!("Enabling toxicity detection");
+            job_builder = job_builder.toxicity_detection(
+                ToxicityDetectionSettings::builder()
+                    .toxicity_categories(ToxicityCategory::All)
+                    .build()?,
+            );
+        }
+        
+        job_builder.send().await
+            .context("Failed to start transcription job")?;
+            
+        Ok(())
+    }
+    
+    /// Wait for transcription job completion
+    ///
+    /// Jobs can outlive temporary credentials; the job keeps running on AWS, so polling
+    /// resumes with reloaded credentials instead of failing at the final fetch.
+    async fn wait_for_transcription(&self, job_id: &str, max_segment_length: f64) -> Result<processor::ProcessedTranscription> {
+        self.heartbeat.set_stage(format!("Waiting for transcription job {}", job_id));
+        
+        let started_at = std::time::Instant::now();
+        let mut refreshes = 0;
+        loop {
+            let result = processor::TranscriptionProcessor::new(
+                self.aws().transcribe,
+                self.http_client.clone(),
+                self.config.http.retries,
+                job_id.to_string(),
+                max_segment_length,
+            )
+            .started_at(started_at)
+            .wait_for_completion()
+            .await;
+            
+            match result {
+                Err(e) if is_expired_credentials(&e) && refreshes < MAX_CREDENTIAL_REFRESHES => {
+                    refreshes += 1;
+                    self.refresh_aws_clients().await;
+                }
+                result => return result,
+            }
+        }
+    }
+    
+    /// Clean up S3 object
+    async fn cleanup_s3(&self, s3_key: &str) -> Result<()> {
+        tracing::debug!("Cleaning up S3 object: {}", s3_key);
+        
+        self.with_fresh_credentials(|aws| async move {
+            aws.s3
+                .delete_object()
+                .bucket(&self.config.aws.s3_bucket)
+                .key(s3_key)
+                .send()
+                .await
+                .context("Failed to clean up S3 object")?;
+            Ok(())
+        })
+        .await
+    }
+    
+    /// Keep the audio where the `saved_audio` settings say, converted if they ask for it
+    async fn preserve_audio_file(
+        &self,
+        temp_path: &Path,
+        audio_info: &AudioInfo,
+    ) -> Result<PathBuf> {
+        self.config.saved_audio.save(temp_path, audio_info).await
+    }
+    
+    /// Move a transcribed local input into the archive folder, if one is configured; failures only warn
+    fn archive_original(&self, audio_info: &AudioInfo) {
+        if self.config.app.demo {
+            return;
+        }
+        match self.config.saved_audio.archive_original(audio_info) {
+            Ok(Some(archived)) => tracing::info!("Moved {} to {}", audio_info.download_url, archived.display()),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Could not archive {}: {:#}", audio_info.download_url, e),
+        }
+    }
+}
+
+/// Reduce a result to its speaker turns, for `--diarize-only`
+fn keep_speaker_turns(result: &mut TranscriptionResult) {
+    result.segments = crate::transcript::speaker_turns(&result.segments, crate::transcript::TURN_PAUSE_SECS);
+    result.words = None;
+    result.transcript = String::new();
+}
+
+/// Check whether a job ended unsuccessfully on AWS, as opposed to us losing track of it
+fn is_job_failure(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<crate::TranscriptorError>().is_some()
+}
+
+/// Check whether an error was caused by AWS being unreachable rather than rejecting the request
+pub fn is_connectivity_error(error: &anyhow::Error) -> bool {
+    use aws_sdk_s3::error::SdkError;
+    use aws_sdk_s3::operation::put_object::PutObjectError;
+    use aws_sdk_transcribe::operation::start_transcription_job::StartTranscriptionJobError;
+    
+    fn unreachable<E, R>(error: &SdkError<E, R>) -> bool {
+        matches!(error, SdkError::DispatchFailure(_) | SdkError::TimeoutError(_))
+    }
+    
+    error.chain().any(|cause| {
+        cause.downcast_ref::<SdkError<PutObjectError>>().is_some_and(unreachable)
+            || cause.downcast_ref::<SdkError<StartTranscriptionJobError>>().is_some_and(unreachable)
+    })
+}
+
+/// Check whether an AWS call failed because the credentials (usually a session token) expired
+pub fn is_expired_credentials(error: &anyhow::Error) -> bool {
+    use aws_sdk_s3::error::{ProvideErrorMetadata, SdkError};
+    use aws_sdk_s3::operation::delete_object::DeleteObjectError;
+    use aws_sdk_s3::operation::put_object::PutObjectError;
+    use aws_sdk_transcribe::operation::get_transcription_job::GetTranscriptionJobError;
+    use aws_sdk_transcribe::operation::start_transcription_job::StartTranscriptionJobError;
+    
+    const EXPIRED_CODES: &[&str] = &["ExpiredToken", "ExpiredTokenException", "RequestExpired", "TokenRefreshRequired"];
+    
+    fn expired<E>(cause: &(dyn std::error::Error + 'static)) -> bool
+    where
+        E: ProvideErrorMetadata + std::error::Error + 'static,
+    {
+        cause
+            .downcast_ref::<SdkError<E>>()
+            .and_then(|e| e.code())
+            .is_some_and(|code| EXPIRED_CODES.contains(&code))
+    }
+    
+    error.chain().any(|cause| {
+        expired::<GetTranscriptionJobError>(cause)
+            || expired::<StartTranscriptionJobError>(cause)
+            || expired::<PutObjectError>(cause)
+            || expired::<DeleteObjectError>(cause)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_split_channels_on_every_path() {
+        let mut config = Config::demo();
+        config.health.heartbeat_secs = 0;
+        let pipeline = TranscriptionPipeline::new(config).await.unwrap();
+        let options = TranscribeOptions {
+            split_channels: true,
+            ..Default::default()
+        };
+
+        let (audio_info, audio_path) = pipeline.prepare_audio("call.wav").await.unwrap();
+        let prepared = pipeline.transcribe_audio(audio_info, &audio_path, &options).await.unwrap();
+        let from_url = pipeline.transcribe_from_url("call.wav", &options).await.unwrap();
+        for result in [prepared, from_url] {
+            let speakers: Vec<_> = result.segments.iter().map(|s| s.speaker_id.as_deref().unwrap()).collect();
+            assert_eq!(speakers[..4], ["left", "left", "right", "left"]);
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_split_channels_runs_hooks_once() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let seen = dir.path().join("seen");
+        let mut config = Config::demo();
+        config.health.heartbeat_secs = 0;
+        // Notes "pre" for the audio, and how often the right channel speaks in the result
+        let script = dir.path().join("hook.sh");
+        fs_err::write(&script, format!("#!/bin/sh\n[ \"$1\" = pre ] && echo pre >> {0} || grep -c right \"$2\" >> {0}\n", seen.display())).unwrap();
+        fs_err::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        config.hooks.pre_upload = vec![format!("{} pre", script.display())];
+        config.hooks.post_transcribe = vec![format!("{} post", script.display())];
+        let pipeline = TranscriptionPipeline::new(config).await.unwrap();
+        let options = TranscribeOptions {
+            split_channels: true,
+            ..Default::default()
+        };
+        pipeline.transcribe_from_url("call.wav", &options).await.unwrap();
+
+        // Both hooks ran once, and the result they saw has the right channel in it
+        let seen = fs_err::read_to_string(&seen).unwrap();
+        let lines: Vec<_> = seen.lines().collect();
+        assert_eq!(lines.len(), 2, "{}", seen);
+        assert_eq!(lines[0], "pre");
+        assert_ne!(lines[1], "0");
+    }
+
+    /// S3 with the bucket `talks` in eu-west-1 and the demo bucket in us-east-1, which only
+    /// answer requests signed for their own region
+    async fn s3_with_bucket_in_eu_west_1() -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = vec![0; 8192];
+                let read = socket.read(&mut request).await.unwrap();
+                let request = String::from_utf8_lossy(&request[..read]).to_lowercase();
+                let (status, body) = if request.starts_with("get /talks/?location") {
+                    ("200 OK", "<LocationConstraint>eu-west-1</LocationConstraint>")
+                } else if request.starts_with("head /talks/monday.mp3") && request.contains("/eu-west-1/s3/") {
+                    ("200 OK", "")
+                } else if request.starts_with("put /rustscribe-demo/") && request.contains("/us-east-1/s3/") {
+                    ("200 OK", "<CopyObjectResult><ETag>\"v1\"</ETag></CopyObjectResult>")
+                } else {
+                    ("301 Moved Permanently", "")
+                };
+                // HEAD answers with the object's length rather than the body's
+                let length = if request.starts_with("head") { 1234 } else { body.len() };
+                let response = format!("HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, length, body);
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn test_s3_object_in_another_region() {
+        let mut config = Config::demo();
+        config.health.heartbeat_secs = 0;
+        config.aws.region = "us-east-1".to_string();
+        let pipeline = TranscriptionPipeline::new(config).await.unwrap();
+        let s3 = aws_sdk_s3::config::Builder::new()
+            .behavior_version(aws_sdk_s3::config::BehaviorVersion::latest())
+            .region(aws_sdk_s3::config::Region::new("us-east-1"))
+            .credentials_provider(aws_sdk_s3::config::Credentials::new("key", "secret", None, None, "test"))
+            .endpoint_url(s3_with_bucket_in_eu_west_1().await)
+            .force_path_style(true)
+            .build();
+        pipeline.aws.write().unwrap().s3 = S3Client::from_conf(s3);
+        let location = S3Location::parse("s3://talks/monday.mp3").unwrap();
+
+        let error = pipeline.stage_s3_object(&location, &TranscribeOptions::default()).await.unwrap_err();
+        assert!(error.to_string().contains("is in eu-west-1"), "{}", error);
+
+        // The object is looked up in its own region and copied into ours
+        let options = TranscribeOptions {
+            copy_cross_region: true,
+            ..Default::default()
+        };
+        let (audio_info, copied_key) = pipeline.stage_s3_object(&location, &options).await.unwrap();
+        assert_eq!(audio_info.file_size, Some(1234));
+        assert!(copied_key.unwrap().ends_with(".mp3"));
+    }
+}
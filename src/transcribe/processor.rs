@@ -1,13 +1,17 @@
 use anyhow::{Context, Result};
 use aws_sdk_transcribe::Client as TranscribeClient;
-use aws_sdk_transcribe::types::{TranscriptionJob, TranscriptionJobStatus};
+use aws_sdk_transcribe::types::{LanguageCode, TranscriptionJob, TranscriptionJobStatus};
 use indicatif::{ProgressBar, ProgressStyle};
 use serde::Deserialize;
+use std::path::PathBuf;
 use std::time::Duration;
 use tokio::time::sleep;
 
-use super::{TranscriptSegment, TranscriptionMetadata};
-use crate::output::formatters::WordTimestamp;
+use super::{Redaction, Toxicity, TranscriptSegment, TranscriptionMetadata, TranscriptionResult};
+use crate::extractors::AudioInfo;
+use crate::output::formatters::{WordAlternative, WordTimestamp};
+use crate::transcript::MAX_SEGMENT_WIDTH;
+use crate::utils::text;
 
 /// Processed transcription result from AWS
 #[derive(Debug, Clone)]
@@ -16,10 +20,34 @@ pub struct ProcessedTranscription {
     pub segments: Vec<TranscriptSegment>,
     pub metadata: TranscriptionMetadata,
     pub words: Option<Vec<WordTimestamp>>,
+    /// Raw backend output, when the backend provides one
+    pub raw: Option<serde_json::Value>,
+    /// Spans redacted by AWS content redaction
+    pub redactions: Vec<Redaction>,
+}
+
+impl ProcessedTranscription {
+    /// The result for `audio_info`, whose chapters belong to the transcript from here on
+    pub fn into_result(self, mut audio_info: AudioInfo, audio_path: Option<PathBuf>) -> TranscriptionResult {
+        let chapters = std::mem::take(&mut audio_info.chapters);
+        TranscriptionResult {
+            transcript: self.transcript,
+            segments: self.segments,
+            audio_info,
+            audio_path,
+            metadata: self.metadata,
+            words: self.words,
+            raw: self.raw,
+            chapters,
+            redactions: self.redactions,
+            keywords: None,
+        }
+    }
 }
 
 /// AWS Transcribe transcript format
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 struct AwsTranscript {
     #[serde(rename = "jobName")]
     job_name: String,
@@ -30,11 +58,55 @@ struct AwsTranscript {
 }
 
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 struct TranscriptResults {
     transcripts: Vec<TranscriptText>,
     items: Vec<TranscriptItem>,
     #[serde(rename = "speaker_labels")]
     speaker_labels: Option<SpeakerLabels>,
+    /// Present when the job ran with toxicity detection
+    #[serde(default)]
+    toxicity_detection: Vec<ToxicitySpan>,
+    /// Ranked transcripts of each stretch of speech, present when the job shows alternatives
+    #[serde(default)]
+    segments: Vec<AlternativeSegment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AlternativeSegment {
+    alternatives: Vec<SegmentAlternative>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SegmentAlternative {
+    items: Vec<AlternativeItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AlternativeItem {
+    start_time: Option<String>,
+    end_time: Option<String>,
+    #[serde(rename = "type", default)]
+    item_type: String,
+    content: String,
+    confidence: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ToxicitySpan {
+    /// Seconds, as a number or a string depending on the transcript version
+    start_time: serde_json::Value,
+    end_time: serde_json::Value,
+    toxicity: f64,
+    #[serde(default)]
+    categories: std::collections::BTreeMap<String, f64>,
+}
+
+impl ToxicitySpan {
+    fn times(&self) -> Option<(f64, f64)> {
+        let seconds = |value: &serde_json::Value| value.as_f64().or_else(|| value.as_str()?.parse().ok());
+        seconds(&self.start_time).zip(seconds(&self.end_time))
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -50,12 +122,25 @@ struct TranscriptItem {
     item_type: String,
     alternatives: Vec<Alternative>,
     speaker_label: Option<String>,
+    /// Set when the job identified multiple languages
+    #[serde(default)]
+    language_code: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct Alternative {
     confidence: Option<String>,
     content: String,
+    /// What AWS replaced this item for, when it redacted it
+    #[serde(default)]
+    redactions: Vec<ItemRedaction>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ItemRedaction {
+    #[serde(rename = "type")]
+    entity_type: String,
+    confidence: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -73,6 +158,7 @@ struct SpeakerSegment {
 }
 
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 struct SpeakerItem {
     start_time: String,
     end_time: String,
@@ -82,26 +168,46 @@ struct SpeakerItem {
 /// Transcription job processor
 pub struct TranscriptionProcessor {
     client: TranscribeClient,
+    http_client: reqwest::Client,
+    http_retries: u32,
     job_id: String,
     max_segment_length: f64,
+    started_at: std::time::Instant,
 }
 
 impl TranscriptionProcessor {
-    pub fn new(client: TranscribeClient, job_id: String, max_segment_length: f64) -> Self {
-        Self { client, job_id, max_segment_length }
+    pub fn new(
+        client: TranscribeClient,
+        http_client: reqwest::Client,
+        http_retries: u32,
+        job_id: String,
+        max_segment_length: f64,
+    ) -> Self {
+        Self { client, http_client, http_retries, job_id, max_segment_length, started_at: std::time::Instant::now() }
+    }
+    
+    /// Count processing time from an earlier start, when resuming a job that is already running
+    pub fn started_at(mut self, started_at: std::time::Instant) -> Self {
+        self.started_at = started_at;
+        self
     }
     
     /// Wait for transcription job completion with progress tracking
     pub async fn wait_for_completion(&self) -> Result<ProcessedTranscription> {
-        let progress = ProgressBar::new_spinner();
-        progress.set_style(
+        let job = self.wait_for_job().await?;
+        self.process_transcription_result(job, self.started_at.elapsed()).await
+    }
+    
+    /// Poll the job until it finishes and return its final state, without fetching the transcript
+    pub async fn wait_for_job(&self) -> Result<TranscriptionJob> {
+        let progress = crate::progress::add(ProgressBar::new_spinner().with_style(
             ProgressStyle::default_spinner()
-                .template("{spinner:.green} {msg}")
+                .template("{spinner:.green} {prefix}{msg}")
                 .unwrap()
-        );
+        ));
         progress.set_message("Starting transcription job...");
         
-        let start_time = std::time::Instant::now();
+        let start_time = self.started_at;
         let mut check_count = 0;
         
         loop {
@@ -131,7 +237,7 @@ impl TranscriptionProcessor {
                     
                     let failure_reason = job.failure_reason()
                         .unwrap_or("Unknown error");
-                    anyhow::bail!("Transcription job failed: {}", failure_reason);
+                    return Err(crate::TranscriptorError::TranscriptionFailed(failure_reason.to_string()).into());
                 }
                 _ => {
                     progress.finish_with_message("Transcription status unknown");
@@ -140,9 +246,7 @@ impl TranscriptionProcessor {
             }
         }
         
-        // Get and process the results
-        let job = self.get_transcription_job().await?;
-        self.process_transcription_result(job, start_time.elapsed()).await
+        self.get_transcription_job().await
     }
     
     /// Get transcription job details
@@ -156,7 +260,7 @@ impl TranscriptionProcessor {
             
         response.transcription_job()
             .ok_or_else(|| anyhow::anyhow!("Transcription job not found"))
-            .map(|job| job.clone())
+            .cloned()
     }
     
     /// Process completed transcription result
@@ -165,17 +269,31 @@ impl TranscriptionProcessor {
         job: TranscriptionJob,
         processing_duration: std::time::Duration,
     ) -> Result<ProcessedTranscription> {
-        // Get transcript URI
-        let transcript_uri = job.transcript()
-            .and_then(|t| t.transcript_file_uri())
+        // Get transcript URI; a redacted job's transcript is the redacted one
+        let redacted_uri = job.transcript().and_then(|t| t.redacted_transcript_file_uri());
+        let unredacted_uri = job.transcript().and_then(|t| t.transcript_file_uri());
+        let transcript_uri = redacted_uri
+            .or(unredacted_uri)
             .ok_or_else(|| anyhow::anyhow!("No transcript URI found"))?;
             
         // Download transcript JSON
         let transcript_json = self.download_transcript(transcript_uri).await?;
         
         // Parse transcript
-        let aws_transcript: AwsTranscript = serde_json::from_str(&transcript_json)
+        let mut aws_transcript: AwsTranscript = serde_json::from_str(&transcript_json)
             .context("Failed to parse transcript JSON")?;
+        assign_speakers(&mut aws_transcript.results);
+        let mut raw = serde_json::from_str::<serde_json::Value>(&transcript_json)
+            .ok()
+            .map(|mut value| value["results"].take());
+        
+        // With redacted-and-unredacted output the original is kept alongside, never in the transcript
+        if let (Some(_), Some(uri), Some(raw)) = (redacted_uri, unredacted_uri, raw.as_mut()) {
+            let unredacted = self.download_transcript(uri).await?;
+            if let Ok(mut value) = serde_json::from_str::<serde_json::Value>(&unredacted) {
+                raw["unredacted"] = value["results"].take();
+            }
+        }
             
         // Extract main transcript text
         let transcript = aws_transcript.results.transcripts
@@ -184,18 +302,23 @@ impl TranscriptionProcessor {
             .unwrap_or_default();
             
         // Process segments with timestamps
-        let (segments, words) = self.process_segments(&aws_transcript.results)?;
+        let (mut segments, mut words) = self.process_segments(&aws_transcript.results)?;
+        attach_alternatives(&mut words, &aws_transcript.results.segments);
+        attach_toxicity(&mut segments, &aws_transcript.results.toxicity_detection);
+        let redactions = redacted_spans(&aws_transcript.results.items);
         
         // Create metadata
         let metadata = TranscriptionMetadata {
             job_id: self.job_id.clone(),
             language: job.language_code()
+                .or_else(|| dominant_language(&job))
                 .map(|lc| lc.as_str().to_string())
                 .unwrap_or_else(|| "unknown".to_string()),
             processing_duration: Some(processing_duration.as_secs_f64()),
             audio_duration: segments.last().map(|s| s.end_time),
             confidence: self.calculate_average_confidence(&segments),
             completed_at: chrono::Utc::now(),
+            speaker_count: aws_transcript.results.speaker_labels.as_ref().map(|labels| labels.speakers),
         };
         
         Ok(ProcessedTranscription {
@@ -203,12 +326,15 @@ impl TranscriptionProcessor {
             segments,
             metadata,
             words: Some(words),
+            raw,
+            redactions,
         })
     }
     
     /// Download transcript from S3
     async fn download_transcript(&self, uri: &str) -> Result<String> {
-        let response = reqwest::get(uri).await
+        let request = self.http_client.get(uri);
+        let response = crate::http::send_with_retry(request, self.http_retries).await
             .context("Failed to download transcript")?;
             
         if !response.status().is_success() {
@@ -238,6 +364,15 @@ impl TranscriptionProcessor {
                                 end_time,
                                 confidence: alt.confidence.as_ref().and_then(|c| c.parse::<f64>().ok()),
                                 speaker_id: item.speaker_label.clone(),
+                                alternatives: item
+                                    .alternatives
+                                    .iter()
+                                    .skip(1)
+                                    .map(|alt| WordAlternative {
+                                        word: alt.content.clone(),
+                                        confidence: alt.confidence.as_ref().and_then(|c| c.parse::<f64>().ok()),
+                                    })
+                                    .collect(),
                             });
                         }
                     }
@@ -251,6 +386,7 @@ impl TranscriptionProcessor {
         let mut current_end_time: Option<f64> = None;
         let mut confidences = Vec::new();
         let mut current_speaker: Option<String> = None;
+        let mut current_language: Option<String> = None;
         
         for item in &results.items {
             if item.item_type == "pronunciation" {
@@ -269,6 +405,7 @@ impl TranscriptionProcessor {
                     
                 // Start new segment if speaker changes, significant gap, or segment is getting too long
                 let speaker_changed = current_speaker.as_ref() != item.speaker_label.as_ref();
+                let language_changed = current_language.as_ref() != item.language_code.as_ref();
                 let time_gap = start_time.zip(current_end_time)
                     .map(|(start, end)| start - end > 1.0)
                     .unwrap_or(false);
@@ -276,11 +413,12 @@ impl TranscriptionProcessor {
                 let segment_too_long = current_start_time.zip(start_time)
                     .map(|(seg_start, current)| current - seg_start > self.max_segment_length)
                     .unwrap_or(false);
+                let segment_too_wide = text::display_width(&current_segment_text) >= MAX_SEGMENT_WIDTH;
                     
                 let natural_break = content.ends_with('.') || content.ends_with('!') || content.ends_with('?');
                     
                 let min_natural_break_length = self.max_segment_length / 2.0;
-                let should_split = speaker_changed || time_gap || segment_too_long || 
+                let should_split = speaker_changed || language_changed || time_gap || segment_too_long || segment_too_wide || 
                     (natural_break && current_start_time.zip(start_time).map(|(seg_start, current)| current - seg_start > min_natural_break_length).unwrap_or(false)) ||
                     current_segment_text.is_empty();
                     
@@ -294,6 +432,9 @@ impl TranscriptionProcessor {
                                 text: current_segment_text.trim().to_string(),
                                 confidence: self.average_confidence(&confidences),
                                 speaker_id: current_speaker.clone(),
+                                toxicity: None,
+                                language: current_language.clone(),
+                                verbatim: None,
                             });
                         }
                     }
@@ -304,12 +445,10 @@ impl TranscriptionProcessor {
                     current_end_time = end_time;
                     confidences = confidence.into_iter().collect();
                     current_speaker = item.speaker_label.clone();
+                    current_language = item.language_code.clone();
                 } else {
                     // Continue current segment
-                    if !current_segment_text.is_empty() {
-                        current_segment_text.push(' ');
-                    }
-                    current_segment_text.push_str(&content);
+                    text::push_token(&mut current_segment_text, &content);
                     current_end_time = end_time.or(current_end_time);
                     
                     if let Some(conf) = confidence {
@@ -333,6 +472,9 @@ impl TranscriptionProcessor {
                     text: current_segment_text.trim().to_string(),
                     confidence: self.average_confidence(&confidences),
                     speaker_id: current_speaker,
+                    toxicity: None,
+                    language: current_language,
+                    verbatim: None,
                 });
             }
         }
@@ -358,4 +500,291 @@ impl TranscriptionProcessor {
             
         self.average_confidence(&confidences)
     }
-} 
\ No newline at end of file
+}
+/// Seconds two timestamps of the same item can differ by in AWS output
+const TIME_TOLERANCE: f64 = 0.001;
+
+/// Label every pronunciation with its speaker from the speaker segments AWS returns
+///
+/// An item takes the speaker of the segment item that starts with it, or else of the segment
+/// that overlaps it most; the item's own `speaker_label` is only kept when neither says.
+fn assign_speakers(results: &mut TranscriptResults) {
+    let Some(labels) = &results.speaker_labels else {
+        return;
+    };
+    let seconds = |time: &str| time.parse::<f64>().ok();
+
+    let mut starts: Vec<(f64, &str)> = labels
+        .segments
+        .iter()
+        .flat_map(|segment| &segment.items)
+        .filter_map(|item| Some((seconds(&item.start_time)?, item.speaker_label.as_str())))
+        .collect();
+    starts.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let spans: Vec<(f64, f64, &str)> = labels
+        .segments
+        .iter()
+        .filter_map(|segment| {
+            Some((seconds(&segment.start_time)?, seconds(&segment.end_time)?, segment.speaker_label.as_str()))
+        })
+        .collect();
+
+    for item in results.items.iter_mut().filter(|item| item.item_type == "pronunciation") {
+        let times = item.start_time.as_deref().and_then(seconds).zip(item.end_time.as_deref().and_then(seconds));
+        let Some((start, end)) = times else {
+            continue;
+        };
+
+        let index = starts.partition_point(|(time, _)| *time < start - TIME_TOLERANCE);
+        let exact = starts
+            .get(index)
+            .filter(|(time, _)| (time - start).abs() <= TIME_TOLERANCE)
+            .map(|(_, speaker)| *speaker);
+        let middle = (start + end) / 2.0;
+        let overlapping = || {
+            spans
+                .iter()
+                .map(|&(from, to, speaker)| (to.min(end) - from.max(start), from <= middle && middle <= to, speaker))
+                .filter(|(overlap, _, _)| *overlap >= 0.0)
+                .max_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)))
+                .map(|(_, _, speaker)| speaker)
+        };
+        if let Some(speaker) = exact.or_else(overlapping) {
+            item.speaker_label = Some(speaker.to_string());
+        }
+    }
+}
+
+/// The language spoken longest in a multi-language job
+fn dominant_language(job: &TranscriptionJob) -> Option<&LanguageCode> {
+    job.language_codes()
+        .iter()
+        .max_by(|a, b| a.duration_in_seconds().unwrap_or(0.0).total_cmp(&b.duration_in_seconds().unwrap_or(0.0)))
+        .and_then(|item| item.language_code())
+}
+
+/// Give each word the different words that lower-ranked transcripts put at its time
+///
+/// AWS ranks whole transcripts of a stretch of speech, so an alternative item is matched to
+/// the word whose span contains its middle.
+fn attach_alternatives(words: &mut [WordTimestamp], segments: &[AlternativeSegment]) {
+    let items = segments
+        .iter()
+        .flat_map(|segment| segment.alternatives.iter().skip(1))
+        .flat_map(|alternative| &alternative.items)
+        .filter(|item| item.item_type != "punctuation");
+    for item in items {
+        let seconds = |time: &Option<String>| time.as_ref().and_then(|t| t.parse::<f64>().ok());
+        let (Some(start_time), Some(end_time)) = (seconds(&item.start_time), seconds(&item.end_time)) else {
+            continue;
+        };
+        let middle = (start_time + end_time) / 2.0;
+        let index = words.partition_point(|w| w.end_time < middle);
+        let Some(word) = words.get_mut(index).filter(|w| w.start_time <= middle) else {
+            continue;
+        };
+        let known = word.word.eq_ignore_ascii_case(&item.content)
+            || word.alternatives.iter().any(|alt| alt.word.eq_ignore_ascii_case(&item.content));
+        if !known {
+            word.alternatives.push(WordAlternative {
+                word: item.content.clone(),
+                confidence: item.confidence.as_ref().and_then(|c| c.parse::<f64>().ok()),
+            });
+        }
+    }
+}
+
+/// Give each segment the highest toxicity scores AWS rated the speech it overlaps with
+fn attach_toxicity(segments: &mut [TranscriptSegment], spans: &[ToxicitySpan]) {
+    let rated: Vec<((f64, f64), Toxicity)> = spans
+        .iter()
+        .filter_map(|span| {
+            let toxicity = Toxicity { score: span.toxicity, categories: span.categories.clone() };
+            Some((span.times()?, toxicity))
+        })
+        .collect();
+    if rated.is_empty() {
+        return;
+    }
+
+    for segment in segments {
+        let overlapping = rated
+            .iter()
+            .filter(|((start, end), _)| *start < segment.end_time && *end > segment.start_time)
+            .map(|(_, toxicity)| toxicity);
+        segment.toxicity = Toxicity::max_of(overlapping);
+    }
+}
+
+/// The redacted spans of a transcript; consecutive words of one type (e.g. a full name) form one span
+fn redacted_spans(items: &[TranscriptItem]) -> Vec<Redaction> {
+    let mut spans: Vec<Redaction> = Vec::new();
+    let mut follows_redaction = false;
+    for item in items.iter().filter(|item| item.item_type == "pronunciation") {
+        let redaction = item.alternatives.first().and_then(|alt| alt.redactions.first());
+        let times = item.start_time.as_deref().and_then(|s| s.parse::<f64>().ok())
+            .zip(item.end_time.as_deref().and_then(|s| s.parse::<f64>().ok()));
+        let (Some(redaction), Some((start_time, end_time))) = (redaction, times) else {
+            follows_redaction = false;
+            continue;
+        };
+        let confidence = redaction.confidence.as_deref().and_then(|c| c.parse::<f64>().ok());
+
+        match spans.last_mut() {
+            Some(span) if follows_redaction && span.entity_type == redaction.entity_type => {
+                span.end_time = end_time;
+                span.confidence = match (span.confidence, confidence) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+            }
+            _ => spans.push(Redaction {
+                start_time,
+                end_time,
+                entity_type: redaction.entity_type.clone(),
+                confidence,
+            }),
+        }
+        follows_redaction = true;
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacted_spans() {
+        let items: Vec<TranscriptItem> = serde_json::from_value(serde_json::json!([
+            { "type": "pronunciation", "start_time": "0.5", "end_time": "0.8",
+              "alternatives": [{ "confidence": "0.99", "content": "Call" }] },
+            { "type": "pronunciation", "start_time": "0.8", "end_time": "1.1",
+              "alternatives": [{ "confidence": "0.0", "content": "[PII]",
+                                 "redactions": [{ "type": "NAME", "category": "PII", "confidence": "0.97" }] }] },
+            { "type": "pronunciation", "start_time": "1.1", "end_time": "1.6",
+              "alternatives": [{ "confidence": "0.0", "content": "[PII]",
+                                 "redactions": [{ "type": "NAME", "category": "PII", "confidence": "0.93" }] }] },
+            { "type": "punctuation", "alternatives": [{ "confidence": "0.0", "content": "," }] },
+            { "type": "pronunciation", "start_time": "1.7", "end_time": "2.9",
+              "alternatives": [{ "confidence": "0.0", "content": "[PII]",
+                                 "redactions": [{ "type": "PHONE", "category": "PII", "confidence": "0.9" }] }] },
+        ]))
+        .unwrap();
+
+        let spans = redacted_spans(&items);
+        assert_eq!(spans.len(), 2);
+        assert_eq!((spans[0].start_time, spans[0].end_time), (0.8, 1.6));
+        assert_eq!(spans[0].entity_type, "NAME");
+        assert_eq!(spans[0].confidence, Some(0.93));
+        assert_eq!(spans[1].entity_type, "PHONE");
+    }
+
+    #[test]
+    fn test_attach_toxicity() {
+        let spans: Vec<ToxicitySpan> = serde_json::from_value(serde_json::json!([
+            { "start_time": 0.5, "end_time": 3.0, "toxicity": 0.8, "categories": { "profanity": 0.9, "insult": 0.2 } },
+            { "start_time": "2.5", "end_time": "4.0", "toxicity": 0.3, "categories": { "profanity": 0.1, "insult": 0.6 } },
+        ]))
+        .unwrap();
+        let segment = |start_time: f64, end_time: f64| TranscriptSegment {
+            start_time,
+            end_time,
+            text: String::new(),
+            confidence: None,
+            speaker_id: None,
+            toxicity: None,
+            language: None,
+            verbatim: None,
+        };
+        let mut segments = vec![segment(0.0, 2.0), segment(2.0, 3.5), segment(5.0, 6.0)];
+
+        attach_toxicity(&mut segments, &spans);
+        assert_eq!(segments[0].toxicity.as_ref().unwrap().score, 0.8);
+        let both = segments[1].toxicity.as_ref().unwrap();
+        assert_eq!((both.score, both.categories["profanity"], both.categories["insult"]), (0.8, 0.9, 0.6));
+        assert!(segments[2].toxicity.is_none());
+    }
+
+    #[test]
+    fn test_attach_alternatives() {
+        let segments: Vec<AlternativeSegment> = serde_json::from_value(serde_json::json!([
+            { "start_time": "0.0", "end_time": "1.0", "alternatives": [
+                { "transcript": "Their ship", "items": [
+                    { "start_time": "0.0", "end_time": "0.4", "type": "pronunciation",
+                      "content": "Their", "confidence": "0.6" },
+                    { "start_time": "0.4", "end_time": "1.0", "type": "pronunciation",
+                      "content": "ship", "confidence": "0.9" },
+                ] },
+                { "transcript": "There, sheep", "items": [
+                    { "start_time": "0.0", "end_time": "0.4", "type": "pronunciation",
+                      "content": "There", "confidence": "0.3" },
+                    { "type": "punctuation", "content": "," },
+                    { "start_time": "0.45", "end_time": "1.0", "type": "pronunciation",
+                      "content": "sheep", "confidence": "0.1" },
+                    { "start_time": "0.0", "end_time": "0.4", "type": "pronunciation", "content": "their" },
+                ] },
+            ] },
+        ]))
+        .unwrap();
+        let word = |word: &str, start_time: f64, end_time: f64| WordTimestamp {
+            word: word.to_string(),
+            start_time,
+            end_time,
+            confidence: Some(0.6),
+            speaker_id: None,
+            alternatives: Vec::new(),
+        };
+        let mut words = vec![word("Their", 0.0, 0.4), word("ship", 0.4, 1.0)];
+
+        attach_alternatives(&mut words, &segments);
+        let there = WordAlternative { word: "There".to_string(), confidence: Some(0.3) };
+        assert_eq!(words[0].alternatives, [there]);
+        assert_eq!(words[1].alternatives[0].word, "sheep");
+    }
+
+    #[test]
+    fn test_assign_speakers() {
+        let mut results: TranscriptResults = serde_json::from_value(serde_json::json!({
+            "transcripts": [{ "transcript": "Hi there. Hello." }],
+            "items": [
+                { "type": "pronunciation", "start_time": "0.0", "end_time": "0.4",
+                  "alternatives": [{ "confidence": "0.99", "content": "Hi" }] },
+                { "type": "pronunciation", "start_time": "0.4", "end_time": "0.9", "speaker_label": "spk_0",
+                  "alternatives": [{ "confidence": "0.99", "content": "there" }] },
+                { "type": "punctuation", "alternatives": [{ "confidence": "0.0", "content": "." }] },
+                { "type": "pronunciation", "start_time": "1.2", "end_time": "1.7",
+                  "alternatives": [{ "confidence": "0.98", "content": "Hello" }] },
+            ],
+            "speaker_labels": { "speakers": 2, "segments": [
+                { "start_time": "0.0", "end_time": "0.9", "speaker_label": "spk_0", "items": [
+                    { "start_time": "0.0", "end_time": "0.4", "speaker_label": "spk_0" },
+                ] },
+                // The second word starts exactly where the other speaker's segment does
+                { "start_time": "0.4", "end_time": "1.8", "speaker_label": "spk_1", "items": [
+                    { "start_time": "0.4", "end_time": "0.9", "speaker_label": "spk_1" },
+                ] },
+            ] },
+        }))
+        .unwrap();
+
+        assign_speakers(&mut results);
+        let speakers: Vec<Option<&str>> = results.items.iter().map(|item| item.speaker_label.as_deref()).collect();
+        assert_eq!(speakers, [Some("spk_0"), Some("spk_1"), None, Some("spk_1")]);
+    }
+
+    #[test]
+    fn test_dominant_language() {
+        use aws_sdk_transcribe::types::LanguageCodeItem;
+
+        let item = |code: LanguageCode, secs: f32| {
+            LanguageCodeItem::builder().language_code(code).duration_in_seconds(secs).build()
+        };
+        let job = TranscriptionJob::builder()
+            .language_codes(item(LanguageCode::EnUs, 12.5))
+            .language_codes(item(LanguageCode::EsUs, 40.0))
+            .build();
+        assert_eq!(dominant_language(&job), Some(&LanguageCode::EsUs));
+        assert_eq!(dominant_language(&TranscriptionJob::builder().build()), None);
+    }
+}
@@ -0,0 +1,162 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::extractors::{AudioInfo, DownloadMethod};
+
+/// What happens to the audio after a run: where `--save-audio` keeps it, and in what format
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedAudioConfig {
+    /// Directory saved audio is written to (default: the current directory)
+    pub dir: Option<PathBuf>,
+
+    /// Convert the saved copy to this format with ffmpeg, e.g. flac for an archive (default: as downloaded)
+    pub format: Option<String>,
+
+    /// Move transcribed local input files into this folder once they are done
+    pub archive_originals: Option<PathBuf>,
+}
+
+impl SavedAudioConfig {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(format) = &self.format {
+            if !crate::deps::AUDIO_FORMATS.contains(&format.as_str()) {
+                anyhow::bail!(
+                    "Unsupported saved audio format '{}' (expected one of: {})",
+                    format,
+                    crate::deps::AUDIO_FORMATS.join(", ")
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Keep the run's audio in the save directory, converted if a format is configured
+    pub async fn save(&self, temp_path: &Path, audio_info: &AudioInfo) -> Result<PathBuf> {
+        let dir = match &self.dir {
+            Some(dir) => dir.clone(),
+            None => std::env::current_dir()?,
+        };
+        fs_err::create_dir_all(&dir)?;
+
+        let extension = self.format.as_deref().unwrap_or(audio_info.format.as_str());
+        let output_path = dir.join(file_name(audio_info, extension));
+        write_as(temp_path, audio_info, &output_path).await?;
+
+        Ok(output_path)
+    }
+
+    /// Move a transcribed local file into the archive folder; other sources have no original here
+    pub fn archive_original(&self, audio_info: &AudioInfo) -> Result<Option<PathBuf>> {
+        let Some(archive) = &self.archive_originals else {
+            return Ok(None);
+        };
+        if audio_info.download_method != DownloadMethod::LocalFile {
+            return Ok(None);
+        }
+
+        let original = Path::new(&audio_info.download_url);
+        let name = original
+            .file_name()
+            .with_context(|| format!("Not a file path: {}", original.display()))?;
+        fs_err::create_dir_all(archive)?;
+        let target = free_path(&archive.join(name));
+
+        // A rename cannot cross file systems; copy and remove the original there instead
+        if fs_err::rename(original, &target).is_err() {
+            fs_err::copy(original, &target)?;
+            fs_err::remove_file(original)?;
+        }
+
+        Ok(Some(target))
+    }
+}
+
+/// `<title>.<ext>` with unsafe characters replaced, or a timestamped name without a title
+fn file_name(audio_info: &AudioInfo, extension: &str) -> String {
+    match &audio_info.title {
+        Some(title) => {
+            let sanitized = title
+                .chars()
+                .map(|c| if c.is_alphanumeric() || c == ' ' { c } else { '_' })
+                .collect::<String>();
+            format!("{}.{}", sanitized, extension)
+        }
+        None => format!("audio_{}.{}", chrono::Utc::now().format("%Y%m%d_%H%M%S"), extension),
+    }
+}
+
+/// `path`, or `name-1.ext`, `name-2.ext`, ... if it is taken, so archiving never overwrites
+fn free_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+    let extension = path.extension().map(|ext| format!(".{}", ext.to_string_lossy())).unwrap_or_default();
+    let mut candidate = path.to_path_buf();
+    let mut index = 1;
+    while candidate.exists() {
+        candidate = path.with_file_name(format!("{}-{}{}", stem, index, extension));
+        index += 1;
+    }
+    candidate
+}
+
+/// Copy downloaded audio to `target`, converting it with ffmpeg if the extension asks for another format
+pub async fn write_as(temp_path: &Path, audio_info: &AudioInfo, target: &Path) -> Result<()> {
+    let extension = target
+        .extension()
+        .with_context(|| format!("{} has no extension to tell the audio format", target.display()))?;
+    if extension.eq_ignore_ascii_case(audio_info.format.as_str()) {
+        fs_err::copy(temp_path, target)?;
+        Ok(())
+    } else {
+        transcode(temp_path, target).await
+    }
+}
+
+/// Convert audio with ffmpeg; the codec follows the target's extension
+async fn transcode(source: &Path, target: &Path) -> Result<()> {
+    let output = tokio::process::Command::new("ffmpeg")
+        .args(["-hide_banner", "-nostdin", "-i"])
+        .arg(source)
+        .args(["-vn", "-y"])
+        .arg(target)
+        .output()
+        .await
+        .context("Failed to run ffmpeg")?;
+
+    crate::logs::check("ffmpeg", &output, "Failed to convert saved audio")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_original() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = dir.path().join("talk.mp3");
+        let archive = dir.path().join("archive");
+        fs_err::create_dir_all(&archive).unwrap();
+        fs_err::write(archive.join("talk.mp3"), "earlier").unwrap();
+        fs_err::write(&original, "audio").unwrap();
+
+        let mut audio_info = crate::demo::sample_audio_info(&original.to_string_lossy());
+        audio_info.download_url = original.to_string_lossy().into_owned();
+        audio_info.download_method = DownloadMethod::LocalFile;
+
+        let config = SavedAudioConfig {
+            archive_originals: Some(archive.clone()),
+            ..Default::default()
+        };
+        let moved = config.archive_original(&audio_info).unwrap().unwrap();
+
+        assert_eq!(moved, archive.join("talk-1.mp3"));
+        assert!(!original.exists());
+        assert_eq!(fs_err::read_to_string(moved).unwrap(), "audio");
+        assert_eq!(fs_err::read_to_string(archive.join("talk.mp3")).unwrap(), "earlier");
+
+        audio_info.download_method = DownloadMethod::YtDlp;
+        assert!(config.archive_original(&audio_info).unwrap().is_none());
+        assert!(SavedAudioConfig { format: Some("ogg".to_string()), ..Default::default() }.validate().is_err());
+    }
+}
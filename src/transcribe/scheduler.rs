@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// How many of each stage may run at once when several inputs are transcribed together
+///
+/// Unset limits fall back to `app.max_concurrent_jobs`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConcurrencyConfig {
+    /// Simultaneous downloads and stream captures
+    pub downloads: Option<usize>,
+
+    /// Simultaneous uploads to S3
+    pub uploads: Option<usize>,
+
+    /// AWS Transcribe jobs running at the same time (bounded by the account's quota)
+    pub aws_jobs: Option<usize>,
+}
+
+/// Limits on downloads, uploads and AWS jobs shared by everything a pipeline runs
+///
+/// A stage holds its permit until it is done, so with three inputs and one upload slot the
+/// second upload starts when the first has finished, while downloads carry on meanwhile.
+#[derive(Debug, Clone)]
+pub struct Scheduler {
+    downloads: Arc<Semaphore>,
+    uploads: Arc<Semaphore>,
+    aws_jobs: Arc<Semaphore>,
+}
+
+impl Scheduler {
+    pub fn new(config: &ConcurrencyConfig, max_concurrent_jobs: usize) -> Self {
+        let limit = |stage: Option<usize>| Arc::new(Semaphore::new(stage.unwrap_or(max_concurrent_jobs).max(1)));
+        Self {
+            downloads: limit(config.downloads),
+            uploads: limit(config.uploads),
+            aws_jobs: limit(config.aws_jobs),
+        }
+    }
+
+    /// Wait for a download slot
+    pub async fn download(&self) -> SemaphorePermit<'_> {
+        acquire(&self.downloads, "download").await
+    }
+
+    /// Wait for an upload slot
+    pub async fn upload(&self) -> SemaphorePermit<'_> {
+        acquire(&self.uploads, "upload").await
+    }
+
+    /// Wait for an AWS job slot; hold it until the job has finished
+    pub async fn aws_job(&self) -> SemaphorePermit<'_> {
+        acquire(&self.aws_jobs, "AWS job").await
+    }
+}
+
+async fn acquire<'a>(semaphore: &'a Semaphore, stage: &str) -> SemaphorePermit<'a> {
+    if semaphore.available_permits() == 0 {
+        tracing::info!("Waiting for a free {} slot", stage);
+    }
+    semaphore.acquire().await.expect("scheduler semaphores are never closed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_stage_limits() {
+        let config = ConcurrencyConfig {
+            uploads: Some(1),
+            aws_jobs: Some(0),
+            ..Default::default()
+        };
+        let scheduler = Scheduler::new(&config, 2);
+
+        let _first = scheduler.download().await;
+        let _second = scheduler.download().await;
+        assert!(scheduler.downloads.try_acquire().is_err());
+
+        let upload = scheduler.upload().await;
+        assert!(scheduler.uploads.try_acquire().is_err());
+        drop(upload);
+        assert!(scheduler.uploads.try_acquire().is_ok());
+
+        // A limit of zero would stall every run, so it means one
+        assert_eq!(scheduler.aws_jobs.available_permits(), 1);
+    }
+}
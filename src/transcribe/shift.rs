@@ -0,0 +1,139 @@
+use super::TranscriptionResult;
+
+/// A linear time correction: every time `t` becomes `t * stretch + offset`
+///
+/// `offset` fixes audio that was trimmed differently than the published video; `stretch`
+/// fixes drift that grows over the recording, e.g. 1.001 for 29.97 vs 30 fps material.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeShift {
+    /// Seconds added after stretching; negative moves cues earlier
+    pub offset: f64,
+
+    /// Factor times are multiplied by
+    pub stretch: f64,
+}
+
+impl Default for TimeShift {
+    fn default() -> Self {
+        Self {
+            offset: 0.0,
+            stretch: 1.0,
+        }
+    }
+}
+
+impl TimeShift {
+    /// Where a time ends up after the correction
+    pub fn map(&self, time: f64) -> f64 {
+        time * self.stretch + self.offset
+    }
+
+    /// Correct every segment, word, chapter and redaction of a result; returns how many segments were dropped
+    ///
+    /// Segments and words that end up entirely before zero are removed, and ones that
+    /// straddle zero are cut to start at it.
+    pub fn apply(&self, result: &mut TranscriptionResult) -> usize {
+        let before = result.segments.len();
+        result.segments.retain_mut(|segment| {
+            (segment.start_time, segment.end_time) = (self.map(segment.start_time), self.map(segment.end_time));
+            clamp(&mut segment.start_time, segment.end_time)
+        });
+        result.chapters.retain_mut(|chapter| {
+            chapter.start_time = self.map(chapter.start_time).max(0.0);
+            chapter.end_time = chapter.end_time.map(|end| self.map(end));
+            chapter.end_time.is_none_or(|end| end > 0.0)
+        });
+        if let Some(words) = &mut result.words {
+            words.retain_mut(|word| {
+                (word.start_time, word.end_time) = (self.map(word.start_time), self.map(word.end_time));
+                clamp(&mut word.start_time, word.end_time)
+            });
+        }
+        result.redactions.retain_mut(|redaction| {
+            (redaction.start_time, redaction.end_time) = (self.map(redaction.start_time), self.map(redaction.end_time));
+            clamp(&mut redaction.start_time, redaction.end_time)
+        });
+
+        let dropped = before - result.segments.len();
+        if dropped > 0 {
+            tracing::warn!("{} segments moved before the start and were dropped", dropped);
+            result.transcript = result
+                .segments
+                .iter()
+                .map(|s| s.text.trim())
+                .collect::<Vec<_>>()
+                .join(" ");
+        }
+        dropped
+    }
+}
+
+/// Keep a span that still ends after zero, moving its start up to zero if needed
+fn clamp(start: &mut f64, end: f64) -> bool {
+    if end <= 0.0 {
+        return false;
+    }
+    *start = start.max(0.0);
+    true
+}
+
+/// Parse a signed offset such as `-2.3s`, `+1.5`, `-1m` or `1m30s`
+pub fn parse_offset(value: &str) -> Result<f64, String> {
+    let value = value.trim();
+    let (sign, magnitude) = match value.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, value.strip_prefix('+').unwrap_or(value)),
+    };
+
+    let seconds = match magnitude.strip_suffix('s').unwrap_or(magnitude).parse::<f64>() {
+        Ok(seconds) if seconds.is_finite() => seconds,
+        _ => crate::utils::parse_duration(magnitude)
+            .map_err(|_| format!("invalid offset '{}': use e.g. -2.3s, +1.5 or 1m30s", value))?
+            .as_secs_f64(),
+    };
+    Ok(sign * seconds)
+}
+
+/// Parse a positive stretch factor such as `1.001`
+pub fn parse_stretch(value: &str) -> Result<f64, String> {
+    match value.trim().parse::<f64>() {
+        Ok(stretch) if stretch.is_finite() && stretch > 0.0 => Ok(stretch),
+        _ => Err(format!("invalid stretch '{}': expected a positive factor such as 1.001", value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(timings: &[(f64, f64)]) -> TranscriptionResult {
+        let texts: Vec<String> = (0..timings.len()).map(|i| format!("cue {}", i)).collect();
+        let cues: Vec<_> = timings.iter().zip(&texts).map(|(&(start, end), text)| (start, end, text.as_str())).collect();
+        crate::transcribe::fixture::result(&cues)
+    }
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset("-2.3s"), Ok(-2.3));
+        assert_eq!(parse_offset("+1.5"), Ok(1.5));
+        assert_eq!(parse_offset("-1m30s"), Ok(-90.0));
+        assert!(parse_offset("soon").is_err());
+        assert!(parse_stretch("0").is_err());
+        assert_eq!(parse_stretch("1.001"), Ok(1.001));
+    }
+
+    #[test]
+    fn test_shift_and_stretch() {
+        let mut shifted = result(&[(1.0, 2.0), (3.0, 4.0), (100.0, 102.0)]);
+        let dropped = TimeShift { offset: -2.5, stretch: 1.0 }.apply(&mut shifted);
+        assert_eq!(dropped, 1);
+        let timings: Vec<(f64, f64)> = shifted.segments.iter().map(|s| (s.start_time, s.end_time)).collect();
+        assert_eq!(timings, vec![(0.5, 1.5), (97.5, 99.5)]);
+        assert_eq!(shifted.transcript, "cue 1 cue 2");
+
+        let mut stretched = result(&[(1.0, 2.0), (3.0, 4.0)]);
+        TimeShift { offset: -2.0, stretch: 2.0 }.apply(&mut stretched);
+        let timings: Vec<(f64, f64)> = stretched.segments.iter().map(|s| (s.start_time, s.end_time)).collect();
+        assert_eq!(timings, vec![(0.0, 2.0), (4.0, 6.0)]);
+    }
+}
@@ -0,0 +1,400 @@
+//! Editing operations on transcript segments
+//!
+//! Segments are merged, split and regrouped so that their timings stay inside the
+//! words they cover and their confidence is recomputed from those words where the
+//! backend provided them.
+
+use anyhow::Result;
+
+use crate::output::formatters::WordTimestamp;
+use crate::transcribe::{Toxicity, TranscriptSegment};
+use crate::utils::text;
+
+/// Display width (in terminal cells) at which a segment is split, so CJK segments stay readable
+pub const MAX_SEGMENT_WIDTH: usize = 84;
+
+/// Silence after which the same speaker starts a new turn
+pub const TURN_PAUSE_SECS: f64 = 2.0;
+
+/// How words are grouped into segments
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentConfig {
+    /// Longest a segment may run, in seconds
+    pub max_length: f64,
+
+    /// Widest a segment's text may get, in terminal cells
+    pub max_width: usize,
+
+    /// Silence, in seconds, that always starts a new segment
+    pub max_gap: f64,
+
+    /// Start a new segment whenever the speaker changes
+    pub split_on_speaker: bool,
+}
+
+impl Default for SegmentConfig {
+    fn default() -> Self {
+        Self {
+            max_length: 10.0,
+            max_width: MAX_SEGMENT_WIDTH,
+            max_gap: 1.0,
+            split_on_speaker: true,
+        }
+    }
+}
+
+/// Join consecutive segments into one
+///
+/// The speaker of the first segment is kept. Confidence is the mean of the covered words'
+/// confidences when `words` has them, otherwise the segments' confidences weighted by duration.
+/// Toxicity scores are the highest of the segments'; the language is the first segment's.
+pub fn merge(segments: &[TranscriptSegment], words: Option<&[WordTimestamp]>) -> Option<TranscriptSegment> {
+    let first = segments.first()?;
+    let start_time = segments.iter().map(|s| s.start_time).fold(f64::INFINITY, f64::min);
+    let end_time = segments.iter().map(|s| s.end_time).fold(f64::NEG_INFINITY, f64::max);
+
+    let mut joined = String::new();
+    for segment in segments {
+        text::push_token(&mut joined, segment.text.trim());
+    }
+
+    let weighted: Vec<(f64, f64)> = segments
+        .iter()
+        .filter_map(|s| s.confidence.map(|c| (c, (s.end_time - s.start_time).max(0.0))))
+        .collect();
+    let total_weight: f64 = weighted.iter().map(|(_, weight)| weight).sum();
+    let segment_confidence = if weighted.is_empty() {
+        None
+    } else if total_weight > 0.0 {
+        Some(weighted.iter().map(|(c, weight)| c * weight).sum::<f64>() / total_weight)
+    } else {
+        Some(weighted.iter().map(|(c, _)| c).sum::<f64>() / weighted.len() as f64)
+    };
+
+    Some(TranscriptSegment {
+        start_time,
+        end_time,
+        text: joined,
+        confidence: words_confidence(words, start_time, end_time).or(segment_confidence),
+        speaker_id: first.speaker_id.clone(),
+        toxicity: Toxicity::max_of(segments.iter().filter_map(|s| s.toxicity.as_ref())),
+        language: first.language.clone(),
+        verbatim: segments.iter().any(|s| s.verbatim.is_some()).then(|| {
+            let mut verbatim = String::new();
+            for segment in segments {
+                text::push_token(&mut verbatim, segment.verbatim.as_deref().unwrap_or(&segment.text).trim());
+            }
+            verbatim
+        }),
+    })
+}
+
+/// Split a segment in two at `time`
+///
+/// With `words`, the text is cut after the last word that starts before `time`;
+/// without them it is cut in proportion to the time. Each half's confidence comes
+/// from its own words when they have one, otherwise it keeps the original's.
+pub fn split_at(
+    segment: &TranscriptSegment,
+    time: f64,
+    words: Option<&[WordTimestamp]>,
+) -> Result<(TranscriptSegment, TranscriptSegment)> {
+    if time <= segment.start_time || time >= segment.end_time {
+        anyhow::bail!(
+            "Split time {:.3}s is not inside the segment ({:.3}s - {:.3}s)",
+            time,
+            segment.start_time,
+            segment.end_time
+        );
+    }
+
+    let tokens = tokens_of(&segment.text);
+    let covered: Vec<&WordTimestamp> = words
+        .unwrap_or_default()
+        .iter()
+        .filter(|w| w.start_time >= segment.start_time && w.start_time < segment.end_time)
+        .collect();
+
+    let cut = if !covered.is_empty() && covered.len() == tokens.len() {
+        covered.iter().filter(|w| w.start_time < time).count()
+    } else {
+        let fraction = (time - segment.start_time) / (segment.end_time - segment.start_time);
+        (tokens.len() as f64 * fraction).round() as usize
+    };
+    // Leave some text on both sides when there is enough to go round
+    let cut = if tokens.len() >= 2 { cut.clamp(1, tokens.len() - 1) } else { cut.min(tokens.len()) };
+
+    let half = |tokens: &[String], start_time: f64, end_time: f64| TranscriptSegment {
+        start_time,
+        end_time,
+        text: join_tokens(tokens),
+        confidence: words_confidence(words, start_time, end_time).or(segment.confidence),
+        speaker_id: segment.speaker_id.clone(),
+        toxicity: segment.toxicity.clone(),
+        language: segment.language.clone(),
+        verbatim: None,
+    };
+
+    Ok((
+        half(&tokens[..cut], segment.start_time, time),
+        half(&tokens[cut..], time, segment.end_time),
+    ))
+}
+
+/// Group words into new segments
+///
+/// `segments` are the current segments; their text is used to put back punctuation the
+/// words lack (AWS keeps it apart from words). A segment ends when the speaker changes,
+/// after a silence longer than `max_gap`, when it would run longer than `max_length` or get
+/// wider than `max_width`, or at a sentence end once it is half of `max_length`.
+pub fn resegment(words: &[WordTimestamp], segments: &[TranscriptSegment], config: &SegmentConfig) -> Vec<TranscriptSegment> {
+    let tokens = punctuated_words(words, segments);
+
+    let mut result: Vec<TranscriptSegment> = Vec::new();
+    let mut confidences: Vec<f64> = Vec::new();
+    let mut current: Option<TranscriptSegment> = None;
+
+    for (word, token) in words.iter().zip(&tokens) {
+        let should_split = match &current {
+            None => true,
+            Some(segment) => {
+                let length = word.start_time - segment.start_time;
+                let sentence_end = segment.text.ends_with(['.', '!', '?', '。', '！', '？']);
+                (config.split_on_speaker && segment.speaker_id != word.speaker_id)
+                    || word.start_time - segment.end_time > config.max_gap
+                    || length > config.max_length
+                    || text::display_width(&segment.text) >= config.max_width
+                    || (sentence_end && length > config.max_length / 2.0)
+            }
+        };
+
+        if should_split {
+            if let Some(mut segment) = current.take() {
+                segment.confidence = mean(&confidences);
+                result.push(segment);
+            }
+            confidences.clear();
+            current = Some(TranscriptSegment {
+                start_time: word.start_time,
+                end_time: word.end_time,
+                text: token.clone(),
+                confidence: None,
+                speaker_id: word.speaker_id.clone(),
+                toxicity: None,
+                language: None,
+                verbatim: None,
+            });
+        } else if let Some(segment) = &mut current {
+            text::push_token(&mut segment.text, token);
+            segment.end_time = segment.end_time.max(word.end_time);
+        }
+        confidences.extend(word.confidence);
+    }
+
+    if let Some(mut segment) = current {
+        segment.confidence = mean(&confidences);
+        result.push(segment);
+    }
+    // New segments keep the toxicity of the ones they overlap, and the language of the one they overlap most
+    for segment in &mut result {
+        let overlap = |s: &TranscriptSegment| s.end_time.min(segment.end_time) - s.start_time.max(segment.start_time);
+        let overlapping: Vec<&TranscriptSegment> = segments.iter().filter(|s| overlap(s) > 0.0).collect();
+        segment.toxicity = Toxicity::max_of(overlapping.iter().filter_map(|s| s.toxicity.as_ref()));
+        segment.language = overlapping
+            .iter()
+            .max_by(|a, b| overlap(a).total_cmp(&overlap(b)))
+            .and_then(|s| s.language.clone());
+    }
+    result
+}
+
+/// Who spoke when: consecutive segments of one speaker joined into a turn without text
+///
+/// A turn ends when the speaker changes or after more than `max_gap` seconds of silence.
+/// Confidence is the duration-weighted mean of the joined segments'.
+pub fn speaker_turns(segments: &[TranscriptSegment], max_gap: f64) -> Vec<TranscriptSegment> {
+    let mut turns = Vec::new();
+    let mut start = 0;
+    for i in 1..=segments.len() {
+        let ends_turn = segments.get(i).is_none_or(|next| {
+            let previous = &segments[i - 1];
+            next.speaker_id != previous.speaker_id || next.start_time - previous.end_time > max_gap
+        });
+        if ends_turn {
+            if let Some(mut turn) = merge(&segments[start..i], None) {
+                turn.text.clear();
+                turns.push(turn);
+            }
+            start = i;
+        }
+    }
+    turns
+}
+
+/// Each word's text with the punctuation its segment gives it, where the segment's
+/// tokens line up one to one with its words
+fn punctuated_words(words: &[WordTimestamp], segments: &[TranscriptSegment]) -> Vec<String> {
+    let mut tokens: Vec<String> = words.iter().map(|w| w.word.clone()).collect();
+
+    for segment in segments {
+        let indices: Vec<usize> = words
+            .iter()
+            .enumerate()
+            .filter(|(_, w)| w.start_time >= segment.start_time && w.start_time < segment.end_time)
+            .map(|(i, _)| i)
+            .collect();
+        let segment_tokens = tokens_of(&segment.text);
+        if indices.is_empty() || indices.len() != segment_tokens.len() {
+            continue;
+        }
+        for (i, token) in indices.into_iter().zip(segment_tokens) {
+            tokens[i] = token;
+        }
+    }
+
+    tokens
+}
+
+fn tokens_of(text: &str) -> Vec<String> {
+    text.split_whitespace().map(str::to_string).collect()
+}
+
+fn join_tokens(tokens: &[String]) -> String {
+    let mut joined = String::new();
+    for token in tokens {
+        text::push_token(&mut joined, token);
+    }
+    joined
+}
+
+/// Mean confidence of the words starting within a span, if any have one
+fn words_confidence(words: Option<&[WordTimestamp]>, start_time: f64, end_time: f64) -> Option<f64> {
+    let confidences: Vec<f64> = words?
+        .iter()
+        .filter(|w| w.start_time >= start_time && w.start_time < end_time)
+        .filter_map(|w| w.confidence)
+        .collect();
+    mean(&confidences)
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(word: &str, start_time: f64, confidence: f64, speaker: &str) -> WordTimestamp {
+        WordTimestamp {
+            word: word.to_string(),
+            start_time,
+            end_time: start_time + 0.5,
+            confidence: Some(confidence),
+            speaker_id: Some(speaker.to_string()),
+            alternatives: Vec::new(),
+        }
+    }
+
+    fn segment(text: &str, start_time: f64, end_time: f64, confidence: Option<f64>) -> TranscriptSegment {
+        TranscriptSegment {
+            start_time,
+            end_time,
+            text: text.to_string(),
+            confidence,
+            speaker_id: Some("spk_0".to_string()),
+            toxicity: None,
+            language: None,
+            verbatim: None,
+        }
+    }
+
+    fn words() -> Vec<WordTimestamp> {
+        vec![
+            word("Hello", 0.0, 0.9, "spk_0"),
+            word("there", 0.6, 0.7, "spk_0"),
+            word("General", 1.2, 0.8, "spk_0"),
+            word("Kenobi", 1.8, 0.6, "spk_0"),
+            word("Hi", 5.0, 1.0, "spk_1"),
+        ]
+    }
+
+    #[test]
+    fn test_merge() {
+        let segments = [segment("Hello there.", 0.0, 1.0, Some(0.8)), segment("General Kenobi!", 1.2, 4.2, Some(0.4))];
+
+        let merged = merge(&segments, None).unwrap();
+        assert_eq!((merged.start_time, merged.end_time), (0.0, 4.2));
+        assert_eq!(merged.text, "Hello there. General Kenobi!");
+        // 1s at 0.8 and 3s at 0.4
+        assert!((merged.confidence.unwrap() - 0.5).abs() < 1e-9);
+
+        let merged = merge(&segments, Some(&words())).unwrap();
+        assert!((merged.confidence.unwrap() - 0.75).abs() < 1e-9);
+        assert!(merge(&[], None).is_none());
+    }
+
+    #[test]
+    fn test_split_at() {
+        let original = segment("Hello there, General Kenobi!", 0.0, 2.3, Some(0.75));
+        let words = words();
+
+        let (first, second) = split_at(&original, 1.1, Some(&words)).unwrap();
+        assert_eq!((first.text.as_str(), second.text.as_str()), ("Hello there,", "General Kenobi!"));
+        assert_eq!((first.end_time, second.start_time), (1.1, 1.1));
+        assert!((first.confidence.unwrap() - 0.8).abs() < 1e-9);
+        assert!((second.confidence.unwrap() - 0.7).abs() < 1e-9);
+
+        let (first, second) = split_at(&original, 0.6, None).unwrap();
+        assert_eq!((first.text.as_str(), second.text.as_str()), ("Hello", "there, General Kenobi!"));
+        assert_eq!(second.confidence, Some(0.75));
+
+        assert!(split_at(&original, 2.3, None).is_err());
+    }
+
+    #[test]
+    fn test_speaker_turns() {
+        let mut segments = vec![
+            segment("One.", 0.0, 2.0, Some(1.0)),
+            segment("Two.", 2.5, 3.5, Some(0.7)),
+            segment("Three.", 10.0, 11.0, None),
+        ];
+        segments.push(TranscriptSegment {
+            speaker_id: Some("spk_1".to_string()),
+            toxicity: None,
+            language: None,
+            ..segment("Four.", 11.0, 12.0, None)
+        });
+
+        let turns = speaker_turns(&segments, TURN_PAUSE_SECS);
+        let spans: Vec<(f64, f64, Option<&str>)> =
+            turns.iter().map(|t| (t.start_time, t.end_time, t.speaker_id.as_deref())).collect();
+        assert_eq!(spans, vec![(0.0, 3.5, Some("spk_0")), (10.0, 11.0, Some("spk_0")), (11.0, 12.0, Some("spk_1"))]);
+        assert!(turns.iter().all(|t| t.text.is_empty()));
+        assert!((turns[0].confidence.unwrap() - 0.9).abs() < 1e-9);
+        assert!(speaker_turns(&[], TURN_PAUSE_SECS).is_empty());
+    }
+
+    #[test]
+    fn test_resegment_keeps_punctuation() {
+        let segments = [
+            segment("Hello there, General Kenobi!", 0.0, 2.3, None),
+            TranscriptSegment {
+                speaker_id: Some("spk_1".to_string()),
+                toxicity: None,
+                language: None,
+                ..segment("Hi.", 5.0, 5.5, None)
+            },
+        ];
+        let config = SegmentConfig {
+            max_length: 1.0,
+            ..Default::default()
+        };
+
+        let resegmented = resegment(&words(), &segments, &config);
+        let texts: Vec<&str> = resegmented.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["Hello there,", "General Kenobi!", "Hi."]);
+        assert!((resegmented[0].confidence.unwrap() - 0.8).abs() < 1e-9);
+        assert_eq!(resegmented[2].speaker_id.as_deref(), Some("spk_1"));
+    }
+}
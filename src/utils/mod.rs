@@ -2,6 +2,8 @@ use anyhow::Result;
 use std::path::Path;
 use url::Url;
 
+pub mod text;
+
 /// Validate a URL and return normalized version
 pub fn validate_and_normalize_url(url: &str) -> Result<String> {
     let parsed = Url::parse(url)
@@ -14,6 +16,24 @@ pub fn validate_and_normalize_url(url: &str) -> Result<String> {
     Ok(parsed.to_string())
 }
 
+#[cfg(feature = "pipeline")]
+/// Build a command from a configured command line, filling in placeholders per argument
+///
+/// The line is split on whitespace and not run through a shell, so substituted values
+/// (URLs, paths) cannot inject anything.
+pub fn command_from_template(line: &str, placeholders: &[(&str, &str)]) -> Result<tokio::process::Command> {
+    let mut parts = line.split_whitespace().map(|part| {
+        placeholders
+            .iter()
+            .fold(part.to_string(), |arg, (name, value)| arg.replace(name, value))
+    });
+    
+    let program = parts.next().ok_or_else(|| anyhow::anyhow!("Empty command"))?;
+    let mut command = tokio::process::Command::new(program);
+    command.args(parts);
+    Ok(command)
+}
+
 /// Format file size in human-readable format
 pub fn format_file_size(bytes: u64) -> String {
     const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
@@ -129,21 +149,53 @@ pub fn extract_domain(url: &str) -> Option<String> {
         .host_str()
         .map(|host| {
             // Remove 'www.' prefix if present
-            if host.starts_with("www.") {
-                host[4..].to_string()
-            } else {
-                host.to_string()
-            }
+            host.strip_prefix("www.").unwrap_or(host).to_string()
         })
 }
 
+/// Parse a duration such as `90`, `45s`, `10m` or `1h30m`
+pub fn parse_duration(value: &str) -> Result<std::time::Duration, String> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Ok(std::time::Duration::from_secs(seconds));
+    }
+
+    let mut total = 0;
+    let mut number = String::new();
+    for c in value.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        let unit = match c {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(format!("invalid duration '{}': unknown unit '{}'", value, c)),
+        };
+        let amount: u64 = number
+            .parse()
+            .map_err(|_| format!("invalid duration '{}': expected a number before '{}'", value, c))?;
+        total += amount * unit;
+        number.clear();
+    }
+
+    if !number.is_empty() || total == 0 {
+        return Err(format!("invalid duration '{}': use e.g. 90s, 10m or 1h30m", value));
+    }
+
+    Ok(std::time::Duration::from_secs(total))
+}
+
+#[cfg(feature = "pipeline")]
 /// Check if the current environment has required tools
-pub async fn check_dependencies() -> Vec<String> {
+pub async fn check_dependencies(yt_dlp: &std::path::Path) -> Vec<String> {
     let mut missing = Vec::new();
     
     // Check for yt-dlp
-    if !check_command_available("yt-dlp").await {
-        missing.push("yt-dlp - required for YouTube and Twitter extraction".to_string());
+    if !check_command_available(yt_dlp).await {
+        missing.push("yt-dlp - required for YouTube and Twitter extraction (install with `rustscribe deps install`)".to_string());
     }
     
     // Check for ffmpeg (optional but recommended)
@@ -154,8 +206,9 @@ pub async fn check_dependencies() -> Vec<String> {
     missing
 }
 
+#[cfg(feature = "pipeline")]
 /// Check if a command is available in PATH
-async fn check_command_available(command: &str) -> bool {
+async fn check_command_available(command: impl AsRef<std::ffi::OsStr>) -> bool {
     use tokio::process::Command;
     
     Command::new(command)
@@ -186,6 +239,19 @@ mod tests {
         assert_eq!(format_duration(3661.0), "1h 1m 1s");
     }
     
+    #[test]
+    fn test_parse_duration() {
+        use std::time::Duration;
+        
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("10m"), Ok(Duration::from_secs(600)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("2m30s"), Ok(Duration::from_secs(150)));
+        assert!(parse_duration("10x").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("1h30").is_err());
+    }
+    
     #[test]
     fn test_sanitize_filename() {
         assert_eq!(sanitize_filename("Hello World!"), "Hello World_");
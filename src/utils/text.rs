@@ -0,0 +1,192 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Punctuation that must not start a line (kinsoku shori)
+const NO_LINE_START: &str = "、。，．・：；？！ー）」』】〕〉》〟’”!?,.:;)]}%";
+
+/// Punctuation that must not end a line
+const NO_LINE_END: &str = "（「『【〔〈《〝‘“([{";
+
+/// Whether a character belongs to a script written without spaces between words
+/// (Chinese, Japanese kana and CJK punctuation; Korean uses spaces and is excluded)
+pub fn is_unspaced_script(c: char) -> bool {
+    matches!(c,
+        '\u{3000}'..='\u{303F}'   // CJK symbols and punctuation
+        | '\u{3040}'..='\u{30FF}' // Hiragana, Katakana
+        | '\u{3400}'..='\u{4DBF}' // CJK extension A
+        | '\u{4E00}'..='\u{9FFF}' // CJK unified ideographs
+        | '\u{F900}'..='\u{FAFF}' // CJK compatibility ideographs
+        | '\u{FF00}'..='\u{FFEF}' // Half-width and full-width forms
+    )
+}
+
+/// Number of terminal cells a string occupies (CJK characters take two)
+pub fn display_width(text: &str) -> usize {
+    text.width()
+}
+
+/// Shorten text to at most `max_width` cells, ending it with `...` if it was cut
+pub fn truncate_to_width(text: &str, max_width: usize) -> String {
+    if display_width(text) <= max_width {
+        return text.to_string();
+    }
+
+    let budget = max_width.saturating_sub(3);
+    let mut result = String::new();
+    let mut width = 0;
+    for c in text.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if width + char_width > budget {
+            break;
+        }
+        result.push(c);
+        width += char_width;
+    }
+
+    format!("{}...", result.trim_end())
+}
+
+/// Append a recognised token to running text, with a space unless both sides are unspaced script
+pub fn push_token(text: &mut String, token: &str) {
+    let joins_directly = match (text.chars().next_back(), token.chars().next()) {
+        (Some(last), Some(first)) => is_unspaced_script(last) && is_unspaced_script(first),
+        _ => true,
+    };
+
+    if !joins_directly {
+        text.push(' ');
+    }
+    text.push_str(token);
+}
+
+/// Wrap text into lines of at most `width` cells
+///
+/// Space-separated text breaks between words; Chinese and Japanese may break between
+/// any two characters, except that closing punctuation never starts a line and
+/// opening brackets never end one. A single unit wider than `width` gets its own line.
+pub fn wrap_to_width(text: &str, width: usize) -> Vec<String> {
+    wrap_after_indent(text, width, 0)
+}
+
+/// Wrap text as `wrap_to_width` does, with the first line starting `indent` cells in
+///
+/// The indent is left for a label put before the text, such as a speaker name.
+pub fn wrap_after_indent(text: &str, width: usize, indent: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_width = indent;
+
+    for (unit, spaced) in break_units(text) {
+        let unit_width = display_width(&unit);
+        let gap = usize::from(spaced && !line.is_empty());
+
+        if !line.is_empty() && line_width + gap + unit_width > width {
+            lines.push(std::mem::take(&mut line));
+            line_width = 0;
+        } else if gap == 1 {
+            line.push(' ');
+            line_width += 1;
+        }
+
+        line.push_str(&unit);
+        line_width += unit_width;
+    }
+
+    if !line.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Split text into units a line may break between, each flagged with whether a space precedes it
+fn break_units(text: &str) -> Vec<(String, bool)> {
+    let mut units: Vec<(String, bool)> = Vec::new();
+    let mut word = String::new();
+    let mut word_spaced = false;
+    let mut spaced = false;
+
+    let flush = |units: &mut Vec<(String, bool)>, word: &mut String, spaced: bool| {
+        if !word.is_empty() {
+            units.push((std::mem::take(word), spaced));
+        }
+    };
+
+    for c in text.chars() {
+        if c.is_whitespace() {
+            flush(&mut units, &mut word, word_spaced);
+            spaced = true;
+            continue;
+        }
+
+        let glued_to_previous = !spaced
+            && word.is_empty()
+            && units.last().is_some_and(|(last, _)| {
+                NO_LINE_START.contains(c) || last.chars().next_back().is_some_and(|l| NO_LINE_END.contains(l))
+            });
+
+        if is_unspaced_script(c) || glued_to_previous {
+            flush(&mut units, &mut word, word_spaced);
+            match units.last_mut() {
+                Some((last, _)) if glued_to_previous => last.push(c),
+                _ => units.push((c.to_string(), spaced)),
+            }
+        } else {
+            if word.is_empty() {
+                word_spaced = spaced;
+            }
+            word.push(c);
+        }
+        spaced = false;
+    }
+    flush(&mut units, &mut word, word_spaced);
+
+    units
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_width_and_truncate() {
+        assert_eq!(display_width("abc"), 3);
+        assert_eq!(display_width("日本語"), 6);
+        assert_eq!(truncate_to_width("日本語のタイトル", 9), "日本語...");
+        assert_eq!(truncate_to_width("short", 10), "short");
+    }
+
+    #[test]
+    fn test_push_token() {
+        let mut text = String::from("今日");
+        push_token(&mut text, "は");
+        push_token(&mut text, "Rust");
+        push_token(&mut text, "です");
+        assert_eq!(text, "今日は Rust です");
+
+        let mut text = String::from("hello");
+        push_token(&mut text, "world");
+        assert_eq!(text, "hello world");
+    }
+
+    #[test]
+    fn test_wrap_latin_text() {
+        assert_eq!(
+            wrap_to_width("the quick brown fox jumps over the lazy dog", 15),
+            vec!["the quick brown", "fox jumps over", "the lazy dog"]
+        );
+        assert_eq!(
+            wrap_after_indent("the quick brown fox jumps over the lazy dog", 15, 6),
+            vec!["the quick", "brown fox jumps", "over the lazy", "dog"]
+        );
+    }
+
+    #[test]
+    fn test_wrap_japanese_keeps_punctuation_attached() {
+        // 「は、」 must not leave 、 at the start of the second line
+        let lines = wrap_to_width("これはテストです、とても長い文章。", 14);
+
+        assert_eq!(lines, vec!["これはテストで", "す、とても長い", "文章。"]);
+        assert!(lines.iter().all(|line| display_width(line) <= 14));
+        assert!(lines.iter().all(|line| !line.starts_with(['、', '。'])));
+    }
+}
@@ -0,0 +1,431 @@
+use anyhow::{Context, Result};
+use aws_sdk_transcribe::types::VocabularyState;
+use aws_sdk_transcribe::Client as TranscribeClient;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::transcribe::TranscriptionResult;
+
+/// Common words that are never worth adding to a custom vocabulary,
+/// even when AWS recognised them with low confidence
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "are", "but", "not", "you", "all", "any", "can", "had", "her",
+    "was", "one", "our", "out", "has", "him", "his", "how", "its", "may", "new", "now",
+    "old", "see", "two", "way", "who", "did", "get", "got", "let", "say", "she", "too",
+    "use", "yeah", "yes", "okay", "that", "this", "with", "have", "from", "they", "will",
+    "what", "when", "your", "there", "their", "would", "about", "which", "were", "been",
+    "then", "them", "than", "just", "like", "know", "some", "into", "also", "well",
+];
+
+/// A term proposed for inclusion in a custom vocabulary
+#[derive(Debug, Clone, PartialEq)]
+pub struct VocabularySuggestion {
+    /// Phrase as it should appear in the vocabulary
+    pub phrase: String,
+
+    /// Total number of occurrences across all transcripts
+    pub occurrences: usize,
+
+    /// Number of occurrences recognised below the confidence threshold
+    pub low_confidence: usize,
+
+    /// Mean confidence over all occurrences
+    pub mean_confidence: f64,
+}
+
+/// Per-term statistics collected while mining transcripts
+#[derive(Default)]
+struct TermStats {
+    spellings: HashMap<String, usize>,
+    occurrences: usize,
+    low_confidence: usize,
+    confidence_sum: f64,
+}
+
+/// Mine transcription results for terms that are frequently recognised with low confidence
+///
+/// A term is proposed when it was recognised below `threshold` at least `min_count`
+/// times and in at least half of its occurrences, so words that AWS usually gets
+/// right are not suggested because of a single bad recording.
+pub fn suggest_terms(
+    results: &[TranscriptionResult],
+    threshold: f64,
+    min_count: usize,
+) -> Vec<VocabularySuggestion> {
+    let mut stats: HashMap<String, TermStats> = HashMap::new();
+
+    for word in results.iter().filter_map(|r| r.words.as_ref()).flatten() {
+        let Some(confidence) = word.confidence else {
+            continue;
+        };
+
+        let phrase = clean_phrase(&word.word);
+        if phrase.chars().count() < 3 || phrase.chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+
+        let key = phrase.to_lowercase();
+        if STOPWORDS.contains(&key.as_str()) {
+            continue;
+        }
+
+        let entry = stats.entry(key).or_default();
+        *entry.spellings.entry(phrase).or_default() += 1;
+        entry.occurrences += 1;
+        entry.confidence_sum += confidence;
+        if confidence < threshold {
+            entry.low_confidence += 1;
+        }
+    }
+
+    let mut suggestions: Vec<VocabularySuggestion> = stats
+        .into_values()
+        .filter(|s| s.low_confidence >= min_count && s.low_confidence * 2 >= s.occurrences)
+        .map(|s| {
+            // Prefer the most common spelling so proper nouns keep their capitalisation
+            let phrase = s.spellings
+                .iter()
+                .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+                .map(|(spelling, _)| spelling.clone())
+                .unwrap_or_default();
+
+            VocabularySuggestion {
+                phrase,
+                occurrences: s.occurrences,
+                low_confidence: s.low_confidence,
+                mean_confidence: s.confidence_sum / s.occurrences as f64,
+            }
+        })
+        .collect();
+
+    suggestions.sort_by(|a, b| {
+        b.low_confidence
+            .cmp(&a.low_confidence)
+            .then_with(|| a.mean_confidence.total_cmp(&b.mean_confidence))
+            .then_with(|| a.phrase.cmp(&b.phrase))
+    });
+
+    suggestions
+}
+
+/// Strip characters AWS does not accept in vocabulary phrases
+fn clean_phrase(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+        .chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, '\'' | '-' | '.'))
+        .collect()
+}
+
+/// Render suggestions as a phrase list (one phrase per line)
+pub fn format_phrase_list(suggestions: &[VocabularySuggestion]) -> String {
+    suggestions
+        .iter()
+        .map(|s| format!("{}\n", s.phrase))
+        .collect()
+}
+
+/// Collect transcript JSON files from a list of files and directories
+pub fn collect_transcript_files(inputs: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    for input in inputs {
+        if input.is_dir() {
+            let mut entries: Vec<PathBuf> = fs_err::read_dir(input)?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("json"))
+                .collect();
+            entries.sort();
+            files.extend(entries);
+        } else {
+            files.push(input.clone());
+        }
+    }
+
+    Ok(files)
+}
+
+/// Load every transcript, skipping files that are not transcription results
+pub fn load_transcripts(files: &[PathBuf]) -> Vec<TranscriptionResult> {
+    files
+        .iter()
+        .filter_map(|path| match crate::output::load_result(path) {
+            Ok(result) => Some(result),
+            Err(e) => {
+                tracing::warn!("Skipping {}: {:#}", path.display(), e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Read a phrase list file, ignoring blank lines and `#` comments
+pub fn read_phrase_file(path: &Path) -> Result<Vec<String>> {
+    let content = fs_err::read_to_string(path)?;
+
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Longest phrase AWS accepts in a vocabulary
+const MAX_PHRASE_CHARS: usize = 256;
+
+/// Largest phrase list AWS accepts, in bytes
+const MAX_PHRASE_LIST_BYTES: usize = 50 * 1024;
+
+/// How long a new or updated vocabulary may take to become READY
+const READY_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Put phrases in the form AWS accepts: the words of a phrase joined with hyphens, repeats dropped
+pub fn prepare_phrases(phrases: Vec<String>) -> Result<Vec<String>> {
+    let mut prepared: Vec<String> = Vec::with_capacity(phrases.len());
+    for phrase in phrases {
+        let phrase = phrase.split_whitespace().collect::<Vec<_>>().join("-");
+        if phrase.chars().count() > MAX_PHRASE_CHARS {
+            anyhow::bail!("Phrase is longer than {} characters: {}", MAX_PHRASE_CHARS, phrase);
+        }
+        if !phrase.is_empty() && !prepared.contains(&phrase) {
+            prepared.push(phrase);
+        }
+    }
+
+    if prepared.is_empty() {
+        anyhow::bail!("The vocabulary has no phrases");
+    }
+    let size: usize = prepared.iter().map(|phrase| phrase.len() + 1).sum();
+    if size > MAX_PHRASE_LIST_BYTES {
+        anyhow::bail!("The phrase list is {} KB; AWS accepts at most 50 KB", size / 1024);
+    }
+    Ok(prepared)
+}
+
+/// A custom vocabulary as `vocab list` shows it
+#[derive(Debug, Clone)]
+pub struct VocabularyInfo {
+    pub name: String,
+    pub language: String,
+    /// PENDING, READY or FAILED
+    pub state: String,
+    pub last_modified: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Manages AWS Transcribe custom vocabularies
+pub struct VocabularyManager {
+    client: TranscribeClient,
+}
+
+impl VocabularyManager {
+    pub fn new(client: TranscribeClient) -> Self {
+        Self { client }
+    }
+
+    /// Check whether a vocabulary with this exact name exists
+    pub async fn exists(&self, name: &str) -> Result<bool> {
+        match self.client.get_vocabulary().vocabulary_name(name).send().await {
+            Ok(_) => Ok(true),
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_not_found_exception()) => Ok(false),
+            Err(e) => Err(e).with_context(|| format!("Failed to look up vocabulary '{}'", name)),
+        }
+    }
+
+    /// Create a vocabulary that does not exist yet
+    pub async fn create(&self, name: &str, language: &str, phrases: Vec<String>) -> Result<()> {
+        if self.exists(name).await? {
+            anyhow::bail!("Vocabulary '{}' already exists; use `vocab update` to replace its phrases", name);
+        }
+        self.put(name, language, phrases, false).await
+    }
+
+    /// Replace the phrases of an existing vocabulary
+    pub async fn update(&self, name: &str, language: &str, phrases: Vec<String>) -> Result<()> {
+        if !self.exists(name).await? {
+            anyhow::bail!("No vocabulary named '{}'; use `vocab create` to make it", name);
+        }
+        self.put(name, language, phrases, true).await
+    }
+
+    /// Create the vocabulary, or replace its phrases if it already exists
+    pub async fn create_or_update(&self, name: &str, language: &str, phrases: Vec<String>) -> Result<()> {
+        let exists = self.exists(name).await?;
+        self.put(name, language, phrases, exists).await
+    }
+
+    /// Update the vocabulary if it `exists`, create it otherwise
+    ///
+    /// Creating one that appeared meanwhile fails with AWS's conflict error rather than
+    /// replacing someone else's phrases.
+    async fn put(&self, name: &str, language: &str, phrases: Vec<String>, exists: bool) -> Result<()> {
+        let language_code = language.parse()
+            .with_context(|| format!("Invalid language code: {}", language))?;
+        let phrases = prepare_phrases(phrases)?;
+
+        if exists {
+            tracing::info!("Updating custom vocabulary: {}", name);
+            self.client
+                .update_vocabulary()
+                .vocabulary_name(name)
+                .language_code(language_code)
+                .set_phrases(Some(phrases))
+                .send()
+                .await
+                .context("Failed to update custom vocabulary")?;
+        } else {
+            tracing::info!("Creating custom vocabulary: {}", name);
+            self.client
+                .create_vocabulary()
+                .vocabulary_name(name)
+                .language_code(language_code)
+                .set_phrases(Some(phrases))
+                .send()
+                .await
+                .context("Failed to create custom vocabulary")?;
+        }
+
+        Ok(())
+    }
+
+    /// Every custom vocabulary in the account's region, by name
+    pub async fn list(&self) -> Result<Vec<VocabularyInfo>> {
+        let mut vocabularies = Vec::new();
+        let mut next_token = None;
+        loop {
+            let response = self.client
+                .list_vocabularies()
+                .set_next_token(next_token)
+                .send()
+                .await
+                .context("Failed to list custom vocabularies")?;
+
+            vocabularies.extend(response.vocabularies().iter().map(|v| VocabularyInfo {
+                name: v.vocabulary_name().unwrap_or_default().to_string(),
+                language: v.language_code().map(|l| l.as_str().to_string()).unwrap_or_default(),
+                state: v.vocabulary_state().map(|s| s.as_str().to_string()).unwrap_or_default(),
+                last_modified: v
+                    .last_modified_time()
+                    .and_then(|t| chrono::DateTime::from_timestamp(t.secs(), t.subsec_nanos())),
+            }));
+
+            next_token = response.next_token().map(str::to_string);
+            if next_token.is_none() {
+                break;
+            }
+        }
+
+        vocabularies.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(vocabularies)
+    }
+
+    pub async fn delete(&self, name: &str) -> Result<()> {
+        self.client
+            .delete_vocabulary()
+            .vocabulary_name(name)
+            .send()
+            .await
+            .with_context(|| format!("Failed to delete vocabulary '{}'", name))?;
+        Ok(())
+    }
+
+    /// Wait until AWS has built the vocabulary, so jobs can use it
+    ///
+    /// Gives up after [`READY_TIMEOUT`], as a vocabulary can stay PENDING when AWS is backed up.
+    pub async fn wait_until_ready(&self, name: &str) -> Result<()> {
+        let deadline = tokio::time::Instant::now() + READY_TIMEOUT;
+        let progress = crate::progress::add(ProgressBar::new_spinner().with_style(
+            ProgressStyle::default_spinner().template("{spinner:.green} [{elapsed_precise}] {msg}").unwrap()
+        ));
+        progress.set_message(format!("Building vocabulary '{}'...", name));
+        progress.enable_steady_tick(Duration::from_millis(250));
+
+        loop {
+            let vocabulary = self.client
+                .get_vocabulary()
+                .vocabulary_name(name)
+                .send()
+                .await
+                .context("Failed to get custom vocabulary")?;
+
+            match vocabulary.vocabulary_state() {
+                Some(VocabularyState::Ready) => {
+                    progress.finish_and_clear();
+                    return Ok(());
+                }
+                Some(VocabularyState::Failed) => {
+                    progress.finish_and_clear();
+                    anyhow::bail!(
+                        "AWS could not build vocabulary '{}': {}",
+                        name,
+                        vocabulary.failure_reason().unwrap_or("unknown reason")
+                    );
+                }
+                _ if tokio::time::Instant::now() >= deadline => {
+                    progress.finish_and_clear();
+                    anyhow::bail!(
+                        "Vocabulary '{}' is still not ready after {} minutes; check it with `vocab list`",
+                        name,
+                        READY_TIMEOUT.as_secs() / 60
+                    );
+                }
+                _ => tokio::time::sleep(Duration::from_secs(10)).await,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::formatters::WordTimestamp;
+
+    fn word(text: &str, confidence: f64) -> WordTimestamp {
+        WordTimestamp {
+            word: text.to_string(),
+            start_time: 0.0,
+            end_time: 0.5,
+            confidence: Some(confidence),
+            speaker_id: None,
+            alternatives: Vec::new(),
+        }
+    }
+
+    fn result_with_words(words: Vec<WordTimestamp>) -> TranscriptionResult {
+        TranscriptionResult {
+            words: Some(words),
+            ..crate::demo::result("talk")
+        }
+    }
+
+    #[test]
+    fn test_suggests_frequent_low_confidence_terms() {
+        let results = vec![
+            result_with_words(vec![word("Kubernetes", 0.4), word("the", 0.3), word("cluster", 0.95)]),
+            result_with_words(vec![word("kubernetes,", 0.5), word("Kubernetes", 0.45), word("cluster", 0.5)]),
+        ];
+
+        let suggestions = suggest_terms(&results, 0.7, 2);
+
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].phrase, "Kubernetes");
+        assert_eq!(suggestions[0].occurrences, 3);
+        assert_eq!(suggestions[0].low_confidence, 3);
+    }
+
+    #[test]
+    fn test_prepare_phrases() {
+        let phrases = vec!["Kubernetes".to_string(), "Los  Angeles".to_string(), "Kubernetes".to_string(), " ".to_string()];
+        assert_eq!(prepare_phrases(phrases).unwrap(), ["Kubernetes", "Los-Angeles"]);
+        assert!(prepare_phrases(Vec::new()).is_err());
+        assert!(prepare_phrases(vec!["x".repeat(MAX_PHRASE_CHARS + 1)]).is_err());
+    }
+
+    #[test]
+    fn test_clean_phrase() {
+        assert_eq!(clean_phrase("\"Hello,"), "Hello");
+        assert_eq!(clean_phrase("O'Neil"), "O'Neil");
+        assert_eq!(clean_phrase("e.g."), "e.g");
+    }
+}
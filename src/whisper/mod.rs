@@ -0,0 +1,451 @@
+use anyhow::{Context, Result};
+use indicatif::{ProgressBar, ProgressStyle};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tokio::process::Command;
+
+pub mod models;
+
+pub use models::ModelManager;
+
+use crate::output::formatters::WordTimestamp;
+use crate::transcribe::boost::PhraseBoost;
+use crate::transcribe::detect::DetectedLanguage;
+use crate::transcribe::processor::ProcessedTranscription;
+use crate::transcribe::{TranscribeOptions, TranscriptSegment, TranscriptionMetadata};
+
+/// GPU backend whisper.cpp should run on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GpuBackend {
+    /// Use whatever GPU support the whisper.cpp build has
+    #[default]
+    Auto,
+    /// NVIDIA GPUs
+    Cuda,
+    /// Apple Silicon GPUs
+    Metal,
+    /// Never use the GPU
+    Cpu,
+}
+
+/// Settings for the local Whisper backend (whisper.cpp)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WhisperConfig {
+    /// whisper.cpp command line binary
+    pub binary: String,
+
+    /// Model name (see `models pull`) or path to a ggml model file
+    pub model: String,
+
+    /// Quantized model variant to use (q4_0, q4_1, q5_0, q5_1, q8_0)
+    pub quantization: Option<String>,
+
+    /// Directory for downloaded models (defaults to the application data directory)
+    pub models_dir: Option<PathBuf>,
+
+    /// GPU backend
+    pub gpu: GpuBackend,
+
+    /// GPU device index when several are available
+    pub gpu_device: Option<u32>,
+
+    /// CPU threads (whisper.cpp default if not set)
+    pub threads: Option<u32>,
+
+    /// Enable flash attention (faster on supported GPUs)
+    pub flash_attention: bool,
+}
+
+impl Default for WhisperConfig {
+    fn default() -> Self {
+        Self {
+            binary: "whisper-cli".to_string(),
+            model: "base".to_string(),
+            quantization: None,
+            models_dir: None,
+            gpu: GpuBackend::Auto,
+            gpu_device: None,
+            threads: None,
+            flash_attention: false,
+        }
+    }
+}
+
+/// whisper.cpp full JSON output (`--output-json-full`)
+#[derive(Debug, Deserialize)]
+struct WhisperOutput {
+    result: Option<WhisperResultInfo>,
+    transcription: Vec<WhisperSegment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WhisperResultInfo {
+    language: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct WhisperSegment {
+    offsets: WhisperOffsets,
+    text: String,
+    #[serde(default)]
+    tokens: Vec<WhisperToken>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WhisperOffsets {
+    from: u64,
+    to: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct WhisperToken {
+    text: String,
+    offsets: WhisperOffsets,
+    p: f64,
+}
+
+/// Transcribes audio locally by running whisper.cpp
+pub struct WhisperBackend {
+    config: WhisperConfig,
+    models: ModelManager,
+}
+
+impl WhisperBackend {
+    pub fn new(config: WhisperConfig) -> Result<Self> {
+        let models = ModelManager::from_config(&config)?;
+        Ok(Self { config, models })
+    }
+
+    /// Model file to load, which must already be downloaded
+    pub fn model_path(&self) -> Result<PathBuf> {
+        if self.config.model.ends_with(".bin") {
+            return Ok(PathBuf::from(&self.config.model));
+        }
+
+        let path = self.models.path(&self.config.model, self.config.quantization.as_deref());
+        if !path.exists() {
+            let mut pull = format!("rustscribe models pull {}", self.config.model);
+            if let Some(quantization) = &self.config.quantization {
+                pull.push_str(&format!(" --quantization {}", quantization));
+            }
+            anyhow::bail!("Whisper model '{}' is not downloaded (run `{}`)", self.config.model, pull);
+        }
+
+        Ok(path)
+    }
+
+    /// Transcribe an audio file
+    pub async fn transcribe(&self, audio_path: &Path, options: &TranscribeOptions) -> Result<ProcessedTranscription> {
+        let model = self.model_path()?;
+        let started = Instant::now();
+
+        if options.speaker_labels {
+            tracing::warn!("Speaker identification is not available with the local Whisper backend");
+        }
+        if options.vocabulary.is_some() {
+            tracing::warn!("Custom vocabularies are only used by the AWS backend");
+        }
+
+        let progress = crate::progress::add(ProgressBar::new_spinner().with_style(
+            ProgressStyle::default_spinner()
+                .template("{spinner:.green} [{elapsed_precise}] {prefix}{msg}")
+                .unwrap()
+        ));
+        progress.enable_steady_tick(std::time::Duration::from_millis(120));
+        progress.set_message(format!("Transcribing locally with Whisper ({})...", self.config.model));
+
+        // whisper.cpp only reads 16 kHz mono WAV
+        let work_dir = tempfile::tempdir().context("Failed to create temporary directory")?;
+        let wav_path = work_dir.path().join("audio.wav");
+        convert_to_wav(audio_path, &wav_path).await?;
+
+        let output_base = work_dir.path().join("transcript");
+        let output = self.command(&model, &wav_path, &output_base, options)?
+            .output()
+            .await
+            .with_context(|| format!("Failed to run {} (is whisper.cpp installed?)", self.config.binary))?;
+
+        crate::logs::check("whisper.cpp", &output, "whisper.cpp failed")?;
+
+        let content = fs_err::read_to_string(output_base.with_extension("json"))?;
+        let parsed: WhisperOutput = serde_json::from_str(&content)
+            .context("Failed to parse whisper.cpp output")?;
+
+        progress.finish_with_message("Transcription completed!");
+
+        let language = parsed
+            .result
+            .as_ref()
+            .map(|r| r.language.clone())
+            .or_else(|| options.language.clone())
+            .unwrap_or_else(|| "auto".to_string());
+
+        Ok(into_transcription(parsed, language, started.elapsed().as_secs_f64()))
+    }
+
+    /// Identify the spoken language of an audio file without transcribing it
+    pub async fn detect_language(&self, audio_path: &Path) -> Result<DetectedLanguage> {
+        let model = self.model_path()?;
+
+        let work_dir = tempfile::tempdir().context("Failed to create temporary directory")?;
+        let wav_path = work_dir.path().join("audio.wav");
+        convert_to_wav(audio_path, &wav_path).await?;
+
+        // The detection is only logged, so prints must stay on
+        let output = self.base_command(&model, &wav_path)?
+            .arg("--detect-language")
+            .output()
+            .await
+            .with_context(|| format!("Failed to run {} (is whisper.cpp installed?)", self.config.binary))?;
+
+        crate::logs::check("whisper.cpp", &output, "whisper.cpp failed")?;
+        let log = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+
+        parse_detected_language(&log).context("whisper.cpp did not report a detected language")
+    }
+
+    /// Build the whisper.cpp invocation
+    fn command(&self, model: &Path, wav_path: &Path, output_base: &Path, options: &TranscribeOptions) -> Result<Command> {
+        // whisper.cpp expects bare language codes ("en" rather than "en-US")
+        let language = options
+            .language
+            .as_deref()
+            .map(|lang| lang.split('-').next().unwrap_or(lang).to_lowercase())
+            .unwrap_or_else(|| "auto".to_string());
+
+        let mut command = self.base_command(model, wav_path)?;
+        command
+            .arg("--output-json-full")
+            .arg("--output-file").arg(output_base)
+            .args(["--language", &language])
+            .arg("--no-prints");
+        if let Some(prompt) = PhraseBoost::new(&options.boost_phrases)?.prompt() {
+            command.arg("--prompt").arg(prompt);
+        }
+
+        Ok(command)
+    }
+
+    /// whisper.cpp with the model, input and the configured threads and GPU settings
+    fn base_command(&self, model: &Path, wav_path: &Path) -> Result<Command> {
+        let mut command = Command::new(&self.config.binary);
+        command
+            .arg("--model").arg(model)
+            .arg("--file").arg(wav_path);
+
+        if let Some(threads) = self.config.threads {
+            command.args(["--threads", &threads.to_string()]);
+        }
+        if self.config.flash_attention {
+            command.arg("--flash-attn");
+        }
+
+        match self.config.gpu {
+            GpuBackend::Cpu => {
+                command.arg("--no-gpu");
+            }
+            GpuBackend::Metal if !cfg!(target_os = "macos") => {
+                anyhow::bail!("The Metal GPU backend is only available on macOS");
+            }
+            GpuBackend::Cuda if cfg!(target_os = "macos") => {
+                anyhow::bail!("The CUDA GPU backend is not available on macOS (use metal)");
+            }
+            GpuBackend::Auto | GpuBackend::Cuda | GpuBackend::Metal => {
+                if let Some(device) = self.config.gpu_device {
+                    command.args(["--device", &device.to_string()]);
+                }
+            }
+        }
+
+        Ok(command)
+    }
+}
+
+/// Read `auto-detected language: de (p = 0.912345)` from whisper.cpp's log
+fn parse_detected_language(log: &str) -> Option<DetectedLanguage> {
+    let line = log.lines().find_map(|line| line.split_once("auto-detected language:"))?.1;
+    let (language, rest) = line.trim().split_once(char::is_whitespace).unwrap_or((line.trim(), ""));
+    let confidence = rest
+        .trim()
+        .strip_prefix("(p =")
+        .and_then(|p| p.trim_end_matches(')').trim().parse::<f64>().ok());
+
+    Some(DetectedLanguage {
+        language: language.to_string(),
+        confidence,
+        duration: None,
+    })
+}
+
+/// Convert audio to the 16 kHz mono PCM WAV whisper.cpp expects
+pub(crate) async fn convert_to_wav(source_path: &Path, target_path: &Path) -> Result<()> {
+    let output = Command::new("ffmpeg")
+        .args([
+            "-i", &source_path.to_string_lossy(),
+            "-vn",
+            "-ar", "16000",
+            "-ac", "1",
+            "-c:a", "pcm_s16le",
+            "-y",
+            &target_path.to_string_lossy(),
+        ])
+        .output()
+        .await
+        .context("Failed to run ffmpeg")?;
+
+    crate::logs::check("ffmpeg", &output, "Failed to convert audio with ffmpeg")?;
+
+    Ok(())
+}
+
+/// Turn whisper.cpp output into segments and word timestamps
+fn into_transcription(output: WhisperOutput, language: String, processing_duration: f64) -> ProcessedTranscription {
+    let mut segments = Vec::new();
+    let mut words: Vec<WordTimestamp> = Vec::new();
+
+    for segment in &output.transcription {
+        // Control tokens look like [_BEG_] or [_TT_150]
+        let tokens: Vec<&WhisperToken> = segment
+            .tokens
+            .iter()
+            .filter(|t| !(t.text.starts_with("[_") && t.text.ends_with(']')))
+            .collect();
+
+        let confidence = if tokens.is_empty() {
+            None
+        } else {
+            Some(tokens.iter().map(|t| t.p).sum::<f64>() / tokens.len() as f64)
+        };
+
+        // Sub-word tokens are merged into the preceding word unless they start with a space
+        let mut probabilities: Vec<Vec<f64>> = Vec::new();
+        for token in tokens {
+            let starts_word = token.text.starts_with(' ') || probabilities.is_empty();
+            let text = token.text.trim();
+            if text.is_empty() {
+                continue;
+            }
+
+            let start_time = token.offsets.from as f64 / 1000.0;
+            let end_time = token.offsets.to as f64 / 1000.0;
+            if starts_word {
+                words.push(WordTimestamp {
+                    word: text.to_string(),
+                    start_time,
+                    end_time,
+                    confidence: None,
+                    speaker_id: None,
+                    alternatives: Vec::new(),
+                });
+                probabilities.push(vec![token.p]);
+            } else if let (Some(word), Some(p)) = (words.last_mut(), probabilities.last_mut()) {
+                word.word.push_str(text);
+                word.end_time = end_time;
+                p.push(token.p);
+            }
+        }
+
+        let first_word = words.len() - probabilities.len();
+        for (word, p) in words[first_word..].iter_mut().zip(&probabilities) {
+            word.confidence = Some(p.iter().sum::<f64>() / p.len() as f64);
+        }
+
+        let text = segment.text.trim();
+        if text.is_empty() {
+            continue;
+        }
+
+        segments.push(TranscriptSegment {
+            start_time: segment.offsets.from as f64 / 1000.0,
+            end_time: segment.offsets.to as f64 / 1000.0,
+            text: text.to_string(),
+            confidence,
+            speaker_id: None,
+            toxicity: None,
+            language: None,
+            verbatim: None,
+        });
+    }
+
+    let transcript = segments
+        .iter()
+        .map(|s| s.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let confidences: Vec<f64> = segments.iter().filter_map(|s| s.confidence).collect();
+    let confidence = if confidences.is_empty() {
+        None
+    } else {
+        Some(confidences.iter().sum::<f64>() / confidences.len() as f64)
+    };
+
+    let metadata = TranscriptionMetadata {
+        job_id: format!("whisper_{}", uuid::Uuid::new_v4()),
+        language,
+        processing_duration: Some(processing_duration),
+        audio_duration: segments.last().map(|s| s.end_time),
+        confidence,
+        completed_at: chrono::Utc::now(),
+        speaker_count: None,
+    };
+
+    ProcessedTranscription {
+        transcript,
+        segments,
+        metadata,
+        words: Some(words),
+        raw: None,
+        redactions: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_transcription() {
+        let json = r#"{
+            "result": {"language": "en"},
+            "transcription": [{
+                "timestamps": {"from": "00:00:00,000", "to": "00:00:02,500"},
+                "offsets": {"from": 0, "to": 2500},
+                "text": " Hello Kubernetes.",
+                "tokens": [
+                    {"text": "[_BEG_]", "offsets": {"from": 0, "to": 0}, "id": 50364, "p": 0.99},
+                    {"text": " Hello", "offsets": {"from": 0, "to": 600}, "id": 2425, "p": 0.9},
+                    {"text": " Kub", "offsets": {"from": 700, "to": 1200}, "id": 7328, "p": 0.5},
+                    {"text": "ernetes", "offsets": {"from": 1200, "to": 1900}, "id": 7310, "p": 0.7},
+                    {"text": ".", "offsets": {"from": 1900, "to": 2000}, "id": 13, "p": 0.9}
+                ]
+            }]
+        }"#;
+
+        let output: WhisperOutput = serde_json::from_str(json).unwrap();
+        let result = into_transcription(output, "en".to_string(), 1.0);
+
+        assert_eq!(result.transcript, "Hello Kubernetes.");
+        assert_eq!(result.segments.len(), 1);
+        assert_eq!(result.segments[0].end_time, 2.5);
+
+        let words = result.words.unwrap();
+        assert_eq!(words.len(), 2);
+        assert_eq!(words[1].word, "Kubernetes.");
+        assert_eq!(words[1].start_time, 0.7);
+        assert_eq!(words[1].end_time, 2.0);
+        assert!((words[1].confidence.unwrap() - 0.7).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_detected_language() {
+        let log = "whisper_init_from_file: loading model\nwhisper_full_with_state: auto-detected language: de (p = 0.912345)\n";
+        let detected = parse_detected_language(log).unwrap();
+        assert_eq!(detected.language, "de");
+        assert_eq!(detected.confidence, Some(0.912345));
+        assert!(parse_detected_language("main: processing").is_none());
+    }
+}
@@ -0,0 +1,195 @@
+use anyhow::{Context, Result};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::path::PathBuf;
+
+use super::WhisperConfig;
+use crate::config::Config;
+
+/// Where ggml model files for whisper.cpp are published
+const MODEL_BASE_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+
+/// Models that can be pulled by name
+pub const KNOWN_MODELS: &[&str] = &[
+    "tiny", "tiny.en", "base", "base.en", "small", "small.en", "medium", "medium.en",
+    "large-v1", "large-v2", "large-v3", "large-v3-turbo",
+];
+
+/// Quantization levels published alongside the full-precision models
+pub const QUANTIZATIONS: &[&str] = &["q4_0", "q4_1", "q5_0", "q5_1", "q8_0"];
+
+/// A model file present in the models directory
+#[derive(Debug, Clone)]
+pub struct InstalledModel {
+    /// Model name including the quantization suffix (e.g. `large-v3-q5_0`)
+    pub name: String,
+
+    /// File size in bytes
+    pub size: u64,
+}
+
+/// Downloads and tracks whisper.cpp models on disk
+pub struct ModelManager {
+    dir: PathBuf,
+}
+
+impl ModelManager {
+    /// Manage models in a specific directory
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Manage models in the configured directory (or the application data directory)
+    pub fn from_config(config: &WhisperConfig) -> Result<Self> {
+        let dir = match &config.models_dir {
+            Some(dir) => dir.clone(),
+            None => Config::data_dir()?.join("models"),
+        };
+
+        Ok(Self::new(dir))
+    }
+
+    /// File name of a model, e.g. `ggml-large-v3-q5_0.bin`
+    pub fn file_name(name: &str, quantization: Option<&str>) -> String {
+        match quantization {
+            Some(quantization) => format!("ggml-{}-{}.bin", name, quantization),
+            None => format!("ggml-{}.bin", name),
+        }
+    }
+
+    /// Local path of a model
+    pub fn path(&self, name: &str, quantization: Option<&str>) -> PathBuf {
+        self.dir.join(Self::file_name(name, quantization))
+    }
+
+    /// List models present in the models directory
+    pub fn installed(&self) -> Result<Vec<InstalledModel>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut models = Vec::new();
+        for entry in fs_err::read_dir(&self.dir)? {
+            let entry = entry?;
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            let Some(name) = file_name
+                .strip_prefix("ggml-")
+                .and_then(|rest| rest.strip_suffix(".bin"))
+            else {
+                continue;
+            };
+
+            models.push(InstalledModel {
+                name: name.to_string(),
+                size: entry.metadata()?.len(),
+            });
+        }
+
+        models.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(models)
+    }
+
+    /// Download a model, reporting progress, unless it is already present
+    pub async fn pull(
+        &self,
+        client: &reqwest::Client,
+        name: &str,
+        quantization: Option<&str>,
+    ) -> Result<PathBuf> {
+        validate(name, quantization)?;
+
+        let path = self.path(name, quantization);
+        if path.exists() {
+            tracing::info!("Model already present: {}", path.display());
+            return Ok(path);
+        }
+
+        fs_err::create_dir_all(&self.dir)?;
+        let file_name = Self::file_name(name, quantization);
+        let url = format!("{}/{}", MODEL_BASE_URL, file_name);
+
+        let progress = crate::progress::add(ProgressBar::new(0).with_style(
+            ProgressStyle::default_bar()
+                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta}) {msg}")
+                .unwrap()
+        ));
+        progress.set_message(format!("Downloading {}", file_name));
+
+        let response = client.get(&url).send().await
+            .with_context(|| format!("Failed to download model {}", file_name))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            anyhow::bail!("Model {} is not published (try another quantization)", file_name);
+        }
+        if !response.status().is_success() {
+            anyhow::bail!("Failed to download model {}: HTTP {}", file_name, response.status());
+        }
+
+        // Download next to the final file so an interrupted pull never looks installed
+        let partial = path.with_extension("bin.part");
+        crate::transcribe::download::stream_to_file(client.get(&url), response, &partial, &progress).await?;
+        fs_err::rename(&partial, &path)?;
+
+        progress.finish_with_message(format!("Downloaded {}", file_name));
+        Ok(path)
+    }
+
+    /// Delete a downloaded model
+    pub fn remove(&self, name: &str, quantization: Option<&str>) -> Result<()> {
+        let path = self.path(name, quantization);
+        if !path.exists() {
+            anyhow::bail!("Model {} is not installed", Self::file_name(name, quantization));
+        }
+
+        fs_err::remove_file(path)?;
+        Ok(())
+    }
+}
+
+/// Check a model name and quantization against the published models
+fn validate(name: &str, quantization: Option<&str>) -> Result<()> {
+    if !KNOWN_MODELS.contains(&name) {
+        anyhow::bail!("Unknown Whisper model '{}' (available: {})", name, KNOWN_MODELS.join(", "));
+    }
+
+    if let Some(quantization) = quantization {
+        if !QUANTIZATIONS.contains(&quantization) {
+            anyhow::bail!(
+                "Unknown quantization '{}' (available: {})",
+                quantization,
+                QUANTIZATIONS.join(", ")
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_name_and_installed() {
+        assert_eq!(ModelManager::file_name("large-v3", Some("q5_0")), "ggml-large-v3-q5_0.bin");
+        assert_eq!(ModelManager::file_name("base.en", None), "ggml-base.en.bin");
+
+        let dir = tempfile::tempdir().unwrap();
+        let manager = ModelManager::new(dir.path().to_path_buf());
+        fs_err::write(manager.path("tiny", None), b"model").unwrap();
+        fs_err::write(dir.path().join("ggml-base.bin.part"), b"partial").unwrap();
+
+        let installed = manager.installed().unwrap();
+        assert_eq!(installed.len(), 1);
+        assert_eq!(installed[0].name, "tiny");
+        assert_eq!(installed[0].size, 5);
+
+        manager.remove("tiny", None).unwrap();
+        assert!(manager.installed().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate("large-v3", Some("q5_0")).is_ok());
+        assert!(validate("huge", None).is_err());
+        assert!(validate("small", Some("q3_k")).is_err());
+    }
+}
@@ -0,0 +1,346 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::Duration;
+
+use crate::archive::{Archive, ArchiveConfig};
+use crate::cli::{Backend, OutputFormat};
+use crate::config::Config;
+use crate::history::HistoryConfig;
+use crate::notify::Notifier;
+use crate::output::summary::{RunSummary, SummaryFormat};
+use crate::server::{InputSources, JobRequest};
+use crate::transcribe::TranscriptionPipeline;
+
+/// Receives that may fail in a row (network, throttling) before the worker gives up
+const MAX_RECEIVE_FAILURES: u32 = 5;
+
+/// `worker`: transcription requests taken from an SQS queue
+///
+/// Message bodies are the JSON of a `serve` job (`{"url": ..., "language": ...}`), with the
+/// same `server.allow_local_inputs` / `server.allow_s3_inputs` limits on the URL. Results go
+/// to `<output>/<message id>.<ext>`; a message is deleted only once they are written, so
+/// failed ones are redelivered or end up in the queue's dead-letter queue. The `notify`
+/// webhook hears about every message, with the message ID as source when the body is invalid.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkerConfig {
+    /// Queue URL used when `worker` is run without --queue
+    pub queue: Option<String>,
+
+    /// s3://bucket/prefix results are written to
+    pub output: Option<String>,
+
+    /// Formats each result is written in
+    pub formats: Vec<OutputFormat>,
+
+    /// Seconds a message is hidden from other workers; extended while its job runs
+    pub visibility_timeout_secs: u64,
+
+    /// Seconds a receive waits for a message to arrive (long polling, at most 20)
+    pub wait_secs: u64,
+}
+
+impl Default for WorkerConfig {
+    fn default() -> Self {
+        Self {
+            queue: None,
+            output: None,
+            formats: vec![OutputFormat::Json, OutputFormat::Srt, OutputFormat::Text],
+            visibility_timeout_secs: 300,
+            wait_secs: 20,
+        }
+    }
+}
+
+impl WorkerConfig {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(output) = &self.output {
+            let bucket = output.strip_prefix("s3://").and_then(|rest| rest.split('/').next());
+            if bucket.is_none_or(str::is_empty) {
+                anyhow::bail!("worker.output must be an s3://bucket/prefix, got {}", output);
+            }
+        }
+        if self.formats.is_empty() {
+            anyhow::bail!("worker.formats must name at least one format");
+        }
+        if !(30..=43_200).contains(&self.visibility_timeout_secs) {
+            anyhow::bail!("worker.visibility_timeout_secs must be between 30 and 43200");
+        }
+        if self.wait_secs > 20 {
+            anyhow::bail!("worker.wait_secs must be at most 20");
+        }
+        Ok(())
+    }
+}
+
+/// A message taken from the queue, hidden from other workers until deleted or timed out
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub message_id: String,
+    pub receipt_handle: String,
+    pub body: String,
+}
+
+/// An SQS queue, reached through the AWS SDK
+pub struct Queue {
+    url: String,
+    client: aws_sdk_sqs::Client,
+}
+
+impl Queue {
+    pub async fn new(url: &str, config: &Config) -> Result<Self> {
+        let (endpoint, region) = endpoint_of(url)?;
+        let mut sqs = aws_sdk_sqs::config::Builder::from(&config.aws_sdk_config().await);
+        if let Some(region) = region {
+            sqs = sqs.region(aws_types::region::Region::new(region));
+        }
+        if let Some(endpoint) = endpoint {
+            sqs = sqs.endpoint_url(endpoint);
+        }
+
+        Ok(Self {
+            url: url.to_string(),
+            client: aws_sdk_sqs::Client::from_conf(sqs.build()),
+        })
+    }
+
+    /// Wait up to `wait` for a message, hiding it from other workers for `visibility`
+    pub async fn receive(&self, wait: Duration, visibility: Duration) -> Result<Option<Message>> {
+        let response = self
+            .client
+            .receive_message()
+            .queue_url(&self.url)
+            .max_number_of_messages(1)
+            .wait_time_seconds(wait.as_secs() as i32)
+            .visibility_timeout(visibility.as_secs() as i32)
+            .send()
+            .await
+            .context("Failed to receive from the SQS queue")?;
+
+        Ok(response.messages.unwrap_or_default().into_iter().find_map(|message| {
+            Some(Message {
+                message_id: message.message_id?,
+                receipt_handle: message.receipt_handle?,
+                body: message.body.unwrap_or_default(),
+            })
+        }))
+    }
+
+    pub async fn delete(&self, message: &Message) -> Result<()> {
+        self.client
+            .delete_message()
+            .queue_url(&self.url)
+            .receipt_handle(&message.receipt_handle)
+            .send()
+            .await
+            .context("Failed to delete the SQS message")?;
+        Ok(())
+    }
+
+    /// Hide a message for another `visibility`, counted from now
+    pub async fn extend(&self, message: &Message, visibility: Duration) -> Result<()> {
+        self.client
+            .change_message_visibility()
+            .queue_url(&self.url)
+            .receipt_handle(&message.receipt_handle)
+            .visibility_timeout(visibility.as_secs() as i32)
+            .send()
+            .await
+            .context("Failed to extend the SQS message's visibility")?;
+        Ok(())
+    }
+}
+
+/// The endpoint to use instead of AWS's for a queue, and the region its URL names
+///
+/// Queues on AWS keep the SDK's endpoint (and its FIPS and dual-stack settings); any other
+/// host, such as a local emulator, is reached where its URL points.
+fn endpoint_of(queue_url: &str) -> Result<(Option<String>, Option<String>)> {
+    let url = url::Url::parse(queue_url).with_context(|| format!("Invalid queue URL: {}", queue_url))?;
+    let host = url.host_str().filter(|_| url.path().len() > 1).context(format!(
+        "Queue URL must look like https://sqs.<region>.amazonaws.com/<account>/<queue>, got {}",
+        queue_url
+    ))?;
+
+    // sqs.<region>.amazonaws.com, or the legacy <region>.queue.amazonaws.com
+    let region = host
+        .strip_prefix("sqs.")
+        .and_then(|rest| rest.split_once(".amazonaws.com"))
+        .map(|(region, _)| region)
+        .or_else(|| host.split_once(".queue.amazonaws.com").map(|(region, _)| region))
+        .map(str::to_string);
+
+    let endpoint = region.is_none().then(|| url.origin().ascii_serialization());
+    Ok((endpoint, region))
+}
+
+/// Transcribes what arrives on a queue and writes the results to S3
+pub struct Worker {
+    queue: Queue,
+    archive: Archive,
+    pipeline: TranscriptionPipeline,
+    notifier: Option<Notifier>,
+    settings: WorkerConfig,
+    backend: Backend,
+    sources: InputSources,
+    prices: HistoryConfig,
+}
+
+impl Worker {
+    pub async fn new(queue_url: &str, config: Config) -> Result<Self> {
+        if config.app.demo {
+            anyhow::bail!("worker reads from SQS and writes to S3, so it is not available with --demo");
+        }
+        let settings = config.worker.clone();
+        settings.validate()?;
+        let output = settings.output.clone().context(
+            "worker needs an S3 output prefix: pass --output s3://bucket/prefix or set worker.output",
+        )?;
+        let archive = ArchiveConfig {
+            destination: Some(output),
+            formats: settings.formats.clone(),
+            ..Default::default()
+        };
+
+        Ok(Self {
+            queue: Queue::new(queue_url, &config).await?,
+            archive: Archive::with_settings(&archive, &config).await?,
+            notifier: Notifier::from_config(&config)?,
+            settings,
+            backend: config.app.backend,
+            sources: InputSources::of(&config.server),
+            prices: config.history.clone(),
+            pipeline: TranscriptionPipeline::new(config).await?,
+        })
+    }
+
+    /// Take messages until Ctrl+C, or with `drain` until the queue is empty
+    ///
+    /// A message that is still being transcribed when the worker stops returns to the queue
+    /// once its visibility timeout runs out.
+    pub async fn run(&self, drain: bool) -> Result<()> {
+        tokio::select! {
+            outcome = self.work(drain) => outcome,
+            _ = tokio::signal::ctrl_c() => {
+                tracing::info!("Stopping worker");
+                Ok(())
+            }
+        }
+    }
+
+    async fn work(&self, drain: bool) -> Result<()> {
+        let wait = Duration::from_secs(self.settings.wait_secs);
+        let visibility = Duration::from_secs(self.settings.visibility_timeout_secs);
+        let mut failures = 0;
+
+        loop {
+            let message = match self.queue.receive(wait, visibility).await {
+                Ok(message) => {
+                    failures = 0;
+                    message
+                }
+                Err(e) if failures + 1 < MAX_RECEIVE_FAILURES => {
+                    failures += 1;
+                    tracing::warn!("Could not receive from the queue (attempt {}): {:#}", failures, e);
+                    tokio::time::sleep(Duration::from_secs(2u64.pow(failures))).await;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            let Some(message) = message else {
+                if drain {
+                    return Ok(());
+                }
+                continue;
+            };
+
+            tracing::info!("Message {} received", message.message_id);
+            let request: Result<JobRequest> =
+                serde_json::from_str(&message.body).context("Message body is not a transcription request");
+            let source = request.as_ref().map_or_else(|_| message.message_id.clone(), |request| request.url.clone());
+            let outcome = match request {
+                Ok(request) => self.handle(&request, &message).await,
+                Err(e) => Err(e),
+            };
+            let summary = match outcome {
+                Ok(summary) => {
+                    // The results are already written, so a failed delete must not stop the worker
+                    match self.queue.delete(&message).await {
+                        Ok(()) => tracing::info!("Message {} done", message.message_id),
+                        Err(e) => tracing::warn!(
+                            "Message {} done, but could not be deleted and may be redelivered: {:#}",
+                            message.message_id,
+                            e
+                        ),
+                    }
+                    summary
+                }
+                Err(e) => {
+                    tracing::warn!("Message {} failed, leaving it for redelivery: {:#}", message.message_id, e);
+                    RunSummary::failed(&e)
+                }
+            };
+            if let Some(notifier) = &self.notifier {
+                if let Err(e) = notifier.send(&source, &summary).await {
+                    tracing::warn!("Could not send the notification for message {}: {:#}", message.message_id, e);
+                }
+            }
+            println!("{}", summary.render(SummaryFormat::Json));
+        }
+    }
+
+    async fn handle(&self, request: &JobRequest, message: &Message) -> Result<RunSummary> {
+        let (options, live) = request.options(self.backend, false, self.sources)?;
+
+        let run = async {
+            match &live {
+                Some(live) => self.pipeline.transcribe_live(&request.url, &options, live, |_, _| {}).await,
+                None => self.pipeline.transcribe_from_url(&request.url, &options).await,
+            }
+        };
+        let result = self.keep_hidden(message, run).await?;
+
+        let path = crate::utils::sanitize_filename(&message.message_id);
+        let location = self.archive.store_at(&result, &path).await?;
+        let mut summary = RunSummary::completed(&result, options.backend, &self.prices);
+        summary.outputs.archive = Some(location);
+        Ok(summary)
+    }
+
+    /// Run `work`, extending the message's visibility so no other worker picks it up meanwhile
+    async fn keep_hidden<T>(&self, message: &Message, work: impl Future<Output = Result<T>>) -> Result<T> {
+        let visibility = Duration::from_secs(self.settings.visibility_timeout_secs);
+        tokio::pin!(work);
+        loop {
+            tokio::select! {
+                outcome = &mut work => return outcome,
+                _ = tokio::time::sleep(visibility / 2) => {
+                    if let Err(e) = self.queue.extend(message, visibility).await {
+                        tracing::warn!("Could not extend the visibility of message {}: {:#}", message.message_id, e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_urls_and_settings() {
+        let (endpoint, region) = endpoint_of("https://sqs.eu-west-1.amazonaws.com/123456789012/transcribe").unwrap();
+        assert_eq!((endpoint, region.as_deref()), (None, Some("eu-west-1")));
+        let (_, region) = endpoint_of("https://us-east-2.queue.amazonaws.com/123456789012/jobs").unwrap();
+        assert_eq!(region.as_deref(), Some("us-east-2"));
+        // Local emulators are reached at their own address, in the configured region
+        let (endpoint, region) = endpoint_of("http://localhost:4566/000000000000/jobs").unwrap();
+        assert_eq!((endpoint.as_deref(), region), (Some("http://localhost:4566"), None));
+        assert!(endpoint_of("https://sqs.eu-west-1.amazonaws.com").is_err());
+
+        assert!(WorkerConfig { output: Some("bucket/prefix".to_string()), ..Default::default() }.validate().is_err());
+        assert!(WorkerConfig { output: Some("s3://bucket/out".to_string()), ..Default::default() }.validate().is_ok());
+    }
+}